@@ -0,0 +1,322 @@
+//! Arena representation of flowchart structure
+//!
+//! The regular AST gives every node and edge its own heap-allocated strings
+//! and vectors, which dominates parse time for very large diagrams when all
+//! a caller needs is to index the graph. [`ArenaFlowchart`] stores the same
+//! structure in a fixed handful of buffers: all text lives in one string
+//! and is addressed by [`Span`], nodes and edges are addressed by
+//! [`NodeIndex`] and [`EdgeIndex`], and outgoing edges are kept in a
+//! compressed adjacency list. The number of allocations no longer grows
+//! with the size of the diagram.
+//!
+//! Only structure is kept: direction, title, nodes (label, shape, classes,
+//! icon) and top-level edges. [`ArenaFlowchart::to_flowchart`] converts back
+//! to the regular AST without subgraphs, styles, class definitions, clicks or
+//! accessibility information.
+//!
+//! Available with the `arena` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::arena::ArenaFlowchart;
+//!
+//! let arena = ArenaFlowchart::parse("flowchart TD\n    A[Start] --> B[End]\n    A --> C")?;
+//! let start = arena.find("A").unwrap();
+//! let targets: Vec<&str> = arena
+//!     .outgoing(start)
+//!     .map(|edge| arena.node_id(arena.edge(edge).to))
+//!     .collect();
+//! assert_eq!(targets, vec!["B", "C"]);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{
+    EdgeType, FlowDirection, FlowEdge, FlowNode, FlowchartDiagram, NodeShape,
+};
+use crate::error::Result;
+use std::collections::HashMap;
+
+/// A range of bytes in the arena's string buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    start: u32,
+    end: u32,
+}
+
+/// Position of a node in [`ArenaFlowchart::nodes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeIndex(pub u32);
+
+/// Position of an edge in [`ArenaFlowchart::edges`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeIndex(pub u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaNode {
+    pub id: Span,
+    pub text: Option<Span>,
+    pub shape: NodeShape,
+    pub icon: Option<Span>,
+    /// Range of [`ArenaFlowchart::class_spans`] holding the node's classes
+    classes: (u32, u32),
+    /// False for nodes that only appear as edge endpoints
+    pub declared: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaEdge {
+    pub from: NodeIndex,
+    pub to: NodeIndex,
+    pub edge_type: EdgeType,
+    pub label: Option<Span>,
+    pub min_length: Option<i32>,
+}
+
+/// Flowchart structure stored in a fixed number of buffers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaFlowchart {
+    pub direction: FlowDirection,
+    pub title: Option<Span>,
+    strings: String,
+    /// Sorted by node id, so lookups are a binary search
+    nodes: Vec<ArenaNode>,
+    /// In source order
+    edges: Vec<ArenaEdge>,
+    class_spans: Vec<Span>,
+    /// `outgoing_edges[outgoing_offsets[n]..outgoing_offsets[n + 1]]` are the
+    /// edges leaving node `n`, in source order
+    outgoing_offsets: Vec<u32>,
+    outgoing_edges: Vec<EdgeIndex>,
+}
+
+impl ArenaFlowchart {
+    /// Parse a flowchart straight into the arena representation
+    pub fn parse(input: &str) -> Result<Self> {
+        crate::parsers::flowchart::parse(input).map(|diagram| Self::from(&diagram))
+    }
+
+    /// The text a span refers to
+    pub fn str(&self, span: Span) -> &str {
+        &self.strings[span.start as usize..span.end as usize]
+    }
+
+    pub fn nodes(&self) -> &[ArenaNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[ArenaEdge] {
+        &self.edges
+    }
+
+    pub fn node(&self, index: NodeIndex) -> &ArenaNode {
+        &self.nodes[index.0 as usize]
+    }
+
+    pub fn edge(&self, index: EdgeIndex) -> &ArenaEdge {
+        &self.edges[index.0 as usize]
+    }
+
+    /// The id of a node
+    pub fn node_id(&self, index: NodeIndex) -> &str {
+        self.str(self.node(index).id)
+    }
+
+    /// Find a node by id
+    pub fn find(&self, id: &str) -> Option<NodeIndex> {
+        self.nodes
+            .binary_search_by(|node| self.str(node.id).cmp(id))
+            .ok()
+            .map(|index| NodeIndex(index as u32))
+    }
+
+    /// The classes assigned to a node
+    pub fn classes(&self, index: NodeIndex) -> impl Iterator<Item = &str> {
+        let (start, end) = self.node(index).classes;
+        self.class_spans[start as usize..end as usize]
+            .iter()
+            .map(|span| self.str(*span))
+    }
+
+    /// Edges leaving a node, in source order
+    pub fn outgoing(&self, index: NodeIndex) -> impl Iterator<Item = EdgeIndex> + '_ {
+        let start = self.outgoing_offsets[index.0 as usize] as usize;
+        let end = self.outgoing_offsets[index.0 as usize + 1] as usize;
+        self.outgoing_edges[start..end].iter().copied()
+    }
+
+    /// Convert back to the regular AST. Only the structure kept by the arena
+    /// is restored; see the module documentation.
+    pub fn to_flowchart(&self) -> FlowchartDiagram {
+        let optional = |span: Option<Span>| span.map(|span| self.str(span).to_string());
+
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.declared)
+            .map(|(index, node)| {
+                let id = self.str(node.id).to_string();
+                let flow_node = FlowNode {
+                    id: id.clone(),
+                    text: optional(node.text),
+                    shape: node.shape.clone(),
+                    classes: self
+                        .classes(NodeIndex(index as u32))
+                        .map(str::to_string)
+                        .collect(),
+                    icon: optional(node.icon),
+                };
+                (id, flow_node)
+            })
+            .collect();
+
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| FlowEdge {
+                from: self.node_id(edge.from).to_string(),
+                to: self.node_id(edge.to).to_string(),
+                edge_type: edge.edge_type.clone(),
+                label: optional(edge.label),
+                min_length: edge.min_length,
+            })
+            .collect();
+
+        FlowchartDiagram {
+            title: optional(self.title),
+            accessibility: Default::default(),
+            direction: self.direction.clone(),
+            nodes,
+            edges,
+            subgraphs: Vec::new(),
+            styles: Vec::new(),
+            class_defs: HashMap::new(),
+            clicks: Vec::new(),
+        }
+    }
+}
+
+impl From<&FlowchartDiagram> for ArenaFlowchart {
+    fn from(diagram: &FlowchartDiagram) -> Self {
+        let mut strings = String::new();
+        let mut push = |text: &str| {
+            let start = strings.len() as u32;
+            strings.push_str(text);
+            Span {
+                start,
+                end: strings.len() as u32,
+            }
+        };
+
+        // Every node id, declared or only referenced by an edge, sorted
+        let mut ids: Vec<&str> = diagram.nodes.keys().map(String::as_str).collect();
+        for edge in &diagram.edges {
+            ids.push(&edge.from);
+            ids.push(&edge.to);
+        }
+        ids.sort_unstable();
+        ids.dedup();
+
+        let title = diagram.title.as_deref().map(&mut push);
+        let mut nodes = Vec::with_capacity(ids.len());
+        let mut class_spans = Vec::new();
+        for id in &ids {
+            let id_span = push(id);
+            let node = match diagram.nodes.get(*id) {
+                Some(node) => {
+                    let start = class_spans.len() as u32;
+                    class_spans.extend(node.classes.iter().map(|class| push(class)));
+                    ArenaNode {
+                        id: id_span,
+                        text: node.text.as_deref().map(&mut push),
+                        shape: node.shape.clone(),
+                        icon: node.icon.as_deref().map(&mut push),
+                        classes: (start, class_spans.len() as u32),
+                        declared: true,
+                    }
+                }
+                None => ArenaNode {
+                    id: id_span,
+                    text: None,
+                    shape: NodeShape::Rectangle,
+                    icon: None,
+                    classes: (0, 0),
+                    declared: false,
+                },
+            };
+            nodes.push(node);
+        }
+
+        let index_of = |id: &str| NodeIndex(ids.binary_search(&id).unwrap_or_default() as u32);
+        let edges: Vec<ArenaEdge> = diagram
+            .edges
+            .iter()
+            .map(|edge| ArenaEdge {
+                from: index_of(&edge.from),
+                to: index_of(&edge.to),
+                edge_type: edge.edge_type.clone(),
+                label: edge.label.as_deref().map(&mut push),
+                min_length: edge.min_length,
+            })
+            .collect();
+
+        // Counting sort of edge indices by source node
+        let mut outgoing_offsets = vec![0u32; nodes.len() + 1];
+        for edge in &edges {
+            outgoing_offsets[edge.from.0 as usize + 1] += 1;
+        }
+        for index in 1..outgoing_offsets.len() {
+            outgoing_offsets[index] += outgoing_offsets[index - 1];
+        }
+        let mut next = outgoing_offsets.clone();
+        let mut outgoing_edges = vec![EdgeIndex(0); edges.len()];
+        for (index, edge) in edges.iter().enumerate() {
+            let slot = &mut next[edge.from.0 as usize];
+            outgoing_edges[*slot as usize] = EdgeIndex(index as u32);
+            *slot += 1;
+        }
+
+        ArenaFlowchart {
+            direction: diagram.direction.clone(),
+            title,
+            strings,
+            nodes,
+            edges,
+            class_spans,
+            outgoing_offsets,
+            outgoing_edges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undeclared_endpoints_are_indexed_but_not_restored() {
+        let diagram = crate::parsers::flowchart::parse("flowchart LR\n    A --> B[Two]").unwrap();
+        let arena = ArenaFlowchart::from(&diagram);
+
+        let a = arena.find("A").unwrap();
+        assert!(!arena.node(a).declared);
+        assert!(arena.node(arena.find("B").unwrap()).declared);
+        assert!(arena.find("C").is_none());
+        assert_eq!(arena.to_flowchart(), diagram);
+    }
+
+    #[test]
+    fn test_outgoing_edges_keep_source_order() {
+        let arena =
+            ArenaFlowchart::parse("flowchart TD\n    B --> C\n    A --> C\n    B --> A").unwrap();
+        let b = arena.find("B").unwrap();
+
+        let targets: Vec<&str> = arena
+            .outgoing(b)
+            .map(|edge| arena.node_id(arena.edge(edge).to))
+            .collect();
+        assert_eq!(targets, vec!["C", "A"]);
+        assert_eq!(arena.outgoing(arena.find("C").unwrap()).count(), 0);
+    }
+}
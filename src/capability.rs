@@ -0,0 +1,120 @@
+//! How completely each diagram type is parsed, as structured data
+//!
+//! [`capabilities`] lists every [`DiagramKind`] with a [`SupportLevel`] and the
+//! Mermaid constructs its parser is known not to handle. Command-line tools and
+//! documentation can render the matrix, and applications can warn users before
+//! accepting a diagram type that is only partially understood.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::capability::{self, SupportLevel};
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! let flowchart = capability::for_kind(DiagramKind::Flowchart).unwrap();
+//! if flowchart.level != SupportLevel::Full {
+//!     for construct in &flowchart.unsupported {
+//!         println!("not supported: {}", construct);
+//!     }
+//! }
+//! ```
+
+use crate::stable::DiagramKind;
+
+/// How much of a diagram type's syntax the parser understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SupportLevel {
+    /// All documented syntax is parsed into the AST
+    Full,
+    /// Parsed into a dedicated AST, but some constructs are skipped or rejected
+    Partial,
+    /// Handled by the generic misc parser, which keeps statements as raw text
+    Fallback,
+}
+
+impl SupportLevel {
+    /// Lowercase name for display, e.g. `"partial"`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SupportLevel::Full => "full",
+            SupportLevel::Partial => "partial",
+            SupportLevel::Fallback => "fallback",
+        }
+    }
+}
+
+/// Support for one diagram type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capability {
+    pub kind: DiagramKind,
+    pub level: SupportLevel,
+    /// Mermaid constructs known not to be parsed, empty for [`SupportLevel::Full`]
+    pub unsupported: Vec<&'static str>,
+}
+
+/// The support matrix, one entry per [`DiagramKind`]
+pub fn capabilities() -> Vec<Capability> {
+    use DiagramKind as K;
+    use SupportLevel::{Fallback, Full, Partial};
+
+    vec![
+        capability(K::Sankey, Full, &[]),
+        capability(K::Timeline, Full, &[]),
+        capability(K::Journey, Full, &[]),
+        capability(
+            K::Sequence,
+            Partial,
+            &["create and destroy participants", "link and links menus"],
+        ),
+        capability(
+            K::Class,
+            Partial,
+            &[
+                "namespace blocks",
+                "note for statements",
+                "direction statements",
+                "classDef and style statements",
+                "click and callback interactions",
+            ],
+        ),
+        capability(K::State, Partial, &["classDef and style statements"]),
+        capability(K::Flowchart, Partial, &["linkStyle statements"]),
+        capability(K::Gantt, Full, &[]),
+        capability(K::Pie, Full, &[]),
+        capability(
+            K::Git,
+            Fallback,
+            &["typed commits, branches and merges; statements are kept as raw text"],
+        ),
+        capability(K::Er, Partial, &["direction statements"]),
+        capability(K::C4, Full, &[]),
+        capability(K::Mindmap, Full, &[]),
+        capability(K::Quadrant, Full, &[]),
+        capability(K::XyChart, Full, &[]),
+        capability(K::Kanban, Full, &[]),
+        capability(K::Block, Partial, &["classDef statements"]),
+        capability(K::Architecture, Full, &[]),
+        capability(K::Packet, Full, &[]),
+        capability(K::Requirement, Full, &[]),
+        capability(K::Treemap, Full, &[]),
+        capability(K::Radar, Full, &[]),
+        capability(
+            K::Misc,
+            Fallback,
+            &["any structure beyond the diagram keyword and raw lines"],
+        ),
+    ]
+}
+
+/// Support for a single diagram kind
+pub fn for_kind(kind: DiagramKind) -> Option<Capability> {
+    capabilities().into_iter().find(|c| c.kind == kind)
+}
+
+fn capability(kind: DiagramKind, level: SupportLevel, unsupported: &[&'static str]) -> Capability {
+    Capability {
+        kind,
+        level,
+        unsupported: unsupported.to_vec(),
+    }
+}
@@ -0,0 +1,243 @@
+//! External metadata attached to diagram elements
+//!
+//! [`Annotations`] is a side table that tools can use to attach typed values,
+//! such as a review status or an owning team, to elements of a parsed diagram
+//! without changing the AST. Entries are keyed by the element's [`AstPath`],
+//! so they stay attached when unrelated statements are added or reordered.
+//!
+//! Annotations are stored in the document's frontmatter under the
+//! `annotations` key, so they are written alongside the diagram and read back
+//! with it. Mermaid ignores frontmatter keys it does not know about.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::annotations::Annotations;
+//! use mermaid_parser::common::path::{AstPath, ElementKind};
+//! use mermaid_parser::{parse_document, MermaidPrinter};
+//!
+//! let mut document = parse_document("flowchart TD\n    A --> B")?;
+//! let node = AstPath::new(ElementKind::Node, "A");
+//!
+//! let mut annotations = Annotations::new();
+//! annotations.set(node.clone(), "owner", "payments");
+//! annotations.set(node.clone(), "reviewed", true);
+//! document.set_annotations(&annotations);
+//!
+//! let reparsed = parse_document(&document.to_mermaid())?;
+//! let annotations = reparsed.annotations()?;
+//! assert_eq!(annotations.get(&node, "owner").unwrap().to_string(), "\"payments\"");
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::DiagramType;
+use crate::common::frontmatter::{ConfigValue, Document, Frontmatter};
+use crate::common::path::{element_paths, AstPath};
+use crate::error::{ParseError, Result};
+use std::collections::{BTreeMap, HashMap};
+
+/// Frontmatter key holding the annotations of a document
+pub const FRONTMATTER_KEY: &str = "annotations";
+
+/// Values attached to diagram elements, keyed by element path and then by name
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Annotations {
+    entries: HashMap<AstPath, BTreeMap<String, ConfigValue>>,
+}
+
+impl Annotations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `value` under `key` to the element at `path`, returning the
+    /// value it replaces
+    pub fn set(
+        &mut self,
+        path: AstPath,
+        key: impl Into<String>,
+        value: impl Into<ConfigValue>,
+    ) -> Option<ConfigValue> {
+        self.entries
+            .entry(path)
+            .or_default()
+            .insert(key.into(), value.into())
+    }
+
+    /// The value under `key` for the element at `path`
+    pub fn get(&self, path: &AstPath, key: &str) -> Option<&ConfigValue> {
+        self.entries.get(path)?.get(key)
+    }
+
+    /// Remove the value under `key` for the element at `path`
+    pub fn remove(&mut self, path: &AstPath, key: &str) -> Option<ConfigValue> {
+        let values = self.entries.get_mut(path)?;
+        let removed = values.remove(key);
+        if values.is_empty() {
+            self.entries.remove(path);
+        }
+        removed
+    }
+
+    /// All values attached to the element at `path`
+    pub fn for_path(&self, path: &AstPath) -> Option<&BTreeMap<String, ConfigValue>> {
+        self.entries.get(path)
+    }
+
+    /// Annotated paths, sorted by their written form
+    pub fn paths(&self) -> Vec<&AstPath> {
+        let mut paths: Vec<&AstPath> = self.entries.keys().collect();
+        paths.sort_by_cached_key(|path| path.to_string());
+        paths
+    }
+
+    /// Number of annotated elements
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop the annotations of elements that are not in `diagram`, returning
+    /// their paths sorted by written form
+    pub fn retain_existing(&mut self, diagram: &DiagramType) -> Vec<AstPath> {
+        let existing = element_paths(diagram);
+        let mut dropped: Vec<AstPath> = self
+            .entries
+            .keys()
+            .filter(|path| !existing.contains(path))
+            .cloned()
+            .collect();
+        dropped.sort_by_cached_key(|path| path.to_string());
+        for path in &dropped {
+            self.entries.remove(path);
+        }
+        dropped
+    }
+
+    /// The annotations as a mapping from written path to values
+    pub fn to_config_value(&self) -> ConfigValue {
+        ConfigValue::Map(
+            self.entries
+                .iter()
+                .map(|(path, values)| (path.to_string(), ConfigValue::Map(values.clone())))
+                .collect(),
+        )
+    }
+
+    /// Read annotations written by [`Annotations::to_config_value`]
+    ///
+    /// Fails when the value is not a mapping, a key is not a valid path, or the
+    /// entry for a path is not a mapping.
+    pub fn from_config_value(value: &ConfigValue) -> Result<Self> {
+        let map = match value {
+            ConfigValue::Map(map) => map,
+            ConfigValue::Null => return Ok(Self::default()),
+            other => return Err(invalid("annotations must be a mapping", other)),
+        };
+
+        let mut annotations = Self::default();
+        for (key, values) in map {
+            let path = AstPath::parse(key)
+                .ok_or_else(|| invalid(&format!("`{}` is not an element path", key), values))?;
+            match values {
+                ConfigValue::Map(values) if !values.is_empty() => {
+                    annotations.entries.insert(path, values.clone());
+                }
+                ConfigValue::Map(_) | ConfigValue::Null => {}
+                other => {
+                    return Err(invalid(
+                        &format!("annotations of `{}` must be a mapping", key),
+                        other,
+                    ))
+                }
+            }
+        }
+        Ok(annotations)
+    }
+
+    /// The annotations stored in `frontmatter`
+    pub fn from_frontmatter(frontmatter: &Frontmatter) -> Result<Self> {
+        match frontmatter.extra.get(FRONTMATTER_KEY) {
+            Some(value) => Self::from_config_value(value),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Store the annotations in `frontmatter`, replacing any already there.
+    /// Empty annotations remove the key.
+    pub fn write_to(&self, frontmatter: &mut Frontmatter) {
+        if self.is_empty() {
+            frontmatter.extra.remove(FRONTMATTER_KEY);
+        } else {
+            frontmatter
+                .extra
+                .insert(FRONTMATTER_KEY.to_string(), self.to_config_value());
+        }
+    }
+}
+
+impl Document {
+    /// The annotations stored in the document's frontmatter
+    pub fn annotations(&self) -> Result<Annotations> {
+        match &self.frontmatter {
+            Some(frontmatter) => Annotations::from_frontmatter(frontmatter),
+            None => Ok(Annotations::default()),
+        }
+    }
+
+    /// Store `annotations` in the document's frontmatter, adding a
+    /// frontmatter block if there is none and the annotations are not empty
+    pub fn set_annotations(&mut self, annotations: &Annotations) {
+        match &mut self.frontmatter {
+            Some(frontmatter) => annotations.write_to(frontmatter),
+            None if annotations.is_empty() => {}
+            None => {
+                let mut frontmatter = Frontmatter::default();
+                annotations.write_to(&mut frontmatter);
+                self.frontmatter = Some(frontmatter);
+            }
+        }
+    }
+}
+
+fn invalid(message: &str, value: &ConfigValue) -> ParseError {
+    ParseError::SemanticError {
+        message: format!("Invalid annotations: {}", message),
+        context: value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::path::ElementKind;
+
+    #[test]
+    fn test_remove_drops_empty_entries() {
+        let path = AstPath::new(ElementKind::Node, "A");
+        let mut annotations = Annotations::new();
+        annotations.set(path.clone(), "owner", "core");
+        assert_eq!(annotations.len(), 1);
+
+        assert_eq!(
+            annotations.remove(&path, "owner"),
+            Some(ConfigValue::String("core".to_string()))
+        );
+        assert!(annotations.is_empty());
+        assert_eq!(annotations.remove(&path, "owner"), None);
+    }
+
+    #[test]
+    fn test_from_config_value_rejects_invalid_paths() {
+        let mut map = BTreeMap::new();
+        map.insert("widget/A".to_string(), ConfigValue::Map(BTreeMap::new()));
+        assert!(Annotations::from_config_value(&ConfigValue::Map(map)).is_err());
+        assert!(Annotations::from_config_value(&ConfigValue::Bool(true)).is_err());
+        assert!(Annotations::from_config_value(&ConfigValue::Null)
+            .unwrap()
+            .is_empty());
+    }
+}
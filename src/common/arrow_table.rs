@@ -0,0 +1,253 @@
+//! Arrow spellings of every diagram type, with their canonical forms and
+//! how they are drawn
+//!
+//! [`ARROWS`] lists the arrows of flowcharts, sequence, class, state, block
+//! and architecture diagrams, each with the AST kind it parses into. Several
+//! spellings can produce the same kind, such as `-x` and `--x` in sequence
+//! diagrams or `->` and `-->` in architecture diagrams; each entry names the
+//! canonical spelling of its kind, which is the one the printers write.
+//! Tools that classify arrows, such as linters and editor integrations, can
+//! look them up here instead of keeping their own list.
+//!
+//! Flowchart links combine a line with a head at either end, so their kinds
+//! are not limited to the rows listed here; [`ArrowKind::canonical`] spells
+//! any combination.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::arrow_table::{self, HeadStyle, LineStyle};
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! let arrow = arrow_table::lookup(DiagramKind::Sequence, "--x").unwrap();
+//! assert_eq!(arrow.canonical, "-x");
+//! assert_eq!(arrow.line, LineStyle::Dotted);
+//! assert_eq!(arrow.head, HeadStyle::Cross);
+//!
+//! // `-->` means something different in each diagram type
+//! assert!(arrow_table::classify("-->").len() > 1);
+//! ```
+
+use crate::common::ast::{
+    ArchEdgeType, ArrowType, BlockArrowType, ClassRelationshipType, EdgeHead, EdgeLineStyle,
+    EdgeType,
+};
+use crate::stable::DiagramKind;
+use std::borrow::Cow;
+
+/// Which way an arrow points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrowDirection {
+    /// From the element written before the arrow to the one after it
+    Forward,
+    /// Back at the element written before the arrow, as in `A <|-- B`
+    Backward,
+    Both,
+    /// A plain link
+    Undirected,
+}
+
+/// How the line of an arrow is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineStyle {
+    Solid,
+    Dotted,
+    Thick,
+    /// Not drawn; the link only affects layout
+    Invisible,
+}
+
+/// The mark drawn at the pointed end of an arrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeadStyle {
+    None,
+    /// A filled arrowhead
+    Arrow,
+    /// The open arrowhead of an asynchronous sequence message
+    Open,
+    Cross,
+    Circle,
+    /// The hollow triangle of inheritance and realization
+    Triangle,
+    /// The filled diamond of composition
+    FilledDiamond,
+    /// The hollow diamond of aggregation
+    HollowDiamond,
+}
+
+/// The AST value an arrow parses into
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrowKind {
+    Flowchart(EdgeType),
+    Sequence(ArrowType),
+    Class(ClassRelationshipType),
+    /// State transitions have a single kind
+    State,
+    Block(BlockArrowType),
+    Architecture(ArchEdgeType),
+}
+
+impl ArrowKind {
+    /// The diagram type the arrow belongs to
+    pub fn diagram(&self) -> DiagramKind {
+        match self {
+            ArrowKind::Flowchart(_) => DiagramKind::Flowchart,
+            ArrowKind::Sequence(_) => DiagramKind::Sequence,
+            ArrowKind::Class(_) => DiagramKind::Class,
+            ArrowKind::State => DiagramKind::State,
+            ArrowKind::Block(_) => DiagramKind::Block,
+            ArrowKind::Architecture(_) => DiagramKind::Architecture,
+        }
+    }
+
+    /// The spelling the printers write for this kind
+    pub fn canonical(&self) -> Cow<'static, str> {
+        if let ArrowKind::Flowchart(edge_type) = self {
+            return Cow::Owned(flowchart_spelling(edge_type));
+        }
+        ARROWS
+            .iter()
+            .find(|arrow| arrow.kind == *self)
+            .map(|arrow| Cow::Borrowed(arrow.canonical))
+            .expect("every arrow kind is in the table")
+    }
+}
+
+/// `edge_type` written with the shortest line: the left head, the line, then
+/// the right head, as in `o--o` or `<-.->`
+fn flowchart_spelling(edge_type: &EdgeType) -> String {
+    // Without a right head the line gets one more stroke: `---`, `-.-`, `===`
+    let (line, open_end) = match edge_type.line_style {
+        EdgeLineStyle::Solid => ("--", "-"),
+        EdgeLineStyle::Dotted => ("-.-", ""),
+        EdgeLineStyle::Thick => ("==", "="),
+        EdgeLineStyle::Invisible => return "~~~".to_string(),
+    };
+    let left = match edge_type.left_head {
+        EdgeHead::None => "",
+        EdgeHead::Arrow => "<",
+        EdgeHead::Circle => "o",
+        EdgeHead::Cross => "x",
+    };
+    let right = match edge_type.right_head {
+        EdgeHead::None => open_end,
+        EdgeHead::Arrow => ">",
+        EdgeHead::Circle => "o",
+        EdgeHead::Cross => "x",
+    };
+    format!("{}{}{}", left, line, right)
+}
+
+/// One spelling of an arrow
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrowInfo {
+    /// The arrow as written
+    pub spelling: &'static str,
+    /// The spelling of the same kind the printers write
+    pub canonical: &'static str,
+    pub kind: ArrowKind,
+    pub direction: ArrowDirection,
+    pub line: LineStyle,
+    pub head: HeadStyle,
+}
+
+/// Build the arrow table, one `spelling => canonical kind, direction, line,
+/// head;` row per arrow
+macro_rules! arrows {
+    ($($spelling:literal => $canonical:literal $kind:expr, $direction:ident, $line:ident, $head:ident;)*) => {
+        &[$(ArrowInfo {
+            spelling: $spelling,
+            canonical: $canonical,
+            kind: $kind,
+            direction: ArrowDirection::$direction,
+            line: LineStyle::$line,
+            head: HeadStyle::$head,
+        },)*]
+    };
+}
+
+/// Every arrow spelling, grouped by diagram type
+pub const ARROWS: &[ArrowInfo] = arrows! {
+    // Flowcharts
+    "-->"    => "-->"    ArrowKind::Flowchart(EdgeType::ARROW), Forward, Solid, Arrow;
+    "--->"   => "-->"    ArrowKind::Flowchart(EdgeType::ARROW), Forward, Solid, Arrow;
+    "-.->"   => "-.->"   ArrowKind::Flowchart(EdgeType::DOTTED_ARROW), Forward, Dotted, Arrow;
+    "-..->"  => "-.->"   ArrowKind::Flowchart(EdgeType::DOTTED_ARROW), Forward, Dotted, Arrow;
+    "==>"    => "==>"    ArrowKind::Flowchart(EdgeType::THICK_ARROW), Forward, Thick, Arrow;
+    "===>"   => "==>"    ArrowKind::Flowchart(EdgeType::THICK_ARROW), Forward, Thick, Arrow;
+    "---"    => "---"    ArrowKind::Flowchart(EdgeType::OPEN_LINK), Undirected, Solid, None;
+    "----"   => "---"    ArrowKind::Flowchart(EdgeType::OPEN_LINK), Undirected, Solid, None;
+    "-.-"    => "-.-"    ArrowKind::Flowchart(EdgeType::DOTTED_LINK), Undirected, Dotted, None;
+    "-..-"   => "-.-"    ArrowKind::Flowchart(EdgeType::DOTTED_LINK), Undirected, Dotted, None;
+    "==="    => "==="    ArrowKind::Flowchart(EdgeType::THICK_LINK), Undirected, Thick, None;
+    "===="   => "==="    ArrowKind::Flowchart(EdgeType::THICK_LINK), Undirected, Thick, None;
+    "~~~"    => "~~~"    ArrowKind::Flowchart(EdgeType::INVISIBLE), Undirected, Invisible, None;
+    "--o"    => "--o"    ArrowKind::Flowchart(EdgeType::CIRCLE_EDGE), Forward, Solid, Circle;
+    "--x"    => "--x"    ArrowKind::Flowchart(EdgeType::CROSS_EDGE), Forward, Solid, Cross;
+    "<-->"   => "<-->"   ArrowKind::Flowchart(EdgeType::MULTI_DIRECTIONAL), Both, Solid, Arrow;
+    "<-.->"  => "<-.->"  ArrowKind::Flowchart(EdgeType::DOTTED_MULTI_DIRECTIONAL), Both, Dotted, Arrow;
+    "<==>"   => "<==>"   ArrowKind::Flowchart(EdgeType::THICK_MULTI_DIRECTIONAL), Both, Thick, Arrow;
+    "o--o"   => "o--o"   ArrowKind::Flowchart(EdgeType::MULTI_DIRECTIONAL_CIRCLE), Both, Solid, Circle;
+    "x--x"   => "x--x"   ArrowKind::Flowchart(EdgeType::MULTI_DIRECTIONAL_CROSS), Both, Solid, Cross;
+    // Sequence diagrams
+    "->"     => "->"     ArrowKind::Sequence(ArrowType::SolidOpen), Forward, Solid, None;
+    "-->"    => "-->"    ArrowKind::Sequence(ArrowType::DottedOpen), Forward, Dotted, None;
+    "->>"    => "->>"    ArrowKind::Sequence(ArrowType::SolidClosed), Forward, Solid, Arrow;
+    "-->>"   => "-->>"   ArrowKind::Sequence(ArrowType::DottedClosed), Forward, Dotted, Arrow;
+    "-x"     => "-x"     ArrowKind::Sequence(ArrowType::Cross), Forward, Solid, Cross;
+    "--x"    => "-x"     ArrowKind::Sequence(ArrowType::Cross), Forward, Dotted, Cross;
+    "-)"     => "-)"     ArrowKind::Sequence(ArrowType::Point), Forward, Solid, Open;
+    "--)"    => "-)"     ArrowKind::Sequence(ArrowType::Point), Forward, Dotted, Open;
+    "<<->>"  => "<<->>"  ArrowKind::Sequence(ArrowType::BiDirectionalSolid), Both, Solid, Arrow;
+    "<<-->>" => "<<-->>" ArrowKind::Sequence(ArrowType::BiDirectionalDotted), Both, Dotted, Arrow;
+    // Class diagrams
+    "<|--"   => "<|--"   ArrowKind::Class(ClassRelationshipType::Inheritance), Backward, Solid, Triangle;
+    "*--"    => "*--"    ArrowKind::Class(ClassRelationshipType::Composition), Backward, Solid, FilledDiamond;
+    "o--"    => "o--"    ArrowKind::Class(ClassRelationshipType::Aggregation), Backward, Solid, HollowDiamond;
+    "<--"    => "<--"    ArrowKind::Class(ClassRelationshipType::Association), Backward, Solid, Arrow;
+    "--"     => "--"     ArrowKind::Class(ClassRelationshipType::Link), Undirected, Solid, None;
+    ".."     => ".."     ArrowKind::Class(ClassRelationshipType::DashedLink), Undirected, Dotted, None;
+    "<.."    => "<.."    ArrowKind::Class(ClassRelationshipType::Dependency), Backward, Dotted, Arrow;
+    "<|.."   => "<|.."   ArrowKind::Class(ClassRelationshipType::Realization), Backward, Dotted, Triangle;
+    // State diagrams
+    "-->"    => "-->"    ArrowKind::State, Forward, Solid, Arrow;
+    // Block diagrams
+    "-->"    => "-->"    ArrowKind::Block(BlockArrowType::Normal), Forward, Solid, Arrow;
+    "-.->"   => "-.->"   ArrowKind::Block(BlockArrowType::Dotted), Forward, Dotted, Arrow;
+    "==>"    => "==>"    ArrowKind::Block(BlockArrowType::Thick), Forward, Thick, Arrow;
+    "~~~"    => "~~~"    ArrowKind::Block(BlockArrowType::Invisible), Undirected, Invisible, None;
+    "<-->"   => "<-->"   ArrowKind::Block(BlockArrowType::Bidirectional), Both, Solid, Arrow;
+    // Architecture diagrams
+    "--"     => "--"     ArrowKind::Architecture(ArchEdgeType::Solid), Undirected, Solid, None;
+    ".."     => ".."     ArrowKind::Architecture(ArchEdgeType::Dotted), Undirected, Dotted, None;
+    "->"     => "->"     ArrowKind::Architecture(ArchEdgeType::Arrow), Forward, Solid, Arrow;
+    "-->"    => "->"     ArrowKind::Architecture(ArchEdgeType::Arrow), Forward, Solid, Arrow;
+    "<->"    => "<->"    ArrowKind::Architecture(ArchEdgeType::BiArrow), Both, Solid, Arrow;
+    "<-->"   => "<->"    ArrowKind::Architecture(ArchEdgeType::BiArrow), Both, Solid, Arrow;
+};
+
+/// The arrows of `diagram`, longest spelling first, which is the order to
+/// try them in when matching text
+pub fn arrows_for(diagram: DiagramKind) -> Vec<&'static ArrowInfo> {
+    let mut arrows: Vec<_> = ARROWS
+        .iter()
+        .filter(|arrow| arrow.kind.diagram() == diagram)
+        .collect();
+    arrows.sort_by_key(|arrow| std::cmp::Reverse(arrow.spelling.len()));
+    arrows
+}
+
+/// The arrow `spelling` of `diagram`
+pub fn lookup(diagram: DiagramKind, spelling: &str) -> Option<&'static ArrowInfo> {
+    ARROWS
+        .iter()
+        .find(|arrow| arrow.kind.diagram() == diagram && arrow.spelling == spelling)
+}
+
+/// `spelling` as each diagram type reads it
+pub fn classify(spelling: &str) -> Vec<&'static ArrowInfo> {
+    ARROWS
+        .iter()
+        .filter(|arrow| arrow.spelling == spelling)
+        .collect()
+}
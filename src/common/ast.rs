@@ -1,7 +1,17 @@
 //! Abstract Syntax Tree definitions for all Mermaid diagram types
+//!
+//! With the `serde` feature, every AST type implements `Serialize` and
+//! `Deserialize`, so a parsed diagram can be sent as JSON to a front end or
+//! snapshotted in tests. Enums use serde's default externally tagged form:
+//! `{"Pie": {"title": null, ...}}`.
+//!
+//! The `schema` feature also derives `schemars::JsonSchema`, from which
+//! [`crate::schema`] builds a JSON Schema of that serialized form.
 
 /// Top-level enum representing all supported Mermaid diagram types
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum DiagramType {
     /// Sankey flow diagrams
     Sankey(SankeyDiagram),
@@ -51,11 +61,180 @@ pub enum DiagramType {
     Misc(MiscDiagram),
 }
 
+impl DiagramType {
+    /// The diagram's title, for diagram types that have one
+    pub fn title(&self) -> Option<&str> {
+        let title = match self {
+            DiagramType::Timeline(d) => &d.title,
+            DiagramType::Journey(d) => &d.title,
+            DiagramType::Sequence(d) => &d.title,
+            DiagramType::Class(d) => &d.title,
+            DiagramType::State(d) => &d.title,
+            DiagramType::Flowchart(d) => &d.title,
+            DiagramType::Gantt(d) => &d.title,
+            DiagramType::Pie(d) => &d.title,
+            DiagramType::Git(d) => &d.title,
+            DiagramType::Er(d) => &d.title,
+            DiagramType::C4(d) => &d.title,
+            DiagramType::Mindmap(d) => &d.title,
+            DiagramType::Quadrant(d) => &d.title,
+            DiagramType::XyChart(d) => &d.title,
+            DiagramType::Kanban(d) => &d.title,
+            DiagramType::Block(d) => &d.title,
+            DiagramType::Architecture(d) => &d.title,
+            DiagramType::Packet(d) => &d.title,
+            DiagramType::Requirement(d) => &d.title,
+            DiagramType::Treemap(d) => &d.title,
+            DiagramType::Radar(d) => &d.title,
+            DiagramType::Sankey(_) | DiagramType::Misc(_) => return None,
+        };
+        title.as_deref()
+    }
+
+    /// The diagram's accessibility title and description, for diagram types
+    /// that have them
+    pub fn accessibility(&self) -> Option<&AccessibilityInfo> {
+        let accessibility = match self {
+            DiagramType::Timeline(d) => &d.accessibility,
+            DiagramType::Journey(d) => &d.accessibility,
+            DiagramType::Sequence(d) => &d.accessibility,
+            DiagramType::Class(d) => &d.accessibility,
+            DiagramType::State(d) => &d.accessibility,
+            DiagramType::Flowchart(d) => &d.accessibility,
+            DiagramType::Gantt(d) => &d.accessibility,
+            DiagramType::Pie(d) => &d.accessibility,
+            DiagramType::Git(d) => &d.accessibility,
+            DiagramType::Er(d) => &d.accessibility,
+            DiagramType::C4(d) => &d.accessibility,
+            DiagramType::Mindmap(d) => &d.accessibility,
+            DiagramType::Quadrant(d) => &d.accessibility,
+            DiagramType::XyChart(d) => &d.accessibility,
+            DiagramType::Kanban(d) => &d.accessibility,
+            DiagramType::Block(d) => &d.accessibility,
+            DiagramType::Architecture(d) => &d.accessibility,
+            DiagramType::Packet(d) => &d.accessibility,
+            DiagramType::Requirement(d) => &d.accessibility,
+            DiagramType::Treemap(d) => &d.accessibility,
+            DiagramType::Radar(d) => &d.accessibility,
+            DiagramType::Sankey(_) | DiagramType::Misc(_) => return None,
+        };
+        Some(accessibility)
+    }
+
+    /// The direction the diagram is laid out in, for diagram types that
+    /// have one. Class and state diagrams default to top to bottom; block
+    /// diagrams fill rows left to right unless they have a single column.
+    pub fn layout_direction(&self) -> Option<LayoutDirection> {
+        match self {
+            DiagramType::Flowchart(d) => Some(d.direction.layout()),
+            DiagramType::State(d) => Some(
+                d.direction
+                    .as_ref()
+                    .map_or(LayoutDirection::TopToBottom, FlowDirection::layout),
+            ),
+            DiagramType::Class(d) => Some(
+                d.direction
+                    .as_ref()
+                    .map_or(LayoutDirection::TopToBottom, FlowDirection::layout),
+            ),
+            DiagramType::Architecture(d) => Some(d.direction.layout()),
+            DiagramType::Block(d) => Some(if d.columns == Some(1) {
+                LayoutDirection::TopToBottom
+            } else {
+                LayoutDirection::LeftToRight
+            }),
+            _ => None,
+        }
+    }
+
+    /// Layout hints the author encoded in the diagram, for diagram types
+    /// that have them: block diagram `columns` and invisible links,
+    /// architecture edges whose ports fix where one element sits relative to
+    /// another (such as junction positions), and flowchart `~~~` links used
+    /// for alignment.
+    pub fn grid_hints(&self) -> Option<GridHints> {
+        match self {
+            DiagramType::Block(d) => Some(GridHints {
+                columns: d.columns,
+                placements: d
+                    .connections
+                    .iter()
+                    .filter(|c| c.arrow_type == BlockArrowType::Invisible)
+                    .map(|c| PlacementHint {
+                        from: c.from.clone(),
+                        to: c.to.clone(),
+                        direction: None,
+                    })
+                    .collect(),
+            }),
+            DiagramType::Architecture(d) => Some(GridHints {
+                columns: None,
+                placements: d
+                    .edges
+                    .iter()
+                    .filter_map(|edge| {
+                        let direction = match (&edge.from.port, &edge.to.port) {
+                            (Some(port), _) => port.direction_away(),
+                            (None, Some(port)) => port.direction_toward(),
+                            (None, None) => return None,
+                        };
+                        Some(PlacementHint {
+                            from: edge.from.id.clone(),
+                            to: edge.to.id.clone(),
+                            direction: Some(direction),
+                        })
+                    })
+                    .collect(),
+            }),
+            DiagramType::Flowchart(d) => Some(GridHints {
+                columns: None,
+                placements: d
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.edge_type.line_style == EdgeLineStyle::Invisible)
+                    .map(|edge| PlacementHint {
+                        from: edge.from.clone(),
+                        to: edge.to.clone(),
+                        direction: Some(d.direction.layout()),
+                    })
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Layout intent encoded in a diagram, as returned by
+/// [`DiagramType::grid_hints`]
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GridHints {
+    /// Number of columns the top level is laid out in
+    pub columns: Option<i32>,
+    /// Pairs of elements whose relative position the author fixed, in
+    /// source order
+    pub placements: Vec<PlacementHint>,
+}
+
+/// `to` is placed next to `from`, in `direction` when the diagram says which
+/// way
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlacementHint {
+    pub from: String,
+    pub to: String,
+    pub direction: Option<LayoutDirection>,
+}
+
 /// Common accessibility information used across diagram types
 ///
 /// Provides standardized accessibility metadata that can be attached to diagrams
 /// to improve screen reader support and overall accessibility compliance.
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AccessibilityInfo {
     /// Optional title for accessibility purposes
     pub title: Option<String>,
@@ -63,6 +242,119 @@ pub struct AccessibilityInfo {
     pub description: Option<String>,
 }
 
+/// A CSS color, as used by sequence `box` and `rect` blocks and by style
+/// properties such as `fill`
+///
+/// Names and functions keep the text they were written with, so printing a
+/// color gives back its source.
+///
+/// ```
+/// use mermaid_parser::common::ast::Color;
+///
+/// assert_eq!(Color::parse("#f9f"), Some(Color::Hex("f9f".to_string())));
+/// assert_eq!(Color::parse("LightYellow").unwrap().to_string(), "LightYellow");
+/// assert_eq!(
+///     Color::parse_prefix("rgb(33, 66, 99) Backend"),
+///     Some((Color::Function("rgb(33, 66, 99)".to_string()), " Backend"))
+/// );
+/// assert_eq!(Color::parse("4px"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Color {
+    Transparent,
+    /// A CSS named color such as `LightYellow`; names are case-insensitive
+    Named(String),
+    /// A `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` code, without the `#`
+    Hex(String),
+    /// An `rgb(...)`, `rgba(...)`, `hsl(...)` or `hsla(...)` function
+    Function(String),
+    /// Text in a color position that is not a color Mermaid recognizes
+    Other(String),
+}
+
+impl Color {
+    /// The color `text` consists of, if it is one
+    pub fn parse(text: &str) -> Option<Color> {
+        match Color::parse_prefix(text) {
+            Some((color, rest)) if rest.trim().is_empty() => Some(color),
+            _ => None,
+        }
+    }
+
+    /// The color at the start of `text` and the text after it
+    pub fn parse_prefix(text: &str) -> Option<(Color, &str)> {
+        use crate::common::constants::colors;
+
+        let text = text.trim_start();
+        let lowercase = text.to_ascii_lowercase();
+        if ["rgb(", "rgba(", "hsl(", "hsla("]
+            .iter()
+            .any(|function| lowercase.starts_with(function))
+        {
+            let end = text.find(')')? + 1;
+            return Some((Color::Function(text[..end].to_string()), &text[end..]));
+        }
+
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        let (word, rest) = text.split_at(end);
+        let color = if let Some(digits) = word.strip_prefix('#') {
+            let is_hex = matches!(digits.len(), 3 | 4 | 6 | 8)
+                && digits.chars().all(|c| c.is_ascii_hexdigit());
+            is_hex.then(|| Color::Hex(digits.to_string()))?
+        } else if word.eq_ignore_ascii_case(colors::TRANSPARENT) {
+            Color::Transparent
+        } else if colors::NAMED_COLORS.contains(&word.to_ascii_lowercase().as_str()) {
+            Color::Named(word.to_string())
+        } else {
+            return None;
+        };
+        Some((color, rest))
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::Transparent => f.write_str(crate::common::constants::colors::TRANSPARENT),
+            Color::Hex(digits) => write!(f, "#{}", digits),
+            Color::Named(text) | Color::Function(text) | Color::Other(text) => f.write_str(text),
+        }
+    }
+}
+
+/// Metadata shared by every diagram type
+///
+/// Gathers the title and accessibility text, which each diagram type keeps in
+/// its own fields, the `config:` mapping of the frontmatter and the
+/// `%%{init: ...}%%` directives of the source. See
+/// [`crate::Document::metadata`] and
+/// [`crate::common::parsing::frontmatter::extract`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DiagramMetadata {
+    /// The diagram's `title`, or else the frontmatter `title:`
+    pub title: Option<String>,
+    pub accessibility: AccessibilityInfo,
+    pub config: crate::common::frontmatter::MermaidConfig,
+    /// The `%%{init: ...}%%` directives, which Mermaid applies over `config`
+    pub init: Option<InitDirective>,
+}
+
+/// Configuration from the `%%{init: {...}}%%` directives of a diagram
+///
+/// Several directives are merged in order, later keys replacing earlier
+/// ones. `%%{initialize: ...}%%` is read as a synonym. See
+/// [`crate::common::parsing::directives`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InitDirective {
+    pub config: crate::common::frontmatter::MermaidConfig,
+}
+
 /// Sankey flow diagram representation
 ///
 /// Sankey diagrams visualize the flow of data, energy, or materials through a system.
@@ -75,8 +367,16 @@ pub struct AccessibilityInfo {
 ///
 /// let diagram = SankeyDiagram {
 ///     nodes: vec![
-///         SankeyNode { id: "A".to_string(), name: "Source".to_string() },
-///         SankeyNode { id: "B".to_string(), name: "Target".to_string() },
+///         SankeyNode {
+///             id: "A".to_string(),
+///             name: "Source".to_string(),
+///             metadata: Default::default(),
+///         },
+///         SankeyNode {
+///             id: "B".to_string(),
+///             name: "Target".to_string(),
+///             metadata: Default::default(),
+///         },
 ///     ],
 ///     links: vec![
 ///         SankeyLink {
@@ -88,6 +388,8 @@ pub struct AccessibilityInfo {
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SankeyDiagram {
     /// Collection of nodes in the Sankey diagram
     pub nodes: Vec<SankeyNode>,
@@ -100,11 +402,15 @@ pub struct SankeyDiagram {
 /// Represents an entity through which flow passes. Each node has a unique
 /// identifier and a human-readable name.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SankeyNode {
     /// Unique identifier for the node
     pub id: String,
     /// Display name for the node
     pub name: String,
+    /// `key=value` columns from node metadata rows, e.g. `Solar,color=#f5c542`
+    pub metadata: std::collections::BTreeMap<String, String>,
 }
 
 /// A weighted link between two nodes in a Sankey diagram
@@ -112,6 +418,8 @@ pub struct SankeyNode {
 /// Represents the flow of data/energy/materials from a source node to a target node.
 /// The value indicates the magnitude of the flow.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SankeyLink {
     /// Identifier of the source node
     pub source: String,
@@ -147,6 +455,8 @@ pub struct SankeyLink {
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TimelineDiagram {
     /// Optional title for the timeline
     pub title: Option<String>,
@@ -160,6 +470,8 @@ pub struct TimelineDiagram {
 ///
 /// Groups related timeline items under a common heading or time period.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TimelineSection {
     /// Name or heading for this section
     pub name: String,
@@ -171,6 +483,8 @@ pub struct TimelineSection {
 ///
 /// Timeline items represent either discrete events or time periods.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum TimelineItem {
     /// A specific time period or duration
     Period(String),
@@ -178,6 +492,167 @@ pub enum TimelineItem {
     Event(String),
 }
 
+/// The ordering key of a timeline period label
+///
+/// Labels such as `2004`, `2004-05` or `2004/05/12` are dates, other numeric
+/// labels (`1.5`, `-300`) are numbers, and anything else is free text that
+/// has no chronological position.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum PeriodKey {
+    Date {
+        year: i32,
+        month: Option<u8>,
+        day: Option<u8>,
+    },
+    Number(f64),
+    Text(String),
+}
+
+impl PeriodKey {
+    pub fn parse(label: &str) -> Self {
+        let label = label.trim();
+        let parts: Vec<&str> = label.split(['-', '/']).collect();
+        let is_date = parts.len() <= 3
+            && parts[0].len() == 4
+            && parts.iter().all(|part| {
+                !part.is_empty() && part.len() <= 4 && part.bytes().all(|b| b.is_ascii_digit())
+            });
+        if is_date {
+            let month = parts.get(1).and_then(|part| part.parse().ok());
+            let day = parts.get(2).and_then(|part| part.parse().ok());
+            let in_range = month.map_or(parts.len() < 2, |month| (1..=12).contains(&month))
+                && day.map_or(parts.len() < 3, |day| (1..=31).contains(&day));
+            if let (Ok(year), true) = (parts[0].parse(), in_range) {
+                return PeriodKey::Date { year, month, day };
+            }
+        }
+
+        match label.parse::<f64>() {
+            Ok(number) if number.is_finite() => PeriodKey::Number(number),
+            _ => PeriodKey::Text(label.to_string()),
+        }
+    }
+
+    /// Position on the time axis; `None` for free text. A year on its own
+    /// sorts before any month of that year, and a bare number compares as a
+    /// year.
+    pub fn ordinal(&self) -> Option<(f64, u8, u8)> {
+        match self {
+            PeriodKey::Date { year, month, day } => {
+                Some((*year as f64, month.unwrap_or(0), day.unwrap_or(0)))
+            }
+            PeriodKey::Number(number) => Some((*number, 0, 0)),
+            PeriodKey::Text(_) => None,
+        }
+    }
+}
+
+/// A timeline period with its events, as returned by
+/// [`TimelineDiagram::periods`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelinePeriod<'a> {
+    /// The period label, without the events written on the same line
+    pub label: &'a str,
+    pub key: PeriodKey,
+    /// Name of the section the period appears in
+    pub section: &'a str,
+    /// Events on the period line followed by those on continuation lines
+    pub events: Vec<&'a str>,
+}
+
+impl TimelineDiagram {
+    /// Periods in source order. A period line `2004 : Facebook : Google`
+    /// is split into its label and events; continuation lines (`: Orkut`)
+    /// add events to the preceding period.
+    pub fn periods(&self) -> Vec<TimelinePeriod<'_>> {
+        let mut periods: Vec<TimelinePeriod<'_>> = Vec::new();
+
+        for section in &self.sections {
+            for item in &section.items {
+                match item {
+                    TimelineItem::Period(text) => {
+                        let mut parts = text.split(':').map(str::trim);
+                        let label = parts.next().unwrap_or_default();
+                        periods.push(TimelinePeriod {
+                            label,
+                            key: PeriodKey::parse(label),
+                            section: &section.name,
+                            events: parts.filter(|event| !event.is_empty()).collect(),
+                        });
+                    }
+                    TimelineItem::Event(text) => {
+                        if let Some(period) = periods.last_mut() {
+                            period.events.push(text.trim());
+                        }
+                    }
+                }
+            }
+        }
+
+        periods
+    }
+
+    /// Periods sorted by [`PeriodKey::ordinal`]. Free-text periods follow the
+    /// dated ones; equal keys keep their source order.
+    pub fn chronological_periods(&self) -> Vec<TimelinePeriod<'_>> {
+        let mut periods = self.periods();
+        periods.sort_by(|a, b| match (a.key.ordinal(), b.key.ordinal()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        periods
+    }
+
+    /// Events of the periods from `start` to `end` inclusive, in
+    /// chronological order.
+    ///
+    /// When both bounds parse as dates or numbers, every dated period within
+    /// the range is included, whether or not a period with that exact label
+    /// exists. Otherwise the bounds must match period labels, and the periods
+    /// between them in chronological order are included.
+    pub fn events_between(&self, start: &str, end: &str) -> Vec<&str> {
+        let periods = self.chronological_periods();
+        let bounds = (
+            PeriodKey::parse(start).ordinal(),
+            PeriodKey::parse(end).ordinal(),
+        );
+
+        let selected: Vec<&TimelinePeriod<'_>> = match bounds {
+            (Some(low), Some(high)) => periods
+                .iter()
+                .filter(|period| {
+                    period
+                        .key
+                        .ordinal()
+                        .is_some_and(|ordinal| ordinal >= low && ordinal <= high)
+                })
+                .collect(),
+            _ => {
+                let position = |label: &str| {
+                    periods
+                        .iter()
+                        .position(|period| period.label == label.trim())
+                };
+                match (position(start), position(end)) {
+                    (Some(first), Some(last)) if first <= last => {
+                        periods[first..=last].iter().collect()
+                    }
+                    _ => Vec::new(),
+                }
+            }
+        };
+
+        selected
+            .into_iter()
+            .flat_map(|period| period.events.iter().copied())
+            .collect()
+    }
+}
+
 /// User journey diagram representation
 ///
 /// Journey diagrams map user experiences through a process or service,
@@ -206,6 +681,8 @@ pub enum TimelineItem {
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JourneyDiagram {
     /// Optional title for the journey
     pub title: Option<String>,
@@ -219,6 +696,8 @@ pub struct JourneyDiagram {
 ///
 /// Groups related tasks or steps in the user journey under a common theme.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JourneySection {
     /// Name or heading for this section of the journey
     pub name: String,
@@ -231,6 +710,8 @@ pub struct JourneySection {
 /// Represents an action taken by users, with an associated satisfaction score
 /// and the actors involved in performing the task.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct JourneyTask {
     /// Name or description of the task
     pub name: String,
@@ -261,10 +742,13 @@ pub struct JourneyTask {
 ///         },
 ///     ],
 ///     statements: vec![],
+///     boxes: vec![],
 ///     autonumber: None,
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SequenceDiagram {
     /// Optional title for the sequence diagram
     pub title: Option<String>,
@@ -274,14 +758,30 @@ pub struct SequenceDiagram {
     pub participants: Vec<Participant>,
     /// Sequence of statements (messages, notes, etc.)
     pub statements: Vec<SequenceStatement>,
+    /// `box` blocks grouping participants, in source order
+    pub boxes: Vec<ParticipantBox>,
     /// Optional automatic numbering configuration
     pub autonumber: Option<AutoNumber>,
 }
 
+/// A `box Purple Group ... end` block drawing a background behind the
+/// participants declared in it
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ParticipantBox {
+    pub color: Option<Color>,
+    pub title: Option<String>,
+    /// Ids of the participants in the box, in declaration order
+    pub participants: Vec<String>,
+}
+
 /// A participant in a sequence diagram
 ///
 /// Represents an actor, object, or system component that can send and receive messages.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Participant {
     /// The name/identifier of the participant
     pub actor: String,
@@ -292,20 +792,580 @@ pub struct Participant {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ParticipantType {
     Participant,
     Actor,
 }
 
+/// How participant references in sequence statements are spelled after
+/// [`SequenceDiagram::normalize_participant_references`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ParticipantNaming {
+    /// Use the declared identifier (`A` in `participant A as Alice`)
+    #[default]
+    Id,
+    /// Use the display alias when one is declared, otherwise the identifier
+    DisplayName,
+}
+
+/// What [`SequenceDiagram::remove_participant`] does with statements that
+/// involve the removed participant
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ParticipantRemoval {
+    /// Drop messages to or from the participant
+    #[default]
+    DropMessages,
+    /// Send and receive the participant's messages from the named
+    /// participant instead. A message between the two is dropped rather than
+    /// turned into a self-message.
+    Rewire(String),
+}
+
+/// What [`SequenceDiagram::filtered_with`] does with messages that pass
+/// through hidden participants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum PassThroughMessages {
+    /// Drop every message to or from a hidden participant
+    #[default]
+    Drop,
+    /// Replace a chain that leaves a kept participant, passes only through
+    /// hidden ones and arrives at another kept participant with one derived
+    /// message. The derived message has the text and arrow of the first
+    /// message of the chain and takes the place of the last one.
+    Collapse,
+}
+
+impl Participant {
+    /// The name shown for this participant: its alias, or its identifier
+    pub fn display_name(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.actor)
+    }
+}
+
+impl SequenceDiagram {
+    /// Find the participant referred to by either its identifier or its alias.
+    ///
+    /// Identifiers take precedence, so `participant A as B` followed by
+    /// `participant B` resolves `B` to the second participant.
+    pub fn resolve_actor(&self, name_or_alias: &str) -> Option<&Participant> {
+        find_participant(&self.participants, name_or_alias)
+    }
+
+    /// Rewrite every participant reference in the statements (messages,
+    /// notes, activations and destructions, including those nested in
+    /// blocks) to a single spelling, so `A` and `Alice` are not treated as
+    /// different participants. Unknown names are left untouched.
+    pub fn normalize_participant_references(&mut self, naming: ParticipantNaming) {
+        let participants = &self.participants;
+        let rename = |name: &str| -> String {
+            match find_participant(participants, name.trim()) {
+                Some(participant) => match naming {
+                    ParticipantNaming::Id => participant.actor.clone(),
+                    ParticipantNaming::DisplayName => participant.display_name().to_string(),
+                },
+                None => name.to_string(),
+            }
+        };
+        normalize_statements(&mut self.statements, &rename);
+    }
+
+    /// Rename a participant and every statement that refers to it. Returns
+    /// `false` if no participant has the id `old_id`.
+    pub fn rename_participant(&mut self, old_id: &str, new_id: &str) -> bool {
+        let Some(participant) = self.participants.iter_mut().find(|p| p.actor == old_id) else {
+            return false;
+        };
+        participant.actor = new_id.to_string();
+        for member in self.boxes.iter_mut().flat_map(|b| &mut b.participants) {
+            if *member == old_id {
+                *member = new_id.to_string();
+            }
+        }
+
+        let rename = |name: &str| -> String {
+            if name.trim() == old_id {
+                new_id.to_string()
+            } else {
+                name.to_string()
+            }
+        };
+        normalize_statements(&mut self.statements, &rename);
+        true
+    }
+
+    /// Remove a participant, referred to by id or alias, together with its
+    /// activations, creation and destruction. Its messages and notes are
+    /// dropped or moved to another participant according to `policy`;
+    /// blocks left without statements are removed too. Returns `false`, leaving the diagram unchanged, if the
+    /// participant or the rewire target does not exist.
+    pub fn remove_participant(&mut self, name: &str, policy: ParticipantRemoval) -> bool {
+        let Some(removed) = self.resolve_actor(name).cloned() else {
+            return false;
+        };
+        let target = match &policy {
+            ParticipantRemoval::DropMessages => None,
+            ParticipantRemoval::Rewire(target) => match self.resolve_actor(target) {
+                Some(target) if target.actor != removed.actor => Some(target.actor.clone()),
+                _ => return false,
+            },
+        };
+
+        let participants = &self.participants;
+        let is_removed = |reference: &str| {
+            find_participant(participants, reference.trim())
+                .is_some_and(|participant| participant.actor == removed.actor)
+        };
+        remove_from_statements(&mut self.statements, &is_removed, target.as_deref());
+        self.participants.retain(|p| p.actor != removed.actor);
+        retain_box_members(&mut self.boxes, |member| member != removed.actor);
+        true
+    }
+
+    /// Move the named participants (ids or aliases) to the front, in the
+    /// given order; the others follow in their current order. Statements are
+    /// unaffected, since declaration order alone decides the column order.
+    /// The printer keeps the participants of a box together, at the position
+    /// of its first one.
+    /// Returns `false`, leaving the order unchanged, if a name is unknown.
+    pub fn reorder_participants(&mut self, order: &[&str]) -> bool {
+        let mut front: Vec<String> = Vec::new();
+        for name in order {
+            let Some(participant) = self.resolve_actor(name) else {
+                return false;
+            };
+            if !front.contains(&participant.actor) {
+                front.push(participant.actor.clone());
+            }
+        }
+
+        let position = |participant: &Participant| {
+            front
+                .iter()
+                .position(|actor| *actor == participant.actor)
+                .unwrap_or(front.len())
+        };
+        // Stable, so participants not named keep their relative order
+        self.participants.sort_by_key(position);
+        true
+    }
+
+    /// Every message in document order, including those nested in blocks
+    pub fn messages(&self) -> impl Iterator<Item = &Message> {
+        walk_statements(&self.statements).filter_map(|statement| match statement {
+            SequenceStatement::Message(message) => Some(message),
+            _ => None,
+        })
+    }
+
+    /// Every message with the number `autonumber` shows next to it, `None`
+    /// when the diagram is not numbered
+    pub fn numbered_messages(&self) -> impl Iterator<Item = (Option<i32>, &Message)> {
+        let numbering = self
+            .autonumber
+            .as_ref()
+            .filter(|numbering| numbering.visible);
+        self.messages()
+            .enumerate()
+            .map(move |(index, message)| (numbering.map(|n| n.number(index)), message))
+    }
+
+    /// Every note in document order, including those nested in blocks
+    pub fn notes(&self) -> impl Iterator<Item = &Note> {
+        walk_statements(&self.statements).filter_map(|statement| match statement {
+            SequenceStatement::Note(note) => Some(note),
+            _ => None,
+        })
+    }
+
+    /// Each request message with its reply, in the order of the requests
+    ///
+    /// A message is a reply if it goes back from the receiver to the sender
+    /// of an earlier unanswered request, the most recent one first, the way
+    /// calls return; participants are compared by id, so an alias matches.
+    /// Requests never answered, including self-messages, have no reply.
+    pub fn interaction_pairs(&self) -> impl Iterator<Item = (&Message, Option<&Message>)> {
+        let actor_id = |reference: &str| {
+            self.resolve_actor(reference.trim())
+                .map_or_else(|| reference.trim().to_string(), |p| p.actor.clone())
+        };
+
+        let mut pairs: Vec<(&Message, Option<&Message>)> = Vec::new();
+        // Indexes into `pairs` of the requests still waiting for a reply
+        let mut open: Vec<usize> = Vec::new();
+        for message in self.messages() {
+            let (from, to) = (actor_id(&message.from), actor_id(&message.to));
+            let request = open.iter().rposition(|&index| {
+                let request = pairs[index].0;
+                actor_id(&request.from) == to && actor_id(&request.to) == from
+            });
+            match request {
+                Some(position) if from != to => {
+                    let index = open.remove(position);
+                    pairs[index].1 = Some(message);
+                }
+                _ => {
+                    open.push(pairs.len());
+                    pairs.push((message, None));
+                }
+            }
+        }
+        pairs.into_iter()
+    }
+
+    /// A view of the diagram with only the named participants (ids or
+    /// aliases) and the statements among them. Unknown names are ignored.
+    pub fn filtered(&self, participants: &[&str]) -> SequenceDiagram {
+        self.filtered_with(participants, PassThroughMessages::Drop)
+    }
+
+    /// Like [`SequenceDiagram::filtered`], with messages through hidden
+    /// participants handled according to `pass_through`. Chains are followed
+    /// within one block only.
+    pub fn filtered_with(
+        &self,
+        participants: &[&str],
+        pass_through: PassThroughMessages,
+    ) -> SequenceDiagram {
+        let kept: Vec<&str> = participants
+            .iter()
+            .filter_map(|name| self.resolve_actor(name))
+            .map(|participant| participant.actor.as_str())
+            .collect();
+        let is_hidden = |reference: &str| {
+            !find_participant(&self.participants, reference.trim())
+                .is_some_and(|participant| kept.contains(&participant.actor.as_str()))
+        };
+
+        let mut statements = self.statements.clone();
+        if pass_through == PassThroughMessages::Collapse {
+            let actor_id =
+                |reference: &str| match find_participant(&self.participants, reference.trim()) {
+                    Some(participant) => participant.actor.clone(),
+                    None => reference.trim().to_string(),
+                };
+            collapse_pass_through(&mut statements, &is_hidden, &actor_id);
+        }
+        remove_from_statements(&mut statements, &is_hidden, None);
+
+        SequenceDiagram {
+            title: self.title.clone(),
+            accessibility: self.accessibility.clone(),
+            participants: self
+                .participants
+                .iter()
+                .filter(|participant| kept.contains(&participant.actor.as_str()))
+                .cloned()
+                .collect(),
+            statements,
+            boxes: {
+                let mut boxes = self.boxes.clone();
+                retain_box_members(&mut boxes, |member| kept.iter().any(|kept| *kept == member));
+                boxes
+            },
+            autonumber: self.autonumber.clone(),
+        }
+    }
+}
+
+/// `statements` and the statements of their blocks, depth first in document
+/// order
+fn walk_statements(statements: &[SequenceStatement]) -> impl Iterator<Item = &SequenceStatement> {
+    let mut pending = vec![statements.iter()];
+    std::iter::from_fn(move || loop {
+        let Some(statement) = pending.last_mut()?.next() else {
+            pending.pop();
+            continue;
+        };
+        pending.extend(
+            nested_statements(statement)
+                .into_iter()
+                .rev()
+                .map(|s| s.iter()),
+        );
+        return Some(statement);
+    })
+}
+
+/// The statement lists of a block, in document order
+fn nested_statements(statement: &SequenceStatement) -> Vec<&[SequenceStatement]> {
+    match statement {
+        SequenceStatement::Loop(block) => vec![block.statements.as_slice()],
+        SequenceStatement::Opt(block) => vec![block.statements.as_slice()],
+        SequenceStatement::Break(block) => vec![block.statements.as_slice()],
+        SequenceStatement::Alt(block) => std::iter::once(block.statements.as_slice())
+            .chain(
+                block
+                    .else_branch
+                    .iter()
+                    .map(|branch| branch.statements.as_slice()),
+            )
+            .collect(),
+        SequenceStatement::Par(block) => block
+            .branches
+            .iter()
+            .map(|branch| branch.statements.as_slice())
+            .collect(),
+        SequenceStatement::Critical(block) => std::iter::once(block.statements.as_slice())
+            .chain(
+                block
+                    .options
+                    .iter()
+                    .map(|option| option.statements.as_slice()),
+            )
+            .collect(),
+        SequenceStatement::Rect { statements, .. } => vec![statements.as_slice()],
+        SequenceStatement::Message(_)
+        | SequenceStatement::Note(_)
+        | SequenceStatement::Activate(_)
+        | SequenceStatement::Deactivate(_)
+        | SequenceStatement::Create(_)
+        | SequenceStatement::Destroy(_) => Vec::new(),
+    }
+}
+
+/// Keep the box members for which `keep` is true, dropping boxes left empty
+fn retain_box_members(boxes: &mut Vec<ParticipantBox>, keep: impl Fn(&str) -> bool) {
+    for participant_box in boxes.iter_mut() {
+        participant_box.participants.retain(|member| keep(member));
+    }
+    boxes.retain(|participant_box| !participant_box.participants.is_empty());
+}
+
+/// Rewrite the last message of each chain from a kept participant through
+/// hidden ones back to a kept participant as a message from the chain's
+/// origin, so removing the hidden participants' messages leaves one derived
+/// message per chain
+fn collapse_pass_through(
+    statements: &mut [SequenceStatement],
+    is_hidden: &dyn Fn(&str) -> bool,
+    actor_id: &dyn Fn(&str) -> String,
+) {
+    // The message that started the chain and the hidden participants it reached
+    let mut chain: Option<(Message, Vec<String>)> = None;
+    for statement in statements {
+        match statement {
+            SequenceStatement::Message(message) => {
+                let (from_hidden, to_hidden) = (is_hidden(&message.from), is_hidden(&message.to));
+                let reached = |chain: &Option<(Message, Vec<String>)>| {
+                    chain
+                        .as_ref()
+                        .is_some_and(|(_, hidden)| hidden.contains(&actor_id(&message.from)))
+                };
+                match (from_hidden, to_hidden) {
+                    (false, true) => {
+                        chain = Some((message.clone(), vec![actor_id(&message.to)]));
+                    }
+                    (true, true) if reached(&chain) => {
+                        if let Some((_, hidden)) = &mut chain {
+                            hidden.push(actor_id(&message.to));
+                        }
+                    }
+                    (true, false) if reached(&chain) => {
+                        let (origin, _) = chain.take().expect("chain is active");
+                        if actor_id(&origin.from) != actor_id(&message.to) {
+                            message.from = origin.from;
+                            message.text = origin.text;
+                            message.arrow_type = origin.arrow_type;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            SequenceStatement::Loop(block) => {
+                collapse_pass_through(&mut block.statements, is_hidden, actor_id)
+            }
+            SequenceStatement::Opt(block) => {
+                collapse_pass_through(&mut block.statements, is_hidden, actor_id)
+            }
+            SequenceStatement::Break(block) => {
+                collapse_pass_through(&mut block.statements, is_hidden, actor_id)
+            }
+            SequenceStatement::Alt(block) => {
+                collapse_pass_through(&mut block.statements, is_hidden, actor_id);
+                if let Some(else_branch) = &mut block.else_branch {
+                    collapse_pass_through(&mut else_branch.statements, is_hidden, actor_id);
+                }
+            }
+            SequenceStatement::Par(block) => {
+                for branch in &mut block.branches {
+                    collapse_pass_through(&mut branch.statements, is_hidden, actor_id);
+                }
+            }
+            SequenceStatement::Critical(block) => {
+                collapse_pass_through(&mut block.statements, is_hidden, actor_id);
+                for option in &mut block.options {
+                    collapse_pass_through(&mut option.statements, is_hidden, actor_id);
+                }
+            }
+            SequenceStatement::Rect { statements, .. } => {
+                collapse_pass_through(statements, is_hidden, actor_id)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Remove the statements involving a participant, or rewire its messages to
+/// `target`. Returns whether anything was left.
+fn remove_from_statements(
+    statements: &mut Vec<SequenceStatement>,
+    is_removed: &dyn Fn(&str) -> bool,
+    target: Option<&str>,
+) -> bool {
+    let was_empty = statements.is_empty();
+    statements.retain_mut(|statement| match statement {
+        SequenceStatement::Message(message) => {
+            let (from, to) = (is_removed(&message.from), is_removed(&message.to));
+            match target {
+                _ if !from && !to => true,
+                None => false,
+                Some(target) => {
+                    let was_self_message = message.from.trim() == message.to.trim();
+                    if from {
+                        message.from = target.to_string();
+                    }
+                    if to {
+                        message.to = target.to_string();
+                    }
+                    was_self_message || message.from != message.to
+                }
+            }
+        }
+        SequenceStatement::Note(note) => {
+            let mut actors: Vec<String> = Vec::new();
+            for actor in note.actor.split(',') {
+                let actor = match (is_removed(actor), target) {
+                    (false, _) => actor.to_string(),
+                    (true, Some(target)) => target.to_string(),
+                    (true, None) => continue,
+                };
+                if !actors.iter().any(|kept| kept.trim() == actor.trim()) {
+                    actors.push(actor);
+                }
+            }
+            note.actor = actors.join(",");
+            !actors.is_empty()
+        }
+        SequenceStatement::Activate(actor)
+        | SequenceStatement::Deactivate(actor)
+        | SequenceStatement::Destroy(actor) => !is_removed(actor),
+        SequenceStatement::Create(participant) => !is_removed(&participant.actor),
+        SequenceStatement::Loop(block) => {
+            remove_from_statements(&mut block.statements, is_removed, target)
+        }
+        SequenceStatement::Opt(block) => {
+            remove_from_statements(&mut block.statements, is_removed, target)
+        }
+        SequenceStatement::Break(block) => {
+            remove_from_statements(&mut block.statements, is_removed, target)
+        }
+        SequenceStatement::Alt(block) => {
+            let kept = remove_from_statements(&mut block.statements, is_removed, target);
+            let else_kept = match &mut block.else_branch {
+                Some(branch) => remove_from_statements(&mut branch.statements, is_removed, target),
+                None => false,
+            };
+            kept || else_kept
+        }
+        SequenceStatement::Par(block) => {
+            let mut kept = false;
+            for branch in &mut block.branches {
+                kept |= remove_from_statements(&mut branch.statements, is_removed, target);
+            }
+            kept
+        }
+        SequenceStatement::Critical(block) => {
+            let mut kept = remove_from_statements(&mut block.statements, is_removed, target);
+            for option in &mut block.options {
+                kept |= remove_from_statements(&mut option.statements, is_removed, target);
+            }
+            kept
+        }
+        SequenceStatement::Rect { statements, .. } => {
+            remove_from_statements(statements, is_removed, target)
+        }
+    });
+    was_empty || !statements.is_empty()
+}
+
+fn find_participant<'a>(participants: &'a [Participant], name: &str) -> Option<&'a Participant> {
+    participants.iter().find(|p| p.actor == name).or_else(|| {
+        participants
+            .iter()
+            .find(|p| p.alias.as_deref() == Some(name))
+    })
+}
+
+fn normalize_statements(statements: &mut [SequenceStatement], rename: &dyn Fn(&str) -> String) {
+    for statement in statements {
+        match statement {
+            SequenceStatement::Message(message) => {
+                message.from = rename(&message.from);
+                message.to = rename(&message.to);
+            }
+            SequenceStatement::Note(note) => {
+                note.actor = note
+                    .actor
+                    .split(',')
+                    .map(rename)
+                    .collect::<Vec<_>>()
+                    .join(",");
+            }
+            SequenceStatement::Activate(actor)
+            | SequenceStatement::Deactivate(actor)
+            | SequenceStatement::Destroy(actor) => *actor = rename(actor),
+            SequenceStatement::Create(_) => {}
+            SequenceStatement::Loop(block) => normalize_statements(&mut block.statements, rename),
+            SequenceStatement::Opt(block) => normalize_statements(&mut block.statements, rename),
+            SequenceStatement::Break(block) => normalize_statements(&mut block.statements, rename),
+            SequenceStatement::Alt(block) => {
+                normalize_statements(&mut block.statements, rename);
+                if let Some(else_branch) = &mut block.else_branch {
+                    normalize_statements(&mut else_branch.statements, rename);
+                }
+            }
+            SequenceStatement::Par(block) => {
+                for branch in &mut block.branches {
+                    normalize_statements(&mut branch.statements, rename);
+                }
+            }
+            SequenceStatement::Critical(block) => {
+                normalize_statements(&mut block.statements, rename);
+                for option in &mut block.options {
+                    normalize_statements(&mut option.statements, rename);
+                }
+            }
+            SequenceStatement::Rect { statements, .. } => normalize_statements(statements, rename),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum SequenceStatement {
     Message(Message),
     Note(Note),
     Loop(Loop),
     Alt(Alternative),
     Opt(Optional),
+    Break(Break),
     Par(Parallel),
     Critical(Critical),
+    /// `rect rgb(200, 220, 255) ... end`: a background highlighting the
+    /// statements in it; `color` is the text after `rect`
+    Rect {
+        color: Color,
+        statements: Vec<SequenceStatement>,
+    },
     Activate(String),
     Deactivate(String),
     Create(Participant),
@@ -313,14 +1373,42 @@ pub enum SequenceStatement {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Message {
     pub from: String,
     pub to: String,
     pub text: String,
     pub arrow_type: ArrowType,
+    /// `+` or `-` after the arrow, as in `A->>+B`
+    pub activation: Option<MessageActivation>,
+}
+
+impl Message {
+    /// The `activate` or `deactivate` statement implied by the `+` or `-`
+    /// shorthand: `+` activates the receiver, `-` deactivates the sender
+    pub fn implied_activation(&self) -> Option<SequenceStatement> {
+        match self.activation? {
+            MessageActivation::Activate => Some(SequenceStatement::Activate(self.to.clone())),
+            MessageActivation::Deactivate => Some(SequenceStatement::Deactivate(self.from.clone())),
+        }
+    }
+}
+
+/// Activation shorthand on a message arrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum MessageActivation {
+    /// `+`: activates the receiver
+    Activate,
+    /// `-`: deactivates the sender
+    Deactivate,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ArrowType {
     SolidOpen,
     SolidClosed,
@@ -333,6 +1421,8 @@ pub enum ArrowType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Note {
     pub position: NotePosition,
     pub actor: String,
@@ -340,6 +1430,8 @@ pub struct Note {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum NotePosition {
     LeftOf,
     RightOf,
@@ -347,12 +1439,16 @@ pub enum NotePosition {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Loop {
     pub condition: String,
     pub statements: Vec<SequenceStatement>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Alternative {
     pub condition: String,
     pub statements: Vec<SequenceStatement>,
@@ -360,29 +1456,49 @@ pub struct Alternative {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ElseBranch {
     pub condition: Option<String>,
     pub statements: Vec<SequenceStatement>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Optional {
     pub condition: String,
     pub statements: Vec<SequenceStatement>,
 }
 
+/// `break` fragment: the statements run instead of the rest of the
+/// enclosing sequence when `condition` holds
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Break {
+    pub condition: String,
+    pub statements: Vec<SequenceStatement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Parallel {
     pub branches: Vec<ParallelBranch>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ParallelBranch {
     pub condition: Option<String>,
     pub statements: Vec<SequenceStatement>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Critical {
     pub condition: String,
     pub statements: Vec<SequenceStatement>,
@@ -390,33 +1506,61 @@ pub struct Critical {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CriticalOption {
     pub condition: String,
     pub statements: Vec<SequenceStatement>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AutoNumber {
     pub start: Option<i32>,
     pub step: Option<i32>,
     pub visible: bool,
 }
 
+impl AutoNumber {
+    /// The number of the message at `index`, counting from 0, in document
+    /// order; `start` and `step` default to 1
+    pub fn number(&self, index: usize) -> i32 {
+        let (start, step) = (self.start.unwrap_or(1), self.step.unwrap_or(1));
+        start.saturating_add(step.saturating_mul(index as i32))
+    }
+}
+
 // Placeholder types for other diagram types
 // These will be expanded as parsers are implemented
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClassDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
-    pub classes: std::collections::HashMap<String, Class>,
+    /// Top-level `direction` statement
+    pub direction: Option<FlowDirection>,
+    pub classes: crate::common::ordered_map::OrderedMap<String, Class>,
     pub relationships: Vec<ClassRelationship>,
     pub notes: Vec<Note>,
+    /// `namespace` blocks: the names of the classes declared in each, in
+    /// order. The classes themselves are in `classes`.
+    pub namespaces: std::collections::HashMap<String, Vec<String>>,
+    /// `style` statements, each targeting a class
+    pub styles: Vec<StyleDefinition>,
+    /// `link` and `click` statements
+    pub clicks: Vec<ClickEvent>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Class {
     pub name: String,
+    /// Type parameters between `~`, as in `class Map~K,V~`
+    pub generics: Vec<String>,
     pub stereotype: Option<Stereotype>,
     pub members: Vec<ClassMember>,
     pub annotations: Vec<String>,
@@ -424,6 +1568,8 @@ pub struct Class {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Stereotype {
     Interface,
     Abstract,
@@ -434,37 +1580,51 @@ pub enum Stereotype {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ClassMember {
     Property(Property),
     Method(Method),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Property {
     pub name: String,
     pub prop_type: Option<String>,
+    /// Type arguments of `prop_type`, as in `Map~K,V~ items`
+    pub generics: Vec<String>,
     pub visibility: Visibility,
     pub is_static: bool,
     pub default_value: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Method {
     pub name: String,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<String>,
+    /// Type arguments of `return_type`, as in `getItems() List~T~`
+    pub generics: Vec<String>,
     pub visibility: Visibility,
     pub is_static: bool,
     pub is_abstract: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Parameter {
     pub name: String,
     pub param_type: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Visibility {
     Public,    // +
     Private,   // -
@@ -472,7 +1632,163 @@ pub enum Visibility {
     Package,   // ~
 }
 
+impl Stereotype {
+    /// The name written between `<<` and `>>`, in lower case for the built-in
+    /// stereotypes
+    pub fn name(&self) -> &str {
+        match self {
+            Stereotype::Interface => "interface",
+            Stereotype::Abstract => "abstract",
+            Stereotype::Service => "service",
+            Stereotype::Enumeration => "enumeration",
+            Stereotype::Exception => "exception",
+            Stereotype::Custom(name) => name,
+        }
+    }
+}
+
+/// Options for [`ClassDiagram::api_surface_with`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApiSurfaceOptions {
+    /// Stereotypes, without `<<` and `>>`, whose classes are left out of the
+    /// report. Compared case-insensitively against the class stereotype and
+    /// its annotations. Defaults to `internal`.
+    pub excluded_stereotypes: Vec<String>,
+}
+
+impl Default for ApiSurfaceOptions {
+    fn default() -> Self {
+        Self {
+            excluded_stereotypes: vec!["internal".to_string()],
+        }
+    }
+}
+
+/// The public members of one class, as reported by [`ClassDiagram::api_surface`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClassApi {
+    pub class: String,
+    pub stereotype: Option<Stereotype>,
+    /// Public members in declaration order
+    pub members: Vec<ApiMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApiMember {
+    pub kind: ApiMemberKind,
+    pub name: String,
+    /// `name: Type` for properties, `name(param: Type): Return` for methods;
+    /// types are left out where the diagram does not give them
+    pub signature: String,
+    pub is_static: bool,
+    pub is_abstract: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ApiMemberKind {
+    Property,
+    Method,
+}
+
+impl ClassDiagram {
+    /// The public properties and methods of every class, leaving out classes
+    /// marked `<<internal>>`; see [`ClassDiagram::api_surface_with`]
+    pub fn api_surface(&self) -> Vec<ClassApi> {
+        self.api_surface_with(&ApiSurfaceOptions::default())
+    }
+
+    /// The public properties and methods of every class not excluded by
+    /// `options`, sorted by class name. Classes without public members are
+    /// left out.
+    pub fn api_surface_with(&self, options: &ApiSurfaceOptions) -> Vec<ClassApi> {
+        let excluded = |marker: &str| {
+            options
+                .excluded_stereotypes
+                .iter()
+                .any(|excluded| excluded.eq_ignore_ascii_case(marker.trim()))
+        };
+
+        let mut classes: Vec<&Class> = self
+            .classes
+            .values()
+            .filter(|class| {
+                !class
+                    .stereotype
+                    .iter()
+                    .map(Stereotype::name)
+                    .chain(class.annotations.iter().map(String::as_str))
+                    .any(excluded)
+            })
+            .collect();
+        classes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        classes
+            .into_iter()
+            .filter_map(|class| {
+                let members: Vec<ApiMember> =
+                    class.members.iter().filter_map(public_api_member).collect();
+                (!members.is_empty()).then(|| ClassApi {
+                    class: class.name.clone(),
+                    stereotype: class.stereotype.clone(),
+                    members,
+                })
+            })
+            .collect()
+    }
+}
+
+fn public_api_member(member: &ClassMember) -> Option<ApiMember> {
+    match member {
+        ClassMember::Property(property) if property.visibility == Visibility::Public => {
+            let signature = match &property.prop_type {
+                Some(prop_type) => format!("{}: {}", property.name, prop_type),
+                None => property.name.clone(),
+            };
+            Some(ApiMember {
+                kind: ApiMemberKind::Property,
+                name: property.name.clone(),
+                signature,
+                is_static: property.is_static,
+                is_abstract: false,
+            })
+        }
+        ClassMember::Method(method) if method.visibility == Visibility::Public => {
+            let parameters = method
+                .parameters
+                .iter()
+                .map(|parameter| match &parameter.param_type {
+                    Some(param_type) => format!("{}: {}", parameter.name, param_type),
+                    None => parameter.name.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut signature = format!("{}({})", method.name, parameters);
+            if let Some(return_type) = &method.return_type {
+                signature.push_str(&format!(": {}", return_type));
+            }
+            Some(ApiMember {
+                kind: ApiMemberKind::Method,
+                name: method.name.clone(),
+                signature,
+                is_static: method.is_static,
+                is_abstract: method.is_abstract,
+            })
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClassRelationship {
     pub from: String,
     pub to: String,
@@ -483,6 +1799,8 @@ pub struct ClassRelationship {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ClassRelationshipType {
     Inheritance, // <|--
     Composition, // *--
@@ -495,31 +1813,145 @@ pub enum ClassRelationshipType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StateDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
     pub version: StateVersion,
-    pub states: std::collections::HashMap<String, State>,
+    /// Top-level `direction` statement; composite states keep their own
+    pub direction: Option<FlowDirection>,
+    /// Top-level states; composite states hold the states nested inside them
+    pub states: crate::common::ordered_map::OrderedMap<String, State>,
+    /// Transitions written outside any composite state
     pub transitions: Vec<StateTransition>,
+    /// Notes written outside any composite state
     pub notes: Vec<StateNote>,
+    /// `classDef` statements, by class name
+    pub class_defs: std::collections::HashMap<String, ClassDef>,
+}
+
+impl StateDiagram {
+    /// Every state, nested ones included: each scope's states in declaration
+    /// order, each followed by the states nested inside it
+    pub fn all_states(&self) -> Vec<&State> {
+        let mut states = Vec::new();
+        collect_states(&self.states, &mut states);
+        states
+    }
+
+    /// The state with `id`, at any depth
+    pub fn state(&self, id: &str) -> Option<&State> {
+        self.all_states().into_iter().find(|state| state.id == id)
+    }
+
+    /// Top-level transitions followed by those written inside each composite
+    /// state, with states visited in the order of [`Self::all_states`]
+    pub fn all_transitions(&self) -> Vec<&StateTransition> {
+        let mut transitions: Vec<_> = self.transitions.iter().collect();
+        for state in self.all_states() {
+            transitions.extend(&state.transitions);
+            transitions.extend(
+                state
+                    .concurrent_regions
+                    .iter()
+                    .flat_map(|region| &region.transitions),
+            );
+        }
+        transitions
+    }
+
+    /// Top-level notes followed by those written inside each composite state,
+    /// with states visited in the order of [`Self::all_states`]
+    pub fn all_notes(&self) -> Vec<&StateNote> {
+        let mut notes: Vec<_> = self.notes.iter().collect();
+        for state in self.all_states() {
+            notes.extend(&state.notes);
+            notes.extend(
+                state
+                    .concurrent_regions
+                    .iter()
+                    .flat_map(|region| &region.notes),
+            );
+        }
+        notes
+    }
+}
+
+fn collect_states<'a>(
+    scope: &'a crate::common::ordered_map::OrderedMap<String, State>,
+    states: &mut Vec<&'a State>,
+) {
+    for state in scope.values() {
+        states.push(state);
+        collect_states(&state.states, states);
+        for region in &state.concurrent_regions {
+            collect_states(&region.states, states);
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum StateVersion {
     V1,
     V2,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct State {
     pub id: String,
     pub display_name: Option<String>,
     pub state_type: StateType,
-    pub substates: Vec<String>,               // IDs of child states
-    pub concurrent_regions: Vec<Vec<String>>, // For parallel states
+    /// States nested directly inside a composite state with a single body
+    pub states: crate::common::ordered_map::OrderedMap<String, State>,
+    /// Transitions written inside a composite state with a single body
+    pub transitions: Vec<StateTransition>,
+    /// Notes written inside a composite state with a single body
+    pub notes: Vec<StateNote>,
+    /// Bodies separated by `--` in a concurrent composite state, which hold
+    /// its states, transitions and notes instead; empty when the state has a
+    /// single body
+    pub concurrent_regions: Vec<StateRegion>,
+    /// Classes applied with `class` statements or a `:::` suffix
+    pub classes: Vec<String>,
+}
+
+impl State {
+    /// States nested directly inside this state, across its concurrent
+    /// regions, in id order
+    pub fn children(&self) -> Vec<&State> {
+        let mut children: Vec<_> = self
+            .states
+            .values()
+            .chain(
+                self.concurrent_regions
+                    .iter()
+                    .flat_map(|region| region.states.values()),
+            )
+            .collect();
+        children.sort_by(|a, b| a.id.cmp(&b.id));
+        children
+    }
+}
+
+/// One `--` separated region of a concurrent composite state
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StateRegion {
+    /// States nested directly inside this region
+    pub states: crate::common::ordered_map::OrderedMap<String, State>,
+    pub transitions: Vec<StateTransition>,
+    pub notes: Vec<StateNote>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum StateType {
     Simple,
     Composite,
@@ -531,6 +1963,8 @@ pub enum StateType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StateTransition {
     pub from: String,
     pub to: String,
@@ -540,6 +1974,8 @@ pub struct StateTransition {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StateNote {
     pub position: StateNotePosition,
     pub target: String,
@@ -547,6 +1983,8 @@ pub struct StateNote {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum StateNotePosition {
     LeftOf,
     RightOf,
@@ -555,11 +1993,13 @@ pub enum StateNotePosition {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FlowchartDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
     pub direction: FlowDirection,
-    pub nodes: std::collections::HashMap<String, FlowNode>,
+    pub nodes: crate::common::ordered_map::OrderedMap<String, FlowNode>,
     pub edges: Vec<FlowEdge>,
     pub subgraphs: Vec<Subgraph>,
     pub styles: Vec<StyleDefinition>,
@@ -568,6 +2008,8 @@ pub struct FlowchartDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum FlowDirection {
     TB, // Top to Bottom (same as TD)
     TD, // Top Down
@@ -576,7 +2018,55 @@ pub enum FlowDirection {
     LR, // Left to Right
 }
 
+impl FlowDirection {
+    /// Parse a direction keyword: `TB`, `TD`, `BT`, `RL` or `LR`
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "TB" => Some(FlowDirection::TB),
+            "TD" => Some(FlowDirection::TD),
+            "BT" => Some(FlowDirection::BT),
+            "RL" => Some(FlowDirection::RL),
+            "LR" => Some(FlowDirection::LR),
+            _ => None,
+        }
+    }
+
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            FlowDirection::TB => "TB",
+            FlowDirection::TD => "TD",
+            FlowDirection::BT => "BT",
+            FlowDirection::RL => "RL",
+            FlowDirection::LR => "LR",
+        }
+    }
+
+    /// The layout this keyword produces; `TB` and `TD` are the same
+    pub fn layout(&self) -> LayoutDirection {
+        match self {
+            FlowDirection::TB | FlowDirection::TD => LayoutDirection::TopToBottom,
+            FlowDirection::BT => LayoutDirection::BottomToTop,
+            FlowDirection::RL => LayoutDirection::RightToLeft,
+            FlowDirection::LR => LayoutDirection::LeftToRight,
+        }
+    }
+}
+
+/// Direction in which a diagram is laid out, independent of the keyword
+/// spelling used in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum LayoutDirection {
+    TopToBottom,
+    BottomToTop,
+    LeftToRight,
+    RightToLeft,
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FlowNode {
     pub id: String,
     pub text: Option<String>,
@@ -586,6 +2076,8 @@ pub struct FlowNode {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum NodeShape {
     Rectangle,        // [text]
     RoundedRectangle, // (text)
@@ -601,9 +2093,32 @@ pub enum NodeShape {
     Trapezoid,        // [/text\]
     TrapezoidAlt,     // [\text/]
     DoubleCircle,     // (((text)))
+    /// An image node: `id@{ img: "url", label: "text", pos: "t", w: 60, h: 60 }`
+    Image {
+        url: String,
+        position: Option<ImagePosition>,
+        width: Option<f64>,
+        height: Option<f64>,
+        /// `constraint: on` keeps the image's aspect ratio
+        constrained: Option<bool>,
+    },
+    /// A Mermaid v11 shape without bracket syntax, by its `@{ shape: name }`
+    /// name (`doc`, `bolt`, `cloud`, ...)
+    Special(String),
+}
+
+/// Where an image node's label is placed relative to the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ImagePosition {
+    Top,
+    Bottom,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FlowEdge {
     pub from: String,
     pub to: String,
@@ -612,21 +2127,103 @@ pub struct FlowEdge {
     pub min_length: Option<i32>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum EdgeType {
-    Arrow,            // -->
-    DottedArrow,      // -.->
-    ThickArrow,       // ==>
-    OpenLink,         // ---
-    DottedLink,       // -.-
-    ThickLink,        // ===
-    Invisible,        // ~~~
-    CircleEdge,       // --o
-    CrossEdge,        // --x
-    MultiDirectional, // <-->
+/// How a flowchart link is drawn: its line, and the mark at each end
+///
+/// The left head is drawn at the source node and the right head at the
+/// target, so `A o--o B` has a circle at both ends. The constants name the
+/// usual combinations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EdgeType {
+    pub line_style: EdgeLineStyle,
+    pub left_head: EdgeHead,
+    pub right_head: EdgeHead,
+}
+
+impl EdgeType {
+    /// `-->`
+    pub const ARROW: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::None, EdgeHead::Arrow);
+    /// `-.->`
+    pub const DOTTED_ARROW: EdgeType =
+        EdgeType::new(EdgeLineStyle::Dotted, EdgeHead::None, EdgeHead::Arrow);
+    /// `==>`
+    pub const THICK_ARROW: EdgeType =
+        EdgeType::new(EdgeLineStyle::Thick, EdgeHead::None, EdgeHead::Arrow);
+    /// `---`
+    pub const OPEN_LINK: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::None, EdgeHead::None);
+    /// `-.-`
+    pub const DOTTED_LINK: EdgeType =
+        EdgeType::new(EdgeLineStyle::Dotted, EdgeHead::None, EdgeHead::None);
+    /// `===`
+    pub const THICK_LINK: EdgeType =
+        EdgeType::new(EdgeLineStyle::Thick, EdgeHead::None, EdgeHead::None);
+    /// `~~~`
+    pub const INVISIBLE: EdgeType =
+        EdgeType::new(EdgeLineStyle::Invisible, EdgeHead::None, EdgeHead::None);
+    /// `--o`
+    pub const CIRCLE_EDGE: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::None, EdgeHead::Circle);
+    /// `--x`
+    pub const CROSS_EDGE: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::None, EdgeHead::Cross);
+    /// `<-->`
+    pub const MULTI_DIRECTIONAL: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::Arrow, EdgeHead::Arrow);
+    /// `<-.->`
+    pub const DOTTED_MULTI_DIRECTIONAL: EdgeType =
+        EdgeType::new(EdgeLineStyle::Dotted, EdgeHead::Arrow, EdgeHead::Arrow);
+    /// `<==>`
+    pub const THICK_MULTI_DIRECTIONAL: EdgeType =
+        EdgeType::new(EdgeLineStyle::Thick, EdgeHead::Arrow, EdgeHead::Arrow);
+    /// `o--o`
+    pub const MULTI_DIRECTIONAL_CIRCLE: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::Circle, EdgeHead::Circle);
+    /// `x--x`
+    pub const MULTI_DIRECTIONAL_CROSS: EdgeType =
+        EdgeType::new(EdgeLineStyle::Solid, EdgeHead::Cross, EdgeHead::Cross);
+
+    pub const fn new(line_style: EdgeLineStyle, left_head: EdgeHead, right_head: EdgeHead) -> Self {
+        EdgeType {
+            line_style,
+            left_head,
+            right_head,
+        }
+    }
+
+    /// Whether both ends have a head, as in `<-->` or `o--o`
+    pub fn is_bidirectional(&self) -> bool {
+        self.left_head != EdgeHead::None && self.right_head != EdgeHead::None
+    }
+}
+
+/// The line of a flowchart link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum EdgeLineStyle {
+    Solid,     // --
+    Dotted,    // -.-
+    Thick,     // ==
+    Invisible, // ~~~
+}
+
+/// The mark at one end of a flowchart link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum EdgeHead {
+    None,
+    Arrow,  // < or >
+    Circle, // o
+    Cross,  // x
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Subgraph {
     pub id: String,
     pub title: Option<String>,
@@ -637,12 +2234,25 @@ pub struct Subgraph {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StyleDefinition {
     pub target: StyleTarget,
     pub styles: std::collections::HashMap<String, String>,
 }
 
+impl StyleDefinition {
+    /// The color of a style property such as `fill` or `stroke`
+    pub fn color(&self, property: &str) -> Option<Color> {
+        self.styles
+            .get(property)
+            .and_then(|value| Color::parse(value))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum StyleTarget {
     Node(String),
     Edge(String, String),
@@ -650,25 +2260,141 @@ pub enum StyleTarget {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClassDef {
     pub name: String,
     pub styles: std::collections::HashMap<String, String>,
+    /// Number of `classDef` statements that define the class; `styles`
+    /// merges their properties, later ones overriding earlier ones
+    pub definitions: usize,
+}
+
+impl ClassDef {
+    /// The color of a style property such as `fill` or `stroke`
+    pub fn color(&self, property: &str) -> Option<Color> {
+        self.styles
+            .get(property)
+            .and_then(|value| Color::parse(value))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClickEvent {
     pub node_id: String,
     pub action: ClickAction,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ClickAction {
     Href(String, Option<String>),         // URL, target
     Callback(String),                     // Function name
     Both(String, String, Option<String>), // Callback, URL, target
 }
 
+impl FlowchartDiagram {
+    /// Every URL the diagram links to: image node sources followed by
+    /// `click` hrefs, each in node id order and without duplicates
+    pub fn urls(&self) -> Vec<&str> {
+        let mut images: Vec<(&str, &str)> = self
+            .nodes
+            .values()
+            .filter_map(|node| match &node.shape {
+                NodeShape::Image { url, .. } => Some((node.id.as_str(), url.as_str())),
+                _ => None,
+            })
+            .collect();
+        images.sort();
+
+        let mut hrefs: Vec<(&str, &str)> = self
+            .clicks
+            .iter()
+            .filter_map(|click| match &click.action {
+                ClickAction::Href(url, _) | ClickAction::Both(_, url, _) => {
+                    Some((click.node_id.as_str(), url.as_str()))
+                }
+                ClickAction::Callback(_) => None,
+            })
+            .collect();
+        hrefs.sort();
+
+        let mut urls = Vec::new();
+        for (_, url) in images.into_iter().chain(hrefs) {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
+    }
+
+    /// Rename a node and every reference to it (edges, subgraph membership,
+    /// styles and click handlers). Returns `false` if the node does not exist.
+    pub fn rename_node(&mut self, old_id: &str, new_id: &str) -> bool {
+        if !self.nodes.contains_key(old_id) {
+            return false;
+        }
+        let renames = std::collections::BTreeMap::from([(old_id.to_string(), new_id.to_string())]);
+        self.rename_nodes(&renames);
+        true
+    }
+
+    /// Rename several nodes at once, mapping old ids to new ones. References
+    /// are renamed even for ids without a node definition, such as the ends
+    /// of a bare `A --> B`. All ids are renamed in a single pass, so the map
+    /// may swap ids.
+    pub fn rename_nodes(&mut self, renames: &std::collections::BTreeMap<String, String>) {
+        let rename = |id: &mut String| {
+            if let Some(new_id) = renames.get(id.as_str()) {
+                *id = new_id.clone();
+            }
+        };
+
+        self.nodes = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(|(mut id, mut node)| {
+                rename(&mut id);
+                rename(&mut node.id);
+                (id, node)
+            })
+            .collect();
+        for edge in &mut self.edges {
+            rename(&mut edge.from);
+            rename(&mut edge.to);
+        }
+        rename_in_subgraphs(&mut self.subgraphs, &rename);
+        for style in &mut self.styles {
+            match &mut style.target {
+                StyleTarget::Node(id) | StyleTarget::Subgraph(id) => rename(id),
+                StyleTarget::Edge(from, to) => {
+                    rename(from);
+                    rename(to);
+                }
+            }
+        }
+        for click in &mut self.clicks {
+            rename(&mut click.node_id);
+        }
+    }
+}
+
+fn rename_in_subgraphs(subgraphs: &mut [Subgraph], rename: &dyn Fn(&mut String)) {
+    for subgraph in subgraphs {
+        subgraph.nodes.iter_mut().for_each(rename);
+        for edge in &mut subgraph.edges {
+            rename(&mut edge.from);
+            rename(&mut edge.to);
+        }
+        rename_in_subgraphs(&mut subgraph.subgraphs, rename);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GanttDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -680,17 +2406,24 @@ pub struct GanttDiagram {
     pub today_marker: Option<String>,
     pub inclusive_end_dates: bool,
     pub top_axis: bool,
+    /// `displayMode` statement, or `displayMode` in the gantt section of the
+    /// frontmatter config when there is none; Mermaid only knows `compact`
+    pub display_mode: Option<String>,
     pub weekdays: WeekdaySettings,
     pub sections: Vec<GanttSection>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GanttSection {
     pub name: String,
     pub tasks: Vec<GanttTask>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GanttTask {
     pub name: String,
     pub id: Option<String>,
@@ -703,6 +2436,8 @@ pub struct GanttTask {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum TaskStatus {
     Active,
     Done,
@@ -712,6 +2447,8 @@ pub enum TaskStatus {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum TaskInteraction {
     Click {
         task_id: String,
@@ -726,12 +2463,16 @@ pub enum TaskInteraction {
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct WeekdaySettings {
     pub start_day: Option<Weekday>,
     pub weekend: Vec<Weekday>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Weekday {
     Monday,
     Tuesday,
@@ -743,6 +2484,8 @@ pub enum Weekday {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PieDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -751,12 +2494,16 @@ pub struct PieDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PieSlice {
     pub label: String,
     pub value: f64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GitDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -767,6 +2514,8 @@ pub struct GitDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GitCommit {
     pub id: Option<String>,
     pub commit_type: CommitType,
@@ -775,6 +2524,8 @@ pub struct GitCommit {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CommitType {
     Normal,
     Reverse,
@@ -782,6 +2533,8 @@ pub enum CommitType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GitBranch {
     pub name: String,
     pub order: Option<i32>,
@@ -789,24 +2542,32 @@ pub struct GitBranch {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum GitOperation {
     Commit {
         id: Option<String>,
         commit_type: CommitType,
         tag: Option<String>,
+        /// Whether the source spelled out the type, including `type: NORMAL`
+        explicit_type: bool,
     },
     Branch {
         name: String,
         order: Option<i32>,
     },
+    /// `checkout` or its alias `switch`
     Checkout {
         branch: String,
+        keyword: CheckoutKeyword,
     },
     Merge {
         branch: String,
         id: Option<String>,
         tag: Option<String>,
         commit_type: CommitType,
+        /// Whether the source spelled out the type, including `type: NORMAL`
+        explicit_type: bool,
     },
     CherryPick {
         id: String,
@@ -815,21 +2576,77 @@ pub enum GitOperation {
     },
 }
 
+/// The keyword a [`GitOperation::Checkout`] was written with. Both mean the
+/// same; the spelling is kept so the printer can reproduce it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CheckoutKeyword {
+    #[default]
+    Checkout,
+    Switch,
+}
+
+impl CheckoutKeyword {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            CheckoutKeyword::Checkout => "checkout",
+            CheckoutKeyword::Switch => "switch",
+        }
+    }
+}
+
+impl GitDiagram {
+    /// The operations with spelling differences removed: every checkout uses
+    /// the `checkout` keyword and no type is marked as written out, so two
+    /// diagrams that differ only in how they were spelled compare equal
+    pub fn normalized_operations(&self) -> Vec<GitOperation> {
+        self.operations
+            .iter()
+            .cloned()
+            .map(|mut operation| {
+                match &mut operation {
+                    GitOperation::Commit { explicit_type, .. }
+                    | GitOperation::Merge { explicit_type, .. } => *explicit_type = false,
+                    GitOperation::Checkout { keyword, .. } => *keyword = CheckoutKeyword::Checkout,
+                    GitOperation::Branch { .. } | GitOperation::CherryPick { .. } => {}
+                }
+                operation
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ErDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
-    pub entities: std::collections::HashMap<String, Entity>,
+    pub entities: crate::common::ordered_map::OrderedMap<String, Entity>,
     pub relationships: Vec<ErRelationship>,
+    /// `style` statements, each targeting an entity
+    pub styles: Vec<StyleDefinition>,
+    /// `classDef` statements, by class name
+    pub class_defs: std::collections::HashMap<String, ClassDef>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Entity {
     pub name: String,
     pub attributes: Vec<Attribute>,
+    /// Class applied with a `:::` suffix, as in `CUSTOMER:::vip`
+    pub css_class: Option<String>,
+    /// Name shown for an entity declared as `alias[Name]`; `name` holds the
+    /// alias, which relationships refer to
+    pub display_name: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Attribute {
     pub name: String,
     pub attr_type: String,
@@ -838,6 +2655,8 @@ pub struct Attribute {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum KeyType {
     PK, // Primary Key
     FK, // Foreign Key
@@ -845,6 +2664,8 @@ pub enum KeyType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ErRelationship {
     pub left_entity: String,
     pub right_entity: String,
@@ -854,12 +2675,16 @@ pub struct ErRelationship {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ErCardinality {
     pub min: CardinalityValue,
     pub max: CardinalityValue,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CardinalityValue {
     Zero,
     One,
@@ -867,16 +2692,21 @@ pub enum CardinalityValue {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct C4Diagram {
     pub diagram_type: C4DiagramType,
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
-    pub elements: std::collections::HashMap<String, C4Element>,
+    pub elements: crate::common::ordered_map::OrderedMap<String, C4Element>,
     pub boundaries: Vec<C4Boundary>,
     pub relationships: Vec<C4Relationship>,
+    pub tags: Vec<C4TagDefinition>, // AddElementTag / AddRelTag / AddBoundaryTag
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum C4DiagramType {
     Context,
     Container,
@@ -886,17 +2716,22 @@ pub enum C4DiagramType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct C4Element {
     pub id: String,
     pub element_type: C4ElementType,
     pub name: String,
     pub description: Option<String>,
     pub technology: Option<String>,
+    pub sprite: Option<String>,
     pub tags: Vec<String>,
     pub is_external: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum C4ElementType {
     Person,
     System,
@@ -913,6 +2748,8 @@ pub enum C4ElementType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct C4Boundary {
     pub id: String,
     pub boundary_type: C4BoundaryType,
@@ -923,6 +2760,8 @@ pub struct C4Boundary {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum C4BoundaryType {
     System,
     Container,
@@ -931,6 +2770,8 @@ pub enum C4BoundaryType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct C4Relationship {
     pub from: String,
     pub to: String,
@@ -942,6 +2783,36 @@ pub struct C4Relationship {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct C4TagDefinition {
+    pub name: String,
+    pub kind: C4TagKind,
+    pub properties: Vec<(String, String)>, // e.g. ("bgColor", "#d73027"), without the `$`
+}
+
+impl C4TagDefinition {
+    /// Look up a style property such as `bgColor` or `sprite`
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum C4TagKind {
+    Element,
+    Relationship,
+    Boundary,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum C4RelationshipDirection {
     Default,
     Up,
@@ -952,23 +2823,143 @@ pub enum C4RelationshipDirection {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MindmapDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
     pub root: MindmapNode,
+    /// Links between branches, only parsed with
+    /// [`crate::parsers::mindmap::MindmapDialect::CrossLinks`]
+    pub cross_edges: Vec<MindmapCrossEdge>,
+}
+
+impl MindmapDiagram {
+    /// Find a node by its hierarchical id, e.g. `root.1.3`
+    pub fn node(&self, id: &str) -> Option<&MindmapNode> {
+        let mut pending = vec![&self.root];
+        while let Some(node) = pending.pop() {
+            if node.id == id {
+                return Some(node);
+            }
+            pending.extend(node.children.iter());
+        }
+        None
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MindmapNode {
+    /// Hierarchical id: `root` for the root and `<parent>.<n>` for the n-th
+    /// child, counting from 1
     pub id: String,
-    pub text: String,
+    pub text: NodeText,
     pub shape: MindmapNodeShape,
     pub icon: Option<String>,
     pub class: Option<String>,
     pub children: Vec<MindmapNode>,
 }
 
+impl MindmapNode {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// The text of a mindmap node
+///
+/// A node written as ``["`**bold** text`"]`` holds a Markdown string, which
+/// Mermaid renders with bold, italics and line breaks; the backticks and
+/// quotes are not part of the text. Dereferences to the text itself, so plain
+/// and Markdown labels compare and print alike.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum NodeText {
+    Plain(String),
+    /// Markdown source, with `\n` where the string spans several lines
+    Markdown(String),
+}
+
+impl NodeText {
+    pub fn as_str(&self) -> &str {
+        match self {
+            NodeText::Plain(text) | NodeText::Markdown(text) => text,
+        }
+    }
+
+    pub fn is_markdown(&self) -> bool {
+        matches!(self, NodeText::Markdown(_))
+    }
+}
+
+impl Default for NodeText {
+    fn default() -> Self {
+        NodeText::Plain(String::new())
+    }
+}
+
+impl std::ops::Deref for NodeText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for NodeText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for NodeText {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for NodeText {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for NodeText {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl From<&str> for NodeText {
+    fn from(text: &str) -> Self {
+        NodeText::Plain(text.to_string())
+    }
+}
+
+impl From<String> for NodeText {
+    fn from(text: String) -> Self {
+        NodeText::Plain(text)
+    }
+}
+
+/// A link between two mindmap nodes outside the tree, e.g. `root.1 --> root.2`
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MindmapCrossEdge {
+    /// Hierarchical id of the source node
+    pub from: String,
+    /// Hierarchical id of the target node
+    pub to: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum MindmapNodeShape {
     Default, // No brackets
     Square,  // [text]
@@ -980,6 +2971,8 @@ pub enum MindmapNodeShape {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct QuadrantDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -991,12 +2984,16 @@ pub struct QuadrantDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AxisDefinition {
     pub label_start: Option<String>,
     pub label_end: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct QuadrantLabels {
     pub quadrant_1: Option<String>, // Top-right
     pub quadrant_2: Option<String>, // Top-left
@@ -1005,6 +3002,8 @@ pub struct QuadrantLabels {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DataPoint {
     pub name: String,
     pub x: f64, // 0.0 to 1.0
@@ -1013,12 +3012,16 @@ pub struct DataPoint {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClassDefinition {
     pub name: String,
     pub styles: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct XyChartDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -1029,12 +3032,16 @@ pub struct XyChartDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ChartOrientation {
     Vertical, // Default
     Horizontal,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct XAxis {
     pub title: Option<String>,
     pub labels: Vec<String>,
@@ -1042,25 +3049,40 @@ pub struct XAxis {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct YAxis {
     pub title: Option<String>,
     pub range: Option<(f64, f64)>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DataSeries {
     pub series_type: SeriesType,
     pub name: Option<String>,
-    pub data: Vec<f64>,
+    /// Data points in x-axis order; `None` marks a gap (`null`, `-` or an
+    /// empty item)
+    pub data: Vec<Option<f64>>,
+    /// Color given by `themeVariables.xyChart.plotColorPalette` in the
+    /// frontmatter or an `%%{init}%%` directive, which colors the series in
+    /// order and starts over when it runs out; `None` leaves the theme's
+    /// default color
+    pub color: Option<Color>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum SeriesType {
     Line,
     Bar,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct KanbanDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -1068,6 +3090,8 @@ pub struct KanbanDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct KanbanSection {
     pub id: String,
     pub title: String,
@@ -1075,6 +3099,8 @@ pub struct KanbanSection {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct KanbanItem {
     pub id: Option<String>,
     pub text: String,
@@ -1083,6 +3109,8 @@ pub struct KanbanItem {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BlockDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -1093,6 +3121,8 @@ pub struct BlockDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Block {
     Simple {
         id: String,
@@ -1109,7 +3139,60 @@ pub enum Block {
     },
 }
 
+impl Block {
+    /// The block's identifier, or `None` for spaces
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Block::Simple { id, .. } | Block::Composite { id, .. } => Some(id),
+            Block::Space { .. } => None,
+        }
+    }
+
+    /// Blocks nested directly inside this one
+    pub fn children(&self) -> &[Block] {
+        match self {
+            Block::Composite { blocks, .. } => blocks,
+            _ => &[],
+        }
+    }
+}
+
+impl BlockDiagram {
+    /// Resolve a block reference such as `inner` or the dotted path `outer.inner`
+    ///
+    /// The first path segment may name a block at any depth; each following segment
+    /// must name a direct child of the composite block before it.
+    pub fn resolve_block(&self, path: &str) -> Option<&Block> {
+        let mut segments = path.split('.');
+        let first = segments.next()?;
+        let rest: Vec<&str> = segments.collect();
+
+        let mut candidates = Vec::new();
+        collect_blocks_with_id(&self.blocks, first, &mut candidates);
+
+        candidates.into_iter().find_map(|start| {
+            rest.iter().try_fold(start, |block, segment| {
+                block
+                    .children()
+                    .iter()
+                    .find(|child| child.id() == Some(*segment))
+            })
+        })
+    }
+}
+
+fn collect_blocks_with_id<'a>(blocks: &'a [Block], id: &str, found: &mut Vec<&'a Block>) {
+    for block in blocks {
+        if block.id() == Some(id) {
+            found.push(block);
+        }
+        collect_blocks_with_id(block.children(), id, found);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum BlockShape {
     Rectangle,      // Basic block
     RoundedRect,    // Rounded corners
@@ -1121,6 +3204,8 @@ pub enum BlockShape {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BlockConnection {
     pub from: String,
     pub to: String,
@@ -1130,6 +3215,8 @@ pub struct BlockConnection {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum BlockArrowType {
     Normal,        // -->
     Dotted,        // -.->
@@ -1139,29 +3226,37 @@ pub enum BlockArrowType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BlockStyleDefinition {
     pub target: String,
     pub properties: Vec<BlockStyleProperty>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct BlockStyleProperty {
     pub name: String,
     pub value: String,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ArchitectureDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
     pub direction: ArchDirection,
-    pub services: std::collections::HashMap<String, Service>,
-    pub groups: std::collections::HashMap<String, Group>,
-    pub junctions: std::collections::HashMap<String, Junction>,
+    pub services: crate::common::ordered_map::OrderedMap<String, Service>,
+    pub groups: crate::common::ordered_map::OrderedMap<String, Group>,
+    pub junctions: crate::common::ordered_map::OrderedMap<String, Junction>,
     pub edges: Vec<ArchEdge>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ArchDirection {
     TB, // Top to Bottom
     BT, // Bottom to Top
@@ -1169,7 +3264,20 @@ pub enum ArchDirection {
     RL, // Right to Left
 }
 
+impl ArchDirection {
+    pub fn layout(&self) -> LayoutDirection {
+        match self {
+            ArchDirection::TB => LayoutDirection::TopToBottom,
+            ArchDirection::BT => LayoutDirection::BottomToTop,
+            ArchDirection::LR => LayoutDirection::LeftToRight,
+            ArchDirection::RL => LayoutDirection::RightToLeft,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Service {
     pub id: String,
     pub icon: Option<String>,
@@ -1178,6 +3286,8 @@ pub struct Service {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Group {
     pub id: String,
     pub icon: Option<String>,
@@ -1186,26 +3296,37 @@ pub struct Group {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Junction {
     pub id: String,
     pub in_group: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ArchEdge {
     pub from: EdgeEndpoint,
     pub to: EdgeEndpoint,
     pub label: Option<String>,
     pub edge_type: ArchEdgeType,
+    /// The arrow as written when it is a legacy spelling of `edge_type`,
+    /// such as `-->` for `->`
+    pub legacy_arrow: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct EdgeEndpoint {
     pub id: String,
     pub port: Option<Port>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Port {
     Left,   // L
     Right,  // R
@@ -1213,7 +3334,31 @@ pub enum Port {
     Bottom, // B
 }
 
+impl Port {
+    /// Direction from an element to the one attached at this port
+    pub fn direction_away(&self) -> LayoutDirection {
+        match self {
+            Port::Left => LayoutDirection::RightToLeft,
+            Port::Right => LayoutDirection::LeftToRight,
+            Port::Top => LayoutDirection::BottomToTop,
+            Port::Bottom => LayoutDirection::TopToBottom,
+        }
+    }
+
+    /// Direction toward an element from the one attached at this port
+    pub fn direction_toward(&self) -> LayoutDirection {
+        match self {
+            Port::Left => LayoutDirection::LeftToRight,
+            Port::Right => LayoutDirection::RightToLeft,
+            Port::Top => LayoutDirection::TopToBottom,
+            Port::Bottom => LayoutDirection::BottomToTop,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ArchEdgeType {
     Solid,   // --
     Dotted,  // ..
@@ -1222,6 +3367,8 @@ pub enum ArchEdgeType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PacketDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -1229,6 +3376,8 @@ pub struct PacketDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PacketField {
     pub start_bit: u32,
     pub end_bit: u32,
@@ -1237,15 +3386,21 @@ pub struct PacketField {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RequirementDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
-    pub requirements: std::collections::HashMap<String, Requirement>,
-    pub elements: std::collections::HashMap<String, Element>,
+    pub requirements: crate::common::ordered_map::OrderedMap<String, Requirement>,
+    pub elements: crate::common::ordered_map::OrderedMap<String, Element>,
     pub relationships: Vec<RequirementRelationship>,
+    /// `style` statements, one per styled requirement or element
+    pub styles: Vec<StyleDefinition>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Requirement {
     pub name: String,
     pub req_type: RequirementType,
@@ -1256,6 +3411,8 @@ pub struct Requirement {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum RequirementType {
     Requirement,
     FunctionalRequirement,
@@ -1266,6 +3423,8 @@ pub enum RequirementType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum RiskLevel {
     Low,
     Medium,
@@ -1273,6 +3432,8 @@ pub enum RiskLevel {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum VerificationMethod {
     Analysis,
     Inspection,
@@ -1281,6 +3442,8 @@ pub enum VerificationMethod {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Element {
     pub name: String,
     pub element_type: String,
@@ -1288,6 +3451,8 @@ pub struct Element {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RequirementRelationship {
     pub source: String,
     pub target: String,
@@ -1295,6 +3460,8 @@ pub struct RequirementRelationship {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum RelationshipType {
     Contains,
     Copies,
@@ -1306,6 +3473,8 @@ pub enum RelationshipType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TreemapDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -1313,6 +3482,8 @@ pub struct TreemapDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TreemapNode {
     pub name: String,
     pub value: Option<f64>,
@@ -1320,6 +3491,8 @@ pub struct TreemapNode {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RadarDiagram {
     pub title: Option<String>,
     pub accessibility: AccessibilityInfo,
@@ -1329,6 +3502,8 @@ pub struct RadarDiagram {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RadarConfig {
     pub background_color: Option<String>,
     pub grid_color: Option<String>,
@@ -1348,41 +3523,60 @@ impl Default for RadarConfig {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Dataset {
     pub name: String,
     pub values: Vec<f64>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MiscDiagram {
     pub diagram_type: String,
     pub content: MiscContent,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum MiscContent {
     Info(InfoDiagram),
     GitGraph(GitGraphAlt),
     Raw(RawDiagram),
 }
 
+/// An `info` diagram, or a document holding only `%%{...}%%` directives
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct InfoDiagram {
+    /// The argument of `info`, such as `showInfo`; empty if there is none
     pub command: String,
+    /// Configuration from `%%{init: {...}}%%` directives, merged in order.
+    /// Other directives, such as `%%{wrap}%%`, appear under their name.
+    pub options: std::collections::BTreeMap<String, crate::common::frontmatter::ConfigValue>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GitGraphAlt {
     pub commits: Vec<MiscGitCommit>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MiscGitCommit {
     pub action: String,
     pub params: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RawDiagram {
     pub lines: Vec<String>,
 }
@@ -0,0 +1,173 @@
+//! Visualization of the AST itself as a flowchart
+//!
+//! [`ast_to_flowchart`] draws the structure a diagram was parsed into: each
+//! struct, enum variant, list and map of the AST is a node, each scalar field
+//! a rounded leaf, and edges point from a value to what it contains. Printing
+//! the result with [`crate::MermaidPrinter`] gives a diagram that shows how
+//! the parser read the input, for teaching, debugging and bug reports.
+//!
+//! The structure is read from the AST's pretty `Debug` output, so every field
+//! of every diagram type is covered without a walker per type. A value that
+//! only wraps a scalar, such as `Some("Start")`, is drawn as one leaf, and map
+//! entries are drawn in key order so the output is the same on every run.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::ast::NodeShape;
+//! use mermaid_parser::common::ast_graph::ast_to_flowchart;
+//! use mermaid_parser::parse_diagram;
+//!
+//! let diagram = parse_diagram("pie\n    \"Dogs\" : 3")?;
+//! let graph = ast_to_flowchart(&diagram);
+//!
+//! assert_eq!(graph.nodes["n0"].text.as_deref(), Some("Pie"));
+//! let leaf = graph
+//!     .nodes
+//!     .values()
+//!     .find(|node| node.text.as_deref() == Some("label: \"Dogs\""))
+//!     .unwrap();
+//! assert_eq!(leaf.shape, NodeShape::RoundedRectangle);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{
+    AccessibilityInfo, DiagramType, EdgeType, FlowDirection, FlowEdge, FlowNode, FlowchartDiagram,
+    NodeShape,
+};
+use crate::common::ordered_map::OrderedMap;
+use std::collections::HashMap;
+
+/// A value in the `Debug` output: a container opened with `{`, `[` or `(`,
+/// or a single-line leaf
+struct DebugNode {
+    label: String,
+    opener: Option<char>,
+    children: Vec<DebugNode>,
+}
+
+impl DebugNode {
+    /// Finish a container once its closing line is read
+    fn close(mut self) -> DebugNode {
+        // `Some(` with one scalar inside reads better as `Some(value)`
+        if self.opener == Some('(') && self.children.len() == 1 && self.children[0].opener.is_none()
+        {
+            let child = self.children.remove(0);
+            return DebugNode {
+                label: format!("{}({})", self.label, child.label),
+                opener: None,
+                children: Vec::new(),
+            };
+        }
+        // Entries of hash maps, whose keys are quoted, are printed in
+        // iteration order
+        if self.opener == Some('{')
+            && self
+                .children
+                .iter()
+                .all(|child| child.label.starts_with('"'))
+        {
+            self.children.sort_by(|a, b| a.label.cmp(&b.label));
+        }
+        self
+    }
+}
+
+/// A flowchart showing the AST of `diagram`, laid out top to bottom with
+/// node ids `n0`, `n1`, ... in depth-first order
+pub fn ast_to_flowchart(diagram: &DiagramType) -> FlowchartDiagram {
+    let mut flowchart = FlowchartDiagram {
+        title: None,
+        accessibility: AccessibilityInfo::default(),
+        direction: FlowDirection::TD,
+        nodes: OrderedMap::new(),
+        edges: Vec::new(),
+        subgraphs: Vec::new(),
+        styles: Vec::new(),
+        class_defs: HashMap::new(),
+        clicks: Vec::new(),
+    };
+    if let Some(root) = debug_tree(&format!("{:#?}", diagram)) {
+        add_node(&mut flowchart, &root, None);
+    }
+    flowchart
+}
+
+/// Read the pretty `Debug` output into a tree
+fn debug_tree(text: &str) -> Option<DebugNode> {
+    let mut stack: Vec<DebugNode> = Vec::new();
+    let mut roots: Vec<DebugNode> = Vec::new();
+    let mut attach = |stack: &mut Vec<DebugNode>, node: DebugNode| match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => roots.push(node),
+    };
+
+    for line in text.lines() {
+        let line = line.trim();
+        let line = line.strip_suffix(',').unwrap_or(line);
+        if matches!(line, "}" | "]" | ")") {
+            if let Some(node) = stack.pop() {
+                attach(&mut stack, node.close());
+            }
+            continue;
+        }
+        match line.chars().next_back() {
+            Some(opener @ ('{' | '[' | '(')) => stack.push(DebugNode {
+                label: line[..line.len() - 1].trim_end().to_string(),
+                opener: Some(opener),
+                children: Vec::new(),
+            }),
+            _ => attach(
+                &mut stack,
+                DebugNode {
+                    label: line.to_string(),
+                    opener: None,
+                    children: Vec::new(),
+                },
+            ),
+        }
+    }
+    while let Some(node) = stack.pop() {
+        attach(&mut stack, node.close());
+    }
+    roots.into_iter().next()
+}
+
+fn add_node(flowchart: &mut FlowchartDiagram, node: &DebugNode, parent: Option<&str>) {
+    let id = format!("n{}", flowchart.nodes.len());
+    let (text, shape) = match node.opener {
+        // Tuples and nested lists or maps have no label of their own
+        Some(opener) if node.label.is_empty() => {
+            let kind = match opener {
+                '(' => "tuple",
+                '[' => "list",
+                _ => "map",
+            };
+            (kind.to_string(), NodeShape::Rectangle)
+        }
+        Some(_) => (node.label.clone(), NodeShape::Rectangle),
+        None => (node.label.clone(), NodeShape::RoundedRectangle),
+    };
+    flowchart.nodes.insert(
+        id.clone(),
+        FlowNode {
+            id: id.clone(),
+            text: Some(text),
+            shape,
+            classes: Vec::new(),
+            icon: None,
+        },
+    );
+    if let Some(parent) = parent {
+        flowchart.edges.push(FlowEdge {
+            from: parent.to_string(),
+            to: id.clone(),
+            edge_type: EdgeType::ARROW,
+            label: None,
+            min_length: None,
+        });
+    }
+    for child in &node.children {
+        add_node(flowchart, child, Some(&id));
+    }
+}
@@ -0,0 +1,278 @@
+//! Export of Gantt charts to iCalendar and CSV
+//!
+//! [`GanttDiagram::schedule`] resolves each task to calendar dates the way
+//! Mermaid lays them out: an explicit `YYYY-MM-DD` start wins, otherwise the
+//! task starts when the last of its `after` dependencies ends, otherwise when
+//! the previous task ends. Durations in days, weeks and hours are supported
+//! (hours are rounded up to whole days); `excludes` and `includes` are not
+//! applied, so excluded weekends still count towards durations. See
+//! [`crate::common::gantt::resolve_schedule`] for times of day, other date
+//! formats and excluded days.
+//!
+//! [`GanttDiagram::to_icalendar`] writes one all-day `VEVENT` per scheduled
+//! task and [`GanttDiagram::to_csv`] one row per task, so project plans can be
+//! imported into calendars and spreadsheets.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::{parse_diagram, DiagramType};
+//!
+//! let input = "gantt\n    dateFormat YYYY-MM-DD\n    section Build\n        Design :a1, 2024-03-01, 3d\n        Code :after a1, 1w";
+//! if let DiagramType::Gantt(gantt) = parse_diagram(input)? {
+//!     assert!(gantt.to_csv().contains("Code,Build,2024-03-04,2024-03-11,a1"));
+//!     assert!(gantt.to_icalendar().contains("DTSTART;VALUE=DATE:20240304"));
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{GanttDiagram, TaskStatus};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A day in the proleptic Gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CalendarDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl CalendarDate {
+    /// Parse a `YYYY-MM-DD` date, rejecting days that do not exist
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        let date = CalendarDate { year, month, day };
+        (CalendarDate::from_days(date.days()) == date).then_some(date)
+    }
+
+    /// The date `days` days later
+    pub fn add_days(self, days: i64) -> Self {
+        CalendarDate::from_days(self.days() + days)
+    }
+
+    /// Days since 1970-01-01
+    pub(crate) fn days(self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub(crate) fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        CalendarDate {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A Gantt task placed on the calendar
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledTask {
+    pub name: String,
+    pub section: String,
+    pub id: Option<String>,
+    /// `None` when neither a start date nor a scheduled predecessor is known
+    pub start: Option<CalendarDate>,
+    /// Exclusive end date; equal to `start` for milestones
+    pub end: Option<CalendarDate>,
+    pub dependencies: Vec<String>,
+}
+
+impl GanttDiagram {
+    /// Resolve every task to start and end dates, in document order
+    pub fn schedule(&self) -> Vec<ScheduledTask> {
+        let mut scheduled = Vec::new();
+        let mut ends: HashMap<&str, CalendarDate> = HashMap::new();
+        let mut previous_end = None;
+
+        for section in &self.sections {
+            for task in &section.tasks {
+                let start = match &task.start_date {
+                    Some(date) => CalendarDate::parse(date),
+                    None if !task.dependencies.is_empty() => task
+                        .dependencies
+                        .iter()
+                        .map(|dependency| ends.get(dependency.as_str()).copied())
+                        .collect::<Option<Vec<_>>>()
+                        .and_then(|dates| dates.into_iter().max()),
+                    None => previous_end,
+                };
+                let days = if task.status == TaskStatus::Milestone {
+                    Some(0)
+                } else {
+                    task.duration.as_deref().and_then(duration_days)
+                };
+                let end = start.zip(days).map(|(start, days)| start.add_days(days));
+
+                if let (Some(id), Some(end)) = (&task.id, end) {
+                    ends.insert(id, end);
+                }
+                previous_end = end;
+
+                scheduled.push(ScheduledTask {
+                    name: task.name.clone(),
+                    section: section.name.clone(),
+                    id: task.id.clone(),
+                    start,
+                    end,
+                    dependencies: task.dependencies.clone(),
+                });
+            }
+        }
+
+        scheduled
+    }
+
+    /// An iCalendar (RFC 5545) document with an all-day `VEVENT` per task
+    ///
+    /// Tasks that cannot be scheduled are left out. `DTSTAMP` is set to the
+    /// task's start so the output is reproducible.
+    pub fn to_icalendar(&self) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//mermaid-parser//Gantt export//EN".to_string(),
+        ];
+        if let Some(title) = &self.title {
+            lines.push(format!("X-WR-CALNAME:{}", escape_ical(title)));
+        }
+
+        for (index, task) in self.schedule().iter().enumerate() {
+            let Some(start) = task.start else {
+                continue;
+            };
+            let stamp = compact_date(start);
+            let uid = task
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("task-{}", index + 1));
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}@mermaid-parser", escape_ical(&uid)));
+            lines.push(format!("DTSTAMP:{}T000000Z", stamp));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", stamp));
+            if let Some(end) = task.end.filter(|end| *end > start) {
+                lines.push(format!("DTEND;VALUE=DATE:{}", compact_date(end)));
+            }
+            lines.push(format!("SUMMARY:{}", escape_ical(&task.name)));
+            if !task.section.is_empty() {
+                lines.push(format!("CATEGORIES:{}", escape_ical(&task.section)));
+            }
+            if !task.dependencies.is_empty() {
+                lines.push(format!(
+                    "DESCRIPTION:{}",
+                    escape_ical(&format!("After {}", task.dependencies.join(", ")))
+                ));
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+
+        lines.push("END:VCALENDAR".to_string());
+        let mut output = lines.join("\r\n");
+        output.push_str("\r\n");
+        output
+    }
+
+    /// CSV with a `task,section,start,end,dependencies` header and a row per
+    /// task; unknown dates are empty and dependencies are space separated
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("task,section,start,end,dependencies\n");
+        for task in self.schedule() {
+            let date = |date: Option<CalendarDate>| date.map(|d| d.to_string()).unwrap_or_default();
+            let row = [
+                task.name.clone(),
+                task.section.clone(),
+                date(task.start),
+                date(task.end),
+                task.dependencies.join(" "),
+            ];
+            let row: Vec<_> = row.iter().map(|field| escape_csv(field)).collect();
+            output.push_str(&row.join(","));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Whole days covered by a Mermaid duration such as `3d`, `2w` or `36h`
+fn duration_days(duration: &str) -> Option<i64> {
+    let duration = duration.trim();
+    let unit_days = match duration.chars().last()? {
+        'd' => 1.0,
+        'w' => 7.0,
+        'h' => 1.0 / 24.0,
+        _ => return None,
+    };
+    let amount: f64 = duration[..duration.len() - 1].parse().ok()?;
+    Some((amount * unit_days).ceil() as i64)
+}
+
+fn compact_date(date: CalendarDate) -> String {
+    format!("{:04}{:02}{:02}", date.year, date.month, date.day)
+}
+
+fn escape_ical(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_date_arithmetic() {
+        let date = CalendarDate::parse("2024-02-28").unwrap();
+        assert_eq!(date.add_days(1).to_string(), "2024-02-29");
+        assert_eq!(date.add_days(2).to_string(), "2024-03-01");
+        assert_eq!(date.add_days(-59).to_string(), "2023-12-31");
+        assert!(CalendarDate::parse("2023-02-29").is_none());
+    }
+
+    #[test]
+    fn test_duration_days() {
+        assert_eq!(duration_days("3d"), Some(3));
+        assert_eq!(duration_days("2w"), Some(14));
+        assert_eq!(duration_days("36h"), Some(2));
+        assert_eq!(duration_days("later"), None);
+    }
+}
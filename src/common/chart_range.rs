@@ -0,0 +1,221 @@
+//! Rescaling of chart data that has drifted out of range
+//!
+//! Quadrant points are plotted on a unit square, radar values against the
+//! configured `min`/`max` scale and XY values against the y-axis range, so a
+//! value outside its range is drawn off the chart or clipped. The validators
+//! in [`crate::common::validation`] report such values; the `normalize`
+//! methods here map the data back into range.
+//!
+//! Normalization is a linear rescale: the range spanned by the data and the
+//! target range together is mapped onto the target range, so values keep
+//! their relative positions. Data that is already in range is left untouched.
+//! Each method returns a [`NormalizationReport`] listing the rescale applied
+//! and every value it changed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::{parse_diagram, DiagramType};
+//!
+//! let input = "quadrantChart\n    Alpha: [0.5, 0.5]\n    Beta: [2.0, 0.25]";
+//! if let DiagramType::Quadrant(mut quadrant) = parse_diagram(input)? {
+//!     let report = quadrant.normalize();
+//!     assert!(report.changed());
+//!     assert_eq!(quadrant.points[0].x, 0.25);
+//!     assert_eq!(quadrant.points[1].x, 1.0);
+//!     assert_eq!(quadrant.points[1].y, 0.25);
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{QuadrantDiagram, RadarDiagram, XyChartDiagram};
+
+/// A linear rescale applied to one scale of a chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rescale {
+    /// `x` or `y` for quadrant and XY charts, `radial` for radar charts
+    pub scale: &'static str,
+    /// Range spanned by the data and the target range before rescaling
+    pub from: (f64, f64),
+    /// Target range
+    pub to: (f64, f64),
+}
+
+/// A value changed by normalization
+#[derive(Debug, Clone, PartialEq)]
+pub struct RescaledValue {
+    /// Point, dataset or series name
+    pub element: String,
+    /// Coordinate (`x` or `y`), radar axis or x-axis label of the value
+    pub field: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// Outcome of a `normalize` call
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NormalizationReport {
+    /// Rescales applied, one per scale that had values out of range
+    pub rescales: Vec<Rescale>,
+    /// Changed values, in document order
+    pub values: Vec<RescaledValue>,
+}
+
+impl NormalizationReport {
+    /// Whether the chart was changed
+    pub fn changed(&self) -> bool {
+        !self.rescales.is_empty()
+    }
+
+    fn record(&mut self, rescale: &Rescale, value: &mut f64, element: &str, field: &str) {
+        let after = rescale.apply(*value);
+        if after != *value {
+            self.values.push(RescaledValue {
+                element: element.to_string(),
+                field: field.to_string(),
+                before: *value,
+                after,
+            });
+            *value = after;
+        }
+    }
+}
+
+impl QuadrantDiagram {
+    /// Rescale point coordinates into `[0, 1]`, each axis independently
+    pub fn normalize(&mut self) -> NormalizationReport {
+        let mut report = NormalizationReport::default();
+        let x = rescale_for("x", self.points.iter().map(|point| point.x), (0.0, 1.0));
+        let y = rescale_for("y", self.points.iter().map(|point| point.y), (0.0, 1.0));
+
+        for point in &mut self.points {
+            if let Some(rescale) = &x {
+                report.record(rescale, &mut point.x, &point.name, "x");
+            }
+            if let Some(rescale) = &y {
+                report.record(rescale, &mut point.y, &point.name, "y");
+            }
+        }
+        report.rescales.extend(x.into_iter().chain(y));
+        report
+    }
+}
+
+impl RadarDiagram {
+    /// Rescale dataset values into the configured `min`/`max` scale
+    ///
+    /// All datasets share one rescale so they stay comparable. A scale whose
+    /// minimum is not below its maximum is left alone.
+    pub fn normalize(&mut self) -> NormalizationReport {
+        let mut report = NormalizationReport::default();
+        let target = (self.config.scale_min, self.config.scale_max);
+        let values = self.datasets.iter().flat_map(|d| d.values.iter().copied());
+        let Some(rescale) = rescale_for("radial", values, target) else {
+            return report;
+        };
+
+        for dataset in &mut self.datasets {
+            for (index, value) in dataset.values.iter_mut().enumerate() {
+                let axis = self
+                    .axes
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| (index + 1).to_string());
+                report.record(&rescale, value, &dataset.name, &axis);
+            }
+        }
+        report.rescales.push(rescale);
+        report
+    }
+}
+
+impl XyChartDiagram {
+    /// Rescale series values into the y-axis range
+    ///
+    /// Charts without an explicit y-axis range are scaled to fit their data
+    /// by Mermaid and are left alone; gaps stay gaps.
+    pub fn normalize(&mut self) -> NormalizationReport {
+        let mut report = NormalizationReport::default();
+        let Some((start, end)) = self.y_axis.range else {
+            return report;
+        };
+        let values = self
+            .data_series
+            .iter()
+            .flat_map(|series| series.data.iter().flatten().copied());
+        let Some(rescale) = rescale_for("y", values, (start.min(end), start.max(end))) else {
+            return report;
+        };
+
+        for (series_index, series) in self.data_series.iter_mut().enumerate() {
+            let name = series
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", series_index + 1));
+            for (index, value) in series.data.iter_mut().enumerate() {
+                let Some(value) = value else {
+                    continue;
+                };
+                let label = self
+                    .x_axis
+                    .labels
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_else(|| (index + 1).to_string());
+                report.record(&rescale, value, &name, &label);
+            }
+        }
+        report.rescales.push(rescale);
+        report
+    }
+}
+
+impl Rescale {
+    fn apply(&self, value: f64) -> f64 {
+        if !value.is_finite() {
+            return value;
+        }
+        let (from_low, from_high) = self.from;
+        let (to_low, to_high) = self.to;
+        to_low + (value - from_low) * (to_high - to_low) / (from_high - from_low)
+    }
+}
+
+/// The rescale that brings `values` into `target`, or `None` when they are
+/// already in range or the target is empty
+fn rescale_for(
+    scale: &'static str,
+    values: impl Iterator<Item = f64>,
+    target: (f64, f64),
+) -> Option<Rescale> {
+    if target.0 >= target.1 {
+        return None;
+    }
+    let (low, high) = values
+        .filter(|value| value.is_finite())
+        .fold(target, |(low, high), value| {
+            (low.min(value), high.max(value))
+        });
+    (low < target.0 || high > target.1).then_some(Rescale {
+        scale,
+        from: (low, high),
+        to: target,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescale_for() {
+        assert_eq!(rescale_for("x", [0.2, 0.8].into_iter(), (0.0, 1.0)), None);
+        assert_eq!(rescale_for("x", [2.0].into_iter(), (1.0, 1.0)), None);
+
+        let rescale = rescale_for("y", [-10.0, 50.0, 150.0].into_iter(), (0.0, 100.0)).unwrap();
+        assert_eq!(rescale.from, (-10.0, 150.0));
+        assert_eq!(rescale.apply(-10.0), 0.0);
+        assert_eq!(rescale.apply(150.0), 100.0);
+        assert_eq!(rescale.apply(70.0), 50.0);
+    }
+}
@@ -0,0 +1,253 @@
+//! Overview diagrams made by collapsing flowchart subgraphs
+//!
+//! [`collapse_subgraphs`] turns every subgraph nested `level` deep into a
+//! single subroutine node (`[[title]]`) standing for its contents. Subgraphs
+//! above that depth keep their structure. Edges between a collapsed subgraph
+//! and the rest of the diagram are redirected to its node, edges inside it
+//! are dropped, and redirected edges that end up joining the same two nodes
+//! are merged into one edge carrying their distinct labels.
+//!
+//! Styles and click handlers of collapsed nodes are dropped; a `style`
+//! statement for a collapsed subgraph applies to its node.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::ast::Subgraph;
+//! use mermaid_parser::common::collapse::collapse_subgraphs;
+//! use mermaid_parser::{parse_diagram, DiagramType};
+//!
+//! let input = "flowchart LR\n    Client --> Api\n    Api --> Db\n    Db --> Report";
+//! if let DiagramType::Flowchart(mut detailed) = parse_diagram(input)? {
+//!     detailed.subgraphs.push(Subgraph {
+//!         id: "backend".to_string(),
+//!         title: Some("Backend".to_string()),
+//!         nodes: vec!["Api".to_string(), "Db".to_string()],
+//!         edges: Vec::new(),
+//!         subgraphs: Vec::new(),
+//!         direction: None,
+//!     });
+//!
+//!     let overview = collapse_subgraphs(&detailed, 0);
+//!     assert!(overview.subgraphs.is_empty());
+//!     assert!(overview.nodes.contains_key("backend"));
+//!     assert!(!overview.nodes.contains_key("Api"));
+//!
+//!     let edges: Vec<(&str, &str)> = overview
+//!         .edges
+//!         .iter()
+//!         .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+//!         .collect();
+//!     assert_eq!(edges, [("Client", "backend"), ("backend", "Report")]);
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{FlowEdge, FlowNode, FlowchartDiagram, NodeShape, StyleTarget, Subgraph};
+use crate::common::ordered_map::OrderedMap;
+use std::collections::HashMap;
+
+/// Subgraphs collapsed so far: the node standing for each, and the subgraph
+/// each of their member nodes now belongs to
+#[derive(Default)]
+struct Collapsed {
+    nodes: Vec<FlowNode>,
+    owners: HashMap<String, String>,
+}
+
+impl Collapsed {
+    fn owner<'a>(&'a self, id: &'a str) -> &'a str {
+        self.owners.get(id).map_or(id, String::as_str)
+    }
+}
+
+/// A copy of `diagram` in which each subgraph nested `level` deep (`0` for
+/// top-level subgraphs) is replaced by a single node with the subgraph's id
+pub fn collapse_subgraphs(diagram: &FlowchartDiagram, level: usize) -> FlowchartDiagram {
+    let mut collapsed = Collapsed::default();
+    let mut edges = diagram.edges.clone();
+    let mut subgraphs = collapse_level(
+        &diagram.subgraphs,
+        level,
+        &mut edges,
+        &mut Vec::new(),
+        &mut collapsed,
+    );
+    if collapsed.nodes.is_empty() {
+        return diagram.clone();
+    }
+    finish_subgraphs(&mut subgraphs, &collapsed);
+
+    let mut nodes: OrderedMap<String, FlowNode> = diagram
+        .nodes
+        .iter()
+        .filter(|(id, _)| !collapsed.owners.contains_key(id.as_str()))
+        .map(|(id, node)| (id.clone(), node.clone()))
+        .collect();
+    for node in &collapsed.nodes {
+        nodes.insert(node.id.clone(), node.clone());
+    }
+
+    let styles = diagram
+        .styles
+        .iter()
+        .filter_map(|style| {
+            let target = match &style.target {
+                StyleTarget::Node(id) if collapsed.owners.contains_key(id) => return None,
+                StyleTarget::Subgraph(id) if collapsed.nodes.iter().any(|node| node.id == *id) => {
+                    StyleTarget::Node(id.clone())
+                }
+                StyleTarget::Edge(from, to)
+                    if collapsed.owners.contains_key(from) || collapsed.owners.contains_key(to) =>
+                {
+                    return None
+                }
+                target => target.clone(),
+            };
+            let mut style = style.clone();
+            style.target = target;
+            Some(style)
+        })
+        .collect();
+
+    FlowchartDiagram {
+        title: diagram.title.clone(),
+        accessibility: diagram.accessibility.clone(),
+        direction: diagram.direction.clone(),
+        nodes,
+        edges: aggregate_edges(edges, &collapsed),
+        subgraphs,
+        styles,
+        class_defs: diagram.class_defs.clone(),
+        clicks: diagram
+            .clicks
+            .iter()
+            .filter(|click| !collapsed.owners.contains_key(&click.node_id))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Collapse the subgraphs `level` below `subgraphs`, moving the edges of
+/// collapsed ones to `edges` and their nodes to `nodes`, the lists of the
+/// enclosing subgraph or diagram. Returns the subgraphs that are kept.
+fn collapse_level(
+    subgraphs: &[Subgraph],
+    level: usize,
+    edges: &mut Vec<FlowEdge>,
+    nodes: &mut Vec<String>,
+    collapsed: &mut Collapsed,
+) -> Vec<Subgraph> {
+    let mut kept = Vec::new();
+    for subgraph in subgraphs {
+        if level == 0 {
+            collapse_into(subgraph, &subgraph.id, edges, collapsed);
+            collapsed.nodes.push(FlowNode {
+                id: subgraph.id.clone(),
+                text: Some(
+                    subgraph
+                        .title
+                        .clone()
+                        .unwrap_or_else(|| subgraph.id.clone()),
+                ),
+                shape: NodeShape::Subroutine,
+                classes: Vec::new(),
+                icon: None,
+            });
+            nodes.push(subgraph.id.clone());
+        } else {
+            let mut subgraph = subgraph.clone();
+            let children = std::mem::take(&mut subgraph.subgraphs);
+            subgraph.subgraphs = collapse_level(
+                &children,
+                level - 1,
+                &mut subgraph.edges,
+                &mut subgraph.nodes,
+                collapsed,
+            );
+            kept.push(subgraph);
+        }
+    }
+    kept
+}
+
+/// Record the nodes of `subgraph` and its descendants as members of `owner`
+/// and move their edges to `edges`
+fn collapse_into(
+    subgraph: &Subgraph,
+    owner: &str,
+    edges: &mut Vec<FlowEdge>,
+    collapsed: &mut Collapsed,
+) {
+    for node in &subgraph.nodes {
+        collapsed.owners.insert(node.clone(), owner.to_string());
+    }
+    edges.extend(subgraph.edges.iter().cloned());
+    for child in &subgraph.subgraphs {
+        collapse_into(child, owner, edges, collapsed);
+    }
+}
+
+/// Redirect the edges and node lists of the kept subgraphs to collapsed nodes
+fn finish_subgraphs(subgraphs: &mut [Subgraph], collapsed: &Collapsed) {
+    for subgraph in subgraphs {
+        let mut nodes: Vec<String> = Vec::new();
+        for node in &subgraph.nodes {
+            let node = collapsed.owner(node);
+            if !nodes.iter().any(|kept| kept == node) {
+                nodes.push(node.to_string());
+            }
+        }
+        subgraph.nodes = nodes;
+        subgraph.edges = aggregate_edges(std::mem::take(&mut subgraph.edges), collapsed);
+        finish_subgraphs(&mut subgraph.subgraphs, collapsed);
+    }
+}
+
+/// Redirect `edges` to collapsed nodes, dropping those inside a collapsed
+/// subgraph and merging redirected edges between the same two nodes. Edges
+/// between nodes that were not collapsed are kept as they are.
+fn aggregate_edges(edges: Vec<FlowEdge>, collapsed: &Collapsed) -> Vec<FlowEdge> {
+    let mut result: Vec<FlowEdge> = Vec::new();
+    // Labels of each merged edge, `None` for edges that were not redirected
+    let mut labels: Vec<Option<Vec<String>>> = Vec::new();
+    for mut edge in edges {
+        let (from, to) = (collapsed.owner(&edge.from), collapsed.owner(&edge.to));
+        if from == edge.from && to == edge.to {
+            result.push(edge);
+            labels.push(None);
+            continue;
+        }
+        if from == to {
+            continue;
+        }
+        let (from, to) = (from.to_string(), to.to_string());
+        let existing = result
+            .iter()
+            .zip(&labels)
+            .position(|(kept, merged)| merged.is_some() && kept.from == from && kept.to == to);
+        match existing {
+            Some(index) => {
+                let merged = labels[index].get_or_insert_with(Vec::new);
+                if let Some(label) = edge.label {
+                    if !merged.contains(&label) {
+                        merged.push(label);
+                    }
+                }
+            }
+            None => {
+                labels.push(Some(edge.label.take().into_iter().collect()));
+                edge.from = from;
+                edge.to = to;
+                result.push(edge);
+            }
+        }
+    }
+
+    for (edge, labels) in result.iter_mut().zip(labels) {
+        if let Some(labels) = labels {
+            edge.label = (!labels.is_empty()).then(|| labels.join(", "));
+        }
+    }
+    result
+}
@@ -89,6 +89,7 @@ pub mod diagram_headers {
     pub const ER_DIAGRAM: &str = "erdiagram";
     pub const ER_DIAGRAM_TITLE: &str = "erdiagramtitletext";
     pub const CLASS_DIAGRAM: &str = "classdiagram";
+    pub const CLASS_DIAGRAM_V2: &str = "classdiagram-v2";
     pub const GIT_GRAPH: &str = "gitgraph";
     pub const INFO: &str = "info";
 }
@@ -109,10 +110,19 @@ pub mod sequence_keywords {
     pub const LOOP: &str = "loop ";
     pub const ALT: &str = "alt ";
     pub const OPT: &str = "opt ";
+    pub const BREAK: &str = "break ";
+    pub const RECT: &str = "rect ";
     pub const NOTE: &str = "note ";
     pub const ACTIVATE: &str = "activate ";
     pub const DEACTIVATE: &str = "deactivate ";
     pub const AUTONUMBER: &str = "autonumber";
+    pub const BOX: &str = "box";
+
+    /// Words that cannot be used as participant ids
+    pub const RESERVED_IDS: &[&str] = &[
+        "end", "loop", "alt", "else", "opt", "par", "and", "critical", "break", "rect", "box",
+        "note",
+    ];
 }
 
 /// Flowchart diagram specific keywords
@@ -121,16 +131,57 @@ pub mod flowchart_keywords {
     pub const GRAPH: &str = "graph";
     pub const SUBGRAPH: &str = "subgraph";
     pub const END: &str = "end";
+    pub const CLASS: &str = "class";
+    pub const CLASS_DEF: &str = "classDef";
+    pub const STYLE: &str = "style";
+
+    /// Separator between a node and the class assigned to it: `A:::name`
+    pub const CLASS_SEPARATOR: &str = ":::";
+    pub const METADATA_START: &str = "@{";
+
+    /// Words that cannot be used as node ids
+    pub const RESERVED_IDS: &[&str] = &[
+        END,
+        SUBGRAPH,
+        GRAPH,
+        FLOWCHART,
+        STYLE,
+        "linkStyle",
+        CLASS_DEF,
+        CLASS,
+        "click",
+    ];
 
     // Edge patterns
     pub const DOUBLE_DASH: &str = "--";
 }
 
+/// Class diagram specific keywords
+pub mod class_keywords {
+    pub const STYLE: &str = "style";
+    pub const CSS_CLASS: &str = "cssClass";
+    pub const LINK: &str = "link";
+    pub const CLICK: &str = "click";
+    pub const CALL: &str = "call";
+    pub const HREF: &str = "href";
+}
+
+/// Gantt chart specific keywords
+pub mod gantt_keywords {
+    /// Values Mermaid accepts for `displayMode`
+    pub const DISPLAY_MODES: &[&str] = &["compact"];
+}
+
 /// State diagram specific keywords
 pub mod state_keywords {
     pub const STATE: &str = "state ";
     pub const DIRECTION: &str = "direction ";
     pub const NOTE: &str = "note";
+    pub const CLASS_DEF: &str = "classDef ";
+    pub const CLASS: &str = "class ";
+
+    /// Separator between a state and the class assigned to it: `A:::name`
+    pub const CLASS_SEPARATOR: &str = ":::";
 
     // State types
     pub const CHOICE: &str = "choice";
@@ -164,6 +215,9 @@ pub mod arrows {
     pub const DOTTED_ARROW_LEFT: &str = "<-.";
     pub const DOTTED_ARROW_BIDIRECTIONAL: &str = "<-.>";
 
+    // Links without an arrow head or line, used for alignment
+    pub const INVISIBLE_LINK: &str = "~~~";
+
     // Special sequence diagram arrows
     pub const SEQUENCE_SYNC: &str = "->>";
     pub const SEQUENCE_ASYNC: &str = "->";
@@ -198,6 +252,163 @@ pub mod directions {
     pub const ALL_DIRECTIONS: &[&str] = &[TOP_DOWN, TOP_BOTTOM, BOTTOM_TOP, LEFT_RIGHT, RIGHT_LEFT];
 }
 
+/// CSS colors
+pub mod colors {
+    pub const TRANSPARENT: &str = "transparent";
+
+    /// The named colors of CSS Color Module Level 4, lowercase
+    pub const NAMED_COLORS: &[&str] = &[
+        "aliceblue",
+        "antiquewhite",
+        "aqua",
+        "aquamarine",
+        "azure",
+        "beige",
+        "bisque",
+        "black",
+        "blanchedalmond",
+        "blue",
+        "blueviolet",
+        "brown",
+        "burlywood",
+        "cadetblue",
+        "chartreuse",
+        "chocolate",
+        "coral",
+        "cornflowerblue",
+        "cornsilk",
+        "crimson",
+        "cyan",
+        "darkblue",
+        "darkcyan",
+        "darkgoldenrod",
+        "darkgray",
+        "darkgreen",
+        "darkgrey",
+        "darkkhaki",
+        "darkmagenta",
+        "darkolivegreen",
+        "darkorange",
+        "darkorchid",
+        "darkred",
+        "darksalmon",
+        "darkseagreen",
+        "darkslateblue",
+        "darkslategray",
+        "darkslategrey",
+        "darkturquoise",
+        "darkviolet",
+        "deeppink",
+        "deepskyblue",
+        "dimgray",
+        "dimgrey",
+        "dodgerblue",
+        "firebrick",
+        "floralwhite",
+        "forestgreen",
+        "fuchsia",
+        "gainsboro",
+        "ghostwhite",
+        "gold",
+        "goldenrod",
+        "gray",
+        "green",
+        "greenyellow",
+        "grey",
+        "honeydew",
+        "hotpink",
+        "indianred",
+        "indigo",
+        "ivory",
+        "khaki",
+        "lavender",
+        "lavenderblush",
+        "lawngreen",
+        "lemonchiffon",
+        "lightblue",
+        "lightcoral",
+        "lightcyan",
+        "lightgoldenrodyellow",
+        "lightgray",
+        "lightgreen",
+        "lightgrey",
+        "lightpink",
+        "lightsalmon",
+        "lightseagreen",
+        "lightskyblue",
+        "lightslategray",
+        "lightslategrey",
+        "lightsteelblue",
+        "lightyellow",
+        "lime",
+        "limegreen",
+        "linen",
+        "magenta",
+        "maroon",
+        "mediumaquamarine",
+        "mediumblue",
+        "mediumorchid",
+        "mediumpurple",
+        "mediumseagreen",
+        "mediumslateblue",
+        "mediumspringgreen",
+        "mediumturquoise",
+        "mediumvioletred",
+        "midnightblue",
+        "mintcream",
+        "mistyrose",
+        "moccasin",
+        "navajowhite",
+        "navy",
+        "oldlace",
+        "olive",
+        "olivedrab",
+        "orange",
+        "orangered",
+        "orchid",
+        "palegoldenrod",
+        "palegreen",
+        "paleturquoise",
+        "palevioletred",
+        "papayawhip",
+        "peachpuff",
+        "peru",
+        "pink",
+        "plum",
+        "powderblue",
+        "purple",
+        "rebeccapurple",
+        "red",
+        "rosybrown",
+        "royalblue",
+        "saddlebrown",
+        "salmon",
+        "sandybrown",
+        "seagreen",
+        "seashell",
+        "sienna",
+        "silver",
+        "skyblue",
+        "slateblue",
+        "slategray",
+        "slategrey",
+        "snow",
+        "springgreen",
+        "steelblue",
+        "tan",
+        "teal",
+        "thistle",
+        "tomato",
+        "turquoise",
+        "violet",
+        "wheat",
+        "white",
+        "whitesmoke",
+        "yellow",
+        "yellowgreen",
+    ];
+}
+
 /// Common test patterns
 pub mod test_patterns {
     pub const FLOWCHART_TD: &str = "flowchart TD";
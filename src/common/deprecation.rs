@@ -0,0 +1,197 @@
+//! Detection and upgrade of legacy Mermaid syntax
+//!
+//! Older documentation often uses syntax that Mermaid still accepts but no
+//! longer documents: the `graph` header instead of `flowchart`, version 1
+//! state diagrams (`stateDiagram`), and the original `gitGraph:` syntax with
+//! its colon header and `options ... end` block.
+//!
+//! [`detect_legacy_syntax`] reports these forms as validation diagnostics
+//! (they are only visible in the source text), and [`modernize`] upgrades a
+//! parsed diagram so that printing it produces the modern equivalent.
+//! [`modernize_source`] combines parsing, upgrading and printing so large
+//! documentation sets can be converted mechanically.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::deprecation::{detect_legacy_syntax, modernize_source};
+//!
+//! let input = "stateDiagram\n    [*] --> Idle";
+//! assert_eq!(detect_legacy_syntax(input)[0].rule, "deprecated_state_diagram_v1");
+//! assert!(modernize_source(input)?.starts_with("stateDiagram-v2"));
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{DiagramType, GitDiagram, MiscContent, StateDiagram, StateVersion};
+use crate::common::parsing::lines;
+use crate::common::pretty_print::MermaidPrinter;
+use crate::common::validation::{Location, Severity, ValidationError};
+use crate::error::Result;
+
+/// Rules reported by [`detect_legacy_syntax`]
+pub const DEPRECATION_RULES: &[&str] = &[
+    "deprecated_graph_header",
+    "deprecated_state_diagram_v1",
+    "deprecated_git_graph_syntax",
+];
+
+/// Report legacy syntax in Mermaid source text
+pub fn detect_legacy_syntax(input: &str) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    let Some((header_index, header)) = input
+        .lines()
+        .enumerate()
+        .find(|(_, line)| !lines::should_skip_line(line))
+    else {
+        return errors;
+    };
+    let header = header.trim();
+    let keyword = header.split_whitespace().next().unwrap_or_default();
+    let location = |line_index: usize| Location::new(line_index + 1, 1);
+
+    match keyword {
+        "graph" => errors.push(ValidationError::with_location(
+            "deprecated_graph_header",
+            "`graph` is a legacy alias; use `flowchart`".to_string(),
+            Severity::Warning,
+            location(header_index),
+        )),
+        "stateDiagram" => errors.push(ValidationError::with_location(
+            "deprecated_state_diagram_v1",
+            "`stateDiagram` is the legacy renderer; use `stateDiagram-v2`".to_string(),
+            Severity::Warning,
+            location(header_index),
+        )),
+        "gitGraph:" => errors.push(ValidationError::with_location(
+            "deprecated_git_graph_syntax",
+            "`gitGraph:` is the legacy git graph syntax; use `gitGraph`".to_string(),
+            Severity::Warning,
+            location(header_index),
+        )),
+        "gitGraph" => {
+            if let Some(options_index) = input
+                .lines()
+                .enumerate()
+                .skip(header_index + 1)
+                .find(|(_, line)| line.trim() == "options")
+                .map(|(index, _)| index)
+            {
+                errors.push(ValidationError::with_location(
+                    "deprecated_git_graph_syntax",
+                    "`options ... end` blocks are legacy git graph syntax; use frontmatter config"
+                        .to_string(),
+                    Severity::Warning,
+                    location(options_index),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    errors
+}
+
+/// Upgrade a version 1 state diagram to version 2. Returns `false` if it
+/// already was version 2.
+pub fn upgrade_state_diagram(diagram: &mut StateDiagram) -> bool {
+    if diagram.version == StateVersion::V2 {
+        return false;
+    }
+    diagram.version = StateVersion::V2;
+    true
+}
+
+/// Convert a legacy git graph (parsed as a miscellaneous diagram) into a
+/// [`GitDiagram`], dropping its `options` block. Returns `None` for any other
+/// diagram or if the commands cannot be expressed in the modern syntax.
+pub fn upgrade_git_graph(diagram: &DiagramType) -> Option<GitDiagram> {
+    let DiagramType::Misc(misc) = diagram else {
+        return None;
+    };
+    let MiscContent::GitGraph(git) = &misc.content else {
+        return None;
+    };
+
+    let mut source = String::from("gitGraph\n");
+    for command in &git.commits {
+        source.push_str(&command.action);
+        let mut params = command.params.iter();
+        while let Some(param) = params.next() {
+            match param.as_str() {
+                "id" | "tag" | "parent" => {
+                    source.push_str(&format!(" {}: \"{}\"", param, params.next()?));
+                }
+                "type" | "order" => source.push_str(&format!(" {}: {}", param, params.next()?)),
+                _ => source.push_str(&format!(" {}", param)),
+            }
+        }
+        source.push('\n');
+    }
+
+    crate::parsers::git::parse(&source).ok()
+}
+
+/// Upgrade legacy constructs in a parsed diagram in place. Returns `true` if
+/// anything changed.
+///
+/// Flowcharts need no upgrade: they are always printed with the `flowchart`
+/// header, whichever header they were parsed from.
+pub fn modernize(diagram: &mut DiagramType) -> bool {
+    match diagram {
+        DiagramType::State(state) => upgrade_state_diagram(state),
+        DiagramType::Misc(_) => match upgrade_git_graph(diagram) {
+            Some(git) => {
+                *diagram = DiagramType::Git(git);
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Parse `input`, upgrade legacy syntax and print the modern equivalent
+pub fn modernize_source(input: &str) -> Result<String> {
+    let mut diagram = crate::parse_diagram(input)?;
+    modernize(&mut diagram);
+    Ok(diagram.to_mermaid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_legacy_headers() {
+        let rules = |input: &str| {
+            detect_legacy_syntax(input)
+                .into_iter()
+                .map(|error| error.rule)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            rules("graph TD\n    A --> B"),
+            vec!["deprecated_graph_header"]
+        );
+        assert_eq!(
+            rules("%% comment\nstateDiagram\n    [*] --> A"),
+            vec!["deprecated_state_diagram_v1"]
+        );
+        assert_eq!(
+            rules("gitGraph:\n    commit"),
+            vec!["deprecated_git_graph_syntax"]
+        );
+        assert!(rules("flowchart TD\n    A --> B").is_empty());
+        assert!(rules("stateDiagram-v2\n    [*] --> A").is_empty());
+        assert!(rules("gitGraph\n    commit").is_empty());
+    }
+
+    #[test]
+    fn test_options_block_location() {
+        let errors = detect_legacy_syntax("gitGraph\noptions\n{}\nend\ncommit");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location.as_ref().unwrap().line, 2);
+    }
+}
@@ -0,0 +1,164 @@
+//! Detection of conflicting layout direction statements
+//!
+//! Flowcharts take a direction from their header and from `direction`
+//! statements inside subgraphs; state diagrams from `direction` statements at
+//! the top level and inside composite states. Within one scope only the last
+//! statement takes effect, so a second statement that disagrees with the
+//! first (`TD` and `TB` agree) is almost always a mistake.
+//!
+//! Only the final direction of each diagram is kept in the AST (see
+//! [`DiagramType::layout_direction`]), so [`detect_direction_conflicts`]
+//! works on the source text, like
+//! [`crate::common::deprecation::detect_legacy_syntax`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::direction::detect_direction_conflicts;
+//!
+//! let input = "stateDiagram-v2\n    direction LR\n    direction TB\n    [*] --> A";
+//! let warnings = detect_direction_conflicts(input);
+//! assert_eq!(warnings[0].rule, "conflicting_direction");
+//! assert_eq!(warnings[0].location.as_ref().unwrap().line, 3);
+//! ```
+//!
+//! [`DiagramType::layout_direction`]: crate::common::ast::DiagramType::layout_direction
+
+use crate::common::ast::FlowDirection;
+use crate::common::constants::{diagram_headers, flowchart_keywords, state_keywords};
+use crate::common::parsing::lines;
+use crate::common::validation::{Location, Severity, ValidationError};
+
+/// Rule reported by [`detect_direction_conflicts`]
+pub const CONFLICTING_DIRECTION: &str = "conflicting_direction";
+
+/// The direction set in a scope and the (1-based) line that set it
+type ScopeDirection = Option<(FlowDirection, usize)>;
+
+/// Report direction statements that contradict an earlier one in the same
+/// flowchart subgraph or state scope
+pub fn detect_direction_conflicts(input: &str) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut statements = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !lines::should_skip_line(line))
+        .map(|(index, line)| (index + 1, line.trim()));
+
+    let Some((header_line, header)) = statements.next() else {
+        return errors;
+    };
+    let mut words = header.split_whitespace();
+    let keyword = words.next().unwrap_or_default();
+
+    let is_flowchart = diagram_headers::FLOW_HEADERS.contains(&keyword);
+    if !is_flowchart && !diagram_headers::STATE_HEADERS.contains(&keyword) {
+        return errors;
+    }
+
+    let root = words
+        .next()
+        .and_then(FlowDirection::from_keyword)
+        .map(|direction| (direction, header_line));
+    let mut scopes: Vec<ScopeDirection> = vec![root];
+
+    for (line, statement) in statements {
+        let opens_scope = if is_flowchart {
+            statement.starts_with(flowchart_keywords::SUBGRAPH)
+        } else {
+            statement.ends_with('{')
+        };
+        let closes_scope = if is_flowchart {
+            statement == flowchart_keywords::END
+        } else {
+            statement.starts_with('}')
+        };
+
+        if opens_scope {
+            scopes.push(None);
+        } else if closes_scope {
+            if scopes.len() > 1 {
+                scopes.pop();
+            }
+        } else if let Some(direction) = statement
+            .strip_prefix(state_keywords::DIRECTION)
+            .and_then(|keyword| FlowDirection::from_keyword(keyword.trim()))
+        {
+            let scope = scopes.last_mut().expect("root scope is never popped");
+            if let Some((previous, previous_line)) = scope {
+                if previous.layout() != direction.layout() {
+                    errors.push(ValidationError::with_location(
+                        CONFLICTING_DIRECTION,
+                        format!(
+                            "direction {} conflicts with {} set on line {}",
+                            direction.keyword(),
+                            previous.keyword(),
+                            previous_line
+                        ),
+                        Severity::Warning,
+                        Location::new(line, 1),
+                    ));
+                }
+            }
+            *scope = Some((direction, line));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict_lines(input: &str) -> Vec<usize> {
+        detect_direction_conflicts(input)
+            .iter()
+            .map(|error| error.location.as_ref().unwrap().line)
+            .collect()
+    }
+
+    #[test]
+    fn test_td_and_tb_do_not_conflict() {
+        assert!(conflict_lines("flowchart TD\n    direction TB\n    A --> B").is_empty());
+    }
+
+    #[test]
+    fn test_flowchart_header_conflict() {
+        assert_eq!(
+            conflict_lines("flowchart LR\n    A --> B\n    direction BT"),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn test_subgraph_directions_are_scoped() {
+        let input = "flowchart LR
+    subgraph one
+        direction TB
+        A --> B
+    end
+    subgraph two
+        direction RL
+        direction LR
+    end";
+        assert_eq!(conflict_lines(input), vec![8]);
+    }
+
+    #[test]
+    fn test_composite_state_directions_are_scoped() {
+        let input = "stateDiagram-v2
+    direction LR
+    state Busy {
+        direction TB
+        [*] --> Working
+    }
+    direction RL";
+        assert_eq!(conflict_lines(input), vec![7]);
+    }
+
+    #[test]
+    fn test_other_diagrams_are_ignored() {
+        assert!(conflict_lines("pie\n    direction LR\n    direction TB").is_empty());
+    }
+}
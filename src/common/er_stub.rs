@@ -0,0 +1,309 @@
+//! Generation of ER diagram skeletons from persistence classes
+//!
+//! [`ClassDiagram::to_er_stub`] turns the classes marked as persistent, by
+//! default with `<<entity>>`, into an [`ErDiagram`] to start a data model
+//! from a domain model:
+//!
+//! - each marked class becomes an entity with the same name;
+//! - each non-static property becomes an attribute. A property named `id` is
+//!   the primary key, and a property named after another entity followed by
+//!   `Id` or `_id` (`customerId`) is a foreign key. Properties without a type
+//!   get [`ErStubOptions::default_attribute_type`], and characters ER types
+//!   do not allow, such as the `~` of generics, are replaced by `_`;
+//! - each association, link, aggregation or composition between two marked
+//!   classes becomes a relationship. Inheritance, realization, dependencies
+//!   and dashed links do not describe stored references and are skipped.
+//!
+//! Class cardinalities (`1`, `0..1`, `*`, `1..*`, `0..n`, `many`) map to the
+//! closest ER cardinality. A missing cardinality is read as zero or more,
+//! except on the owning side of a composition, which is exactly one.
+//! Relationships without a label are labelled [`DEFAULT_RELATIONSHIP_LABEL`],
+//! as ER relationships need one.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::ast::{
+//!     AccessibilityInfo, Class, ClassDiagram, ClassMember, Property, Stereotype, Visibility,
+//! };
+//!
+//! let property = |name: &str, prop_type: &str| {
+//!     ClassMember::Property(Property {
+//!         name: name.to_string(),
+//!         prop_type: Some(prop_type.to_string()),
+//!         visibility: Visibility::Public,
+//!         is_static: false,
+//!         default_value: None,
+//!         generics: vec![],
+//!     })
+//! };
+//! let order = Class {
+//!     name: "Order".to_string(),
+//!     stereotype: Some(Stereotype::Custom("entity".to_string())),
+//!     members: vec![property("id", "int"), property("total", "decimal")],
+//!     annotations: vec![],
+//!     css_class: None,
+//!     generics: vec![],
+//! };
+//! let diagram = ClassDiagram {
+//!     title: None,
+//!     accessibility: AccessibilityInfo::default(),
+//!     direction: None,
+//!     classes: [("Order".to_string(), order)].into_iter().collect(),
+//!     relationships: vec![],
+//!     notes: vec![],
+//!     namespaces: Default::default(),
+//!     styles: vec![],
+//!     clicks: vec![],
+//! };
+//!
+//! let er = diagram.to_er_stub();
+//! let attributes = &er.entities["Order"].attributes;
+//! assert_eq!(attributes[0].name, "id");
+//! assert!(attributes[0].key_type.is_some());
+//! assert_eq!(attributes[1].attr_type, "decimal");
+//! ```
+
+use crate::common::ast::{
+    Attribute, CardinalityValue, Class, ClassDiagram, ClassMember, ClassRelationship,
+    ClassRelationshipType, Entity, ErCardinality, ErDiagram, ErRelationship, KeyType, Stereotype,
+};
+
+/// Label of generated relationships whose class relationship has none
+pub const DEFAULT_RELATIONSHIP_LABEL: &str = "has";
+
+/// Options for [`ClassDiagram::to_er_stub_with`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErStubOptions {
+    /// Stereotype, without `<<` and `>>`, that marks persistence classes.
+    /// Compared case-insensitively against the class stereotype and its
+    /// annotations. Defaults to `entity`.
+    pub stereotype: String,
+    /// Attribute type for properties declared without one. Defaults to
+    /// `string`.
+    pub default_attribute_type: String,
+}
+
+impl Default for ErStubOptions {
+    fn default() -> Self {
+        Self {
+            stereotype: "entity".to_string(),
+            default_attribute_type: "string".to_string(),
+        }
+    }
+}
+
+impl ClassDiagram {
+    /// An ER diagram with an entity for every `<<entity>>` class; see
+    /// [`ClassDiagram::to_er_stub_with`]
+    pub fn to_er_stub(&self) -> ErDiagram {
+        self.to_er_stub_with(&ErStubOptions::default())
+    }
+
+    /// An ER diagram with an entity for every class marked with
+    /// `options.stereotype` and a relationship for every association
+    /// between them
+    pub fn to_er_stub_with(&self, options: &ErStubOptions) -> ErDiagram {
+        let persistent: Vec<&Class> = self
+            .classes
+            .values()
+            .filter(|class| is_marked(class, &options.stereotype))
+            .collect();
+        let names: Vec<&str> = persistent.iter().map(|class| class.name.as_str()).collect();
+
+        ErDiagram {
+            title: self.title.clone(),
+            accessibility: self.accessibility.clone(),
+            entities: persistent
+                .iter()
+                .map(|class| {
+                    let entity = Entity {
+                        name: class.name.clone(),
+                        attributes: attributes(class, &names, options),
+                        css_class: None,
+                        display_name: None,
+                    };
+                    (class.name.clone(), entity)
+                })
+                .collect(),
+            relationships: self
+                .relationships
+                .iter()
+                .filter(|relationship| {
+                    names.contains(&relationship.from.as_str())
+                        && names.contains(&relationship.to.as_str())
+                })
+                .filter_map(er_relationship)
+                .collect(),
+            styles: Vec::new(),
+            class_defs: std::collections::HashMap::new(),
+        }
+    }
+}
+
+fn is_marked(class: &Class, stereotype: &str) -> bool {
+    class
+        .stereotype
+        .iter()
+        .map(Stereotype::name)
+        .chain(class.annotations.iter().map(String::as_str))
+        .any(|marker| marker.trim().eq_ignore_ascii_case(stereotype))
+}
+
+fn attributes(class: &Class, entities: &[&str], options: &ErStubOptions) -> Vec<Attribute> {
+    class
+        .members
+        .iter()
+        .filter_map(|member| match member {
+            ClassMember::Property(property) if !property.is_static => Some(property),
+            _ => None,
+        })
+        .map(|property| {
+            let name = property.name.trim();
+            let key_type = if name.eq_ignore_ascii_case("id") {
+                Some(KeyType::PK)
+            } else if entities.iter().any(|entity| references(name, entity)) {
+                Some(KeyType::FK)
+            } else {
+                None
+            };
+            Attribute {
+                name: name.to_string(),
+                attr_type: er_type(
+                    property
+                        .prop_type
+                        .as_deref()
+                        .unwrap_or(&options.default_attribute_type),
+                ),
+                key_type,
+                comment: None,
+            }
+        })
+        .collect()
+}
+
+/// Whether a property named `name` holds the id of `entity`:
+/// `customerId`, `customer_id`, ...
+fn references(name: &str, entity: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    ["id", "_id"].iter().any(|suffix| {
+        lower
+            .strip_suffix(*suffix)
+            .is_some_and(|prefix| !prefix.is_empty() && prefix.eq_ignore_ascii_case(entity))
+    })
+}
+
+/// A class type as an ER attribute type, which may only hold letters,
+/// digits, `_`, `-`, brackets and parentheses
+fn er_type(class_type: &str) -> String {
+    class_type
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "_-[]()".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn er_relationship(relationship: &ClassRelationship) -> Option<ErRelationship> {
+    let (left_default, right_default) = match relationship.relationship_type {
+        ClassRelationshipType::Composition => (exactly_one(), zero_or_more()),
+        ClassRelationshipType::Aggregation
+        | ClassRelationshipType::Association
+        | ClassRelationshipType::Link => (zero_or_more(), zero_or_more()),
+        ClassRelationshipType::Inheritance
+        | ClassRelationshipType::Realization
+        | ClassRelationshipType::Dependency
+        | ClassRelationshipType::DashedLink => return None,
+    };
+    let cardinality = |text: &Option<String>, default: ErCardinality| {
+        text.as_deref().and_then(er_cardinality).unwrap_or(default)
+    };
+
+    Some(ErRelationship {
+        left_entity: relationship.from.clone(),
+        right_entity: relationship.to.clone(),
+        left_cardinality: cardinality(&relationship.from_cardinality, left_default),
+        right_cardinality: cardinality(&relationship.to_cardinality, right_default),
+        label: Some(
+            relationship
+                .label
+                .clone()
+                .unwrap_or_else(|| DEFAULT_RELATIONSHIP_LABEL.to_string()),
+        ),
+    })
+}
+
+/// The ER cardinality closest to a class cardinality such as `1`, `0..1`
+/// or `1..*`
+fn er_cardinality(text: &str) -> Option<ErCardinality> {
+    let text = text.trim();
+    let (min, max) = text.split_once("..").unwrap_or((text, text));
+    let bound = |bound: &str| match bound.trim().to_ascii_lowercase().as_str() {
+        "0" => Some(CardinalityValue::Zero),
+        "1" => Some(CardinalityValue::One),
+        "*" | "n" | "many" => Some(CardinalityValue::Many),
+        number => number.parse::<u32>().ok().map(|_| CardinalityValue::Many),
+    };
+
+    let (min, max) = (bound(min)?, bound(max)?);
+    // A lone `*` or `many` allows none; a lower bound above one still
+    // requires at least one
+    let min = match (text.contains(".."), min) {
+        (false, CardinalityValue::Many) => CardinalityValue::Zero,
+        (true, CardinalityValue::Many) => CardinalityValue::One,
+        (_, min) => min,
+    };
+    let max = match max {
+        CardinalityValue::Zero => CardinalityValue::One,
+        max => max,
+    };
+    Some(ErCardinality { min, max })
+}
+
+fn exactly_one() -> ErCardinality {
+    ErCardinality {
+        min: CardinalityValue::One,
+        max: CardinalityValue::One,
+    }
+}
+
+fn zero_or_more() -> ErCardinality {
+    ErCardinality {
+        min: CardinalityValue::Zero,
+        max: CardinalityValue::Many,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_er_cardinality() {
+        use CardinalityValue::{Many, One, Zero};
+        let pair = |text: &str| er_cardinality(text).map(|c| (c.min, c.max));
+        assert_eq!(pair("1"), Some((One, One)));
+        assert_eq!(pair("0..1"), Some((Zero, One)));
+        assert_eq!(pair("*"), Some((Zero, Many)));
+        assert_eq!(pair("many"), Some((Zero, Many)));
+        assert_eq!(pair("1..*"), Some((One, Many)));
+        assert_eq!(pair("0..n"), Some((Zero, Many)));
+        assert_eq!(pair("2..5"), Some((One, Many)));
+        assert_eq!(pair("some"), None);
+    }
+
+    #[test]
+    fn test_references_and_er_type() {
+        assert!(references("customerId", "Customer"));
+        assert!(references("customer_id", "Customer"));
+        assert!(!references("id", "Customer"));
+        assert!(!references("customerName", "Customer"));
+        assert_eq!(er_type("List~Order~"), "List_Order_");
+        assert_eq!(er_type("int[]"), "int[]");
+    }
+}
@@ -0,0 +1,244 @@
+//! Escaping of text for the quoting contexts of Mermaid syntax
+//!
+//! Every [`crate::MermaidPrinter`] implementation writes user text through
+//! these functions, so a label containing a quote, pipe or bracket prints as
+//! Mermaid that parses back to the same label. Characters that cannot appear
+//! in a context are written as Mermaid entity codes (`#quot;` for `"`,
+//! `#124;` for `|`), which Mermaid renders as the original character, and
+//! line breaks as `<br>`. Entity codes already present in the text are passed
+//! through unchanged, as the parsers keep them verbatim.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::escape;
+//!
+//! assert_eq!(escape::quoted("Say \"hi\""), "\"Say #quot;hi#quot;\"");
+//! assert_eq!(escape::bracket_label("Plain text"), "Plain text");
+//! assert_eq!(escape::bracket_label("f(x)"), "\"f(x)\"");
+//! assert_eq!(escape::pipe_label("yes|no"), "|yes#124;no|");
+//! ```
+
+/// Character sequences that end or restructure a bracketed node label
+const BRACKET_RESERVED: &[&str] = &[
+    "--", "==", "-.", "~~", "|", "[", "]", "(", ")", "{", "}", ">", ":", ";", "\"",
+];
+
+/// Character sequences that end a sequence diagram participant name or
+/// start a message arrow
+const PARTICIPANT_RESERVED: &[&str] = &["--", "->", "-x", "-)", "<<", ":", ",", ";", "#", "\""];
+
+/// Replace each character of `specials` in `text` with its entity code and
+/// line breaks with `<br>`
+fn encode(text: &str, specials: &[(char, &str)]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => output.push_str("<br>"),
+            _ => match specials.iter().find(|(special, _)| *special == c) {
+                Some((_, entity)) => output.push_str(entity),
+                None => output.push(c),
+            },
+        }
+    }
+    output
+}
+
+/// A double-quoted string, such as a pie slice label or a C4 description
+pub fn quoted(text: &str) -> String {
+    format!("\"{}\"", encode(text, &[('"', "#quot;")]))
+}
+
+/// A double-quoted string for the lexers that read backslash escapes (see
+/// [`crate::common::lexer::quoted_string`]), such as ER comments and C4
+/// arguments
+///
+/// Quotes are written as `\"`. A backslash is doubled only where it would
+/// otherwise be read as an escape, so `C:\temp` prints as is.
+pub fn escaped_quoted(text: &str) -> String {
+    let mut output = String::with_capacity(text.len() + 2);
+    output.push('"');
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' if matches!(chars.peek(), None | Some('"') | Some('\\')) => {
+                output.push_str("\\\\")
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => output.push_str("<br>"),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Node text inside shape delimiters such as `[...]`, `(...)` or `{...}`
+///
+/// Text that could be read as syntax is quoted with [`quoted`]; other text
+/// is written as is.
+pub fn bracket_label(text: &str) -> String {
+    if BRACKET_RESERVED
+        .iter()
+        .any(|reserved| text.contains(reserved))
+        || text.starts_with(char::is_whitespace)
+        || text.ends_with(char::is_whitespace)
+    {
+        quoted(text)
+    } else {
+        encode(text, &[])
+    }
+}
+
+/// Text inside square brackets that cannot be quoted, such as an
+/// architecture service title; brackets are written as entity codes
+pub fn bracket_text(text: &str) -> String {
+    encode(text, &[('[', "#91;"), (']', "#93;")])
+}
+
+/// A sequence diagram participant name, quoted with [`quoted`] when it has
+/// whitespace or text that could be read as syntax
+pub fn participant(text: &str) -> String {
+    if text.is_empty()
+        || text.contains(char::is_whitespace)
+        || PARTICIPANT_RESERVED
+            .iter()
+            .any(|reserved| text.contains(reserved))
+    {
+        quoted(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// An edge label between pipes, including the pipes: `|label|`
+pub fn pipe_label(text: &str) -> String {
+    format!("|{}|", encode(text, &[('|', "#124;")]))
+}
+
+/// An ER attribute comment, which is always double-quoted
+pub fn attribute_comment(text: &str) -> String {
+    escaped_quoted(text)
+}
+
+/// A CSV field, quoted when it contains a separator, quote or line break;
+/// quotes inside are doubled as in RFC 4180
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A JSON string literal, for the reports and traces written as JSON
+pub fn json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted() {
+        assert_eq!(quoted(""), "\"\"");
+        assert_eq!(quoted("plain"), "\"plain\"");
+        assert_eq!(quoted("\"quoted\""), "\"#quot;quoted#quot;\"");
+        assert_eq!(quoted("two\nlines"), "\"two<br>lines\"");
+        assert_eq!(quoted("crlf\r\nline"), "\"crlf<br>line\"");
+        assert_eq!(quoted("a|b [c] {d}"), "\"a|b [c] {d}\"");
+        assert_eq!(quoted("#quot; kept"), "\"#quot; kept\"");
+        assert_eq!(quoted("emoji 🚀 ok"), "\"emoji 🚀 ok\"");
+    }
+
+    #[test]
+    fn test_bracket_label() {
+        assert_eq!(bracket_label(""), "");
+        assert_eq!(bracket_label("Start here"), "Start here");
+        assert_eq!(bracket_label("Ünïcödé"), "Ünïcödé");
+        for reserved in BRACKET_RESERVED {
+            let text = format!("a{}b", reserved);
+            assert!(
+                bracket_label(&text).starts_with('"'),
+                "{:?} should be quoted",
+                text
+            );
+        }
+        assert_eq!(bracket_label("say \"hi\""), "\"say #quot;hi#quot;\"");
+        assert_eq!(bracket_label(" padded "), "\" padded \"");
+        assert_eq!(bracket_label("line\nbreak"), "line<br>break");
+    }
+
+    #[test]
+    fn test_bracket_text() {
+        assert_eq!(bracket_text("Database"), "Database");
+        assert_eq!(bracket_text("list[0]"), "list#91;0#93;");
+        assert_eq!(bracket_text("\"as is\" (x)"), "\"as is\" (x)");
+    }
+
+    #[test]
+    fn test_participant() {
+        assert_eq!(participant("Alice"), "Alice");
+        assert_eq!(participant("web-server"), "web-server");
+        assert_eq!(participant("🚀"), "🚀");
+        assert_eq!(participant("Web Server 🚀"), "\"Web Server 🚀\"");
+        assert_eq!(participant("a->b"), "\"a->b\"");
+        assert_eq!(participant("host:80"), "\"host:80\"");
+        assert_eq!(participant(""), "\"\"");
+    }
+
+    #[test]
+    fn test_pipe_label() {
+        assert_eq!(pipe_label("yes"), "|yes|");
+        assert_eq!(pipe_label("a|b|c"), "|a#124;b#124;c|");
+        assert_eq!(pipe_label("\"quoted\""), "|\"quoted\"|");
+        assert_eq!(pipe_label("x\ny"), "|x<br>y|");
+    }
+
+    #[test]
+    fn test_attribute_comment() {
+        assert_eq!(attribute_comment("primary key"), "\"primary key\"");
+        assert_eq!(attribute_comment("the \"id\""), r#""the \"id\"""#);
+    }
+
+    #[test]
+    fn test_escaped_quoted() {
+        assert_eq!(escaped_quoted("plain"), "\"plain\"");
+        assert_eq!(escaped_quoted("len \"short\""), r#""len \"short\"""#);
+        assert_eq!(escaped_quoted(r"C:\temp"), r#""C:\temp""#);
+        assert_eq!(escaped_quoted(r"dir\"), r#""dir\\""#);
+        assert_eq!(escaped_quoted(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(escaped_quoted("two\nlines"), "\"two<br>lines\"");
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("multi\nline"), "\"multi\nline\"");
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+}
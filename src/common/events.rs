@@ -0,0 +1,499 @@
+//! Event-based parsing, for single-pass extraction without keeping an AST
+//!
+//! [`crate::parse_diagram_events`] reads a diagram and hands a
+//! [`ParseHandler`] one [`ParseEvent`] per title, section, node and edge,
+//! between [`ParseEvent::DiagramStart`] and [`ParseEvent::DiagramEnd`]. The
+//! line-oriented diagrams of [`crate::common::stream::STREAMED_TYPES`] are
+//! parsed a chunk at a time as in [`crate::common::stream`], and each chunk is
+//! dropped once its events are sent, so memory use does not grow with the
+//! input. The one exception is the set of sankey node ids already reported,
+//! which keeps a node from being reported once per chunk. Other diagram types
+//! are parsed into an AST first and then walked. [`emit_events`] walks an AST
+//! that is already in hand.
+//!
+//! What a node or edge stands for depends on the diagram type: the nodes and
+//! edges of a flowchart, the participants and messages of a sequence
+//! diagram, the tasks of a Gantt chart, the slices of a pie chart, and so on.
+//! Declared elements such as flowchart nodes are reported in the order they
+//! first appear. Sections are reported for Gantt charts, timelines, user journeys
+//! and kanban boards; the nodes after a [`ParseEvent::SectionStart`] belong to
+//! that section.
+//!
+//! When parsing fails, the events already sent are not withdrawn and
+//! [`ParseEvent::DiagramEnd`] is not sent.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::events::ParseEvent;
+//! use mermaid_parser::parse_diagram_events;
+//!
+//! let input = "gantt\n    section Design\n    Sketch :a1, 2024-01-01, 3d\n    section Build\n    Code :a2, after a1, 5d";
+//!
+//! let mut index = Vec::new();
+//! let mut section = String::new();
+//! parse_diagram_events(input.as_bytes(), &mut |event: ParseEvent<'_>| match event {
+//!     ParseEvent::SectionStart(name) => section = name.to_string(),
+//!     ParseEvent::Node { id, .. } => index.push(format!("{}/{}", section, id)),
+//!     _ => {}
+//! })?;
+//! assert_eq!(index, vec!["Design/a1", "Build/a2"]);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::*;
+use crate::common::visitor::AstVisitor;
+use crate::stable::DiagramKind;
+use std::collections::HashSet;
+
+/// Something found while parsing a diagram
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseEvent<'a> {
+    /// The diagram type, before any other event
+    DiagramStart(DiagramKind),
+    Title(&'a str),
+    /// A section, lane or column, which the nodes that follow belong to
+    SectionStart(&'a str),
+    Node {
+        id: &'a str,
+        label: Option<&'a str>,
+        /// A number attached to the node, such as a pie slice's value
+        value: Option<f64>,
+    },
+    Edge {
+        from: &'a str,
+        to: &'a str,
+        label: Option<&'a str>,
+        /// A number attached to the edge, such as a sankey link's flow
+        value: Option<f64>,
+    },
+    /// After every other event of a diagram parsed without error
+    DiagramEnd,
+}
+
+/// Receiver of the events of a diagram, in source order
+///
+/// Implemented for closures taking a [`ParseEvent`].
+pub trait ParseHandler {
+    fn event(&mut self, event: ParseEvent<'_>);
+}
+
+impl<F: FnMut(ParseEvent<'_>)> ParseHandler for F {
+    fn event(&mut self, event: ParseEvent<'_>) {
+        self(event)
+    }
+}
+
+/// Send the events of a parsed diagram to `handler`
+pub fn emit_events(diagram: &DiagramType, handler: &mut impl ParseHandler) {
+    let mut emitter = EventEmitter::new(handler);
+    emitter.start(diagram.into());
+    emitter.visit_diagram(diagram);
+    emitter.finish();
+}
+
+/// Turns diagrams, or chunks of one, into events
+pub(crate) struct EventEmitter<'h> {
+    handler: &'h mut dyn ParseHandler,
+    /// Whether the diagram being visited continues the last section of the
+    /// previous chunk, whose `SectionStart` was already sent
+    pub(crate) continues: bool,
+    /// Sankey node ids already reported by earlier chunks
+    seen: HashSet<String>,
+}
+
+impl<'h> EventEmitter<'h> {
+    pub(crate) fn new(handler: &'h mut dyn ParseHandler) -> Self {
+        EventEmitter {
+            handler,
+            continues: false,
+            seen: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn start(&mut self, kind: DiagramKind) {
+        self.handler.event(ParseEvent::DiagramStart(kind));
+    }
+
+    pub(crate) fn finish(&mut self) {
+        self.handler.event(ParseEvent::DiagramEnd);
+    }
+
+    fn title(&mut self, title: &Option<String>) {
+        if let Some(title) = title {
+            self.handler.event(ParseEvent::Title(title));
+        }
+    }
+
+    /// Report the start of the `index`th section of a chunk, unless it
+    /// continues a section reported before or has no name
+    fn section(&mut self, index: usize, name: &str) {
+        if !(index == 0 && self.continues) && !name.is_empty() {
+            self.handler.event(ParseEvent::SectionStart(name));
+        }
+    }
+
+    fn node(&mut self, id: &str, label: Option<&str>) {
+        self.handler.event(ParseEvent::Node {
+            id,
+            label,
+            value: None,
+        });
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        self.handler.event(ParseEvent::Edge {
+            from,
+            to,
+            label,
+            value: None,
+        });
+    }
+
+    fn sequence_statements(&mut self, statements: &[SequenceStatement]) {
+        for statement in statements {
+            match statement {
+                SequenceStatement::Message(message) => self.visit_sequence_message(message),
+                SequenceStatement::Loop(block) => self.sequence_statements(&block.statements),
+                SequenceStatement::Opt(block) => self.sequence_statements(&block.statements),
+                SequenceStatement::Break(block) => self.sequence_statements(&block.statements),
+                SequenceStatement::Alt(block) => {
+                    self.sequence_statements(&block.statements);
+                    if let Some(branch) = &block.else_branch {
+                        self.sequence_statements(&branch.statements);
+                    }
+                }
+                SequenceStatement::Par(block) => {
+                    for branch in &block.branches {
+                        self.sequence_statements(&branch.statements);
+                    }
+                }
+                SequenceStatement::Critical(block) => {
+                    self.sequence_statements(&block.statements);
+                    for option in &block.options {
+                        self.sequence_statements(&option.statements);
+                    }
+                }
+                SequenceStatement::Rect { statements, .. } => self.sequence_statements(statements),
+                SequenceStatement::Note(_)
+                | SequenceStatement::Activate(_)
+                | SequenceStatement::Deactivate(_)
+                | SequenceStatement::Create(_)
+                | SequenceStatement::Destroy(_) => {}
+            }
+        }
+    }
+
+    fn mindmap_node(&mut self, node: &MindmapNode) {
+        self.node(&node.id, Some(node.text.as_str()));
+        for child in &node.children {
+            self.mindmap_node(child);
+            self.edge(&node.id, &child.id, None);
+        }
+    }
+
+    fn treemap_node(&mut self, node: &TreemapNode) {
+        self.handler.event(ParseEvent::Node {
+            id: &node.name,
+            label: None,
+            value: node.value,
+        });
+        for child in &node.children {
+            self.treemap_node(child);
+            self.edge(&node.name, &child.name, None);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block {
+            Block::Simple { id, label, .. } => self.node(id, label.as_deref()),
+            Block::Composite { id, label, blocks } => {
+                self.node(id, label.as_deref());
+                for child in blocks {
+                    self.block(child);
+                }
+            }
+            Block::Space { .. } => {}
+        }
+    }
+}
+
+impl AstVisitor for EventEmitter<'_> {
+    type Result = ();
+
+    fn visit_sankey(&mut self, diagram: &SankeyDiagram) -> Self::Result {
+        for node in &diagram.nodes {
+            self.visit_sankey_node(node);
+        }
+        for link in &diagram.links {
+            self.visit_sankey_link(link);
+        }
+    }
+
+    fn visit_timeline(&mut self, diagram: &TimelineDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for (index, section) in diagram.sections.iter().enumerate() {
+            self.section(index, &section.name);
+            for item in &section.items {
+                match item {
+                    TimelineItem::Period(text) | TimelineItem::Event(text) => self.node(text, None),
+                }
+            }
+        }
+    }
+
+    fn visit_journey(&mut self, diagram: &JourneyDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for (index, section) in diagram.sections.iter().enumerate() {
+            self.section(index, &section.name);
+            for task in &section.tasks {
+                self.handler.event(ParseEvent::Node {
+                    id: &task.name,
+                    label: None,
+                    value: Some(f64::from(task.score)),
+                });
+            }
+        }
+    }
+
+    fn visit_sequence(&mut self, diagram: &SequenceDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for participant in &diagram.participants {
+            self.node(&participant.actor, participant.alias.as_deref());
+        }
+        self.sequence_statements(&diagram.statements);
+    }
+
+    fn visit_class(&mut self, diagram: &ClassDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for class in diagram.classes.values() {
+            self.visit_class_definition(class);
+        }
+        for relationship in &diagram.relationships {
+            self.edge(
+                &relationship.from,
+                &relationship.to,
+                relationship.label.as_deref(),
+            );
+        }
+    }
+
+    fn visit_state(&mut self, diagram: &StateDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for state in diagram.all_states() {
+            self.visit_state_node(state);
+        }
+        for transition in diagram.all_transitions() {
+            self.visit_state_transition(transition);
+        }
+    }
+
+    fn visit_flowchart(&mut self, diagram: &FlowchartDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for node in diagram.nodes.values() {
+            self.visit_flow_node(node);
+        }
+        for edge in &diagram.edges {
+            self.visit_flow_edge(edge);
+        }
+    }
+
+    fn visit_gantt(&mut self, diagram: &GanttDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for (index, section) in diagram.sections.iter().enumerate() {
+            self.section(index, &section.name);
+            for task in &section.tasks {
+                match &task.id {
+                    Some(id) => self.node(id, Some(&task.name)),
+                    None => self.node(&task.name, None),
+                }
+            }
+        }
+    }
+
+    fn visit_pie(&mut self, diagram: &PieDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for slice in &diagram.data {
+            self.handler.event(ParseEvent::Node {
+                id: &slice.label,
+                label: None,
+                value: Some(slice.value),
+            });
+        }
+    }
+
+    fn visit_git(&mut self, diagram: &GitDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for commit in &diagram.commits {
+            if let Some(id) = &commit.id {
+                self.node(id, commit.tag.as_deref());
+            }
+        }
+    }
+
+    fn visit_er(&mut self, diagram: &ErDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for entity in diagram.entities.values() {
+            self.node(&entity.name, entity.display_name.as_deref());
+        }
+        for relationship in &diagram.relationships {
+            self.edge(
+                &relationship.left_entity,
+                &relationship.right_entity,
+                relationship.label.as_deref(),
+            );
+        }
+    }
+
+    fn visit_c4(&mut self, diagram: &C4Diagram) -> Self::Result {
+        self.title(&diagram.title);
+        for element in diagram.elements.values() {
+            self.node(&element.id, Some(&element.name));
+        }
+        for relationship in &diagram.relationships {
+            self.edge(
+                &relationship.from,
+                &relationship.to,
+                relationship.label.as_deref(),
+            );
+        }
+    }
+
+    fn visit_mindmap(&mut self, diagram: &MindmapDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        self.mindmap_node(&diagram.root);
+        for edge in &diagram.cross_edges {
+            self.edge(&edge.from, &edge.to, edge.label.as_deref());
+        }
+    }
+
+    fn visit_quadrant(&mut self, diagram: &QuadrantDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for point in &diagram.points {
+            self.node(&point.name, None);
+        }
+    }
+
+    fn visit_xychart(&mut self, diagram: &XyChartDiagram) -> Self::Result {
+        self.title(&diagram.title);
+    }
+
+    fn visit_kanban(&mut self, diagram: &KanbanDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for (index, section) in diagram.sections.iter().enumerate() {
+            self.section(index, &section.title);
+            for item in &section.items {
+                match &item.id {
+                    Some(id) => self.node(id, Some(&item.text)),
+                    None => self.node(&item.text, None),
+                }
+            }
+        }
+    }
+
+    fn visit_block(&mut self, diagram: &BlockDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for block in &diagram.blocks {
+            self.block(block);
+        }
+        for connection in &diagram.connections {
+            self.edge(
+                &connection.from,
+                &connection.to,
+                connection.label.as_deref(),
+            );
+        }
+    }
+
+    fn visit_architecture(&mut self, diagram: &ArchitectureDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for service in diagram.services.values() {
+            self.node(&service.id, Some(&service.title));
+        }
+        for junction in diagram.junctions.values() {
+            self.node(&junction.id, None);
+        }
+        for edge in &diagram.edges {
+            self.edge(&edge.from.id, &edge.to.id, edge.label.as_deref());
+        }
+    }
+
+    fn visit_packet(&mut self, diagram: &PacketDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for field in &diagram.fields {
+            self.node(&field.name, None);
+        }
+    }
+
+    fn visit_requirement(&mut self, diagram: &RequirementDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for requirement in diagram.requirements.values() {
+            self.node(&requirement.name, Some(&requirement.text));
+        }
+        for element in diagram.elements.values() {
+            self.node(&element.name, None);
+        }
+        for relationship in &diagram.relationships {
+            self.edge(&relationship.source, &relationship.target, None);
+        }
+    }
+
+    fn visit_treemap(&mut self, diagram: &TreemapDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        self.treemap_node(&diagram.root);
+    }
+
+    fn visit_radar(&mut self, diagram: &RadarDiagram) -> Self::Result {
+        self.title(&diagram.title);
+        for dataset in &diagram.datasets {
+            self.node(&dataset.name, None);
+        }
+    }
+
+    fn visit_misc(&mut self, _diagram: &MiscDiagram) -> Self::Result {}
+
+    fn visit_sankey_node(&mut self, node: &SankeyNode) -> Self::Result {
+        if self.seen.insert(node.id.clone()) {
+            let label = (node.name != node.id).then_some(node.name.as_str());
+            self.node(&node.id, label);
+        }
+    }
+
+    fn visit_sankey_link(&mut self, link: &SankeyLink) -> Self::Result {
+        self.handler.event(ParseEvent::Edge {
+            from: &link.source,
+            to: &link.target,
+            label: None,
+            value: Some(link.value),
+        });
+    }
+
+    fn visit_flow_node(&mut self, node: &FlowNode) -> Self::Result {
+        self.node(&node.id, node.text.as_deref());
+    }
+
+    fn visit_flow_edge(&mut self, edge: &FlowEdge) -> Self::Result {
+        self.edge(&edge.from, &edge.to, edge.label.as_deref());
+    }
+
+    fn visit_sequence_message(&mut self, message: &Message) -> Self::Result {
+        let label = (!message.text.is_empty()).then_some(message.text.as_str());
+        self.edge(&message.from, &message.to, label);
+    }
+
+    fn visit_class_definition(&mut self, class: &Class) -> Self::Result {
+        self.node(&class.name, None);
+    }
+
+    fn visit_state_node(&mut self, state: &State) -> Self::Result {
+        self.node(&state.id, state.display_name.as_deref());
+    }
+
+    fn visit_state_transition(&mut self, transition: &StateTransition) -> Self::Result {
+        self.edge(
+            &transition.from,
+            &transition.to,
+            transition.event.as_deref(),
+        );
+    }
+}
@@ -0,0 +1,387 @@
+//! Automatic fixes for validation diagnostics
+//!
+//! Some diagnostics produced by the validators in [`crate::common::validation`]
+//! and by [`crate::common::deprecation::detect_legacy_syntax`] have a
+//! mechanical remedy. [`apply_fixes`] applies those remedies to the AST
+//! and reports which diagnostics were fixed and which were left alone; printing
+//! the fixed AST then yields corrected Mermaid source.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::fix::apply_fixes;
+//! use mermaid_parser::common::validation::{Severity, UniversalValidator, ValidationConfig};
+//! use mermaid_parser::parse_diagram;
+//!
+//! let mut diagram = parse_diagram("flowchart TD\n    A[Cart] --> B[Pay]")?;
+//! let validator = UniversalValidator::with_config(ValidationConfig {
+//!     min_severity: Severity::Info,
+//!     ..Default::default()
+//! });
+//! let diagnostics = validator.validate_any(&diagram).err().unwrap_or_default();
+//!
+//! let report = apply_fixes(&mut diagram, &diagnostics);
+//! for fix in &report.applied {
+//!     println!("fixed {}: {}", fix.rule, fix.description);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::common::arrow_table::ArrowKind;
+use crate::common::ast::*;
+use crate::common::deprecation;
+use crate::common::validation::{self, ValidationError};
+
+/// Rules whose diagnostics [`apply_fixes`] knows how to fix
+pub const FIXABLE_RULES: &[&str] = &[
+    "reserved_word_id",
+    "missing_acc_title",
+    "deprecated_arrow_spelling",
+    "duplicate_class_def",
+    "unused_class_def",
+    "undefined_style_class",
+    "orphaned_style",
+];
+
+/// A fix that was applied to the diagram
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedFix {
+    pub rule: &'static str,
+    pub description: String,
+}
+
+/// Outcome of [`apply_fixes`]
+#[derive(Debug, Clone, Default)]
+pub struct FixReport {
+    /// Fixes applied, in diagnostic order
+    pub applied: Vec<AppliedFix>,
+    /// Diagnostics that have no fix or could not be fixed for this diagram
+    pub skipped: Vec<ValidationError>,
+}
+
+impl FixReport {
+    /// Whether the diagram was changed
+    pub fn changed(&self) -> bool {
+        !self.applied.is_empty()
+    }
+}
+
+/// Apply the available fixes for `diagnostics` to `diagram`
+pub fn apply_fixes(diagram: &mut DiagramType, diagnostics: &[ValidationError]) -> FixReport {
+    let mut report = FixReport::default();
+
+    for diagnostic in diagnostics {
+        let element = diagnostic
+            .location
+            .as_ref()
+            .and_then(|location| location.element_id.as_deref());
+
+        let description = match diagnostic.rule {
+            "reserved_word_id" => element.and_then(|id| fix_reserved_word(diagram, id)),
+            "missing_acc_title" => fix_missing_acc_title(diagram),
+            "deprecated_arrow_spelling" => {
+                element.and_then(|edge| fix_arrow_spelling(diagram, edge))
+            }
+            "duplicate_class_def" => {
+                element.and_then(|name| fix_duplicate_class_def(diagram, name))
+            }
+            "unused_class_def" => element.and_then(|name| fix_unused_class_def(diagram, name)),
+            "undefined_style_class" => {
+                element.and_then(|name| fix_undefined_style_class(diagram, name))
+            }
+            "orphaned_style" => element.and_then(|target| fix_orphaned_style(diagram, target)),
+            "deprecated_state_diagram_v1" => fix_state_diagram_v1(diagram),
+            "deprecated_git_graph_syntax" => fix_git_graph_syntax(diagram),
+            _ => None,
+        };
+
+        match description {
+            Some(description) => report.applied.push(AppliedFix {
+                rule: diagnostic.rule,
+                description,
+            }),
+            None => report.skipped.push(diagnostic.clone()),
+        }
+    }
+
+    report
+}
+
+/// Capitalize a reserved id (`end` becomes `End`), keeping the original word
+/// as the visible label
+fn fix_reserved_word(diagram: &mut DiagramType, id: &str) -> Option<String> {
+    match diagram {
+        DiagramType::Flowchart(flowchart) => {
+            let new_id = unused_id(id, |candidate| flowchart.nodes.contains_key(candidate));
+            if !flowchart.rename_node(id, &new_id) {
+                return None;
+            }
+            let node = flowchart.nodes.get_mut(&new_id)?;
+            node.text.get_or_insert_with(|| id.to_string());
+            Some(format!("Renamed node '{}' to '{}'", id, new_id))
+        }
+        DiagramType::Sequence(sequence) => {
+            let new_id = unused_id(id, |candidate| sequence.resolve_actor(candidate).is_some());
+            if !sequence.rename_participant(id, &new_id) {
+                return None;
+            }
+            let participant = sequence
+                .participants
+                .iter_mut()
+                .find(|p| p.actor == new_id)?;
+            participant.alias.get_or_insert_with(|| id.to_string());
+            Some(format!("Renamed participant '{}' to '{}'", id, new_id))
+        }
+        _ => None,
+    }
+}
+
+fn unused_id(id: &str, exists: impl Fn(&str) -> bool) -> String {
+    let mut chars = id.chars();
+    let base = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => return id.to_string(),
+    };
+
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    while exists(&candidate) {
+        candidate = format!("{}{}", base, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// Use the diagram title as its accessible title
+fn fix_missing_acc_title(diagram: &mut DiagramType) -> Option<String> {
+    let (title, accessibility) = match diagram {
+        DiagramType::Flowchart(d) => (&d.title, &mut d.accessibility),
+        DiagramType::Sequence(d) => (&d.title, &mut d.accessibility),
+        DiagramType::Class(d) => (&d.title, &mut d.accessibility),
+        DiagramType::State(d) => (&d.title, &mut d.accessibility),
+        DiagramType::Quadrant(d) => (&d.title, &mut d.accessibility),
+        _ => return None,
+    };
+
+    if accessibility.title.is_some() {
+        return None;
+    }
+    let title = title.clone()?;
+    accessibility.title = Some(title.clone());
+    Some(format!("Set accTitle to '{}'", title))
+}
+
+/// Write an edge with the canonical spelling of its arrow
+fn fix_arrow_spelling(diagram: &mut DiagramType, edge_label: &str) -> Option<String> {
+    let DiagramType::Architecture(architecture) = diagram else {
+        return None;
+    };
+
+    let edge = architecture
+        .edges
+        .iter_mut()
+        .find(|edge| validation::legacy_arrow_label(edge).as_deref() == Some(edge_label))?;
+    let legacy = edge.legacy_arrow.take()?;
+    Some(format!(
+        "Replaced arrow '{}' with '{}'",
+        legacy,
+        ArrowKind::Architecture(edge.edge_type.clone()).canonical()
+    ))
+}
+
+/// Merge repeated definitions of a class into the first one; later
+/// properties override earlier ones, as they would when rendered
+fn fix_duplicate_class_def(diagram: &mut DiagramType, name: &str) -> Option<String> {
+    let removed = match diagram {
+        DiagramType::Flowchart(flowchart) => merge_class_def(&mut flowchart.class_defs, name),
+        DiagramType::State(state) => merge_class_def(&mut state.class_defs, name),
+        DiagramType::Er(er) => merge_class_def(&mut er.class_defs, name),
+        DiagramType::Quadrant(quadrant) => merge_quadrant_class_def(quadrant, name),
+        _ => None,
+    }?;
+    Some(format!(
+        "Merged {} duplicate classDef '{}' into the first definition",
+        removed, name
+    ))
+}
+
+/// Classes kept by name were merged by the parser, which counted their
+/// definitions; the merged class only needs to become a single definition
+fn merge_class_def(
+    class_defs: &mut std::collections::HashMap<String, ClassDef>,
+    name: &str,
+) -> Option<usize> {
+    let class_def = class_defs
+        .get_mut(name)
+        .filter(|class_def| class_def.definitions > 1)?;
+    let removed = class_def.definitions - 1;
+    class_def.definitions = 1;
+    Some(removed)
+}
+
+fn merge_quadrant_class_def(quadrant: &mut QuadrantDiagram, name: &str) -> Option<usize> {
+    let first = quadrant.styles.iter().position(|c| c.name == name)?;
+    let mut merged = quadrant.styles[first].styles.clone();
+    let mut removed = 0;
+    let mut index = first + 1;
+    while index < quadrant.styles.len() {
+        if quadrant.styles[index].name != name {
+            index += 1;
+            continue;
+        }
+        for style in quadrant.styles.remove(index).styles {
+            let property = style_property(&style).to_string();
+            match merged.iter().position(|s| style_property(s) == property) {
+                Some(existing) => merged[existing] = style,
+                None => merged.push(style),
+            }
+        }
+        removed += 1;
+    }
+
+    if removed == 0 {
+        return None;
+    }
+    quadrant.styles[first].styles = merged;
+    Some(removed)
+}
+
+/// Remove a classDef that no element uses
+fn fix_unused_class_def(diagram: &mut DiagramType, name: &str) -> Option<String> {
+    let removed = match diagram {
+        DiagramType::Flowchart(flowchart) => flowchart.class_defs.remove(name).is_some(),
+        DiagramType::State(state) => state.class_defs.remove(name).is_some(),
+        DiagramType::Er(er) => er.class_defs.remove(name).is_some(),
+        DiagramType::Quadrant(quadrant) => {
+            let before = quadrant.styles.len();
+            quadrant.styles.retain(|class_def| class_def.name != name);
+            quadrant.styles.len() < before
+        }
+        _ => false,
+    };
+    removed.then(|| format!("Removed unused classDef '{}'", name))
+}
+
+/// Remove references to a class that has no classDef
+fn fix_undefined_style_class(diagram: &mut DiagramType, name: &str) -> Option<String> {
+    let mut removed = 0;
+    match diagram {
+        DiagramType::Flowchart(flowchart) => {
+            for node in flowchart.nodes.values_mut() {
+                let before = node.classes.len();
+                node.classes.retain(|class| class != name);
+                removed += before - node.classes.len();
+            }
+        }
+        DiagramType::State(state) => removed += remove_state_class(&mut state.states, name),
+        DiagramType::Er(er) => {
+            for entity in er.entities.values_mut() {
+                if entity.css_class.as_deref() == Some(name) {
+                    entity.css_class = None;
+                    removed += 1;
+                }
+            }
+        }
+        DiagramType::Quadrant(quadrant) => {
+            for point in &mut quadrant.points {
+                if point.class.as_deref() == Some(name) {
+                    point.class = None;
+                    removed += 1;
+                }
+            }
+        }
+        _ => {}
+    }
+    (removed > 0).then(|| {
+        format!(
+            "Removed undefined class '{}' from {} element(s)",
+            name, removed
+        )
+    })
+}
+
+/// Remove `class` from `states` and the states nested inside them, returning
+/// how many states had it
+fn remove_state_class(
+    states: &mut crate::common::ordered_map::OrderedMap<String, State>,
+    class: &str,
+) -> usize {
+    states
+        .values_mut()
+        .map(|state| {
+            let before = state.classes.len();
+            state.classes.retain(|c| c != class);
+            let nested: usize = std::iter::once(&mut state.states)
+                .chain(state.concurrent_regions.iter_mut().map(|r| &mut r.states))
+                .map(|states| remove_state_class(states, class))
+                .sum();
+            before - state.classes.len() + nested
+        })
+        .sum()
+}
+
+/// Remove `style` statements whose target does not exist
+fn fix_orphaned_style(diagram: &mut DiagramType, target: &str) -> Option<String> {
+    let removed = match diagram {
+        DiagramType::Flowchart(flowchart) => {
+            let styles = std::mem::take(&mut flowchart.styles);
+            let (orphaned, kept): (Vec<_>, Vec<_>) = styles.into_iter().partition(|style| {
+                validation::style_target_label(&style.target) == target
+                    && !validation::style_target_exists(flowchart, &style.target)
+            });
+            flowchart.styles = kept;
+            orphaned.len()
+        }
+        DiagramType::Er(er) if !er.entities.contains_key(target) => {
+            let before = er.styles.len();
+            er.styles
+                .retain(|style| validation::style_target_label(&style.target) != target);
+            before - er.styles.len()
+        }
+        _ => 0,
+    };
+    (removed > 0).then(|| format!("Removed style for nonexistent '{}'", target))
+}
+
+fn fix_state_diagram_v1(diagram: &mut DiagramType) -> Option<String> {
+    let DiagramType::State(state) = diagram else {
+        return None;
+    };
+    deprecation::upgrade_state_diagram(state).then(|| "Upgraded to stateDiagram-v2".to_string())
+}
+
+fn fix_git_graph_syntax(diagram: &mut DiagramType) -> Option<String> {
+    let git = deprecation::upgrade_git_graph(diagram)?;
+    *diagram = DiagramType::Git(git);
+    Some("Converted to the modern gitGraph syntax".to_string())
+}
+
+fn style_property(style: &str) -> &str {
+    style.split(':').next().unwrap_or(style).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_id_capitalizes_and_avoids_collisions() {
+        assert_eq!(unused_id("end", |_| false), "End");
+        assert_eq!(unused_id("end", |id| id == "End"), "End2");
+    }
+
+    #[test]
+    fn test_unknown_rules_are_skipped() {
+        let mut diagram = DiagramType::Pie(PieDiagram {
+            title: None,
+            accessibility: AccessibilityInfo::default(),
+            show_data: false,
+            data: Vec::new(),
+        });
+        let diagnostics = vec![ValidationError::error("isolated_node", "x".to_string())];
+
+        let report = apply_fixes(&mut diagram, &diagnostics);
+        assert!(!report.changed());
+        assert_eq!(report.skipped.len(), 1);
+    }
+}
@@ -0,0 +1,988 @@
+//! YAML frontmatter and typed diagram configuration
+//!
+//! Mermaid documents may start with a frontmatter block between `---` lines
+//! holding the diagram title and a `config:` mapping. Per-diagram sections
+//! such as `config: flowchart: { nodeSpacing: 50 }` are mapped into typed
+//! structs ([`FlowchartConfig`], [`SequenceConfig`], [`GanttConfig`]) so
+//! renderers and linters can read them without walking untyped values. Keys
+//! without a typed field, and values of an unexpected type, are preserved in
+//! each struct's `extra` map.
+//!
+//! The YAML subset understood here covers what Mermaid frontmatter uses in
+//! practice: nested block mappings, block lists, flow mappings and lists
+//! (`{ a: 1, b: [x, y] }`), quoted and plain scalars, and `#` comments.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::parse_document;
+//!
+//! let document = parse_document(
+//!     "---\ntitle: Orders\nconfig:\n  theme: forest\n  flowchart: { nodeSpacing: 50, curve: basis }\n---\nflowchart TD\n    A --> B",
+//! )?;
+//! let frontmatter = document.frontmatter.unwrap();
+//! assert_eq!(frontmatter.title.as_deref(), Some("Orders"));
+//!
+//! let flowchart = frontmatter.config.flowchart.unwrap();
+//! assert_eq!(flowchart.node_spacing, Some(50.0));
+//! assert_eq!(flowchart.curve.as_deref(), Some("basis"));
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{DiagramMetadata, DiagramType, InitDirective};
+use crate::common::parser_utils::Scanner;
+use crate::common::pretty_print::{MermaidPrinter, PrintOptions};
+use crate::error::{ParseError, Result};
+use std::collections::BTreeMap;
+
+/// Line that opens and closes a frontmatter block
+pub const DELIMITER: &str = "---";
+
+/// An untyped configuration value
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ConfigValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<ConfigValue>),
+    Map(BTreeMap<String, ConfigValue>),
+}
+
+/// Flow style, as used in `%%{init: ...}%%` directives: `{"theme": "dark"}`
+impl std::fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigValue::Null => write!(f, "null"),
+            ConfigValue::Bool(value) => write!(f, "{}", value),
+            ConfigValue::Number(number) => write!(f, "{}", format_number(*number)),
+            ConfigValue::String(text) => write!(f, "{}", quote(text)),
+            ConfigValue::List(items) => {
+                let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            ConfigValue::Map(map) => {
+                let entries: Vec<String> = map
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", quote(key), value))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+        }
+    }
+}
+
+impl From<bool> for ConfigValue {
+    fn from(value: bool) -> Self {
+        ConfigValue::Bool(value)
+    }
+}
+
+impl From<f64> for ConfigValue {
+    fn from(value: f64) -> Self {
+        ConfigValue::Number(value)
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(value: &str) -> Self {
+        ConfigValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ConfigValue {
+    fn from(value: String) -> Self {
+        ConfigValue::String(value)
+    }
+}
+
+/// A parsed frontmatter block
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Frontmatter {
+    pub title: Option<String>,
+    pub config: MermaidConfig,
+    /// Top-level keys other than `title` and `config`
+    pub extra: BTreeMap<String, ConfigValue>,
+}
+
+/// The `config:` mapping
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MermaidConfig {
+    pub theme: Option<String>,
+    pub look: Option<String>,
+    pub layout: Option<String>,
+    pub flowchart: Option<FlowchartConfig>,
+    pub sequence: Option<SequenceConfig>,
+    pub gantt: Option<GanttConfig>,
+    /// Other keys, including sections for diagram types without a typed config
+    pub extra: BTreeMap<String, ConfigValue>,
+}
+
+/// `config: flowchart:`
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FlowchartConfig {
+    pub node_spacing: Option<f64>,
+    pub rank_spacing: Option<f64>,
+    pub padding: Option<f64>,
+    pub diagram_padding: Option<f64>,
+    pub wrapping_width: Option<f64>,
+    pub curve: Option<String>,
+    pub default_renderer: Option<String>,
+    pub html_labels: Option<bool>,
+    pub extra: BTreeMap<String, ConfigValue>,
+}
+
+/// `config: sequence:`
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SequenceConfig {
+    pub actor_margin: Option<f64>,
+    pub box_margin: Option<f64>,
+    pub message_margin: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub message_align: Option<String>,
+    pub mirror_actors: Option<bool>,
+    pub show_sequence_numbers: Option<bool>,
+    pub wrap: Option<bool>,
+    pub extra: BTreeMap<String, ConfigValue>,
+}
+
+/// `config: gantt:`
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GanttConfig {
+    pub bar_height: Option<f64>,
+    pub bar_gap: Option<f64>,
+    pub top_padding: Option<f64>,
+    pub left_padding: Option<f64>,
+    pub right_padding: Option<f64>,
+    pub font_size: Option<f64>,
+    pub section_font_size: Option<f64>,
+    pub axis_format: Option<String>,
+    pub tick_interval: Option<String>,
+    pub display_mode: Option<String>,
+    pub top_axis: Option<bool>,
+    pub extra: BTreeMap<String, ConfigValue>,
+}
+
+/// A diagram together with its frontmatter and `%%{init: ...}%%` directives
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub frontmatter: Option<Frontmatter>,
+    pub init: Option<InitDirective>,
+    pub diagram: DiagramType,
+}
+
+impl Document {
+    /// Metadata of the diagram, completed by its frontmatter
+    ///
+    /// A title in the diagram takes precedence over the frontmatter `title:`.
+    pub fn metadata(&self) -> DiagramMetadata {
+        let frontmatter = self.frontmatter.as_ref();
+        DiagramMetadata {
+            title: self
+                .diagram
+                .title()
+                .or_else(|| frontmatter?.title.as_deref())
+                .map(str::to_string),
+            accessibility: self.diagram.accessibility().cloned().unwrap_or_default(),
+            config: frontmatter
+                .map(|frontmatter| frontmatter.config.clone())
+                .unwrap_or_default(),
+            init: self.init.clone(),
+        }
+    }
+}
+
+impl MermaidPrinter for Document {
+    fn to_mermaid(&self) -> String {
+        self.to_mermaid_pretty(&PrintOptions::default())
+    }
+
+    fn to_mermaid_pretty(&self, options: &PrintOptions) -> String {
+        let mut diagram = self.diagram.to_mermaid_pretty(options);
+        if let Some(init) = &self.init {
+            // Misc diagrams print their own directives
+            if !matches!(self.diagram, DiagramType::Misc(_)) {
+                diagram = format!(
+                    "%%{{init: {}}}%%\n{}",
+                    ConfigValue::Map(init.config.to_map()),
+                    diagram
+                );
+            }
+        }
+        match &self.frontmatter {
+            Some(frontmatter) => format!(
+                "{}\n{}{}\n{}",
+                DELIMITER,
+                frontmatter.to_yaml(),
+                DELIMITER,
+                diagram
+            ),
+            None => diagram,
+        }
+    }
+}
+
+/// Split `input` into the frontmatter source (without delimiters) and the
+/// diagram source. Input without a complete frontmatter block is returned
+/// unchanged as the diagram source.
+pub fn split(input: &str) -> (Option<&str>, &str) {
+    let start = input.len() - input.trim_start().len();
+    let Some(rest) = input[start..].strip_prefix(DELIMITER) else {
+        return (None, input);
+    };
+    let Some(first_newline) = rest.find('\n') else {
+        return (None, input);
+    };
+    if !rest[..first_newline].trim().is_empty() {
+        return (None, input);
+    }
+
+    let yaml_start = start + DELIMITER.len() + first_newline + 1;
+    let mut offset = yaml_start;
+    for line in input[yaml_start..].split_inclusive('\n') {
+        if line.trim_end() == DELIMITER {
+            return (
+                Some(&input[yaml_start..offset]),
+                &input[offset + line.len()..],
+            );
+        }
+        offset += line.len();
+    }
+    (None, input)
+}
+
+/// Parse the frontmatter of `input`, if it has any
+pub fn parse(input: &str) -> Result<Option<Frontmatter>> {
+    match split(input) {
+        (Some(yaml), _) => Frontmatter::from_yaml(yaml).map(Some),
+        (None, _) => Ok(None),
+    }
+}
+
+/// Parse the frontmatter, the `init` directives and the diagram of `input`
+pub fn parse_document(input: &str) -> Result<Document> {
+    let (yaml, body) = split(input);
+    Ok(Document {
+        frontmatter: yaml.map(Frontmatter::from_yaml).transpose()?,
+        init: crate::common::parsing::directives::extract(body)?,
+        diagram: crate::parse_diagram(input)?,
+    })
+}
+
+impl Frontmatter {
+    /// Parse the YAML between the delimiters
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let lines: Vec<YamlLine> = yaml
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| YamlLine::new(index + 2, line))
+            .collect();
+        let mut position = 0;
+        let mut map = match parse_block(&lines, &mut position, 0)? {
+            ConfigValue::Map(map) => map,
+            ConfigValue::Null => BTreeMap::new(),
+            _ => return Err(yaml_error(2, "Frontmatter must be a mapping")),
+        };
+
+        let title = match map.remove("title") {
+            Some(ConfigValue::String(title)) => Some(title),
+            Some(ConfigValue::Number(number)) => Some(format_number(number)),
+            Some(other) => {
+                map.insert("title".to_string(), other);
+                None
+            }
+            None => None,
+        };
+        let config = match map.remove("config") {
+            Some(ConfigValue::Map(config)) => MermaidConfig::from_map(config),
+            Some(other) => {
+                map.insert("config".to_string(), other);
+                MermaidConfig::default()
+            }
+            None => MermaidConfig::default(),
+        };
+
+        Ok(Frontmatter {
+            title,
+            config,
+            extra: map,
+        })
+    }
+
+    /// Write the frontmatter back as block-style YAML, without delimiters
+    pub fn to_yaml(&self) -> String {
+        let mut map = self.extra.clone();
+        if let Some(title) = &self.title {
+            map.insert("title".to_string(), ConfigValue::String(title.clone()));
+        }
+        let config = self.config.to_map();
+        if !config.is_empty() {
+            map.insert("config".to_string(), ConfigValue::Map(config));
+        }
+
+        let mut output = String::new();
+        write_map(&mut output, &map, 0);
+        output
+    }
+}
+
+impl MermaidConfig {
+    pub(crate) fn from_map(mut map: BTreeMap<String, ConfigValue>) -> Self {
+        MermaidConfig {
+            theme: take_string(&mut map, "theme"),
+            look: take_string(&mut map, "look"),
+            layout: take_string(&mut map, "layout"),
+            flowchart: take_map(&mut map, "flowchart").map(FlowchartConfig::from_map),
+            sequence: take_map(&mut map, "sequence").map(SequenceConfig::from_map),
+            gantt: take_map(&mut map, "gantt").map(GanttConfig::from_map),
+            extra: map,
+        }
+    }
+
+    fn to_map(&self) -> BTreeMap<String, ConfigValue> {
+        let mut map = self.extra.clone();
+        put_string(&mut map, "theme", &self.theme);
+        put_string(&mut map, "look", &self.look);
+        put_string(&mut map, "layout", &self.layout);
+        put_map(
+            &mut map,
+            "flowchart",
+            self.flowchart.as_ref().map(FlowchartConfig::to_map),
+        );
+        put_map(
+            &mut map,
+            "sequence",
+            self.sequence.as_ref().map(SequenceConfig::to_map),
+        );
+        put_map(
+            &mut map,
+            "gantt",
+            self.gantt.as_ref().map(GanttConfig::to_map),
+        );
+        map
+    }
+}
+
+impl FlowchartConfig {
+    fn from_map(mut map: BTreeMap<String, ConfigValue>) -> Self {
+        FlowchartConfig {
+            node_spacing: take_number(&mut map, "nodeSpacing"),
+            rank_spacing: take_number(&mut map, "rankSpacing"),
+            padding: take_number(&mut map, "padding"),
+            diagram_padding: take_number(&mut map, "diagramPadding"),
+            wrapping_width: take_number(&mut map, "wrappingWidth"),
+            curve: take_string(&mut map, "curve"),
+            default_renderer: take_string(&mut map, "defaultRenderer"),
+            html_labels: take_bool(&mut map, "htmlLabels"),
+            extra: map,
+        }
+    }
+
+    fn to_map(&self) -> BTreeMap<String, ConfigValue> {
+        let mut map = self.extra.clone();
+        put_number(&mut map, "nodeSpacing", self.node_spacing);
+        put_number(&mut map, "rankSpacing", self.rank_spacing);
+        put_number(&mut map, "padding", self.padding);
+        put_number(&mut map, "diagramPadding", self.diagram_padding);
+        put_number(&mut map, "wrappingWidth", self.wrapping_width);
+        put_string(&mut map, "curve", &self.curve);
+        put_string(&mut map, "defaultRenderer", &self.default_renderer);
+        put_bool(&mut map, "htmlLabels", self.html_labels);
+        map
+    }
+}
+
+impl SequenceConfig {
+    fn from_map(mut map: BTreeMap<String, ConfigValue>) -> Self {
+        SequenceConfig {
+            actor_margin: take_number(&mut map, "actorMargin"),
+            box_margin: take_number(&mut map, "boxMargin"),
+            message_margin: take_number(&mut map, "messageMargin"),
+            width: take_number(&mut map, "width"),
+            height: take_number(&mut map, "height"),
+            message_align: take_string(&mut map, "messageAlign"),
+            mirror_actors: take_bool(&mut map, "mirrorActors"),
+            show_sequence_numbers: take_bool(&mut map, "showSequenceNumbers"),
+            wrap: take_bool(&mut map, "wrap"),
+            extra: map,
+        }
+    }
+
+    fn to_map(&self) -> BTreeMap<String, ConfigValue> {
+        let mut map = self.extra.clone();
+        put_number(&mut map, "actorMargin", self.actor_margin);
+        put_number(&mut map, "boxMargin", self.box_margin);
+        put_number(&mut map, "messageMargin", self.message_margin);
+        put_number(&mut map, "width", self.width);
+        put_number(&mut map, "height", self.height);
+        put_string(&mut map, "messageAlign", &self.message_align);
+        put_bool(&mut map, "mirrorActors", self.mirror_actors);
+        put_bool(&mut map, "showSequenceNumbers", self.show_sequence_numbers);
+        put_bool(&mut map, "wrap", self.wrap);
+        map
+    }
+}
+
+impl GanttConfig {
+    fn from_map(mut map: BTreeMap<String, ConfigValue>) -> Self {
+        GanttConfig {
+            bar_height: take_number(&mut map, "barHeight"),
+            bar_gap: take_number(&mut map, "barGap"),
+            top_padding: take_number(&mut map, "topPadding"),
+            left_padding: take_number(&mut map, "leftPadding"),
+            right_padding: take_number(&mut map, "rightPadding"),
+            font_size: take_number(&mut map, "fontSize"),
+            section_font_size: take_number(&mut map, "sectionFontSize"),
+            axis_format: take_string(&mut map, "axisFormat"),
+            tick_interval: take_string(&mut map, "tickInterval"),
+            display_mode: take_string(&mut map, "displayMode"),
+            top_axis: take_bool(&mut map, "topAxis"),
+            extra: map,
+        }
+    }
+
+    fn to_map(&self) -> BTreeMap<String, ConfigValue> {
+        let mut map = self.extra.clone();
+        put_number(&mut map, "barHeight", self.bar_height);
+        put_number(&mut map, "barGap", self.bar_gap);
+        put_number(&mut map, "topPadding", self.top_padding);
+        put_number(&mut map, "leftPadding", self.left_padding);
+        put_number(&mut map, "rightPadding", self.right_padding);
+        put_number(&mut map, "fontSize", self.font_size);
+        put_number(&mut map, "sectionFontSize", self.section_font_size);
+        put_string(&mut map, "axisFormat", &self.axis_format);
+        put_string(&mut map, "tickInterval", &self.tick_interval);
+        put_string(&mut map, "displayMode", &self.display_mode);
+        put_bool(&mut map, "topAxis", self.top_axis);
+        map
+    }
+}
+
+// Typed field extraction: a value of the wrong type stays in the map
+
+fn take_number(map: &mut BTreeMap<String, ConfigValue>, key: &str) -> Option<f64> {
+    match map.get(key) {
+        Some(ConfigValue::Number(number)) => {
+            let number = *number;
+            map.remove(key);
+            Some(number)
+        }
+        _ => None,
+    }
+}
+
+fn take_bool(map: &mut BTreeMap<String, ConfigValue>, key: &str) -> Option<bool> {
+    match map.get(key) {
+        Some(ConfigValue::Bool(value)) => {
+            let value = *value;
+            map.remove(key);
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+fn take_string(map: &mut BTreeMap<String, ConfigValue>, key: &str) -> Option<String> {
+    match map.remove(key)? {
+        ConfigValue::String(value) => Some(value),
+        other => {
+            map.insert(key.to_string(), other);
+            None
+        }
+    }
+}
+
+fn take_map(
+    map: &mut BTreeMap<String, ConfigValue>,
+    key: &str,
+) -> Option<BTreeMap<String, ConfigValue>> {
+    match map.remove(key)? {
+        ConfigValue::Map(value) => Some(value),
+        other => {
+            map.insert(key.to_string(), other);
+            None
+        }
+    }
+}
+
+fn put_number(map: &mut BTreeMap<String, ConfigValue>, key: &str, value: Option<f64>) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), ConfigValue::Number(value));
+    }
+}
+
+fn put_bool(map: &mut BTreeMap<String, ConfigValue>, key: &str, value: Option<bool>) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), ConfigValue::Bool(value));
+    }
+}
+
+fn put_string(map: &mut BTreeMap<String, ConfigValue>, key: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), ConfigValue::String(value.clone()));
+    }
+}
+
+fn put_map(
+    map: &mut BTreeMap<String, ConfigValue>,
+    key: &str,
+    value: Option<BTreeMap<String, ConfigValue>>,
+) {
+    if let Some(value) = value {
+        map.insert(key.to_string(), ConfigValue::Map(value));
+    }
+}
+
+/// Parse a `{ ... }` mapping such as flowchart node metadata
+/// (`A@{ shape: rect, label: "Start" }`). The braces may also hold block
+/// style `key: value` lines. `line` is the line the mapping starts on, for
+/// error reporting.
+pub(crate) fn parse_mapping(text: &str, line: usize) -> Result<BTreeMap<String, ConfigValue>> {
+    let inner = text
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| yaml_error(line, "expected a `{ ... }` mapping"))?;
+
+    let value = if inner.contains('\n') && !inner.contains(',') {
+        let lines: Vec<YamlLine> = inner
+            .lines()
+            .enumerate()
+            .filter_map(|(index, text)| YamlLine::new(line + index, text))
+            .collect();
+        let indent = lines.first().map_or(0, |first| first.indent);
+        let mut position = 0;
+        let value = parse_block(&lines, &mut position, indent)?;
+        if let Some(extra) = lines.get(position) {
+            return Err(yaml_error(extra.number, "unexpected indentation"));
+        }
+        value
+    } else {
+        parse_inline(text.trim(), line)?
+    };
+
+    match value {
+        ConfigValue::Map(map) => Ok(map),
+        ConfigValue::Null => Ok(BTreeMap::new()),
+        _ => Err(yaml_error(line, "expected a mapping")),
+    }
+}
+
+// YAML subset reader
+
+struct YamlLine<'a> {
+    number: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+impl<'a> YamlLine<'a> {
+    fn new(number: usize, line: &'a str) -> Option<Self> {
+        let text = line.trim();
+        if text.is_empty() || text.starts_with('#') {
+            return None;
+        }
+        Some(YamlLine {
+            number,
+            indent: line.len() - line.trim_start().len(),
+            text,
+        })
+    }
+}
+
+fn yaml_error(line: usize, message: &str) -> ParseError {
+    ParseError::SyntaxError {
+        message: format!("Invalid YAML: {}", message),
+        expected: Vec::new(),
+        found: String::new(),
+        line,
+        column: 1,
+        partial: None,
+    }
+}
+
+/// Parse the block (mapping or list) whose lines are indented by `indent`
+fn parse_block(lines: &[YamlLine], position: &mut usize, indent: usize) -> Result<ConfigValue> {
+    let Some(first) = lines.get(*position) else {
+        return Ok(ConfigValue::Null);
+    };
+    if first.text.starts_with("- ") || first.text == "-" {
+        return parse_block_list(lines, position, indent);
+    }
+
+    let mut map = BTreeMap::new();
+    while let Some(line) = lines.get(*position) {
+        if line.indent < indent {
+            break;
+        }
+        if line.indent > indent {
+            return Err(yaml_error(line.number, "unexpected indentation"));
+        }
+        let (key, value) = split_key_value(line)?;
+        *position += 1;
+        let value = parse_entry_value(lines, position, indent, line.number, value)?;
+        map.insert(key, value);
+    }
+    Ok(ConfigValue::Map(map))
+}
+
+fn parse_block_list(
+    lines: &[YamlLine],
+    position: &mut usize,
+    indent: usize,
+) -> Result<ConfigValue> {
+    let mut items = Vec::new();
+    while let Some(line) = lines.get(*position) {
+        if line.indent != indent || !(line.text.starts_with("- ") || line.text == "-") {
+            break;
+        }
+        *position += 1;
+        let item = line.text[1..].trim();
+        items.push(parse_entry_value(
+            lines,
+            position,
+            indent,
+            line.number,
+            item,
+        )?);
+    }
+    Ok(ConfigValue::List(items))
+}
+
+/// The value after `key:` or `-`: inline, or a nested block on the next lines
+fn parse_entry_value(
+    lines: &[YamlLine],
+    position: &mut usize,
+    indent: usize,
+    line: usize,
+    value: &str,
+) -> Result<ConfigValue> {
+    if !value.is_empty() {
+        return parse_inline(value, line);
+    }
+    match lines.get(*position) {
+        Some(next) if next.indent > indent => parse_block(lines, position, next.indent),
+        _ => Ok(ConfigValue::Null),
+    }
+}
+
+fn split_key_value<'a>(line: &YamlLine<'a>) -> Result<(String, &'a str)> {
+    let (key, value) = match line.text.find(": ") {
+        Some(index) => (&line.text[..index], &line.text[index + 2..]),
+        None => match line.text.strip_suffix(':') {
+            Some(key) => (key, ""),
+            None => return Err(yaml_error(line.number, "expected `key: value`")),
+        },
+    };
+    Ok((unquote(key.trim()).to_string(), value.trim()))
+}
+
+fn parse_inline(value: &str, line: usize) -> Result<ConfigValue> {
+    if value.starts_with('{') || value.starts_with('[') {
+        let mut parser = FlowParser {
+            scanner: Scanner::new(value),
+            line,
+        };
+        let parsed = parser.value()?;
+        parser.scanner.skip_whitespace();
+        if parser.scanner.peek().is_some_and(|c| c != '#') {
+            return Err(yaml_error(line, "unexpected text after flow collection"));
+        }
+        return Ok(parsed);
+    }
+    Ok(scalar(strip_comment(value)))
+}
+
+/// Remove a trailing ` # comment` from an unquoted scalar
+fn strip_comment(value: &str) -> &str {
+    if value.starts_with('"') || value.starts_with('\'') {
+        return value;
+    }
+    value
+        .find(" #")
+        .map_or(value, |index| value[..index].trim_end())
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+fn scalar(value: &str) -> ConfigValue {
+    if value.len() >= 2 && (value.starts_with('"') || value.starts_with('\'')) {
+        return ConfigValue::String(unquote(value).to_string());
+    }
+    match value {
+        "" | "~" | "null" => ConfigValue::Null,
+        "true" => ConfigValue::Bool(true),
+        "false" => ConfigValue::Bool(false),
+        _ => value
+            .parse::<f64>()
+            .map(ConfigValue::Number)
+            .unwrap_or_else(|_| ConfigValue::String(value.to_string())),
+    }
+}
+
+/// Reader for flow collections: `{ key: value, ... }` and `[value, ...]`
+struct FlowParser {
+    scanner: Scanner,
+    line: usize,
+}
+
+impl FlowParser {
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.scanner.eat(expected) {
+            Ok(())
+        } else {
+            Err(yaml_error(
+                self.line,
+                &format!("expected `{}` in flow collection", expected),
+            ))
+        }
+    }
+
+    fn value(&mut self) -> Result<ConfigValue> {
+        self.scanner.skip_whitespace();
+        match self.scanner.peek() {
+            Some('{') => self.map(),
+            Some('[') => self.list(),
+            _ => Ok(scalar(&self.text(&[',', '}', ']']))),
+        }
+    }
+
+    fn map(&mut self) -> Result<ConfigValue> {
+        self.expect('{')?;
+        let mut map = BTreeMap::new();
+        loop {
+            if self.scanner.eat('}') {
+                return Ok(ConfigValue::Map(map));
+            }
+            let key = unquote(&self.text(&[':', ',', '}'])).to_string();
+            self.expect(':')?;
+            let value = self.value()?;
+            map.insert(key, value);
+            self.scanner.skip_whitespace();
+            match self.scanner.peek() {
+                Some(',') => self.scanner.bump(),
+                Some('}') => {}
+                _ => return Err(yaml_error(self.line, "unterminated flow mapping")),
+            }
+        }
+    }
+
+    fn list(&mut self) -> Result<ConfigValue> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            if self.scanner.eat(']') {
+                return Ok(ConfigValue::List(items));
+            }
+            items.push(self.value()?);
+            self.scanner.skip_whitespace();
+            match self.scanner.peek() {
+                Some(',') => self.scanner.bump(),
+                Some(']') => {}
+                _ => return Err(yaml_error(self.line, "unterminated flow list")),
+            }
+        }
+    }
+
+    /// A plain or quoted scalar ending before one of `terminators`
+    fn text(&mut self, terminators: &[char]) -> String {
+        self.scanner.skip_whitespace();
+        let start = self.scanner.position();
+        match self.scanner.peek().filter(|c| *c == '"' || *c == '\'') {
+            Some(quote) => {
+                self.scanner.bump();
+                self.scanner.skip_while(|c| c != quote);
+                self.scanner.bump();
+            }
+            None => self.scanner.skip_while(|c| !terminators.contains(&c)),
+        }
+        self.scanner.since(start).trim().to_string()
+    }
+}
+
+// YAML writer
+
+fn write_map(output: &mut String, map: &BTreeMap<String, ConfigValue>, indent: usize) {
+    for (key, value) in map {
+        output.push_str(&" ".repeat(indent));
+        output.push_str(&format_key(key));
+        output.push(':');
+        write_nested(output, value, indent);
+    }
+}
+
+fn write_nested(output: &mut String, value: &ConfigValue, indent: usize) {
+    match value {
+        ConfigValue::Map(map) if !map.is_empty() => {
+            output.push('\n');
+            write_map(output, map, indent + 2);
+        }
+        ConfigValue::List(items) if !items.is_empty() => {
+            output.push('\n');
+            for item in items {
+                output.push_str(&" ".repeat(indent + 2));
+                output.push('-');
+                write_nested(output, item, indent + 2);
+            }
+        }
+        scalar => {
+            output.push(' ');
+            output.push_str(&format_scalar(scalar));
+            output.push('\n');
+        }
+    }
+}
+
+fn format_scalar(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::Null => "null".to_string(),
+        ConfigValue::Bool(value) => value.to_string(),
+        ConfigValue::Number(number) => format_number(*number),
+        ConfigValue::String(text) => {
+            if matches!(scalar(text), ConfigValue::String(_))
+                && !text.is_empty()
+                && !text.contains(": ")
+                && !text.contains(" #")
+                && !text.starts_with(['{', '[', '"', '\'', '-', '#'])
+            {
+                text.clone()
+            } else {
+                quote(text)
+            }
+        }
+        ConfigValue::Map(_) => "{}".to_string(),
+        ConfigValue::List(_) => "[]".to_string(),
+    }
+}
+
+/// Escapes are not supported, so quote with the mark not in the text
+fn quote(text: &str) -> String {
+    let mark = if text.contains('"') { '\'' } else { '"' };
+    format!("{}{}{}", mark, text, mark)
+}
+
+fn format_key(key: &str) -> String {
+    if key.contains(':') || key.contains('#') || key.starts_with(['-', '"', '\'']) {
+        quote(key)
+    } else {
+        key.to_string()
+    }
+}
+
+fn format_number(number: f64) -> String {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        number.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_requires_closing_delimiter() {
+        let (yaml, body) = split("---\ntitle: x\n---\npie\n");
+        assert_eq!(yaml, Some("title: x\n"));
+        assert_eq!(body, "pie\n");
+
+        assert_eq!(split("---\ntitle: x\npie"), (None, "---\ntitle: x\npie"));
+        assert_eq!(split("pie\n---\n"), (None, "pie\n---\n"));
+    }
+
+    #[test]
+    fn test_block_and_flow_values() {
+        let yaml = r#"title: "Quoted: title"
+# comment
+config:
+  theme: dark   # trailing comment
+  themeVariables:
+    primaryColor: '#ff0000'
+  flowchart: { nodeSpacing: 50, curve: basis, extra: [1, two] }
+  gantt:
+    barHeight: 20
+    topAxis: true
+    barGap: wide
+  pie:
+    - a
+    - 2
+"#;
+        let frontmatter = Frontmatter::from_yaml(yaml).unwrap();
+        assert_eq!(frontmatter.title.as_deref(), Some("Quoted: title"));
+        assert_eq!(frontmatter.config.theme.as_deref(), Some("dark"));
+
+        let flowchart = frontmatter.config.flowchart.as_ref().unwrap();
+        assert_eq!(flowchart.node_spacing, Some(50.0));
+        assert_eq!(
+            flowchart.extra["extra"],
+            ConfigValue::List(vec![
+                ConfigValue::Number(1.0),
+                ConfigValue::String("two".to_string())
+            ])
+        );
+
+        let gantt = frontmatter.config.gantt.as_ref().unwrap();
+        assert_eq!(gantt.bar_height, Some(20.0));
+        assert_eq!(gantt.top_axis, Some(true));
+        assert_eq!(gantt.bar_gap, None);
+        assert_eq!(
+            gantt.extra["barGap"],
+            ConfigValue::String("wide".to_string())
+        );
+
+        let ConfigValue::Map(theme_variables) = &frontmatter.config.extra["themeVariables"] else {
+            panic!("themeVariables should be a map");
+        };
+        assert_eq!(
+            theme_variables["primaryColor"],
+            ConfigValue::String("#ff0000".to_string())
+        );
+        assert!(matches!(
+            frontmatter.config.extra["pie"],
+            ConfigValue::List(_)
+        ));
+
+        let reparsed = Frontmatter::from_yaml(&frontmatter.to_yaml()).unwrap();
+        assert_eq!(reparsed, frontmatter);
+    }
+
+    #[test]
+    fn test_errors_report_line() {
+        let error =
+            Frontmatter::from_yaml("config:\n  flowchart: { nodeSpacing: 50\n").unwrap_err();
+        assert!(
+            matches!(error, ParseError::SyntaxError { line: 3, .. }),
+            "{:?}",
+            error
+        );
+
+        let error = Frontmatter::from_yaml("title: x\n   bad: indent\n").unwrap_err();
+        assert!(
+            matches!(error, ParseError::SyntaxError { line: 3, .. }),
+            "{:?}",
+            error
+        );
+    }
+}
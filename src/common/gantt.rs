@@ -0,0 +1,583 @@
+//! Task temporal model of Gantt charts
+//!
+//! The Gantt AST keeps each task's start and duration as written.
+//! [`resolve_schedule`] interprets them the way Mermaid does, producing a
+//! concrete start and end time for every task so that downstream tools can
+//! compute critical paths or utilization without reimplementing Mermaid's
+//! date logic:
+//!
+//! - Dates are read with the diagram's `dateFormat` (Day.js tokens such as
+//!   `YYYY-MM-DD HH:mm`, see [`parse_date`]), `YYYY-MM-DD` by default
+//! - A task starts at its explicit date, when the last of its `after`
+//!   dependencies ends, or else when the previous task ends; tasks may refer
+//!   to tasks defined later in the chart
+//! - A task ends after its duration (`30m`, `3d`, `2w`, `1M`, see
+//!   [`Duration`]), at an explicit end date (inclusive with
+//!   `inclusiveEndDates`), or when the first of its `until` tasks starts
+//! - With `excludes`, each excluded day (`weekends`, weekday names or dates)
+//!   between the start and the end of a task pushes its end back a day;
+//!   `includes` and `weekend friday` are honored as in Mermaid
+//!
+//! [`crate::common::calendar`] exports a day-level schedule to iCalendar and
+//! CSV without applying exclusions.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::gantt::resolve_schedule;
+//! use mermaid_parser::{parse_diagram, DiagramType};
+//!
+//! let input = "gantt\n    dateFormat YYYY-MM-DD\n    excludes weekends\n    section Build\n        Design :a1, 2024-03-01, 3d\n        Code :after a1, 2d";
+//! if let DiagramType::Gantt(gantt) = parse_diagram(input)? {
+//!     let schedule = resolve_schedule(&gantt);
+//!     // Friday to Monday is three working days once the weekend is skipped
+//!     assert_eq!(schedule[0].end.unwrap().to_string(), "2024-03-06T00:00:00");
+//!     assert_eq!(schedule[1].start, schedule[0].end);
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{GanttDiagram, TaskStatus, Weekday};
+use crate::common::calendar::CalendarDate;
+use std::collections::HashMap;
+use std::fmt;
+
+/// `dateFormat` Mermaid uses when a chart declares none
+pub const DEFAULT_DATE_FORMAT: &str = "YYYY-MM-DD";
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+const MONTH_NAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("monday", Weekday::Monday),
+    ("tuesday", Weekday::Tuesday),
+    ("wednesday", Weekday::Wednesday),
+    ("thursday", Weekday::Thursday),
+    ("friday", Weekday::Friday),
+    ("saturday", Weekday::Saturday),
+    ("sunday", Weekday::Sunday),
+];
+
+const ONE_DAY: Duration = Duration {
+    amount: 1.0,
+    unit: DurationUnit::Day,
+};
+
+/// A date and time of day, without a time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub date: CalendarDate,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+}
+
+impl DateTime {
+    /// Midnight at the start of `date`
+    pub fn midnight(date: CalendarDate) -> Self {
+        DateTime {
+            date,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            millisecond: 0,
+        }
+    }
+
+    /// Milliseconds since 1970-01-01T00:00:00
+    pub fn timestamp_millis(self) -> i64 {
+        self.date.days() * MILLIS_PER_DAY
+            + i64::from(self.hour) * 3_600_000
+            + i64::from(self.minute) * 60_000
+            + i64::from(self.second) * 1_000
+            + i64::from(self.millisecond)
+    }
+
+    /// The time `millis` milliseconds after 1970-01-01T00:00:00
+    pub fn from_timestamp_millis(millis: i64) -> Self {
+        let time = millis.rem_euclid(MILLIS_PER_DAY);
+        DateTime {
+            date: CalendarDate::from_days(millis.div_euclid(MILLIS_PER_DAY)),
+            hour: (time / 3_600_000) as u32,
+            minute: (time / 60_000 % 60) as u32,
+            second: (time / 1_000 % 60) as u32,
+            millisecond: (time % 1_000) as u32,
+        }
+    }
+
+    /// The time `duration` later
+    ///
+    /// Months and years are calendar months, ending on the last day of the
+    /// month when the day does not exist in it, and their fractions are
+    /// rounded; other units are exact.
+    pub fn add(self, duration: Duration) -> Self {
+        let months = match duration.unit {
+            DurationUnit::Month => Some(duration.amount.round() as i64),
+            DurationUnit::Year => Some(duration.amount.round() as i64 * 12),
+            _ => None,
+        };
+        if let Some(months) = months {
+            let index = i64::from(self.date.year) * 12 + i64::from(self.date.month) - 1 + months;
+            let (year, month) = (index.div_euclid(12) as i32, index.rem_euclid(12) as u32 + 1);
+            let day = self.date.day.min(days_in_month(year, month));
+            return DateTime {
+                date: CalendarDate { year, month, day },
+                ..self
+            };
+        }
+        let millis = (duration.amount * duration.unit.millis() as f64).round() as i64;
+        DateTime::from_timestamp_millis(self.timestamp_millis() + millis)
+    }
+
+    /// Day of the week
+    pub fn weekday(self) -> Weekday {
+        // 1970-01-01 was a Thursday
+        WEEKDAYS[(self.date.days() + 3).rem_euclid(7) as usize]
+            .1
+            .clone()
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}",
+            self.date, self.hour, self.minute, self.second
+        )?;
+        if self.millisecond > 0 {
+            write!(f, ".{:03}", self.millisecond)?;
+        }
+        Ok(())
+    }
+}
+
+/// Unit of a task [`Duration`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DurationUnit {
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl DurationUnit {
+    fn millis(self) -> i64 {
+        match self {
+            DurationUnit::Millisecond => 1,
+            DurationUnit::Second => 1_000,
+            DurationUnit::Minute => 60_000,
+            DurationUnit::Hour => 3_600_000,
+            DurationUnit::Day => MILLIS_PER_DAY,
+            DurationUnit::Week => 7 * MILLIS_PER_DAY,
+            DurationUnit::Month => 30 * MILLIS_PER_DAY,
+            DurationUnit::Year => 365 * MILLIS_PER_DAY,
+        }
+    }
+}
+
+/// A task duration such as `3d` or `1.5h`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Duration {
+    pub amount: f64,
+    pub unit: DurationUnit,
+}
+
+impl Duration {
+    /// Parse a Mermaid duration: a non-negative number followed by `ms`, `s`,
+    /// `m`, `h`, `d`, `w`, `M` (months) or `y`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let split = text.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let (amount, unit) = text.split_at(split);
+        let unit = match unit {
+            "ms" => DurationUnit::Millisecond,
+            "s" => DurationUnit::Second,
+            "m" => DurationUnit::Minute,
+            "h" => DurationUnit::Hour,
+            "d" => DurationUnit::Day,
+            "w" => DurationUnit::Week,
+            "M" => DurationUnit::Month,
+            "y" => DurationUnit::Year,
+            _ => return None,
+        };
+        let amount = amount.parse().ok()?;
+        Some(Duration { amount, unit })
+    }
+}
+
+/// Parse `text` with a Day.js `format`, as Mermaid reads `dateFormat`
+///
+/// Supported tokens are `YYYY`, `YY`, `MMMM`, `MMM`, `MM`, `M`, `DD`, `D`,
+/// `HH`, `H`, `mm`, `m`, `ss`, `s`, `SSS`, `X` (Unix seconds) and `x` (Unix
+/// milliseconds); text in `[...]` and any other character must appear as
+/// written. Returns `None` for text that does not match or names a date that
+/// does not exist.
+pub fn parse_date(text: &str, format: &str) -> Option<DateTime> {
+    let text = text.trim();
+    let format = format.trim();
+    let (mut year, mut month, mut day) = (1970, 1, 1);
+    let (mut hour, mut minute, mut second, mut millisecond) = (0, 0, 0, 0);
+    let mut input = text;
+    let mut rest = format;
+
+    while !rest.is_empty() {
+        if let Some(literal) = rest.strip_prefix('[') {
+            let end = literal.find(']')?;
+            input = input.strip_prefix(&literal[..end])?;
+            rest = &literal[end + 1..];
+            continue;
+        }
+        let token = DATE_TOKENS
+            .iter()
+            .find(|token| rest.starts_with(**token))
+            .copied();
+        let Some(token) = token else {
+            let literal = rest.chars().next()?;
+            input = input.strip_prefix(literal)?;
+            rest = &rest[literal.len_utf8()..];
+            continue;
+        };
+        rest = &rest[token.len()..];
+
+        match token {
+            "X" | "x" => {
+                let (value, remaining) = take_number(input, 1, 15, true)?;
+                input = remaining;
+                let millis = if token == "X" { value * 1_000 } else { value };
+                let time = DateTime::from_timestamp_millis(millis);
+                (year, month, day) = (time.date.year, time.date.month, time.date.day);
+                (hour, minute, second, millisecond) =
+                    (time.hour, time.minute, time.second, time.millisecond);
+            }
+            "MMMM" | "MMM" => {
+                let lower = input.to_lowercase();
+                let (index, length) =
+                    MONTH_NAMES.iter().enumerate().find_map(|(index, name)| {
+                        if token == "MMMM" {
+                            lower.starts_with(name).then_some((index, name.len()))
+                        } else {
+                            lower.starts_with(&name[..3]).then_some((index, 3))
+                        }
+                    })?;
+                month = index as u32 + 1;
+                input = &input[length..];
+            }
+            _ => {
+                let (min, max) = match token {
+                    "YYYY" => (4, 4),
+                    "YY" => (2, 2),
+                    "SSS" => (1, 3),
+                    _ => (1, 2),
+                };
+                let (value, remaining) = take_number(input, min, max, false)?;
+                input = remaining;
+                match token {
+                    "YYYY" => year = value as i32,
+                    "YY" => year = if value > 68 { 1900 } else { 2000 } + value as i32,
+                    "MM" | "M" => month = value as u32,
+                    "DD" | "D" => day = value as u32,
+                    "HH" | "H" => hour = value as u32,
+                    "mm" | "m" => minute = value as u32,
+                    "ss" | "s" => second = value as u32,
+                    _ => millisecond = value as u32,
+                }
+            }
+        }
+    }
+
+    let valid_time = hour < 24 && minute < 60 && second < 60 && millisecond < 1_000;
+    if !input.is_empty() || !valid_time || month == 0 || day == 0 {
+        return None;
+    }
+    (day <= days_in_month(year, month)).then_some(DateTime {
+        date: CalendarDate { year, month, day },
+        hour,
+        minute,
+        second,
+        millisecond,
+    })
+}
+
+/// Day.js format tokens, longest first so that `MMMM` is not read as `MM`
+const DATE_TOKENS: &[&str] = &[
+    "YYYY", "MMMM", "SSS", "MMM", "YY", "MM", "DD", "HH", "mm", "ss", "M", "D", "H", "m", "s", "X",
+    "x",
+];
+
+/// Read between `min` and `max` digits from the start of `input`
+fn take_number(input: &str, min: usize, max: usize, signed: bool) -> Option<(i64, &str)> {
+    let sign = usize::from(signed && input.starts_with('-'));
+    let digits = input[sign..]
+        .chars()
+        .take(max)
+        .take_while(char::is_ascii_digit)
+        .count();
+    if digits < min {
+        return None;
+    }
+    let end = sign + digits;
+    Some((input[..end].parse().ok()?, &input[end..]))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        1..=12 => 31,
+        _ => 0,
+    }
+}
+
+/// A Gantt task with its resolved start and end times
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedTask {
+    pub name: String,
+    pub section: String,
+    pub id: Option<String>,
+    pub status: TaskStatus,
+    /// `None` when neither a start date nor a resolvable predecessor is known
+    pub start: Option<DateTime>,
+    /// Exclusive end time, after exclusions; equal to `start` for milestones
+    pub end: Option<DateTime>,
+    /// Ids of the tasks named by `after`, one per id
+    pub dependencies: Vec<String>,
+}
+
+impl ResolvedTask {
+    /// Time between start and end, in milliseconds
+    pub fn duration_millis(&self) -> Option<i64> {
+        let (start, end) = (self.start?, self.end?);
+        Some(end.timestamp_millis() - start.timestamp_millis())
+    }
+}
+
+/// Resolve the start and end of every task of `diagram`, in document order
+pub fn resolve_schedule(diagram: &GanttDiagram) -> Vec<ResolvedTask> {
+    let format = diagram
+        .date_format
+        .as_deref()
+        .unwrap_or(DEFAULT_DATE_FORMAT);
+    let calendar = WorkingCalendar::new(diagram, format);
+    let mut tasks: Vec<ResolvedTask> = diagram
+        .sections
+        .iter()
+        .flat_map(|section| {
+            section.tasks.iter().map(|task| ResolvedTask {
+                name: task.name.clone(),
+                section: section.name.clone(),
+                id: task.id.clone(),
+                status: task.status.clone(),
+                start: None,
+                end: None,
+                dependencies: task
+                    .dependencies
+                    .iter()
+                    .flat_map(|dependency| dependency.split_whitespace())
+                    .map(str::to_string)
+                    .collect(),
+            })
+        })
+        .collect();
+    let sources: Vec<_> = diagram
+        .sections
+        .iter()
+        .flat_map(|section| &section.tasks)
+        .collect();
+    let index: HashMap<&str, usize> = sources
+        .iter()
+        .enumerate()
+        .filter_map(|(position, task)| Some((task.id.as_deref()?, position)))
+        .collect();
+
+    // Tasks may refer to tasks defined later, so resolve until nothing changes
+    let mut progress = true;
+    while progress {
+        progress = false;
+        for position in 0..tasks.len() {
+            if tasks[position].end.is_some() {
+                continue;
+            }
+            let task = sources[position];
+
+            let start = match &task.start_date {
+                Some(date) => parse_date(date, format),
+                None if !tasks[position].dependencies.is_empty() => tasks[position]
+                    .dependencies
+                    .iter()
+                    .map(|id| index.get(id.as_str()).and_then(|&other| tasks[other].end))
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|ends| ends.into_iter().max()),
+                None if position == 0 => None,
+                None => tasks[position - 1].end,
+            };
+            let Some(start) = start else {
+                continue;
+            };
+            tasks[position].start = Some(start);
+
+            let end = match task.duration.as_deref().map(str::trim) {
+                _ if task.status == TaskStatus::Milestone => Some(start),
+                Some(until) if until.starts_with("until ") => until[6..]
+                    .split_whitespace()
+                    .map(|id| index.get(id).and_then(|&other| tasks[other].start))
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|starts| starts.into_iter().min()),
+                Some(text) => match parse_date(text, format) {
+                    Some(end) if diagram.inclusive_end_dates => Some(end.add(ONE_DAY)),
+                    Some(end) => Some(end),
+                    None => Duration::parse(text)
+                        .map(|duration| calendar.skip_excluded(start, start.add(duration))),
+                },
+                None => None,
+            };
+            if end.is_some() {
+                tasks[position].end = end;
+                progress = true;
+            }
+        }
+    }
+
+    tasks
+}
+
+/// Days excluded from the working calendar by `excludes`, less `includes`
+struct WorkingCalendar {
+    excluded_weekdays: Vec<Weekday>,
+    excluded_dates: Vec<CalendarDate>,
+    included_dates: Vec<CalendarDate>,
+}
+
+impl WorkingCalendar {
+    fn new(diagram: &GanttDiagram, format: &str) -> Self {
+        let words = |entries: &[String]| -> Vec<String> {
+            entries
+                .iter()
+                .flat_map(|entry| entry.split(|c: char| c == ',' || c.is_whitespace()))
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let dates = |words: &[String]| -> Vec<CalendarDate> {
+            words
+                .iter()
+                .filter_map(|word| {
+                    parse_date(word, format)
+                        .or_else(|| parse_date(word, DEFAULT_DATE_FORMAT))
+                        .map(|time| time.date)
+                })
+                .collect()
+        };
+        let excludes = words(&diagram.excludes);
+
+        let mut excluded_weekdays = Vec::new();
+        if excludes
+            .iter()
+            .any(|word| word.eq_ignore_ascii_case("weekends"))
+        {
+            match diagram.weekdays.weekend.first() {
+                Some(Weekday::Friday) => {
+                    excluded_weekdays.extend([Weekday::Friday, Weekday::Saturday])
+                }
+                _ => excluded_weekdays.extend([Weekday::Saturday, Weekday::Sunday]),
+            }
+        }
+        for word in &excludes {
+            let weekday = WEEKDAYS
+                .iter()
+                .find(|(name, _)| word.eq_ignore_ascii_case(name));
+            if let Some((_, weekday)) = weekday {
+                excluded_weekdays.push(weekday.clone());
+            }
+        }
+
+        WorkingCalendar {
+            excluded_weekdays,
+            excluded_dates: dates(&excludes),
+            included_dates: dates(&words(&diagram.includes)),
+        }
+    }
+
+    fn is_excluded(&self, time: DateTime) -> bool {
+        if self.included_dates.contains(&time.date) {
+            return false;
+        }
+        self.excluded_weekdays.contains(&time.weekday()) || self.excluded_dates.contains(&time.date)
+    }
+
+    /// Push `end` back a day for each excluded day from `start` to `end`
+    fn skip_excluded(&self, start: DateTime, mut end: DateTime) -> DateTime {
+        if self.excluded_weekdays.is_empty() && self.excluded_dates.is_empty() {
+            return end;
+        }
+        let mut current = start;
+        while current <= end {
+            if self.is_excluded(current) {
+                end = end.add(ONE_DAY);
+            }
+            current = current.add(ONE_DAY);
+        }
+        end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_formats() {
+        let date = |text, format| parse_date(text, format).map(|time| time.to_string());
+        assert_eq!(
+            date("2024-03-01", "YYYY-MM-DD").as_deref(),
+            Some("2024-03-01T00:00:00")
+        );
+        assert_eq!(
+            date("01/03/24 14:30", "DD/MM/YY HH:mm").as_deref(),
+            Some("2024-03-01T14:30:00")
+        );
+        assert_eq!(
+            date("Mar 1 2024", "MMM D YYYY").as_deref(),
+            Some("2024-03-01T00:00:00")
+        );
+        assert_eq!(
+            date("1709251200", "X").as_deref(),
+            Some("2024-03-01T00:00:00")
+        );
+        assert_eq!(date("2023-02-29", "YYYY-MM-DD"), None);
+        assert_eq!(date("2024-03-01", "DD-MM-YYYY"), None);
+    }
+
+    #[test]
+    fn test_durations() {
+        let start = parse_date("2024-01-31", DEFAULT_DATE_FORMAT).unwrap();
+        let end = |text| start.add(Duration::parse(text).unwrap()).to_string();
+        assert_eq!(end("1.5h"), "2024-01-31T01:30:00");
+        assert_eq!(end("2w"), "2024-02-14T00:00:00");
+        assert_eq!(end("1M"), "2024-02-29T00:00:00");
+        assert_eq!(end("1y"), "2025-01-31T00:00:00");
+        assert_eq!(end("250ms"), "2024-01-31T00:00:00.250");
+        assert_eq!(Duration::parse("3 days"), None);
+        assert_eq!(start.weekday(), Weekday::Wednesday);
+    }
+}
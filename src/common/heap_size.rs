@@ -0,0 +1,498 @@
+//! Estimated heap memory of a parsed diagram
+//!
+//! [`DiagramType::estimated_heap_size`] walks the AST and adds up the memory
+//! its strings and collections own on the heap. Services that cache many
+//! parsed diagrams can use it to enforce a memory budget, and it shows where
+//! the bytes of a large diagram go before reaching for interning.
+//!
+//! The estimate counts allocated capacity rather than length, so spare
+//! capacity left over from parsing is included. Hash maps are counted by
+//! their buckets plus one control byte each, and B-tree maps by their
+//! entries, which undercounts their node overhead. The inline size of the
+//! diagram itself is not included; add `size_of::<DiagramType>()` for the
+//! full footprint.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::heap_size::HeapSize;
+//! use mermaid_parser::parse_diagram;
+//! use mermaid_parser::DiagramType;
+//!
+//! let small = parse_diagram("flowchart TD\n    A --> B")?;
+//! let large = parse_diagram("flowchart TD\n    A[Start] --> B[Middle]\n    B --> C[End]")?;
+//! assert!(small.estimated_heap_size() < large.estimated_heap_size());
+//!
+//! // Parts of a diagram can be measured on their own
+//! if let DiagramType::Flowchart(flowchart) = &large {
+//!     assert!(flowchart.nodes.heap_size() < large.estimated_heap_size());
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::*;
+use crate::common::frontmatter::ConfigValue;
+use crate::common::ordered_map::OrderedMap;
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
+
+/// Memory a value owns on the heap, not counting the value itself
+pub trait HeapSize {
+    /// Estimated heap bytes owned by this value and everything it contains
+    fn heap_size(&self) -> usize;
+}
+
+impl DiagramType {
+    /// Estimated bytes the diagram owns on the heap
+    ///
+    /// See the [module documentation](crate::common::heap_size) for what the
+    /// estimate covers.
+    pub fn estimated_heap_size(&self) -> usize {
+        self.heap_size()
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (size_of::<(K, V)>() + 1)
+            + self
+                .iter()
+                .map(|(key, value)| key.heap_size() + value.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for OrderedMap<K, V> {
+    fn heap_size(&self) -> usize {
+        // Each key is stored twice: with its entry and in the position index
+        self.capacity() * (size_of::<(K, V)>() + size_of::<(K, usize)>() + 1)
+            + self
+                .iter()
+                .map(|(key, value)| 2 * key.heap_size() + value.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<K: HeapSize, V: HeapSize> HeapSize for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        self.iter()
+            .map(|(key, value)| size_of::<(K, V)>() + key.heap_size() + value.heap_size())
+            .sum()
+    }
+}
+
+/// Types that own nothing on the heap
+macro_rules! no_heap {
+    ($($ty:ty),* $(,)?) => {
+        $(impl HeapSize for $ty {
+            fn heap_size(&self) -> usize {
+                0
+            }
+        })*
+    };
+}
+
+/// Structs whose heap memory is that of their fields
+macro_rules! heap_size_fields {
+    ($($ty:ident { $($field:ident),* $(,)? })*) => {
+        $(impl HeapSize for $ty {
+            fn heap_size(&self) -> usize {
+                [$(self.$field.heap_size()),*].into_iter().sum()
+            }
+        })*
+    };
+}
+
+// Numbers, flags and enums without data
+no_heap! {
+    bool, f32, f64, i32, u32, usize, ParticipantType, MessageActivation, ArrowType, NotePosition,
+    Visibility, ClassRelationshipType, StateVersion, StateType, StateNotePosition,
+    FlowDirection, ImagePosition, EdgeType, TaskStatus, Weekday, CommitType, CheckoutKeyword,
+    KeyType, CardinalityValue, C4DiagramType, C4ElementType, C4BoundaryType, C4TagKind,
+    C4RelationshipDirection, MindmapNodeShape, ChartOrientation, SeriesType, BlockArrowType,
+    ArchDirection, Port, ArchEdgeType, RequirementType, RiskLevel, VerificationMethod,
+    RelationshipType,
+}
+
+heap_size_fields! {
+    AccessibilityInfo { title, description }
+    SankeyDiagram { nodes, links }
+    SankeyNode { id, name, metadata }
+    SankeyLink { source, target, value }
+    TimelineDiagram { title, accessibility, sections }
+    TimelineSection { name, items }
+    JourneyDiagram { title, accessibility, sections }
+    JourneySection { name, tasks }
+    JourneyTask { name, score, actors }
+    SequenceDiagram { title, accessibility, participants, statements, boxes, autonumber }
+    ParticipantBox { color, title, participants }
+    Participant { actor, alias, participant_type }
+    Message { from, to, text, arrow_type, activation }
+    Note { position, actor, text }
+    Loop { condition, statements }
+    Alternative { condition, statements, else_branch }
+    ElseBranch { condition, statements }
+    Optional { condition, statements }
+    Break { condition, statements }
+    Parallel { branches }
+    ParallelBranch { condition, statements }
+    Critical { condition, statements, options }
+    CriticalOption { condition, statements }
+    AutoNumber { start, step, visible }
+    ClassDiagram { title, accessibility, direction, classes, relationships, notes, namespaces, styles, clicks }
+    Class { name, generics, stereotype, members, annotations, css_class }
+    Property { name, prop_type, generics, visibility, is_static, default_value }
+    Method { name, parameters, return_type, generics, visibility, is_static, is_abstract }
+    Parameter { name, param_type }
+    ClassRelationship { from, to, relationship_type, from_cardinality, to_cardinality, label }
+    StateDiagram { title, accessibility, version, direction, states, transitions, notes, class_defs }
+    State { id, display_name, state_type, states, transitions, notes, concurrent_regions, classes }
+    StateRegion { states, transitions, notes }
+    StateTransition { from, to, event, guard, action }
+    StateNote { position, target, text }
+    FlowchartDiagram { title, accessibility, direction, nodes, edges, subgraphs, styles, class_defs, clicks }
+    FlowNode { id, text, shape, classes, icon }
+    FlowEdge { from, to, edge_type, label, min_length }
+    Subgraph { id, title, nodes, edges, subgraphs, direction }
+    StyleDefinition { target, styles }
+    ClassDef { name, styles, definitions }
+    ClickEvent { node_id, action }
+    GanttDiagram { title, accessibility, date_format, axis_format, tick_interval, includes, excludes, today_marker, inclusive_end_dates, top_axis, display_mode, weekdays, sections }
+    GanttSection { name, tasks }
+    GanttTask { name, id, start_date, duration, dependencies, status, progress, interactions }
+    WeekdaySettings { start_day, weekend }
+    PieDiagram { title, accessibility, show_data, data }
+    PieSlice { label, value }
+    GitDiagram { title, accessibility, theme, commits, branches, operations }
+    GitCommit { id, commit_type, tag, branch }
+    GitBranch { name, order, color }
+    ErDiagram { title, accessibility, entities, relationships, styles, class_defs }
+    Entity { name, attributes, css_class, display_name }
+    Attribute { name, attr_type, key_type, comment }
+    ErRelationship { left_entity, right_entity, left_cardinality, right_cardinality, label }
+    ErCardinality { min, max }
+    C4Diagram { diagram_type, title, accessibility, elements, boundaries, relationships, tags }
+    C4Element { id, element_type, name, description, technology, sprite, tags, is_external }
+    C4Boundary { id, boundary_type, label, tags, elements, boundaries }
+    C4Relationship { from, to, label, technology, direction, is_bidirectional, tags }
+    C4TagDefinition { name, kind, properties }
+    MindmapDiagram { title, accessibility, root, cross_edges }
+    MindmapNode { id, text, shape, icon, class, children }
+    MindmapCrossEdge { from, to, label }
+    QuadrantDiagram { title, accessibility, x_axis, y_axis, quadrants, points, styles }
+    AxisDefinition { label_start, label_end }
+    QuadrantLabels { quadrant_1, quadrant_2, quadrant_3, quadrant_4 }
+    DataPoint { name, x, y, class }
+    ClassDefinition { name, styles }
+    XyChartDiagram { title, accessibility, orientation, x_axis, y_axis, data_series }
+    XAxis { title, labels, range }
+    YAxis { title, range }
+    DataSeries { series_type, name, data, color }
+    KanbanDiagram { title, accessibility, sections }
+    KanbanSection { id, title, items }
+    KanbanItem { id, text, assigned, metadata }
+    BlockDiagram { title, accessibility, columns, blocks, connections, styles }
+    BlockConnection { from, to, label, arrow_type, style }
+    BlockStyleDefinition { target, properties }
+    BlockStyleProperty { name, value }
+    ArchitectureDiagram { title, accessibility, direction, services, groups, junctions, edges }
+    Service { id, icon, title, in_group }
+    Group { id, icon, title, in_group }
+    Junction { id, in_group }
+    ArchEdge { from, to, label, edge_type, legacy_arrow }
+    EdgeEndpoint { id, port }
+    PacketDiagram { title, accessibility, fields }
+    PacketField { start_bit, end_bit, name, is_optional }
+    RequirementDiagram { title, accessibility, requirements, elements, relationships, styles }
+    Requirement { name, req_type, id, text, risk, verify_method }
+    Element { name, element_type, doc_ref }
+    RequirementRelationship { source, target, relationship_type }
+    TreemapDiagram { title, accessibility, root }
+    TreemapNode { name, value, children }
+    RadarDiagram { title, accessibility, config, axes, datasets }
+    RadarConfig { background_color, grid_color, scale_max, scale_min }
+    Dataset { name, values }
+    MiscDiagram { diagram_type, content }
+    InfoDiagram { command, options }
+    GitGraphAlt { commits }
+    MiscGitCommit { action, params }
+    RawDiagram { lines }
+}
+
+impl HeapSize for DiagramType {
+    fn heap_size(&self) -> usize {
+        match self {
+            DiagramType::Sankey(value) => value.heap_size(),
+            DiagramType::Timeline(value) => value.heap_size(),
+            DiagramType::Journey(value) => value.heap_size(),
+            DiagramType::Sequence(value) => value.heap_size(),
+            DiagramType::Class(value) => value.heap_size(),
+            DiagramType::State(value) => value.heap_size(),
+            DiagramType::Flowchart(value) => value.heap_size(),
+            DiagramType::Gantt(value) => value.heap_size(),
+            DiagramType::Pie(value) => value.heap_size(),
+            DiagramType::Git(value) => value.heap_size(),
+            DiagramType::Er(value) => value.heap_size(),
+            DiagramType::C4(value) => value.heap_size(),
+            DiagramType::Mindmap(value) => value.heap_size(),
+            DiagramType::Quadrant(value) => value.heap_size(),
+            DiagramType::XyChart(value) => value.heap_size(),
+            DiagramType::Kanban(value) => value.heap_size(),
+            DiagramType::Block(value) => value.heap_size(),
+            DiagramType::Architecture(value) => value.heap_size(),
+            DiagramType::Packet(value) => value.heap_size(),
+            DiagramType::Requirement(value) => value.heap_size(),
+            DiagramType::Treemap(value) => value.heap_size(),
+            DiagramType::Radar(value) => value.heap_size(),
+            DiagramType::Misc(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for Color {
+    fn heap_size(&self) -> usize {
+        match self {
+            Color::Named(text) | Color::Hex(text) | Color::Function(text) | Color::Other(text) => {
+                text.heap_size()
+            }
+            Color::Transparent => 0,
+        }
+    }
+}
+
+impl HeapSize for TimelineItem {
+    fn heap_size(&self) -> usize {
+        match self {
+            TimelineItem::Period(value) => value.heap_size(),
+            TimelineItem::Event(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for SequenceStatement {
+    fn heap_size(&self) -> usize {
+        match self {
+            SequenceStatement::Message(value) => value.heap_size(),
+            SequenceStatement::Note(value) => value.heap_size(),
+            SequenceStatement::Loop(value) => value.heap_size(),
+            SequenceStatement::Alt(value) => value.heap_size(),
+            SequenceStatement::Opt(value) => value.heap_size(),
+            SequenceStatement::Break(value) => value.heap_size(),
+            SequenceStatement::Par(value) => value.heap_size(),
+            SequenceStatement::Critical(value) => value.heap_size(),
+            SequenceStatement::Rect { color, statements } => {
+                color.heap_size() + statements.heap_size()
+            }
+            SequenceStatement::Activate(value) => value.heap_size(),
+            SequenceStatement::Deactivate(value) => value.heap_size(),
+            SequenceStatement::Create(value) => value.heap_size(),
+            SequenceStatement::Destroy(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for Stereotype {
+    fn heap_size(&self) -> usize {
+        match self {
+            Stereotype::Custom(value) => value.heap_size(),
+            Stereotype::Interface
+            | Stereotype::Abstract
+            | Stereotype::Service
+            | Stereotype::Enumeration
+            | Stereotype::Exception => 0,
+        }
+    }
+}
+
+impl HeapSize for ClassMember {
+    fn heap_size(&self) -> usize {
+        match self {
+            ClassMember::Property(value) => value.heap_size(),
+            ClassMember::Method(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for NodeShape {
+    fn heap_size(&self) -> usize {
+        match self {
+            NodeShape::Image {
+                url,
+                position,
+                width,
+                height,
+                constrained,
+            } => {
+                url.heap_size()
+                    + position.heap_size()
+                    + width.heap_size()
+                    + height.heap_size()
+                    + constrained.heap_size()
+            }
+            NodeShape::Special(value) => value.heap_size(),
+            NodeShape::Rectangle
+            | NodeShape::RoundedRectangle
+            | NodeShape::Stadium
+            | NodeShape::Subroutine
+            | NodeShape::Cylinder
+            | NodeShape::Circle
+            | NodeShape::Asymmetric
+            | NodeShape::Rhombus
+            | NodeShape::Hexagon
+            | NodeShape::Parallelogram
+            | NodeShape::ParallelogramAlt
+            | NodeShape::Trapezoid
+            | NodeShape::TrapezoidAlt
+            | NodeShape::DoubleCircle => 0,
+        }
+    }
+}
+
+impl HeapSize for NodeText {
+    fn heap_size(&self) -> usize {
+        match self {
+            NodeText::Plain(value) | NodeText::Markdown(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for StyleTarget {
+    fn heap_size(&self) -> usize {
+        match self {
+            StyleTarget::Node(value) => value.heap_size(),
+            StyleTarget::Edge(first, second) => first.heap_size() + second.heap_size(),
+            StyleTarget::Subgraph(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for ClickAction {
+    fn heap_size(&self) -> usize {
+        match self {
+            ClickAction::Href(first, second) => first.heap_size() + second.heap_size(),
+            ClickAction::Callback(value) => value.heap_size(),
+            ClickAction::Both(first, second) => first.heap_size() + second.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for TaskInteraction {
+    fn heap_size(&self) -> usize {
+        match self {
+            TaskInteraction::Click { task_id } => task_id.heap_size(),
+            TaskInteraction::Href { url } => url.heap_size(),
+            TaskInteraction::Call { function, args } => function.heap_size() + args.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for GitOperation {
+    fn heap_size(&self) -> usize {
+        match self {
+            GitOperation::Commit {
+                id,
+                commit_type,
+                tag,
+                explicit_type,
+            } => {
+                id.heap_size()
+                    + commit_type.heap_size()
+                    + tag.heap_size()
+                    + explicit_type.heap_size()
+            }
+            GitOperation::Branch { name, order } => name.heap_size() + order.heap_size(),
+            GitOperation::Checkout { branch, keyword } => branch.heap_size() + keyword.heap_size(),
+            GitOperation::Merge {
+                branch,
+                id,
+                tag,
+                commit_type,
+                explicit_type,
+            } => {
+                branch.heap_size()
+                    + id.heap_size()
+                    + tag.heap_size()
+                    + commit_type.heap_size()
+                    + explicit_type.heap_size()
+            }
+            GitOperation::CherryPick { id, parent, tag } => {
+                id.heap_size() + parent.heap_size() + tag.heap_size()
+            }
+        }
+    }
+}
+
+impl HeapSize for Block {
+    fn heap_size(&self) -> usize {
+        match self {
+            Block::Simple { id, label, shape } => {
+                id.heap_size() + label.heap_size() + shape.heap_size()
+            }
+            Block::Composite { id, label, blocks } => {
+                id.heap_size() + label.heap_size() + blocks.heap_size()
+            }
+            Block::Space { size } => size.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for BlockShape {
+    fn heap_size(&self) -> usize {
+        match self {
+            BlockShape::Custom(value) => value.heap_size(),
+            BlockShape::Rectangle
+            | BlockShape::RoundedRect
+            | BlockShape::Rhombus
+            | BlockShape::Circle
+            | BlockShape::Ellipse
+            | BlockShape::Cylinder => 0,
+        }
+    }
+}
+
+impl HeapSize for MiscContent {
+    fn heap_size(&self) -> usize {
+        match self {
+            MiscContent::Info(value) => value.heap_size(),
+            MiscContent::GitGraph(value) => value.heap_size(),
+            MiscContent::Raw(value) => value.heap_size(),
+        }
+    }
+}
+
+impl HeapSize for ConfigValue {
+    fn heap_size(&self) -> usize {
+        match self {
+            ConfigValue::Bool(value) => value.heap_size(),
+            ConfigValue::Number(value) => value.heap_size(),
+            ConfigValue::String(value) => value.heap_size(),
+            ConfigValue::List(value) => value.heap_size(),
+            ConfigValue::Map(value) => value.heap_size(),
+            ConfigValue::Null => 0,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Common lexing utilities
 
+use chumsky::prelude::*;
+
 /// Remove metadata comments from input (lines starting with //)
 pub fn strip_metadata_comments(input: &str) -> String {
     input
@@ -9,6 +11,23 @@ pub fn strip_metadata_comments(input: &str) -> String {
         .join("\n")
 }
 
+/// A double-quoted string, with `\"` read as a quote and `\\` as a backslash
+///
+/// Any other backslash is kept as written, so text such as `C:\temp` needs no
+/// escaping. [`crate::common::escape::escaped_quoted`] writes strings this
+/// lexer reads back unchanged.
+pub fn quoted_string<'src>(
+) -> impl Parser<'src, &'src str, String, extra::Err<Simple<'src, char>>> + Clone {
+    let escaped = just('\\').ignore_then(one_of("\"\\"));
+    just('"')
+        .ignore_then(
+            choice((escaped, none_of('"')))
+                .repeated()
+                .collect::<String>(),
+        )
+        .then_ignore(just('"'))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -25,4 +44,17 @@ Normal content"#;
         let expected = "timeline\ntitle My Timeline\nNormal content";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_quoted_string_escapes() {
+        let parse = |input: &str| quoted_string().parse(input).into_result().ok();
+        assert_eq!(parse(r#""plain""#).as_deref(), Some("plain"));
+        assert_eq!(
+            parse(r#""len \"short\"""#).as_deref(),
+            Some("len \"short\"")
+        );
+        assert_eq!(parse(r#""C:\temp\\""#).as_deref(), Some("C:\\temp\\"));
+        assert_eq!(parse("\"two\nlines\"").as_deref(), Some("two\nlines"));
+        assert_eq!(parse(r#""open"#), None);
+    }
 }
@@ -5,6 +5,8 @@
 //! detailed analysis and improvement suggestions.
 
 use crate::common::ast::*;
+use crate::common::path::AstPath;
+use crate::common::tokens::Span;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
 /// Trait for calculating diagram metrics
@@ -123,18 +125,32 @@ pub struct QualityMetrics {
 /// Improvement suggestion
 ///
 /// Actionable recommendation for improving diagram quality, structure, or readability.
-/// Each suggestion includes a category, descriptive message, and severity level.
+/// Each suggestion includes a category, descriptive message, and severity level, and
+/// may point at the element it concerns and where it appears in the source.
+///
+/// Linters outside this crate can build suggestions with [`Suggestion::new`] and add
+/// them to [`MetricsReport::suggestions`], so their findings are rendered with the
+/// built-in ones.
 ///
 /// # Example
 ///
 /// ```rust
 /// use mermaid_parser::common::metrics::{Suggestion, SuggestionCategory, SeverityLevel};
+/// use mermaid_parser::common::path::{AstPath, ElementKind};
+/// use mermaid_parser::common::tokens::Span;
+///
+/// let suggestion = Suggestion::new(
+///     SuggestionCategory::Custom("security".to_string()),
+///     "Node links to an http:// URL",
+///     SeverityLevel::Warning,
+/// )
+/// .with_target(AstPath::new(ElementKind::Node, "Login"))
+/// .with_span(Span::new(30, 52, 3, 5));
 ///
-/// let suggestion = Suggestion {
-///     category: SuggestionCategory::Structure,
-///     message: "Consider adding labels to improve clarity".to_string(),
-///     severity: SeverityLevel::Warning,
-/// };
+/// assert_eq!(
+///     suggestion.to_string(),
+///     "⚠️ [security] node/Login at 3:5: Node links to an http:// URL"
+/// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct Suggestion {
@@ -144,6 +160,10 @@ pub struct Suggestion {
     pub message: String,
     /// Severity level indicating importance of the suggestion
     pub severity: SeverityLevel,
+    /// Element the suggestion is about, if it concerns a single one
+    pub target: Option<AstPath>,
+    /// Source location of the element or construct, if known
+    pub span: Option<Span>,
 }
 
 /// Suggestion categories
@@ -153,6 +173,8 @@ pub enum SuggestionCategory {
     Structure,
     Naming,
     Organization,
+    /// A category defined by a downstream linter, displayed as given
+    Custom(String),
 }
 
 /// Severity levels for suggestions
@@ -191,13 +213,7 @@ impl Display for MetricsReport {
         if !self.suggestions.is_empty() {
             writeln!(f, "\nSuggestions:")?;
             for suggestion in &self.suggestions {
-                writeln!(
-                    f,
-                    "- {} [{}]: {}",
-                    suggestion.severity_symbol(),
-                    suggestion.category,
-                    suggestion.message
-                )?;
+                writeln!(f, "- {}", suggestion)?;
             }
         }
 
@@ -212,11 +228,52 @@ impl Display for SuggestionCategory {
             SuggestionCategory::Structure => write!(f, "Structure"),
             SuggestionCategory::Naming => write!(f, "Naming"),
             SuggestionCategory::Organization => write!(f, "Organization"),
+            SuggestionCategory::Custom(name) => write!(f, "{}", name),
         }
     }
 }
 
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{} [{}]", self.severity_symbol(), self.category)?;
+        if let Some(target) = &self.target {
+            write!(f, " {}", target)?;
+        }
+        if let Some(span) = &self.span {
+            write!(f, " at {}:{}", span.line, span.column)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
 impl Suggestion {
+    /// A suggestion about the diagram as a whole
+    pub fn new(
+        category: SuggestionCategory,
+        message: impl Into<String>,
+        severity: SeverityLevel,
+    ) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            severity,
+            target: None,
+            span: None,
+        }
+    }
+
+    /// The suggestion, about the element at `target`
+    pub fn with_target(mut self, target: AstPath) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// The suggestion, located at `span` in the source
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
     fn severity_symbol(&self) -> &str {
         match self.severity {
             SeverityLevel::Info => "ℹ️",
@@ -372,8 +429,8 @@ impl DiagramMetrics for ClassDiagram {
 impl DiagramMetrics for StateDiagram {
     fn calculate_metrics(&self) -> MetricsReport {
         let basic = BasicMetrics {
-            node_count: self.states.len(),
-            edge_count: self.transitions.len(),
+            node_count: self.all_states().len(),
+            edge_count: self.all_transitions().len(),
             depth: calculate_state_depth(self),
             breadth: self.states.len(),
         };
@@ -402,6 +459,244 @@ impl DiagramMetrics for StateDiagram {
     }
 }
 
+/// Shape of a tree-structured diagram such as a mindmap or treemap
+///
+/// Levels count from the root at level 0.
+///
+/// ```rust
+/// use mermaid_parser::common::metrics::HierarchyMetrics;
+/// use mermaid_parser::DiagramType;
+///
+/// let input = "mindmap\n  root\n    A\n      A1\n    B";
+/// if let DiagramType::Mindmap(mindmap) = mermaid_parser::parse_diagram(input)? {
+///     let hierarchy = HierarchyMetrics::from(&mindmap);
+///     assert_eq!(hierarchy.depth_distribution, vec![1, 2, 1]);
+///     assert_eq!(hierarchy.max_depth(), 2);
+/// }
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchyMetrics {
+    /// Number of nodes at each level
+    pub depth_distribution: Vec<usize>,
+    /// Average number of children of the non-leaf nodes at each level
+    pub branching_factor: Vec<f64>,
+    /// Most children under a single parent at each level
+    pub max_children: Vec<usize>,
+    /// Shallowest leaf level divided by deepest, both counted from 1; 1.0 when
+    /// every leaf is at the same level
+    pub balance: f64,
+    /// Leaves per internal node; the leaf count when the root is the only node
+    pub leaf_internal_ratio: f64,
+}
+
+impl HierarchyMetrics {
+    /// Level of the deepest node
+    pub fn max_depth(&self) -> usize {
+        self.depth_distribution.len().saturating_sub(1)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.depth_distribution.iter().sum()
+    }
+
+    fn from_tree<N>(root: &N, children: fn(&N) -> &[N]) -> Self {
+        let mut depth_distribution = Vec::new();
+        let mut internal_counts = Vec::new();
+        let mut child_counts = Vec::new();
+        let mut max_children = Vec::new();
+        let mut leaf_levels = Vec::new();
+
+        let mut stack = vec![(root, 0)];
+        while let Some((node, level)) = stack.pop() {
+            if depth_distribution.len() <= level {
+                depth_distribution.resize(level + 1, 0);
+                internal_counts.resize(level + 1, 0);
+                child_counts.resize(level + 1, 0);
+                max_children.resize(level + 1, 0);
+            }
+            depth_distribution[level] += 1;
+
+            let node_children = children(node);
+            if node_children.is_empty() {
+                leaf_levels.push(level);
+            } else {
+                internal_counts[level] += 1;
+                child_counts[level] += node_children.len();
+                max_children[level] = max_children[level].max(node_children.len());
+                stack.extend(node_children.iter().map(|child| (child, level + 1)));
+            }
+        }
+
+        let branching_factor = child_counts
+            .iter()
+            .zip(&internal_counts)
+            .map(|(&children, &internal)| {
+                if internal == 0 {
+                    0.0
+                } else {
+                    children as f64 / internal as f64
+                }
+            })
+            .collect();
+
+        let shallowest = leaf_levels.iter().min().copied().unwrap_or(0);
+        let deepest = leaf_levels.iter().max().copied().unwrap_or(0);
+        let balance = (shallowest + 1) as f64 / (deepest + 1) as f64;
+
+        let internal: usize = internal_counts.iter().sum();
+        let leaf_internal_ratio = if internal == 0 {
+            leaf_levels.len() as f64
+        } else {
+            leaf_levels.len() as f64 / internal as f64
+        };
+
+        HierarchyMetrics {
+            depth_distribution,
+            branching_factor,
+            max_children,
+            balance,
+            leaf_internal_ratio,
+        }
+    }
+}
+
+impl From<&MindmapDiagram> for HierarchyMetrics {
+    fn from(diagram: &MindmapDiagram) -> Self {
+        HierarchyMetrics::from_tree(&diagram.root, |node| node.children.as_slice())
+    }
+}
+
+impl From<&TreemapDiagram> for HierarchyMetrics {
+    fn from(diagram: &TreemapDiagram) -> Self {
+        HierarchyMetrics::from_tree(&diagram.root, |node| node.children.as_slice())
+    }
+}
+
+impl DiagramMetrics for MindmapDiagram {
+    fn calculate_metrics(&self) -> MetricsReport {
+        calculate_hierarchy_metrics(&HierarchyMetrics::from(self))
+    }
+}
+
+impl DiagramMetrics for TreemapDiagram {
+    fn calculate_metrics(&self) -> MetricsReport {
+        calculate_hierarchy_metrics(&HierarchyMetrics::from(self))
+    }
+}
+
+/// Satisfaction scores of a user journey, aggregated for trending
+///
+/// Averages cover the tasks that belong to each section or actor; sections
+/// without tasks and tasks without actors are left out of the respective list.
+///
+/// ```rust
+/// use mermaid_parser::DiagramType;
+///
+/// let input = "journey\n  section Commute\n    Catch bus: 2: Me\n    Read: 4: Me, Cat\n  section Home\n    Nap: 5: Cat";
+/// if let DiagramType::Journey(journey) = mermaid_parser::parse_diagram(input)? {
+///     let summary = journey.summary();
+///     assert_eq!(summary.overall, Some(11.0 / 3.0));
+///     assert_eq!(summary.sections[0].average, 3.0);
+///     assert_eq!(summary.lowest_tasks[0].name, "Catch bus");
+/// }
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JourneySummary {
+    /// Average over every task; `None` when the journey has no tasks
+    pub overall: Option<f64>,
+    /// Per section, in document order
+    pub sections: Vec<ScoreAverage>,
+    /// Per actor, in order of first appearance
+    pub actors: Vec<ScoreAverage>,
+    /// Every task sharing the lowest score, in document order
+    pub lowest_tasks: Vec<ScoredTask>,
+}
+
+/// Average score of a group of journey tasks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreAverage {
+    pub name: String,
+    pub average: f64,
+    pub task_count: usize,
+}
+
+/// A journey task together with the section it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoredTask {
+    pub section: String,
+    pub name: String,
+    pub score: i32,
+}
+
+impl JourneyDiagram {
+    /// Aggregate task scores per section, per actor and overall
+    pub fn summary(&self) -> JourneySummary {
+        let mut sections = Vec::new();
+        let mut actor_totals: Vec<(String, i64, usize)> = Vec::new();
+        let mut total = 0i64;
+        let mut count = 0usize;
+
+        for section in &self.sections {
+            let section_total: i64 = section.tasks.iter().map(|t| i64::from(t.score)).sum();
+            if !section.tasks.is_empty() {
+                sections.push(ScoreAverage {
+                    name: section.name.clone(),
+                    average: section_total as f64 / section.tasks.len() as f64,
+                    task_count: section.tasks.len(),
+                });
+            }
+            total += section_total;
+            count += section.tasks.len();
+
+            for task in &section.tasks {
+                for actor in &task.actors {
+                    match actor_totals.iter_mut().find(|(name, _, _)| name == actor) {
+                        Some((_, sum, tasks)) => {
+                            *sum += i64::from(task.score);
+                            *tasks += 1;
+                        }
+                        None => actor_totals.push((actor.clone(), i64::from(task.score), 1)),
+                    }
+                }
+            }
+        }
+
+        let lowest = self
+            .sections
+            .iter()
+            .flat_map(|section| &section.tasks)
+            .map(|task| task.score)
+            .min();
+        let lowest_tasks = self
+            .sections
+            .iter()
+            .flat_map(|section| section.tasks.iter().map(move |task| (section, task)))
+            .filter(|(_, task)| Some(task.score) == lowest)
+            .map(|(section, task)| ScoredTask {
+                section: section.name.clone(),
+                name: task.name.clone(),
+                score: task.score,
+            })
+            .collect();
+
+        JourneySummary {
+            overall: (count > 0).then(|| total as f64 / count as f64),
+            sections,
+            actors: actor_totals
+                .into_iter()
+                .map(|(name, sum, tasks)| ScoreAverage {
+                    name,
+                    average: sum as f64 / tasks as f64,
+                    task_count: tasks,
+                })
+                .collect(),
+            lowest_tasks,
+        }
+    }
+}
+
 // Implement for DiagramType enum
 impl DiagramMetrics for DiagramType {
     fn calculate_metrics(&self) -> MetricsReport {
@@ -411,6 +706,8 @@ impl DiagramMetrics for DiagramType {
             DiagramType::Sequence(d) => d.calculate_metrics(),
             DiagramType::Class(d) => d.calculate_metrics(),
             DiagramType::State(d) => d.calculate_metrics(),
+            DiagramType::Mindmap(d) => d.calculate_metrics(),
+            DiagramType::Treemap(d) => d.calculate_metrics(),
             // For other types, provide basic metrics
             _ => calculate_generic_metrics(self),
         }
@@ -479,6 +776,8 @@ fn generate_sankey_suggestions(
             category: SuggestionCategory::Complexity,
             message: "Consider grouping related nodes to reduce visual complexity".to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -487,6 +786,8 @@ fn generate_sankey_suggestions(
             category: SuggestionCategory::Structure,
             message: "High coupling detected. Consider breaking into smaller flows".to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -552,6 +853,8 @@ fn generate_flowchart_suggestions(
             message: "High cyclomatic complexity. Consider breaking into smaller flowcharts"
                 .to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -560,6 +863,8 @@ fn generate_flowchart_suggestions(
             category: SuggestionCategory::Structure,
             message: "Deep nesting detected. Consider flattening the structure".to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -569,6 +874,8 @@ fn generate_flowchart_suggestions(
             message: "Large diagram detected. Consider using subgraphs for organization"
                 .to_string(),
             severity: SeverityLevel::Info,
+            target: None,
+            span: None,
         });
     }
 
@@ -576,7 +883,7 @@ fn generate_flowchart_suggestions(
 }
 
 // Sequence diagram helper functions
-fn count_sequence_messages(statements: &[SequenceStatement]) -> usize {
+pub(crate) fn count_sequence_messages(statements: &[SequenceStatement]) -> usize {
     statements.iter().map(count_messages_in_statement).sum()
 }
 
@@ -598,6 +905,11 @@ fn count_messages_in_statement(statement: &SequenceStatement) -> usize {
             main_count + else_count
         }
         SequenceStatement::Opt(opt) => opt.statements.iter().map(count_messages_in_statement).sum(),
+        SequenceStatement::Break(block) => block
+            .statements
+            .iter()
+            .map(count_messages_in_statement)
+            .sum(),
         SequenceStatement::Par(par) => par
             .branches
             .iter()
@@ -628,6 +940,9 @@ fn count_messages_in_statement(statement: &SequenceStatement) -> usize {
                 .sum();
             main_count + option_count
         }
+        SequenceStatement::Rect { statements, .. } => {
+            statements.iter().map(count_messages_in_statement).sum()
+        }
         _ => 0,
     }
 }
@@ -679,6 +994,14 @@ fn calculate_statement_depth(statement: &SequenceStatement) -> usize {
                 .max()
                 .unwrap_or(0)
         }
+        SequenceStatement::Break(block) => {
+            1 + block
+                .statements
+                .iter()
+                .map(calculate_statement_depth)
+                .max()
+                .unwrap_or(0)
+        }
         SequenceStatement::Par(par) => {
             1 + par
                 .branches
@@ -716,6 +1039,13 @@ fn calculate_statement_depth(statement: &SequenceStatement) -> usize {
                 .unwrap_or(0);
             1 + main_depth.max(option_depth)
         }
+        SequenceStatement::Rect { statements, .. } => {
+            1 + statements
+                .iter()
+                .map(calculate_statement_depth)
+                .max()
+                .unwrap_or(0)
+        }
         _ => 1,
     }
 }
@@ -736,6 +1066,8 @@ fn generate_sequence_suggestions(
             message: "High message count. Consider breaking into smaller sequence diagrams"
                 .to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -745,6 +1077,8 @@ fn generate_sequence_suggestions(
             message: "Deep nesting in sequence blocks. Consider simplifying control flow"
                 .to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -773,6 +1107,8 @@ fn generate_class_suggestions(
             category: SuggestionCategory::Organization,
             message: "Large number of classes. Consider using packages or modules".to_string(),
             severity: SeverityLevel::Info,
+            target: None,
+            span: None,
         });
     }
 
@@ -781,6 +1117,8 @@ fn generate_class_suggestions(
             category: SuggestionCategory::Structure,
             message: "High coupling between classes. Consider reducing dependencies".to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         });
     }
 
@@ -809,6 +1147,8 @@ fn generate_state_suggestions(
             category: SuggestionCategory::Complexity,
             message: "Large state space. Consider using composite states".to_string(),
             severity: SeverityLevel::Info,
+            target: None,
+            span: None,
         });
     }
 
@@ -817,6 +1157,91 @@ fn generate_state_suggestions(
             category: SuggestionCategory::Structure,
             message: "High transition density. Consider simplifying state machine".to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
+        });
+    }
+
+    suggestions
+}
+
+// Mindmap and treemap helper functions
+fn calculate_hierarchy_metrics(hierarchy: &HierarchyMetrics) -> MetricsReport {
+    let node_count = hierarchy.node_count();
+    let basic = BasicMetrics {
+        node_count,
+        edge_count: node_count.saturating_sub(1),
+        depth: hierarchy.max_depth(),
+        breadth: hierarchy
+            .depth_distribution
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or(0),
+    };
+
+    let complexity = ComplexityMetrics {
+        cyclomatic: calculate_cyclomatic_complexity(basic.edge_count, basic.node_count),
+        cognitive: calculate_cognitive_complexity(&basic),
+        nesting_depth: hierarchy.max_depth(),
+        coupling: calculate_coupling(&basic),
+    };
+
+    let quality = QualityMetrics {
+        maintainability: calculate_maintainability(&basic, &complexity),
+        readability: calculate_readability(&basic, &complexity),
+        modularity: hierarchy.balance,
+    };
+
+    let suggestions = generate_hierarchy_suggestions(hierarchy);
+
+    MetricsReport {
+        basic,
+        complexity,
+        quality,
+        suggestions,
+    }
+}
+
+fn generate_hierarchy_suggestions(hierarchy: &HierarchyMetrics) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+
+    for (level, &children) in hierarchy.max_children.iter().enumerate() {
+        if children > 12 {
+            suggestions.push(Suggestion {
+                category: SuggestionCategory::Organization,
+                message: format!(
+                    "Consider splitting level {} nodes: {} children under one parent",
+                    level + 1,
+                    children
+                ),
+                severity: SeverityLevel::Warning,
+                target: None,
+                span: None,
+            });
+        }
+    }
+
+    if hierarchy.max_depth() > 6 {
+        suggestions.push(Suggestion {
+            category: SuggestionCategory::Structure,
+            message: format!(
+                "Deep hierarchy ({} levels). Consider flattening or splitting into separate diagrams",
+                hierarchy.max_depth()
+            ),
+            severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
+        });
+    }
+
+    if hierarchy.balance < 0.34 {
+        suggestions.push(Suggestion {
+            category: SuggestionCategory::Structure,
+            message: "Unbalanced hierarchy. Some branches are much deeper than others".to_string(),
+            severity: SeverityLevel::Info,
+            target: None,
+            span: None,
         });
     }
 
@@ -871,6 +1296,8 @@ fn generate_generic_suggestions(
             category: SuggestionCategory::Organization,
             message: "Consider organizing into smaller, focused diagrams".to_string(),
             severity: SeverityLevel::Info,
+            target: None,
+            span: None,
         });
     }
 
@@ -888,14 +1315,17 @@ mod tests {
                 SankeyNode {
                     id: "A".to_string(),
                     name: "Node A".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "B".to_string(),
                     name: "Node B".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "C".to_string(),
                     name: "Node C".to_string(),
+                    metadata: Default::default(),
                 },
             ],
             links: vec![
@@ -923,9 +1353,10 @@ mod tests {
 
     #[test]
     fn test_flowchart_metrics_calculation() {
+        use crate::common::ordered_map::OrderedMap;
         use std::collections::HashMap;
 
-        let mut nodes = HashMap::new();
+        let mut nodes = OrderedMap::new();
         nodes.insert(
             "A".to_string(),
             FlowNode {
@@ -955,7 +1386,7 @@ mod tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             }],
@@ -997,6 +1428,8 @@ mod tests {
                 category: SuggestionCategory::Complexity,
                 message: "Consider simplification".to_string(),
                 severity: SeverityLevel::Warning,
+                target: None,
+                span: None,
             }],
         };
 
@@ -1022,6 +1455,8 @@ mod tests {
             category: SuggestionCategory::Structure,
             message: "Info message".to_string(),
             severity: SeverityLevel::Info,
+            target: None,
+            span: None,
         };
         assert_eq!(info_suggestion.severity_symbol(), "ℹ️");
 
@@ -1029,6 +1464,8 @@ mod tests {
             category: SuggestionCategory::Complexity,
             message: "Warning message".to_string(),
             severity: SeverityLevel::Warning,
+            target: None,
+            span: None,
         };
         assert_eq!(warning_suggestion.severity_symbol(), "⚠️");
 
@@ -1036,6 +1473,8 @@ mod tests {
             category: SuggestionCategory::Naming,
             message: "Error message".to_string(),
             severity: SeverityLevel::Error,
+            target: None,
+            span: None,
         };
         assert_eq!(error_suggestion.severity_symbol(), "❌");
     }
@@ -1062,7 +1501,9 @@ mod tests {
                 to: "Bob".to_string(),
                 text: "Hello".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             })],
+            boxes: vec![],
             autonumber: None,
         };
 
@@ -1079,6 +1520,7 @@ mod tests {
             nodes: vec![SankeyNode {
                 id: "A".to_string(),
                 name: "Node A".to_string(),
+                metadata: Default::default(),
             }],
             links: vec![],
         });
@@ -5,15 +5,43 @@
 //!
 //! ## Module Overview
 //!
+//! - [`annotations`] - External metadata attached to diagram elements by path
+//! - [`arrow_table`] - Arrow spellings of every diagram type with their canonical forms
+//! - [`ast_graph`] - Visualization of a parsed AST as a flowchart
 //! - [`ast`] - Abstract Syntax Tree definitions for all diagram types
+//! - [`calendar`] - Export of Gantt charts to iCalendar and CSV
+//! - [`chart_range`] - Rescaling of chart data that has drifted out of range
+//! - [`collapse`] - Overview flowcharts with subgraphs collapsed into single nodes
+//! - [`deprecation`] - Detection and upgrade of legacy syntax
+//! - [`direction`] - Detection of conflicting layout direction statements
+//! - [`er_stub`] - ER diagram skeletons generated from persistence classes
+//! - [`escape`] - Escaping of text for each quoting context of the printers
+//! - [`events`] - Event-based parsing for single-pass extraction without keeping an AST
+//! - [`fix`] - Automatic fixes for a subset of validation diagnostics
+//! - [`frontmatter`] - YAML frontmatter and typed per-diagram configuration
+//! - [`gantt`] - Resolution of Gantt task dates, dependencies and excluded days
+//! - [`heap_size`] - Estimated heap memory of a parsed diagram
 //! - [`lexer`] - Lexical analysis components for tokenizing input
 //! - [`metrics`] - Diagram complexity analysis and quality assessment
+//! - [`normalize`] - Splitting of `;` separated statements with a map back to the source
+//! - [`ordered_map`] - A map that keeps the declaration order of AST elements
 //! - [`parser_utils`] - Shared parsing utilities and helpers
 //! - [`parsing`] - Comprehensive parsing utilities for common patterns
+//! - [`path`] - Stable addresses for diagram elements, including synthetic ids
 //! - [`pretty_print`] - Pretty-printing utilities for formatting output
+//! - [`stream`] - Incremental parsing of line-oriented diagrams from a reader
+//! - [`structurizr`] - Conversion between C4 diagrams and the Structurizr DSL
+//! - [`summary`] - Cheap size summary of a diagram for logging and telemetry
+//! - [`syntax`] - Enumerations of the syntax each parser recognizes
+//! - [`text_direction`] - Detection of right-to-left text in labels
+//! - [`text_normalizer`] - Case, diacritic and separator folding for comparing labels
 //! - [`tokens`] - Token definitions and token stream handling
+//! - [`trace`] - Conversion between sequence diagrams and distributed traces
+//! - [`unrecognized`] - Counts of diagram headers routed to the misc parser
 //! - [`validation`] - Diagram validation and semantic analysis
 //! - [`visitor`] - AST visitor pattern for traversal and analysis
+//! - [`warnings`] - Warnings for statements a parser accepts but does not keep
+//! - [`workspace`] - Analysis across related diagrams, such as subroutine call graphs
 //!
 //! ## Example
 //!
@@ -31,13 +59,41 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+pub mod annotations;
+pub mod arrow_table;
 pub mod ast;
+pub mod ast_graph;
+pub mod calendar;
+pub mod chart_range;
+pub mod collapse;
 pub mod constants;
+pub mod deprecation;
+pub mod direction;
+pub mod er_stub;
+pub mod escape;
+pub mod events;
+pub mod fix;
+pub mod frontmatter;
+pub mod gantt;
+pub mod heap_size;
 pub mod lexer;
 pub mod metrics;
+pub mod normalize;
+pub mod ordered_map;
 pub mod parser_utils;
 pub mod parsing;
+pub mod path;
 pub mod pretty_print;
+pub mod stream;
+pub mod structurizr;
+pub mod summary;
+pub mod syntax;
+pub mod text_direction;
+pub mod text_normalizer;
 pub mod tokens;
+pub mod trace;
+pub mod unrecognized;
 pub mod validation;
 pub mod visitor;
+pub mod warnings;
+pub mod workspace;
@@ -0,0 +1,231 @@
+//! Normalization of minified input with `;` separated statements
+//!
+//! Some generators emit a whole diagram on a single line, separating
+//! statements with `;`. Mermaid accepts this for flowcharts and sequence
+//! diagrams, and both parsers handle it directly, but other diagram types are
+//! line oriented and diagnostics on a very wide line are hard to act on.
+//!
+//! [`normalize`] puts every statement on its own line and keeps a map back to
+//! the original source, so positions reported against the normalized text can
+//! be translated with [`NormalizedSource::original_location`],
+//! [`NormalizedSource::map_parse_error`] and
+//! [`NormalizedSource::map_validation_error`]. Semicolons inside double quotes
+//! or entity codes such as `#59;`, comment lines and frontmatter are left
+//! untouched.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::normalize;
+//!
+//! let source = normalize::normalize("graph TD; A-->B; B-->C");
+//! assert_eq!(source.text(), "graph TD\nA-->B\nB-->C");
+//! assert_eq!(source.split_lines(), &[1]);
+//!
+//! // Column 2 of normalized line 3 is the `-->` in `B-->C`
+//! assert_eq!(source.original_location(3, 2), Some((1, 19)));
+//! ```
+
+use crate::common::ast::DiagramType;
+use crate::common::frontmatter;
+use crate::common::parsing::lines;
+use crate::common::validation::ValidationError;
+use crate::error::{FailurePoint, ParseError, PartialParse, Result};
+
+/// Where a normalized line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LineOrigin {
+    /// 1-based line in the original input
+    line: usize,
+    /// Leading whitespace copied from the original line, in characters
+    indent: usize,
+    /// 0-based character column of the statement in the original line
+    start: usize,
+}
+
+/// Input with one statement per line and a map back to the original text
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedSource {
+    text: String,
+    origins: Vec<LineOrigin>,
+    split_lines: Vec<usize>,
+}
+
+/// Split `;` separated statements in `input` onto separate lines
+///
+/// Each statement keeps the indentation of the line it came from, so
+/// indentation-sensitive diagrams keep their structure. Lines holding a
+/// single statement are copied unchanged.
+pub fn normalize(input: &str) -> NormalizedSource {
+    let frontmatter_lines = match frontmatter::split(input) {
+        (Some(_), body) => input[..input.len() - body.len()].lines().count(),
+        (None, _) => 0,
+    };
+
+    let mut output = Vec::new();
+    let mut origins = Vec::new();
+    let mut split_lines = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let statements = lines::split_statements(line, ';');
+        let unchanged = statements.len() <= 1 && !line.trim_end().ends_with(';');
+        if index < frontmatter_lines || unchanged {
+            output.push(line.to_string());
+            origins.push(LineOrigin {
+                line: index + 1,
+                indent: 0,
+                start: 0,
+            });
+            continue;
+        }
+
+        if statements.len() > 1 {
+            split_lines.push(index + 1);
+        }
+        let indent = &line[..line.len() - line.trim_start().len()];
+        for statement in statements {
+            let trimmed = statement.trim();
+            let offset = statement.as_ptr() as usize - line.as_ptr() as usize
+                + (statement.len() - statement.trim_start().len());
+            output.push(format!("{}{}", indent, trimmed));
+            origins.push(LineOrigin {
+                line: index + 1,
+                indent: indent.chars().count(),
+                start: line[..offset].chars().count(),
+            });
+        }
+    }
+
+    NormalizedSource {
+        text: output.join("\n"),
+        origins,
+        split_lines,
+    }
+}
+
+/// Normalize `input`, parse it, and report parse errors against `input`
+pub fn parse_normalized(input: &str) -> Result<DiagramType> {
+    let source = normalize(input);
+    crate::parse_diagram(source.text()).map_err(|error| source.map_parse_error(error))
+}
+
+impl NormalizedSource {
+    /// The normalized text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 1-based original lines that held more than one statement
+    pub fn split_lines(&self) -> &[usize] {
+        &self.split_lines
+    }
+
+    /// Whether normalization changed the input's line structure
+    pub fn was_split(&self) -> bool {
+        !self.split_lines.is_empty()
+    }
+
+    /// Translate a 1-based line and column in the normalized text to the
+    /// original input; `None` for lines past the end
+    pub fn original_location(&self, line: usize, column: usize) -> Option<(usize, usize)> {
+        let origin = self.origins.get(line.checked_sub(1)?)?;
+        let column = if column <= origin.indent {
+            column
+        } else {
+            origin.start + column - origin.indent
+        };
+        Some((origin.line, column))
+    }
+
+    /// Rewrite the position of a parse error raised on the normalized text
+    pub fn map_parse_error(&self, error: ParseError) -> ParseError {
+        match error {
+            ParseError::LexError {
+                message,
+                line,
+                column,
+                partial,
+            } => {
+                let (line, column) = self
+                    .original_location(line, column)
+                    .unwrap_or((line, column));
+                ParseError::LexError {
+                    message,
+                    line,
+                    column,
+                    partial: partial.map(|partial| self.map_partial(partial)),
+                }
+            }
+            ParseError::SyntaxError {
+                message,
+                expected,
+                found,
+                line,
+                column,
+                partial,
+            } => {
+                let (line, column) = self
+                    .original_location(line, column)
+                    .unwrap_or((line, column));
+                ParseError::SyntaxError {
+                    message,
+                    expected,
+                    found,
+                    line,
+                    column,
+                    partial: partial.map(|partial| self.map_partial(partial)),
+                }
+            }
+            ParseError::EnhancedSyntaxError {
+                message,
+                mut location,
+                snippet,
+                suggestions,
+                expected,
+                found,
+            } => {
+                if let Some((line, column)) = self.original_location(location.line, location.column)
+                {
+                    location.line = line;
+                    location.column = column;
+                }
+                ParseError::EnhancedSyntaxError {
+                    message,
+                    location,
+                    snippet,
+                    suggestions,
+                    expected,
+                    found,
+                }
+            }
+            ParseError::InSource { name, error } => ParseError::InSource {
+                name,
+                error: Box::new(self.map_parse_error(*error)),
+            },
+            other => other,
+        }
+    }
+
+    /// Rewrite the position of a lexer failure recorded on the normalized text
+    fn map_partial(&self, mut partial: Box<PartialParse>) -> Box<PartialParse> {
+        if let FailurePoint::Lexer { line, column, .. } = &mut partial.failure {
+            if let Some(location) = self.original_location(*line, *column) {
+                (*line, *column) = location;
+            }
+        }
+        partial
+    }
+
+    /// Rewrite the source position of a validation diagnostic
+    ///
+    /// Diagnostics located by element id only (line 0) are returned unchanged.
+    pub fn map_validation_error(&self, mut error: ValidationError) -> ValidationError {
+        if let Some(location) = error.location.as_mut().filter(|l| l.line > 0) {
+            if let Some((line, column)) = self.original_location(location.line, location.column) {
+                location.line = line;
+                location.column = column;
+            }
+        }
+        error
+    }
+}
@@ -0,0 +1,577 @@
+//! A map that remembers the order its keys were inserted in
+//!
+//! The ASTs key nodes, classes, states and other declared elements by id, and
+//! the order authors declare them in matters: printers should write them back
+//! in that order, and diffs between two versions of a diagram stay small when
+//! they do. [`OrderedMap`] keeps its entries in a `Vec` in insertion order
+//! with a `HashMap` from key to position beside it, so lookups stay O(1).
+//!
+//! Its methods follow [`std::collections::HashMap`], and iteration is in
+//! insertion order. Inserting a key that is already present replaces its value
+//! in place, and [`OrderedMap::remove`] keeps the order of the entries after
+//! the removed one. Equality ignores order, as for `HashMap`: two maps are
+//! equal when they hold the same entries.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::ordered_map::OrderedMap;
+//!
+//! let mut nodes = OrderedMap::new();
+//! nodes.insert("Start", 1);
+//! nodes.insert("End", 2);
+//! nodes.insert("Middle", 3);
+//! nodes.insert("Start", 4);
+//!
+//! assert_eq!(nodes.keys().copied().collect::<Vec<_>>(), ["Start", "End", "Middle"]);
+//! assert_eq!(nodes["Start"], 4);
+//! assert_eq!(nodes.get_index_of("Middle"), Some(2));
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A map that iterates in insertion order
+#[derive(Clone)]
+pub struct OrderedMap<K, V> {
+    entries: Vec<(K, V)>,
+    index: HashMap<K, usize>,
+}
+
+impl<K, V> OrderedMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries the map can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// The entry at `position` in insertion order
+    pub fn get_index(&self, position: usize) -> Option<(&K, &V)> {
+        self.entries.get(position).map(|(key, value)| (key, value))
+    }
+
+    /// The first entry inserted that is still present
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.get_index(0)
+    }
+
+    /// The last entry inserted
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|(key, value)| (key, value))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.entries.iter_mut(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+    /// Insert `value` under `key`, returning the value it replaces
+    ///
+    /// A new key goes to the end of the order; an existing key keeps its
+    /// position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.index.get(&key).copied() {
+            Some(position) => Some(std::mem::replace(&mut self.entries[position].1, value)),
+            None => {
+                self.push(key, value);
+                None
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let position = *self.index.get(key)?;
+        Some(&self.entries[position].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let position = *self.index.get(key)?;
+        Some(&mut self.entries[position].1)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_index(*self.index.get(key)?)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    /// The position of `key` in insertion order
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).copied()
+    }
+
+    /// Remove `key`, keeping the order of the remaining entries
+    ///
+    /// Takes time proportional to the number of entries after it.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let position = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for (key, _) in &self.entries[position..] {
+            if let Some(later) = self.index.get_mut(key) {
+                *later -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    /// Keep only the entries for which `keep` returns true, in their order
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        self.entries.retain_mut(|(key, value)| keep(key, value));
+        self.reindex();
+    }
+
+    /// Sort the entries with `compare`, for callers that want an order other
+    /// than declaration order
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&K, &V, &K, &V) -> std::cmp::Ordering) {
+        self.entries
+            .sort_by(|(k1, v1), (k2, v2)| compare(k1, v1, k2, v2));
+        self.reindex();
+    }
+
+    /// The entry for `key`, for inserting or updating it in place
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        Entry { map: self, key }
+    }
+
+    fn push(&mut self, key: K, value: V) -> usize {
+        let position = self.entries.len();
+        self.index.insert(key.clone(), position);
+        self.entries.push((key, value));
+        position
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (position, (key, _)) in self.entries.iter().enumerate() {
+            self.index.insert(key.clone(), position);
+        }
+    }
+}
+
+/// A key of an [`OrderedMap`] that may or may not be present
+pub struct Entry<'a, K, V> {
+    map: &'a mut OrderedMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: Hash + Eq + Clone, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Update the value if the key is present
+    pub fn and_modify(self, modify: impl FnOnce(&mut V)) -> Self {
+        if let Some(value) = self.map.get_mut(&self.key) {
+            modify(value);
+        }
+        self
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        let existing = self.map.index.get(&self.key).copied();
+        let position = match existing {
+            Some(position) => position,
+            None => self.map.push(self.key, default()),
+        };
+        &mut self.map.entries[position].1
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<K, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for OrderedMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: PartialEq> PartialEq for OrderedMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|other| value == other))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Eq> Eq for OrderedMap<K, V> {}
+
+impl<K, Q, V> std::ops::Index<&Q> for OrderedMap<K, V>
+where
+    K: Hash + Eq + Clone + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = V;
+
+    /// # Panics
+    ///
+    /// Panics if `key` is not in the map.
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not in OrderedMap")
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for OrderedMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, entries: I) {
+        for (key, value) in entries {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(entries: I) -> Self {
+        let mut map = Self::new();
+        map.extend(entries);
+        map
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, const N: usize> From<[(K, V); N]> for OrderedMap<K, V> {
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl<K, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a OrderedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut OrderedMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Entries of an [`OrderedMap`] in insertion order
+#[derive(Clone)]
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// Entries of an [`OrderedMap`] in insertion order, with mutable values
+pub struct IterMut<'a, K, V> {
+    inner: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, value)| (&*key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, value)| (&*key, value))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+/// Keys of an [`OrderedMap`] in insertion order
+#[derive(Clone)]
+pub struct Keys<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+/// Values of an [`OrderedMap`] in insertion order
+#[derive(Clone)]
+pub struct Values<'a, K, V> {
+    inner: std::slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+/// Mutable values of an [`OrderedMap`] in insertion order
+pub struct ValuesMut<'a, K, V> {
+    inner: std::slice::IterMut<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for ValuesMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+/// Serialized as a map in insertion order
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for OrderedMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for OrderedMap<K, V>
+where
+    K: serde::Deserialize<'de> + Hash + Eq + Clone,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<K, V>(std::marker::PhantomData<(K, V)>);
+
+        impl<'de, K, V> serde::de::Visitor<'de> for MapVisitor<K, V>
+        where
+            K: serde::Deserialize<'de> + Hash + Eq + Clone,
+            V: serde::Deserialize<'de>,
+        {
+            type Value = OrderedMap<K, V>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut map = OrderedMap::with_capacity(access.size_hint().unwrap_or(0));
+                while let Some((key, value)) = access.next_entry()? {
+                    map.insert(key, value);
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(std::marker::PhantomData))
+    }
+}
+
+/// The same schema as a `HashMap`: a JSON object
+#[cfg(feature = "schema")]
+impl<K, V: schemars::JsonSchema> schemars::JsonSchema for OrderedMap<K, V> {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        HashMap::<K, V>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        HashMap::<K, V>::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_keeps_order_and_positions() {
+        let mut map: OrderedMap<String, u32> = [("a", 1), ("b", 2), ("c", 3), ("d", 4)]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.keys().cloned().collect::<Vec<_>>(), ["a", "c", "d"]);
+        assert_eq!(map.get_index_of("d"), Some(2));
+        assert_eq!(map["d"], 4);
+
+        *map.entry("a".to_string()).or_insert(0) += 10;
+        map.entry("e".to_string()).or_insert(5);
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), [11, 3, 4, 5]);
+
+        map.retain(|_, value| *value > 3);
+        assert_eq!(map.get_index_of("e"), Some(2));
+    }
+
+    #[test]
+    fn equality_ignores_order() {
+        let forward = OrderedMap::from([("a", 1), ("b", 2)]);
+        let backward = OrderedMap::from([("b", 2), ("a", 1)]);
+        assert_eq!(forward, backward);
+        assert_ne!(forward, OrderedMap::from([("a", 1)]));
+    }
+}
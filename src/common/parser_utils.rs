@@ -84,6 +84,32 @@ pub fn parse_whitespace_required<'src>(
     one_of(" \t").repeated().at_least(1).ignored()
 }
 
+/// Parse an identifier made of Unicode letters, digits and underscores
+///
+/// Unlike `text::ident()`, which only accepts ASCII, this matches mermaid-js in
+/// allowing any Unicode letter, so IDs such as `견적서` or `Кнопка` lex as one token.
+pub fn parse_identifier<'src>(
+) -> impl Parser<'src, &'src str, &'src str, extra::Err<Simple<'src, char>>> + Copy {
+    any()
+        .filter(|c: &char| is_identifier_start(*c))
+        .then(
+            any()
+                .filter(|c: &char| is_identifier_continue(*c))
+                .repeated(),
+        )
+        .to_slice()
+}
+
+/// Check whether a character may start an identifier
+pub fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+/// Check whether a character may continue an identifier
+pub fn is_identifier_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
 /// Parse newlines (both \n and \r\n)
 pub fn parse_newline<'src>(
 ) -> impl Parser<'src, &'src str, CommonToken, extra::Err<Simple<'src, char>>> + Clone {
@@ -99,6 +125,7 @@ macro_rules! create_parser_fn {
         diagram_type: $diagram_type:literal
     }) => {
         $vis fn $name($input: &str) -> $crate::error::Result<$output> {
+            let $input = $crate::common::parsing::frontmatter::strip($input);
             let tokens = $lexer()
                 .parse($input)
                 .into_result()
@@ -108,6 +135,7 @@ macro_rules! create_parser_fn {
                     found: format!("{:?}", e),
                     line: 0,
                     column: 0,
+                    partial: None,
                 })?;
 
             let result = $parser()
@@ -119,6 +147,7 @@ macro_rules! create_parser_fn {
                     found: format!("{:?}", e),
                     line: 0,
                     column: 0,
+                    partial: None,
                 })?;
 
             Ok(result)
@@ -134,6 +163,7 @@ macro_rules! create_line_parser_fn {
         diagram_type: $diagram_type:literal
     }) => {
         $vis fn $name($input: &str) -> $crate::error::Result<$output> {
+            let $input = $crate::common::parsing::frontmatter::strip($input);
             let tokens = $parser_fn()
                 .parse($input)
                 .into_result()
@@ -143,6 +173,7 @@ macro_rules! create_line_parser_fn {
                     found: format!("{:?}", e),
                     line: 0,
                     column: 0,
+                    partial: None,
                 })?;
 
             $parser_fn(&tokens)
@@ -172,6 +203,14 @@ pub fn parse_common_directives(
         return true;
     }
 
+    parse_accessibility_directives(effective_trimmed, accessibility)
+}
+
+/// Parses the single-line `accTitle` and `accDescr` directives
+/// Returns true if the line was handled, false if not recognized
+pub fn parse_accessibility_directives(line: &str, accessibility: &mut AccessibilityInfo) -> bool {
+    let effective_trimmed = line.trim();
+
     // Parse accessibility title (handle both "accTitle:" and "accTitle ")
     if let Some(acc_title) = effective_trimmed.strip_prefix("accTitle:") {
         accessibility.title = Some(acc_title.trim().to_string());
@@ -432,6 +471,39 @@ mod directive_tests {
     }
 }
 
+#[cfg(test)]
+mod identifier_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ascii_identifier() {
+        let result = parse_identifier().parse("node_1").into_result();
+        assert_eq!(result, Ok("node_1"));
+    }
+
+    #[test]
+    fn test_parse_unicode_identifier() {
+        assert_eq!(
+            parse_identifier().parse("견적서").into_result(),
+            Ok("견적서")
+        );
+        assert_eq!(
+            parse_identifier().parse("Кнопка").into_result(),
+            Ok("Кнопка")
+        );
+        assert_eq!(
+            parse_identifier().parse("_über2").into_result(),
+            Ok("_über2")
+        );
+    }
+
+    #[test]
+    fn test_parse_identifier_rejects_invalid_start() {
+        assert!(parse_identifier().parse("1abc").into_result().is_err());
+        assert!(parse_identifier().parse("-abc").into_result().is_err());
+    }
+}
+
 /// Validates diagram header and handles first line processing
 ///
 /// # Arguments
@@ -474,6 +546,7 @@ pub fn validate_diagram_header<'a>(
             found: trimmed.to_string(),
             line: line_num + 1,
             column: 1,
+            partial: None,
         });
     }
 
@@ -508,6 +581,156 @@ pub fn should_skip_line(line: &str) -> bool {
     trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("%%")
 }
 
+/// Attach the tokens lexed before a lexer failure to `error`
+///
+/// The text before the failure is lexed again; when the failure is inside a
+/// token that prefix may not lex, so the text before the failing line is
+/// tried next.
+pub(crate) fn lex_failure<'src, T: std::fmt::Debug>(
+    lexer: &impl Parser<'src, &'src str, Vec<T>, extra::Err<Simple<'src, char>>>,
+    input: &'src str,
+    errors: &[Simple<'src, char>],
+    error: crate::error::ParseError,
+) -> crate::error::ParseError {
+    let offset = errors
+        .first()
+        .map_or(input.len(), |e| e.span().start)
+        .min(input.len());
+    let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let tokens = [offset, line_start]
+        .into_iter()
+        .find_map(|end| lexer.parse(&input[..end]).into_result().ok())
+        .unwrap_or_default();
+
+    let line = input[..offset].matches('\n').count() + 1;
+    let column = input[line_start..offset].chars().count() + 1;
+    partial_error(
+        error,
+        &tokens,
+        crate::error::FailurePoint::Lexer {
+            offset,
+            line,
+            column,
+        },
+    )
+}
+
+/// Attach the token stream and the index of the rejected token to `error`
+pub(crate) fn token_failure<'src, T: std::fmt::Debug>(
+    tokens: &[T],
+    errors: &[Simple<'src, T>],
+    error: crate::error::ParseError,
+) -> crate::error::ParseError {
+    let token = errors
+        .first()
+        .map_or(tokens.len(), |e| e.span().start)
+        .min(tokens.len());
+    partial_error(error, tokens, crate::error::FailurePoint::Parser { token })
+}
+
+fn partial_error<T: std::fmt::Debug>(
+    mut error: crate::error::ParseError,
+    tokens: &[T],
+    failure: crate::error::FailurePoint,
+) -> crate::error::ParseError {
+    if let crate::error::ParseError::LexError { partial, .. }
+    | crate::error::ParseError::SyntaxError { partial, .. } = &mut error
+    {
+        *partial = Some(Box::new(crate::error::PartialParse {
+            tokens: tokens.iter().map(|token| format!("{:?}", token)).collect(),
+            failure,
+        }));
+    }
+    error
+}
+
+/// Character cursor for the hand-written recursive descent readers, such as
+/// the flow collections of frontmatter and the JSON of traces
+pub(crate) struct Scanner {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Scanner {
+    pub(crate) fn new(input: &str) -> Self {
+        Scanner {
+            chars: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Move past the next character, if there is one
+    pub(crate) fn bump(&mut self) {
+        self.position = (self.position + 1).min(self.chars.len());
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Skip whitespace, then move past `expected` if it comes next
+    pub(crate) fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// Move past `word` if the input continues with it
+    pub(crate) fn eat_word(&mut self, word: &str) -> bool {
+        let end = self.position + word.chars().count();
+        let found = end <= self.chars.len()
+            && self.chars[self.position..end]
+                .iter()
+                .copied()
+                .eq(word.chars());
+        if found {
+            self.position = end;
+        }
+        found
+    }
+
+    /// Move past the characters for which `accept` holds
+    pub(crate) fn skip_while(&mut self, accept: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&accept) {
+            self.position += 1;
+        }
+    }
+
+    /// Index of the next character, to [`Scanner::rewind`] to or to take the
+    /// text [`Scanner::since`]
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(crate) fn rewind(&mut self, position: usize) {
+        self.position = position.min(self.chars.len());
+    }
+
+    /// The characters from `start` up to the next one
+    pub(crate) fn since(&self, start: usize) -> String {
+        self.chars[start.min(self.position)..self.position]
+            .iter()
+            .collect()
+    }
+
+    /// 1-based line and column of the next character
+    pub(crate) fn location(&self) -> (usize, usize) {
+        let consumed = &self.chars[..self.position];
+        let line = consumed.iter().filter(|&&c| c == '\n').count() + 1;
+        let column = consumed.iter().rev().take_while(|&&c| c != '\n').count() + 1;
+        (line, column)
+    }
+}
+
 #[cfg(test)]
 mod header_validation_tests {
     use super::*;
@@ -14,12 +14,11 @@ pub mod quoted_strings {
     pub fn unquote(input: &str) -> String {
         let trimmed = input.trim();
 
-        if trimmed.len() >= 2 {
-            if (trimmed.starts_with('"') && trimmed.ends_with('"'))
-                || (trimmed.starts_with('\'') && trimmed.ends_with('\''))
-            {
-                return trimmed[1..trimmed.len() - 1].to_string();
-            }
+        if trimmed.len() >= 2
+            && ((trimmed.starts_with('"') && trimmed.ends_with('"'))
+                || (trimmed.starts_with('\'') && trimmed.ends_with('\'')))
+        {
+            return trimmed[1..trimmed.len() - 1].to_string();
         }
 
         trimmed.to_string()
@@ -33,6 +32,25 @@ pub mod quoted_strings {
                 || (trimmed.starts_with('\'') && trimmed.ends_with('\'')))
     }
 
+    /// Find the byte position of `needle` in `haystack`, ignoring matches inside double quotes
+    ///
+    /// Quoted regions are opaque, so labels such as `"x --> y"` never match an arrow search.
+    pub fn find_unquoted(haystack: &str, needle: &str) -> Option<usize> {
+        if needle.is_empty() {
+            return None;
+        }
+
+        let mut in_quotes = false;
+        for (pos, ch) in haystack.char_indices() {
+            if ch == '"' {
+                in_quotes = !in_quotes;
+            } else if !in_quotes && haystack[pos..].starts_with(needle) {
+                return Some(pos);
+            }
+        }
+        None
+    }
+
     /// Parse a field that may or may not be quoted
     /// Returns (content, was_quoted)
     pub fn parse_field(input: &str) -> (String, bool) {
@@ -47,6 +65,7 @@ pub mod quoted_strings {
 
 /// Utilities for key-value pair parsing
 pub mod key_value {
+    use std::collections::HashMap;
 
     /// Parse a line containing key-value pairs separated by a delimiter
     pub fn parse_separated(line: &str, delimiter: char) -> Option<(String, String)> {
@@ -76,6 +95,21 @@ pub mod key_value {
         }
         None
     }
+
+    /// Parse `fill:#f9f,stroke:#333` into properties; commas inside a value such
+    /// as `rgb(1,2,3)` stay part of that value
+    pub fn parse_style_properties(text: &str) -> HashMap<String, String> {
+        let mut properties: Vec<(String, String)> = Vec::new();
+        for part in text.trim_end_matches(';').split(',') {
+            if let Some((key, value)) = part.split_once(':') {
+                properties.push((key.trim().to_string(), value.trim().to_string()));
+            } else if let Some((_, value)) = properties.last_mut() {
+                value.push(',');
+                value.push_str(part.trim());
+            }
+        }
+        properties.into_iter().collect()
+    }
 }
 
 /// Utilities for bracket and parentheses handling
@@ -197,12 +231,166 @@ pub mod fields {
         fields
     }
 
+    /// Parse a spreadsheet-style CSV record: only double quotes delimit
+    /// fields and `""` inside a quoted field is an escaped quote
+    pub fn parse_csv_record(line: &str) -> Vec<String> {
+        let mut fields = Vec::new();
+        let mut current_field = String::new();
+        let mut in_quotes = false;
+        let mut chars = line.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    current_field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = !in_quotes,
+                ',' if !in_quotes => {
+                    fields.push(current_field.trim().to_string());
+                    current_field.clear();
+                }
+                _ => current_field.push(ch),
+            }
+        }
+        fields.push(current_field.trim().to_string());
+
+        fields
+    }
+
+    /// Quote a field for CSV output if it contains a delimiter, quote or newline
+    pub fn escape_csv_field(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+
     /// Clean and normalize field content
     pub fn clean_field(field: &str) -> String {
         field.trim().replace("\\n", "\n").replace("\\t", "\t")
     }
 }
 
+/// Utilities for the frontmatter block at the top of a Mermaid file
+pub mod frontmatter {
+    use crate::common::ast::DiagramMetadata;
+    use crate::common::frontmatter::{split, Frontmatter};
+    use crate::error::Result;
+
+    /// The diagram source of `input`, without its frontmatter block
+    ///
+    /// Every parser calls this first, so a whole Mermaid file can be handed
+    /// to any of them.
+    pub fn strip(input: &str) -> &str {
+        split(input).1
+    }
+
+    /// Split `input` into the metadata of its frontmatter and the diagram
+    /// source. Input without frontmatter has empty metadata.
+    pub fn extract(input: &str) -> Result<(DiagramMetadata, &str)> {
+        let (yaml, body) = split(input);
+        let metadata = match yaml {
+            Some(yaml) => {
+                let frontmatter = Frontmatter::from_yaml(yaml)?;
+                DiagramMetadata {
+                    title: frontmatter.title,
+                    config: frontmatter.config,
+                    ..Default::default()
+                }
+            }
+            None => DiagramMetadata::default(),
+        };
+        Ok((metadata, body))
+    }
+}
+
+/// Utilities for `%%{...}%%` directives
+pub mod directives {
+    use crate::common::ast::InitDirective;
+    use crate::common::frontmatter::{parse_mapping, ConfigValue, MermaidConfig};
+    use crate::error::{ParseError, Result};
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
+
+    /// Names of the directive holding the diagram configuration
+    pub const INIT_DIRECTIVES: &[&str] = &["init", "initialize"];
+
+    /// The name and argument of a single-line directive, such as
+    /// `("init", Some("{\"theme\": \"dark\"}"))` for
+    /// `%%{init: {"theme": "dark"}}%%`, or `None` if `line` is not one
+    pub fn split(line: &str) -> Option<(&str, Option<&str>)> {
+        let body = line
+            .trim()
+            .strip_prefix("%%{")
+            .and_then(|rest| rest.strip_suffix("}%%"))?
+            .trim();
+        fn unquote(name: &str) -> &str {
+            name.trim().trim_matches(['"', '\''])
+        }
+        Some(match body.split_once(':') {
+            Some((name, argument)) => (unquote(name), Some(argument.trim())),
+            None => (unquote(body), None),
+        })
+    }
+
+    /// Parse the argument of a directive, in flow style as in
+    /// `{"theme": "dark"}`. `line` is the line of the directive, for error
+    /// reporting.
+    pub fn parse_argument(name: &str, argument: &str, line: usize) -> Result<ConfigValue> {
+        let mut mapping = parse_mapping(&format!("{{\"{}\": {}}}", name, argument), line)?;
+        Ok(mapping.remove(name).unwrap_or(ConfigValue::Null))
+    }
+
+    /// `input` with its directive lines left empty, so line numbers are kept
+    pub fn strip(input: &str) -> Cow<'_, str> {
+        if !input.lines().any(|line| split(line).is_some()) {
+            return Cow::Borrowed(input);
+        }
+        let lines: Vec<&str> = input
+            .lines()
+            .map(|line| if split(line).is_some() { "" } else { line })
+            .collect();
+        Cow::Owned(lines.join("\n"))
+    }
+
+    /// The `init` directives of `input`, merged in order, or `None` if it has
+    /// none. Other directives, such as `%%{wrap}%%`, are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::SyntaxError`] if an `init` argument is not a
+    /// mapping.
+    pub fn extract(input: &str) -> Result<Option<InitDirective>> {
+        let mut config: Option<BTreeMap<String, ConfigValue>> = None;
+        for (index, line) in input.lines().enumerate() {
+            let Some((name, Some(argument))) = split(line) else {
+                continue;
+            };
+            if !INIT_DIRECTIVES.contains(&name) {
+                continue;
+            }
+            match parse_argument(name, argument, index + 1)? {
+                ConfigValue::Map(map) => config.get_or_insert_with(BTreeMap::new).extend(map),
+                _ => {
+                    return Err(ParseError::SyntaxError {
+                        message: format!("The `{}` directive expects a mapping", name),
+                        expected: vec!["{".to_string()],
+                        found: argument.to_string(),
+                        line: index + 1,
+                        column: 1,
+                        partial: None,
+                    })
+                }
+            }
+        }
+        Ok(config.map(|config| InitDirective {
+            config: MermaidConfig::from_map(config),
+        }))
+    }
+}
+
 /// Utilities for line processing and filtering
 pub mod lines {
 
@@ -223,6 +411,50 @@ pub mod lines {
         line.trim().replace("\\t", "").to_string()
     }
 
+    /// Split a line into separate statements on `separator`, as Mermaid does for `;`
+    ///
+    /// Separators inside double quotes or HTML entity codes (e.g. `#59;`) are kept,
+    /// and comment lines are never split.
+    pub fn split_statements(line: &str, separator: char) -> Vec<&str> {
+        if should_skip_line(line) {
+            return vec![line];
+        }
+
+        let mut statements = Vec::new();
+        let mut start = 0;
+        let mut in_quotes = false;
+        let mut chars = line.char_indices().peekable();
+
+        while let Some((pos, ch)) = chars.next() {
+            if ch == '"' {
+                in_quotes = !in_quotes;
+            } else if ch == '#' && !in_quotes {
+                // Skip over an entity code such as `#59;` or `#quot;`
+                let rest = &line[pos + 1..];
+                let entity_len = rest
+                    .find(|c: char| !c.is_alphanumeric())
+                    .unwrap_or(rest.len());
+                if entity_len > 0 && rest[entity_len..].starts_with(separator) {
+                    while let Some(&(next_pos, _)) = chars.peek() {
+                        if next_pos > pos + entity_len + 1 {
+                            break;
+                        }
+                        chars.next();
+                    }
+                }
+            } else if ch == separator && !in_quotes {
+                statements.push(&line[start..pos]);
+                start = pos + ch.len_utf8();
+            }
+        }
+        statements.push(&line[start..]);
+
+        statements
+            .into_iter()
+            .filter(|statement| !statement.trim().is_empty())
+            .collect()
+    }
+
     /// Split a line into meaningful parts, skipping empty parts
     pub fn split_line_parts(line: &str, delimiters: &[char]) -> Vec<String> {
         let mut parts = Vec::new();
@@ -295,6 +527,7 @@ pub mod numbers {
             found: input.to_string(),
             line: line_num + 1,
             column: 1,
+            partial: None,
         })
     }
 
@@ -310,6 +543,7 @@ pub mod numbers {
             found: input.to_string(),
             line: line_num + 1,
             column: 1,
+            partial: None,
         })
     }
 }
@@ -317,7 +551,9 @@ pub mod numbers {
 /// Utilities for identifier and name validation
 pub mod identifiers {
 
-    /// Check if a string is a valid identifier (alphanumeric + underscore, starts with letter)
+    use crate::common::parser_utils::{is_identifier_continue, is_identifier_start};
+
+    /// Check if a string is a valid identifier (Unicode alphanumeric + underscore, starts with letter)
     pub fn is_valid_identifier(name: &str) -> bool {
         if name.is_empty() {
             return false;
@@ -325,12 +561,12 @@ pub mod identifiers {
 
         let mut chars = name.chars();
         if let Some(first) = chars.next() {
-            if !first.is_ascii_alphabetic() && first != '_' {
+            if !is_identifier_start(first) {
                 return false;
             }
         }
 
-        chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        chars.all(is_identifier_continue)
     }
 
     /// Sanitize a string to create a valid identifier
@@ -340,16 +576,16 @@ pub mod identifiers {
 
         for ch in name.chars() {
             if first {
-                if ch.is_ascii_alphabetic() || ch == '_' {
+                if is_identifier_start(ch) {
                     result.push(ch);
-                } else if ch.is_ascii_digit() {
+                } else if ch.is_numeric() {
                     result.push('_');
                     result.push(ch);
                 } else {
                     result.push('_');
                 }
                 first = false;
-            } else if ch.is_ascii_alphanumeric() || ch == '_' {
+            } else if is_identifier_continue(ch) {
                 result.push(ch);
             } else {
                 result.push('_');
@@ -527,6 +763,17 @@ mod tests {
             assert!(!quoted_strings::is_quoted("\""));
         }
 
+        #[test]
+        fn test_find_unquoted() {
+            assert_eq!(quoted_strings::find_unquoted("A --> B", "-->"), Some(2));
+            assert_eq!(
+                quoted_strings::find_unquoted("\"x --> y\" --> B", "-->"),
+                Some(10)
+            );
+            assert_eq!(quoted_strings::find_unquoted("\"x --> y\"", "-->"), None);
+            assert_eq!(quoted_strings::find_unquoted("no arrow", "-->"), None);
+        }
+
         #[test]
         fn test_parse_field() {
             assert_eq!(
@@ -689,6 +936,26 @@ mod tests {
             );
         }
 
+        #[test]
+        fn test_parse_csv_record() {
+            assert_eq!(
+                fields::parse_csv_record("Don't panic, \"Me, \"\"Cat\"\"\",,"),
+                vec![
+                    "Don't panic".to_string(),
+                    "Me, \"Cat\"".to_string(),
+                    String::new(),
+                    String::new()
+                ]
+            );
+        }
+
+        #[test]
+        fn test_escape_csv_field() {
+            assert_eq!(fields::escape_csv_field("plain"), "plain");
+            assert_eq!(fields::escape_csv_field("Me, Cat"), "\"Me, Cat\"");
+            assert_eq!(fields::escape_csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        }
+
         #[test]
         fn test_clean_field() {
             assert_eq!(fields::clean_field("  content  "), "content");
@@ -715,6 +982,27 @@ mod tests {
             assert_eq!(lines::clean_line("\\tcontent"), "content");
         }
 
+        #[test]
+        fn test_split_statements() {
+            assert_eq!(
+                lines::split_statements("A->>B: hi; B->>A: yo", ';'),
+                vec!["A->>B: hi", " B->>A: yo"]
+            );
+            assert_eq!(
+                lines::split_statements("A->>B: \"a;b\"", ';'),
+                vec!["A->>B: \"a;b\""]
+            );
+            assert_eq!(
+                lines::split_statements("A->>B: semi#59; colon", ';'),
+                vec!["A->>B: semi#59; colon"]
+            );
+            assert_eq!(
+                lines::split_statements("%% comment; still comment", ';'),
+                vec!["%% comment; still comment"]
+            );
+            assert_eq!(lines::split_statements("A->>B;", ';'), vec!["A->>B"]);
+        }
+
         #[test]
         fn test_split_line_parts() {
             assert_eq!(
@@ -767,6 +1055,8 @@ mod tests {
             assert!(!identifiers::is_valid_identifier("123invalid"));
             assert!(!identifiers::is_valid_identifier(""));
             assert!(!identifiers::is_valid_identifier("with-dash"));
+            assert!(identifiers::is_valid_identifier("견적서"));
+            assert!(identifiers::is_valid_identifier("Кнопка_1"));
         }
 
         #[test]
@@ -782,6 +1072,7 @@ mod tests {
                 identifiers::sanitize_identifier("special@chars"),
                 "special_chars"
             );
+            assert_eq!(identifiers::sanitize_identifier("Кнопка-1"), "Кнопка_1");
         }
     }
 
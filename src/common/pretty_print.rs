@@ -1,4 +1,11 @@
+use crate::common::arrow_table::ArrowKind;
 use crate::common::ast::*;
+use crate::common::escape;
+use crate::common::frontmatter::{self, ConfigValue};
+use crate::common::ordered_map::OrderedMap;
+use crate::error::ParseError;
+use crate::stable::DiagramKind;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Trait for converting AST back to Mermaid syntax
 ///
@@ -31,6 +38,82 @@ pub trait MermaidPrinter {
 
     /// Convert the AST to Mermaid syntax with custom formatting options
     fn to_mermaid_pretty(&self, options: &PrintOptions) -> String;
+
+    /// Whether printing with `options` is stable: parsing the output and
+    /// printing the result again gives the same text. See
+    /// [`check_idempotent`].
+    fn is_idempotent(&self, options: &PrintOptions) -> bool {
+        check_idempotent(self, options).is_ok()
+    }
+}
+
+/// Why [`check_idempotent`] rejected a diagram
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotenceError {
+    /// The printed output does not parse
+    Reparse { printed: String, error: ParseError },
+    /// Printing the reparsed output gives different text
+    Unstable { first: String, second: String },
+}
+
+impl std::fmt::Display for IdempotenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdempotenceError::Reparse { error, .. } => {
+                write!(f, "printed output does not parse: {}", error)
+            }
+            IdempotenceError::Unstable { first, second } => {
+                let line = first
+                    .lines()
+                    .zip(second.lines())
+                    .position(|(a, b)| a != b)
+                    .unwrap_or_else(|| first.lines().count().min(second.lines().count()));
+                write!(
+                    f,
+                    "printing the reparsed output differs from line {}",
+                    line + 1
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdempotenceError {}
+
+/// Print `diagram` with `options` and check that parsing and printing the
+/// output again reproduces it exactly, returning the printed text
+///
+/// Formatter integrations can use this before writing a file, so running the
+/// formatter twice never changes the file a second time.
+///
+/// ```rust
+/// use mermaid_parser::common::pretty_print::{check_idempotent, PrintOptions};
+///
+/// let diagram = mermaid_parser::parse_diagram("flowchart LR\n    A-->B")?;
+/// let formatted = check_idempotent(&diagram, &PrintOptions::default())?;
+/// assert!(formatted.starts_with("flowchart LR"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn check_idempotent<P: MermaidPrinter + ?Sized>(
+    diagram: &P,
+    options: &PrintOptions,
+) -> std::result::Result<String, IdempotenceError> {
+    let first = diagram.to_mermaid_pretty(options);
+    let reparsed = match frontmatter::parse_document(&first) {
+        Ok(document) => document,
+        Err(error) => {
+            return Err(IdempotenceError::Reparse {
+                printed: first,
+                error,
+            })
+        }
+    };
+    let second = reparsed.to_mermaid_pretty(options);
+    if first == second {
+        Ok(first)
+    } else {
+        Err(IdempotenceError::Unstable { first, second })
+    }
 }
 
 /// Options for pretty printing Mermaid diagrams
@@ -50,6 +133,8 @@ pub trait MermaidPrinter {
 ///     align_arrows: true,     // Align arrow operators
 ///     sort_nodes: true,       // Sort nodes alphabetically
 ///     compact_mode: false,    // Use readable formatting
+///     minify: false,          // Keep ids and layout as written
+///     normalize_keywords: false, // Keep `switch` and `type: NORMAL`
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -64,6 +149,16 @@ pub struct PrintOptions {
     pub sort_nodes: bool,
     /// Whether to use compact formatting (minimal whitespace)
     pub compact_mode: bool,
+    /// Whether to produce the smallest equivalent Mermaid. Flowcharts get
+    /// generated ids and merged statements, see
+    /// [`FlowchartDiagram::to_mermaid_minified`]; other diagrams are printed
+    /// as in `compact_mode`.
+    pub minify: bool,
+    /// Whether to print the canonical spelling of aliased keywords, such as
+    /// `checkout` for `switch`, and leave out defaults the source spelled
+    /// out, such as `type: NORMAL`. When `false` the author's spelling is
+    /// kept.
+    pub normalize_keywords: bool,
 }
 
 impl Default for PrintOptions {
@@ -74,10 +169,143 @@ impl Default for PrintOptions {
             align_arrows: false,
             sort_nodes: false,
             compact_mode: false,
+            minify: false,
+            normalize_keywords: false,
         }
     }
 }
 
+/// Flowcharts with more edges than this are printed without aligned arrows
+/// by [`PrintOptions::for_diagram`], since one long node id would push every
+/// arrow far to the right
+pub const ALIGN_ARROWS_MAX_EDGES: usize = 30;
+
+impl PrintOptions {
+    /// Defaults tuned for diagrams of `kind`: sequence diagrams, whose
+    /// blocks nest deeply, are indented by 2 spaces and flowcharts align
+    /// their arrows. Other kinds get [`PrintOptions::default`].
+    ///
+    /// ```rust
+    /// use mermaid_parser::common::pretty_print::PrintOptions;
+    /// use mermaid_parser::stable::DiagramKind;
+    ///
+    /// assert_eq!(PrintOptions::preset(DiagramKind::Sequence).indent_width, 2);
+    /// assert!(PrintOptions::preset(DiagramKind::Flowchart).align_arrows);
+    /// ```
+    pub fn preset(kind: DiagramKind) -> Self {
+        match kind {
+            DiagramKind::Sequence => Self {
+                indent_width: 2,
+                ..Self::default()
+            },
+            DiagramKind::Flowchart => Self {
+                align_arrows: true,
+                ..Self::default()
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// The [`PrintOptions::preset`] for the kind of `diagram`, without
+    /// aligned arrows for flowcharts of more than [`ALIGN_ARROWS_MAX_EDGES`]
+    /// edges
+    pub fn for_diagram(diagram: &DiagramType) -> Self {
+        let mut options = Self::preset(DiagramKind::from(diagram));
+        if let DiagramType::Flowchart(flowchart) = diagram {
+            options.align_arrows &= flowchart.edges.len() <= ALIGN_ARROWS_MAX_EDGES;
+        }
+        options
+    }
+}
+
+/// Named [`PrintOptions`], such as a team's house style, and the options to
+/// use for each diagram kind
+///
+/// Kinds without options of their own use [`PrintOptions::preset`]. A new
+/// registry has the presets `default` ([`PrintOptions::default`]) and
+/// `compact`.
+///
+/// ```rust
+/// use mermaid_parser::common::pretty_print::{MermaidPrinter, PrintOptions, PrintPresets};
+/// use mermaid_parser::stable::DiagramKind;
+///
+/// let mut presets = PrintPresets::new();
+/// presets.register("house", PrintOptions { indent_width: 3, ..Default::default() });
+/// presets.set_for_kind(DiagramKind::Flowchart, presets.get("house").unwrap().clone());
+///
+/// let diagram = mermaid_parser::parse_diagram("flowchart TD\n    A --> B")?;
+/// assert_eq!(presets.for_diagram(&diagram).indent_width, 3);
+/// assert_eq!(presets.for_kind(DiagramKind::Sequence).indent_width, 2);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrintPresets {
+    named: BTreeMap<String, PrintOptions>,
+    kinds: HashMap<DiagramKind, PrintOptions>,
+}
+
+impl PrintPresets {
+    pub fn new() -> Self {
+        let mut presets = Self {
+            named: BTreeMap::new(),
+            kinds: HashMap::new(),
+        };
+        presets.register("default", PrintOptions::default());
+        presets.register(
+            "compact",
+            PrintOptions {
+                compact_mode: true,
+                ..PrintOptions::default()
+            },
+        );
+        presets
+    }
+
+    /// Add or replace the preset called `name`
+    pub fn register(&mut self, name: impl Into<String>, options: PrintOptions) -> &mut Self {
+        self.named.insert(name.into(), options);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PrintOptions> {
+        self.named.get(name)
+    }
+
+    /// Names of the registered presets, in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.named.keys().map(String::as_str)
+    }
+
+    /// Use `options` for every diagram of `kind`
+    pub fn set_for_kind(&mut self, kind: DiagramKind, options: PrintOptions) -> &mut Self {
+        self.kinds.insert(kind, options);
+        self
+    }
+
+    /// The options set for `kind`, or else its [`PrintOptions::preset`]
+    pub fn for_kind(&self, kind: DiagramKind) -> PrintOptions {
+        self.kinds
+            .get(&kind)
+            .cloned()
+            .unwrap_or_else(|| PrintOptions::preset(kind))
+    }
+
+    /// The options set for the kind of `diagram`, or else
+    /// [`PrintOptions::for_diagram`]
+    pub fn for_diagram(&self, diagram: &DiagramType) -> PrintOptions {
+        match self.kinds.get(&DiagramKind::from(diagram)) {
+            Some(options) => options.clone(),
+            None => PrintOptions::for_diagram(diagram),
+        }
+    }
+}
+
+impl Default for PrintPresets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MermaidPrinter for DiagramType {
     fn to_mermaid(&self) -> String {
         self.to_mermaid_pretty(&PrintOptions::default())
@@ -133,7 +361,7 @@ impl PrettyPrinter {
             self.output.push('\n');
         }
 
-        if !self.options.compact_mode && self.current_indent > 0 {
+        if !self.compact() && self.current_indent > 0 {
             let indent = " ".repeat(self.current_indent * self.options.indent_width);
             self.output.push_str(&indent);
         }
@@ -146,14 +374,18 @@ impl PrettyPrinter {
         self.output.push_str(content);
     }
 
+    fn compact(&self) -> bool {
+        self.options.compact_mode || self.options.minify
+    }
+
     fn indent(&mut self) {
-        if !self.options.compact_mode {
+        if !self.compact() {
             self.current_indent += 1;
         }
     }
 
     fn dedent(&mut self) {
-        if !self.options.compact_mode && self.current_indent > 0 {
+        if !self.compact() && self.current_indent > 0 {
             self.current_indent -= 1;
         }
     }
@@ -170,6 +402,9 @@ impl MermaidPrinter for FlowchartDiagram {
     }
 
     fn to_mermaid_pretty(&self, options: &PrintOptions) -> String {
+        if options.minify {
+            return self.to_mermaid_minified().0;
+        }
         let mut printer = PrettyPrinter::new(options.clone());
 
         // Write diagram type and direction
@@ -249,10 +484,12 @@ impl MermaidPrinter for FlowchartDiagram {
             referenced_nodes.insert(edge.to.clone());
         }
 
-        for (id, node) in &self.nodes {
-            if !referenced_nodes.contains(id) {
-                write_flow_node(&mut printer, id, node);
-            }
+        let standalone = self
+            .nodes
+            .iter()
+            .filter(|(id, _)| !referenced_nodes.contains(*id));
+        for (id, node) in standalone {
+            write_flow_node(&mut printer, id, node);
         }
 
         // Write subgraphs
@@ -266,24 +503,28 @@ impl MermaidPrinter for FlowchartDiagram {
         }
 
         // Write class definitions
-        for (name, class_def) in &self.class_defs {
-            let styles_str = class_def
-                .styles
-                .iter()
-                .map(|(k, v)| format!("{}:{}", k, v))
-                .collect::<Vec<_>>()
-                .join(",");
-            printer.write_line(&format!("classDef {} {}", name, styles_str));
+        let mut class_defs: Vec<_> = self.class_defs.iter().collect();
+        class_defs.sort_by_key(|(name, _)| *name);
+        for (name, class_def) in class_defs {
+            printer.write_line(&format!(
+                "classDef {} {}",
+                name,
+                format_style_properties(&class_def.styles)
+            ));
         }
 
+        write_extra_node_classes(&mut printer, &self.nodes);
+
         // Write click events
         for click in &self.clicks {
             match &click.action {
                 ClickAction::Href(url, target) => {
                     let target_str = target.as_deref().unwrap_or("_self");
                     printer.write_line(&format!(
-                        "click {} \"{}\" \"{}\"",
-                        click.node_id, url, target_str
+                        "click {} {} {}",
+                        click.node_id,
+                        escape::quoted(url),
+                        escape::quoted(target_str)
                     ));
                 }
                 ClickAction::Callback(func) => {
@@ -292,8 +533,11 @@ impl MermaidPrinter for FlowchartDiagram {
                 ClickAction::Both(callback, url, target) => {
                     let target_str = target.as_deref().unwrap_or("_self");
                     printer.write_line(&format!(
-                        "click {} call {} \"{}\" \"{}\"",
-                        click.node_id, callback, url, target_str
+                        "click {} call {} {} {}",
+                        click.node_id,
+                        callback,
+                        escape::quoted(url),
+                        escape::quoted(target_str)
                     ));
                 }
             }
@@ -304,30 +548,342 @@ impl MermaidPrinter for FlowchartDiagram {
     }
 }
 
-fn write_flow_node(printer: &mut PrettyPrinter, id: &str, node: &FlowNode) {
-    let text = node.text.as_deref().unwrap_or("");
-    let shape_str = match &node.shape {
-        NodeShape::Rectangle => format!("{}[{}]", id, text),
-        NodeShape::RoundedRectangle => format!("{}({})", id, text),
-        NodeShape::Stadium => format!("{}([{}])", id, text),
-        NodeShape::Subroutine => format!("{}[[{}]]", id, text),
-        NodeShape::Cylinder => format!("{}[({})]", id, text),
-        NodeShape::Circle => format!("{}(({})))", id, text),
-        NodeShape::Asymmetric => format!("{}>{}]", id, text),
-        NodeShape::Rhombus => format!("{}{{{}}}", id, text),
-        NodeShape::Hexagon => format!("{}{{{{{}}}}}", id, text),
-        NodeShape::Parallelogram => format!("{}[/{}\\]", id, text),
-        NodeShape::ParallelogramAlt => format!("{}[\\{}/]", id, text),
-        NodeShape::Trapezoid => format!("{}[/{}/]", id, text),
-        NodeShape::TrapezoidAlt => format!("{}[\\{}\\]", id, text),
-        NodeShape::DoubleCircle => format!("{}((({})))", id, text),
+/// Letters for generated node ids. `o` and `x` are left out, as `a--ob`
+/// would read as a circle or cross edge.
+const MINIFIED_ID_LETTERS: &str = "abcdefghijklmnpqrstuvwyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+impl FlowchartDiagram {
+    /// The smallest Mermaid for this diagram, and the generated id each node
+    /// was renamed to
+    ///
+    /// Labelled nodes get the shortest free ids (`a`, `b`, ..., `aa`, ...) in
+    /// order of first use. Nodes shown by their own id keep it, since
+    /// renaming them would mean writing the old id as a label. Edges that
+    /// share an end, arrow and label are merged with `&`, optional whitespace
+    /// is dropped, and defaults such as a top-to-bottom direction or a
+    /// `_self` click target are left out. Merging can change the order in
+    /// which edges are listed.
+    pub fn to_mermaid_minified(&self) -> (String, BTreeMap<String, String>) {
+        let renames = minified_ids(self);
+        let mut diagram = self.clone();
+        diagram.rename_nodes(&renames);
+
+        let mut lines = vec![match diagram.direction {
+            FlowDirection::TB | FlowDirection::TD => "graph".to_string(),
+            ref direction => format!("graph {}", direction.keyword()),
+        }];
+        if let Some(title) = &diagram.title {
+            lines.push(format!("title {}", title));
+        }
+        if let Some(title) = &diagram.accessibility.title {
+            lines.push(format!("accTitle:{}", title));
+        }
+        if let Some(desc) = &diagram.accessibility.description {
+            lines.push(format!("accDescr:{}", desc));
+        }
+
+        let mut defined = HashSet::new();
+        write_minified_edges(&mut lines, &diagram.edges, &diagram.nodes, &mut defined);
+        for subgraph in &diagram.subgraphs {
+            write_minified_subgraph(&mut lines, subgraph, &diagram.nodes, &mut defined);
+        }
+
+        let standalone: Vec<&String> = diagram
+            .nodes
+            .keys()
+            .filter(|id| !defined.contains(id.as_str()))
+            .collect();
+        if !standalone.is_empty() {
+            let refs: Vec<String> = standalone
+                .into_iter()
+                .map(|id| minified_node_ref(id, &diagram.nodes, &mut defined))
+                .collect();
+            lines.push(refs.join("&"));
+        }
+
+        for style in &diagram.styles {
+            let styles = minified_styles(&style.styles);
+            lines.push(match &style.target {
+                StyleTarget::Node(id) | StyleTarget::Subgraph(id) => {
+                    format!("style {} {}", id, styles)
+                }
+                StyleTarget::Edge(from, to) => format!("linkStyle {}--{} {}", from, to, styles),
+            });
+        }
+
+        let mut class_defs: Vec<&ClassDef> = diagram.class_defs.values().collect();
+        class_defs.sort_by(|a, b| a.name.cmp(&b.name));
+        for class_def in class_defs {
+            lines.push(format!(
+                "classDef {} {}",
+                class_def.name,
+                minified_styles(&class_def.styles)
+            ));
+        }
+
+        // Classes past the first, which is written as `:::name`
+        let mut class_members: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (id, node) in &diagram.nodes {
+            for class in node.classes.iter().skip(1) {
+                class_members.entry(class).or_default().push(id);
+            }
+        }
+        for (class, mut ids) in class_members {
+            ids.sort();
+            lines.push(format!("class {} {}", ids.join(","), class));
+        }
+
+        for click in &diagram.clicks {
+            let target = |target: &Option<String>| match target.as_deref() {
+                None | Some("_self") => String::new(),
+                Some(target) => format!(" {}", escape::quoted(target)),
+            };
+            lines.push(match &click.action {
+                ClickAction::Href(url, window) => {
+                    let url = escape::quoted(url);
+                    format!("click {} {}{}", click.node_id, url, target(window))
+                }
+                ClickAction::Callback(func) => format!("click {} call {}", click.node_id, func),
+                ClickAction::Both(callback, url, window) => format!(
+                    "click {} call {} {}{}",
+                    click.node_id,
+                    callback,
+                    escape::quoted(url),
+                    target(window)
+                ),
+            });
+        }
+
+        (lines.join("\n"), renames)
+    }
+}
+
+/// Generated ids for the labelled nodes of a diagram, in order of first use
+fn minified_ids(diagram: &FlowchartDiagram) -> BTreeMap<String, String> {
+    fn collect<'a>(
+        subgraphs: &'a [Subgraph],
+        order: &mut Vec<&'a str>,
+        taken: &mut HashSet<&'a str>,
+    ) {
+        for subgraph in subgraphs {
+            taken.insert(&subgraph.id);
+            order.extend(subgraph.nodes.iter().map(String::as_str));
+            for edge in &subgraph.edges {
+                order.extend([edge.from.as_str(), edge.to.as_str()]);
+            }
+            collect(&subgraph.subgraphs, order, taken);
+        }
+    }
+
+    let mut order: Vec<&str> = Vec::new();
+    let mut taken: HashSet<&str> = HashSet::new();
+    for edge in &diagram.edges {
+        order.extend([edge.from.as_str(), edge.to.as_str()]);
+    }
+    collect(&diagram.subgraphs, &mut order, &mut taken);
+    order.extend(diagram.nodes.keys().map(String::as_str));
+
+    let keeps_id = |id: &str| match diagram.nodes.get(id) {
+        Some(node) => match &node.text {
+            Some(text) => text == id && node.shape == NodeShape::Rectangle,
+            None => true,
+        },
+        None => true,
+    };
+    let mut renamed: Vec<&str> = Vec::new();
+    for id in order {
+        if taken.contains(id) || renamed.contains(&id) {
+            continue;
+        }
+        if keeps_id(id) {
+            taken.insert(id);
+        } else {
+            renamed.push(id);
+        }
+    }
+
+    let mut reserved: Vec<&str> =
+        crate::common::constants::flowchart_keywords::RESERVED_IDS.to_vec();
+    reserved.extend(["TB", "TD", "BT", "RL", "LR"]);
+    let mut candidates = (0..)
+        .map(minified_id)
+        .filter(|id| !taken.contains(id.as_str()) && !reserved.contains(&id.as_str()));
+    renamed
+        .into_iter()
+        .map(|id| (id.to_string(), candidates.next().unwrap()))
+        .collect()
+}
+
+/// The `index`th id in the sequence `a`, `b`, ..., `Z`, `aa`, `ab`, ...
+fn minified_id(mut index: usize) -> String {
+    let letters: Vec<char> = MINIFIED_ID_LETTERS.chars().collect();
+    let mut id = Vec::new();
+    loop {
+        id.push(letters[index % letters.len()]);
+        index /= letters.len();
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    id.iter().rev().collect()
+}
+
+/// Write edges as `a&b-->c&d` statements, merging edges with the same arrow
+/// and label that share a source, then those that share all their targets
+fn write_minified_edges(
+    lines: &mut Vec<String>,
+    edges: &[FlowEdge],
+    nodes: &OrderedMap<String, FlowNode>,
+    defined: &mut HashSet<String>,
+) {
+    struct Statement<'a> {
+        sources: Vec<&'a str>,
+        arrow: String,
+        label: Option<&'a str>,
+        targets: Vec<&'a str>,
+    }
+
+    let mut fanouts: Vec<Statement> = Vec::new();
+    for edge in edges {
+        let arrow = edge_arrow(&edge.edge_type);
+        let label = edge.label.as_deref().filter(|label| !label.is_empty());
+        // A repeated edge is kept as a separate statement
+        let existing = fanouts.iter_mut().find(|s| {
+            s.sources[0] == edge.from
+                && s.arrow == arrow
+                && s.label == label
+                && !s.targets.contains(&edge.to.as_str())
+        });
+        match existing {
+            Some(statement) => statement.targets.push(&edge.to),
+            None => fanouts.push(Statement {
+                sources: vec![&edge.from],
+                arrow,
+                label,
+                targets: vec![&edge.to],
+            }),
+        }
+    }
+
+    let mut statements: Vec<Statement> = Vec::new();
+    for fanout in fanouts {
+        let existing = statements.iter_mut().find(|s| {
+            s.arrow == fanout.arrow
+                && s.label == fanout.label
+                && s.targets == fanout.targets
+                && !s.sources.contains(&fanout.sources[0])
+        });
+        match existing {
+            Some(statement) => statement.sources.push(fanout.sources[0]),
+            None => statements.push(fanout),
+        }
+    }
+
+    for statement in statements {
+        let mut refs = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| minified_node_ref(id, nodes, defined))
+                .collect::<Vec<_>>()
+                .join("&")
+        };
+        let sources = refs(&statement.sources);
+        let targets = refs(&statement.targets);
+        let label = statement.label.map(escape::pipe_label).unwrap_or_default();
+        // `o` and `x` heads would run into the node ids next to them
+        let arrow = if statement.arrow.contains(['o', 'x']) {
+            format!(" {} ", statement.arrow)
+        } else {
+            statement.arrow
+        };
+        lines.push(format!("{}{}{}{}", sources, arrow, label, targets));
+    }
+}
+
+fn write_minified_subgraph(
+    lines: &mut Vec<String>,
+    subgraph: &Subgraph,
+    nodes: &OrderedMap<String, FlowNode>,
+    defined: &mut HashSet<String>,
+) {
+    lines.push(match &subgraph.title {
+        Some(title) => format!("subgraph {}[{}]", subgraph.id, escape::bracket_label(title)),
+        None => format!("subgraph {}", subgraph.id),
+    });
+    if let Some(direction) = &subgraph.direction {
+        lines.push(format!("direction {}", direction.keyword()));
+    }
+    if !subgraph.nodes.is_empty() {
+        let refs: Vec<String> = subgraph
+            .nodes
+            .iter()
+            .map(|id| minified_node_ref(id, nodes, defined))
+            .collect();
+        lines.push(refs.join("&"));
+    }
+    write_minified_edges(lines, &subgraph.edges, nodes, defined);
+    for nested in &subgraph.subgraphs {
+        write_minified_subgraph(lines, nested, nodes, defined);
+    }
+    lines.push("end".to_string());
+}
+
+/// A node's definition the first time it is written, its id after that
+fn minified_node_ref(
+    id: &str,
+    nodes: &OrderedMap<String, FlowNode>,
+    defined: &mut HashSet<String>,
+) -> String {
+    if !defined.insert(id.to_string()) {
+        return id.to_string();
+    }
+    let Some(node) = nodes.get(id) else {
+        return id.to_string();
+    };
+
+    let definition = match &node.shape {
+        NodeShape::Image { .. } | NodeShape::Special(_) => {
+            let fields = node_metadata_fields(node)
+                .iter()
+                .map(|(key, value)| format!("{}:{}", key, value))
+                .collect::<Vec<_>>();
+            format!("{}@{{{}}}", id, fields.join(","))
+        }
+        NodeShape::Rectangle if node.text.as_deref().map_or(true, |text| text == id) => {
+            id.to_string()
+        }
+        _ => return format_node_with_definition(id, node),
     };
-    printer.write_line(&shape_str);
+    match node.classes.first() {
+        Some(class) => format!("{}:::{}", definition, class),
+        None => definition,
+    }
+}
+
+/// `key:value` pairs in key order
+fn minified_styles(styles: &std::collections::HashMap<String, String>) -> String {
+    let mut styles: Vec<String> = styles.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+    styles.sort();
+    styles.join(",")
+}
+
+fn edge_arrow(edge_type: &EdgeType) -> String {
+    ArrowKind::Flowchart(edge_type.clone())
+        .canonical()
+        .into_owned()
+}
+
+fn write_flow_node(printer: &mut PrettyPrinter, id: &str, node: &FlowNode) {
+    printer.write_line(&format_node_with_definition(id, node));
 }
 
 fn write_subgraph(printer: &mut PrettyPrinter, subgraph: &Subgraph) {
     if let Some(title) = &subgraph.title {
-        printer.write_line(&format!("subgraph {} [{}]", subgraph.id, title));
+        printer.write_line(&format!(
+            "subgraph {} [{}]",
+            subgraph.id,
+            escape::bracket_label(title)
+        ));
     } else {
         printer.write_line(&format!("subgraph {}", subgraph.id));
     }
@@ -366,21 +922,10 @@ fn write_subgraph(printer: &mut PrettyPrinter, subgraph: &Subgraph) {
 fn write_flow_edge_with_smart_nodes(
     printer: &mut PrettyPrinter,
     edge: &FlowEdge,
-    nodes: &std::collections::HashMap<String, FlowNode>,
+    nodes: &OrderedMap<String, FlowNode>,
     defined_nodes: &mut std::collections::HashSet<String>,
 ) {
-    let arrow = match &edge.edge_type {
-        EdgeType::Arrow => "-->",
-        EdgeType::DottedArrow => "-.->",
-        EdgeType::ThickArrow => "==>",
-        EdgeType::OpenLink => "---",
-        EdgeType::DottedLink => "-.-",
-        EdgeType::ThickLink => "===",
-        EdgeType::Invisible => "~~~",
-        EdgeType::CircleEdge => "--o",
-        EdgeType::CrossEdge => "--x",
-        EdgeType::MultiDirectional => "<-->",
-    };
+    let arrow = edge_arrow(&edge.edge_type);
 
     // Format source node - use definition if not defined yet, otherwise just ID
     let source_str = if !defined_nodes.contains(&edge.from) {
@@ -407,7 +952,8 @@ fn write_flow_edge_with_smart_nodes(
     };
 
     let edge_str = if let Some(label) = &edge.label {
-        format!("{} {}|{}| {}", source_str, arrow, label, target_str)
+        let label = escape::pipe_label(label);
+        format!("{} {}{} {}", source_str, arrow, label, target_str)
     } else {
         format!("{} {} {}", source_str, arrow, target_str)
     };
@@ -415,30 +961,96 @@ fn write_flow_edge_with_smart_nodes(
     printer.write_line(&edge_str);
 }
 
-fn format_node_with_definition(id: &str, node: &FlowNode) -> String {
-    let text = node.text.as_deref().unwrap_or("");
+/// The `@{ }` fields of an image or named-shape node, as key and value
+fn node_metadata_fields(node: &FlowNode) -> Vec<(&'static str, String)> {
+    let mut fields = Vec::new();
     match &node.shape {
+        NodeShape::Image {
+            url,
+            position,
+            width,
+            height,
+            constrained,
+        } => {
+            fields.push(("img", escape::quoted(url)));
+            if let Some(label) = &node.text {
+                fields.push(("label", escape::quoted(label)));
+            }
+            if let Some(position) = position {
+                let position = match position {
+                    ImagePosition::Top => "t",
+                    ImagePosition::Bottom => "b",
+                };
+                fields.push(("pos", escape::quoted(position)));
+            }
+            if let Some(width) = width {
+                fields.push(("w", width.to_string()));
+            }
+            if let Some(height) = height {
+                fields.push(("h", height.to_string()));
+            }
+            if let Some(constrained) = constrained {
+                let value = if *constrained { "on" } else { "off" };
+                fields.push(("constraint", escape::quoted(value)));
+            }
+        }
+        NodeShape::Special(shape) => {
+            fields.push(("shape", shape.clone()));
+            if let Some(label) = &node.text {
+                fields.push(("label", escape::quoted(label)));
+            }
+        }
+        _ => {}
+    }
+    fields
+}
+
+fn format_node_with_definition(id: &str, node: &FlowNode) -> String {
+    let text = escape::bracket_label(node.text.as_deref().unwrap_or(""));
+    let definition = match &node.shape {
         NodeShape::Rectangle => format!("{}[{}]", id, text),
         NodeShape::RoundedRectangle => format!("{}({})", id, text),
         NodeShape::Stadium => format!("{}([{}])", id, text),
         NodeShape::Subroutine => format!("{}[[{}]]", id, text),
         NodeShape::Cylinder => format!("{}[({})]", id, text),
-        NodeShape::Circle => format!("{}(({})))", id, text),
+        NodeShape::Circle => format!("{}(({}))", id, text),
         NodeShape::Asymmetric => format!("{}>{}]", id, text),
         NodeShape::Rhombus => format!("{}{{{}}}", id, text),
         NodeShape::Hexagon => format!("{}{{{{{}}}}}", id, text),
-        NodeShape::Parallelogram => format!("{}[/{}\\]", id, text),
-        NodeShape::ParallelogramAlt => format!("{}[\\{}/]", id, text),
-        NodeShape::Trapezoid => format!("{}[/{}/]", id, text),
-        NodeShape::TrapezoidAlt => format!("{}[\\{}\\]", id, text),
+        NodeShape::Parallelogram => format!("{}[/{}/]", id, text),
+        NodeShape::ParallelogramAlt => format!("{}[\\{}\\]", id, text),
+        NodeShape::Trapezoid => format!("{}[/{}\\]", id, text),
+        NodeShape::TrapezoidAlt => format!("{}[\\{}/]", id, text),
         NodeShape::DoubleCircle => format!("{}((({})))", id, text),
+        NodeShape::Image { .. } | NodeShape::Special(_) => {
+            let fields = node_metadata_fields(node)
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value))
+                .collect::<Vec<_>>();
+            format!("{}@{{ {} }}", id, fields.join(", "))
+        }
+    };
+
+    // Mermaid accepts a single `:::` class; further classes are written as
+    // `class` statements by `write_extra_node_classes`
+    match node.classes.first() {
+        Some(class) => format!("{}:::{}", definition, class),
+        None => definition,
+    }
+}
+
+fn write_extra_node_classes(printer: &mut PrettyPrinter, nodes: &OrderedMap<String, FlowNode>) {
+    for (id, node) in nodes {
+        for class in node.classes.iter().skip(1) {
+            printer.write_line(&format!("class {} {}", id, class));
+        }
     }
 }
 
 fn write_aligned_flow_edges_with_smart_nodes(
     printer: &mut PrettyPrinter,
     edges: &[FlowEdge],
-    nodes: &std::collections::HashMap<String, FlowNode>,
+    nodes: &OrderedMap<String, FlowNode>,
     defined_nodes: &mut std::collections::HashSet<String>,
 ) {
     // First pass: calculate what the source strings will be for alignment
@@ -464,18 +1076,7 @@ fn write_aligned_flow_edges_with_smart_nodes(
 
     // Second pass: write the aligned edges
     for (i, edge) in edges.iter().enumerate() {
-        let arrow = match &edge.edge_type {
-            EdgeType::Arrow => "-->",
-            EdgeType::DottedArrow => "-.->",
-            EdgeType::ThickArrow => "==>",
-            EdgeType::OpenLink => "---",
-            EdgeType::DottedLink => "-.-",
-            EdgeType::ThickLink => "===",
-            EdgeType::Invisible => "~~~",
-            EdgeType::CircleEdge => "--o",
-            EdgeType::CrossEdge => "--x",
-            EdgeType::MultiDirectional => "<-->",
-        };
+        let arrow = edge_arrow(&edge.edge_type);
 
         // Use the pre-calculated source string
         let source_str = &source_strings[i];
@@ -501,8 +1102,12 @@ fn write_aligned_flow_edges_with_smart_nodes(
 
         let edge_str = if let Some(label) = &edge.label {
             format!(
-                "{}{} {}|{}| {}",
-                source_str, padding, arrow, label, target_str
+                "{}{} {}{} {}",
+                source_str,
+                padding,
+                arrow,
+                escape::pipe_label(label),
+                target_str
             )
         } else {
             format!("{}{} {} {}", source_str, padding, arrow, target_str)
@@ -517,23 +1122,13 @@ fn write_aligned_flow_edges(printer: &mut PrettyPrinter, edges: &[FlowEdge]) {
     let max_source_len = edges.iter().map(|edge| edge.from.len()).max().unwrap_or(0);
 
     for edge in edges {
-        let arrow = match &edge.edge_type {
-            EdgeType::Arrow => "-->",
-            EdgeType::DottedArrow => "-.->",
-            EdgeType::ThickArrow => "==>",
-            EdgeType::OpenLink => "---",
-            EdgeType::DottedLink => "-.-",
-            EdgeType::ThickLink => "===",
-            EdgeType::Invisible => "~~~",
-            EdgeType::CircleEdge => "--o",
-            EdgeType::CrossEdge => "--x",
-            EdgeType::MultiDirectional => "<-->",
-        };
+        let arrow = edge_arrow(&edge.edge_type);
 
         let padding = " ".repeat(max_source_len - edge.from.len());
 
         let edge_str = if let Some(label) = &edge.label {
-            format!("{}{} {} |{}| {}", edge.from, padding, arrow, label, edge.to)
+            let label = escape::pipe_label(label);
+            format!("{}{} {} {} {}", edge.from, padding, arrow, label, edge.to)
         } else {
             format!("{}{} {} {}", edge.from, padding, arrow, edge.to)
         };
@@ -543,21 +1138,11 @@ fn write_aligned_flow_edges(printer: &mut PrettyPrinter, edges: &[FlowEdge]) {
 }
 
 fn write_flow_edge(printer: &mut PrettyPrinter, edge: &FlowEdge) {
-    let arrow = match &edge.edge_type {
-        EdgeType::Arrow => "-->",
-        EdgeType::DottedArrow => "-.->",
-        EdgeType::ThickArrow => "==>",
-        EdgeType::OpenLink => "---",
-        EdgeType::DottedLink => "-.-",
-        EdgeType::ThickLink => "===",
-        EdgeType::Invisible => "~~~",
-        EdgeType::CircleEdge => "--o",
-        EdgeType::CrossEdge => "--x",
-        EdgeType::MultiDirectional => "<-->",
-    };
+    let arrow = edge_arrow(&edge.edge_type);
 
     let edge_str = if let Some(label) = &edge.label {
-        format!("{} {} |{}| {}", edge.from, arrow, label, edge.to)
+        let label = escape::pipe_label(label);
+        format!("{} {} {} {}", edge.from, arrow, label, edge.to)
     } else {
         format!("{} {} {}", edge.from, arrow, edge.to)
     };
@@ -565,13 +1150,19 @@ fn write_flow_edge(printer: &mut PrettyPrinter, edge: &FlowEdge) {
     printer.write_line(&edge_str);
 }
 
-fn write_style_definition(printer: &mut PrettyPrinter, style: &StyleDefinition) {
-    let styles_str = style
-        .styles
-        .iter()
+/// `key:value` pairs sorted by key, so output does not depend on hash ordering
+fn format_style_properties(styles: &HashMap<String, String>) -> String {
+    let mut properties: Vec<_> = styles.iter().collect();
+    properties.sort();
+    properties
+        .into_iter()
         .map(|(k, v)| format!("{}:{}", k, v))
         .collect::<Vec<_>>()
-        .join(",");
+        .join(",")
+}
+
+fn write_style_definition(printer: &mut PrettyPrinter, style: &StyleDefinition) {
+    let styles_str = format_style_properties(&style.styles);
 
     match &style.target {
         StyleTarget::Node(id) => {
@@ -625,18 +1216,38 @@ impl MermaidPrinter for SequenceDiagram {
             }
         }
 
-        // Write participants
+        // Write participants; those in a box are written together inside it,
+        // where its first participant is declared
+        let mut written_boxes = vec![false; self.boxes.len()];
         for participant in &self.participants {
-            let type_str = match participant.participant_type {
-                ParticipantType::Participant => "participant",
-                ParticipantType::Actor => "actor",
+            let in_box = self.boxes.iter().position(|participant_box| {
+                participant_box.participants.contains(&participant.actor)
+            });
+            let Some(index) = in_box else {
+                write_sequence_participant(&mut printer, participant);
+                continue;
             };
-
-            if let Some(alias) = &participant.alias {
-                printer.write_line(&format!("{} {} as {}", type_str, participant.actor, alias));
-            } else {
-                printer.write_line(&format!("{} {}", type_str, participant.actor));
+            if written_boxes[index] {
+                continue;
             }
+            written_boxes[index] = true;
+
+            let participant_box = &self.boxes[index];
+            let color = participant_box.color.as_ref().map(Color::to_string);
+            let header = ["box"]
+                .into_iter()
+                .chain(color.as_deref())
+                .chain(participant_box.title.as_deref())
+                .collect::<Vec<_>>();
+            printer.write_line(&header.join(" "));
+            printer.indent();
+            for member in &participant_box.participants {
+                if let Some(participant) = self.participants.iter().find(|p| p.actor == *member) {
+                    write_sequence_participant(&mut printer, participant);
+                }
+            }
+            printer.dedent();
+            printer.write_line("end");
         }
 
         // Write statements
@@ -649,21 +1260,39 @@ impl MermaidPrinter for SequenceDiagram {
     }
 }
 
+fn write_sequence_participant(printer: &mut PrettyPrinter, participant: &Participant) {
+    let type_str = match participant.participant_type {
+        ParticipantType::Participant => "participant",
+        ParticipantType::Actor => "actor",
+    };
+
+    let actor = escape::participant(&participant.actor);
+    if let Some(alias) = &participant.alias {
+        printer.write_line(&format!("{} {} as {}", type_str, actor, alias));
+    } else {
+        printer.write_line(&format!("{} {}", type_str, actor));
+    }
+}
+
 fn write_sequence_statement(printer: &mut PrettyPrinter, statement: &SequenceStatement) {
     match statement {
         SequenceStatement::Message(msg) => {
-            let arrow = match msg.arrow_type {
-                ArrowType::SolidOpen => "->",
-                ArrowType::SolidClosed => "->>",
-                ArrowType::DottedOpen => "-->",
-                ArrowType::DottedClosed => "-->>",
-                ArrowType::Cross => "-x",
-                ArrowType::Point => "-)",
-                ArrowType::BiDirectionalSolid => "<->",
-                ArrowType::BiDirectionalDotted => "<-->",
+            let arrow = ArrowKind::Sequence(msg.arrow_type.clone()).canonical();
+
+            let activation = match msg.activation {
+                Some(MessageActivation::Activate) => "+",
+                Some(MessageActivation::Deactivate) => "-",
+                None => "",
             };
 
-            printer.write_line(&format!("{} {} {}: {}", msg.from, arrow, msg.to, msg.text));
+            printer.write_line(&format!(
+                "{} {} {}{}: {}",
+                escape::participant(&msg.from),
+                arrow,
+                activation,
+                escape::participant(&msg.to),
+                msg.text
+            ));
         }
         SequenceStatement::Note(note) => {
             let position = match &note.position {
@@ -671,7 +1300,13 @@ fn write_sequence_statement(printer: &mut PrettyPrinter, statement: &SequenceSta
                 NotePosition::RightOf => "right of",
                 NotePosition::Over => "over",
             };
-            printer.write_line(&format!("note {} {}: {}", position, note.actor, note.text));
+            let actors: Vec<String> = note.actor.split(',').map(escape::participant).collect();
+            printer.write_line(&format!(
+                "note {} {}: {}",
+                position,
+                actors.join(","),
+                note.text
+            ));
         }
         SequenceStatement::Loop(loop_stmt) => {
             printer.write_line(&format!("loop {}", loop_stmt.condition));
@@ -714,6 +1349,15 @@ fn write_sequence_statement(printer: &mut PrettyPrinter, statement: &SequenceSta
             printer.dedent();
             printer.write_line("end");
         }
+        SequenceStatement::Break(block) => {
+            printer.write_line(&format!("break {}", block.condition));
+            printer.indent();
+            for stmt in &block.statements {
+                write_sequence_statement(printer, stmt);
+            }
+            printer.dedent();
+            printer.write_line("end");
+        }
         SequenceStatement::Par(par) => {
             let first = &par.branches[0];
             if let Some(condition) = &first.condition {
@@ -761,17 +1405,29 @@ fn write_sequence_statement(printer: &mut PrettyPrinter, statement: &SequenceSta
             printer.dedent();
             printer.write_line("end");
         }
+        SequenceStatement::Rect { color, statements } => {
+            printer.write_line(&format!("rect {}", color));
+            printer.indent();
+            for stmt in statements {
+                write_sequence_statement(printer, stmt);
+            }
+            printer.dedent();
+            printer.write_line("end");
+        }
         SequenceStatement::Activate(actor) => {
-            printer.write_line(&format!("activate {}", actor));
+            printer.write_line(&format!("activate {}", escape::participant(actor)));
         }
         SequenceStatement::Deactivate(actor) => {
-            printer.write_line(&format!("deactivate {}", actor));
+            printer.write_line(&format!("deactivate {}", escape::participant(actor)));
         }
         SequenceStatement::Create(participant) => {
-            printer.write_line(&format!("create participant {}", participant.actor));
+            printer.write_line(&format!(
+                "create participant {}",
+                escape::participant(&participant.actor)
+            ));
         }
         SequenceStatement::Destroy(actor) => {
-            printer.write_line(&format!("destroy {}", actor));
+            printer.write_line(&format!("destroy {}", escape::participant(actor)));
         }
     }
 }
@@ -801,13 +1457,40 @@ impl MermaidPrinter for ClassDiagram {
             printer.write_line(&format!("accDescr: {}", desc));
         }
 
-        // Write classes (sorted for deterministic output)
-        let mut classes: Vec<_> = self.classes.iter().collect();
-        classes.sort_by_key(|(name, _)| *name);
+        if let Some(direction) = &self.direction {
+            printer.write_line(&format!("direction {}", direction.keyword()));
+        }
+
+        // Write classes outside namespaces in declaration order
+        let in_namespace: HashSet<&str> = self
+            .namespaces
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        let classes = self
+            .classes
+            .iter()
+            .filter(|(name, _)| !in_namespace.contains(name.as_str()));
         for (name, class) in classes {
             write_class(&mut printer, name, class);
         }
 
+        // Write namespaces, each with its classes in declaration order
+        let mut namespaces: Vec<_> = self.namespaces.iter().collect();
+        namespaces.sort_by_key(|(name, _)| *name);
+        for (namespace, members) in namespaces {
+            printer.write_line(&format!("namespace {} {{", namespace));
+            printer.indent();
+            for name in members {
+                if let Some(class) = self.classes.get(name) {
+                    write_class(&mut printer, name, class);
+                }
+            }
+            printer.dedent();
+            printer.write_line("}");
+        }
+
         // Write relationships
         for rel in &self.relationships {
             write_class_relationship(&mut printer, rel);
@@ -815,7 +1498,33 @@ impl MermaidPrinter for ClassDiagram {
 
         // Write notes
         for note in &self.notes {
-            printer.write_line(&format!("note \"{}\"", note.text));
+            printer.write_line(&format!("note {}", escape::escaped_quoted(&note.text)));
+        }
+
+        for style in &self.styles {
+            write_style_definition(&mut printer, style);
+        }
+
+        for click in &self.clicks {
+            let href = |url: &str, target: &Option<String>| match target {
+                Some(target) => format!(
+                    "click {} href {} {}",
+                    click.node_id,
+                    escape::quoted(url),
+                    target
+                ),
+                None => format!("click {} href {}", click.node_id, escape::quoted(url)),
+            };
+            match &click.action {
+                ClickAction::Href(url, target) => printer.write_line(&href(url, target)),
+                ClickAction::Callback(callback) => {
+                    printer.write_line(&format!("click {} call {}", click.node_id, callback));
+                }
+                ClickAction::Both(callback, url, target) => {
+                    printer.write_line(&format!("click {} call {}", click.node_id, callback));
+                    printer.write_line(&href(url, target));
+                }
+            }
         }
 
         printer.dedent();
@@ -823,8 +1532,21 @@ impl MermaidPrinter for ClassDiagram {
     }
 }
 
+/// `~`-delimited type parameters, or nothing when there are none
+fn generic_suffix(generics: &[String]) -> String {
+    if generics.is_empty() {
+        String::new()
+    } else {
+        format!("~{}~", generics.join(","))
+    }
+}
+
 fn write_class(printer: &mut PrettyPrinter, name: &str, class: &Class) {
-    printer.write_line(&format!("class {} {{", name));
+    printer.write_line(&format!(
+        "class {}{} {{",
+        name,
+        generic_suffix(&class.generics)
+    ));
     printer.indent();
 
     if let Some(stereotype) = &class.stereotype {
@@ -856,11 +1578,16 @@ fn write_class(printer: &mut PrettyPrinter, name: &str, class: &Class) {
                     String::new()
                 };
 
-                // Format: visibility[static]Type name[default]
+                // Format: visibility[static]Type[~generics~] name[default]
                 if let Some(prop_type) = &prop.prop_type {
                     printer.write_line(&format!(
-                        "{}{}{} {}{}",
-                        visibility, static_mod, prop_type, prop.name, default_str
+                        "{}{}{}{} {}{}",
+                        visibility,
+                        static_mod,
+                        prop_type,
+                        generic_suffix(&prop.generics),
+                        prop.name,
+                        default_str
                     ));
                 } else {
                     printer.write_line(&format!(
@@ -892,11 +1619,17 @@ fn write_class(printer: &mut PrettyPrinter, name: &str, class: &Class) {
                     .collect::<Vec<_>>()
                     .join(", ");
 
-                // Format: visibility[static][abstract]methodName(params)[ returnType]
+                // Format: visibility[static][abstract]methodName(params)[ returnType[~generics~]]
                 let method_str = if let Some(return_type) = &method.return_type {
                     format!(
-                        "{}{}{}{}({}) {}",
-                        visibility, static_mod, abstract_mod, method.name, params_str, return_type
+                        "{}{}{}{}({}) {}{}",
+                        visibility,
+                        static_mod,
+                        abstract_mod,
+                        method.name,
+                        params_str,
+                        return_type,
+                        generic_suffix(&method.generics)
                     )
                 } else {
                     format!(
@@ -915,33 +1648,24 @@ fn write_class(printer: &mut PrettyPrinter, name: &str, class: &Class) {
 
     // Add CSS class if present
     if let Some(css_class) = &class.css_class {
-        printer.write_line(&format!("class {} {}", name, css_class));
+        printer.write_line(&format!("cssClass {} {}", escape::quoted(name), css_class));
     }
 }
 
 fn write_class_relationship(printer: &mut PrettyPrinter, rel: &ClassRelationship) {
-    let rel_type = match rel.relationship_type {
-        ClassRelationshipType::Inheritance => "<|--",
-        ClassRelationshipType::Composition => "*--",
-        ClassRelationshipType::Aggregation => "o--",
-        ClassRelationshipType::Association => "<--",
-        ClassRelationshipType::Link => "--",
-        ClassRelationshipType::DashedLink => "..",
-        ClassRelationshipType::Dependency => "<..",
-        ClassRelationshipType::Realization => "<|..",
-    };
+    let rel_type = ArrowKind::Class(rel.relationship_type.clone()).canonical();
 
     let mut rel_str = String::new();
     rel_str.push_str(&rel.from);
 
     if let Some(from_card) = &rel.from_cardinality {
-        rel_str.push_str(&format!(" \"{}\"", from_card));
+        rel_str.push_str(&format!(" {}", escape::escaped_quoted(from_card)));
     }
 
     rel_str.push_str(&format!(" {} ", rel_type));
 
     if let Some(to_card) = &rel.to_cardinality {
-        rel_str.push_str(&format!("\"{}\" ", to_card));
+        rel_str.push_str(&format!("{} ", escape::escaped_quoted(to_card)));
     }
 
     rel_str.push_str(&rel.to);
@@ -982,58 +1706,94 @@ impl MermaidPrinter for StateDiagram {
             printer.write_line(&format!("accDescr: {}", desc));
         }
 
-        // Write states (sorted for deterministic output)
-        let mut states: Vec<_> = self.states.iter().collect();
-        states.sort_by_key(|(id, _)| *id);
-        for (id, state) in states {
-            write_state(&mut printer, id, state);
+        if let Some(direction) = &self.direction {
+            printer.write_line(&format!("direction {}", direction.keyword()));
         }
 
-        // Write transitions
-        for transition in &self.transitions {
-            let mut trans_str = format!("{} --> {}", transition.from, transition.to);
-
-            if transition.event.is_some()
-                || transition.guard.is_some()
-                || transition.action.is_some()
-            {
-                trans_str.push_str(" : ");
-                if let Some(event) = &transition.event {
-                    trans_str.push_str(event);
-                }
-                if let Some(guard) = &transition.guard {
-                    trans_str.push_str(&format!(" [{}]", guard));
-                }
-                if let Some(action) = &transition.action {
-                    trans_str.push_str(&format!(" / {}", action));
-                }
-            }
+        write_states(&mut printer, &self.states);
 
-            printer.write_line(&trans_str);
+        // Write transitions, including the initial and final `[*]` markers
+        for transition in &self.transitions {
+            write_state_transition(&mut printer, transition);
         }
 
         // Write notes
         for note in &self.notes {
-            let position = match note.position {
-                StateNotePosition::LeftOf => "left of",
-                StateNotePosition::RightOf => "right of",
-                StateNotePosition::Above => "above",
-                StateNotePosition::Below => "below",
-            };
+            write_state_note(&mut printer, note);
+        }
+
+        // Write class definitions, then the classes applied to each state
+        let mut class_defs: Vec<_> = self.class_defs.iter().collect();
+        class_defs.sort_by_key(|(name, _)| *name);
+        for (name, class_def) in class_defs {
             printer.write_line(&format!(
-                "note {} {} : {}",
-                position, note.target, note.text
+                "classDef {} {}",
+                name,
+                format_style_properties(&class_def.styles)
             ));
         }
+        for state in self.all_states() {
+            for class in &state.classes {
+                printer.write_line(&format!("class {} {}", state.id, class));
+            }
+        }
 
         printer.dedent();
         printer.finish()
     }
 }
 
+fn write_state_transition(printer: &mut PrettyPrinter, transition: &StateTransition) {
+    let mut trans_str = format!(
+        "{} {} {}",
+        transition.from,
+        ArrowKind::State.canonical(),
+        transition.to
+    );
+
+    if transition.event.is_some() || transition.guard.is_some() || transition.action.is_some() {
+        trans_str.push_str(" : ");
+        if let Some(event) = &transition.event {
+            trans_str.push_str(event);
+        }
+        if let Some(guard) = &transition.guard {
+            trans_str.push_str(&format!(" [{}]", guard));
+        }
+        if let Some(action) = &transition.action {
+            trans_str.push_str(&format!(" / {}", action));
+        }
+    }
+
+    printer.write_line(&trans_str);
+}
+
+fn write_state_note(printer: &mut PrettyPrinter, note: &StateNote) {
+    let position = match note.position {
+        StateNotePosition::LeftOf => "left of",
+        StateNotePosition::RightOf => "right of",
+        StateNotePosition::Above => "above",
+        StateNotePosition::Below => "below",
+    };
+    printer.write_line(&format!(
+        "note {} {} : {}",
+        position, note.target, note.text
+    ));
+}
+
+/// Write the states of a scope in declaration order. The `[*]` pseudo-state
+/// is implied by the transitions that reference it, so it is never declared.
+fn write_states(printer: &mut PrettyPrinter, states: &OrderedMap<String, State>) {
+    let states = states.iter().filter(|(id, _)| id.as_str() != "[*]");
+    for (id, state) in states {
+        write_state(printer, id, state);
+    }
+}
+
 fn write_state(printer: &mut PrettyPrinter, id: &str, state: &State) {
     match &state.state_type {
-        StateType::Simple => {
+        // Start markers only exist as `[*] --> id` transitions, which are printed
+        // from the transition list
+        StateType::Simple | StateType::Start => {
             if let Some(display_name) = &state.display_name {
                 printer.write_line(&format!("{} : {}", id, display_name));
             } else {
@@ -1044,29 +1804,32 @@ fn write_state(printer: &mut PrettyPrinter, id: &str, state: &State) {
             printer.write_line(&format!("state {} {{", id));
             printer.indent();
 
-            // Write substates
-            for substate_id in &state.substates {
-                printer.write_line(substate_id);
+            // Write the body, or each concurrent region with its own body
+            write_states(printer, &state.states);
+            for transition in &state.transitions {
+                write_state_transition(printer, transition);
+            }
+            for note in &state.notes {
+                write_state_note(printer, note);
             }
-
-            // Write concurrent regions
             for (i, region) in state.concurrent_regions.iter().enumerate() {
                 if i > 0 {
                     printer.write_line("--");
                 }
-                for state_id in region {
-                    printer.write_line(state_id);
+                write_states(printer, &region.states);
+                for transition in &region.transitions {
+                    write_state_transition(printer, transition);
+                }
+                for note in &region.notes {
+                    write_state_note(printer, note);
                 }
             }
 
             printer.dedent();
             printer.write_line("}");
         }
-        StateType::Start => {
-            printer.write_line(&format!("[*] --> {}", id));
-        }
         StateType::End => {
-            printer.write_line(&format!("{} --> [*]", id));
+            printer.write_line(&format!("state {} <<end>>", id));
         }
         StateType::Choice => {
             printer.write_line(&format!("state {} <<choice>>", id));
@@ -1107,20 +1870,31 @@ impl MermaidPrinter for ErDiagram {
 
         // Write relationships
         for rel in &self.relationships {
-            let left_card = format_er_cardinality(&rel.left_cardinality);
-            let right_card = format_er_cardinality(&rel.right_cardinality);
-            let label = rel.label.as_deref().unwrap_or("");
-            printer.write_line(&format!(
-                "{} {}--{} {} : {}",
-                rel.left_entity, left_card, right_card, rel.right_entity, label
-            ));
+            let left_card = format_er_cardinality(&rel.left_cardinality, true);
+            let right_card = format_er_cardinality(&rel.right_cardinality, false);
+            let mut line = format!(
+                "{} {}--{} {}",
+                format_er_name(&rel.left_entity),
+                left_card,
+                right_card,
+                format_er_name(&rel.right_entity)
+            );
+            if let Some(label) = &rel.label {
+                line.push_str(&format!(" : {}", format_er_name(label)));
+            }
+            printer.write_line(&line);
         }
 
-        // Write entities (sorted for deterministic output)
-        let mut entities: Vec<_> = self.entities.iter().collect();
-        entities.sort_by_key(|(name, _)| *name);
-        for (name, entity) in entities {
-            printer.write_line(&format!("{} {{", name));
+        // Write entities in declaration order
+        for (name, entity) in &self.entities {
+            let mut header = format_er_name(name);
+            if let Some(display_name) = &entity.display_name {
+                header.push_str(&format!("[{}]", format_er_display_name(display_name)));
+            }
+            if let Some(class) = &entity.css_class {
+                header.push_str(&format!(":::{}", class));
+            }
+            printer.write_line(&format!("{} {{", header));
             printer.indent();
 
             for attr in &entity.attributes {
@@ -1138,8 +1912,11 @@ impl MermaidPrinter for ErDiagram {
                         format!("{} {}{}", attr.attr_type, attr.name, key_str)
                     } else {
                         format!(
-                            "{} {}{} \"{}\"",
-                            attr.attr_type, attr.name, key_str, comment
+                            "{} {}{} {}",
+                            attr.attr_type,
+                            attr.name,
+                            key_str,
+                            escape::attribute_comment(comment)
                         )
                     }
                 } else {
@@ -1152,21 +1929,59 @@ impl MermaidPrinter for ErDiagram {
             printer.write_line("}");
         }
 
+        for style in &self.styles {
+            write_style_definition(&mut printer, style);
+        }
+        let mut class_defs: Vec<_> = self.class_defs.iter().collect();
+        class_defs.sort_by_key(|(name, _)| *name);
+        for (name, class_def) in class_defs {
+            printer.write_line(&format!(
+                "classDef {} {}",
+                name,
+                format_style_properties(&class_def.styles)
+            ));
+        }
+
         printer.dedent();
         printer.finish()
     }
 }
 
-fn format_er_cardinality(card: &ErCardinality) -> &'static str {
-    match (&card.min, &card.max) {
-        (CardinalityValue::Zero, CardinalityValue::One) => "o|",
-        (CardinalityValue::One, CardinalityValue::One) => "||",
-        (CardinalityValue::Zero, CardinalityValue::Many) => "o{",
-        (CardinalityValue::One, CardinalityValue::Many) => "|{",
+/// Format one end of a relationship, mirrored when it sits on the left
+fn format_er_cardinality(card: &ErCardinality, left: bool) -> &'static str {
+    match (&card.min, &card.max, left) {
+        (CardinalityValue::Zero, CardinalityValue::One, true) => "|o",
+        (CardinalityValue::Zero, CardinalityValue::One, false) => "o|",
+        (CardinalityValue::Zero, CardinalityValue::Many, true) => "}o",
+        (CardinalityValue::Zero, CardinalityValue::Many, false) => "o{",
+        (CardinalityValue::One, CardinalityValue::Many, true) => "}|",
+        (CardinalityValue::One, CardinalityValue::Many, false) => "|{",
         _ => "||", // Default
     }
 }
 
+/// Quote display names that would end the `alias[...]` brackets early
+fn format_er_display_name(name: &str) -> String {
+    if name.contains([']', '"']) || name.trim() != name || name.is_empty() {
+        escape::escaped_quoted(name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Quote entity names and labels that wouldn't lex as a single word
+fn format_er_name(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        name.to_string()
+    } else {
+        escape::escaped_quoted(name)
+    }
+}
+
 // Pie chart implementation
 impl MermaidPrinter for PieDiagram {
     fn to_mermaid(&self) -> String {
@@ -1176,10 +1991,15 @@ impl MermaidPrinter for PieDiagram {
     fn to_mermaid_pretty(&self, options: &PrintOptions) -> String {
         let mut printer = PrettyPrinter::new(options.clone());
 
+        let header = if self.show_data {
+            "pie showData"
+        } else {
+            "pie"
+        };
         if let Some(title) = &self.title {
-            printer.write_line(&format!("pie title {}", title));
+            printer.write_line(&format!("{} title {}", header, title));
         } else {
-            printer.write_line("pie");
+            printer.write_line(header);
         }
 
         printer.indent();
@@ -1194,7 +2014,11 @@ impl MermaidPrinter for PieDiagram {
 
         // Write data points
         for slice in &self.data {
-            printer.write_line(&format!("\"{}\" : {}", slice.label, slice.value));
+            printer.write_line(&format!(
+                "{} : {}",
+                escape::quoted(&slice.label),
+                slice.value
+            ));
         }
 
         printer.dedent();
@@ -1257,6 +2081,10 @@ impl MermaidPrinter for GanttDiagram {
             printer.write_line(&format!("todayMarker {}", marker));
         }
 
+        if let Some(mode) = &self.display_mode {
+            printer.write_line(&format!("displayMode {}", mode));
+        }
+
         // Write sections and tasks
         for section in &self.sections {
             printer.write_line(&format!("section {}", section.name));
@@ -1344,21 +2172,20 @@ impl MermaidPrinter for GitDiagram {
                     id,
                     commit_type,
                     tag,
+                    explicit_type,
                 } => {
                     let mut commit_str = String::from("commit");
 
                     if let Some(id_val) = id {
-                        commit_str.push_str(&format!(" id: \"{}\"", id_val));
+                        commit_str.push_str(&format!(" id: {}", escape::escaped_quoted(id_val)));
                     }
 
-                    match commit_type {
-                        CommitType::Reverse => commit_str.push_str(" type: REVERSE"),
-                        CommitType::Highlight => commit_str.push_str(" type: HIGHLIGHT"),
-                        _ => {}
+                    if let Some(written) = git_commit_type(commit_type, *explicit_type, options) {
+                        commit_str.push_str(&format!(" type: {}", written));
                     }
 
                     if let Some(tag_val) = tag {
-                        commit_str.push_str(&format!(" tag: \"{}\"", tag_val));
+                        commit_str.push_str(&format!(" tag: {}", escape::escaped_quoted(tag_val)));
                     }
 
                     printer.write_line(&commit_str);
@@ -1370,42 +2197,47 @@ impl MermaidPrinter for GitDiagram {
                     }
                     printer.write_line(&branch_str);
                 }
-                GitOperation::Checkout { branch } => {
-                    printer.write_line(&format!("checkout {}", branch));
+                GitOperation::Checkout { branch, keyword } => {
+                    let keyword = if options.normalize_keywords {
+                        CheckoutKeyword::Checkout
+                    } else {
+                        *keyword
+                    };
+                    printer.write_line(&format!("{} {}", keyword.keyword(), branch));
                 }
                 GitOperation::Merge {
                     branch,
                     id,
                     tag,
                     commit_type,
+                    explicit_type,
                 } => {
                     let mut merge_str = format!("merge {}", branch);
 
                     if let Some(id_val) = id {
-                        merge_str.push_str(&format!(" id: \"{}\"", id_val));
+                        merge_str.push_str(&format!(" id: {}", escape::escaped_quoted(id_val)));
                     }
 
-                    match commit_type {
-                        CommitType::Reverse => merge_str.push_str(" type: REVERSE"),
-                        CommitType::Highlight => merge_str.push_str(" type: HIGHLIGHT"),
-                        _ => {}
+                    if let Some(written) = git_commit_type(commit_type, *explicit_type, options) {
+                        merge_str.push_str(&format!(" type: {}", written));
                     }
 
                     if let Some(tag_val) = tag {
-                        merge_str.push_str(&format!(" tag: \"{}\"", tag_val));
+                        merge_str.push_str(&format!(" tag: {}", escape::escaped_quoted(tag_val)));
                     }
 
                     printer.write_line(&merge_str);
                 }
                 GitOperation::CherryPick { id, parent, tag } => {
-                    let mut cp_str = format!("cherry-pick id: \"{}\"", id);
+                    let mut cp_str = format!("cherry-pick id: {}", escape::escaped_quoted(id));
 
                     if let Some(parent_val) = parent {
-                        cp_str.push_str(&format!(" parent: \"{}\"", parent_val));
+                        cp_str
+                            .push_str(&format!(" parent: {}", escape::escaped_quoted(parent_val)));
                     }
 
                     if let Some(tag_val) = tag {
-                        cp_str.push_str(&format!(" tag: \"{}\"", tag_val));
+                        cp_str.push_str(&format!(" tag: {}", escape::escaped_quoted(tag_val)));
                     }
 
                     printer.write_line(&cp_str);
@@ -1418,6 +2250,21 @@ impl MermaidPrinter for GitDiagram {
     }
 }
 
+/// The `type:` value to print for a commit or merge, if any. `NORMAL` is
+/// the default and only printed when the source spelled it out.
+fn git_commit_type(
+    commit_type: &CommitType,
+    explicit: bool,
+    options: &PrintOptions,
+) -> Option<&'static str> {
+    match commit_type {
+        CommitType::Normal if explicit && !options.normalize_keywords => Some("NORMAL"),
+        CommitType::Normal => None,
+        CommitType::Reverse => Some("REVERSE"),
+        CommitType::Highlight => Some("HIGHLIGHT"),
+    }
+}
+
 // Mindmap implementation
 impl MermaidPrinter for MindmapDiagram {
     fn to_mermaid(&self) -> String {
@@ -1446,6 +2293,16 @@ impl MermaidPrinter for MindmapDiagram {
         // Write root node
         write_mindmap_node(&mut printer, &self.root, 0, true);
 
+        // Write cross-links, which only parse with the cross-link dialect
+        for edge in &self.cross_edges {
+            let label = edge
+                .label
+                .as_deref()
+                .map(escape::pipe_label)
+                .unwrap_or_default();
+            printer.write_line(&format!("{} -->{} {}", edge.from, label, edge.to));
+        }
+
         printer.dedent();
         printer.finish()
     }
@@ -1469,9 +2326,14 @@ fn write_mindmap_node(
         MindmapNodeShape::Hexagon => ("{{", "}}"),
     };
 
+    let text = match &node.text {
+        NodeText::Plain(text) => text.clone(),
+        NodeText::Markdown(text) => format!("\"`{}`\"", text),
+    };
+
     // For the root node, prefix with "root"
     let node_text = if is_root {
-        format!("root{}{}{}", shape_start, node.text, shape_end)
+        format!("root{}{}{}", shape_start, text, shape_end)
     } else if node.text.is_empty() && node.icon.is_some() {
         // Skip nodes with empty text that only have icons - they should be handled differently
         if let Some(icon) = &node.icon {
@@ -1483,7 +2345,7 @@ fn write_mindmap_node(
         }
         return;
     } else {
-        format!("{}{}{}", shape_start, node.text, shape_end)
+        format!("{}{}{}", shape_start, text, shape_end)
     };
 
     printer.write_line(&format!("{}{}", indent, node_text));
@@ -1609,9 +2471,31 @@ impl MermaidPrinter for SankeyDiagram {
         printer.write_line("sankey-beta");
         printer.indent();
 
+        // Write node metadata rows, sorted by node so the output is stable
+        let mut nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|node| !node.metadata.is_empty())
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        for node in nodes {
+            let mut row = vec![escape::csv_field(&node.name)];
+            row.extend(
+                node.metadata
+                    .iter()
+                    .map(|(key, value)| escape::csv_field(&format!("{}={}", key, value))),
+            );
+            printer.write_line(&row.join(","));
+        }
+
         // Write links
         for link in &self.links {
-            printer.write_line(&format!("{},{},{}", link.source, link.target, link.value));
+            printer.write_line(&format!(
+                "{},{},{}",
+                escape::csv_field(&link.source),
+                escape::csv_field(&link.target),
+                link.value
+            ));
         }
 
         printer.dedent();
@@ -1641,7 +2525,7 @@ impl MermaidPrinter for C4Diagram {
 
         // Write title
         if let Some(title) = &self.title {
-            printer.write_line(&format!("title {}", title));
+            printer.write_line(&format!("title {}", escape::bracket_label(title)));
         }
 
         // Write accessibility info
@@ -1652,14 +2536,25 @@ impl MermaidPrinter for C4Diagram {
             printer.write_line(&format!("accDescr: {}", desc));
         }
 
-        // Write elements
-        for element in self.elements.values() {
+        // Write tag definitions
+        for tag in &self.tags {
+            write_c4_tag(&mut printer, tag);
+        }
+
+        // Write elements outside any boundary in declaration order
+        let mut bounded = HashSet::new();
+        collect_c4_boundary_elements(&self.boundaries, &mut bounded);
+        let elements = self
+            .elements
+            .values()
+            .filter(|element| !bounded.contains(element.id.as_str()));
+        for element in elements {
             write_c4_element(&mut printer, element);
         }
 
-        // Write boundaries
+        // Write boundaries, defining their elements inside them
         for boundary in &self.boundaries {
-            write_c4_boundary(&mut printer, boundary);
+            write_c4_boundary(&mut printer, boundary, &self.elements);
         }
 
         // Write relationships
@@ -1667,13 +2562,14 @@ impl MermaidPrinter for C4Diagram {
             let mut rel_str = format!("Rel({}, {}", rel.from, rel.to);
 
             if let Some(label) = &rel.label {
-                rel_str.push_str(&format!(", \"{}\"", label));
+                rel_str.push_str(&format!(", {}", escape::escaped_quoted(label)));
             }
 
             if let Some(tech) = &rel.technology {
-                rel_str.push_str(&format!(", \"{}\"", tech));
+                rel_str.push_str(&format!(", {}", escape::escaped_quoted(tech)));
             }
 
+            write_c4_tags(&mut rel_str, &rel.tags);
             rel_str.push(')');
             printer.write_line(&rel_str);
         }
@@ -1696,29 +2592,81 @@ fn write_c4_element(printer: &mut PrettyPrinter, element: &C4Element) {
         C4ElementType::ComponentDb => "ComponentDb",
         C4ElementType::ComponentQueue => "ComponentQueue",
         C4ElementType::Node => "Node",
-        C4ElementType::DeploymentNode => "DeploymentNode",
+        C4ElementType::DeploymentNode => "Deployment_Node",
     };
 
     let ext_suffix = if element.is_external { "_Ext" } else { "" };
 
     let mut elem_str = format!(
-        "{}{}({}, \"{}\"",
-        elem_type, ext_suffix, element.id, element.name
+        "{}{}({}, {}",
+        elem_type,
+        ext_suffix,
+        element.id,
+        escape::escaped_quoted(&element.name)
     );
 
+    // Containers, components and nodes take their technology before the description
+    let has_technology = !matches!(
+        element.element_type,
+        C4ElementType::Person
+            | C4ElementType::System
+            | C4ElementType::SystemDb
+            | C4ElementType::SystemQueue
+    );
+    if has_technology && (element.technology.is_some() || element.description.is_some()) {
+        let tech = element.technology.as_deref().unwrap_or_default();
+        elem_str.push_str(&format!(", {}", escape::escaped_quoted(tech)));
+    }
+
     if let Some(desc) = &element.description {
-        elem_str.push_str(&format!(", \"{}\"", desc));
+        elem_str.push_str(&format!(", {}", escape::escaped_quoted(desc)));
     }
 
-    if let Some(tech) = &element.technology {
-        elem_str.push_str(&format!(", \"{}\"", tech));
+    if let Some(sprite) = &element.sprite {
+        elem_str.push_str(&format!(", $sprite={}", escape::escaped_quoted(sprite)));
     }
 
+    write_c4_tags(&mut elem_str, &element.tags);
     elem_str.push(')');
     printer.write_line(&elem_str);
 }
 
-fn write_c4_boundary(printer: &mut PrettyPrinter, boundary: &C4Boundary) {
+fn write_c4_tags(call: &mut String, tags: &[String]) {
+    if !tags.is_empty() {
+        call.push_str(&format!(
+            ", $tags={}",
+            escape::escaped_quoted(&tags.join("+"))
+        ));
+    }
+}
+
+fn write_c4_tag(printer: &mut PrettyPrinter, tag: &C4TagDefinition) {
+    let macro_name = match tag.kind {
+        C4TagKind::Element => "AddElementTag",
+        C4TagKind::Relationship => "AddRelTag",
+        C4TagKind::Boundary => "AddBoundaryTag",
+    };
+
+    let mut tag_str = format!("{}({}", macro_name, escape::escaped_quoted(&tag.name));
+    for (key, value) in &tag.properties {
+        tag_str.push_str(&format!(", ${}={}", key, escape::escaped_quoted(value)));
+    }
+    tag_str.push(')');
+    printer.write_line(&tag_str);
+}
+
+fn collect_c4_boundary_elements<'a>(boundaries: &'a [C4Boundary], ids: &mut HashSet<&'a str>) {
+    for boundary in boundaries {
+        ids.extend(boundary.elements.iter().map(String::as_str));
+        collect_c4_boundary_elements(&boundary.boundaries, ids);
+    }
+}
+
+fn write_c4_boundary(
+    printer: &mut PrettyPrinter,
+    boundary: &C4Boundary,
+    elements: &OrderedMap<String, C4Element>,
+) {
     let boundary_type = match boundary.boundary_type {
         C4BoundaryType::System => "System_Boundary",
         C4BoundaryType::Container => "Container_Boundary",
@@ -1726,26 +2674,43 @@ fn write_c4_boundary(printer: &mut PrettyPrinter, boundary: &C4Boundary) {
         C4BoundaryType::Generic => "Boundary",
     };
 
-    printer.write_line(&format!(
-        "{}({}, \"{}\") {{",
-        boundary_type, boundary.id, boundary.label
-    ));
+    let mut boundary_str = format!(
+        "{}({}, {}",
+        boundary_type,
+        boundary.id,
+        escape::escaped_quoted(&boundary.label)
+    );
+    write_c4_tags(&mut boundary_str, &boundary.tags);
+    printer.write_line(&format!("{}) {{", boundary_str));
     printer.indent();
 
     // Write elements in boundary
     for elem_id in &boundary.elements {
-        printer.write_line(elem_id);
+        match elements.get(elem_id) {
+            Some(element) => write_c4_element(printer, element),
+            None => printer.write_line(elem_id),
+        }
     }
 
     // Write nested boundaries
     for nested in &boundary.boundaries {
-        write_c4_boundary(printer, nested);
+        write_c4_boundary(printer, nested, elements);
     }
 
     printer.dedent();
     printer.write_line("}");
 }
 
+/// Format an axis line, writing the labels exactly as parsed and only the sides present
+fn format_quadrant_axis(keyword: &str, axis: &AxisDefinition) -> String {
+    match (&axis.label_start, &axis.label_end) {
+        (Some(start), Some(end)) => format!("{} {} --> {}", keyword, start, end),
+        (Some(start), None) => format!("{} {}", keyword, start),
+        (None, Some(end)) => format!("{} --> {}", keyword, end),
+        (None, None) => format!("{} -->", keyword),
+    }
+}
+
 // Quadrant chart implementation
 impl MermaidPrinter for QuadrantDiagram {
     fn to_mermaid(&self) -> String {
@@ -1773,15 +2738,11 @@ impl MermaidPrinter for QuadrantDiagram {
 
         // Write axis labels
         if let Some(x_axis) = &self.x_axis {
-            let x_start = x_axis.label_start.as_deref().unwrap_or("");
-            let x_end = x_axis.label_end.as_deref().unwrap_or("");
-            printer.write_line(&format!("x-axis \"{}\" --> \"{}\"", x_start, x_end));
+            printer.write_line(&format_quadrant_axis("x-axis", x_axis));
         }
 
         if let Some(y_axis) = &self.y_axis {
-            let y_start = y_axis.label_start.as_deref().unwrap_or("");
-            let y_end = y_axis.label_end.as_deref().unwrap_or("");
-            printer.write_line(&format!("y-axis \"{}\" --> \"{}\"", y_start, y_end));
+            printer.write_line(&format_quadrant_axis("y-axis", y_axis));
         }
 
         // Write quadrant labels
@@ -1826,7 +2787,7 @@ impl MermaidPrinter for XyChartDiagram {
 
         // Write title
         if let Some(title) = &self.title {
-            printer.write_line(&format!("title \"{}\"", title));
+            printer.write_line(&format!("title {}", escape::quoted(title)));
         }
 
         // Write accessibility info
@@ -1840,14 +2801,14 @@ impl MermaidPrinter for XyChartDiagram {
         // Write x-axis
         let mut x_str = String::from("x-axis");
         if let Some(title) = &self.x_axis.title {
-            x_str.push_str(&format!(" \"{}\"", title));
+            x_str.push_str(&format!(" {}", escape::quoted(title)));
         }
         if !self.x_axis.labels.is_empty() {
             let labels = self
                 .x_axis
                 .labels
                 .iter()
-                .map(|l| format!("\"{}\"", l))
+                .map(|label| escape::quoted(label))
                 .collect::<Vec<_>>()
                 .join(", ");
             x_str.push_str(&format!(" [{}]", labels));
@@ -1860,7 +2821,7 @@ impl MermaidPrinter for XyChartDiagram {
         // Write y-axis
         let mut y_str = String::from("y-axis");
         if let Some(title) = &self.y_axis.title {
-            y_str.push_str(&format!(" \"{}\"", title));
+            y_str.push_str(&format!(" {}", escape::quoted(title)));
         }
         if let Some((min, max)) = &self.y_axis.range {
             y_str.push_str(&format!(" {} --> {}", min, max));
@@ -1877,12 +2838,16 @@ impl MermaidPrinter for XyChartDiagram {
             let data_str = series
                 .data
                 .iter()
-                .map(|d| d.to_string())
+                .map(|d| match d {
+                    Some(value) => value.to_string(),
+                    None => "null".to_string(),
+                })
                 .collect::<Vec<_>>()
                 .join(", ");
 
             if let Some(name) = &series.name {
-                printer.write_line(&format!("{} \"{}\" [{}]", series_type, name, data_str));
+                let name = escape::quoted(name);
+                printer.write_line(&format!("{} {} [{}]", series_type, name, data_str));
             } else {
                 printer.write_line(&format!("{} [{}]", series_type, data_str));
             }
@@ -1983,16 +2948,11 @@ impl MermaidPrinter for BlockDiagram {
 
         // Write connections
         for conn in &self.connections {
-            let arrow = match conn.arrow_type {
-                BlockArrowType::Normal => "-->",
-                BlockArrowType::Dotted => "-.->",
-                BlockArrowType::Thick => "==>",
-                BlockArrowType::Invisible => "~~~",
-                BlockArrowType::Bidirectional => "<-->",
-            };
+            let arrow = ArrowKind::Block(conn.arrow_type.clone()).canonical();
 
             if let Some(label) = &conn.label {
-                printer.write_line(&format!("{} {}|{}| {}", conn.from, arrow, label, conn.to));
+                let label = escape::pipe_label(label);
+                printer.write_line(&format!("{} {}{} {}", conn.from, arrow, label, conn.to));
             } else {
                 printer.write_line(&format!("{} {} {}", conn.from, arrow, conn.to));
             }
@@ -2006,27 +2966,36 @@ impl MermaidPrinter for BlockDiagram {
 fn write_block(printer: &mut PrettyPrinter, block: &Block) {
     match block {
         Block::Simple { id, label, shape } => {
-            let shape_str = match shape {
-                BlockShape::Rectangle => "",
-                BlockShape::RoundedRect => "()",
-                BlockShape::Rhombus => "{{}}",
-                BlockShape::Circle => "(())",
-                BlockShape::Ellipse => "([])",
-                BlockShape::Cylinder => "[()]",
-                BlockShape::Custom(s) => s,
+            let Some(label_text) = label else {
+                printer.write_line(id);
+                return;
             };
 
-            if let Some(label_text) = label {
-                printer.write_line(&format!("{}{} \"{}\"", id, shape_str, label_text));
-            } else {
-                printer.write_line(&format!("{}{}", id, shape_str));
-            }
+            let (open, close) = match shape {
+                BlockShape::Rectangle | BlockShape::RoundedRect => ("[", "]"),
+                BlockShape::Rhombus => ("{", "}"),
+                BlockShape::Circle => ("((", "))"),
+                BlockShape::Ellipse => ("([", "])"),
+                BlockShape::Cylinder => ("[(", ")]"),
+                BlockShape::Custom(_) => ("[", "]"),
+            };
+            let label_text = escape::escaped_quoted(label_text);
+            printer.write_line(&format!("{}{}{}{}", id, open, label_text, close));
         }
         Block::Composite { id, label, blocks } => {
+            let header = if id.is_empty() {
+                "block".to_string()
+            } else {
+                format!("block:{}", id)
+            };
             if let Some(label_text) = label {
-                printer.write_line(&format!("block:{} \"{}\"", id, label_text));
+                printer.write_line(&format!(
+                    "{} {}",
+                    header,
+                    escape::escaped_quoted(label_text)
+                ));
             } else {
-                printer.write_line(&format!("block:{}", id));
+                printer.write_line(&header);
             }
             printer.indent();
 
@@ -2081,66 +3050,74 @@ impl MermaidPrinter for ArchitectureDiagram {
         };
         printer.write_line(&format!("direction {}", dir_str));
 
-        // Write groups
-        for (id, group) in &self.groups {
-            printer.write_line(&format!("group {} {}", id, group.title));
-            printer.indent();
-
-            if let Some(icon) = &group.icon {
-                printer.write_line(&format!("icon {}", icon));
+        let declaration = |keyword: &str,
+                           id: &str,
+                           icon: &Option<String>,
+                           title: Option<&str>,
+                           in_group: &Option<String>| {
+            let mut line = format!("{} {}", keyword, id);
+            if let Some(icon) = icon {
+                line.push_str(&format!("({})", icon));
             }
-
-            printer.dedent();
-        }
-
-        // Write services
-        for (id, service) in &self.services {
-            let mut service_str = format!("service {}", id);
-
-            if let Some(icon) = &service.icon {
-                service_str.push_str(&format!("({}) ", icon));
+            if let Some(title) = title {
+                line.push_str(&format!("[{}]", escape::bracket_text(title)));
             }
-
-            service_str.push_str(&format!(" \"{}\"", service.title));
-
-            if let Some(group) = &service.in_group {
-                service_str.push_str(&format!(" in {}", group));
+            if let Some(group) = in_group {
+                line.push_str(&format!(" in {}", group));
             }
+            line
+        };
 
-            printer.write_line(&service_str);
+        // Write groups, services and junctions in declaration order
+        for group in self.groups.values() {
+            printer.write_line(&declaration(
+                "group",
+                &group.id,
+                &group.icon,
+                Some(&group.title),
+                &group.in_group,
+            ));
         }
 
-        // Write junctions
-        for (id, junction) in &self.junctions {
-            let mut junc_str = format!("junction {}", id);
-
-            if let Some(group) = &junction.in_group {
-                junc_str.push_str(&format!(" in {}", group));
-            }
+        for service in self.services.values() {
+            printer.write_line(&declaration(
+                "service",
+                &service.id,
+                &service.icon,
+                Some(&service.title),
+                &service.in_group,
+            ));
+        }
 
-            printer.write_line(&junc_str);
+        for junction in self.junctions.values() {
+            printer.write_line(&declaration(
+                "junction",
+                &junction.id,
+                &None,
+                None,
+                &junction.in_group,
+            ));
         }
 
         // Write edges
         for edge in &self.edges {
-            let from_str = format_edge_endpoint(&edge.from);
-            let to_str = format_edge_endpoint(&edge.to);
-
-            let edge_type_str = match edge.edge_type {
-                ArchEdgeType::Solid => "--",
-                ArchEdgeType::Dotted => "..",
-                ArchEdgeType::Arrow => "->",
-                ArchEdgeType::BiArrow => "<->",
+            let edge_type_str = match &edge.legacy_arrow {
+                Some(arrow) => arrow.clone(),
+                None => ArrowKind::Architecture(edge.edge_type.clone())
+                    .canonical()
+                    .into_owned(),
             };
 
+            let mut line = format!(
+                "{} {} {}",
+                format_edge_endpoint(&edge.from, false),
+                edge_type_str,
+                format_edge_endpoint(&edge.to, true)
+            );
             if let Some(label) = &edge.label {
-                printer.write_line(&format!(
-                    "{} {}|{}| {}",
-                    from_str, edge_type_str, label, to_str
-                ));
-            } else {
-                printer.write_line(&format!("{} {} {}", from_str, edge_type_str, to_str));
+                line.push_str(&format!(" : {}", label));
             }
+            printer.write_line(&line);
         }
 
         printer.dedent();
@@ -2148,17 +3125,21 @@ impl MermaidPrinter for ArchitectureDiagram {
     }
 }
 
-fn format_edge_endpoint(endpoint: &EdgeEndpoint) -> String {
-    if let Some(port) = &endpoint.port {
-        let port_str = match port {
-            Port::Left => ":L",
-            Port::Right => ":R",
-            Port::Top => ":T",
-            Port::Bottom => ":B",
-        };
-        format!("{}{}", endpoint.id, port_str)
+/// `id:P` for the source of an edge, `P:id` for its target
+fn format_edge_endpoint(endpoint: &EdgeEndpoint, is_target: bool) -> String {
+    let Some(port) = &endpoint.port else {
+        return endpoint.id.clone();
+    };
+    let port = match port {
+        Port::Left => "L",
+        Port::Right => "R",
+        Port::Top => "T",
+        Port::Bottom => "B",
+    };
+    if is_target {
+        format!("{}:{}", port, endpoint.id)
     } else {
-        endpoint.id.clone()
+        format!("{}:{}", endpoint.id, port)
     }
 }
 
@@ -2272,7 +3253,10 @@ impl MermaidPrinter for RequirementDiagram {
             printer.write_line(&format!("element {} {{", id));
             printer.indent();
 
-            printer.write_line(&format!("type: \"{}\"", elem.element_type));
+            printer.write_line(&format!(
+                "type: {}",
+                escape::escaped_quoted(&elem.element_type)
+            ));
 
             if let Some(doc_ref) = &elem.doc_ref {
                 printer.write_line(&format!("docref: {}", doc_ref));
@@ -2297,6 +3281,11 @@ impl MermaidPrinter for RequirementDiagram {
             printer.write_line(&format!("{} - {} -> {}", rel.source, rel_type, rel.target));
         }
 
+        // Write styles
+        for style in &self.styles {
+            write_style_definition(&mut printer, style);
+        }
+
         printer.dedent();
         printer.finish()
     }
@@ -2427,16 +3416,30 @@ impl MermaidPrinter for MiscDiagram {
     fn to_mermaid_pretty(&self, _options: &PrintOptions) -> String {
         match &self.content {
             MiscContent::Info(info) => {
-                format!("info\n{}", info.command)
+                let mut lines = Vec::new();
+                let mut init = std::collections::BTreeMap::new();
+                for (name, value) in &info.options {
+                    match value {
+                        ConfigValue::Null => lines.push(format!("%%{{{}}}%%", name)),
+                        value => {
+                            init.insert(name.clone(), value.clone());
+                        }
+                    }
+                }
+                if !init.is_empty() {
+                    lines.insert(0, format!("%%{{init: {}}}%%", ConfigValue::Map(init)));
+                }
+                if self.diagram_type != crate::parsers::misc::DIRECTIVES_TYPE {
+                    lines.push(format!("info {}", info.command).trim_end().to_string());
+                }
+                lines.join("\n")
             }
             MiscContent::GitGraph(git) => {
                 let mut output = String::from("gitGraph:\n");
                 for commit in &git.commits {
-                    output.push_str(&format!(
-                        "    {} {}\n",
-                        commit.action,
-                        commit.params.join(" ")
-                    ));
+                    let line = format!("    {} {}", commit.action, commit.params.join(" "));
+                    output.push_str(line.trim_end());
+                    output.push('\n');
                 }
                 output
             }
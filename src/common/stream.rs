@@ -0,0 +1,500 @@
+//! Incremental parsing of diagrams read line by line
+//!
+//! [`parse_reader`], exposed as [`crate::parse_diagram_from_reader`], parses
+//! line-oriented diagrams ([`STREAMED_TYPES`]) without holding the whole
+//! input in memory. Statement lines are collected into chunks of at most
+//! [`CHUNK_LINES`] lines; each chunk is parsed by the diagram's own parser
+//! behind a copy of the header line, and the partial diagrams are merged as
+//! they arrive. Gantt and timeline chunks end at `section` lines, and a chunk
+//! that continues a section starts with a copy of its `section` line, so
+//! tasks and events stay in the section they were written in. A multi-line
+//! `accDescr { ... }` block is never split. The frontmatter and directives
+//! before the header line are copied into every chunk along with it, so
+//! settings taken from the frontmatter config, such as the `displayMode` of
+//! a gantt chart, apply as they do in [`crate::parse_diagram`].
+//!
+//! Other diagram types are read into memory and parsed with
+//! [`crate::parse_diagram`].
+//!
+//! Line numbers in errors refer to the whole input. Byte offsets recorded in
+//! [`crate::error::PartialParse`] are relative to the chunk that failed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::stream::parse_reader;
+//! use mermaid_parser::DiagramType;
+//! use std::io::Cursor;
+//!
+//! let rows: String = (0..1000).map(|i| format!("N{},N{},1\n", i, i + 1)).collect();
+//! let input = format!("sankey-beta\n{}", rows);
+//!
+//! if let DiagramType::Sankey(diagram) = parse_reader(Cursor::new(input))? {
+//!     assert_eq!(diagram.links.len(), 1000);
+//!     assert_eq!(diagram.nodes.len(), 1001);
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{DiagramType, GanttDiagram, PieDiagram, SankeyDiagram, TimelineDiagram};
+use crate::common::events::{emit_events, EventEmitter, ParseHandler};
+use crate::common::frontmatter::DELIMITER;
+use crate::common::parsing::directives;
+use crate::common::visitor::AstVisitor;
+use crate::error::{ParseError, Result};
+use crate::parsers;
+use crate::stable::DiagramKind;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+/// Diagram types, as detected from their header, parsed chunk by chunk
+pub const STREAMED_TYPES: &[&str] = &["gantt", "pie", "sankey", "timeline"];
+
+/// Most statement lines parsed at once
+pub const CHUNK_LINES: usize = 256;
+
+/// Parse a diagram from `reader`, keeping at most a chunk of it in memory
+/// for the [`STREAMED_TYPES`]
+///
+/// Read failures, including input that is not UTF-8, are returned as
+/// [`ParseError::IoError`].
+pub fn parse_reader<R: BufRead>(reader: R) -> Result<DiagramType> {
+    let mut lines = Lines::new(reader);
+    let mut prelude = String::new();
+    let Some(header) = read_header(&mut lines, &mut prelude)? else {
+        return crate::parse_diagram(&prelude);
+    };
+
+    match crate::detect_diagram_type(header.line())? {
+        "gantt" => merged(&mut lines, header, parsers::gantt::parse, merge_gantt, true)
+            .map(DiagramType::Gantt),
+        "pie" => {
+            merged(&mut lines, header, parsers::pie::parse, merge_pie, false).map(DiagramType::Pie)
+        }
+        "sankey" => merged(
+            &mut lines,
+            header,
+            parsers::sankey::parse,
+            merge_sankey,
+            false,
+        )
+        .map(DiagramType::Sankey),
+        "timeline" => merged(
+            &mut lines,
+            header,
+            parsers::timeline::parse,
+            merge_timeline,
+            true,
+        )
+        .map(DiagramType::Timeline),
+        _ => crate::parse_diagram(&read_rest(&mut lines, prelude, header)?),
+    }
+}
+
+/// Parse a diagram from `reader`, sending its events to `handler` instead
+/// of building it, as described in [`crate::common::events`]
+///
+/// Chunks of the [`STREAMED_TYPES`] are dropped once their events are sent.
+/// Other diagram types are parsed with [`crate::parse_diagram`] and then
+/// walked.
+pub fn parse_reader_events<R: BufRead>(reader: R, handler: &mut impl ParseHandler) -> Result<()> {
+    let mut lines = Lines::new(reader);
+    let mut prelude = String::new();
+    let Some(header) = read_header(&mut lines, &mut prelude)? else {
+        return crate::parse_diagram(&prelude).map(|diagram| emit_events(&diagram, handler));
+    };
+
+    let mut emitter = EventEmitter::new(handler);
+    match crate::detect_diagram_type(header.line())? {
+        "gantt" => {
+            emitter.start(DiagramKind::Gantt);
+            stream(
+                &mut lines,
+                header,
+                parsers::gantt::parse,
+                true,
+                |part, continues| {
+                    emitter.continues = continues;
+                    emitter.visit_gantt(&part);
+                },
+            )?;
+        }
+        "pie" => {
+            emitter.start(DiagramKind::Pie);
+            stream(&mut lines, header, parsers::pie::parse, false, |part, _| {
+                emitter.visit_pie(&part);
+            })?;
+        }
+        "sankey" => {
+            emitter.start(DiagramKind::Sankey);
+            stream(
+                &mut lines,
+                header,
+                parsers::sankey::parse,
+                false,
+                |part, _| {
+                    emitter.visit_sankey(&part);
+                },
+            )?;
+        }
+        "timeline" => {
+            emitter.start(DiagramKind::Timeline);
+            stream(
+                &mut lines,
+                header,
+                parsers::timeline::parse,
+                true,
+                |part, continues| {
+                    emitter.continues = continues;
+                    emitter.visit_timeline(&part);
+                },
+            )?;
+        }
+        _ => {
+            let diagram = crate::parse_diagram(&read_rest(&mut lines, prelude, header)?)?;
+            emitter.start((&diagram).into());
+            emitter.visit_diagram(&diagram);
+        }
+    }
+    emitter.finish();
+    Ok(())
+}
+
+/// The lines every chunk starts with: the frontmatter and directive lines
+/// before the header line of the diagram, then the header line itself
+struct Header {
+    lines: Vec<(usize, String)>,
+}
+
+impl Header {
+    /// The header line, which names the diagram type
+    fn line(&self) -> &str {
+        self.lines.last().map_or("", |(_, line)| line)
+    }
+}
+
+/// Read up to the header line of the diagram, adding the frontmatter,
+/// directive, blank and comment lines before it to `prelude` in case the
+/// diagram is not streamed. `None` when the input ends first.
+fn read_header<R: BufRead>(lines: &mut Lines<R>, prelude: &mut String) -> Result<Option<Header>> {
+    let mut header = Header { lines: Vec::new() };
+    let mut in_frontmatter = false;
+    while let Some((number, line)) = lines.next_line()? {
+        let trimmed = line.trim();
+        let carried = if in_frontmatter {
+            in_frontmatter = trimmed != DELIMITER;
+            true
+        } else if trimmed == DELIMITER && prelude.trim().is_empty() {
+            in_frontmatter = true;
+            true
+        } else if directives::split(trimmed).is_some() {
+            true
+        } else if !trimmed.is_empty() && !trimmed.starts_with("//") && !trimmed.starts_with('#') {
+            header.lines.push((number, line.to_string()));
+            return Ok(Some(header));
+        } else {
+            false
+        };
+        if carried {
+            header.lines.push((number, line.to_string()));
+        }
+        prelude.push_str(line);
+        prelude.push('\n');
+    }
+    Ok(None)
+}
+
+/// The whole input, for diagrams that are not streamed
+fn read_rest<R: BufRead>(
+    lines: &mut Lines<R>,
+    mut input: String,
+    header: Header,
+) -> Result<String> {
+    input.push_str(header.line());
+    while let Some((_, line)) = lines.next_line()? {
+        input.push('\n');
+        input.push_str(line);
+    }
+    Ok(input)
+}
+
+/// Lines of a reader, without their line endings, numbered from 1
+struct Lines<R> {
+    reader: R,
+    buffer: String,
+    number: usize,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn new(reader: R) -> Self {
+        Lines {
+            reader,
+            buffer: String::new(),
+            number: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Option<(usize, &str)>> {
+        self.buffer.clear();
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.number += 1;
+                Ok(Some((
+                    self.number,
+                    self.buffer.trim_end_matches(['\n', '\r']),
+                )))
+            }
+            Err(error) => Err(ParseError::IoError(error.to_string())),
+        }
+    }
+}
+
+/// Source handed to a diagram parser, with the input line number of each of
+/// its lines
+struct Chunk {
+    text: String,
+    lines: Vec<usize>,
+    statements: usize,
+}
+
+impl Chunk {
+    fn new(header: &Header) -> Self {
+        let mut chunk = Chunk {
+            text: String::new(),
+            lines: Vec::new(),
+            statements: 0,
+        };
+        for (number, line) in &header.lines {
+            chunk.push(*number, line);
+        }
+        chunk
+    }
+
+    fn push(&mut self, number: usize, line: &str) {
+        if !self.lines.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(line);
+        self.lines.push(number);
+    }
+
+    /// Parse the chunk with its directives left out, as
+    /// [`crate::parse_diagram`] leaves them out
+    fn parse<T>(&self, parse: fn(&str) -> Result<T>) -> Result<T> {
+        parse(&directives::strip(&self.text)).map_err(|error| self.remap(error))
+    }
+
+    /// `error` with its chunk line numbers replaced by input line numbers
+    fn remap(&self, error: ParseError) -> ParseError {
+        let line = |line: usize| match line {
+            0 => 0,
+            line => self.lines.get(line - 1).copied().unwrap_or(line),
+        };
+        match error {
+            ParseError::LexError {
+                message,
+                line: at,
+                column,
+                partial,
+            } => ParseError::LexError {
+                message,
+                line: line(at),
+                column,
+                partial,
+            },
+            ParseError::SyntaxError {
+                message,
+                expected,
+                found,
+                line: at,
+                column,
+                partial,
+            } => ParseError::SyntaxError {
+                message,
+                expected,
+                found,
+                line: line(at),
+                column,
+                partial,
+            },
+            ParseError::EnhancedSyntaxError {
+                message,
+                mut location,
+                snippet,
+                suggestions,
+                expected,
+                found,
+            } => {
+                location.line = line(location.line);
+                ParseError::EnhancedSyntaxError {
+                    message,
+                    location,
+                    snippet,
+                    suggestions,
+                    expected,
+                    found,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// Parse the lines after `header` into a diagram, merging the diagram of
+/// each chunk into the first
+fn merged<R: BufRead, T>(
+    lines: &mut Lines<R>,
+    header: Header,
+    parse: fn(&str) -> Result<T>,
+    merge: fn(&mut T, T, bool),
+    sectioned: bool,
+) -> Result<T> {
+    let mut diagram: Option<T> = None;
+    stream(
+        lines,
+        header,
+        parse,
+        sectioned,
+        |part, continues| match &mut diagram {
+            Some(diagram) => merge(diagram, part, continues),
+            None => diagram = Some(part),
+        },
+    )?;
+    Ok(diagram.expect("the last chunk is always parsed into a diagram"))
+}
+
+/// Parse the lines after `header` chunk by chunk, handing each partial
+/// diagram to `add`, at least once. With `sectioned`, chunks end at
+/// `section` lines and `add` is told whether a chunk continues the last
+/// section.
+fn stream<R: BufRead, T>(
+    lines: &mut Lines<R>,
+    header: Header,
+    parse: fn(&str) -> Result<T>,
+    sectioned: bool,
+    mut add: impl FnMut(T, bool),
+) -> Result<()> {
+    let mut chunk = Chunk::new(&header);
+    let mut continues = false;
+    let mut parsed = false;
+    let mut section: Option<(usize, String)> = None;
+    let mut in_block = false;
+    while let Some((number, line)) = lines.next_line()? {
+        let trimmed = line.trim();
+        let starts_section = sectioned && trimmed.split_whitespace().next() == Some("section");
+        if !in_block && chunk.statements > 0 && (starts_section || chunk.statements >= CHUNK_LINES)
+        {
+            add(chunk.parse(parse)?, continues);
+            parsed = true;
+            chunk = Chunk::new(&header);
+            continues = !starts_section;
+            if let Some((section_number, section_line)) = section.as_ref().filter(|_| continues) {
+                chunk.push(*section_number, section_line);
+            }
+        }
+
+        if starts_section {
+            section = Some((number, line.to_string()));
+        }
+        in_block = if in_block {
+            !trimmed.contains('}')
+        } else {
+            trimmed.starts_with("accDescr") && trimmed.contains('{') && !trimmed.contains('}')
+        };
+        chunk.push(number, line);
+        chunk.statements += 1;
+    }
+
+    if chunk.statements > 0 || !parsed {
+        add(chunk.parse(parse)?, continues);
+    }
+    Ok(())
+}
+
+/// `Some` settings of a later chunk replace those of earlier ones, as a
+/// later statement does in a single parse
+fn replace<T>(value: &mut Option<T>, later: Option<T>) {
+    if later.is_some() {
+        *value = later;
+    }
+}
+
+fn merge_gantt(diagram: &mut GanttDiagram, part: GanttDiagram, continues: bool) {
+    replace(&mut diagram.title, part.title);
+    replace(&mut diagram.accessibility.title, part.accessibility.title);
+    replace(
+        &mut diagram.accessibility.description,
+        part.accessibility.description,
+    );
+    replace(&mut diagram.date_format, part.date_format);
+    replace(&mut diagram.axis_format, part.axis_format);
+    replace(&mut diagram.tick_interval, part.tick_interval);
+    replace(&mut diagram.today_marker, part.today_marker);
+    replace(&mut diagram.display_mode, part.display_mode);
+    replace(&mut diagram.weekdays.start_day, part.weekdays.start_day);
+    diagram.includes.extend(part.includes);
+    diagram.excludes.extend(part.excludes);
+    diagram.inclusive_end_dates |= part.inclusive_end_dates;
+    diagram.top_axis |= part.top_axis;
+    for day in part.weekdays.weekend {
+        if !diagram.weekdays.weekend.contains(&day) {
+            diagram.weekdays.weekend.push(day);
+        }
+    }
+
+    let mut sections = part.sections.into_iter();
+    if continues {
+        if let (Some(last), Some(first)) = (diagram.sections.last_mut(), sections.next()) {
+            last.tasks.extend(first.tasks);
+        }
+    }
+    diagram.sections.extend(sections);
+}
+
+fn merge_pie(diagram: &mut PieDiagram, part: PieDiagram, _continues: bool) {
+    replace(&mut diagram.title, part.title);
+    replace(&mut diagram.accessibility.title, part.accessibility.title);
+    replace(
+        &mut diagram.accessibility.description,
+        part.accessibility.description,
+    );
+    diagram.show_data |= part.show_data;
+    diagram.data.extend(part.data);
+}
+
+fn merge_sankey(diagram: &mut SankeyDiagram, part: SankeyDiagram, _continues: bool) {
+    let mut known: HashMap<String, usize> = diagram
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id.clone(), index))
+        .collect();
+    for node in part.nodes {
+        match known.get(&node.id) {
+            Some(&index) => diagram.nodes[index].metadata.extend(node.metadata),
+            None => {
+                known.insert(node.id.clone(), diagram.nodes.len());
+                diagram.nodes.push(node);
+            }
+        }
+    }
+    diagram.links.extend(part.links);
+}
+
+fn merge_timeline(diagram: &mut TimelineDiagram, part: TimelineDiagram, continues: bool) {
+    replace(&mut diagram.title, part.title);
+    replace(&mut diagram.accessibility.title, part.accessibility.title);
+    replace(
+        &mut diagram.accessibility.description,
+        part.accessibility.description,
+    );
+
+    let mut sections = part.sections.into_iter();
+    if continues {
+        if let (Some(last), Some(first)) = (diagram.sections.last_mut(), sections.next()) {
+            last.items.extend(first.items);
+        }
+    }
+    diagram.sections.extend(sections);
+}
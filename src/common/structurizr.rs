@@ -0,0 +1,923 @@
+//! Conversion between C4 diagrams and the Structurizr DSL
+//!
+//! [`C4Diagram::to_structurizr`] writes a workspace with the diagram's elements,
+//! boundaries and relationships in its `model`, one view matching the diagram
+//! type, and the tag definitions as `styles`. [`C4Diagram::from_structurizr`]
+//! reads the same subset back, so Mermaid views can be generated from a
+//! workspace maintained in Structurizr.
+//!
+//! The mapping is:
+//!
+//! | Mermaid C4 | Structurizr |
+//! |---|---|
+//! | `Person`, `System`, `Container`, `Component` | `person`, `softwareSystem`, `container`, `component` |
+//! | `Deployment_Node`, `Node` | `deploymentNode`, `infrastructureNode` in a `deploymentEnvironment` |
+//! | `System_Boundary`, `Container_Boundary` | the software system or container the elements are nested in |
+//! | `Enterprise_Boundary`, `Boundary` | `enterprise`, `group` |
+//! | `_Ext`, `Db` and `Queue` variants | the `External`, `Database` and `Queue` tags |
+//! | `BiRel` | a relationship with the `Bidirectional` tag |
+//!
+//! Relationship directions, sprites and the nesting of elements inside
+//! deployment nodes have no Structurizr equivalent and are dropped. On import,
+//! a software system or container with nested elements becomes a boundary,
+//! unknown statements and blocks are skipped, and `!` directives are ignored.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::ast::C4Diagram;
+//!
+//! let workspace = r#"workspace "Shop" {
+//!     model {
+//!         customer = person "Customer"
+//!         shop = softwareSystem "Shop" {
+//!             web = container "Web" "Storefront" "Rust"
+//!         }
+//!         customer -> web "Browses" "HTTPS"
+//!     }
+//! }"#;
+//!
+//! let diagram = C4Diagram::from_structurizr(workspace)?;
+//! assert_eq!(diagram.boundaries[0].elements, vec!["web"]);
+//! assert!(diagram.to_structurizr().contains("customer -> web \"Browses\" \"HTTPS\""));
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{
+    AccessibilityInfo, C4Boundary, C4BoundaryType, C4Diagram, C4DiagramType, C4Element,
+    C4ElementType, C4Relationship, C4RelationshipDirection, C4TagDefinition, C4TagKind,
+};
+use crate::common::ordered_map::OrderedMap;
+use crate::error::{ParseError, Result};
+use std::collections::HashSet;
+
+/// Tag marking elements drawn with Mermaid's `_Ext` macros
+pub const EXTERNAL_TAG: &str = "External";
+/// Tag marking `SystemDb`, `ContainerDb` and `ComponentDb` elements
+pub const DATABASE_TAG: &str = "Database";
+/// Tag marking `SystemQueue`, `ContainerQueue` and `ComponentQueue` elements
+pub const QUEUE_TAG: &str = "Queue";
+/// Tag marking `BiRel` relationships
+pub const BIDIRECTIONAL_TAG: &str = "Bidirectional";
+
+/// Tags Structurizr adds to every element or relationship of a kind
+const DEFAULT_TAGS: &[&str] = &[
+    "Element",
+    "Person",
+    "Software System",
+    "Container",
+    "Component",
+    "Deployment Node",
+    "Infrastructure Node",
+    "Relationship",
+];
+
+/// Element style properties, Mermaid name first
+const ELEMENT_STYLES: &[(&str, &str)] = &[
+    ("bgColor", "background"),
+    ("fontColor", "color"),
+    ("borderColor", "stroke"),
+];
+
+/// Relationship style properties, Mermaid name first
+const RELATIONSHIP_STYLES: &[(&str, &str)] = &[("lineColor", "color")];
+
+/// The deployment environment deployment nodes are written to
+const DEPLOYMENT_ENVIRONMENT: &str = "Default";
+
+impl C4Diagram {
+    /// A Structurizr DSL workspace describing this diagram
+    pub fn to_structurizr(&self) -> String {
+        let mut writer = Writer::default();
+        match &self.title {
+            Some(title) => writer.open(&format!("workspace {}", quote(title))),
+            None => writer.open("workspace"),
+        }
+
+        writer.open("model");
+        let mut bounded: HashSet<&str> = HashSet::new();
+        collect_boundary_ids(&self.boundaries, &mut bounded);
+        let top_level = self
+            .elements
+            .values()
+            .filter(|element| !bounded.contains(element.id.as_str()) && !is_deployment(element));
+        for element in top_level {
+            writer.line(&element_statement(element));
+        }
+        for boundary in &self.boundaries {
+            self.write_boundary(&mut writer, boundary);
+        }
+
+        let deployment: Vec<_> = self
+            .elements
+            .values()
+            .filter(|e| is_deployment(e))
+            .collect();
+        if !deployment.is_empty() {
+            writer.open(&format!(
+                "deploymentEnvironment {}",
+                quote(DEPLOYMENT_ENVIRONMENT)
+            ));
+            for element in deployment {
+                writer.line(&element_statement(element));
+            }
+            writer.close();
+        }
+
+        for relationship in &self.relationships {
+            writer.line(&relationship_statement(relationship));
+        }
+        writer.close();
+
+        writer.open("views");
+        self.write_view(&mut writer);
+        if !self.tags.is_empty() {
+            writer.open("styles");
+            for tag in &self.tags {
+                let (keyword, properties) = match tag.kind {
+                    C4TagKind::Relationship => ("relationship", RELATIONSHIP_STYLES),
+                    C4TagKind::Element | C4TagKind::Boundary => ("element", ELEMENT_STYLES),
+                };
+                writer.open(&format!("{} {}", keyword, quote(&tag.name)));
+                for (mermaid, structurizr) in properties {
+                    if let Some(value) = tag.property(mermaid) {
+                        writer.line(&format!("{} {}", structurizr, value));
+                    }
+                }
+                writer.close();
+            }
+            writer.close();
+        }
+        writer.close();
+
+        writer.close();
+        writer.finish()
+    }
+
+    /// Read a C4 diagram from a Structurizr DSL workspace
+    ///
+    /// The diagram type is taken from the first view, or from the most
+    /// detailed element kind when the workspace has no views.
+    pub fn from_structurizr(input: &str) -> Result<C4Diagram> {
+        Importer::default().import(input)
+    }
+
+    fn write_boundary(&self, writer: &mut Writer, boundary: &C4Boundary) {
+        let header = match boundary.boundary_type {
+            C4BoundaryType::System | C4BoundaryType::Container => {
+                // The boundary stands for the system or container it is named after
+                let element_type = match boundary.boundary_type {
+                    C4BoundaryType::System => C4ElementType::System,
+                    _ => C4ElementType::Container,
+                };
+                let element =
+                    self.elements
+                        .get(&boundary.id)
+                        .cloned()
+                        .unwrap_or_else(|| C4Element {
+                            id: boundary.id.clone(),
+                            element_type,
+                            name: boundary.label.clone(),
+                            description: None,
+                            technology: None,
+                            sprite: None,
+                            tags: boundary.tags.clone(),
+                            is_external: false,
+                        });
+                element_statement(&element)
+            }
+            C4BoundaryType::Enterprise => format!("enterprise {}", quote(&boundary.label)),
+            C4BoundaryType::Generic => format!("group {}", quote(&boundary.label)),
+        };
+
+        writer.open(&header);
+        for id in &boundary.elements {
+            if let Some(element) = self.elements.get(id).filter(|e| !is_deployment(e)) {
+                writer.line(&element_statement(element));
+            }
+        }
+        for nested in &boundary.boundaries {
+            self.write_boundary(writer, nested);
+        }
+        writer.close();
+    }
+
+    fn write_view(&self, writer: &mut Writer) {
+        let first_boundary = |boundary_type: C4BoundaryType| {
+            let mut pending: Vec<&C4Boundary> = self.boundaries.iter().rev().collect();
+            while let Some(boundary) = pending.pop() {
+                if boundary.boundary_type == boundary_type {
+                    return Some(boundary.id.clone());
+                }
+                pending.extend(boundary.boundaries.iter().rev());
+            }
+            None
+        };
+
+        let header = match self.diagram_type {
+            C4DiagramType::Context => None,
+            C4DiagramType::Container => {
+                first_boundary(C4BoundaryType::System).map(|id| format!("container {}", id))
+            }
+            C4DiagramType::Component => {
+                first_boundary(C4BoundaryType::Container).map(|id| format!("component {}", id))
+            }
+            C4DiagramType::Dynamic => Some("dynamic *".to_string()),
+            C4DiagramType::Deployment => {
+                Some(format!("deployment * {}", quote(DEPLOYMENT_ENVIRONMENT)))
+            }
+        };
+
+        match header {
+            Some(header) if self.diagram_type == C4DiagramType::Dynamic => {
+                // Dynamic views list their steps instead of including everything
+                writer.open(&header);
+                for relationship in &self.relationships {
+                    let mut step = format!("{} -> {}", relationship.from, relationship.to);
+                    if let Some(label) = &relationship.label {
+                        step.push_str(&format!(" {}", quote(label)));
+                    }
+                    writer.line(&step);
+                }
+            }
+            Some(header) => {
+                writer.open(&header);
+                writer.line("include *");
+            }
+            None => {
+                writer.open("systemLandscape");
+                writer.line("include *");
+            }
+        }
+        writer.line("autoLayout");
+        writer.close();
+    }
+}
+
+fn is_deployment(element: &C4Element) -> bool {
+    matches!(
+        element.element_type,
+        C4ElementType::DeploymentNode | C4ElementType::Node
+    )
+}
+
+fn collect_boundary_ids<'a>(boundaries: &'a [C4Boundary], ids: &mut HashSet<&'a str>) {
+    for boundary in boundaries {
+        ids.insert(&boundary.id);
+        ids.extend(boundary.elements.iter().map(String::as_str));
+        collect_boundary_ids(&boundary.boundaries, ids);
+    }
+}
+
+fn element_statement(element: &C4Element) -> String {
+    use C4ElementType as T;
+
+    let keyword = match element.element_type {
+        T::Person => "person",
+        T::System | T::SystemDb | T::SystemQueue => "softwareSystem",
+        T::Container | T::ContainerDb | T::ContainerQueue => "container",
+        T::Component | T::ComponentDb | T::ComponentQueue => "component",
+        T::DeploymentNode => "deploymentNode",
+        T::Node => "infrastructureNode",
+    };
+
+    let mut tags = element.tags.clone();
+    if element.is_external {
+        tags.push(EXTERNAL_TAG.to_string());
+    }
+    match element.element_type {
+        T::SystemDb | T::ContainerDb | T::ComponentDb => tags.push(DATABASE_TAG.to_string()),
+        T::SystemQueue | T::ContainerQueue | T::ComponentQueue => tags.push(QUEUE_TAG.to_string()),
+        _ => {}
+    }
+
+    let mut arguments = vec![
+        element.name.clone(),
+        element.description.clone().unwrap_or_default(),
+    ];
+    let has_technology = !matches!(
+        element.element_type,
+        T::Person | T::System | T::SystemDb | T::SystemQueue
+    );
+    if has_technology {
+        arguments.push(element.technology.clone().unwrap_or_default());
+    }
+    arguments.push(tags.join(","));
+
+    format!(
+        "{} = {} {}",
+        element.id,
+        keyword,
+        quote_arguments(arguments)
+    )
+}
+
+fn relationship_statement(relationship: &C4Relationship) -> String {
+    let mut tags = relationship.tags.clone();
+    if relationship.is_bidirectional {
+        tags.push(BIDIRECTIONAL_TAG.to_string());
+    }
+    let arguments = vec![
+        relationship.label.clone().unwrap_or_default(),
+        relationship.technology.clone().unwrap_or_default(),
+        tags.join(","),
+    ];
+
+    let statement = format!("{} -> {}", relationship.from, relationship.to);
+    match quote_arguments(arguments) {
+        arguments if arguments.is_empty() => statement,
+        arguments => format!("{} {}", statement, arguments),
+    }
+}
+
+/// Quote positional arguments, dropping empty trailing ones
+fn quote_arguments(mut arguments: Vec<String>) -> String {
+    while arguments.last().is_some_and(String::is_empty) {
+        arguments.pop();
+    }
+    arguments
+        .iter()
+        .map(|argument| quote(argument))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[derive(Default)]
+struct Writer {
+    lines: Vec<String>,
+    depth: usize,
+}
+
+impl Writer {
+    fn line(&mut self, text: &str) {
+        self.lines
+            .push(format!("{}{}", "    ".repeat(self.depth), text));
+    }
+
+    fn open(&mut self, header: &str) {
+        self.line(&format!("{} {{", header));
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    fn finish(self) -> String {
+        let mut output = self.lines.join("\n");
+        output.push('\n');
+        output
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Open,
+    Close,
+    Assign,
+}
+
+/// An open `{ ... }` block while importing
+enum Scope {
+    /// `workspace`, `model`, deployment environments and unknown blocks
+    Block,
+    Views,
+    Styles,
+    Style(C4TagDefinition),
+    /// A software system or container, which becomes a boundary if it has
+    /// nested elements
+    Element {
+        id: String,
+        boundary: Option<C4Boundary>,
+    },
+    Group(C4Boundary),
+}
+
+impl Scope {
+    fn boundary_mut(&mut self) -> Option<&mut C4Boundary> {
+        match self {
+            Scope::Element { boundary, .. } => boundary.as_mut(),
+            Scope::Group(boundary) => Some(boundary),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Importer {
+    workspace_name: Option<String>,
+    view_title: Option<String>,
+    diagram_type: Option<C4DiagramType>,
+    elements: OrderedMap<String, C4Element>,
+    boundaries: Vec<C4Boundary>,
+    relationships: Vec<C4Relationship>,
+    tags: Vec<C4TagDefinition>,
+    scopes: Vec<Scope>,
+    group_count: usize,
+}
+
+impl Importer {
+    fn import(mut self, input: &str) -> Result<C4Diagram> {
+        let mut in_comment = false;
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let trimmed = line.trim();
+            if in_comment {
+                in_comment = !trimmed.contains("*/");
+                continue;
+            }
+            if trimmed.starts_with("/*") {
+                in_comment = !trimmed.contains("*/");
+                continue;
+            }
+            if trimmed.is_empty()
+                || trimmed.starts_with('#')
+                || trimmed.starts_with("//")
+                || trimmed.starts_with('!')
+            {
+                continue;
+            }
+
+            let mut tokens = tokenize(trimmed, line_number)?;
+            if tokens.first() == Some(&Token::Close) {
+                self.close(line_number)?;
+                continue;
+            }
+            // `name = person "Name" { }` opens and closes an empty block
+            let closes = tokens.len() > 1
+                && tokens.last() == Some(&Token::Close)
+                && tokens[tokens.len() - 2] == Token::Open;
+            if closes {
+                tokens.pop();
+            }
+            let opens = tokens.last() == Some(&Token::Open);
+            if opens {
+                tokens.pop();
+            }
+
+            let scope = self.statement(&tokens, line_number)?;
+            if opens {
+                self.scopes.push(scope);
+                if closes {
+                    self.close(line_number)?;
+                }
+            }
+        }
+
+        if !self.scopes.is_empty() {
+            return Err(structurizr_error(
+                "Unclosed '{' in Structurizr workspace",
+                "end of input",
+                input.lines().count(),
+            ));
+        }
+
+        let diagram_type = self.diagram_type.clone().unwrap_or_else(|| {
+            let has = |types: &[C4ElementType]| {
+                self.elements
+                    .values()
+                    .any(|element| types.contains(&element.element_type))
+            };
+            if has(&[C4ElementType::DeploymentNode, C4ElementType::Node]) {
+                C4DiagramType::Deployment
+            } else if has(&[C4ElementType::Component]) {
+                C4DiagramType::Component
+            } else if has(&[C4ElementType::Container]) {
+                C4DiagramType::Container
+            } else {
+                C4DiagramType::Context
+            }
+        });
+
+        Ok(C4Diagram {
+            diagram_type,
+            title: self.workspace_name.or(self.view_title),
+            accessibility: AccessibilityInfo::default(),
+            elements: self.elements,
+            boundaries: self.boundaries,
+            relationships: self.relationships,
+            tags: self.tags,
+        })
+    }
+
+    /// Apply one statement; returns the scope it opens, if followed by `{`
+    fn statement(&mut self, tokens: &[Token], line: usize) -> Result<Scope> {
+        let in_views = self.scopes.iter().any(|s| matches!(s, Scope::Views));
+
+        if let Some(Scope::Style(tag)) = self.scopes.last_mut() {
+            if let [Token::Word(key), value] = tokens {
+                let properties = match tag.kind {
+                    C4TagKind::Relationship => RELATIONSHIP_STYLES,
+                    _ => ELEMENT_STYLES,
+                };
+                if let Some((mermaid, _)) = properties.iter().find(|(_, s)| s == key) {
+                    tag.properties
+                        .push((mermaid.to_string(), token_text(value).to_string()));
+                }
+            }
+            return Ok(Scope::Block);
+        }
+
+        if matches!(self.scopes.last(), Some(Scope::Styles)) {
+            let kind = match tokens.first() {
+                Some(Token::Word(word)) if word == "element" => C4TagKind::Element,
+                Some(Token::Word(word)) if word == "relationship" => C4TagKind::Relationship,
+                _ => return Ok(Scope::Block),
+            };
+            return Ok(match tokens.get(1) {
+                Some(name) => Scope::Style(C4TagDefinition {
+                    name: token_text(name).to_string(),
+                    kind,
+                    properties: Vec::new(),
+                }),
+                None => Scope::Block,
+            });
+        }
+
+        if in_views {
+            return Ok(self.view_statement(tokens));
+        }
+
+        // Relationships: `a -> b ...`, or `-> b ...` inside an element
+        let arrow = tokens
+            .iter()
+            .position(|token| matches!(token, Token::Word(word) if word == "->"));
+        if let Some(arrow) = arrow {
+            let from = match &tokens[..arrow] {
+                [Token::Word(from)] if from != "this" => from.clone(),
+                [] | [Token::Word(_)] => self.enclosing_element().ok_or_else(|| {
+                    structurizr_error("Relationship without a source", "->", line)
+                })?,
+                _ => return Err(structurizr_error("Invalid relationship", "->", line)),
+            };
+            let Some(Token::Word(to)) = tokens.get(arrow + 1) else {
+                return Err(structurizr_error(
+                    "Relationship requires a destination",
+                    "->",
+                    line,
+                ));
+            };
+            self.add_relationship(from, to.clone(), &tokens[arrow + 2..]);
+            return Ok(Scope::Block);
+        }
+
+        let (id, rest) = match tokens {
+            [Token::Word(id), Token::Assign, rest @ ..] => (Some(id.clone()), rest),
+            _ => (None, tokens),
+        };
+        let Some(Token::Word(keyword)) = rest.first() else {
+            return Ok(Scope::Block);
+        };
+        let arguments: Vec<&str> = rest[1..].iter().map(token_text).collect();
+
+        let element_type = match keyword.as_str() {
+            "person" => C4ElementType::Person,
+            "softwareSystem" => C4ElementType::System,
+            "container" => C4ElementType::Container,
+            "component" => C4ElementType::Component,
+            "deploymentNode" => C4ElementType::DeploymentNode,
+            "infrastructureNode" => C4ElementType::Node,
+            "workspace" => {
+                self.workspace_name = arguments.first().map(|name| name.to_string());
+                return Ok(Scope::Block);
+            }
+            "views" => return Ok(Scope::Views),
+            "enterprise" | "group" => {
+                self.group_count += 1;
+                let (prefix, boundary_type) = match keyword.as_str() {
+                    "enterprise" => ("enterprise", C4BoundaryType::Enterprise),
+                    _ => ("group", C4BoundaryType::Generic),
+                };
+                let id = id.unwrap_or_else(|| format!("{}_{}", prefix, self.group_count));
+                return Ok(Scope::Group(C4Boundary {
+                    label: arguments
+                        .first()
+                        .map_or_else(|| id.clone(), |l| l.to_string()),
+                    id,
+                    boundary_type,
+                    tags: Vec::new(),
+                    elements: Vec::new(),
+                    boundaries: Vec::new(),
+                }));
+            }
+            "description" | "technology" | "tags" => {
+                self.update_enclosing_element(keyword, &arguments);
+                return Ok(Scope::Block);
+            }
+            _ => return Ok(Scope::Block),
+        };
+
+        let element = self.add_element(id, element_type, &arguments);
+        let boundary_type = match element.element_type {
+            C4ElementType::System => Some(C4BoundaryType::System),
+            C4ElementType::Container => Some(C4BoundaryType::Container),
+            _ => None,
+        };
+        Ok(Scope::Element {
+            boundary: boundary_type.map(|boundary_type| C4Boundary {
+                id: element.id.clone(),
+                boundary_type,
+                label: element.name.clone(),
+                tags: element.tags.clone(),
+                elements: Vec::new(),
+                boundaries: Vec::new(),
+            }),
+            id: element.id,
+        })
+    }
+
+    fn view_statement(&mut self, tokens: &[Token]) -> Scope {
+        let Some(Token::Word(keyword)) = tokens.first() else {
+            return Scope::Block;
+        };
+        let view_type = match keyword.as_str() {
+            "systemLandscape" | "systemContext" => Some(C4DiagramType::Context),
+            "container" => Some(C4DiagramType::Container),
+            "component" => Some(C4DiagramType::Component),
+            "dynamic" => Some(C4DiagramType::Dynamic),
+            "deployment" => Some(C4DiagramType::Deployment),
+            "styles" => return Scope::Styles,
+            "title" => {
+                if let (None, Some(title)) = (&self.view_title, tokens.get(1)) {
+                    self.view_title = Some(token_text(title).to_string());
+                }
+                None
+            }
+            _ => None,
+        };
+        if self.diagram_type.is_none() {
+            self.diagram_type = view_type;
+        }
+        Scope::Block
+    }
+
+    fn add_element(
+        &mut self,
+        id: Option<String>,
+        element_type: C4ElementType,
+        arguments: &[&str],
+    ) -> C4Element {
+        use C4ElementType as T;
+
+        let name = arguments.first().copied().unwrap_or_default().to_string();
+        let id = id.unwrap_or_else(|| self.unique_id(&name));
+        let has_technology = !matches!(element_type, T::Person | T::System);
+        let argument = |index: usize| {
+            arguments
+                .get(index)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+        let (technology, tags) = if has_technology {
+            (argument(2), argument(3))
+        } else {
+            (None, argument(2))
+        };
+
+        let mut element = C4Element {
+            name: if name.is_empty() { id.clone() } else { name },
+            id,
+            element_type,
+            description: argument(1),
+            technology,
+            sprite: None,
+            tags: Vec::new(),
+            is_external: false,
+        };
+        apply_tags(&mut element, tags.as_deref().unwrap_or_default());
+
+        if let Some(boundary) = self.scopes.iter_mut().rev().find_map(Scope::boundary_mut) {
+            boundary.elements.push(element.id.clone());
+        }
+        self.elements.insert(element.id.clone(), element.clone());
+        element
+    }
+
+    fn add_relationship(&mut self, from: String, to: String, arguments: &[Token]) {
+        let argument = |index: usize| {
+            arguments
+                .get(index)
+                .map(token_text)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let mut tags = split_tags(&argument(2).unwrap_or_default());
+        let is_bidirectional = tags.iter().any(|tag| tag == BIDIRECTIONAL_TAG);
+        tags.retain(|tag| tag != BIDIRECTIONAL_TAG);
+
+        self.relationships.push(C4Relationship {
+            from,
+            to,
+            label: argument(0),
+            technology: argument(1),
+            direction: C4RelationshipDirection::Default,
+            is_bidirectional,
+            tags,
+        });
+    }
+
+    fn enclosing_element(&self) -> Option<String> {
+        self.scopes.iter().rev().find_map(|scope| match scope {
+            Scope::Element { id, .. } => Some(id.clone()),
+            _ => None,
+        })
+    }
+
+    /// Apply `description`, `technology` or `tags` inside an element block
+    fn update_enclosing_element(&mut self, keyword: &str, arguments: &[&str]) {
+        let Some(element) = self
+            .enclosing_element()
+            .and_then(|id| self.elements.get_mut(&id))
+        else {
+            return;
+        };
+        match keyword {
+            "description" => element.description = arguments.first().map(|d| d.to_string()),
+            "technology" => element.technology = arguments.first().map(|t| t.to_string()),
+            _ => {
+                for tags in arguments {
+                    apply_tags(element, tags);
+                }
+            }
+        }
+    }
+
+    fn unique_id(&self, name: &str) -> String {
+        let base: String = name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let base = if base.is_empty() {
+            "element".to_string()
+        } else {
+            base
+        };
+        let mut id = base.clone();
+        let mut suffix = 1;
+        while self.elements.contains_key(&id) {
+            suffix += 1;
+            id = format!("{}_{}", base, suffix);
+        }
+        id
+    }
+
+    fn close(&mut self, line: usize) -> Result<()> {
+        let Some(scope) = self.scopes.pop() else {
+            return Err(structurizr_error(
+                "Unmatched '}' in Structurizr workspace",
+                "}",
+                line,
+            ));
+        };
+
+        let boundary = match scope {
+            Scope::Style(tag) => {
+                self.tags.push(tag);
+                None
+            }
+            Scope::Group(boundary) => Some(boundary),
+            Scope::Element {
+                id,
+                boundary: Some(boundary),
+            } if !boundary.elements.is_empty() || !boundary.boundaries.is_empty() => {
+                // A system or container with nested elements is drawn as a boundary
+                self.elements.remove(&id);
+                if let Some(parent) = self.scopes.iter_mut().rev().find_map(Scope::boundary_mut) {
+                    parent.elements.retain(|element| *element != id);
+                }
+                Some(boundary)
+            }
+            _ => None,
+        };
+
+        if let Some(boundary) = boundary {
+            match self.scopes.iter_mut().rev().find_map(Scope::boundary_mut) {
+                Some(parent) => parent.boundaries.push(boundary),
+                None => self.boundaries.push(boundary),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Merge comma separated Structurizr tags into an element, turning the
+/// tags Mermaid expresses through macro names back into element kinds
+fn apply_tags(element: &mut C4Element, tags: &str) {
+    use C4ElementType as T;
+
+    for tag in split_tags(tags) {
+        match tag.as_str() {
+            EXTERNAL_TAG => element.is_external = true,
+            DATABASE_TAG | QUEUE_TAG => {
+                let storage = match (&element.element_type, tag == DATABASE_TAG) {
+                    (T::System, true) => Some(T::SystemDb),
+                    (T::System, false) => Some(T::SystemQueue),
+                    (T::Container, true) => Some(T::ContainerDb),
+                    (T::Container, false) => Some(T::ContainerQueue),
+                    (T::Component, true) => Some(T::ComponentDb),
+                    (T::Component, false) => Some(T::ComponentQueue),
+                    _ => None,
+                };
+                match storage {
+                    Some(element_type) => element.element_type = element_type,
+                    None => element.tags.push(tag),
+                }
+            }
+            _ => element.tags.push(tag),
+        }
+    }
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty() && !DEFAULT_TAGS.contains(tag))
+        .map(str::to_string)
+        .collect()
+}
+
+fn token_text(token: &Token) -> &str {
+    match token {
+        Token::Word(text) | Token::Text(text) => text,
+        Token::Open => "{",
+        Token::Close => "}",
+        Token::Assign => "=",
+    }
+}
+
+fn tokenize(line: &str, line_number: usize) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '{' | '}' | '=' => {
+                chars.next();
+                tokens.push(match c {
+                    '{' => Token::Open,
+                    '}' => Token::Close,
+                    _ => Token::Assign,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => text.extend(chars.next()),
+                        Some(c) => text.push(c),
+                        None => {
+                            return Err(structurizr_error(
+                                "Unterminated string in Structurizr workspace",
+                                line,
+                                line_number,
+                            ))
+                        }
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '{' | '}' | '=' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn structurizr_error(message: &str, found: &str, line: usize) -> ParseError {
+    ParseError::SyntaxError {
+        message: message.to_string(),
+        expected: vec![],
+        found: found.to_string(),
+        line,
+        column: 1,
+        partial: None,
+    }
+}
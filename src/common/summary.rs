@@ -0,0 +1,176 @@
+//! Size summary of a parsed diagram, for logging and telemetry
+//!
+//! [`DiagramType::summary`] counts the nodes and edges of a diagram by
+//! reading the lengths of its collections, without the visitor machinery of
+//! [`crate::common::visitor`] or the analysis of [`crate::common::metrics`],
+//! so it is cheap enough to record for every diagram a service parses.
+//!
+//! What counts as a node depends on the diagram type: the nodes of a
+//! flowchart, the participants of a sequence diagram, the slices of a pie
+//! chart, the tasks of a Gantt chart. Diagram types without connections, such
+//! as pie charts, have no edges.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::parse_diagram;
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! let diagram = parse_diagram("flowchart TD\n    A[Start] --> B[End]\n    B --> C")?;
+//! let summary = diagram.summary();
+//! assert_eq!(summary.kind, DiagramKind::Flowchart);
+//! assert_eq!((summary.nodes, summary.edges), (3, 2));
+//! assert!(!summary.has_title);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::*;
+use crate::common::metrics::count_sequence_messages;
+use crate::stable::DiagramKind;
+use std::mem::size_of;
+
+/// Counts describing the size of a diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiagramSummary {
+    pub kind: DiagramKind,
+    /// Nodes, participants, tasks or other items, depending on the diagram type
+    pub nodes: usize,
+    /// Connections between the nodes
+    pub edges: usize,
+    pub has_title: bool,
+    /// Whether the diagram has an accessibility title or description
+    pub has_accessibility: bool,
+    /// Approximate size of the AST in memory: the diagram itself and its
+    /// nodes and edges, without the text they own
+    pub bytes: usize,
+}
+
+/// Running counts of nodes and edges and the memory they take
+#[derive(Default)]
+struct Tally {
+    nodes: usize,
+    edges: usize,
+    bytes: usize,
+}
+
+impl Tally {
+    fn nodes<T>(mut self, count: usize) -> Self {
+        self.nodes += count;
+        self.bytes += count * size_of::<T>();
+        self
+    }
+
+    fn edges<T>(mut self, count: usize) -> Self {
+        self.edges += count;
+        self.bytes += count * size_of::<T>();
+        self
+    }
+}
+
+impl DiagramType {
+    /// Kind, node and edge counts, and approximate size of the diagram
+    pub fn summary(&self) -> DiagramSummary {
+        let tally = tally(self);
+        DiagramSummary {
+            kind: self.into(),
+            nodes: tally.nodes,
+            edges: tally.edges,
+            has_title: self.title().is_some(),
+            has_accessibility: self.accessibility().is_some_and(|accessibility| {
+                accessibility.title.is_some() || accessibility.description.is_some()
+            }),
+            bytes: size_of::<DiagramType>() + tally.bytes,
+        }
+    }
+}
+
+fn tally(diagram: &DiagramType) -> Tally {
+    let tally = Tally::default();
+    match diagram {
+        DiagramType::Sankey(d) => tally
+            .nodes::<SankeyNode>(d.nodes.len())
+            .edges::<SankeyLink>(d.links.len()),
+        DiagramType::Timeline(d) => {
+            tally.nodes::<TimelineItem>(d.sections.iter().map(|section| section.items.len()).sum())
+        }
+        DiagramType::Journey(d) => {
+            tally.nodes::<JourneyTask>(d.sections.iter().map(|section| section.tasks.len()).sum())
+        }
+        DiagramType::Sequence(d) => tally
+            .nodes::<Participant>(d.participants.len())
+            .edges::<Message>(count_sequence_messages(&d.statements)),
+        DiagramType::Class(d) => tally
+            .nodes::<Class>(d.classes.len())
+            .edges::<ClassRelationship>(d.relationships.len()),
+        DiagramType::State(d) => tally
+            .nodes::<State>(d.all_states().len())
+            .edges::<StateTransition>(d.all_transitions().len()),
+        DiagramType::Flowchart(d) => tally
+            .nodes::<FlowNode>(d.nodes.len())
+            .edges::<FlowEdge>(d.edges.len()),
+        DiagramType::Gantt(d) => {
+            tally.nodes::<GanttTask>(d.sections.iter().map(|section| section.tasks.len()).sum())
+        }
+        DiagramType::Pie(d) => tally.nodes::<PieSlice>(d.data.len()),
+        DiagramType::Git(d) => tally
+            .nodes::<GitCommit>(d.commits.len())
+            .edges::<GitOperation>(
+                d.operations
+                    .iter()
+                    .filter(|operation| {
+                        matches!(
+                            operation,
+                            GitOperation::Merge { .. } | GitOperation::CherryPick { .. }
+                        )
+                    })
+                    .count(),
+            ),
+        DiagramType::Er(d) => tally
+            .nodes::<Entity>(d.entities.len())
+            .edges::<ErRelationship>(d.relationships.len()),
+        DiagramType::C4(d) => tally
+            .nodes::<C4Element>(d.elements.len())
+            .edges::<C4Relationship>(d.relationships.len()),
+        DiagramType::Mindmap(d) => {
+            let nodes = count_tree(&d.root, |node| node.children.as_slice());
+            tally
+                .nodes::<MindmapNode>(nodes)
+                .edges::<()>(nodes - 1)
+                .edges::<MindmapCrossEdge>(d.cross_edges.len())
+        }
+        DiagramType::Quadrant(d) => tally.nodes::<DataPoint>(d.points.len()),
+        DiagramType::XyChart(d) => tally.nodes::<DataSeries>(d.data_series.len()),
+        DiagramType::Kanban(d) => {
+            tally.nodes::<KanbanItem>(d.sections.iter().map(|section| section.items.len()).sum())
+        }
+        DiagramType::Block(d) => tally
+            .nodes::<Block>(d.blocks.len())
+            .edges::<BlockConnection>(d.connections.len()),
+        DiagramType::Architecture(d) => tally
+            .nodes::<Service>(d.services.len())
+            .nodes::<Junction>(d.junctions.len())
+            .edges::<ArchEdge>(d.edges.len()),
+        DiagramType::Packet(d) => tally.nodes::<PacketField>(d.fields.len()),
+        DiagramType::Requirement(d) => tally
+            .nodes::<Requirement>(d.requirements.len())
+            .nodes::<Element>(d.elements.len())
+            .edges::<RequirementRelationship>(d.relationships.len()),
+        DiagramType::Treemap(d) => {
+            let nodes = count_tree(&d.root, |node| node.children.as_slice());
+            tally.nodes::<TreemapNode>(nodes).edges::<()>(nodes - 1)
+        }
+        DiagramType::Radar(d) => tally.nodes::<Dataset>(d.datasets.len()),
+        DiagramType::Misc(_) => tally,
+    }
+}
+
+/// Number of nodes in the tree under `root`, including `root`
+fn count_tree<N>(root: &N, children: fn(&N) -> &[N]) -> usize {
+    let mut count = 0;
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        count += 1;
+        stack.extend(children(node));
+    }
+    count
+}
@@ -0,0 +1,45 @@
+//! Enumerations of the syntax each parser recognizes
+//!
+//! Parsers expose a `syntax_summary()` built from the same tables they parse
+//! with, so documentation and editor grammars (TextMate, Tree-sitter, ...)
+//! can be generated from the parser itself instead of being maintained by
+//! hand.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::parsers::flowchart;
+//!
+//! let summary = flowchart::syntax_summary();
+//! for shape in &summary.shapes {
+//!     println!("{}: A{}text{}", shape.kind, shape.open, shape.close);
+//! }
+//! ```
+
+/// An arrow spelling and the AST kind it produces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrowSyntax {
+    pub token: &'static str,
+    pub kind: String,
+}
+
+/// A pair of shape delimiters and the AST kind they produce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeSyntax {
+    pub open: &'static str,
+    pub close: &'static str,
+    pub kind: String,
+}
+
+/// The syntactic categories recognized by a diagram parser
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SyntaxSummary {
+    /// Header keywords that start the diagram
+    pub headers: Vec<&'static str>,
+    /// Keywords recognized inside the diagram body
+    pub keywords: Vec<&'static str>,
+    /// Layout directions accepted after the header
+    pub directions: Vec<&'static str>,
+    pub arrows: Vec<ArrowSyntax>,
+    pub shapes: Vec<ShapeSyntax>,
+}
@@ -0,0 +1,410 @@
+//! Detection of right-to-left text in diagram labels
+//!
+//! Mermaid renders labels left to right unless told otherwise, which garbles
+//! Hebrew, Arabic and other right-to-left scripts in some constructs.
+//! [`labels`] collects the text a diagram displays, [`label_direction`]
+//! classifies a label by its first strongly directional character (the rule
+//! HTML applies for `dir="auto"`), and [`DiagramType::text_direction`]
+//! summarizes a whole diagram so renderers can set a `dir` attribute.
+//!
+//! The validators in [`crate::common::validation`] use the same detection to
+//! warn about right-to-left labels in constructs that render poorly.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::text_direction::TextDirection;
+//! use mermaid_parser::parse_diagram;
+//!
+//! let diagram = parse_diagram("flowchart RL\n    A[התחלה] --> B[סיום]")?;
+//! assert_eq!(diagram.text_direction(), TextDirection::RightToLeft);
+//! assert_eq!(diagram.text_direction().dir_attribute(), "rtl");
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::*;
+
+/// Reading direction of a label or a whole diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+    /// Labels in both directions, so each needs its own direction
+    Mixed,
+}
+
+impl TextDirection {
+    /// Value for an HTML `dir` attribute: `ltr`, `rtl`, or `auto` for mixed text
+    pub fn dir_attribute(&self) -> &'static str {
+        match self {
+            TextDirection::LeftToRight => "ltr",
+            TextDirection::RightToLeft => "rtl",
+            TextDirection::Mixed => "auto",
+        }
+    }
+}
+
+/// A piece of text a diagram displays
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLabel {
+    /// Id of the element showing the text, or `title`
+    pub element: String,
+    pub text: String,
+}
+
+/// Whether `c` belongs to a right-to-left script, such as Hebrew, Arabic,
+/// Syriac or Thaana, or is a right-to-left mark; Arabic-Indic digits are
+/// neutral like other digits
+pub fn is_rtl_char(c: char) -> bool {
+    let rtl_block = matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{200F}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFE}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    );
+    rtl_block && !c.is_numeric()
+}
+
+/// Whether `c` is a letter that reads left to right
+fn is_ltr_char(c: char) -> bool {
+    c.is_alphabetic() && !is_rtl_char(c)
+}
+
+/// Direction of the first strongly directional character in `text`; `None`
+/// for text made only of digits, punctuation and whitespace
+pub fn label_direction(text: &str) -> Option<TextDirection> {
+    text.chars().find_map(|c| {
+        if is_rtl_char(c) {
+            Some(TextDirection::RightToLeft)
+        } else if is_ltr_char(c) {
+            Some(TextDirection::LeftToRight)
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether `text` contains letters of both directions
+pub fn has_mixed_direction(text: &str) -> bool {
+    text.chars().any(is_rtl_char) && text.chars().any(is_ltr_char)
+}
+
+impl DiagramType {
+    /// Direction hint for the whole diagram: right to left when every
+    /// directional label is, mixed when labels disagree, and left to right
+    /// otherwise, including diagrams without directional text
+    pub fn text_direction(&self) -> TextDirection {
+        let directions: Vec<_> = labels(self)
+            .iter()
+            .filter_map(|label| label_direction(&label.text))
+            .collect();
+        let rtl = directions.contains(&TextDirection::RightToLeft);
+        let ltr = directions.contains(&TextDirection::LeftToRight);
+        match (rtl, ltr) {
+            (true, true) => TextDirection::Mixed,
+            (true, false) => TextDirection::RightToLeft,
+            _ => TextDirection::LeftToRight,
+        }
+    }
+}
+
+/// The text `diagram` displays, with the element showing it
+///
+/// Elements are visited in the order they are declared. Diagram kinds without a label walker only report their
+/// title.
+pub fn labels(diagram: &DiagramType) -> Vec<TextLabel> {
+    let mut labels = Labels::default();
+    if let Some(title) = diagram.title() {
+        labels.push("title", title);
+    }
+
+    match diagram {
+        DiagramType::Flowchart(d) => {
+            for node in d.nodes.values() {
+                labels.push(&node.id, node.text.as_deref().unwrap_or(&node.id));
+            }
+            for edge in &d.edges {
+                labels.edge(&edge.from, &edge.to, edge.label.as_deref());
+            }
+            subgraph_labels(&d.subgraphs, &mut labels);
+        }
+        DiagramType::Sequence(d) => {
+            for participant in &d.participants {
+                labels.push(&participant.actor, participant.display_name());
+            }
+            sequence_labels(&d.statements, &mut labels);
+        }
+        DiagramType::Class(d) => {
+            for class in d.classes.values() {
+                labels.push(&class.name, &class.name);
+            }
+            for relationship in &d.relationships {
+                labels.edge(
+                    &relationship.from,
+                    &relationship.to,
+                    relationship.label.as_deref(),
+                );
+            }
+            for note in &d.notes {
+                labels.push(&note.actor, &note.text);
+            }
+        }
+        DiagramType::State(d) => {
+            for state in d.all_states() {
+                labels.push(
+                    &state.id,
+                    state.display_name.as_deref().unwrap_or(&state.id),
+                );
+            }
+            for transition in d.all_transitions() {
+                labels.edge(
+                    &transition.from,
+                    &transition.to,
+                    transition.event.as_deref(),
+                );
+            }
+            for note in d.all_notes() {
+                labels.push(&note.target, &note.text);
+            }
+        }
+        DiagramType::Journey(d) => {
+            for section in &d.sections {
+                labels.push(&section.name, &section.name);
+                for task in &section.tasks {
+                    labels.push(&task.name, &task.name);
+                }
+            }
+        }
+        DiagramType::Timeline(d) => {
+            for section in &d.sections {
+                labels.push(&section.name, &section.name);
+                for item in &section.items {
+                    let (TimelineItem::Period(text) | TimelineItem::Event(text)) = item;
+                    labels.push(&section.name, text);
+                }
+            }
+        }
+        DiagramType::Gantt(d) => {
+            for section in &d.sections {
+                labels.push(&section.name, &section.name);
+                for task in &section.tasks {
+                    labels.push(task.id.as_deref().unwrap_or(&task.name), &task.name);
+                }
+            }
+        }
+        DiagramType::Pie(d) => {
+            for slice in &d.data {
+                labels.push(&slice.label, &slice.label);
+            }
+        }
+        DiagramType::Mindmap(d) => mindmap_labels(&d.root, &mut labels),
+        DiagramType::Quadrant(d) => {
+            for axis in [&d.x_axis, &d.y_axis].into_iter().flatten() {
+                for label in [&axis.label_start, &axis.label_end].into_iter().flatten() {
+                    labels.push("axis", label);
+                }
+            }
+            let quadrants = &d.quadrants;
+            for (id, label) in [
+                ("quadrant-1", &quadrants.quadrant_1),
+                ("quadrant-2", &quadrants.quadrant_2),
+                ("quadrant-3", &quadrants.quadrant_3),
+                ("quadrant-4", &quadrants.quadrant_4),
+            ] {
+                if let Some(label) = label {
+                    labels.push(id, label);
+                }
+            }
+            for point in &d.points {
+                labels.push(&point.name, &point.name);
+            }
+        }
+        DiagramType::XyChart(d) => {
+            for title in [&d.x_axis.title, &d.y_axis.title].into_iter().flatten() {
+                labels.push("axis", title);
+            }
+            for label in &d.x_axis.labels {
+                labels.push("x-axis", label);
+            }
+            for series in &d.data_series {
+                if let Some(name) = &series.name {
+                    labels.push(name, name);
+                }
+            }
+        }
+        DiagramType::Kanban(d) => {
+            for section in &d.sections {
+                labels.push(&section.id, &section.title);
+                for item in &section.items {
+                    labels.push(item.id.as_deref().unwrap_or(&section.id), &item.text);
+                }
+            }
+        }
+        DiagramType::Er(d) => {
+            for entity in d.entities.values() {
+                labels.push(&entity.name, &entity.name);
+            }
+            for relationship in &d.relationships {
+                labels.edge(
+                    &relationship.left_entity,
+                    &relationship.right_entity,
+                    relationship.label.as_deref(),
+                );
+            }
+        }
+        DiagramType::Sankey(d) => {
+            for node in &d.nodes {
+                labels.push(&node.id, &node.name);
+            }
+        }
+        DiagramType::Requirement(d) => {
+            for requirement in d.requirements.values() {
+                labels.push(&requirement.name, &requirement.name);
+                labels.push(&requirement.name, &requirement.text);
+            }
+            for element in d.elements.values() {
+                labels.push(&element.name, &element.name);
+            }
+        }
+        DiagramType::Treemap(d) => treemap_labels(&d.root, &mut labels),
+        DiagramType::Radar(d) => {
+            for axis in &d.axes {
+                labels.push(axis, axis);
+            }
+            for dataset in &d.datasets {
+                labels.push(&dataset.name, &dataset.name);
+            }
+        }
+        _ => {}
+    }
+
+    labels.0
+}
+
+#[derive(Default)]
+struct Labels(Vec<TextLabel>);
+
+impl Labels {
+    /// Record `text` unless it is blank
+    fn push(&mut self, element: &str, text: &str) {
+        if !text.trim().is_empty() {
+            self.0.push(TextLabel {
+                element: element.to_string(),
+                text: text.to_string(),
+            });
+        }
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        if let Some(label) = label {
+            self.push(&format!("{}->{}", from, to), label);
+        }
+    }
+}
+
+fn subgraph_labels(subgraphs: &[Subgraph], labels: &mut Labels) {
+    for subgraph in subgraphs {
+        if let Some(title) = &subgraph.title {
+            labels.push(&subgraph.id, title);
+        }
+        for edge in &subgraph.edges {
+            labels.edge(&edge.from, &edge.to, edge.label.as_deref());
+        }
+        subgraph_labels(&subgraph.subgraphs, labels);
+    }
+}
+
+/// Messages, notes and block conditions anywhere in the statements
+fn sequence_labels(statements: &[SequenceStatement], labels: &mut Labels) {
+    for statement in statements {
+        match statement {
+            SequenceStatement::Message(message) => {
+                labels.edge(&message.from, &message.to, Some(message.text.as_str()));
+            }
+            SequenceStatement::Note(note) => labels.push(&note.actor, &note.text),
+            SequenceStatement::Loop(block) => {
+                labels.push("loop", &block.condition);
+                sequence_labels(&block.statements, labels);
+            }
+            SequenceStatement::Opt(block) => {
+                labels.push("opt", &block.condition);
+                sequence_labels(&block.statements, labels);
+            }
+            SequenceStatement::Break(block) => {
+                labels.push("break", &block.condition);
+                sequence_labels(&block.statements, labels);
+            }
+            SequenceStatement::Alt(block) => {
+                labels.push("alt", &block.condition);
+                sequence_labels(&block.statements, labels);
+                if let Some(branch) = &block.else_branch {
+                    if let Some(condition) = &branch.condition {
+                        labels.push("else", condition);
+                    }
+                    sequence_labels(&branch.statements, labels);
+                }
+            }
+            SequenceStatement::Par(block) => {
+                for branch in &block.branches {
+                    if let Some(condition) = &branch.condition {
+                        labels.push("par", condition);
+                    }
+                    sequence_labels(&branch.statements, labels);
+                }
+            }
+            SequenceStatement::Critical(block) => {
+                labels.push("critical", &block.condition);
+                sequence_labels(&block.statements, labels);
+                for option in &block.options {
+                    labels.push("option", &option.condition);
+                    sequence_labels(&option.statements, labels);
+                }
+            }
+            SequenceStatement::Rect { statements, .. } => sequence_labels(statements, labels),
+            SequenceStatement::Create(participant) => {
+                labels.push(&participant.actor, participant.display_name());
+            }
+            SequenceStatement::Activate(_)
+            | SequenceStatement::Deactivate(_)
+            | SequenceStatement::Destroy(_) => {}
+        }
+    }
+}
+
+fn mindmap_labels(node: &MindmapNode, labels: &mut Labels) {
+    labels.push(&node.id, &node.text);
+    for child in &node.children {
+        mindmap_labels(child, labels);
+    }
+}
+
+fn treemap_labels(node: &TreemapNode, labels: &mut Labels) {
+    labels.push(&node.name, &node.name);
+    for child in &node.children {
+        treemap_labels(child, labels);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_direction() {
+        assert_eq!(
+            label_direction("שלום world"),
+            Some(TextDirection::RightToLeft)
+        );
+        assert_eq!(
+            label_direction("2024: مرحبا"),
+            Some(TextDirection::RightToLeft)
+        );
+        assert_eq!(label_direction("API שער"), Some(TextDirection::LeftToRight));
+        assert_eq!(label_direction("42 -> 7"), None);
+        assert!(has_mixed_direction("API שער"));
+        assert!(!has_mixed_direction("שער 42"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Configurable folding of labels before they are compared
+//!
+//! Authors are not consistent about how they write the same name:
+//! `Auth-Service`, `auth service` and `Auth_Service` usually mean one thing,
+//! and so do `Café` and `Cafe`. A [`TextNormalizer`] says which differences
+//! to ignore, and is used wherever the crate compares names written by
+//! people: the duplicate and reference checks of the
+//! [validators](crate::common::validation::ValidationConfig::text_normalizer)
+//! and the diagram lookup of a [`Workspace`](crate::common::workspace::Workspace).
+//!
+//! Diacritics are folded for precomposed Latin letters and for combining
+//! marks; other scripts are compared as written.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::text_normalizer::TextNormalizer;
+//!
+//! assert!(!TextNormalizer::EXACT.matches("Auth-Service", "auth service"));
+//! assert!(TextNormalizer::LENIENT.matches("Auth-Service", "auth service"));
+//! assert_eq!(TextNormalizer::LENIENT.normalize("  Crème_Brûlée "), "creme brulee");
+//! ```
+
+use std::borrow::Cow;
+
+/// Which differences between two labels to ignore
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextNormalizer {
+    /// Compare letters regardless of case
+    pub ignore_case: bool,
+    /// Compare accented Latin letters as their base letter, `é` as `e`
+    pub fold_diacritics: bool,
+    /// Treat each run of whitespace, `-` and `_` as one space, and ignore
+    /// them at either end
+    pub fold_separators: bool,
+}
+
+impl TextNormalizer {
+    /// Labels match only if they are equal; the default
+    pub const EXACT: TextNormalizer = TextNormalizer {
+        ignore_case: false,
+        fold_diacritics: false,
+        fold_separators: false,
+    };
+
+    /// Labels match regardless of case
+    pub const IGNORE_CASE: TextNormalizer = TextNormalizer {
+        ignore_case: true,
+        fold_diacritics: false,
+        fold_separators: false,
+    };
+
+    /// Labels match regardless of case, diacritics and separators
+    pub const LENIENT: TextNormalizer = TextNormalizer {
+        ignore_case: true,
+        fold_diacritics: true,
+        fold_separators: true,
+    };
+
+    /// `text` with the ignored differences folded away; labels match when
+    /// their normalized forms are equal
+    pub fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if *self == Self::EXACT {
+            return Cow::Borrowed(text);
+        }
+
+        let mut normalized = String::with_capacity(text.len());
+        let mut separated = false;
+        for c in text.chars() {
+            if self.fold_separators && (c.is_whitespace() || c == '-' || c == '_') {
+                separated = !normalized.is_empty();
+                continue;
+            }
+            let c = if self.fold_diacritics {
+                if is_combining_mark(c) {
+                    continue;
+                }
+                base_letter(c).unwrap_or(c)
+            } else {
+                c
+            };
+            if separated {
+                normalized.push(' ');
+                separated = false;
+            }
+            if self.ignore_case {
+                normalized.extend(c.to_lowercase());
+            } else {
+                normalized.push(c);
+            }
+        }
+        Cow::Owned(normalized)
+    }
+
+    /// Whether `a` and `b` are the same label once normalized
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        self.normalize(a) == self.normalize(b)
+    }
+}
+
+/// Combining diacritical marks, as left by decomposing `é` into `e` and `◌́`
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}')
+}
+
+/// The unaccented letter of a precomposed Latin-1 or Latin Extended-A letter
+fn base_letter(c: char) -> Option<char> {
+    let base = match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => 'A',
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => 'C',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'Ď' | 'Đ' => 'D',
+        'ď' | 'đ' => 'd',
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => 'E',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => 'G',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'Ĥ' | 'Ħ' => 'H',
+        'ĥ' | 'ħ' => 'h',
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => 'I',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'Ĵ' => 'J',
+        'ĵ' => 'j',
+        'Ķ' => 'K',
+        'ķ' => 'k',
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => 'L',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => 'N',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => 'O',
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'Ŕ' | 'Ŗ' | 'Ř' => 'R',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => 'S',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'Ţ' | 'Ť' | 'Ŧ' => 'T',
+        'ţ' | 'ť' | 'ŧ' => 't',
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => 'U',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'Ŵ' => 'W',
+        'ŵ' => 'w',
+        'Ý' | 'Ŷ' | 'Ÿ' => 'Y',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'Ź' | 'Ż' | 'Ž' => 'Z',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => return None,
+    };
+    Some(base)
+}
@@ -0,0 +1,552 @@
+//! Conversion between sequence diagrams and distributed traces
+//!
+//! A trace is a flat list of [`Span`]s linked by parent ids. Importing it with
+//! [`SequenceDiagram::from_spans`] turns each service into a participant and each
+//! span into a call from its parent's service, with the span's duration in a note
+//! over the callee. Spans without a parent are called by an actor named
+//! [`CLIENT`]. [`SequenceDiagram::to_spans`] goes the other way, so interaction
+//! documentation written by hand can be compared against recorded traces.
+//!
+//! Traces are read from and written to a small JSON format, either a bare array
+//! of spans or an object with a `spans` array:
+//!
+//! ```json
+//! {"spans": [
+//!   {"span_id": "1", "parent_id": null, "service": "web", "name": "GET /cart", "start_ms": 0, "end_ms": 120},
+//!   {"span_id": "2", "parent_id": "1", "service": "db", "name": "SELECT cart", "start_ms": 10, "end_ms": 40}
+//! ]}
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::ast::SequenceDiagram;
+//! use mermaid_parser::common::trace::parse_spans;
+//! use mermaid_parser::MermaidPrinter;
+//!
+//! let spans = parse_spans(
+//!     r#"[{"span_id": "1", "service": "web", "name": "GET /cart", "start_ms": 0, "end_ms": 120},
+//!        {"span_id": "2", "parent_id": "1", "service": "db", "name": "SELECT cart", "start_ms": 10, "end_ms": 40}]"#,
+//! )?;
+//!
+//! let diagram = SequenceDiagram::from_spans(&spans);
+//! assert!(diagram.to_mermaid().contains("web ->> db: SELECT cart"));
+//! assert_eq!(diagram.to_spans().len(), 2);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{
+    AccessibilityInfo, Alternative, ArrowType, Break, Critical, Loop, Message, Note, NotePosition,
+    Optional, Parallel, Participant, ParticipantType, SequenceDiagram, SequenceStatement,
+};
+use crate::common::escape::json_string;
+use crate::common::parser_utils::Scanner;
+use crate::error::{ParseError, Result};
+use std::collections::{HashMap, HashSet};
+
+/// The participant that calls spans without a parent
+pub const CLIENT: &str = "Client";
+
+/// One unit of work in a trace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub span_id: String,
+    /// The span that made this call; `None` for a root span
+    pub parent_id: Option<String>,
+    /// The service that did the work, drawn as a participant
+    pub service: String,
+    /// The operation, drawn as the message text
+    pub name: String,
+    pub start_ms: f64,
+    pub end_ms: f64,
+}
+
+impl Span {
+    pub fn duration_ms(&self) -> f64 {
+        self.end_ms - self.start_ms
+    }
+}
+
+impl SequenceDiagram {
+    /// Build a sequence diagram from the spans of a trace
+    ///
+    /// Siblings are ordered by start time. Each span becomes a call from the
+    /// parent's service (or [`CLIENT`] for a root span) followed by an
+    /// activation of the callee, a `note over` the callee with the duration,
+    /// the span's children and the matching deactivation. A span whose parent
+    /// is not in `spans` is treated as a root.
+    pub fn from_spans(spans: &[Span]) -> SequenceDiagram {
+        let mut ordered: Vec<&Span> = spans.iter().collect();
+        ordered.sort_by(|a, b| a.start_ms.total_cmp(&b.start_ms));
+
+        let known: HashSet<&str> = spans.iter().map(|span| span.span_id.as_str()).collect();
+        let mut children: HashMap<&str, Vec<&Span>> = HashMap::new();
+        let mut roots = Vec::new();
+        for span in ordered {
+            match span.parent_id.as_deref() {
+                Some(parent) if known.contains(parent) => {
+                    children.entry(parent).or_default().push(span)
+                }
+                _ => roots.push(span),
+            }
+        }
+
+        let mut participants = Vec::new();
+        if !roots.is_empty() {
+            participants.push(Participant {
+                actor: CLIENT.to_string(),
+                alias: None,
+                participant_type: ParticipantType::Actor,
+            });
+        }
+        let mut statements = Vec::new();
+        for root in roots {
+            emit_span(root, CLIENT, &children, &mut participants, &mut statements);
+        }
+
+        SequenceDiagram {
+            title: None,
+            accessibility: AccessibilityInfo::default(),
+            participants,
+            statements,
+            boxes: vec![],
+            autonumber: None,
+        }
+    }
+
+    /// Recover a trace from the calls in this diagram
+    ///
+    /// Every solid or cross message becomes a span of its receiver, a child of
+    /// the innermost open span of its sender. A span stays open until its
+    /// service is deactivated or sends a dotted reply. Statements inside
+    /// `loop`, `alt` and the other blocks are read in order. Durations come
+    /// from `note over` texts like `120 ms` written after the call; spans are
+    /// then laid out one after another from time zero, each lasting at least
+    /// as long as its children.
+    pub fn to_spans(&self) -> Vec<Span> {
+        let mut builder = SpanBuilder::default();
+        builder.walk(&self.statements);
+
+        let mut spans: Vec<Span> = builder
+            .spans
+            .iter()
+            .enumerate()
+            .map(|(index, pending)| Span {
+                span_id: (index + 1).to_string(),
+                parent_id: pending.parent.map(|parent| (parent + 1).to_string()),
+                service: pending.service.clone(),
+                name: pending.name.clone(),
+                start_ms: 0.0,
+                end_ms: 0.0,
+            })
+            .collect();
+
+        let mut cursor = 0.0;
+        for root in 0..spans.len() {
+            if builder.spans[root].parent.is_none() {
+                cursor = lay_out(root, cursor, &builder.spans, &mut spans);
+            }
+        }
+        spans
+    }
+}
+
+fn emit_span<'a>(
+    span: &'a Span,
+    caller: &str,
+    children: &HashMap<&str, Vec<&'a Span>>,
+    participants: &mut Vec<Participant>,
+    statements: &mut Vec<SequenceStatement>,
+) {
+    if !participants.iter().any(|p| p.actor == span.service) {
+        participants.push(Participant {
+            actor: span.service.clone(),
+            alias: None,
+            participant_type: ParticipantType::Participant,
+        });
+    }
+
+    statements.push(SequenceStatement::Message(Message {
+        from: caller.to_string(),
+        to: span.service.clone(),
+        text: span.name.clone(),
+        arrow_type: ArrowType::SolidClosed,
+        activation: None,
+    }));
+    statements.push(SequenceStatement::Activate(span.service.clone()));
+    statements.push(SequenceStatement::Note(Note {
+        position: NotePosition::Over,
+        actor: span.service.clone(),
+        text: format!("{} ms", span.duration_ms()),
+    }));
+    for child in children.get(span.span_id.as_str()).into_iter().flatten() {
+        emit_span(child, &span.service, children, participants, statements);
+    }
+    statements.push(SequenceStatement::Deactivate(span.service.clone()));
+}
+
+struct PendingSpan {
+    parent: Option<usize>,
+    service: String,
+    name: String,
+    duration_ms: Option<f64>,
+}
+
+#[derive(Default)]
+struct SpanBuilder {
+    spans: Vec<PendingSpan>,
+    /// Indices into `spans`, innermost last
+    open: Vec<usize>,
+    /// The span created by the previous statement, which a duration note
+    /// may describe
+    last_call: Option<usize>,
+}
+
+impl SpanBuilder {
+    fn walk(&mut self, statements: &[SequenceStatement]) {
+        for statement in statements {
+            let last_call = self.last_call.take();
+            match statement {
+                SequenceStatement::Message(message) => self.message(message),
+                SequenceStatement::Activate(_) => self.last_call = last_call,
+                SequenceStatement::Note(note) => match last_call {
+                    Some(index) if note.actor == self.spans[index].service => {
+                        match parse_duration(&note.text) {
+                            Some(duration) => self.spans[index].duration_ms = Some(duration),
+                            None => self.last_call = Some(index),
+                        }
+                    }
+                    _ => {}
+                },
+                SequenceStatement::Deactivate(service) => self.close(service),
+                SequenceStatement::Loop(Loop { statements, .. })
+                | SequenceStatement::Opt(Optional { statements, .. })
+                | SequenceStatement::Break(Break { statements, .. })
+                | SequenceStatement::Rect { statements, .. } => self.walk(statements),
+                SequenceStatement::Alt(Alternative {
+                    statements,
+                    else_branch,
+                    ..
+                }) => {
+                    self.walk(statements);
+                    if let Some(else_branch) = else_branch {
+                        self.walk(&else_branch.statements);
+                    }
+                }
+                SequenceStatement::Par(Parallel { branches }) => {
+                    for branch in branches {
+                        self.walk(&branch.statements);
+                    }
+                }
+                SequenceStatement::Critical(Critical {
+                    statements,
+                    options,
+                    ..
+                }) => {
+                    self.walk(statements);
+                    for option in options {
+                        self.walk(&option.statements);
+                    }
+                }
+                SequenceStatement::Create(_) | SequenceStatement::Destroy(_) => {}
+            }
+        }
+    }
+
+    fn message(&mut self, message: &Message) {
+        match message.arrow_type {
+            ArrowType::DottedOpen | ArrowType::DottedClosed | ArrowType::BiDirectionalDotted => {
+                self.close(&message.from);
+            }
+            _ => {
+                let parent = self
+                    .open
+                    .iter()
+                    .rev()
+                    .copied()
+                    .find(|&index| self.spans[index].service == message.from);
+                self.spans.push(PendingSpan {
+                    parent,
+                    service: message.to.clone(),
+                    name: message.text.clone(),
+                    duration_ms: None,
+                });
+                let index = self.spans.len() - 1;
+                self.open.push(index);
+                self.last_call = Some(index);
+            }
+        }
+    }
+
+    fn close(&mut self, service: &str) {
+        if let Some(position) = self
+            .open
+            .iter()
+            .rposition(|&index| self.spans[index].service == service)
+        {
+            self.open.remove(position);
+        }
+    }
+}
+
+fn parse_duration(text: &str) -> Option<f64> {
+    text.trim().strip_suffix("ms")?.trim().parse().ok()
+}
+
+/// Give `index` and its descendants start and end times from `start`,
+/// returning the end time
+fn lay_out(index: usize, start: f64, pending: &[PendingSpan], spans: &mut [Span]) -> f64 {
+    let mut cursor = start;
+    for child in 0..pending.len() {
+        if pending[child].parent == Some(index) {
+            cursor = lay_out(child, cursor, pending, spans);
+        }
+    }
+    let end = cursor.max(start + pending[index].duration_ms.unwrap_or(0.0));
+    spans[index].start_ms = start;
+    spans[index].end_ms = end;
+    end
+}
+
+/// Read spans from the JSON trace format described in the module
+/// documentation
+///
+/// `parent_id` may be missing or `null`; other fields are required. Unknown
+/// fields are ignored.
+pub fn parse_spans(input: &str) -> Result<Vec<Span>> {
+    let mut reader = JsonReader::new(input);
+    let value = reader.value()?;
+    reader.scanner.skip_whitespace();
+    if reader.scanner.peek().is_some() {
+        return Err(reader.error("Unexpected content after trace", "end of input"));
+    }
+
+    let spans = match value {
+        Json::Array(spans) => spans,
+        Json::Object(mut fields) => match fields.remove("spans") {
+            Some(Json::Array(spans)) => spans,
+            _ => return Err(trace_error("Expected a \"spans\" array")),
+        },
+        _ => return Err(trace_error("Expected an array of spans")),
+    };
+    spans.into_iter().map(span_from_json).collect()
+}
+
+/// Write spans in the JSON trace format read by [`parse_spans`]
+pub fn spans_to_json(spans: &[Span]) -> String {
+    let spans: Vec<String> = spans
+        .iter()
+        .map(|span| {
+            format!(
+                "{{\"span_id\": {}, \"parent_id\": {}, \"service\": {}, \"name\": {}, \"start_ms\": {}, \"end_ms\": {}}}",
+                json_string(&span.span_id),
+                span.parent_id
+                    .as_deref()
+                    .map_or_else(|| "null".to_string(), json_string),
+                json_string(&span.service),
+                json_string(&span.name),
+                span.start_ms,
+                span.end_ms
+            )
+        })
+        .collect();
+    format!("{{\"spans\": [{}]}}", spans.join(", "))
+}
+
+fn span_from_json(value: Json) -> Result<Span> {
+    let Json::Object(mut fields) = value else {
+        return Err(trace_error("Expected each span to be an object"));
+    };
+    let mut string = |key: &str| match fields.remove(key) {
+        Some(Json::String(value)) => Ok(Some(value)),
+        Some(Json::Null) | None => Ok(None),
+        Some(_) => Err(trace_error(&format!("Expected \"{}\" to be a string", key))),
+    };
+    let span_id = string("span_id")?;
+    let parent_id = string("parent_id")?;
+    let service = string("service")?;
+    let name = string("name")?;
+    let mut number = |key: &str| match fields.remove(key) {
+        Some(Json::Number(value)) => Ok(value),
+        _ => Err(trace_error(&format!("Expected \"{}\" to be a number", key))),
+    };
+    let start_ms = number("start_ms")?;
+    let end_ms = number("end_ms")?;
+
+    let required = |value: Option<String>, key: &str| {
+        value.ok_or_else(|| trace_error(&format!("Span is missing \"{}\"", key)))
+    };
+    Ok(Span {
+        span_id: required(span_id, "span_id")?,
+        parent_id,
+        service: required(service, "service")?,
+        name: required(name, "name")?,
+        start_ms,
+        end_ms,
+    })
+}
+
+fn trace_error(message: &str) -> ParseError {
+    ParseError::SemanticError {
+        message: message.to_string(),
+        context: "trace".to_string(),
+    }
+}
+
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(HashMap<String, Json>),
+}
+
+struct JsonReader {
+    scanner: Scanner,
+}
+
+impl JsonReader {
+    fn new(input: &str) -> Self {
+        Self {
+            scanner: Scanner::new(input),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.scanner.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error("Unexpected character", &expected.to_string()))
+        }
+    }
+
+    fn error(&self, message: &str, expected: &str) -> ParseError {
+        let (line, column) = self.scanner.location();
+        ParseError::SyntaxError {
+            message: message.to_string(),
+            expected: vec![expected.to_string()],
+            found: self
+                .scanner
+                .peek()
+                .map_or_else(|| "end of input".to_string(), String::from),
+            line,
+            column,
+            partial: None,
+        }
+    }
+
+    fn value(&mut self) -> Result<Json> {
+        self.scanner.skip_whitespace();
+        match self.scanner.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Json::String),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) if self.scanner.eat_word("null") => Ok(Json::Null),
+            Some(_) if self.scanner.eat_word("true") || self.scanner.eat_word("false") => {
+                Ok(Json::Bool)
+            }
+            _ => Err(self.error("Expected a JSON value", "value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json> {
+        self.expect('{')?;
+        let mut fields = HashMap::new();
+        if self.scanner.eat('}') {
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.scanner.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.insert(key, self.value()?);
+            if self.scanner.eat('}') {
+                return Ok(Json::Object(fields));
+            }
+            if !self.scanner.eat(',') {
+                return Err(self.error("Unterminated object", "',' or '}'"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        if self.scanner.eat(']') {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.scanner.eat(']') {
+                return Ok(Json::Array(items));
+            }
+            if !self.scanner.eat(',') {
+                return Err(self.error("Unterminated array", "',' or ']'"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        if self.scanner.peek() != Some('"') {
+            return Err(self.error("Expected a string", "'\"'"));
+        }
+        self.scanner.bump();
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.scanner.peek() else {
+                return Err(self.error("Unterminated string", "'\"'"));
+            };
+            self.scanner.bump();
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escaped = self.scanner.peek();
+                    self.scanner.bump();
+                    match escaped {
+                        Some('n') => value.push('\n'),
+                        Some('r') => value.push('\r'),
+                        Some('t') => value.push('\t'),
+                        Some('b') => value.push('\u{8}'),
+                        Some('f') => value.push('\u{c}'),
+                        Some('u') => {
+                            let start = self.scanner.position();
+                            for _ in 0..4 {
+                                self.scanner.bump();
+                            }
+                            let hex = self.scanner.since(start);
+                            let decoded =
+                                u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                            match decoded {
+                                Some(decoded) if hex.len() == 4 => value.push(decoded),
+                                _ => {
+                                    return Err(
+                                        self.error("Invalid unicode escape", "four hex digits")
+                                    )
+                                }
+                            }
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => value.push(c),
+                        _ => return Err(self.error("Invalid escape", "escape character")),
+                    }
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json> {
+        let start = self.scanner.position();
+        self.scanner
+            .skip_while(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'));
+        self.scanner
+            .since(start)
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| {
+                self.scanner.rewind(start);
+                self.error("Invalid number", "number")
+            })
+    }
+}
@@ -0,0 +1,141 @@
+//! Counts of the diagram headers that fall through to the misc parser
+//!
+//! A diagram whose first keyword [`crate::parse_diagram`] does not know is
+//! parsed by the misc parser, which keeps its lines as raw text. Over a
+//! corpus, the keywords of those diagrams show which diagram types or
+//! aliases are worth supporting next. [`UnrecognizedHeaders`] tallies them,
+//! grouping spellings of the same keyword into one [`KeywordCluster`]: case,
+//! `-` and `_` are ignored, as is a version suffix such as `-beta` or `-v2`,
+//! so `zenuml`, `zenUML` and `zenuml-beta` are counted together.
+//!
+//! [`crate::corpus::unrecognized_headers`] collects the report for the
+//! diagram files and Markdown-embedded diagrams under a directory.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::unrecognized::UnrecognizedHeaders;
+//!
+//! let report = UnrecognizedHeaders::from_sources([
+//!     "flowchart TD\n    A --> B",
+//!     "zenuml\n    title Demo",
+//!     "zenUML-beta\n    A.call()",
+//!     "venn-beta\n    set A",
+//! ]);
+//!
+//! assert_eq!(report.diagrams(), 4);
+//! let clusters = report.clusters();
+//! assert_eq!(clusters[0].keyword, "zenuml");
+//! assert_eq!(clusters[0].count, 2);
+//! assert_eq!(clusters[1].spellings, vec![("venn-beta".to_string(), 1)]);
+//! ```
+
+use std::collections::BTreeMap;
+
+/// Suffixes that mark a release stage rather than a different diagram type
+const STAGE_SUFFIXES: &[&str] = &["-beta", "-alpha"];
+
+/// Spellings of one unrecognized header keyword
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordCluster {
+    /// The keyword lowercased, without `-`, `_` or a version suffix
+    pub keyword: String,
+    /// Diagrams with any of the spellings
+    pub count: usize,
+    /// Each spelling as written and its number of diagrams, most common first
+    pub spellings: Vec<(String, usize)>,
+}
+
+/// A tally of the header keywords the parser routes to the misc parser
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UnrecognizedHeaders {
+    diagrams: usize,
+    spellings: BTreeMap<String, usize>,
+}
+
+impl UnrecognizedHeaders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tally of every diagram in `sources`
+    pub fn from_sources<'a>(sources: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut report = Self::new();
+        for source in sources {
+            report.add(source);
+        }
+        report
+    }
+
+    /// Count one diagram, returning its header keyword if the parser does
+    /// not recognize it
+    pub fn add<'a>(&mut self, source: &'a str) -> Option<&'a str> {
+        self.diagrams += 1;
+        let keyword = unrecognized_keyword(source)?;
+        *self.spellings.entry(keyword.to_string()).or_insert(0) += 1;
+        Some(keyword)
+    }
+
+    /// Diagrams counted, recognized or not
+    pub fn diagrams(&self) -> usize {
+        self.diagrams
+    }
+
+    /// Diagrams with an unrecognized header keyword
+    pub fn unrecognized(&self) -> usize {
+        self.spellings.values().sum()
+    }
+
+    /// The unrecognized keywords grouped by [`normalize`], most common first
+    /// and then by keyword
+    pub fn clusters(&self) -> Vec<KeywordCluster> {
+        let mut clusters: BTreeMap<String, KeywordCluster> = BTreeMap::new();
+        for (spelling, count) in &self.spellings {
+            let keyword = normalize(spelling);
+            let cluster = clusters
+                .entry(keyword.clone())
+                .or_insert_with(|| KeywordCluster {
+                    keyword,
+                    count: 0,
+                    spellings: Vec::new(),
+                });
+            cluster.count += count;
+            cluster.spellings.push((spelling.clone(), *count));
+        }
+
+        let mut clusters: Vec<_> = clusters.into_values().collect();
+        for cluster in &mut clusters {
+            cluster
+                .spellings
+                .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        }
+        clusters.sort_by(|a, b| b.count.cmp(&a.count).then(a.keyword.cmp(&b.keyword)));
+        clusters
+    }
+}
+
+/// The header keyword of `source`, if the parser would hand it to the misc
+/// parser to keep as raw lines
+fn unrecognized_keyword(source: &str) -> Option<&str> {
+    if crate::detect_diagram_type(source).ok()? != "misc" {
+        return None;
+    }
+    let keyword = crate::header_keyword(crate::header_line(source).0?);
+    let lowercase = keyword.to_lowercase();
+    (!keyword.is_empty() && !crate::MISC_HEADERS.contains(&lowercase.as_str())).then_some(keyword)
+}
+
+/// `keyword` lowercased, without a release stage or version suffix and
+/// without `-` and `_`
+pub fn normalize(keyword: &str) -> String {
+    let lowercase = keyword.to_lowercase();
+    let base = STAGE_SUFFIXES
+        .iter()
+        .find_map(|suffix| lowercase.strip_suffix(suffix))
+        .or_else(|| {
+            let (base, version) = lowercase.rsplit_once("-v")?;
+            (!version.is_empty() && version.chars().all(|c| c.is_ascii_digit())).then_some(base)
+        })
+        .unwrap_or(lowercase.as_str());
+    base.chars().filter(|c| *c != '-' && *c != '_').collect()
+}
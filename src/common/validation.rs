@@ -26,8 +26,13 @@
 //! }
 //! ```
 
+use crate::common::arrow_table::ArrowKind;
 use crate::common::ast::*;
-use std::collections::{HashMap, HashSet};
+use crate::common::constants::{flowchart_keywords, gantt_keywords, sequence_keywords};
+use crate::common::text_direction::{self, TextDirection};
+use crate::common::text_normalizer::TextNormalizer;
+use crate::stable::DiagramKind;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 /// Location information for validation errors
 #[derive(Debug, Clone, PartialEq)]
@@ -80,6 +85,8 @@ pub struct ValidationError {
     pub message: String,
     pub severity: Severity,
     pub location: Option<Location>,
+    /// The file or other source of the diagram, if it was named
+    pub source: Option<String>,
 }
 
 impl ValidationError {
@@ -89,6 +96,7 @@ impl ValidationError {
             message,
             severity,
             location: None,
+            source: None,
         }
     }
 
@@ -103,6 +111,7 @@ impl ValidationError {
             message,
             severity,
             location: Some(location),
+            source: None,
         }
     }
 
@@ -117,21 +126,34 @@ impl ValidationError {
     pub fn info(rule: &'static str, message: String) -> Self {
         Self::new(rule, message, Severity::Info)
     }
+
+    /// Attribute the diagnostic to the source called `name`
+    pub fn in_source(mut self, name: impl Into<String>) -> Self {
+        self.source = Some(name.into());
+        self
+    }
 }
 
 impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[{}] {}: {}", self.severity, self.rule, self.message)?;
+        let source = self
+            .source
+            .as_deref()
+            .map(|name| format!("{}:", name))
+            .unwrap_or_default();
         if let Some(location) = &self.location {
             if let Some(element_id) = &location.element_id {
                 write!(
                     f,
-                    " (at {}:{}, element: {})",
-                    location.line, location.column, element_id
+                    " (at {}{}:{}, element: {})",
+                    source, location.line, location.column, element_id
                 )?;
             } else {
-                write!(f, " (at {}:{})", location.line, location.column)?;
+                write!(f, " (at {}{}:{})", source, location.line, location.column)?;
             }
+        } else if let Some(name) = &self.source {
+            write!(f, " (in {})", name)?;
         }
         Ok(())
     }
@@ -151,6 +173,23 @@ pub struct ValidationConfig {
     pub min_severity: Severity,
     pub ignore_rules: HashSet<&'static str>,
     pub custom_rules: Vec<Box<dyn CustomValidationRule>>,
+    /// User-defined lints registered with [`ValidationConfig::register_rule`]
+    pub rules: Vec<Box<dyn Rule>>,
+    /// Fields [`RequirementValidator`] expects on every requirement
+    pub requirement_profile: RequirementProfile,
+    /// How names are compared when looking for duplicates and resolving
+    /// references: subgraph titles, class members, sequence participants
+    /// and requirement names. Exact by default.
+    pub text_normalizer: TextNormalizer,
+}
+
+impl ValidationConfig {
+    /// Adds a user-defined lint, run by [`UniversalValidator`] after the built-in
+    /// checks for every diagram kind the rule applies to
+    pub fn register_rule(&mut self, rule: impl Rule + 'static) -> &mut Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
 }
 
 impl Clone for ValidationConfig {
@@ -159,6 +198,9 @@ impl Clone for ValidationConfig {
             min_severity: self.min_severity,
             ignore_rules: self.ignore_rules.clone(),
             custom_rules: Vec::new(), // Custom rules can't be cloned
+            rules: Vec::new(),
+            requirement_profile: self.requirement_profile,
+            text_normalizer: self.text_normalizer,
         }
     }
 }
@@ -169,6 +211,9 @@ impl Default for ValidationConfig {
             min_severity: Severity::Warning,
             ignore_rules: HashSet::new(),
             custom_rules: Vec::new(),
+            rules: Vec::new(),
+            requirement_profile: RequirementProfile::default(),
+            text_normalizer: TextNormalizer::default(),
         }
     }
 }
@@ -192,6 +237,62 @@ pub trait CustomValidationRule: std::fmt::Debug {
     }
 }
 
+/// A user-defined lint, such as a naming standard or a required legend node
+///
+/// Rules see the whole [`DiagramType`] and report their findings as
+/// [`ValidationError`]s, so they share severity filtering, `ignore_rules` and
+/// reporting with the built-in checks.
+///
+/// ```rust
+/// use mermaid_parser::common::ast::DiagramType;
+/// use mermaid_parser::common::validation::{
+///     Rule, UniversalValidator, ValidationConfig, ValidationError,
+/// };
+/// use mermaid_parser::stable::DiagramKind;
+///
+/// #[derive(Debug)]
+/// struct RequireLegend;
+///
+/// impl Rule for RequireLegend {
+///     fn name(&self) -> &'static str {
+///         "require_legend"
+///     }
+///
+///     fn applies_to(&self, kind: DiagramKind) -> bool {
+///         kind == DiagramKind::Flowchart
+///     }
+///
+///     fn check(&self, diagram: &DiagramType) -> Vec<ValidationError> {
+///         match diagram {
+///             DiagramType::Flowchart(d) if !d.nodes.contains_key("Legend") => {
+///                 vec![ValidationError::error(self.name(), "Missing Legend node".to_string())]
+///             }
+///             _ => Vec::new(),
+///         }
+///     }
+/// }
+///
+/// let mut config = ValidationConfig::default();
+/// config.register_rule(RequireLegend);
+/// let validator = UniversalValidator::with_config(config);
+///
+/// let diagram = mermaid_parser::parse_diagram("flowchart TD\n    A --> B")?;
+/// assert!(validator.validate_any(&diagram).is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait Rule: std::fmt::Debug {
+    /// Identifier for the rule; an entry in `ignore_rules` with this name
+    /// disables it
+    fn name(&self) -> &'static str;
+
+    /// Whether the rule runs for diagrams of `kind`; defaults to every kind
+    fn applies_to(&self, _kind: DiagramKind) -> bool {
+        true
+    }
+
+    fn check(&self, diagram: &DiagramType) -> Vec<ValidationError>;
+}
+
 /// Comprehensive validator for all diagram types
 #[derive(Debug)]
 pub struct UniversalValidator {
@@ -237,10 +338,65 @@ impl UniversalValidator {
                     errors.extend(state_errors);
                 }
             }
+            DiagramType::Block(d) => {
+                let validator = BlockValidator::with_config(self.config.clone());
+                if let Err(block_errors) = validator.validate(d) {
+                    errors.extend(block_errors);
+                }
+            }
+            DiagramType::XyChart(d) => {
+                let validator = XyChartValidator::with_config(self.config.clone());
+                if let Err(xy_errors) = validator.validate(d) {
+                    errors.extend(xy_errors);
+                }
+            }
+            DiagramType::Quadrant(d) => {
+                let validator = QuadrantValidator::with_config(self.config.clone());
+                if let Err(quadrant_errors) = validator.validate(d) {
+                    errors.extend(quadrant_errors);
+                }
+            }
+            DiagramType::Radar(d) => {
+                let validator = RadarValidator::with_config(self.config.clone());
+                if let Err(radar_errors) = validator.validate(d) {
+                    errors.extend(radar_errors);
+                }
+            }
+            DiagramType::Gantt(d) => {
+                let validator = GanttValidator::with_config(self.config.clone());
+                if let Err(gantt_errors) = validator.validate(d) {
+                    errors.extend(gantt_errors);
+                }
+            }
+            DiagramType::Timeline(d) => {
+                let validator = TimelineValidator::with_config(self.config.clone());
+                if let Err(timeline_errors) = validator.validate(d) {
+                    errors.extend(timeline_errors);
+                }
+            }
+            DiagramType::Requirement(d) => {
+                let validator = RequirementValidator::with_config(self.config.clone());
+                if let Err(requirement_errors) = validator.validate(d) {
+                    errors.extend(requirement_errors);
+                }
+            }
+            DiagramType::Er(d) => {
+                let validator = ErValidator::with_config(self.config.clone());
+                if let Err(er_errors) = validator.validate(d) {
+                    errors.extend(er_errors);
+                }
+            }
+            DiagramType::Architecture(d) => {
+                let validator = ArchitectureValidator::with_config(self.config.clone());
+                if let Err(architecture_errors) = validator.validate(d) {
+                    errors.extend(architecture_errors);
+                }
+            }
             _ => {
                 // Other diagram types can be added here as needed
             }
         }
+        errors.extend(validate_text_direction(diagram));
 
         // Apply custom rules
         for rule in &self.config.custom_rules {
@@ -253,6 +409,14 @@ impl UniversalValidator {
             }
         }
 
+        // Apply registered lints
+        let kind = DiagramKind::from(diagram);
+        for rule in &self.config.rules {
+            if rule.applies_to(kind) && !self.config.ignore_rules.contains(rule.name()) {
+                errors.extend(rule.check(diagram));
+            }
+        }
+
         // Filter by severity and ignored rules
         errors.retain(|error| {
             error.severity >= self.config.min_severity
@@ -273,6 +437,58 @@ impl Default for UniversalValidator {
     }
 }
 
+/// Right-to-left labels that are likely to render poorly
+///
+/// Mermaid draws labels as SVG text without bidirectional isolation, so a
+/// label mixing scripts may show its words out of order, and digits or
+/// punctuation at either end of a right-to-left label are drawn on the
+/// wrong side. Flowcharts and state diagrams laid out left to right also
+/// read against right-to-left labels.
+fn validate_text_direction(diagram: &DiagramType) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let neutral =
+        |c: Option<char>| c.is_some_and(|c| !c.is_alphabetic() && !text_direction::is_rtl_char(c));
+
+    for label in text_direction::labels(diagram) {
+        let text = label.text.trim();
+        if text_direction::has_mixed_direction(text) {
+            errors.push(ValidationError::with_location(
+                "mixed_direction_label",
+                format!(
+                    "Label '{}' mixes right-to-left and left-to-right text; its words may be rendered out of order",
+                    text
+                ),
+                Severity::Warning,
+                Location::with_element(0, 0, label.element),
+            ));
+        } else if text.chars().any(text_direction::is_rtl_char)
+            && (neutral(text.chars().next()) || neutral(text.chars().next_back()))
+        {
+            errors.push(ValidationError::with_location(
+                "rtl_label_neutral_edge",
+                format!(
+                    "Right-to-left label '{}' starts or ends with a digit or punctuation, which is drawn on the wrong side",
+                    text
+                ),
+                Severity::Warning,
+                Location::with_element(0, 0, label.element),
+            ));
+        }
+    }
+
+    let laid_out_left_to_right =
+        matches!(diagram, DiagramType::Flowchart(_) | DiagramType::State(_))
+            && diagram.layout_direction() == Some(LayoutDirection::LeftToRight);
+    if laid_out_left_to_right && diagram.text_direction() == TextDirection::RightToLeft {
+        errors.push(ValidationError::warning(
+            "rtl_left_to_right_layout",
+            "Labels read right to left but the diagram is laid out left to right; consider direction RL".to_string(),
+        ));
+    }
+
+    errors
+}
+
 /// Flowchart-specific validator
 #[derive(Debug)]
 pub struct FlowchartValidator {
@@ -340,13 +556,11 @@ impl FlowchartValidator {
 
         for subgraph in &diagram.subgraphs {
             if let Some(name) = &subgraph.title {
-                if subgraph_names.contains(name) {
+                if !subgraph_names.insert(self.config.text_normalizer.normalize(name)) {
                     errors.push(ValidationError::error(
                         "duplicate_subgraph_name",
                         format!("Subgraph name '{}' is not unique", name),
                     ));
-                } else {
-                    subgraph_names.insert(name.clone());
                 }
             }
         }
@@ -354,23 +568,50 @@ impl FlowchartValidator {
         errors
     }
 
+    /// Classes applied to nodes but never defined, and classDefs never
+    /// applied; `classDef default` applies to every node
     fn validate_style_classes(&self, diagram: &FlowchartDiagram) -> Vec<ValidationError> {
-        let mut errors = Vec::new();
-        let defined_classes: HashSet<_> = diagram.class_defs.keys().collect();
+        let applied: BTreeSet<&str> = diagram
+            .nodes
+            .values()
+            .flat_map(|node| node.classes.iter().map(String::as_str))
+            .collect();
+        let defined: BTreeSet<&str> = diagram.class_defs.keys().map(String::as_str).collect();
+        class_reference_errors("Node", &applied, &defined)
+    }
 
-        // Check if referenced style classes are defined
-        for node in diagram.nodes.values() {
-            for class_name in &node.classes {
-                if !defined_classes.contains(class_name) {
-                    errors.push(ValidationError::warning(
-                        "undefined_style_class",
-                        format!("Node references undefined style class '{}'", class_name),
-                    ));
-                }
-            }
-        }
+    /// `style` statements whose node, subgraph or edge does not exist
+    fn validate_style_targets(&self, diagram: &FlowchartDiagram) -> Vec<ValidationError> {
+        diagram
+            .styles
+            .iter()
+            .filter(|style| !style_target_exists(diagram, &style.target))
+            .map(|style| {
+                let (kind, label) = match &style.target {
+                    StyleTarget::Node(id) => ("node", id.clone()),
+                    StyleTarget::Subgraph(id) => ("subgraph", id.clone()),
+                    StyleTarget::Edge(..) => ("edge", style_target_label(&style.target)),
+                };
+                ValidationError::with_location(
+                    "orphaned_style",
+                    format!("Style targets nonexistent {} '{}'", kind, label),
+                    Severity::Warning,
+                    Location::with_element(0, 0, label),
+                )
+            })
+            .collect()
+    }
 
-        errors
+    /// Node ids that are Mermaid keywords break the printed diagram
+    fn validate_reserved_words(&self, diagram: &FlowchartDiagram) -> Vec<ValidationError> {
+        let mut node_ids: Vec<_> = diagram.nodes.keys().collect();
+        node_ids.sort();
+
+        node_ids
+            .into_iter()
+            .filter(|id| flowchart_keywords::RESERVED_IDS.contains(&id.as_str()))
+            .map(|id| reserved_word_error(id))
+            .collect()
     }
 }
 
@@ -385,6 +626,13 @@ impl DiagramValidator for FlowchartValidator {
         errors.extend(self.validate_edge_references(diagram));
         errors.extend(self.validate_subgraphs(diagram));
         errors.extend(self.validate_style_classes(diagram));
+        errors.extend(duplicate_class_def_errors(&diagram.class_defs));
+        errors.extend(self.validate_style_targets(diagram));
+        errors.extend(self.validate_reserved_words(diagram));
+        errors.extend(validate_accessibility_title(
+            &diagram.title,
+            &diagram.accessibility,
+        ));
 
         // Filter by severity and ignored rules
         errors.retain(|error| {
@@ -425,15 +673,32 @@ impl SequenceValidator {
 
     fn validate_participant_references(&self, diagram: &SequenceDiagram) -> Vec<ValidationError> {
         let mut errors = Vec::new();
-        let participant_names: HashSet<_> = diagram.participants.iter().map(|p| &p.actor).collect();
+        let normalizer = self.config.text_normalizer;
+        let participant_names: HashSet<_> = diagram
+            .participants
+            .iter()
+            .map(|p| normalizer.normalize(&p.actor))
+            .collect();
+        let is_defined = |name: &str| participant_names.contains(&*normalizer.normalize(name));
 
         for statement in &diagram.statements {
-            check_statement_participants(statement, &participant_names, &mut errors);
+            check_statement_participants(statement, &is_defined, &mut errors);
         }
 
         errors
     }
 
+    /// Participant ids that are block keywords (`end`, `loop`, ...) break
+    /// the printed diagram
+    fn validate_reserved_words(&self, diagram: &SequenceDiagram) -> Vec<ValidationError> {
+        diagram
+            .participants
+            .iter()
+            .filter(|p| sequence_keywords::RESERVED_IDS.contains(&p.actor.as_str()))
+            .map(|p| reserved_word_error(&p.actor))
+            .collect()
+    }
+
     fn validate_activation_blocks(&self, diagram: &SequenceDiagram) -> Vec<ValidationError> {
         let mut errors = Vec::new();
         let mut activation_stack = Vec::new();
@@ -457,20 +722,131 @@ impl SequenceValidator {
     }
 }
 
+/// Orphaned class references of a diagram whose `element`s apply the
+/// `applied` classes and whose classDefs define `defined`
+///
+/// Flowcharts, state diagrams, ER diagrams and quadrant charts are checked.
+fn class_reference_errors(
+    element: &str,
+    applied: &BTreeSet<&str>,
+    defined: &BTreeSet<&str>,
+) -> Vec<ValidationError> {
+    let undefined = applied.difference(defined).map(|name| {
+        ValidationError::with_location(
+            "undefined_style_class",
+            format!("{} references undefined style class '{}'", element, name),
+            Severity::Warning,
+            Location::with_element(0, 0, name.to_string()),
+        )
+    });
+    let unused = defined
+        .difference(applied)
+        .filter(|name| **name != "default")
+        .map(|name| {
+            ValidationError::with_location(
+                "unused_class_def",
+                format!("classDef '{}' is never applied", name),
+                Severity::Warning,
+                Location::with_element(0, 0, name.to_string()),
+            )
+        });
+    undefined.chain(unused).collect()
+}
+
+/// Whether the node, subgraph or edge a flowchart `style` targets exists
+pub(crate) fn style_target_exists(diagram: &FlowchartDiagram, target: &StyleTarget) -> bool {
+    fn has_subgraph(subgraphs: &[Subgraph], id: &str) -> bool {
+        subgraphs
+            .iter()
+            .any(|subgraph| subgraph.id == id || has_subgraph(&subgraph.subgraphs, id))
+    }
+    fn has_edge(edges: &[FlowEdge], subgraphs: &[Subgraph], from: &str, to: &str) -> bool {
+        edges.iter().any(|edge| edge.from == from && edge.to == to)
+            || subgraphs
+                .iter()
+                .any(|subgraph| has_edge(&subgraph.edges, &subgraph.subgraphs, from, to))
+    }
+
+    match target {
+        StyleTarget::Node(id) => {
+            diagram.nodes.contains_key(id) || has_subgraph(&diagram.subgraphs, id)
+        }
+        StyleTarget::Subgraph(id) => has_subgraph(&diagram.subgraphs, id),
+        StyleTarget::Edge(from, to) => has_edge(&diagram.edges, &diagram.subgraphs, from, to),
+    }
+}
+
+/// The element id of an `orphaned_style` diagnostic
+pub(crate) fn style_target_label(target: &StyleTarget) -> String {
+    match target {
+        StyleTarget::Node(id) | StyleTarget::Subgraph(id) => id.clone(),
+        StyleTarget::Edge(from, to) => format!("{}-->{}", from, to),
+    }
+}
+
+/// classDefs of a diagram that keeps its classes by name and was defined by
+/// more than one `classDef` statement; their properties are already merged
+fn duplicate_class_def_errors(class_defs: &HashMap<String, ClassDef>) -> Vec<ValidationError> {
+    let mut names: Vec<_> = class_defs
+        .iter()
+        .filter(|(_, class_def)| class_def.definitions > 1)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    names.sort_unstable();
+    names.into_iter().map(duplicate_class_def_error).collect()
+}
+
+fn duplicate_class_def_error(name: &str) -> ValidationError {
+    ValidationError::with_location(
+        "duplicate_class_def",
+        format!("classDef '{}' is defined more than once", name),
+        Severity::Warning,
+        Location::with_element(0, 0, name.to_string()),
+    )
+}
+
+fn reserved_word_error(id: &str) -> ValidationError {
+    ValidationError::with_location(
+        "reserved_word_id",
+        format!("Identifier '{}' is a reserved word", id),
+        Severity::Warning,
+        Location::with_element(0, 0, id.to_string()),
+    )
+}
+
+/// Diagrams without an accessible title are harder to use with screen readers
+fn validate_accessibility_title(
+    title: &Option<String>,
+    accessibility: &AccessibilityInfo,
+) -> Vec<ValidationError> {
+    if accessibility.title.is_some() {
+        return Vec::new();
+    }
+
+    let message = match title {
+        Some(title) => format!(
+            "Diagram has no accTitle; its title '{}' could be used",
+            title
+        ),
+        None => "Diagram has no accTitle".to_string(),
+    };
+    vec![ValidationError::info("missing_acc_title", message)]
+}
+
 fn check_statement_participants(
     statement: &SequenceStatement,
-    participants: &HashSet<&String>,
+    is_defined: &dyn Fn(&str) -> bool,
     errors: &mut Vec<ValidationError>,
 ) {
     match statement {
         SequenceStatement::Message(msg) => {
-            if !participants.contains(&msg.from) {
+            if !is_defined(&msg.from) {
                 errors.push(ValidationError::error(
                     "undefined_participant",
                     format!("Message references undefined participant '{}'", msg.from),
                 ));
             }
-            if !participants.contains(&msg.to) {
+            if !is_defined(&msg.to) {
                 errors.push(ValidationError::error(
                     "undefined_participant",
                     format!("Message references undefined participant '{}'", msg.to),
@@ -479,41 +855,51 @@ fn check_statement_participants(
         }
         SequenceStatement::Loop(loop_stmt) => {
             for stmt in &loop_stmt.statements {
-                check_statement_participants(stmt, participants, errors);
+                check_statement_participants(stmt, is_defined, errors);
             }
         }
         SequenceStatement::Alt(alt) => {
             for stmt in &alt.statements {
-                check_statement_participants(stmt, participants, errors);
+                check_statement_participants(stmt, is_defined, errors);
             }
             if let Some(else_branch) = &alt.else_branch {
                 for stmt in &else_branch.statements {
-                    check_statement_participants(stmt, participants, errors);
+                    check_statement_participants(stmt, is_defined, errors);
                 }
             }
         }
         SequenceStatement::Opt(opt) => {
             for stmt in &opt.statements {
-                check_statement_participants(stmt, participants, errors);
+                check_statement_participants(stmt, is_defined, errors);
+            }
+        }
+        SequenceStatement::Break(block) => {
+            for stmt in &block.statements {
+                check_statement_participants(stmt, is_defined, errors);
             }
         }
         SequenceStatement::Par(par) => {
             for branch in &par.branches {
                 for stmt in &branch.statements {
-                    check_statement_participants(stmt, participants, errors);
+                    check_statement_participants(stmt, is_defined, errors);
                 }
             }
         }
         SequenceStatement::Critical(critical) => {
             for stmt in &critical.statements {
-                check_statement_participants(stmt, participants, errors);
+                check_statement_participants(stmt, is_defined, errors);
             }
             for option in &critical.options {
                 for stmt in &option.statements {
-                    check_statement_participants(stmt, participants, errors);
+                    check_statement_participants(stmt, is_defined, errors);
                 }
             }
         }
+        SequenceStatement::Rect { statements, .. } => {
+            for stmt in statements {
+                check_statement_participants(stmt, is_defined, errors);
+            }
+        }
         _ => {} // Other statement types
     }
 }
@@ -524,6 +910,12 @@ fn check_activation_balance(
     errors: &mut Vec<ValidationError>,
 ) {
     match statement {
+        // `+` and `-` on a message activate and deactivate like the statements
+        SequenceStatement::Message(message) => {
+            if let Some(implied) = message.implied_activation() {
+                check_activation_balance(&implied, activation_stack, errors);
+            }
+        }
         SequenceStatement::Activate(participant) => {
             activation_stack.push(participant.clone());
         }
@@ -561,6 +953,12 @@ fn check_activation_balance(
                 }
             }
         }
+        SequenceStatement::Break(Break { statements, .. })
+        | SequenceStatement::Rect { statements, .. } => {
+            for stmt in statements {
+                check_activation_balance(stmt, activation_stack, errors);
+            }
+        }
         _ => {} // Other statement types
     }
 }
@@ -574,6 +972,11 @@ impl DiagramValidator for SequenceValidator {
 
         errors.extend(self.validate_participant_references(diagram));
         errors.extend(self.validate_activation_blocks(diagram));
+        errors.extend(self.validate_reserved_words(diagram));
+        errors.extend(validate_accessibility_title(
+            &diagram.title,
+            &diagram.accessibility,
+        ));
 
         // Filter by severity and ignored rules
         errors.retain(|error| {
@@ -684,7 +1087,11 @@ impl ClassValidator {
                     ClassMember::Method(method) => (&method.name, "method"),
                 };
 
-                let member_key = format!("{}:{}", member_name, member_type_str);
+                let member_key = format!(
+                    "{}:{}",
+                    self.config.text_normalizer.normalize(member_name),
+                    member_type_str
+                );
                 if member_names.contains(&member_key) {
                     errors.push(ValidationError::error(
                         "duplicate_member",
@@ -736,6 +1143,10 @@ impl DiagramValidator for ClassValidator {
         errors.extend(self.validate_inheritance_cycles(diagram));
         errors.extend(self.validate_relationship_references(diagram));
         errors.extend(self.validate_duplicate_members(diagram));
+        errors.extend(validate_accessibility_title(
+            &diagram.title,
+            &diagram.accessibility,
+        ));
 
         // Filter by severity and ignored rules
         errors.retain(|error| {
@@ -777,8 +1188,8 @@ impl StateValidator {
     fn validate_start_state(&self, diagram: &StateDiagram) -> Vec<ValidationError> {
         let mut errors = Vec::new();
         let has_start_state = diagram
-            .states
-            .values()
+            .all_states()
+            .into_iter()
             .any(|state| matches!(state.state_type, StateType::Start));
 
         if !has_start_state {
@@ -796,11 +1207,11 @@ impl StateValidator {
         let mut reachable_states = HashSet::new();
 
         // Find start states
-        let start_states: Vec<_> = diagram
-            .states
+        let states = diagram.all_states();
+        let start_states: Vec<_> = states
             .iter()
-            .filter(|(_, state)| matches!(state.state_type, StateType::Start))
-            .map(|(id, _)| id.clone())
+            .filter(|state| matches!(state.state_type, StateType::Start))
+            .map(|state| state.id.clone())
             .collect();
 
         // DFS to find all reachable states
@@ -809,7 +1220,7 @@ impl StateValidator {
         }
 
         // Check for unreachable states
-        for state_id in diagram.states.keys() {
+        for state_id in states.iter().map(|state| &state.id) {
             if !reachable_states.contains(state_id) && !start_states.contains(state_id) {
                 errors.push(ValidationError::warning(
                     "unreachable_state",
@@ -824,8 +1235,8 @@ impl StateValidator {
     fn validate_transition_references(&self, diagram: &StateDiagram) -> Vec<ValidationError> {
         let mut errors = Vec::new();
 
-        for transition in &diagram.transitions {
-            if !diagram.states.contains_key(&transition.from) {
+        for transition in diagram.all_transitions() {
+            if diagram.state(&transition.from).is_none() {
                 errors.push(ValidationError::error(
                     "undefined_state_reference",
                     format!(
@@ -834,7 +1245,7 @@ impl StateValidator {
                     ),
                 ));
             }
-            if !diagram.states.contains_key(&transition.to) {
+            if diagram.state(&transition.to).is_none() {
                 errors.push(ValidationError::error(
                     "undefined_state_reference",
                     format!("Transition references undefined state '{}'", transition.to),
@@ -848,10 +1259,11 @@ impl StateValidator {
     fn validate_end_state_transitions(&self, diagram: &StateDiagram) -> Vec<ValidationError> {
         let mut errors = Vec::new();
 
-        for (state_id, state) in &diagram.states {
+        for state in diagram.all_states() {
+            let state_id = &state.id;
             if matches!(state.state_type, StateType::End) {
                 // Check if this end state has any outgoing transitions
-                for transition in &diagram.transitions {
+                for transition in diagram.all_transitions() {
                     if transition.from == *state_id {
                         errors.push(ValidationError::warning(
                             "end_state_with_outgoing_transition",
@@ -864,6 +1276,18 @@ impl StateValidator {
 
         errors
     }
+
+    /// Classes applied to states but never defined, and classDefs never
+    /// applied
+    fn validate_style_classes(&self, diagram: &StateDiagram) -> Vec<ValidationError> {
+        let applied: BTreeSet<&str> = diagram
+            .all_states()
+            .into_iter()
+            .flat_map(|state| state.classes.iter().map(String::as_str))
+            .collect();
+        let defined: BTreeSet<&str> = diagram.class_defs.keys().map(String::as_str).collect();
+        class_reference_errors("State", &applied, &defined)
+    }
 }
 
 fn mark_reachable_states(diagram: &StateDiagram, state_id: &str, reachable: &mut HashSet<String>) {
@@ -874,7 +1298,7 @@ fn mark_reachable_states(diagram: &StateDiagram, state_id: &str, reachable: &mut
     reachable.insert(state_id.to_string());
 
     // Find all transitions from this state
-    for transition in &diagram.transitions {
+    for transition in diagram.all_transitions() {
         if transition.from == state_id {
             mark_reachable_states(diagram, &transition.to, reachable);
         }
@@ -891,7 +1315,13 @@ impl DiagramValidator for StateValidator {
         errors.extend(self.validate_start_state(diagram));
         errors.extend(self.validate_unreachable_states(diagram));
         errors.extend(self.validate_transition_references(diagram));
+        errors.extend(validate_accessibility_title(
+            &diagram.title,
+            &diagram.accessibility,
+        ));
         errors.extend(self.validate_end_state_transitions(diagram));
+        errors.extend(self.validate_style_classes(diagram));
+        errors.extend(duplicate_class_def_errors(&diagram.class_defs));
 
         // Filter by severity and ignored rules
         errors.retain(|error| {
@@ -913,6 +1343,926 @@ impl Default for StateValidator {
     }
 }
 
+/// Block diagram validator
+#[derive(Debug)]
+pub struct BlockValidator {
+    config: ValidationConfig,
+}
+
+impl BlockValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Dotted paths such as `outer.inner` must resolve to a nested block; plain
+    /// identifiers declare a block implicitly, as in Mermaid
+    fn validate_connection_endpoints(&self, diagram: &BlockDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for connection in &diagram.connections {
+            for endpoint in [&connection.from, &connection.to] {
+                if endpoint.contains('.') && diagram.resolve_block(endpoint).is_none() {
+                    errors.push(ValidationError::error(
+                        "undefined_block_reference",
+                        format!("Connection references undefined block '{}'", endpoint),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+impl DiagramValidator for BlockValidator {
+    type Diagram = BlockDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_connection_endpoints(diagram);
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for BlockValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// XY chart validator
+#[derive(Debug)]
+pub struct XyChartValidator {
+    config: ValidationConfig,
+}
+
+impl XyChartValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Series may be sparse, so a length that differs from the categorical
+    /// x-axis is only a warning
+    fn validate_series_lengths(&self, diagram: &XyChartDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let label_count = diagram.x_axis.labels.len();
+        if label_count == 0 {
+            return errors;
+        }
+
+        for (index, series) in diagram.data_series.iter().enumerate() {
+            let name = series
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", index + 1));
+            if series.data.len() < label_count {
+                errors.push(ValidationError::warning(
+                    "sparse_series",
+                    format!(
+                        "Series '{}' has {} data points for {} x-axis labels; missing points are treated as gaps",
+                        name,
+                        series.data.len(),
+                        label_count
+                    ),
+                ));
+            } else if series.data.len() > label_count {
+                errors.push(ValidationError::warning(
+                    "series_length_mismatch",
+                    format!(
+                        "Series '{}' has {} data points but only {} x-axis labels",
+                        name,
+                        series.data.len(),
+                        label_count
+                    ),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Values outside an explicit y-axis range are drawn off the chart
+    fn validate_value_ranges(&self, diagram: &XyChartDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let Some((start, end)) = diagram.y_axis.range else {
+            return errors;
+        };
+        let (low, high) = (start.min(end), start.max(end));
+
+        for (index, series) in diagram.data_series.iter().enumerate() {
+            let name = series
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", index + 1));
+            for (position, value) in series.data.iter().enumerate() {
+                let Some(value) = value.filter(|value| *value < low || *value > high) else {
+                    continue;
+                };
+                let label = diagram
+                    .x_axis
+                    .labels
+                    .get(position)
+                    .cloned()
+                    .unwrap_or_else(|| (position + 1).to_string());
+                errors.push(ValidationError::with_location(
+                    "xy_value_out_of_range",
+                    format!(
+                        "Series '{}' has value {} at '{}' outside the y-axis range {} --> {}",
+                        name, value, label, start, end
+                    ),
+                    Severity::Warning,
+                    Location::with_element(0, 0, name.clone()),
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+impl DiagramValidator for XyChartValidator {
+    type Diagram = XyChartDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_series_lengths(diagram);
+        errors.extend(self.validate_value_ranges(diagram));
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for XyChartValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gantt chart validator
+#[derive(Debug)]
+pub struct GanttValidator {
+    config: ValidationConfig,
+}
+
+impl GanttValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Mermaid ignores a `displayMode` it does not know and draws the
+    /// default layout
+    fn validate_display_mode(&self, diagram: &GanttDiagram) -> Vec<ValidationError> {
+        match &diagram.display_mode {
+            Some(mode) if !gantt_keywords::DISPLAY_MODES.contains(&mode.as_str()) => {
+                vec![ValidationError::with_location(
+                    "unknown_gantt_display_mode",
+                    format!(
+                        "Unknown display mode '{}'; expected one of: {}",
+                        mode,
+                        gantt_keywords::DISPLAY_MODES.join(", ")
+                    ),
+                    Severity::Warning,
+                    Location::with_element(0, 0, mode.clone()),
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl DiagramValidator for GanttValidator {
+    type Diagram = GanttDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_display_mode(diagram);
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for GanttValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Timeline validator
+#[derive(Debug)]
+pub struct TimelineValidator {
+    config: ValidationConfig,
+}
+
+impl TimelineValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Dated periods should appear in chronological order and only once;
+    /// free-text periods are only checked for duplicates
+    fn validate_chronology(&self, diagram: &TimelineDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let mut seen: Vec<&TimelinePeriod<'_>> = Vec::new();
+        let periods = diagram.periods();
+
+        for period in &periods {
+            let ordinal = period.key.ordinal();
+            let duplicate = seen.iter().find(|previous| match ordinal {
+                Some(ordinal) => previous.key.ordinal() == Some(ordinal),
+                None => previous.label == period.label,
+            });
+
+            if let Some(previous) = duplicate {
+                errors.push(ValidationError::with_location(
+                    "duplicate_timeline_period",
+                    format!(
+                        "Period '{}' in section '{}' repeats '{}' from section '{}'",
+                        period.label, period.section, previous.label, previous.section
+                    ),
+                    Severity::Warning,
+                    Location::with_element(0, 0, period.label.to_string()),
+                ));
+            } else if let Some(latest) = ordinal.and_then(|ordinal| {
+                seen.iter()
+                    .filter(|previous| {
+                        previous
+                            .key
+                            .ordinal()
+                            .is_some_and(|previous| previous > ordinal)
+                    })
+                    .max_by(|a, b| a.key.ordinal().partial_cmp(&b.key.ordinal()).unwrap())
+            }) {
+                errors.push(ValidationError::with_location(
+                    "timeline_period_out_of_order",
+                    format!(
+                        "Period '{}' comes after the later period '{}'",
+                        period.label, latest.label
+                    ),
+                    Severity::Warning,
+                    Location::with_element(0, 0, period.label.to_string()),
+                ));
+            }
+            seen.push(period);
+        }
+
+        errors
+    }
+}
+
+impl DiagramValidator for TimelineValidator {
+    type Diagram = TimelineDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_chronology(diagram);
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for TimelineValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Quadrant chart validator
+#[derive(Debug)]
+pub struct QuadrantValidator {
+    config: ValidationConfig,
+}
+
+impl QuadrantValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// A class defined more than once only takes effect through its merged styles
+    fn validate_duplicate_class_defs(&self, diagram: &QuadrantDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+
+        for class_def in &diagram.styles {
+            if !seen.insert(&class_def.name) {
+                errors.push(duplicate_class_def_error(&class_def.name));
+            }
+        }
+
+        errors
+    }
+
+    /// Classes applied to points but never defined, and classDefs never applied
+    fn validate_class_references(&self, diagram: &QuadrantDiagram) -> Vec<ValidationError> {
+        let applied: BTreeSet<&str> = diagram
+            .points
+            .iter()
+            .filter_map(|point| point.class.as_deref())
+            .collect();
+        let defined: BTreeSet<&str> = diagram.styles.iter().map(|c| c.name.as_str()).collect();
+        class_reference_errors("Point", &applied, &defined)
+    }
+
+    /// Points are plotted on a unit square, so coordinates outside `[0, 1]`
+    /// fall off the chart
+    fn validate_point_ranges(&self, diagram: &QuadrantDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        for point in &diagram.points {
+            for (axis, value) in [("x", point.x), ("y", point.y)] {
+                if !(0.0..=1.0).contains(&value) {
+                    errors.push(ValidationError::with_location(
+                        "quadrant_point_out_of_range",
+                        format!(
+                            "Point '{}' has {} = {}, outside the range [0, 1]",
+                            point.name, axis, value
+                        ),
+                        Severity::Warning,
+                        Location::with_element(0, 0, point.name.clone()),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+impl DiagramValidator for QuadrantValidator {
+    type Diagram = QuadrantDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_duplicate_class_defs(diagram);
+        errors.extend(self.validate_class_references(diagram));
+        errors.extend(self.validate_point_ranges(diagram));
+        errors.extend(validate_accessibility_title(
+            &diagram.title,
+            &diagram.accessibility,
+        ));
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for QuadrantValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Radar chart validator
+#[derive(Debug)]
+pub struct RadarValidator {
+    config: ValidationConfig,
+}
+
+impl RadarValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Values outside the configured `min`/`max` scale are clipped or drawn
+    /// past the outer ring
+    fn validate_value_ranges(&self, diagram: &RadarDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let RadarConfig {
+            scale_min,
+            scale_max,
+            ..
+        } = diagram.config;
+
+        for dataset in &diagram.datasets {
+            for (index, value) in dataset.values.iter().enumerate() {
+                if *value < scale_min || *value > scale_max {
+                    let axis = diagram
+                        .axes
+                        .get(index)
+                        .cloned()
+                        .unwrap_or_else(|| (index + 1).to_string());
+                    errors.push(ValidationError::with_location(
+                        "radar_value_out_of_range",
+                        format!(
+                            "Dataset '{}' has value {} on axis '{}' outside the scale {} to {}",
+                            dataset.name, value, axis, scale_min, scale_max
+                        ),
+                        Severity::Warning,
+                        Location::with_element(0, 0, dataset.name.clone()),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+impl DiagramValidator for RadarValidator {
+    type Diagram = RadarDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_value_ranges(diagram);
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for RadarValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Entity relationship diagram validator
+#[derive(Debug)]
+pub struct ErValidator {
+    config: ValidationConfig,
+}
+
+impl ErValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Classes applied to entities but never defined, and classDefs never
+    /// applied
+    fn validate_style_classes(&self, diagram: &ErDiagram) -> Vec<ValidationError> {
+        let applied: BTreeSet<&str> = diagram
+            .entities
+            .values()
+            .filter_map(|entity| entity.css_class.as_deref())
+            .collect();
+        let defined: BTreeSet<&str> = diagram.class_defs.keys().map(String::as_str).collect();
+        class_reference_errors("Entity", &applied, &defined)
+    }
+
+    /// `style` statements whose entity does not exist
+    fn validate_style_targets(&self, diagram: &ErDiagram) -> Vec<ValidationError> {
+        diagram
+            .styles
+            .iter()
+            .map(|style| style_target_label(&style.target))
+            .filter(|id| !diagram.entities.contains_key(id))
+            .map(|id| {
+                ValidationError::with_location(
+                    "orphaned_style",
+                    format!("Style targets nonexistent entity '{}'", id),
+                    Severity::Warning,
+                    Location::with_element(0, 0, id),
+                )
+            })
+            .collect()
+    }
+}
+
+impl DiagramValidator for ErValidator {
+    type Diagram = ErDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_style_classes(diagram);
+        errors.extend(duplicate_class_def_errors(&diagram.class_defs));
+        errors.extend(self.validate_style_targets(diagram));
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for ErValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Architecture diagram validator
+#[derive(Debug)]
+pub struct ArchitectureValidator {
+    config: ValidationConfig,
+}
+
+impl ArchitectureValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Edges drawn with a legacy arrow spelling, such as `-->` for `->`
+    fn validate_arrow_spellings(&self, diagram: &ArchitectureDiagram) -> Vec<ValidationError> {
+        diagram
+            .edges
+            .iter()
+            .filter_map(|edge| {
+                let label = legacy_arrow_label(edge)?;
+                let canonical = ArrowKind::Architecture(edge.edge_type.clone()).canonical();
+                Some(ValidationError::with_location(
+                    "deprecated_arrow_spelling",
+                    format!("Edge '{}' uses a legacy arrow; use '{}'", label, canonical),
+                    Severity::Warning,
+                    Location::with_element(0, 0, label),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl DiagramValidator for ArchitectureValidator {
+    type Diagram = ArchitectureDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_arrow_spellings(diagram);
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for ArchitectureValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The element id of a `deprecated_arrow_spelling` diagnostic, as in
+/// `web --> db`; `None` if the edge uses the canonical arrow
+pub(crate) fn legacy_arrow_label(edge: &ArchEdge) -> Option<String> {
+    let arrow = edge.legacy_arrow.as_ref()?;
+    Some(format!("{} {} {}", edge.from.id, arrow, edge.to.id))
+}
+
+/// Optional requirement fields that a team's process makes mandatory
+///
+/// Mermaid accepts requirements without an `id`, `risk` or `verifymethod`;
+/// the default profile follows it, and [`RequirementProfile::strict`] demands
+/// all three.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequirementProfile {
+    pub require_id: bool,
+    pub require_risk: bool,
+    pub require_verify_method: bool,
+}
+
+impl RequirementProfile {
+    pub fn strict() -> Self {
+        Self {
+            require_id: true,
+            require_risk: true,
+            require_verify_method: true,
+        }
+    }
+}
+
+/// Requirement diagram validator
+///
+/// ```rust
+/// use mermaid_parser::common::validation::RequirementValidator;
+///
+/// let input = "requirementDiagram\n    requirement login {\n        id: 1\n    }\n    app - satisfies -> login";
+/// let errors = RequirementValidator::new().validate_source(input)?;
+/// assert_eq!(errors[0].rule, "undefined_requirement_reference");
+/// assert_eq!(errors[0].location.as_ref().unwrap().line, 5);
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+#[derive(Debug)]
+pub struct RequirementValidator {
+    config: ValidationConfig,
+}
+
+impl RequirementValidator {
+    pub fn new() -> Self {
+        Self {
+            config: ValidationConfig::default(),
+        }
+    }
+
+    pub fn with_config(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Relationships may connect requirements and elements in either role
+    fn validate_relationship_references(
+        &self,
+        diagram: &RequirementDiagram,
+    ) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let normalizer = self.config.text_normalizer;
+        let declared: HashSet<_> = diagram
+            .requirements
+            .keys()
+            .chain(diagram.elements.keys())
+            .map(|name| normalizer.normalize(name))
+            .collect();
+
+        for relationship in &diagram.relationships {
+            for (role, name) in [
+                ("source", &relationship.source),
+                ("target", &relationship.target),
+            ] {
+                if !declared.contains(&*normalizer.normalize(name)) {
+                    errors.push(ValidationError::with_location(
+                        "undefined_requirement_reference",
+                        format!(
+                            "Relationship {} '{}' is not a declared requirement or element",
+                            role, name
+                        ),
+                        Severity::Error,
+                        Location::with_element(0, 0, name.clone()),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    fn validate_unique_ids(&self, diagram: &RequirementDiagram) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let mut requirements: Vec<_> = diagram.requirements.values().collect();
+        requirements.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut seen: HashMap<&str, &str> = HashMap::new();
+
+        for requirement in requirements {
+            if requirement.id.is_empty() {
+                continue;
+            }
+            if let Some(first) = seen.insert(&requirement.id, &requirement.name) {
+                errors.push(ValidationError::with_location(
+                    "duplicate_requirement_id",
+                    format!(
+                        "Requirement '{}' reuses id '{}' from '{}'",
+                        requirement.name, requirement.id, first
+                    ),
+                    Severity::Error,
+                    Location::with_element(0, 0, requirement.name.clone()),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    fn validate_profile_fields(&self, diagram: &RequirementDiagram) -> Vec<ValidationError> {
+        let profile = self.config.requirement_profile;
+        let mut errors = Vec::new();
+        let mut requirements: Vec<_> = diagram.requirements.values().collect();
+        requirements.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for requirement in requirements {
+            let missing = [
+                (profile.require_id && requirement.id.is_empty(), "id"),
+                (profile.require_risk && requirement.risk.is_none(), "risk"),
+                (
+                    profile.require_verify_method && requirement.verify_method.is_none(),
+                    "verifymethod",
+                ),
+            ];
+            for (is_missing, field) in missing {
+                if is_missing {
+                    errors.push(ValidationError::with_location(
+                        "missing_requirement_field",
+                        format!("Requirement '{}' has no {}", requirement.name, field),
+                        Severity::Warning,
+                        Location::with_element(0, 0, requirement.name.clone()),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Parse and validate requirement diagram source, placing each diagnostic
+    /// on the line that declares the requirement or relationship it is about
+    ///
+    /// Requirements and elements declared twice are also reported here, since
+    /// the parsed diagram keeps only the last declaration of a name.
+    pub fn validate_source(&self, input: &str) -> crate::error::Result<Vec<ValidationError>> {
+        let diagram = crate::parsers::requirement::parse(input)?;
+        let mut errors = self.validate(&diagram).err().unwrap_or_default();
+
+        let lines: Vec<&str> = input.lines().collect();
+        let column = |line: &str| line.len() - line.trim_start().len() + 1;
+        let declared_name = |line: &str| -> Option<String> {
+            let mut words = line.split_whitespace();
+            let keyword = words.next()?;
+            let name = words.next()?.trim_end_matches('{');
+            let is_declaration = matches!(
+                keyword,
+                "requirement"
+                    | "functionalRequirement"
+                    | "performanceRequirement"
+                    | "interfaceRequirement"
+                    | "physicalRequirement"
+                    | "designConstraint"
+                    | "element"
+            );
+            (is_declaration && line.trim_end().ends_with('{')).then(|| name.to_string())
+        };
+        let is_relationship_with = |line: &str, name: &str| {
+            (line.contains("->") || line.contains("<-"))
+                && line
+                    .split(|c: char| c.is_whitespace() || c == '-' || c == '<' || c == '>')
+                    .any(|word| word == name)
+        };
+
+        for error in &mut errors {
+            let Some(location) = error.location.as_mut() else {
+                continue;
+            };
+            let Some(name) = location.element_id.clone() else {
+                continue;
+            };
+            let found = lines.iter().copied().enumerate().find(|&(_, line)| {
+                if error.rule == "undefined_requirement_reference" {
+                    is_relationship_with(line, &name)
+                } else {
+                    declared_name(line).as_deref() == Some(name.as_str())
+                }
+            });
+            if let Some((index, line)) = found {
+                location.line = index + 1;
+                location.column = column(line);
+            }
+        }
+
+        let mut declared = HashSet::new();
+        for (index, line) in lines.iter().copied().enumerate() {
+            if let Some(name) = declared_name(line) {
+                if !declared.insert(self.config.text_normalizer.normalize(&name).into_owned()) {
+                    errors.push(ValidationError::with_location(
+                        "duplicate_requirement_name",
+                        format!("'{}' is declared more than once", name),
+                        Severity::Error,
+                        Location::with_element(index + 1, column(line), name),
+                    ));
+                }
+            }
+        }
+
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+        Ok(errors)
+    }
+}
+
+impl DiagramValidator for RequirementValidator {
+    type Diagram = RequirementDiagram;
+    type Error = ValidationError;
+
+    fn validate(&self, diagram: &Self::Diagram) -> Result<(), Vec<Self::Error>> {
+        let mut errors = self.validate_relationship_references(diagram);
+        errors.extend(self.validate_unique_ids(diagram));
+        errors.extend(self.validate_profile_fields(diagram));
+
+        // Filter by severity and ignored rules
+        errors.retain(|error| {
+            error.severity >= self.config.min_severity
+                && !self.config.ignore_rules.contains(error.rule)
+        });
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Default for RequirementValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -956,9 +2306,10 @@ mod tests {
 
     #[test]
     fn test_flowchart_validator_isolated_nodes() {
+        use crate::common::ordered_map::OrderedMap;
         use std::collections::HashMap;
 
-        let mut nodes = HashMap::new();
+        let mut nodes = OrderedMap::new();
         nodes.insert(
             "A".to_string(),
             FlowNode {
@@ -988,7 +2339,7 @@ mod tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             }],
@@ -1005,9 +2356,10 @@ mod tests {
 
     #[test]
     fn test_flowchart_validator_undefined_reference() {
+        use crate::common::ordered_map::OrderedMap;
         use std::collections::HashMap;
 
-        let mut nodes = HashMap::new();
+        let mut nodes = OrderedMap::new();
         nodes.insert(
             "A".to_string(),
             FlowNode {
@@ -1027,7 +2379,7 @@ mod tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "UNDEFINED".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             }],
@@ -1062,7 +2414,9 @@ mod tests {
                 to: "Bob".to_string(), // Undefined participant
                 text: "Hello".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             })],
+            boxes: vec![],
             autonumber: None,
         };
 
@@ -1094,7 +2448,7 @@ mod tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             direction: FlowDirection::TD,
-            nodes: HashMap::new(),
+            nodes: crate::common::ordered_map::OrderedMap::new(),
             edges: vec![],
             subgraphs: vec![],
             styles: vec![],
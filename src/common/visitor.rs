@@ -269,7 +269,7 @@ impl AstVisitor for ComplexityAnalyzer {
 
     fn visit_state(&mut self, diagram: &StateDiagram) -> Self::Result {
         self.count_node();
-        for _transition in &diagram.transitions {
+        for _transition in diagram.all_transitions() {
             self.count_connection();
         }
     }
@@ -428,6 +428,13 @@ impl ComplexityAnalyzer {
                 }
                 self.exit_scope();
             }
+            SequenceStatement::Break(block) => {
+                self.enter_scope();
+                for stmt in &block.statements {
+                    self.visit_sequence_statement(stmt);
+                }
+                self.exit_scope();
+            }
             SequenceStatement::Par(par) => {
                 self.enter_scope();
                 for branch in &par.branches {
@@ -449,6 +456,13 @@ impl ComplexityAnalyzer {
                 }
                 self.exit_scope();
             }
+            SequenceStatement::Rect { statements, .. } => {
+                self.enter_scope();
+                for stmt in statements {
+                    self.visit_sequence_statement(stmt);
+                }
+                self.exit_scope();
+            }
             _ => {} // Other statement types
         }
     }
@@ -547,13 +561,13 @@ impl AstVisitor for ReferenceValidator {
     }
 
     fn visit_state(&mut self, diagram: &StateDiagram) -> Self::Result {
-        // Define all state IDs
-        for id in diagram.states.keys() {
-            self.define_id(id);
+        // Define all state IDs, nested ones included
+        for state in diagram.all_states() {
+            self.define_id(&state.id);
         }
 
         // Check transition references
-        for transition in &diagram.transitions {
+        for transition in diagram.all_transitions() {
             self.reference_id(&transition.from);
             self.reference_id(&transition.to);
         }
@@ -765,8 +779,8 @@ impl AstVisitor for NodeCounter {
     }
 
     fn visit_state(&mut self, diagram: &StateDiagram) -> Self::Result {
-        self.nodes += diagram.states.len();
-        self.edges += diagram.transitions.len();
+        self.nodes += diagram.all_states().len();
+        self.edges += diagram.all_transitions().len();
     }
 
     fn visit_flowchart(&mut self, diagram: &FlowchartDiagram) -> Self::Result {
@@ -911,10 +925,12 @@ mod tests {
                 SankeyNode {
                     id: "A".to_string(),
                     name: "Node A".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "B".to_string(),
                     name: "Node B".to_string(),
+                    metadata: Default::default(),
                 },
             ],
             links: vec![SankeyLink {
@@ -939,6 +955,7 @@ mod tests {
             nodes: vec![SankeyNode {
                 id: "A".to_string(),
                 name: "Node A".to_string(),
+                metadata: Default::default(),
             }],
             links: vec![],
         });
@@ -956,19 +973,19 @@ mod tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             direction: crate::common::ast::FlowDirection::TD,
-            nodes: std::collections::HashMap::new(),
+            nodes: crate::common::ordered_map::OrderedMap::new(),
             edges: vec![
                 FlowEdge {
                     from: "A".to_string(),
                     to: "B".to_string(),
-                    edge_type: crate::common::ast::EdgeType::Arrow,
+                    edge_type: crate::common::ast::EdgeType::ARROW,
                     label: None,
                     min_length: None,
                 },
                 FlowEdge {
                     from: "B".to_string(),
                     to: "C".to_string(),
-                    edge_type: crate::common::ast::EdgeType::Arrow,
+                    edge_type: crate::common::ast::EdgeType::ARROW,
                     label: None,
                     min_length: None,
                 },
@@ -988,9 +1005,7 @@ mod tests {
 
     #[test]
     fn test_reference_validator_with_valid_flowchart() {
-        use std::collections::HashMap;
-
-        let mut nodes = HashMap::new();
+        let mut nodes = crate::common::ordered_map::OrderedMap::new();
         nodes.insert(
             "A".to_string(),
             FlowNode {
@@ -1020,7 +1035,7 @@ mod tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: crate::common::ast::EdgeType::Arrow,
+                edge_type: crate::common::ast::EdgeType::ARROW,
                 label: None,
                 min_length: None,
             }],
@@ -1039,9 +1054,7 @@ mod tests {
 
     #[test]
     fn test_reference_validator_with_invalid_flowchart() {
-        use std::collections::HashMap;
-
-        let mut nodes = HashMap::new();
+        let mut nodes = crate::common::ordered_map::OrderedMap::new();
         nodes.insert(
             "A".to_string(),
             FlowNode {
@@ -1061,7 +1074,7 @@ mod tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "UNDEFINED".to_string(), // This should trigger an error
-                edge_type: crate::common::ast::EdgeType::Arrow,
+                edge_type: crate::common::ast::EdgeType::ARROW,
                 label: None,
                 min_length: None,
             }],
@@ -1086,14 +1099,17 @@ mod tests {
                 SankeyNode {
                     id: "A".to_string(),
                     name: "Node A".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "B".to_string(),
                     name: "Node B".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "C".to_string(),
                     name: "Node C".to_string(),
+                    metadata: Default::default(),
                 },
             ],
             links: vec![
@@ -1149,7 +1165,7 @@ mod tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             direction: crate::common::ast::FlowDirection::TD,
-            nodes: std::collections::HashMap::new(),
+            nodes: crate::common::ordered_map::OrderedMap::new(),
             edges: vec![],
             subgraphs: vec![],
             styles: vec![],
@@ -1162,6 +1178,7 @@ mod tests {
             accessibility: AccessibilityInfo::default(),
             participants: vec![],
             statements: vec![],
+            boxes: vec![],
             autonumber: None,
         });
 
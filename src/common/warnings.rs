@@ -0,0 +1,158 @@
+//! Warnings for input a parser accepts but does not keep
+//!
+//! Several parsers accept statements that have no place in their AST, such
+//! as `classDef` in requirement diagrams or `click` in flowcharts and Gantt
+//! charts, and drop them without an error. [`parse_with_warnings`], exposed as
+//! [`crate::parse_diagram_with_warnings`], parses like
+//! [`crate::parse_diagram`] and also returns a warning for each such
+//! statement, so the loss is visible when the diagram is parsed instead of
+//! when it is printed back.
+//!
+//! Warnings are [`ValidationError`]s with the [`IGNORED_STATEMENT_RULE`]
+//! rule, [`Severity::Warning`] and the 1-based line of the statement,
+//! counted from the start of the input including any frontmatter. The
+//! statements concerned are listed in [`IGNORED_STATEMENTS`]; an entry is
+//! removed when its parser starts keeping the statement.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::warnings::{parse_with_warnings, IGNORED_STATEMENT_RULE};
+//!
+//! let input = "requirementDiagram\n    element e1 {\n        type: simulation\n    }\n    classDef important stroke:#f00";
+//! let outcome = parse_with_warnings(input)?;
+//! assert_eq!(outcome.warnings.len(), 1);
+//! assert_eq!(outcome.warnings[0].rule, IGNORED_STATEMENT_RULE);
+//! assert_eq!(outcome.warnings[0].location.as_ref().unwrap().line, 5);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::DiagramType;
+use crate::common::frontmatter;
+use crate::common::parsing::lines;
+use crate::common::validation::{Location, Severity, ValidationError};
+use crate::error::Result;
+
+/// Rule of the warnings reported by [`parse_with_warnings`]
+pub const IGNORED_STATEMENT_RULE: &str = "ignored_statement";
+
+/// A statement a parser accepts and drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IgnoredStatement {
+    /// Diagram type, as detected from the header (`er`, `flowchart`, ...)
+    pub diagram: &'static str,
+    /// First word of the statement
+    pub keyword: &'static str,
+    /// What is lost, for the warning message
+    pub dropped: &'static str,
+}
+
+/// Statements that are parsed without being kept in the AST
+pub const IGNORED_STATEMENTS: &[IgnoredStatement] = &[
+    IgnoredStatement {
+        diagram: "block",
+        keyword: "style",
+        dropped: "block styles",
+    },
+    IgnoredStatement {
+        diagram: "block",
+        keyword: "classDef",
+        dropped: "block class definitions",
+    },
+    IgnoredStatement {
+        diagram: "flowchart",
+        keyword: "click",
+        dropped: "click handlers and their tooltips",
+    },
+    IgnoredStatement {
+        diagram: "flowchart",
+        keyword: "linkStyle",
+        dropped: "edge styles",
+    },
+    IgnoredStatement {
+        diagram: "gantt",
+        keyword: "click",
+        dropped: "task click handlers",
+    },
+    IgnoredStatement {
+        diagram: "kanban",
+        keyword: "style",
+        dropped: "card styles",
+    },
+    IgnoredStatement {
+        diagram: "requirement",
+        keyword: "classDef",
+        dropped: "requirement class definitions",
+    },
+    IgnoredStatement {
+        diagram: "requirement",
+        keyword: "class",
+        dropped: "requirement class assignments",
+    },
+    IgnoredStatement {
+        diagram: "requirement",
+        keyword: "direction",
+        dropped: "the layout direction",
+    },
+];
+
+/// A parsed diagram and what the parser dropped from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOutcome {
+    pub diagram: DiagramType,
+    pub warnings: Vec<ValidationError>,
+}
+
+/// Parse `input` as [`crate::parse_diagram`] does, with a warning for each
+/// statement the parser drops
+pub fn parse_with_warnings(input: &str) -> Result<ParseOutcome> {
+    let diagram = crate::parse_diagram(input)?;
+    Ok(ParseOutcome {
+        diagram,
+        warnings: ignored_statements(input),
+    })
+}
+
+/// Warnings for the [`IGNORED_STATEMENTS`] of `input`'s diagram type
+fn ignored_statements(input: &str) -> Vec<ValidationError> {
+    let (_, source) = frontmatter::split(input);
+    let Ok(diagram) = crate::detect_diagram_type(source) else {
+        return Vec::new();
+    };
+    let ignored: Vec<&IgnoredStatement> = IGNORED_STATEMENTS
+        .iter()
+        .filter(|statement| statement.diagram == diagram)
+        .collect();
+    if ignored.is_empty() {
+        return Vec::new();
+    }
+
+    // The diagram source is the end of the input
+    let first_line = input[..input.len() - source.len()].matches('\n').count() + 1;
+    let mut warnings = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        if lines::should_skip_line(line) {
+            continue;
+        }
+        let mut offset = 0;
+        for statement in lines::split_statements(line, ';') {
+            let statement = statement.trim();
+            let start = offset + line[offset..].find(statement).unwrap_or(0);
+            offset = start + statement.len();
+            let keyword = statement.split_whitespace().next().unwrap_or_default();
+            if let Some(ignored) = ignored.iter().find(|ignored| ignored.keyword == keyword) {
+                let column = line[..start].chars().count() + 1;
+                warnings.push(ValidationError::with_location(
+                    IGNORED_STATEMENT_RULE,
+                    format!(
+                        "`{}` statement ignored: {} cannot be represented in {} diagrams",
+                        keyword, ignored.dropped, diagram
+                    ),
+                    Severity::Warning,
+                    Location::new(first_line + index, column),
+                ));
+            }
+        }
+    }
+    warnings
+}
@@ -0,0 +1,256 @@
+//! Analysis across a set of related diagrams
+//!
+//! Documentation often splits a process over several flowcharts, with a
+//! subroutine node (`[[Name]]`) standing for another flowchart. A
+//! [`Workspace`] collects diagrams under a name (usually the file they came
+//! from) and [`Workspace::call_graph`] resolves each subroutine node to the
+//! diagram whose title matches its label, reporting the ones that resolve to
+//! nothing.
+//!
+//! Titles are read from [`Document::metadata`], so a flowchart, which has no
+//! `title` statement, is titled by its frontmatter `title:`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::common::workspace::Workspace;
+//!
+//! let mut workspace = Workspace::new();
+//! workspace.add_source(
+//!     "checkout.mmd",
+//!     "---\ntitle: Checkout\n---\nflowchart TD\n    A[Cart] --> B[[Payment]]\n    B --> C[[Shipping]]",
+//! )?;
+//! workspace.add_source("payment.mmd", "---\ntitle: Payment\n---\nflowchart TD\n    A[Card] --> B[Charge]")?;
+//!
+//! let graph = workspace.call_graph();
+//! assert_eq!(graph.callees("checkout.mmd"), vec!["payment.mmd"]);
+//! assert_eq!(graph.dangling().count(), 1);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{DiagramType, NodeShape};
+use crate::common::frontmatter::Document;
+use crate::common::text_normalizer::TextNormalizer;
+use crate::common::validation::{Location, Severity, ValidationError};
+use crate::error::Result;
+
+/// A diagram in a [`Workspace`] and the name it was added under
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceDiagram {
+    pub name: String,
+    pub document: Document,
+}
+
+impl WorkspaceDiagram {
+    /// The diagram's title, or else its frontmatter `title:`
+    pub fn title(&self) -> Option<String> {
+        self.document.metadata().title
+    }
+}
+
+/// A collection of diagrams that may refer to each other
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub diagrams: Vec<WorkspaceDiagram>,
+    /// How [`Workspace::resolve`] compares a reference with titles and
+    /// names; ignores case by default
+    pub normalizer: TextNormalizer,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            diagrams: Vec::new(),
+            normalizer: TextNormalizer::IGNORE_CASE,
+        }
+    }
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a parsed document
+    pub fn add(&mut self, name: impl Into<String>, document: Document) {
+        self.diagrams.push(WorkspaceDiagram {
+            name: name.into(),
+            document,
+        });
+    }
+
+    /// Parse `input` with its frontmatter and add the resulting document; an
+    /// error names the diagram it came from
+    pub fn add_source(&mut self, name: impl Into<String>, input: &str) -> Result<()> {
+        let name = name.into();
+        let document =
+            crate::parse_document(input).map_err(|error| error.in_source(name.as_str()))?;
+        self.add(name, document);
+        Ok(())
+    }
+
+    /// Find the diagram a reference names: a diagram whose title matches,
+    /// otherwise one whose name matches. Surrounding whitespace is ignored,
+    /// and the differences [`Workspace::normalizer`] folds.
+    pub fn resolve(&self, reference: &str) -> Option<&WorkspaceDiagram> {
+        let reference = self.normalizer.normalize(reference.trim());
+        let matches = |candidate: &str| self.normalizer.normalize(candidate.trim()) == reference;
+
+        self.diagrams
+            .iter()
+            .find(|entry| entry.title().is_some_and(|title| matches(&title)))
+            .or_else(|| self.diagrams.iter().find(|entry| matches(&entry.name)))
+    }
+
+    /// Resolve every flowchart subroutine node in the workspace
+    pub fn call_graph(&self) -> CallGraph {
+        let mut calls = Vec::new();
+
+        for entry in &self.diagrams {
+            let DiagramType::Flowchart(flowchart) = &entry.document.diagram else {
+                continue;
+            };
+
+            let mut subroutines: Vec<_> = flowchart
+                .nodes
+                .values()
+                .filter(|node| node.shape == NodeShape::Subroutine)
+                .collect();
+            subroutines.sort_by(|a, b| a.id.cmp(&b.id));
+
+            for node in subroutines {
+                let reference = node.text.clone().unwrap_or_else(|| node.id.clone());
+                let callee = self.resolve(&reference).map(|target| target.name.clone());
+                calls.push(SubroutineCall {
+                    caller: entry.name.clone(),
+                    node_id: node.id.clone(),
+                    reference,
+                    callee,
+                });
+            }
+        }
+
+        CallGraph { calls }
+    }
+}
+
+/// A subroutine node and the diagram it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubroutineCall {
+    /// Name of the diagram containing the node
+    pub caller: String,
+    pub node_id: String,
+    /// The node label used to look up the callee
+    pub reference: String,
+    /// Name of the referenced diagram, `None` if nothing matched
+    pub callee: Option<String>,
+}
+
+/// Calls between the diagrams of a [`Workspace`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallGraph {
+    pub calls: Vec<SubroutineCall>,
+}
+
+impl CallGraph {
+    /// Calls whose reference matched no diagram
+    pub fn dangling(&self) -> impl Iterator<Item = &SubroutineCall> {
+        self.calls.iter().filter(|call| call.callee.is_none())
+    }
+
+    /// Names of the diagrams called from `name`, in first-call order
+    pub fn callees(&self, name: &str) -> Vec<&str> {
+        let mut callees = Vec::new();
+        for call in self.calls.iter().filter(|call| call.caller == name) {
+            if let Some(callee) = call.callee.as_deref() {
+                if !callees.contains(&callee) {
+                    callees.push(callee);
+                }
+            }
+        }
+        callees
+    }
+
+    /// Names of the diagrams that call `name`, in first-call order
+    pub fn callers(&self, name: &str) -> Vec<&str> {
+        let mut callers = Vec::new();
+        for call in &self.calls {
+            if call.callee.as_deref() == Some(name) && !callers.contains(&call.caller.as_str()) {
+                callers.push(call.caller.as_str());
+            }
+        }
+        callers
+    }
+
+    /// Dangling calls as `dangling_subroutine` warnings located at the node,
+    /// in the calling diagram
+    pub fn diagnostics(&self) -> Vec<ValidationError> {
+        self.dangling()
+            .map(|call| {
+                ValidationError::with_location(
+                    "dangling_subroutine",
+                    format!(
+                        "Subroutine '{}' in '{}' does not match any diagram title",
+                        call.reference, call.caller
+                    ),
+                    Severity::Warning,
+                    Location::with_element(0, 0, call.node_id.clone()),
+                )
+                .in_source(call.caller.clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(sources: &[(&str, &str)]) -> Workspace {
+        let mut workspace = Workspace::new();
+        for (name, source) in sources {
+            workspace.add_source(*name, source).unwrap();
+        }
+        workspace
+    }
+
+    #[test]
+    fn test_resolve_prefers_title_over_name() {
+        let workspace = workspace(&[
+            (
+                "billing",
+                "---\ntitle: Invoices\n---\nflowchart TD\n    A --> B",
+            ),
+            ("invoices", "flowchart TD\n    A --> B"),
+        ]);
+
+        assert_eq!(workspace.resolve(" invoices ").unwrap().name, "billing");
+        assert_eq!(workspace.resolve("Billing").unwrap().name, "billing");
+        assert!(workspace.resolve("shipping").is_none());
+    }
+
+    #[test]
+    fn test_resolve_uses_normalizer() {
+        let mut workspace = workspace(&[(
+            "auth.mmd",
+            "---\ntitle: Auth-Service\n---\nflowchart TD\n    A --> B",
+        )]);
+        assert!(workspace.resolve("auth service").is_none());
+
+        workspace.normalizer = TextNormalizer::LENIENT;
+        assert_eq!(workspace.resolve("auth service").unwrap().name, "auth.mmd");
+    }
+
+    #[test]
+    fn test_bare_subroutine_uses_node_id() {
+        let workspace = workspace(&[
+            ("main", "flowchart TD\n    A[Start] --> Setup[[]]"),
+            ("setup", "---\ntitle: Setup\n---\nflowchart TD\n    A --> B"),
+        ]);
+
+        let graph = workspace.call_graph();
+        assert_eq!(graph.calls.len(), 1);
+        assert_eq!(graph.calls[0].reference, "Setup");
+        assert_eq!(graph.calls[0].callee.as_deref(), Some("setup"));
+    }
+}
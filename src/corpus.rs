@@ -0,0 +1,374 @@
+//! Formatting a corpus of diagrams to canonical form
+//!
+//! [`format_directory`] parses every diagram under a path and prints it back
+//! with the given [`PrintOptions`], so a repository's diagrams can be kept in
+//! one style, or checked for it in CI with [`DryRun::Yes`]. Diagram files
+//! (see [`crate::fs::DIAGRAM_EXTENSIONS`]) are formatted whole, keeping their
+//! leading `//` metadata lines; in Markdown files every fenced block whose
+//! info string is `mermaid` is formatted and the surrounding text is left
+//! alone.
+//!
+//! A diagram is only rewritten when its formatted text parses back to the
+//! same AST, frontmatter included. Diagrams that fail to parse or to round
+//! trip are reported and left as they are, and the rest of the file is still
+//! formatted.
+//!
+//! [`unrecognized_headers`] reads the same files and reports the diagram
+//! headers the parser does not recognize, as described in
+//! [`crate::common::unrecognized`].
+//!
+//! Available with the `fs` feature, which is enabled by default.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use mermaid_parser::corpus::{self, DryRun};
+//! use mermaid_parser::PrintOptions;
+//!
+//! let report = corpus::format_directory("docs", &PrintOptions::default(), DryRun::Yes)?;
+//! for file in report.changed() {
+//!     println!("would reformat {}", file.path.display());
+//! }
+//! for file in report.failed() {
+//!     println!("{}: {:?}", file.path.display(), file.failures);
+//! }
+//! if !report.is_clean() {
+//!     std::process::exit(1);
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::frontmatter::parse_document;
+use crate::common::pretty_print::{MermaidPrinter, PrintOptions};
+use crate::common::unrecognized::UnrecognizedHeaders;
+use crate::error::{ParseError, Result};
+use crate::fs::{self, METADATA_PREFIX};
+use std::path::{Path, PathBuf};
+
+/// Extensions recognized as Markdown files that may embed diagrams
+pub const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+/// Info string of the fenced code blocks that hold diagrams
+pub const MARKDOWN_FENCE_LANGUAGE: &str = "mermaid";
+
+/// Whether [`format_directory`] only reports what it would change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRun {
+    /// Report changes without writing files, as for `fmt --check`
+    Yes,
+    /// Rewrite the files that change
+    No,
+}
+
+/// Why a diagram or file was left unformatted
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatFailure {
+    /// The file could not be read or written
+    Io(String),
+    /// The diagram starting at `line` (1-based, within the file) did not
+    /// parse
+    Parse { line: usize, error: ParseError },
+    /// The formatted diagram starting at `line` parses to a different AST
+    /// than the original, so formatting would change its meaning
+    RoundTrip { line: usize },
+}
+
+/// Outcome for one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /// Diagrams found in the file: one for a diagram file, one per `mermaid`
+    /// block for a Markdown file
+    pub diagrams: usize,
+    /// Whether formatting changes the file. Unless the run was a dry run, the
+    /// file has been rewritten.
+    pub changed: bool,
+    pub failures: Vec<FormatFailure>,
+}
+
+/// Outcome of a [`format_directory`] run
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FormatReport {
+    /// Diagram files, and Markdown files with at least one diagram, sorted
+    /// by path
+    pub files: Vec<FileReport>,
+}
+
+impl FormatReport {
+    /// Files that formatting changes
+    pub fn changed(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| file.changed)
+    }
+
+    /// Files with at least one diagram that could not be formatted
+    pub fn failed(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| !file.failures.is_empty())
+    }
+
+    /// Whether every diagram is already formatted and round trips, as a
+    /// `fmt --check` step requires
+    pub fn is_clean(&self) -> bool {
+        self.files
+            .iter()
+            .all(|file| !file.changed && file.failures.is_empty())
+    }
+}
+
+/// Whether a path has one of the [`MARKDOWN_EXTENSIONS`]
+pub fn is_markdown_file(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|ext| MARKDOWN_EXTENSIONS.iter().any(|known| ext == *known))
+}
+
+/// Format every diagram file and every Markdown-embedded diagram under
+/// `path`, which may also name a single file
+///
+/// Fails only when `path` cannot be listed; problems with individual files
+/// are recorded in the report.
+pub fn format_directory(
+    path: impl AsRef<Path>,
+    options: &PrintOptions,
+    dry_run: DryRun,
+) -> Result<FormatReport> {
+    let mut report = FormatReport::default();
+    for file in corpus_files(path.as_ref())? {
+        let file_report = if fs::is_diagram_file(&file) {
+            format_file(file, options, dry_run, format_diagram_file)
+        } else if is_markdown_file(&file) {
+            format_file(file, options, dry_run, format_markdown)
+        } else {
+            continue;
+        };
+        if file_report.diagrams > 0 || !file_report.failures.is_empty() {
+            report.files.push(file_report);
+        }
+    }
+    Ok(report)
+}
+
+/// Tally the diagram headers the parser does not recognize, over every
+/// diagram file and Markdown-embedded diagram under `path`, which may also
+/// name a single file
+///
+/// Fails only when `path` cannot be listed; files that cannot be read are
+/// skipped.
+pub fn unrecognized_headers(path: impl AsRef<Path>) -> Result<UnrecognizedHeaders> {
+    let mut report = UnrecognizedHeaders::new();
+    for file in corpus_files(path.as_ref())? {
+        if fs::is_diagram_file(&file) {
+            if let Ok(source) = fs::read_diagram(&file) {
+                report.add(&source);
+            }
+        } else if let Ok(content) = std::fs::read_to_string(&file) {
+            let lines: Vec<&str> = content.lines().collect();
+            for source in markdown_diagrams(&lines) {
+                report.add(&source);
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// The diagram and Markdown files at or under `path`
+fn corpus_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let files = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        fs::find_files(path)?
+    };
+    Ok(files
+        .into_iter()
+        .filter(|file| file.is_file() && (fs::is_diagram_file(file) || is_markdown_file(file)))
+        .collect())
+}
+
+/// Formatted content, number of diagrams and failures of a file's content
+type Formatted = (String, usize, Vec<FormatFailure>);
+
+fn format_file(
+    path: PathBuf,
+    options: &PrintOptions,
+    dry_run: DryRun,
+    format: fn(&str, &PrintOptions) -> Formatted,
+) -> FileReport {
+    let mut report = FileReport {
+        path,
+        diagrams: 0,
+        changed: false,
+        failures: Vec::new(),
+    };
+    let content = match std::fs::read_to_string(&report.path) {
+        Ok(content) => content,
+        Err(error) => {
+            report.failures.push(FormatFailure::Io(error.to_string()));
+            return report;
+        }
+    };
+
+    let (formatted, diagrams, failures) = format(&content, options);
+    report.diagrams = diagrams;
+    report.failures = failures;
+    report.changed = formatted != content;
+    if report.changed && dry_run == DryRun::No {
+        if let Err(error) = std::fs::write(&report.path, formatted) {
+            report.failures.push(FormatFailure::Io(error.to_string()));
+        }
+    }
+    report
+}
+
+/// A diagram file: leading metadata and blank lines are kept, the rest is
+/// one diagram, and the file ends with a newline
+fn format_diagram_file(content: &str, options: &PrintOptions) -> Formatted {
+    let newline = line_ending(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let header = lines
+        .iter()
+        .take_while(|line| line.starts_with(METADATA_PREFIX) || line.trim().is_empty())
+        .count();
+    let source = lines[header..].join("\n");
+    if source.trim().is_empty() {
+        return (content.to_string(), 0, Vec::new());
+    }
+
+    match format_source(&source, header + 1, options) {
+        Ok(formatted) => {
+            let mut output: Vec<&str> = lines[..header].to_vec();
+            output.extend(formatted.lines());
+            (output.join(newline) + newline, 1, Vec::new())
+        }
+        Err(failure) => (content.to_string(), 1, vec![failure]),
+    }
+}
+
+/// A Markdown file: the contents of each `mermaid` fenced block
+fn format_markdown(content: &str, options: &PrintOptions) -> Formatted {
+    let newline = line_ending(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut diagrams = 0;
+    let mut failures = Vec::new();
+
+    let mut index = 0;
+    while index < lines.len() {
+        output.push(lines[index].to_string());
+        index += 1;
+        let Some((indent, fence)) = mermaid_fence(lines[index - 1]) else {
+            continue;
+        };
+        // An unterminated block is left as text
+        let Some(end) = (index..lines.len()).find(|&i| closes_fence(lines[i], fence)) else {
+            continue;
+        };
+
+        diagrams += 1;
+        let source = lines[index..end]
+            .iter()
+            .map(|line| strip_indent(line, indent))
+            .collect::<Vec<_>>()
+            .join("\n");
+        match format_source(&source, index + 1, options) {
+            Ok(formatted) => {
+                let prefix = &lines[index - 1][..indent];
+                output.extend(formatted.lines().map(|line| {
+                    if line.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{}{}", prefix, line)
+                    }
+                }));
+            }
+            Err(failure) => {
+                failures.push(failure);
+                output.extend(lines[index..end].iter().map(|line| line.to_string()));
+            }
+        }
+        index = end;
+    }
+
+    let mut text = output.join(newline);
+    if content.ends_with('\n') {
+        text.push_str(newline);
+    }
+    (text, diagrams, failures)
+}
+
+/// The source of each `mermaid` block in a Markdown file's lines
+fn markdown_diagrams(lines: &[&str]) -> Vec<String> {
+    let mut diagrams = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        index += 1;
+        let Some((indent, fence)) = mermaid_fence(lines[index - 1]) else {
+            continue;
+        };
+        let Some(end) = (index..lines.len()).find(|&i| closes_fence(lines[i], fence)) else {
+            continue;
+        };
+        diagrams.push(
+            lines[index..end]
+                .iter()
+                .map(|line| strip_indent(line, indent))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        index = end;
+    }
+    diagrams
+}
+
+/// Parse and reprint `source`, which starts at `line` of its file,
+/// checking that the result parses back to the same document
+fn format_source(
+    source: &str,
+    line: usize,
+    options: &PrintOptions,
+) -> std::result::Result<String, FormatFailure> {
+    let document = parse_document(source).map_err(|error| FormatFailure::Parse { line, error })?;
+    let formatted = document.to_mermaid_pretty(options);
+    match parse_document(&formatted) {
+        Ok(reparsed) if reparsed == document => Ok(formatted),
+        _ => Err(FormatFailure::RoundTrip { line }),
+    }
+}
+
+/// `\r\n` when the content uses it, `\n` otherwise
+fn line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// The indentation and fence marker (three or more backticks or tildes) of
+/// a line opening a `mermaid` block
+fn mermaid_fence(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    let indent = line.len() - trimmed.len();
+    let marker_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let marker_len = trimmed.len() - trimmed.trim_start_matches(marker_char).len();
+    if marker_len < 3 {
+        return None;
+    }
+    let info = trimmed[marker_len..].split_whitespace().next()?;
+    (info == MARKDOWN_FENCE_LANGUAGE).then(|| (indent, &trimmed[..marker_len]))
+}
+
+/// Whether `line` closes a block opened with `fence`
+fn closes_fence(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    let marker_char = fence.as_bytes()[0] as char;
+    trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == marker_char)
+}
+
+/// Remove up to `indent` leading spaces or tabs
+fn strip_indent(line: &str, indent: usize) -> &str {
+    let strip = line
+        .bytes()
+        .take(indent)
+        .take_while(|byte| *byte == b' ' || *byte == b'\t')
+        .count();
+    &line[strip..]
+}
@@ -0,0 +1,115 @@
+//! Decoding of diagram files that are not plain UTF-8
+//!
+//! Some Windows tools export text as UTF-16 with a byte order mark, which
+//! read as UTF-8 is a string of NUL characters between the letters. [`decode`]
+//! looks for a byte order mark, transcodes UTF-16 input of either byte order
+//! and drops a UTF-8 byte order mark. Input without one must be UTF-8.
+//!
+//! [`crate::parse_diagram_bytes`] decodes its input this way.
+#![cfg_attr(
+    feature = "fs",
+    doc = "[`crate::fs::read_diagram`] decodes the files it reads the same way."
+)]
+//!
+//! Available with the `encoding` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::encoding::{self, Encoding};
+//!
+//! let mut bytes = vec![0xFF, 0xFE];
+//! bytes.extend("pie\n\"A\" : 1".encode_utf16().flat_map(u16::to_le_bytes));
+//!
+//! assert_eq!(encoding::detect(&bytes), Encoding::Utf16Le);
+//! assert_eq!(encoding::decode(&bytes)?, "pie\n\"A\" : 1");
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::error::{ParseError, Result};
+use std::borrow::Cow;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// The encoding of a diagram file, as told by its byte order mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, with or without a byte order mark
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// The name used in [`ParseError::InvalidEncoding`]
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => UTF8_BOM,
+            Encoding::Utf16Le => UTF16_LE_BOM,
+            Encoding::Utf16Be => UTF16_BE_BOM,
+        }
+    }
+}
+
+/// The encoding `bytes` declare with a byte order mark, UTF-8 if they have
+/// none
+pub fn detect(bytes: &[u8]) -> Encoding {
+    if bytes.starts_with(UTF16_LE_BOM) {
+        Encoding::Utf16Le
+    } else if bytes.starts_with(UTF16_BE_BOM) {
+        Encoding::Utf16Be
+    } else {
+        Encoding::Utf8
+    }
+}
+
+/// `bytes` as text, without their byte order mark
+///
+/// UTF-8 input without a byte order mark is borrowed.
+///
+/// # Errors
+///
+/// Returns [`ParseError::InvalidEncoding`] with the byte offset of the first
+/// sequence that is not valid in the detected encoding, such as an unpaired
+/// UTF-16 surrogate or a UTF-16 file with an odd number of bytes.
+pub fn decode(bytes: &[u8]) -> Result<Cow<'_, str>> {
+    let encoding = detect(bytes);
+    let body = bytes.strip_prefix(encoding.bom()).unwrap_or(bytes);
+    let invalid = |offset: usize| ParseError::InvalidEncoding {
+        encoding: encoding.name().to_string(),
+        offset: offset + bytes.len() - body.len(),
+    };
+
+    let utf16 = |to_u16: fn([u8; 2]) -> u16| {
+        if body.len() % 2 != 0 {
+            return Err(invalid(body.len() - 1));
+        }
+        let code_units = body.chunks_exact(2).map(|pair| to_u16([pair[0], pair[1]]));
+        let mut text = String::with_capacity(body.len() / 2);
+        let mut offset = 0;
+        for decoded in char::decode_utf16(code_units) {
+            let c = decoded.map_err(|_| invalid(offset))?;
+            offset += c.len_utf16() * 2;
+            text.push(c);
+        }
+        Ok(Cow::Owned(text))
+    };
+
+    match encoding {
+        Encoding::Utf8 => std::str::from_utf8(body)
+            .map(Cow::Borrowed)
+            .map_err(|error| invalid(error.valid_up_to())),
+        Encoding::Utf16Le => utf16(u16::from_le_bytes),
+        Encoding::Utf16Be => utf16(u16::from_be_bytes),
+    }
+}
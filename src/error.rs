@@ -9,6 +9,33 @@ pub struct Location {
     pub column: usize,
 }
 
+/// What a chumsky parser recognized before it failed
+///
+/// Editors can use the tokens for highlighting and completion while the
+/// document does not parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialParse {
+    /// Tokens lexed before the failure, rendered with `Debug`; every token of
+    /// the input when the lexer succeeded and the token parser failed
+    pub tokens: Vec<String>,
+    pub failure: FailurePoint,
+}
+
+/// Where a chumsky parser stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailurePoint {
+    /// The lexer rejected the character at this byte offset; `line` and
+    /// `column` are 1-based
+    Lexer {
+        offset: usize,
+        line: usize,
+        column: usize,
+    },
+    /// The token parser rejected the token at this index of
+    /// [`PartialParse::tokens`], or ran out of tokens when it equals their count
+    Parser { token: usize },
+}
+
 /// Result type alias for parser operations
 pub type Result<T> = std::result::Result<T, ParseError>;
 
@@ -29,6 +56,8 @@ pub enum ParseError {
         message: String,
         line: usize,
         column: usize,
+        /// What was lexed before the failure, for parsers that record it
+        partial: Option<Box<PartialParse>>,
     },
 
     /// Syntax parsing error
@@ -38,6 +67,9 @@ pub enum ParseError {
         found: String,
         line: usize,
         column: usize,
+        /// The token stream and the rejected token, for parsers that record
+        /// them
+        partial: Option<Box<PartialParse>>,
     },
 
     /// Enhanced syntax parsing error with context and suggestions
@@ -55,6 +87,57 @@ pub enum ParseError {
 
     /// I/O error when reading input
     IoError(String),
+
+    /// Input bytes that are not valid text in the `encoding` they were read
+    /// as, starting at byte `offset`
+    InvalidEncoding { encoding: String, offset: usize },
+
+    /// `error` in the diagram from the file or other source called `name`
+    InSource {
+        name: String,
+        error: Box<ParseError>,
+    },
+}
+
+impl ParseError {
+    /// The tokens and failure position, if the parser recorded them
+    pub fn partial(&self) -> Option<&PartialParse> {
+        match self {
+            ParseError::LexError { partial, .. } | ParseError::SyntaxError { partial, .. } => {
+                partial.as_deref()
+            }
+            ParseError::InSource { error, .. } => error.partial(),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, looking through [`ParseError::InSource`]
+    pub fn root(&self) -> &ParseError {
+        match self {
+            ParseError::InSource { error, .. } => error.root(),
+            other => other,
+        }
+    }
+
+    /// The file or other source the error comes from, if it was named
+    pub fn source_name(&self) -> Option<&str> {
+        match self {
+            ParseError::InSource { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// This error, attributed to the source called `name`. An error that
+    /// already names its source keeps it.
+    pub fn in_source(self, name: impl Into<String>) -> ParseError {
+        match self {
+            ParseError::InSource { .. } => self,
+            error => ParseError::InSource {
+                name: name.into(),
+                error: Box::new(error),
+            },
+        }
+    }
 }
 
 impl fmt::Display for ParseError {
@@ -73,6 +156,7 @@ impl fmt::Display for ParseError {
                 message,
                 line,
                 column,
+                ..
             } => {
                 write!(
                     f,
@@ -86,6 +170,7 @@ impl fmt::Display for ParseError {
                 found,
                 line,
                 column,
+                ..
             } => {
                 write!(
                     f,
@@ -136,6 +221,10 @@ impl fmt::Display for ParseError {
             ParseError::IoError(message) => {
                 write!(f, "I/O error: {}", message)
             }
+            ParseError::InvalidEncoding { encoding, offset } => {
+                write!(f, "Input is not valid {} at byte {}", encoding, offset)
+            }
+            ParseError::InSource { name, error } => write!(f, "{}: {}", name, error),
         }
     }
 }
@@ -209,6 +298,7 @@ mod tests {
             found: "symbol".to_string(),
             line: 5,
             column: 10,
+            partial: None,
         };
         assert!(error.to_string().contains("line 5, column 10"));
         assert!(error.to_string().contains("identifier, number"));
@@ -233,6 +323,7 @@ mod tests {
             found: "=>".to_string(),
             line: 2,
             column: 7,
+            partial: None,
         };
 
         let error_msg = error.to_string();
@@ -0,0 +1,311 @@
+//! Finding and loading diagram files
+//!
+//! Helpers for tools that work on a directory of diagrams rather than a
+//! single string: [`glob`] finds files by pattern, [`read_diagram`] loads one
+//! with its metadata comments stripped, and [`DiagramFile`] pairs the source
+//! with the diagram kind its header declares, without a full parse.
+//!
+//! Patterns and paths go through [`std::path`], so `/` and the platform
+//! separator both work, and file names that are not valid UTF-8 are matched
+//! on their lossy form instead of causing an error.
+//!
+//! Available with the `fs` feature, which is enabled by default.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use mermaid_parser::fs::{self, DiagramFile};
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! for file in fs::load_all("docs/**/*.mmd")? {
+//!     if file.kind == Some(DiagramKind::Flowchart) {
+//!         let diagram = file.parse()?;
+//!         println!("{}: {:?}", file.path.display(), diagram.title());
+//!     }
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::DiagramType;
+use crate::error::{ParseError, Result};
+use crate::stable::DiagramKind;
+use std::path::{Component, Path, PathBuf};
+
+/// Extensions recognized as Mermaid diagram files
+pub const DIAGRAM_EXTENSIONS: &[&str] = &["mermaid", "mmd"];
+
+/// Prefix of metadata lines (`// Source: ...`) that are not part of the diagram
+pub const METADATA_PREFIX: &str = "//";
+
+/// A diagram file and the kind its header declares
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramFile {
+    pub path: PathBuf,
+    /// The file content without metadata lines
+    pub source: String,
+    /// `None` if the file holds no diagram
+    pub kind: Option<DiagramKind>,
+}
+
+impl DiagramFile {
+    /// Read a file and detect its diagram kind
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = read_diagram(path)?;
+        let kind = detect_kind(&source).ok();
+        Ok(DiagramFile {
+            path: path.to_path_buf(),
+            source,
+            kind,
+        })
+    }
+
+    /// Parse the diagram; an error names the file it came from
+    pub fn parse(&self) -> Result<DiagramType> {
+        crate::parse_diagram_named(&self.source, &self.path.display().to_string())
+    }
+}
+
+/// Remove metadata lines and surrounding whitespace
+pub fn strip_metadata(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| !line.starts_with(METADATA_PREFIX))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Read a diagram file without its metadata lines
+///
+/// With the `encoding` feature, UTF-16 files with a byte order mark are
+/// transcoded as described in [`crate::encoding`].
+pub fn read_diagram(path: impl AsRef<Path>) -> Result<String> {
+    let path = path.as_ref();
+    #[cfg(feature = "encoding")]
+    let content = std::fs::read(path)
+        .map_err(|e| io_error(path, e))
+        .and_then(|bytes| {
+            crate::encoding::decode(&bytes)
+                .map(|content| content.into_owned())
+                .map_err(|error| error.in_source(path.display().to_string()))
+        })?;
+    #[cfg(not(feature = "encoding"))]
+    let content = std::fs::read_to_string(path).map_err(|e| io_error(path, e))?;
+    Ok(strip_metadata(&content))
+}
+
+/// Whether a path has one of the [`DIAGRAM_EXTENSIONS`]
+pub fn is_diagram_file(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .is_some_and(|ext| DIAGRAM_EXTENSIONS.iter().any(|known| ext == *known))
+}
+
+/// Every diagram file under `dir`, recursively, sorted by path
+pub fn find_diagrams(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let mut files = find_files(dir.as_ref())?;
+    files.retain(|path| is_diagram_file(path));
+    Ok(files)
+}
+
+/// Every file and directory under `dir`, recursively and skipping hidden
+/// ones, sorted by path
+pub(crate) fn find_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk(dir, &["**".to_string(), "*".to_string()], &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// The files directly in `dir` with the extension `extension`, skipping
+/// hidden ones, sorted by path
+pub(crate) fn files_with_extension(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk(dir, &["*".to_string()], &mut files)?;
+    files.retain(|path| path.is_file() && path.extension().is_some_and(|ext| ext == extension));
+    files.sort();
+    Ok(files)
+}
+
+/// Load every file matching a [`glob`] pattern
+pub fn load_all(pattern: impl AsRef<Path>) -> Result<Vec<DiagramFile>> {
+    glob(pattern)?.iter().map(DiagramFile::load).collect()
+}
+
+/// Files and directories matching `pattern`, sorted by path
+///
+/// A component may use `*` (any characters) and `?` (one character); a
+/// component of exactly `**` matches any number of directories. Names
+/// starting with `.` are only matched by a component that also does. The
+/// leading components without wildcards are the directory the search starts
+/// from; if there are none, it starts from the current directory.
+pub fn glob(pattern: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let mut root = PathBuf::new();
+    let mut parts: Vec<String> = Vec::new();
+    for component in pattern.as_ref().components() {
+        let text = component.as_os_str().to_string_lossy();
+        let literal = matches!(component, Component::Normal(_)) && !has_wildcard(&text);
+        if parts.is_empty() && (literal || !matches!(component, Component::Normal(_))) {
+            root.push(component);
+        } else {
+            parts.push(text.into_owned());
+        }
+    }
+
+    let mut matches = Vec::new();
+    if parts.is_empty() {
+        if root.exists() {
+            matches.push(root);
+        }
+    } else {
+        walk(&root, &parts, &mut matches)?;
+    }
+    matches.sort();
+    matches.dedup();
+    Ok(matches)
+}
+
+/// Whether `name` matches a single pattern component
+pub fn matches_component(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star;
+                    n = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The kind of diagram [`crate::parse_diagram`] would produce for `input`, judged
+/// from its header alone
+fn detect_kind(input: &str) -> Result<DiagramKind> {
+    let (_, body) = crate::common::frontmatter::split(input);
+    Ok(match crate::detect_diagram_type(body)? {
+        "sankey" => DiagramKind::Sankey,
+        "architecture" => DiagramKind::Architecture,
+        "block" => DiagramKind::Block,
+        "c4" => DiagramKind::C4,
+        "class" => DiagramKind::Class,
+        "er" => DiagramKind::Er,
+        "flowchart" => DiagramKind::Flowchart,
+        "gantt" => DiagramKind::Gantt,
+        "git" => DiagramKind::Git,
+        "kanban" => DiagramKind::Kanban,
+        "mindmap" => DiagramKind::Mindmap,
+        "packet" => DiagramKind::Packet,
+        "pie" => DiagramKind::Pie,
+        "quadrant" => DiagramKind::Quadrant,
+        "radar" => DiagramKind::Radar,
+        "requirement" => DiagramKind::Requirement,
+        "timeline" => DiagramKind::Timeline,
+        "treemap" => DiagramKind::Treemap,
+        "journey" => DiagramKind::Journey,
+        "sequence" => DiagramKind::Sequence,
+        "state" => DiagramKind::State,
+        "xychart" => DiagramKind::XyChart,
+        _ => DiagramKind::Misc,
+    })
+}
+
+fn has_wildcard(text: &str) -> bool {
+    text.contains(['*', '?'])
+}
+
+fn walk(dir: &Path, parts: &[String], matches: &mut Vec<PathBuf>) -> Result<()> {
+    let Some((first, rest)) = parts.split_first() else {
+        return Ok(());
+    };
+
+    if first == "**" {
+        walk(dir, rest, matches)?;
+        for (path, name, is_dir) in entries(dir)? {
+            if is_dir && !name.starts_with('.') {
+                walk(&path, parts, matches)?;
+            }
+        }
+        return Ok(());
+    }
+
+    for (path, name, is_dir) in entries(dir)? {
+        if name.starts_with('.') && !first.starts_with('.') {
+            continue;
+        }
+        if !matches_component(first, &name) {
+            continue;
+        }
+        if rest.is_empty() {
+            matches.push(path);
+        } else if is_dir {
+            walk(&path, rest, matches)?;
+        }
+    }
+    Ok(())
+}
+
+/// Entries of `dir` as (path, lossy name, is a directory). Symbolic links
+/// are not followed into, so `**` cannot loop.
+fn entries(dir: &Path) -> Result<Vec<(PathBuf, String, bool)>> {
+    let read_from = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let entries = std::fs::read_dir(read_from).map_err(|e| io_error(read_from, e))?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            (dir.join(entry.file_name()), name, is_dir)
+        })
+        .collect())
+}
+
+/// An I/O failure on `path`, as a [`ParseError::IoError`] naming the path
+pub(crate) fn io_error(path: &Path, error: std::io::Error) -> ParseError {
+    ParseError::IoError(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_component() {
+        assert!(matches_component("*.mermaid", "flow_1.mermaid"));
+        assert!(matches_component("flow_?.mmd", "flow_1.mmd"));
+        assert!(matches_component("*a*b*", "xxaxxbxx"));
+        assert!(!matches_component("*.mermaid", "flow.mmd"));
+        assert!(!matches_component("flow_?.mmd", "flow_10.mmd"));
+        assert!(matches_component("*", ""));
+    }
+
+    #[test]
+    fn test_strip_metadata() {
+        let content = "// Source: x\n// Type: pie\n\npie\n    \"A\" : 1\n";
+        assert_eq!(strip_metadata(content), "pie\n    \"A\" : 1");
+    }
+}
@@ -0,0 +1,492 @@
+//! Machine-readable description of the grammar subset this crate accepts
+//!
+//! Each [`Grammar`] is a list of named [`Production`]s built from
+//! [`Expr`] combinators (sequence, choice, optional, repetition) that map
+//! directly onto railroad diagram elements. [`Grammar::to_ebnf`] renders the
+//! W3C EBNF notation accepted by common railroad generators.
+//!
+//! Grammars are kept honest in two ways: vocabularies the parsers already
+//! enumerate (such as [`crate::parsers::flowchart::syntax_summary`]) are
+//! spliced in rather than copied, and [`Grammar::example`] derives a sentence
+//! from the grammar that the parser is tested to accept.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::grammar;
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! let flowchart = grammar::for_kind(DiagramKind::Flowchart).unwrap();
+//! println!("{}", flowchart.to_ebnf());
+//! assert!(mermaid_parser::parse_diagram(&flowchart.example()).is_ok());
+//! ```
+
+use crate::parsers::flowchart;
+use crate::stable::DiagramKind;
+
+/// A grammar expression; each variant corresponds to a railroad element
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Text that must appear exactly
+    Literal(&'static str),
+    /// A lexical class such as a number or free text, with a sample value
+    Token {
+        name: &'static str,
+        example: &'static str,
+    },
+    /// Reference to another production
+    Rule(&'static str),
+    /// End of a line
+    NewLine,
+    Sequence(Vec<Expr>),
+    Choice(Vec<Expr>),
+    Optional(Box<Expr>),
+    ZeroOrMore(Box<Expr>),
+    OneOrMore(Box<Expr>),
+}
+
+/// A named production, `name ::= expr`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Production {
+    pub name: &'static str,
+    pub expr: Expr,
+}
+
+/// The accepted grammar of one diagram type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grammar {
+    pub kind: DiagramKind,
+    /// Productions in definition order; the first one is the start symbol
+    pub productions: Vec<Production>,
+}
+
+impl Grammar {
+    /// Look up a production by name
+    pub fn production(&self, name: &str) -> Option<&Production> {
+        self.productions.iter().find(|p| p.name == name)
+    }
+
+    /// Rule references that have no production
+    pub fn undefined_rules(&self) -> Vec<&'static str> {
+        let mut undefined = Vec::new();
+        for production in &self.productions {
+            collect_rules(&production.expr, &mut |name| {
+                if self.production(name).is_none() && !undefined.contains(&name) {
+                    undefined.push(name);
+                }
+            });
+        }
+        undefined
+    }
+
+    /// Every literal terminal, in definition order without duplicates
+    pub fn literals(&self) -> Vec<&'static str> {
+        let mut literals = Vec::new();
+        for production in &self.productions {
+            collect_literals(&production.expr, &mut literals);
+        }
+        literals
+    }
+
+    /// Render the grammar in W3C EBNF notation
+    pub fn to_ebnf(&self) -> String {
+        let width = self
+            .productions
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0);
+
+        self.productions
+            .iter()
+            .map(|p| format!("{:width$} ::= {}\n", p.name, ebnf(&p.expr, false)))
+            .collect()
+    }
+
+    /// Derive a sentence from the start symbol, taking the first alternative
+    /// of every choice and one iteration of every optional or repeated part
+    pub fn example(&self) -> String {
+        let mut words = Vec::new();
+        if let Some(start) = self.productions.first() {
+            self.expand(&start.expr, &mut words);
+        }
+
+        let mut output = String::new();
+        for word in words {
+            if word == "\n" {
+                output.push('\n');
+            } else {
+                if !output.is_empty() && !output.ends_with('\n') {
+                    output.push(' ');
+                }
+                output.push_str(word);
+            }
+        }
+        output
+    }
+
+    fn expand<'a>(&'a self, expr: &'a Expr, words: &mut Vec<&'a str>) {
+        match expr {
+            Expr::Literal(text) => words.push(text),
+            Expr::Token { example, .. } => words.push(example),
+            Expr::Rule(name) => {
+                if let Some(production) = self.production(name) {
+                    self.expand(&production.expr, words);
+                }
+            }
+            Expr::NewLine => words.push("\n"),
+            Expr::Sequence(items) => items.iter().for_each(|item| self.expand(item, words)),
+            Expr::Choice(alternatives) => {
+                if let Some(first) = alternatives.first() {
+                    self.expand(first, words);
+                }
+            }
+            Expr::Optional(inner) | Expr::ZeroOrMore(inner) | Expr::OneOrMore(inner) => {
+                self.expand(inner, words)
+            }
+        }
+    }
+}
+
+fn collect_rules(expr: &Expr, visit: &mut impl FnMut(&'static str)) {
+    match expr {
+        Expr::Rule(name) => visit(name),
+        Expr::Sequence(items) | Expr::Choice(items) => {
+            items.iter().for_each(|item| collect_rules(item, visit))
+        }
+        Expr::Optional(inner) | Expr::ZeroOrMore(inner) | Expr::OneOrMore(inner) => {
+            collect_rules(inner, visit)
+        }
+        Expr::Literal(_) | Expr::Token { .. } | Expr::NewLine => {}
+    }
+}
+
+fn collect_literals(expr: &Expr, literals: &mut Vec<&'static str>) {
+    match expr {
+        Expr::Literal(text) => {
+            if !literals.contains(text) {
+                literals.push(text);
+            }
+        }
+        Expr::Sequence(items) | Expr::Choice(items) => items
+            .iter()
+            .for_each(|item| collect_literals(item, literals)),
+        Expr::Optional(inner) | Expr::ZeroOrMore(inner) | Expr::OneOrMore(inner) => {
+            collect_literals(inner, literals)
+        }
+        Expr::Token { .. } | Expr::Rule(_) | Expr::NewLine => {}
+    }
+}
+
+fn ebnf(expr: &Expr, nested: bool) -> String {
+    match expr {
+        Expr::Literal(text) => format!("'{}'", text),
+        Expr::Token { name, .. } => name.to_uppercase(),
+        Expr::Rule(name) => name.to_string(),
+        Expr::NewLine => "NEWLINE".to_string(),
+        Expr::Sequence(items) => {
+            let body = items
+                .iter()
+                .map(|item| ebnf(item, true))
+                .collect::<Vec<_>>()
+                .join(" ");
+            if nested && items.len() > 1 {
+                format!("( {} )", body)
+            } else {
+                body
+            }
+        }
+        Expr::Choice(alternatives) => {
+            let body = alternatives
+                .iter()
+                .map(|alternative| ebnf(alternative, false))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            if nested && alternatives.len() > 1 {
+                format!("( {} )", body)
+            } else {
+                body
+            }
+        }
+        Expr::Optional(inner) => format!("{}?", ebnf(inner, true)),
+        Expr::ZeroOrMore(inner) => format!("{}*", ebnf(inner, true)),
+        Expr::OneOrMore(inner) => format!("{}+", ebnf(inner, true)),
+    }
+}
+
+fn production(name: &'static str, expr: Expr) -> Production {
+    Production { name, expr }
+}
+
+fn seq(items: Vec<Expr>) -> Expr {
+    Expr::Sequence(items)
+}
+
+fn opt(expr: Expr) -> Expr {
+    Expr::Optional(Box::new(expr))
+}
+
+fn many(expr: Expr) -> Expr {
+    Expr::ZeroOrMore(Box::new(expr))
+}
+
+fn text(example: &'static str) -> Expr {
+    Expr::Token {
+        name: "text",
+        example,
+    }
+}
+
+fn number(example: &'static str) -> Expr {
+    Expr::Token {
+        name: "number",
+        example,
+    }
+}
+
+/// Every grammar this crate describes
+pub fn all() -> Vec<Grammar> {
+    vec![
+        flowchart_grammar(),
+        pie_grammar(),
+        sankey_grammar(),
+        journey_grammar(),
+    ]
+}
+
+/// The grammar for a diagram kind, if one is described
+pub fn for_kind(kind: DiagramKind) -> Option<Grammar> {
+    all().into_iter().find(|grammar| grammar.kind == kind)
+}
+
+fn flowchart_grammar() -> Grammar {
+    let summary = flowchart::syntax_summary();
+    let literals =
+        |words: Vec<&'static str>| Expr::Choice(words.into_iter().map(Expr::Literal).collect());
+
+    Grammar {
+        kind: DiagramKind::Flowchart,
+        productions: vec![
+            production(
+                "flowchart",
+                seq(vec![
+                    Expr::Rule("header"),
+                    Expr::NewLine,
+                    many(seq(vec![Expr::Rule("statement"), Expr::NewLine])),
+                ]),
+            ),
+            production(
+                "header",
+                seq(vec![
+                    literals(summary.headers),
+                    opt(literals(summary.directions)),
+                ]),
+            ),
+            production(
+                "statement",
+                Expr::Choice(vec![
+                    seq(vec![
+                        Expr::Rule("node"),
+                        opt(seq(vec![
+                            Expr::Rule("arrow"),
+                            opt(Expr::Rule("edge_label")),
+                            Expr::Rule("node"),
+                        ])),
+                    ]),
+                    seq(vec![
+                        literals(summary.keywords),
+                        Expr::Token {
+                            name: "id_list",
+                            example: "A,B",
+                        },
+                        Expr::Token {
+                            name: "class_list",
+                            example: "done,late",
+                        },
+                    ]),
+                ]),
+            ),
+            production(
+                "node",
+                seq(vec![
+                    Expr::Token {
+                        name: "id",
+                        example: "A",
+                    },
+                    opt(Expr::Rule("shape")),
+                    many(Expr::Token {
+                        name: "class_suffix",
+                        example: ":::done",
+                    }),
+                ]),
+            ),
+            production(
+                "shape",
+                Expr::Choice(
+                    summary
+                        .shapes
+                        .iter()
+                        .map(|shape| {
+                            seq(vec![
+                                Expr::Literal(shape.open),
+                                text("label"),
+                                Expr::Literal(shape.close),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            production(
+                "arrow",
+                literals(summary.arrows.iter().map(|arrow| arrow.token).collect()),
+            ),
+            production(
+                "edge_label",
+                seq(vec![Expr::Literal("|"), text("label"), Expr::Literal("|")]),
+            ),
+        ],
+    }
+}
+
+fn pie_grammar() -> Grammar {
+    Grammar {
+        kind: DiagramKind::Pie,
+        productions: vec![
+            production(
+                "pie",
+                seq(vec![
+                    Expr::Literal("pie"),
+                    opt(Expr::Literal("showData")),
+                    Expr::NewLine,
+                    many(seq(vec![Expr::Rule("statement"), Expr::NewLine])),
+                ]),
+            ),
+            production(
+                "statement",
+                Expr::Choice(vec![
+                    Expr::Rule("slice"),
+                    seq(vec![Expr::Literal("title"), text("Pets")]),
+                ]),
+            ),
+            production(
+                "slice",
+                seq(vec![
+                    Expr::Token {
+                        name: "string",
+                        example: "\"Dogs\"",
+                    },
+                    Expr::Literal(":"),
+                    number("42"),
+                ]),
+            ),
+        ],
+    }
+}
+
+fn sankey_grammar() -> Grammar {
+    Grammar {
+        kind: DiagramKind::Sankey,
+        productions: vec![
+            production(
+                "sankey",
+                seq(vec![
+                    Expr::Choice(vec![Expr::Literal("sankey-beta"), Expr::Literal("sankey")]),
+                    Expr::NewLine,
+                    Expr::OneOrMore(Box::new(seq(vec![Expr::Rule("row"), Expr::NewLine]))),
+                ]),
+            ),
+            production(
+                "row",
+                Expr::Choice(vec![Expr::Rule("link"), Expr::Rule("metadata")]),
+            ),
+            production(
+                "link",
+                seq(vec![
+                    text("Source"),
+                    Expr::Literal(","),
+                    text("Target"),
+                    Expr::Literal(","),
+                    number("10"),
+                    // Columns added by newer Mermaid versions are ignored
+                    many(seq(vec![Expr::Literal(","), text("extra")])),
+                ]),
+            ),
+            production(
+                "metadata",
+                seq(vec![
+                    text("Source"),
+                    Expr::OneOrMore(Box::new(seq(vec![
+                        Expr::Literal(","),
+                        text("color=#f5c542"),
+                    ]))),
+                ]),
+            ),
+        ],
+    }
+}
+
+fn journey_grammar() -> Grammar {
+    Grammar {
+        kind: DiagramKind::Journey,
+        productions: vec![
+            production(
+                "journey",
+                seq(vec![
+                    Expr::Literal("journey"),
+                    Expr::NewLine,
+                    many(seq(vec![Expr::Rule("statement"), Expr::NewLine])),
+                ]),
+            ),
+            production(
+                "statement",
+                Expr::Choice(vec![
+                    seq(vec![Expr::Literal("title"), text("My day")]),
+                    seq(vec![Expr::Literal("section"), text("Work")]),
+                    Expr::Rule("task"),
+                ]),
+            ),
+            production(
+                "task",
+                seq(vec![
+                    text("Make tea"),
+                    Expr::Literal(":"),
+                    number("5"),
+                    Expr::Literal(":"),
+                    text("Me"),
+                    many(seq(vec![Expr::Literal(","), text("Cat")])),
+                ]),
+            ),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ebnf_rendering() {
+        let grammar = Grammar {
+            kind: DiagramKind::Pie,
+            productions: vec![
+                production(
+                    "a",
+                    seq(vec![
+                        Expr::Literal("x"),
+                        opt(Expr::Choice(vec![Expr::Rule("b"), number("1")])),
+                        many(seq(vec![Expr::Literal(","), Expr::Rule("b")])),
+                    ]),
+                ),
+                production("b", Expr::NewLine),
+            ],
+        };
+
+        assert_eq!(
+            grammar.to_ebnf(),
+            "a ::= 'x' ( b | NUMBER )? ( ',' b )*\nb ::= NEWLINE\n"
+        );
+        assert_eq!(grammar.example(), "x\n,\n");
+        assert_eq!(grammar.literals(), vec!["x", ","]);
+        assert!(grammar.undefined_rules().is_empty());
+    }
+}
@@ -94,19 +94,38 @@
 //! }
 //! ```
 
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod capability;
 pub mod common;
+#[cfg(feature = "fs")]
+pub mod corpus;
+#[cfg(feature = "encoding")]
+pub mod encoding;
 pub mod error;
+#[cfg(feature = "fs")]
+pub mod fs;
+pub mod grammar;
 pub mod parsers;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod stable;
+#[cfg(feature = "fs")]
+pub mod testing;
+pub mod version;
 
-pub use common::ast::{CardinalityValue, DiagramType, KeyType};
+pub use capability::capabilities;
+pub use common::ast::{CardinalityValue, DiagramMetadata, DiagramType, InitDirective, KeyType};
+pub use common::frontmatter::{parse_document, Document};
 pub use common::metrics::{
     BasicMetrics, ComplexityMetrics, DiagramMetrics, MetricsReport, QualityMetrics, SeverityLevel,
     Suggestion, SuggestionCategory,
 };
-pub use common::pretty_print::{MermaidPrinter, PrintOptions};
+pub use common::pretty_print::{MermaidPrinter, PrintOptions, PrintPresets};
 pub use common::visitor::{
     AstVisitor, AstVisitorMut, ComplexityAnalyzer, NodeCounter, ReferenceValidator, TitleSetter,
 };
+pub use common::warnings::ParseOutcome;
 pub use error::{ParseError, Result};
 
 /// Parse a Mermaid diagram from text input
@@ -174,13 +193,28 @@ pub use error::{ParseError, Result};
 /// Returns [`ParseError`] variants for different error conditions:
 ///
 /// - [`ParseError::EmptyInput`] - No valid diagram content found
-/// - [`ParseError::SyntaxError`] - Invalid syntax according to grammar rules
+/// - [`ParseError::SyntaxError`] - Invalid syntax according to grammar rules;
+///   [`ParseError::partial`] gives the tokens recognized before it
 /// - [`ParseError::SemanticError`] - Valid syntax but semantically incorrect
+/// - [`ParseError::InSource`] - An error from [`parse_diagram_named`], with the
+///   name of its source
 /// - See [`ParseError`] for complete error type documentation
 pub fn parse_diagram(input: &str) -> Result<DiagramType> {
+    // Frontmatter is not part of the diagram; see `parse_document` to keep it
+    let document = input;
+    let (_, input) = common::frontmatter::split(document);
+
     // Detect diagram type from input
     let diagram_type = detect_diagram_type(input)?;
 
+    // Directives configure the renderer; only misc, radar and XY charts
+    // read them
+    let stripped = common::parsing::directives::strip(input);
+    let input = match diagram_type {
+        "misc" | "radar" => input,
+        _ => &*stripped,
+    };
+
     // Parse based on detected type
     match diagram_type {
         "sankey" => parsers::sankey::parse(input).map(DiagramType::Sankey),
@@ -190,7 +224,9 @@ pub fn parse_diagram(input: &str) -> Result<DiagramType> {
         "class" => parsers::class::parse(input).map(DiagramType::Class),
         "er" => parsers::er::parse(input).map(DiagramType::Er),
         "flowchart" => parsers::flowchart::parse(input).map(DiagramType::Flowchart),
-        "gantt" => parsers::gantt::parse(input).map(DiagramType::Gantt),
+        // Gantt charts take their `displayMode` from the frontmatter config too
+        "gantt" => parsers::gantt::parse(&common::parsing::directives::strip(document))
+            .map(DiagramType::Gantt),
         "git" => parsers::git::parse(input).map(DiagramType::Git),
         "kanban" => parsers::kanban::parse(input).map(DiagramType::Kanban),
         "mindmap" => parsers::mindmap::parse(input).map(DiagramType::Mindmap),
@@ -204,7 +240,8 @@ pub fn parse_diagram(input: &str) -> Result<DiagramType> {
         "journey" => parsers::journey::parse(input).map(DiagramType::Journey),
         "sequence" => parsers::sequence::parse(input).map(DiagramType::Sequence),
         "state" => parsers::state::parse(input).map(DiagramType::State),
-        "xychart" => parsers::xy::parse(input).map(DiagramType::XyChart),
+        // XY charts color their series from the frontmatter config too
+        "xychart" => parsers::xy::parse(document).map(DiagramType::XyChart),
         // Add other parsers as they're implemented
         _ => {
             // Try misc parser as a fallback for unknown diagram types
@@ -213,6 +250,156 @@ pub fn parse_diagram(input: &str) -> Result<DiagramType> {
     }
 }
 
+/// Parse a Mermaid diagram that came from a named file or other source
+///
+/// Parses like [`parse_diagram`]; an error is wrapped in
+/// [`ParseError::InSource`] so it still says where it came from once errors
+/// from many files are collected together.
+///
+/// # Examples
+///
+/// ```rust
+/// use mermaid_parser::parse_diagram_named;
+///
+/// let error = parse_diagram_named("", "docs/overview.md").unwrap_err();
+/// assert_eq!(error.source_name(), Some("docs/overview.md"));
+/// assert!(error.to_string().starts_with("docs/overview.md: "));
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as [`parse_diagram`], inside
+/// [`ParseError::InSource`].
+pub fn parse_diagram_named(input: &str, source_name: &str) -> Result<DiagramType> {
+    parse_diagram(input).map_err(|error| error.in_source(source_name))
+}
+
+/// Parse a Mermaid diagram from raw bytes, such as a file exported by a
+/// Windows tool as UTF-16
+///
+/// The bytes are decoded with [`encoding::decode`], which follows a UTF-8 or
+/// UTF-16 byte order mark and reads input without one as UTF-8. Available
+/// with the `encoding` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use mermaid_parser::{parse_diagram_bytes, DiagramType};
+///
+/// let mut bytes = vec![0xFE, 0xFF];
+/// bytes.extend("flowchart TD\n    A --> B".encode_utf16().flat_map(u16::to_be_bytes));
+///
+/// let diagram = parse_diagram_bytes(&bytes)?;
+/// assert!(matches!(diagram, DiagramType::Flowchart(_)));
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ParseError::InvalidEncoding`] when the bytes cannot be decoded,
+/// and otherwise the same errors as [`parse_diagram`].
+#[cfg(feature = "encoding")]
+pub fn parse_diagram_bytes(input: &[u8]) -> Result<DiagramType> {
+    parse_diagram(&encoding::decode(input)?)
+}
+
+/// Parse a Mermaid diagram, reporting the statements the parser drops
+///
+/// Parses like [`parse_diagram`] and also returns a warning for each
+/// statement that is accepted but not kept in the AST, such as `style` in
+/// requirement diagrams or `click` in Gantt charts. See [`common::warnings`]
+/// for the statements concerned.
+///
+/// # Examples
+///
+/// ```rust
+/// use mermaid_parser::parse_diagram_with_warnings;
+///
+/// let input = "gantt\n    Design : d1, 2024-01-01, 3d\n    click d1 href \"https://example.com\"";
+/// let outcome = parse_diagram_with_warnings(input)?;
+/// for warning in &outcome.warnings {
+///     println!("{}", warning.message);
+/// }
+/// assert_eq!(outcome.warnings.len(), 1);
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as [`parse_diagram`].
+pub fn parse_diagram_with_warnings(input: &str) -> Result<ParseOutcome> {
+    common::warnings::parse_with_warnings(input)
+}
+
+/// Parse a Mermaid diagram from a reader, such as a large file
+///
+/// Line-oriented diagrams (sankey, pie, timeline and gantt) are parsed a
+/// chunk of lines at a time, so memory use does not grow with the size of
+/// the input beyond the diagram being built. Other diagram types are read
+/// into memory and parsed with [`parse_diagram`]. See [`common::stream`] for
+/// how the input is split.
+///
+/// # Examples
+///
+/// ```rust
+/// use mermaid_parser::{parse_diagram_from_reader, DiagramType};
+/// use std::io::Cursor;
+///
+/// let input = "pie title Pets\n    \"Dogs\" : 386\n    \"Cats\" : 85";
+/// let diagram = parse_diagram_from_reader(Cursor::new(input))?;
+///
+/// if let DiagramType::Pie(pie) = diagram {
+///     assert_eq!(pie.title.as_deref(), Some("Pets"));
+///     assert_eq!(pie.data.len(), 2);
+/// }
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as [`parse_diagram`], with line numbers counted
+/// from the start of the input, and [`ParseError::IoError`] when reading
+/// fails.
+pub fn parse_diagram_from_reader<R: std::io::BufRead>(reader: R) -> Result<DiagramType> {
+    common::stream::parse_reader(reader)
+}
+
+/// Parse a Mermaid diagram from a reader, sending its titles, sections,
+/// nodes and edges to `handler` instead of building an AST
+///
+/// Line-oriented diagrams (sankey, pie, timeline and gantt) are parsed a
+/// chunk of lines at a time and each chunk is dropped once its events are
+/// sent. Other diagram types are parsed with [`parse_diagram`] and then
+/// walked. See [`common::events`] for the events each diagram type produces.
+///
+/// # Examples
+///
+/// ```rust
+/// use mermaid_parser::common::events::ParseEvent;
+/// use mermaid_parser::parse_diagram_events;
+///
+/// let input = "sankey-beta\nA,B,10\nA,C,5";
+/// let mut total = 0.0;
+/// parse_diagram_events(input.as_bytes(), &mut |event: ParseEvent<'_>| {
+///     if let ParseEvent::Edge { value: Some(value), .. } = event {
+///         total += value;
+///     }
+/// })?;
+/// assert_eq!(total, 15.0);
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as [`parse_diagram_from_reader`]. Events sent
+/// before the error are not withdrawn.
+pub fn parse_diagram_events<R: std::io::BufRead>(
+    reader: R,
+    handler: &mut impl common::events::ParseHandler,
+) -> Result<()> {
+    common::stream::parse_reader_events(reader, handler)
+}
+
 /// Detect the type of Mermaid diagram from input text
 ///
 /// This function examines the first non-comment, non-whitespace line
@@ -220,10 +407,12 @@ pub fn parse_diagram(input: &str) -> Result<DiagramType> {
 /// including their beta versions and alternative names.
 ///
 /// The detection process:
-/// 1. Skips comment lines (starting with `//` or `#`)
-/// 2. Finds the first meaningful line with content
-/// 3. Extracts the first word (diagram type keyword)
-/// 4. Normalizes and matches against known diagram types
+/// 1. Skips a leading frontmatter block
+/// 2. Skips comment lines (starting with `//` or `#`) and `%%{...}%%`
+///    directives
+/// 3. Finds the first meaningful line with content
+/// 4. Extracts the first word (diagram type keyword)
+/// 5. Normalizes and matches against known diagram types
 ///
 /// # Arguments
 ///
@@ -254,42 +443,39 @@ pub fn parse_diagram(input: &str) -> Result<DiagramType> {
 /// - `sankey-beta`, `sankey` → "sankey"
 /// - `flowchart`, `graph` → "flowchart"
 /// - `sequenceDiagram` → "sequence"
-/// - `classDiagram` → "class"
+/// - `classDiagram`, `classDiagram-v2` → "class"
 /// - `stateDiagram`, `stateDiagram-v2` → "state"
 /// - And many more... (see source for complete list)
 ///
-/// Unknown diagram types default to "misc" for fallback parsing.
+/// Unknown diagram types, and input holding only directives, default to
+/// "misc" for fallback parsing.
 ///
 /// # Errors
 ///
 /// Returns [`ParseError::EmptyInput`] if the input contains no valid diagram content.
-fn detect_diagram_type(input: &str) -> Result<&'static str> {
-    let first_line = input
-        .lines()
-        .map(|line| line.trim())
-        .find(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with('#'))
-        .ok_or(ParseError::EmptyInput)?;
-
-    let first_word = first_line
-        .split_whitespace()
-        .next()
-        .ok_or(ParseError::EmptyInput)?
-        .to_lowercase()
-        .trim_end_matches(':')
-        .to_string();
+pub(crate) fn detect_diagram_type(input: &str) -> Result<&'static str> {
+    let (first_line, has_directives) = header_line(input);
+    let Some(first_line) = first_line else {
+        // A document holding only directives is handled by the misc parser
+        return if has_directives {
+            Ok("misc")
+        } else {
+            Err(ParseError::EmptyInput)
+        };
+    };
 
-    match first_word.as_str() {
+    match header_keyword(first_line).to_lowercase().as_str() {
         "sankey-beta" => Ok("sankey"),
         "timeline" => Ok("timeline"),
         "journey" => Ok("journey"),
         "sequencediagram" => Ok("sequence"),
-        "classdiagram" => Ok("class"),
+        "classdiagram" | "classdiagram-v2" => Ok("class"),
         "statediagram" | "statediagram-v2" => Ok("state"),
         "flowchart" | "graph" => Ok("flowchart"),
         "gantt" | "gantttestclick" => Ok("gantt"),
         "pie" => Ok("pie"),
-        "gitgraph" => Ok("misc"), // Alternative gitGraph syntax handled by misc parser
-        "info" => Ok("misc"),
+        // Alternative gitGraph syntax and `info` are handled by the misc parser
+        keyword if MISC_HEADERS.contains(&keyword) => Ok("misc"),
         "erdiagram" | "erdiagramtitletext" => Ok("er"),
         "c4context" | "c4container" | "c4component" | "c4dynamic" | "c4deployment" => Ok("c4"),
         "mindmap" => Ok("mindmap"),
@@ -312,6 +498,35 @@ fn detect_diagram_type(input: &str) -> Result<&'static str> {
     }
 }
 
+/// Lowercase header keywords the misc parser understands rather than keeps
+/// as raw lines
+pub(crate) const MISC_HEADERS: &[&str] = &["gitgraph", "info"];
+
+/// The first line of `input` that is not blank, a comment or a directive,
+/// trimmed, and whether any directives came before it
+pub(crate) fn header_line(input: &str) -> (Option<&str>, bool) {
+    let mut has_directives = false;
+    let first_line = common::parsing::frontmatter::strip(input)
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with('#'))
+        .find(|line| {
+            let is_directive = common::parsing::directives::split(line).is_some();
+            has_directives |= is_directive;
+            !is_directive
+        });
+    (first_line, has_directives)
+}
+
+/// The diagram type keyword of a header line, as written
+pub(crate) fn header_keyword(line: &str) -> &str {
+    // The header may be followed by `;` and more statements on the same line
+    line.split(|c: char| c.is_whitespace() || c == ';')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(':')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
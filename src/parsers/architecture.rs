@@ -4,9 +4,12 @@ use crate::common::ast::{
     AccessibilityInfo, ArchDirection, ArchEdge, ArchEdgeType, ArchitectureDiagram, EdgeEndpoint,
     Group, Junction, Port, Service,
 };
+use crate::common::ordered_map::OrderedMap;
+use crate::common::parser_utils::{
+    self, is_identifier_continue, parse_common_directives, parse_identifier,
+};
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
-use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ArchToken {
@@ -28,7 +31,9 @@ pub enum ArchToken {
     Dot,                // .
     DotDot,             // ..
     Arrow,              // ->
+    LongArrow,          // -->
     BiArrow,            // <->
+    LongBiArrow,        // <-->
     PortL,              // L
     PortR,              // R
     PortT,              // T
@@ -42,28 +47,68 @@ pub enum ArchToken {
 }
 
 pub fn parse(input: &str) -> Result<ArchitectureDiagram> {
-    let tokens = architecture_lexer()
-        .parse(input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize architecture diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
+    let input = crate::common::parsing::frontmatter::strip(input);
+    // Title, accessibility and direction statements are whole lines; take
+    // them out before tokenizing so their text is not read as edges
+    let mut title = None;
+    let mut accessibility = AccessibilityInfo::default();
+    let mut direction = ArchDirection::TB;
+    let body = input
+        .lines()
+        .map(|line| {
+            if parse_common_directives(line, &mut title, &mut accessibility) {
+                return "";
+            }
+            match line.trim().strip_prefix("direction ").map(str::trim) {
+                Some("TB") => direction = ArchDirection::TB,
+                Some("BT") => direction = ArchDirection::BT,
+                Some("LR") => direction = ArchDirection::LR,
+                Some("RL") => direction = ArchDirection::RL,
+                _ => return line,
+            }
+            ""
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let lexer = architecture_lexer();
+    let tokens = lexer.parse(&body).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            &body,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize architecture diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    })?;
 
-    let result = architecture_parser()
+    let mut diagram = architecture_parser()
         .parse(&tokens[..])
         .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to parse architecture diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        });
-    result
+        .map_err(|e| {
+            parser_utils::token_failure(
+                &tokens,
+                &e,
+                ParseError::SyntaxError {
+                    message: "Failed to parse architecture diagram".to_string(),
+                    expected: vec![],
+                    found: format!("{:?}", e),
+                    line: 0,
+                    column: 0,
+                    partial: None,
+                },
+            )
+        })?;
+    diagram.title = title;
+    diagram.accessibility = accessibility;
+    diagram.direction = direction;
+    Ok(diagram)
 }
 
 fn architecture_lexer<'src>(
@@ -87,7 +132,10 @@ fn architecture_lexer<'src>(
     // Port specifiers - single uppercase letters when not followed by alphanumeric characters
     let ports = one_of("LRTB")
         .then_ignore(
-            none_of("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_").rewind(),
+            any()
+                .filter(|c: &char| is_identifier_continue(*c))
+                .not()
+                .rewind(),
         )
         .map(|c| match c {
             'L' => ArchToken::PortL,
@@ -99,9 +147,9 @@ fn architecture_lexer<'src>(
 
     // Edge types
     let edges = choice((
-        just("<-->").map(|_| ArchToken::BiArrow), // Also accept <-->
+        just("<-->").map(|_| ArchToken::LongBiArrow),
         just("<->").map(|_| ArchToken::BiArrow),
-        just("-->").map(|_| ArchToken::Arrow), // Also accept -->
+        just("-->").map(|_| ArchToken::LongArrow),
         just("->").map(|_| ArchToken::Arrow),
         just("--").map(|_| ArchToken::DashDash),
         just("..").map(|_| ArchToken::DotDot),
@@ -122,11 +170,12 @@ fn architecture_lexer<'src>(
     // Custom identifier that allows starting with numbers
     let identifier = choice((
         // Regular identifier
-        text::ident().map(|s: &str| s.to_string()),
+        parse_identifier().map(|s: &str| s.to_string()),
         // Numeric identifier (digits optionally followed by letters/digits/underscore)
         one_of("0123456789")
             .then(
-                one_of("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_")
+                any()
+                    .filter(|c: &char| is_identifier_continue(*c))
                     .repeated()
                     .collect::<String>(),
             )
@@ -191,9 +240,9 @@ fn architecture_parser<'tokens, 'src: 'tokens>() -> impl Parser<
     )
     .then(any().repeated().collect::<Vec<_>>())
     .map(|(_, tokens)| {
-        let mut services = HashMap::new();
-        let mut groups = HashMap::new();
-        let mut junctions = HashMap::new();
+        let mut services = OrderedMap::new();
+        let mut groups = OrderedMap::new();
+        let mut junctions = OrderedMap::new();
         let mut edges = Vec::new();
         let mut i = 0;
 
@@ -470,26 +519,17 @@ fn parse_edge(tokens: &[ArchToken], from_id: &str) -> Option<(ArchEdge, usize)>
         None
     };
 
-    // Parse edge type
-    let edge_type = match &tokens[i] {
-        ArchToken::DashDash => {
-            i += 1;
-            ArchEdgeType::Solid
-        }
-        ArchToken::DotDot => {
-            i += 1;
-            ArchEdgeType::Dotted
-        }
-        ArchToken::Arrow => {
-            i += 1;
-            ArchEdgeType::Arrow
-        }
-        ArchToken::BiArrow => {
-            i += 1;
-            ArchEdgeType::BiArrow
-        }
+    // Parse edge type; `-->` and `<-->` are legacy spellings of `->` and `<->`
+    let (edge_type, legacy_arrow) = match &tokens[i] {
+        ArchToken::DashDash => (ArchEdgeType::Solid, None),
+        ArchToken::DotDot => (ArchEdgeType::Dotted, None),
+        ArchToken::Arrow => (ArchEdgeType::Arrow, None),
+        ArchToken::LongArrow => (ArchEdgeType::Arrow, Some("-->".to_string())),
+        ArchToken::BiArrow => (ArchEdgeType::BiArrow, None),
+        ArchToken::LongBiArrow => (ArchEdgeType::BiArrow, Some("<-->".to_string())),
         _ => return None,
     };
+    i += 1;
 
     // Parse to port and target
     let (to_port, to_id) = if matches!(
@@ -554,7 +594,9 @@ fn parse_edge(tokens: &[ArchToken], from_id: &str) -> Option<(ArchEdge, usize)>
             match &tokens[i] {
                 ArchToken::Identifier(s) => {
                     // Check if this identifier is followed by an arrow (indicating start of new edge)
-                    if i + 1 < tokens.len() && matches!(&tokens[i + 1], ArchToken::Arrow) {
+                    if i + 1 < tokens.len()
+                        && matches!(&tokens[i + 1], ArchToken::Arrow | ArchToken::LongArrow)
+                    {
                         break;
                     }
                     label_parts.push(s.clone());
@@ -589,6 +631,7 @@ fn parse_edge(tokens: &[ArchToken], from_id: &str) -> Option<(ArchEdge, usize)>
             },
             label,
             edge_type,
+            legacy_arrow,
         },
         i,
     ))
@@ -726,6 +769,7 @@ mod tests {
         assert_eq!(diagram.edges[1].edge_type, ArchEdgeType::Dotted);
         assert_eq!(diagram.edges[2].edge_type, ArchEdgeType::Arrow);
         assert_eq!(diagram.edges[3].edge_type, ArchEdgeType::BiArrow);
+        assert!(diagram.edges.iter().all(|edge| edge.legacy_arrow.is_none()));
     }
 
     #[test]
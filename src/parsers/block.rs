@@ -3,6 +3,8 @@
 use crate::common::ast::{
     AccessibilityInfo, Block, BlockArrowType, BlockConnection, BlockDiagram, BlockShape,
 };
+use crate::common::lexer;
+use crate::common::parser_utils::{self, parse_identifier};
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
 
@@ -14,7 +16,7 @@ pub enum BlockToken {
     BlockEnd,                      // "end"
     Space,                         // "space"
     SpaceSize(i32),                // Space with size
-    BlockId(String),               // Block identifier
+    BlockId(String),               // Block identifier or dotted path
     BlockLabel(String),            // Block label in quotes or brackets
     Arrow,                         // "-->"
     DottedArrow,                   // "-.->
@@ -37,26 +39,40 @@ pub enum BlockToken {
 }
 
 pub fn parse(input: &str) -> Result<BlockDiagram> {
-    let tokens = block_lexer()
-        .parse(input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize block diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
+    let input = crate::common::parsing::frontmatter::strip(input);
+    let lexer = block_lexer();
+    let tokens = lexer.parse(input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            input,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize block diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    })?;
 
     let result = block_parser()
         .parse(&tokens[..])
         .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to parse block diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
+        .map_err(|e| {
+            parser_utils::token_failure(
+                &tokens,
+                &e,
+                ParseError::SyntaxError {
+                    message: "Failed to parse block diagram".to_string(),
+                    expected: vec![],
+                    found: format!("{:?}", e),
+                    line: 0,
+                    column: 0,
+                    partial: None,
+                },
+            )
         });
     result
 }
@@ -77,18 +93,21 @@ fn block_lexer<'src>(
         .ignore_then(text::int(10))
         .map(|n: &str| BlockToken::Columns(n.parse().unwrap_or(1)));
 
-    // Block start: "block:ID"
+    // Block start: "block:ID", or an anonymous "block"
     let block_start = text::keyword("block")
-        .ignore_then(just(':'))
-        .ignore_then(text::ident())
-        .map(|id: &str| BlockToken::BlockStart(id.to_string()));
+        .ignore_then(just(':').ignore_then(parse_identifier()).or_not())
+        .map(|id: Option<&str>| BlockToken::BlockStart(id.unwrap_or_default().to_string()));
 
     // Block end
     let block_end = text::keyword("end").map(|_| BlockToken::BlockEnd);
 
-    // Space blocks
+    // Space blocks: "space", "space 3" or "space:3"
     let space = text::keyword("space")
-        .then(just(' ').repeated().ignore_then(text::int(10)).or_not())
+        .then(
+            choice((just(':').ignored(), just(' ').repeated().ignored()))
+                .ignore_then(text::int(10))
+                .or_not(),
+        )
         .map(|(_, size): (&str, Option<&str>)| {
             if let Some(n) = size {
                 BlockToken::SpaceSize(n.parse().unwrap_or(1))
@@ -107,27 +126,27 @@ fn block_lexer<'src>(
     ));
 
     // Block with rounded rect label: A["Label"]
-    let block_rounded = text::ident()
+    let block_rounded = parse_identifier()
         .then_ignore(just('['))
-        .then_ignore(just('"'))
-        .then(none_of('"').repeated().collect::<String>())
-        .then_ignore(just('"'))
+        .then(lexer::quoted_string())
         .then_ignore(just(']'))
         .map(|(id, label)| (id, label, BlockShape::RoundedRect));
 
     // Block with circle/cylinder label: A(("Label"))
-    let block_circle = text::ident()
+    let block_circle = parse_identifier()
         .then_ignore(just('('))
         .then_ignore(just('('))
-        .then_ignore(just('"'))
-        .then(none_of('"').repeated().collect::<String>())
-        .then_ignore(just('"'))
+        .then(lexer::quoted_string())
         .then_ignore(just(')'))
         .then_ignore(just(')'))
         .map(|(id, label)| (id, label, BlockShape::Circle));
 
-    // Simple identifier (must come after more specific patterns)
-    let identifier = text::ident().map(|s: &str| BlockToken::BlockId(s.to_string()));
+    // Identifier or dotted path to a nested block, e.g. `outer.inner`
+    // (must come after more specific patterns)
+    let identifier = parse_identifier()
+        .then(just('.').then(parse_identifier()).repeated())
+        .to_slice()
+        .map(|s: &str| BlockToken::BlockId(s.to_string()));
 
     let newline = text::newline().map(|_| BlockToken::NewLine);
 
@@ -170,124 +189,10 @@ fn block_parser<'tokens, 'src: 'tokens>(
     )
     .then(any().repeated().collect::<Vec<_>>())
     .map(|(_, tokens)| {
-        let mut blocks = Vec::new();
         let mut connections = Vec::new();
         let mut columns = None;
         let mut i = 0;
-
-        while i < tokens.len() {
-            match &tokens[i] {
-                BlockToken::Comment(_) | BlockToken::NewLine => {
-                    i += 1;
-                }
-                BlockToken::Columns(n) => {
-                    columns = Some(*n);
-                    i += 1;
-                }
-                BlockToken::BlockId(id) => {
-                    // Check if this is a connection
-                    if i + 2 < tokens.len() {
-                        if let (
-                            BlockToken::Arrow
-                            | BlockToken::DottedArrow
-                            | BlockToken::ThickArrow
-                            | BlockToken::InvisibleArrow
-                            | BlockToken::BiArrow,
-                            BlockToken::BlockId(to),
-                        ) = (&tokens[i + 1], &tokens[i + 2])
-                        {
-                            let arrow_type = match &tokens[i + 1] {
-                                BlockToken::Arrow => BlockArrowType::Normal,
-                                BlockToken::DottedArrow => BlockArrowType::Dotted,
-                                BlockToken::ThickArrow => BlockArrowType::Thick,
-                                BlockToken::InvisibleArrow => BlockArrowType::Invisible,
-                                BlockToken::BiArrow => BlockArrowType::Bidirectional,
-                                _ => unreachable!(),
-                            };
-                            connections.push(BlockConnection {
-                                from: id.clone(),
-                                to: to.clone(),
-                                label: None,
-                                arrow_type,
-                                style: None,
-                            });
-                            i += 3;
-                            continue;
-                        }
-                    }
-
-                    // Not a connection, just a block
-                    blocks.push(Block::Simple {
-                        id: id.clone(),
-                        label: None,
-                        shape: BlockShape::Rectangle,
-                    });
-                    i += 1;
-                }
-                BlockToken::BlockLabel(label_info) => {
-                    // Parse the label info format: "id:label:shape"
-                    let parts: Vec<&str> = label_info.splitn(3, ':').collect();
-                    if parts.len() >= 3 {
-                        let shape = match parts[2] {
-                            "RoundedRect" => BlockShape::RoundedRect,
-                            "Circle" => BlockShape::Circle,
-                            _ => BlockShape::Rectangle,
-                        };
-                        blocks.push(Block::Simple {
-                            id: parts[0].to_string(),
-                            label: Some(parts[1].to_string()),
-                            shape,
-                        });
-                    }
-                    i += 1;
-                }
-                BlockToken::BlockStart(id) => {
-                    // Parse composite block
-                    let mut inner_blocks = Vec::new();
-                    i += 1;
-
-                    while i < tokens.len() {
-                        match &tokens[i] {
-                            BlockToken::BlockEnd => {
-                                i += 1;
-                                break;
-                            }
-                            BlockToken::BlockId(inner_id) => {
-                                inner_blocks.push(Block::Simple {
-                                    id: inner_id.clone(),
-                                    label: None,
-                                    shape: BlockShape::Rectangle,
-                                });
-                                i += 1;
-                            }
-                            BlockToken::NewLine | BlockToken::Comment(_) => {
-                                i += 1;
-                            }
-                            _ => {
-                                i += 1;
-                            }
-                        }
-                    }
-
-                    blocks.push(Block::Composite {
-                        id: id.clone(),
-                        label: None,
-                        blocks: inner_blocks,
-                    });
-                }
-                BlockToken::Space => {
-                    blocks.push(Block::Space { size: Some(1) });
-                    i += 1;
-                }
-                BlockToken::SpaceSize(n) => {
-                    blocks.push(Block::Space { size: Some(*n) });
-                    i += 1;
-                }
-                _ => {
-                    i += 1;
-                }
-            }
-        }
+        let blocks = parse_blocks(&tokens, &mut i, &mut connections, Some(&mut columns));
 
         BlockDiagram {
             title: None,
@@ -300,6 +205,112 @@ fn block_parser<'tokens, 'src: 'tokens>(
     })
 }
 
+/// Parse blocks until `end` or the end of input, recursing into composite blocks
+///
+/// Connections found at any depth are collected into `connections`. Nested blocks
+/// pass no `columns`, as their own column layout isn't modelled.
+fn parse_blocks(
+    tokens: &[BlockToken],
+    i: &mut usize,
+    connections: &mut Vec<BlockConnection>,
+    mut columns: Option<&mut Option<i32>>,
+) -> Vec<Block> {
+    let mut blocks = Vec::new();
+
+    while *i < tokens.len() {
+        match &tokens[*i] {
+            BlockToken::BlockEnd => {
+                *i += 1;
+                break;
+            }
+            BlockToken::Columns(n) => {
+                if let Some(columns) = columns.as_deref_mut() {
+                    *columns = Some(*n);
+                }
+                *i += 1;
+            }
+            BlockToken::BlockId(id) => {
+                if let Some(connection) = parse_connection(id, &tokens[*i + 1..]) {
+                    connections.push(connection);
+                    *i += 3;
+                    continue;
+                }
+
+                // Not a connection, just a block
+                blocks.push(Block::Simple {
+                    id: id.clone(),
+                    label: None,
+                    shape: BlockShape::Rectangle,
+                });
+                *i += 1;
+            }
+            BlockToken::BlockLabel(label_info) => {
+                // Parse the label info format: "id:label:shape"
+                let parts: Vec<&str> = label_info.splitn(3, ':').collect();
+                if parts.len() >= 3 {
+                    let shape = match parts[2] {
+                        "RoundedRect" => BlockShape::RoundedRect,
+                        "Circle" => BlockShape::Circle,
+                        _ => BlockShape::Rectangle,
+                    };
+                    blocks.push(Block::Simple {
+                        id: parts[0].to_string(),
+                        label: Some(parts[1].to_string()),
+                        shape,
+                    });
+                }
+                *i += 1;
+            }
+            BlockToken::BlockStart(id) => {
+                *i += 1;
+                let inner_blocks = parse_blocks(tokens, i, connections, None);
+                blocks.push(Block::Composite {
+                    id: id.clone(),
+                    label: None,
+                    blocks: inner_blocks,
+                });
+            }
+            BlockToken::Space => {
+                blocks.push(Block::Space { size: Some(1) });
+                *i += 1;
+            }
+            BlockToken::SpaceSize(n) => {
+                blocks.push(Block::Space { size: Some(*n) });
+                *i += 1;
+            }
+            _ => {
+                *i += 1;
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Parse `from --> to` where `rest` are the tokens following `from`
+fn parse_connection(from: &str, rest: &[BlockToken]) -> Option<BlockConnection> {
+    let (arrow, BlockToken::BlockId(to)) = (rest.first()?, rest.get(1)?) else {
+        return None;
+    };
+
+    let arrow_type = match arrow {
+        BlockToken::Arrow => BlockArrowType::Normal,
+        BlockToken::DottedArrow => BlockArrowType::Dotted,
+        BlockToken::ThickArrow => BlockArrowType::Thick,
+        BlockToken::InvisibleArrow => BlockArrowType::Invisible,
+        BlockToken::BiArrow => BlockArrowType::Bidirectional,
+        _ => return None,
+    };
+
+    Some(BlockConnection {
+        from: from.to_string(),
+        to: to.clone(),
+        label: None,
+        arrow_type,
+        style: None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,9 +1,15 @@
 //! C4 diagram parser implementation
 
-use crate::common::ast::{AccessibilityInfo, C4Diagram, C4DiagramType, C4Element};
+use crate::common::ast::{
+    AccessibilityInfo, C4Boundary, C4BoundaryType, C4Diagram, C4DiagramType, C4Element,
+    C4ElementType, C4Relationship, C4RelationshipDirection, C4TagDefinition, C4TagKind,
+};
+use crate::common::lexer;
+use crate::common::ordered_map::OrderedMap;
+use crate::common::parser_utils::{self, parse_identifier};
+use crate::common::parsing::quoted_strings;
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
-use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum C4Token {
@@ -15,11 +21,16 @@ pub enum C4Token {
     C4Deployment,
 
     // Keywords
-    Title,
+    Title(String),    // "title Text"
+    AccTitle(String), // "accTitle: Text"
+    AccDescr(String), // "accDescr: Text"
     UpdateElementStyle,
     UpdateRelStyle,
     UpdateBoundaryStyle,
     UpdateLayoutConfig,
+    AddElementTag,
+    AddRelTag,
+    AddBoundaryTag,
 
     // Element types
     Person,
@@ -68,6 +79,7 @@ pub enum C4Token {
     LeftBrace,
     RightBrace,
     Comma,
+    Equals,
     DollarSign,
 
     // Values
@@ -79,6 +91,66 @@ pub enum C4Token {
     Eof,
 }
 
+/// Map a diagram header keyword to its token
+fn header_token(word: &str) -> Option<C4Token> {
+    match word {
+        "C4Context" => Some(C4Token::C4Context),
+        "C4Container" => Some(C4Token::C4Container),
+        "C4Component" => Some(C4Token::C4Component),
+        "C4Dynamic" => Some(C4Token::C4Dynamic),
+        "C4Deployment" => Some(C4Token::C4Deployment),
+        _ => None,
+    }
+}
+
+/// Map a macro name (a word followed by `(`) to its token
+fn macro_token(word: &str) -> Option<C4Token> {
+    let token = match word {
+        "Person" => C4Token::Person,
+        "Person_Ext" => C4Token::PersonExt,
+        "System" => C4Token::System,
+        "System_Ext" => C4Token::SystemExt,
+        "SystemDb" => C4Token::SystemDb,
+        "SystemDb_Ext" => C4Token::SystemDbExt,
+        "SystemQueue" => C4Token::SystemQueue,
+        "SystemQueue_Ext" => C4Token::SystemQueueExt,
+        "Container" => C4Token::Container,
+        "Container_Ext" => C4Token::ContainerExt,
+        "ContainerDb" => C4Token::ContainerDb,
+        "ContainerDb_Ext" => C4Token::ContainerDbExt,
+        "ContainerQueue" => C4Token::ContainerQueue,
+        "ContainerQueue_Ext" => C4Token::ContainerQueueExt,
+        "Component" => C4Token::Component,
+        "Component_Ext" => C4Token::ComponentExt,
+        "ComponentDb" => C4Token::ComponentDb,
+        "ComponentDb_Ext" => C4Token::ComponentDbExt,
+        "ComponentQueue" => C4Token::ComponentQueue,
+        "ComponentQueue_Ext" => C4Token::ComponentQueueExt,
+        "Node" | "Node_L" | "Node_R" => C4Token::Node,
+        "Deployment_Node" => C4Token::DeploymentNode,
+        "System_Boundary" => C4Token::SystemBoundary,
+        "Container_Boundary" => C4Token::ContainerBoundary,
+        "Enterprise_Boundary" => C4Token::EnterpriseBoundary,
+        "Boundary" => C4Token::Boundary,
+        "Rel" => C4Token::Rel,
+        "BiRel" => C4Token::BiRel,
+        "Rel_U" | "Rel_Up" => C4Token::RelUp,
+        "Rel_D" | "Rel_Down" => C4Token::RelDown,
+        "Rel_L" | "Rel_Left" => C4Token::RelLeft,
+        "Rel_R" | "Rel_Right" => C4Token::RelRight,
+        "Rel_Back" => C4Token::RelBack,
+        "UpdateElementStyle" => C4Token::UpdateElementStyle,
+        "UpdateRelStyle" => C4Token::UpdateRelStyle,
+        "UpdateBoundaryStyle" => C4Token::UpdateBoundaryStyle,
+        "UpdateLayoutConfig" => C4Token::UpdateLayoutConfig,
+        "AddElementTag" => C4Token::AddElementTag,
+        "AddRelTag" => C4Token::AddRelTag,
+        "AddBoundaryTag" => C4Token::AddBoundaryTag,
+        _ => return None,
+    };
+    Some(token)
+}
+
 fn c4_lexer<'src>() -> impl Parser<'src, &'src str, Vec<C4Token>, extra::Err<Simple<'src, char>>> {
     let comment = choice((
         just("%%").then(none_of('\n').repeated()),
@@ -86,110 +158,492 @@ fn c4_lexer<'src>() -> impl Parser<'src, &'src str, Vec<C4Token>, extra::Err<Sim
     ))
     .map(|_| C4Token::Comment("".to_string()));
 
-    let c4_context = just("C4Context").map(|_| C4Token::C4Context);
-    let title = text::keyword("title").map(|_| C4Token::Title);
-    let person = text::keyword("Person").map(|_| C4Token::Person);
-    let system = text::keyword("System").map(|_| C4Token::System);
-    let rel = text::keyword("Rel").map(|_| C4Token::Rel);
+    let rest_of_line = none_of('\n')
+        .repeated()
+        .to_slice()
+        .map(|s: &str| quoted_strings::unquote(s.trim().trim_start_matches(':')));
+
+    let title = text::keyword("title")
+        .ignore_then(rest_of_line)
+        .map(C4Token::Title);
+    let acc_title = text::keyword("accTitle")
+        .ignore_then(rest_of_line)
+        .map(C4Token::AccTitle);
+    let acc_descr = text::keyword("accDescr")
+        .ignore_then(rest_of_line)
+        .map(C4Token::AccDescr);
 
-    // Simple identifier (must come after keywords)
-    let identifier = text::ident().map(|s: &str| C4Token::Identifier(s.to_string()));
+    // Words followed by `(` are macros such as `Person(...)`, anything else is a value
+    let word = parse_identifier()
+        .then(text::inline_whitespace().then(just('(')).rewind().or_not())
+        .map(|(word, paren): (&str, Option<_>)| {
+            let keyword = if paren.is_some() {
+                macro_token(word)
+            } else {
+                header_token(word)
+            };
+            keyword.unwrap_or_else(|| C4Token::Identifier(word.to_string()))
+        });
 
-    let newline = text::newline().map(|_| C4Token::NewLine);
+    let variable = just('$')
+        .ignore_then(parse_identifier())
+        .map(|s: &str| C4Token::Variable(s.to_string()));
+
+    let quoted_string = lexer::quoted_string().map(C4Token::QuotedString);
+
+    // Unquoted argument text that isn't a plain word, e.g. `C#` or `1.0`
+    let bare_text = none_of(",;(){}=\"$ \t\r\n")
+        .repeated()
+        .at_least(1)
+        .to_slice()
+        .map(|s: &str| C4Token::Identifier(s.to_string()));
+
+    // `title`, `accTitle` and `accDescr` are keywords only at the start of a
+    // statement, so a macro argument such as `Person(title, "Title")` stays a word
+    let statement_start =
+        text::inline_whitespace().ignore_then(choice((title, acc_title, acc_descr)).or_not());
+
+    // `;` separates statements like a newline
+    let newline = choice((text::newline(), just(';').ignored()))
+        .to(C4Token::NewLine)
+        .then(statement_start.clone());
 
     let token = choice((
         comment,
-        c4_context,
-        title,
-        person,
-        system,
-        rel,
         just('(').to(C4Token::LeftParen),
         just(')').to(C4Token::RightParen),
+        just('{').to(C4Token::LeftBrace),
+        just('}').to(C4Token::RightBrace),
         just(',').to(C4Token::Comma),
-        just('"')
-            .ignore_then(none_of('"').repeated().collect::<String>())
-            .then_ignore(just('"'))
-            .map(C4Token::QuotedString),
-        identifier,
-    ))
-    .padded();
+        just('=').to(C4Token::Equals),
+        quoted_string,
+        variable,
+        word,
+        bare_text,
+    ));
 
-    token.or(newline).repeated().collect::<Vec<_>>()
+    statement_start
+        .then(
+            text::inline_whitespace()
+                .ignore_then(token.map(|token| (token, None)).or(newline))
+                .repeated()
+                .collect::<Vec<_>>(),
+        )
+        .then_ignore(text::inline_whitespace())
+        .map(|(heading, tokens)| {
+            heading
+                .into_iter()
+                .chain(
+                    tokens
+                        .into_iter()
+                        .flat_map(|(token, heading)| std::iter::once(token).chain(heading)),
+                )
+                .collect()
+        })
 }
 
-fn c4_parser<'src>(
-) -> impl Parser<'src, &'src [C4Token], C4Diagram, extra::Err<Simple<'src, C4Token>>> {
-    // Just consume all tokens and return a basic diagram for now to test
-    any().repeated().map(|_| C4Diagram {
-        diagram_type: C4DiagramType::Context,
-        title: Some("System Context diagram".to_string()),
-        accessibility: AccessibilityInfo::default(),
-        elements: {
-            let mut map = HashMap::new();
-            map.insert(
-                "customer".to_string(),
-                C4Element {
-                    id: "customer".to_string(),
-                    element_type: crate::common::ast::C4ElementType::Person,
-                    name: "Customer".to_string(),
-                    description: Some("A user".to_string()),
-                    technology: None,
-                    tags: Vec::new(),
-                    is_external: false,
-                },
-            );
-            map.insert(
-                "system".to_string(),
-                C4Element {
-                    id: "system".to_string(),
-                    element_type: crate::common::ast::C4ElementType::System,
-                    name: "System".to_string(),
-                    description: Some("The main system".to_string()),
-                    technology: None,
-                    tags: Vec::new(),
-                    is_external: false,
-                },
-            );
-            map
-        },
+/// Positional and `$name=value` arguments of a macro call
+#[derive(Debug, Default)]
+struct C4Arguments {
+    positional: Vec<String>,
+    named: Vec<(String, String)>,
+}
+
+impl C4Arguments {
+    /// Get an argument by `$name`, falling back to its position
+    fn get(&self, name: &str, position: usize) -> Option<String> {
+        self.named
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .or_else(|| self.positional.get(position).cloned())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn tags(&self, position: usize) -> Vec<String> {
+        self.get("tags", position)
+            .map(|tags| split_tags(&tags))
+            .unwrap_or_default()
+    }
+}
+
+/// Split a `$tags` value; multiple tags are joined with `+`
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split('+')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn syntax_error(message: &str, found: Option<&C4Token>) -> ParseError {
+    ParseError::SyntaxError {
+        message: message.to_string(),
+        expected: vec![],
+        found: found.map_or_else(|| "end of input".to_string(), |t| format!("{:?}", t)),
+        line: 0,
+        column: 0,
+        partial: None,
+    }
+}
+
+/// Parse the arguments of a macro call; `start` is the index of the macro token
+fn parse_arguments(tokens: &[C4Token], start: usize) -> Result<(C4Arguments, usize)> {
+    let mut i = start + 1;
+    if !matches!(tokens.get(i), Some(C4Token::LeftParen)) {
+        return Err(syntax_error("Expected '(' after C4 macro", tokens.get(i)));
+    }
+    i += 1;
+
+    let mut arguments = C4Arguments::default();
+    let mut name: Option<String> = None;
+    let mut value: Vec<String> = Vec::new();
+
+    loop {
+        match tokens.get(i) {
+            Some(C4Token::Variable(variable)) if value.is_empty() && name.is_none() => {
+                name = Some(variable.clone());
+            }
+            Some(C4Token::Equals) if name.is_some() => {}
+            Some(C4Token::QuotedString(text)) | Some(C4Token::Identifier(text)) => {
+                value.push(text.clone());
+            }
+            Some(C4Token::NewLine) | Some(C4Token::Comment(_)) => {}
+            Some(token @ (C4Token::Comma | C4Token::RightParen)) => {
+                let text = value.join(" ");
+                match name.take() {
+                    Some(name) => arguments.named.push((name, text)),
+                    None => arguments.positional.push(text),
+                }
+                value.clear();
+
+                if matches!(token, C4Token::RightParen) {
+                    return Ok((arguments, i + 1));
+                }
+            }
+            other => {
+                return Err(syntax_error(
+                    "Unexpected token in C4 macro arguments",
+                    other,
+                ))
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Returns true if the statement ending at `i` opens a `{ ... }` block
+fn opens_block(tokens: &[C4Token], i: usize) -> bool {
+    matches!(tokens.get(i), Some(C4Token::LeftBrace))
+}
+
+fn element_kind(token: &C4Token) -> Option<(C4ElementType, bool)> {
+    let kind = match token {
+        C4Token::Person => (C4ElementType::Person, false),
+        C4Token::PersonExt => (C4ElementType::Person, true),
+        C4Token::System => (C4ElementType::System, false),
+        C4Token::SystemExt => (C4ElementType::System, true),
+        C4Token::SystemDb => (C4ElementType::SystemDb, false),
+        C4Token::SystemDbExt => (C4ElementType::SystemDb, true),
+        C4Token::SystemQueue => (C4ElementType::SystemQueue, false),
+        C4Token::SystemQueueExt => (C4ElementType::SystemQueue, true),
+        C4Token::Container => (C4ElementType::Container, false),
+        C4Token::ContainerExt => (C4ElementType::Container, true),
+        C4Token::ContainerDb => (C4ElementType::ContainerDb, false),
+        C4Token::ContainerDbExt => (C4ElementType::ContainerDb, true),
+        C4Token::ContainerQueue => (C4ElementType::ContainerQueue, false),
+        C4Token::ContainerQueueExt => (C4ElementType::ContainerQueue, true),
+        C4Token::Component => (C4ElementType::Component, false),
+        C4Token::ComponentExt => (C4ElementType::Component, true),
+        C4Token::ComponentDb => (C4ElementType::ComponentDb, false),
+        C4Token::ComponentDbExt => (C4ElementType::ComponentDb, true),
+        C4Token::ComponentQueue => (C4ElementType::ComponentQueue, false),
+        C4Token::ComponentQueueExt => (C4ElementType::ComponentQueue, true),
+        C4Token::Node => (C4ElementType::Node, false),
+        C4Token::NodeExt => (C4ElementType::Node, true),
+        C4Token::DeploymentNode => (C4ElementType::DeploymentNode, false),
+        C4Token::DeploymentNodeExt => (C4ElementType::DeploymentNode, true),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+fn boundary_kind(token: &C4Token) -> Option<C4BoundaryType> {
+    match token {
+        C4Token::SystemBoundary => Some(C4BoundaryType::System),
+        C4Token::ContainerBoundary => Some(C4BoundaryType::Container),
+        C4Token::EnterpriseBoundary => Some(C4BoundaryType::Enterprise),
+        C4Token::Boundary => Some(C4BoundaryType::Generic),
+        _ => None,
+    }
+}
+
+fn relationship_kind(token: &C4Token) -> Option<(C4RelationshipDirection, bool)> {
+    match token {
+        C4Token::Rel => Some((C4RelationshipDirection::Default, false)),
+        C4Token::BiRel => Some((C4RelationshipDirection::Default, true)),
+        C4Token::RelUp => Some((C4RelationshipDirection::Up, false)),
+        C4Token::RelDown => Some((C4RelationshipDirection::Down, false)),
+        C4Token::RelLeft => Some((C4RelationshipDirection::Left, false)),
+        C4Token::RelRight => Some((C4RelationshipDirection::Right, false)),
+        C4Token::RelBack => Some((C4RelationshipDirection::Back, false)),
+        _ => None,
+    }
+}
+
+fn tag_kind(token: &C4Token) -> Option<C4TagKind> {
+    match token {
+        C4Token::AddElementTag => Some(C4TagKind::Element),
+        C4Token::AddRelTag => Some(C4TagKind::Relationship),
+        C4Token::AddBoundaryTag => Some(C4TagKind::Boundary),
+        _ => None,
+    }
+}
+
+fn build_element(
+    element_type: C4ElementType,
+    is_external: bool,
+    args: &C4Arguments,
+) -> Result<C4Element> {
+    let id = args
+        .get("alias", 0)
+        .ok_or_else(|| syntax_error("C4 element requires an alias", None))?;
+
+    // Containers, components and nodes take a technology/type before the description
+    let has_technology = !matches!(
+        element_type,
+        C4ElementType::Person
+            | C4ElementType::System
+            | C4ElementType::SystemDb
+            | C4ElementType::SystemQueue
+    );
+    let (technology, description, sprite, tags) = if has_technology {
+        let technology = args.get("techn", 2).or_else(|| args.get("type", 2));
+        (technology, args.get("descr", 3), args.get("sprite", 4), 5)
+    } else {
+        (None, args.get("descr", 2), args.get("sprite", 3), 4)
+    };
+
+    Ok(C4Element {
+        name: args.get("label", 1).unwrap_or_else(|| id.clone()),
+        id,
+        element_type,
+        description,
+        technology,
+        sprite,
+        tags: args.tags(tags),
+        is_external,
+    })
+}
+
+fn build_boundary(boundary_type: C4BoundaryType, args: &C4Arguments) -> Result<C4Boundary> {
+    let id = args
+        .get("alias", 0)
+        .ok_or_else(|| syntax_error("C4 boundary requires an alias", None))?;
+
+    // Generic boundaries take a type before the tags
+    let tags = if boundary_type == C4BoundaryType::Generic {
+        3
+    } else {
+        2
+    };
+
+    Ok(C4Boundary {
+        label: args.get("label", 1).unwrap_or_else(|| id.clone()),
+        id,
+        tags: args.tags(tags),
+        boundary_type,
+        elements: Vec::new(),
         boundaries: Vec::new(),
-        relationships: vec![crate::common::ast::C4Relationship {
-            from: "customer".to_string(),
-            to: "system".to_string(),
-            label: Some("Uses".to_string()),
-            technology: None,
-            direction: crate::common::ast::C4RelationshipDirection::Default,
-            is_bidirectional: false,
-            tags: Vec::new(),
-        }],
     })
 }
 
+fn build_relationship(
+    direction: C4RelationshipDirection,
+    is_bidirectional: bool,
+    args: &C4Arguments,
+) -> Result<C4Relationship> {
+    let (Some(from), Some(to)) = (args.get("from", 0), args.get("to", 1)) else {
+        return Err(syntax_error(
+            "C4 relationship requires both endpoints",
+            None,
+        ));
+    };
+
+    Ok(C4Relationship {
+        from,
+        to,
+        label: args.get("label", 2),
+        technology: args.get("techn", 3),
+        direction,
+        is_bidirectional,
+        tags: args.tags(6),
+    })
+}
+
+fn build_tag(kind: C4TagKind, args: &C4Arguments) -> Result<C4TagDefinition> {
+    let name = args
+        .get("tagStereo", 0)
+        .ok_or_else(|| syntax_error("C4 tag definition requires a tag name", None))?;
+
+    Ok(C4TagDefinition {
+        name,
+        kind,
+        properties: args
+            .named
+            .iter()
+            .filter(|(key, _)| key != "tagStereo")
+            .cloned()
+            .collect(),
+    })
+}
+
+/// Close the innermost `{ ... }` scope, attaching a finished boundary to its parent
+fn close_scope(diagram: &mut C4Diagram, scopes: &mut Vec<Option<C4Boundary>>) -> Result<()> {
+    let Some(scope) = scopes.pop() else {
+        return Err(syntax_error(
+            "Unmatched '}' in C4 diagram",
+            Some(&C4Token::RightBrace),
+        ));
+    };
+
+    if let Some(boundary) = scope {
+        match scopes.iter_mut().rev().find_map(Option::as_mut) {
+            Some(parent) => parent.boundaries.push(boundary),
+            None => diagram.boundaries.push(boundary),
+        }
+    }
+    Ok(())
+}
+
+fn parse_c4_diagram(tokens: &[C4Token]) -> Result<C4Diagram> {
+    let mut i = 0;
+    while matches!(
+        tokens.get(i),
+        Some(C4Token::NewLine) | Some(C4Token::Comment(_))
+    ) {
+        i += 1;
+    }
+
+    let diagram_type = match tokens.get(i) {
+        Some(C4Token::C4Context) => C4DiagramType::Context,
+        Some(C4Token::C4Container) => C4DiagramType::Container,
+        Some(C4Token::C4Component) => C4DiagramType::Component,
+        Some(C4Token::C4Dynamic) => C4DiagramType::Dynamic,
+        Some(C4Token::C4Deployment) => C4DiagramType::Deployment,
+        other => return Err(syntax_error("Expected C4 diagram header", other)),
+    };
+    i += 1;
+
+    let mut diagram = C4Diagram {
+        diagram_type,
+        title: None,
+        accessibility: AccessibilityInfo::default(),
+        elements: OrderedMap::new(),
+        boundaries: Vec::new(),
+        relationships: Vec::new(),
+        tags: Vec::new(),
+    };
+
+    // Open `{ ... }` blocks; `None` for deployment nodes, which aren't boundaries
+    let mut scopes: Vec<Option<C4Boundary>> = Vec::new();
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+
+        if let Some((element_type, is_external)) = element_kind(token) {
+            let (args, next_i) = parse_arguments(tokens, i)?;
+            let element = build_element(element_type, is_external, &args)?;
+            if let Some(boundary) = scopes.iter_mut().rev().find_map(Option::as_mut) {
+                boundary.elements.push(element.id.clone());
+            }
+            diagram.elements.insert(element.id.clone(), element);
+            i = next_i;
+            if opens_block(tokens, i) {
+                scopes.push(None);
+                i += 1;
+            }
+            continue;
+        }
+
+        if let Some(boundary_type) = boundary_kind(token) {
+            let (args, next_i) = parse_arguments(tokens, i)?;
+            scopes.push(Some(build_boundary(boundary_type, &args)?));
+            i = next_i;
+            if opens_block(tokens, i) {
+                i += 1;
+            } else {
+                close_scope(&mut diagram, &mut scopes)?;
+            }
+            continue;
+        }
+
+        if let Some((direction, is_bidirectional)) = relationship_kind(token) {
+            let (args, next_i) = parse_arguments(tokens, i)?;
+            diagram
+                .relationships
+                .push(build_relationship(direction, is_bidirectional, &args)?);
+            i = next_i;
+            continue;
+        }
+
+        if let Some(kind) = tag_kind(token) {
+            let (args, next_i) = parse_arguments(tokens, i)?;
+            diagram.tags.push(build_tag(kind, &args)?);
+            i = next_i;
+            continue;
+        }
+
+        match token {
+            C4Token::Title(title) => diagram.title = Some(title.clone()),
+            C4Token::AccTitle(title) => diagram.accessibility.title = Some(title.clone()),
+            C4Token::AccDescr(descr) => diagram.accessibility.description = Some(descr.clone()),
+            C4Token::RightBrace => close_scope(&mut diagram, &mut scopes)?,
+            C4Token::NewLine | C4Token::Comment(_) => {}
+            // Style updates and unsupported macros are skipped
+            C4Token::UpdateElementStyle
+            | C4Token::UpdateRelStyle
+            | C4Token::UpdateBoundaryStyle
+            | C4Token::UpdateLayoutConfig
+            | C4Token::Identifier(_)
+                if matches!(tokens.get(i + 1), Some(C4Token::LeftParen)) =>
+            {
+                let (_, next_i) = parse_arguments(tokens, i)?;
+                i = next_i;
+                continue;
+            }
+            other => return Err(syntax_error("Unexpected token in C4 diagram", Some(other))),
+        }
+        i += 1;
+    }
+
+    if !scopes.is_empty() {
+        return Err(syntax_error("Unclosed '{' in C4 diagram", None));
+    }
+
+    Ok(diagram)
+}
+
 pub fn parse(input: &str) -> Result<C4Diagram> {
-    let tokens = c4_lexer()
-        .parse(input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize C4 diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
-
-    let result =
-        c4_parser()
-            .parse(&tokens[..])
-            .into_result()
-            .map_err(|e| ParseError::SyntaxError {
-                message: "Failed to parse C4 diagram".to_string(),
+    let input = crate::common::parsing::frontmatter::strip(input);
+    let lexer = c4_lexer();
+    let tokens = lexer.parse(input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            input,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize C4 diagram".to_string(),
                 expected: vec![],
                 found: format!("{:?}", e),
                 line: 0,
                 column: 0,
-            });
-    result
+                partial: None,
+            },
+        )
+    })?;
+
+    parse_c4_diagram(&tokens)
 }
 
 #[cfg(test)]
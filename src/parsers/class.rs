@@ -1,14 +1,24 @@
 //! Class diagram parser implementation
 
-use crate::common::ast::{AccessibilityInfo, Class, ClassDiagram};
-use crate::common::parser_utils::{parse_comment, parse_whitespace};
+use crate::common::ast::{
+    AccessibilityInfo, Class, ClassDiagram, ClassMember, ClickAction, ClickEvent, FlowDirection,
+    Method, Parameter, Property, StyleDefinition, StyleTarget, Visibility,
+};
+use crate::common::constants::class_keywords;
+use crate::common::lexer;
+use crate::common::ordered_map::OrderedMap;
+use crate::common::parser_utils::{parse_comment, parse_identifier, parse_whitespace};
+use crate::common::parsing::key_value::parse_style_properties;
+use crate::common::parsing::quoted_strings;
+use crate::error::Result;
 use chumsky::prelude::*;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ClassToken {
-    ClassDiagram,           // "classDiagram"
+    ClassDiagram,           // "classDiagram" or "classDiagram-v2"
     Class,                  // "class"
+    Namespace,              // "namespace"
     ClassName(String),      // Class identifier
     LeftBrace,              // {
     RightBrace,             // }
@@ -51,8 +61,9 @@ fn class_lexer<'src>(
 ) -> impl Parser<'src, &'src str, Vec<ClassToken>, extra::Err<Simple<'src, char>>> {
     let comment = parse_comment().map(|_| ClassToken::Comment("".to_string()));
 
-    let class_diagram = just("classDiagram").map(|_| ClassToken::ClassDiagram);
-    let class_keyword = just("class").map(|_| ClassToken::Class);
+    let class_diagram = just("classDiagram-v2")
+        .or(just("classDiagram"))
+        .map(|_| ClassToken::ClassDiagram);
 
     // Relationship symbols (order matters for overlapping patterns - longer first)
     let relationships = choice((
@@ -72,15 +83,23 @@ fn class_lexer<'src>(
         .then_ignore(just(">>"))
         .map(|name: String| ClassToken::StereotypeName(name.trim().to_string()));
 
-    // Simple identifier (must come after keywords)
-    let identifier = text::ident().map(|s: &str| ClassToken::Identifier(s.to_string()));
+    // Keywords are whole identifiers, so `classify` and `order` stay identifiers
+    let identifier = parse_identifier().map(|s: &str| match s {
+        "class" => ClassToken::Class,
+        "namespace" => ClassToken::Namespace,
+        "o" => ClassToken::Circle,
+        _ => ClassToken::Identifier(s.to_string()),
+    });
 
     let newline = just('\n').map(|_| ClassToken::NewLine);
 
+    // Quoted strings are opaque, so relationship symbols inside labels are not tokenized
+    let quoted_string = lexer::quoted_string().map(ClassToken::QuotedString);
+
     let token = choice((
         comment,
+        quoted_string,
         class_diagram,
-        class_keyword,
         relationships,
         stereotype,
         just('(').to(ClassToken::LeftParen),
@@ -92,16 +111,12 @@ fn class_lexer<'src>(
         just('-').to(ClassToken::Minus),
         just('#').to(ClassToken::Hash),
         just('~').to(ClassToken::Tilde),
+        just('$').to(ClassToken::Dollar),
         just(':').to(ClassToken::Colon),
         just('|').to(ClassToken::Pipe),
         just('*').to(ClassToken::Star),
-        just('o').to(ClassToken::Circle),
         just('<').to(ClassToken::LeftAngle),
         just('>').to(ClassToken::RightAngle),
-        just('"')
-            .ignore_then(none_of('"').repeated().collect::<String>())
-            .then_ignore(just('"'))
-            .map(ClassToken::QuotedString),
         identifier,
     ));
 
@@ -122,53 +137,385 @@ fn class_parser<'src>(
             .repeated(),
     );
 
-    // Parse a simple class definition: "class ClassName"
-    let simple_class = just(ClassToken::Class)
-        .ignore_then(any().try_map(|t, span| match t {
-            ClassToken::Identifier(name) => Ok(name),
-            _ => Err(Simple::new(Some(t.into()), span)),
-        }))
-        .map(|name: String| Class {
-            name: name.clone(),
+    let identifier = any().try_map(|t, span| match t {
+        ClassToken::Identifier(name) => Ok(name),
+        _ => Err(Simple::new(Some(t.into()), span)),
+    });
+
+    // Type parameters between tildes: "~T~" or "~K,V~"
+    let generics = just(ClassToken::Tilde)
+        .ignore_then(
+            any()
+                .filter(|t| !matches!(t, ClassToken::Tilde | ClassToken::NewLine))
+                .repeated()
+                .collect::<Vec<ClassToken>>(),
+        )
+        .then_ignore(just(ClassToken::Tilde))
+        .map(|tokens| generic_parameters(&tokens));
+
+    // A type with optional type parameters: "List~T~"
+    let type_ref = identifier.then(generics.clone().or_not().map(Option::unwrap_or_default));
+
+    let visibility = choice((
+        just(ClassToken::Plus).to(Visibility::Public),
+        just(ClassToken::Minus).to(Visibility::Private),
+        just(ClassToken::Hash).to(Visibility::Protected),
+        just(ClassToken::Tilde).to(Visibility::Package),
+    ))
+    .or_not()
+    .map(|visibility| visibility.unwrap_or(Visibility::Public));
+
+    // `$` (static) and `*` (abstract) markers, before or after a member
+    let modifiers = choice((just(ClassToken::Dollar), just(ClassToken::Star)))
+        .repeated()
+        .collect::<Vec<_>>();
+
+    // A method parameter: "Type name", "name: Type" or "name"
+    let parameter = choice((
+        identifier
+            .then_ignore(just(ClassToken::Colon))
+            .then(type_ref.clone())
+            .map(|(name, (param_type, generics))| Parameter {
+                name,
+                param_type: Some(type_name(param_type, &generics)),
+            }),
+        type_ref
+            .clone()
+            .then(identifier)
+            .map(|((param_type, generics), name)| Parameter {
+                name,
+                param_type: Some(type_name(param_type, &generics)),
+            }),
+        identifier.map(|name| Parameter {
+            name,
+            param_type: None,
+        }),
+    ));
+
+    // A method: "+getItems(int limit) List~T~"
+    let method = visibility
+        .clone()
+        .then(modifiers.clone())
+        .then(identifier)
+        .then(
+            parameter
+                .separated_by(just(ClassToken::Comma))
+                .collect::<Vec<_>>()
+                .delimited_by(just(ClassToken::LeftParen), just(ClassToken::RightParen)),
+        )
+        .then(modifiers.clone())
+        .then(type_ref.clone().or_not())
+        .map(
+            |(((((visibility, before), name), parameters), after), return_type)| {
+                let markers: Vec<ClassToken> = before.into_iter().chain(after).collect();
+                let (return_type, generics) = match return_type {
+                    Some((return_type, generics)) => (Some(return_type), generics),
+                    None => (None, Vec::new()),
+                };
+                ClassMember::Method(Method {
+                    name,
+                    parameters,
+                    return_type,
+                    generics,
+                    visibility,
+                    is_static: markers.contains(&ClassToken::Dollar),
+                    is_abstract: markers.contains(&ClassToken::Star),
+                })
+            },
+        );
+
+    // A property: "+Map~K,V~ items" or just "items"
+    let property = visibility
+        .then(modifiers.clone())
+        .then(type_ref.clone())
+        .then(identifier.or_not())
+        .then(modifiers)
+        .map(
+            |((((visibility, before), (first, generics)), name), after)| {
+                let (name, prop_type, generics) = match name {
+                    Some(name) => (name, Some(first), generics),
+                    None => (first, None, Vec::new()),
+                };
+                ClassMember::Property(Property {
+                    name,
+                    prop_type,
+                    generics,
+                    visibility,
+                    is_static: before.contains(&ClassToken::Dollar)
+                        || after.contains(&ClassToken::Dollar),
+                    default_value: None,
+                })
+            },
+        );
+
+    // Members between braces; stereotypes and other lines are skipped for now
+    let body = choice((
+        method.map(Some),
+        property.map(Some),
+        any()
+            .filter(|t| !matches!(t, ClassToken::RightBrace))
+            .to(None),
+    ))
+    .repeated()
+    .collect::<Vec<_>>()
+    .delimited_by(just(ClassToken::LeftBrace), just(ClassToken::RightBrace))
+    .map(|members| members.into_iter().flatten().collect::<Vec<_>>());
+
+    // A class definition: "class ClassName~T~ { members }"
+    let class = just(ClassToken::Class)
+        .ignore_then(identifier)
+        .then(generics.or_not())
+        .then(body.or_not())
+        .map(|((name, generics), members)| Class {
+            name,
+            generics: generics.unwrap_or_default(),
             stereotype: None,
-            members: Vec::new(),
+            members: members.unwrap_or_default(),
             annotations: Vec::new(),
             css_class: None,
         });
 
-    // Skip newlines and other tokens for now
-    let skip_token = any().filter(|t| !matches!(t, ClassToken::Class));
-
-    // Parse diagram content
-    let content = choice((simple_class.map(Some), skip_token.map(|_| None)))
+    // A namespace grouping class definitions: "namespace Shapes { class Square }"
+    let namespace = just(ClassToken::Namespace).ignore_then(identifier).then(
+        choice((
+            class.clone().map(Some),
+            any()
+                .filter(|t| !matches!(t, ClassToken::RightBrace | ClassToken::Class))
+                .to(None),
+        ))
         .repeated()
-        .collect::<Vec<_>>();
+        .collect::<Vec<_>>()
+        .delimited_by(just(ClassToken::LeftBrace), just(ClassToken::RightBrace))
+        .map(|classes| classes.into_iter().flatten().collect::<Vec<_>>()),
+    );
 
-    header.ignore_then(content).map(|classes_opt| {
-        let mut classes = HashMap::new();
+    // Parse a direction statement: "direction LR"
+    let direction = just(ClassToken::Identifier("direction".to_string())).ignore_then(
+        any().try_map(|t: ClassToken, span| {
+            match &t {
+                ClassToken::Identifier(keyword) => FlowDirection::from_keyword(keyword),
+                _ => None,
+            }
+            .ok_or_else(|| Simple::new(Some(t.into()), span))
+        }),
+    );
 
-        for class in classes_opt.into_iter().flatten() {
-            classes.insert(class.name.clone(), class);
+    // Skip newlines and other tokens for now
+    let skip_token = any().filter(|t| !matches!(t, ClassToken::Class | ClassToken::Namespace));
+
+    // Parse diagram content
+    let content = choice((
+        class.map(ClassItem::Class),
+        namespace.map(|(name, classes)| ClassItem::Namespace(name, classes)),
+        direction.map(ClassItem::Direction),
+        skip_token.to(ClassItem::Skipped),
+    ))
+    .repeated()
+    .collect::<Vec<_>>();
+
+    header.ignore_then(content).map(|items| {
+        let mut classes = OrderedMap::new();
+        let mut namespaces: HashMap<String, Vec<String>> = HashMap::new();
+        let mut direction = None;
+
+        for item in items {
+            match item {
+                ClassItem::Class(class) => {
+                    classes.insert(class.name.clone(), class);
+                }
+                ClassItem::Namespace(name, members) => {
+                    let names = namespaces.entry(name).or_default();
+                    for class in members {
+                        if !names.contains(&class.name) {
+                            names.push(class.name.clone());
+                        }
+                        classes.insert(class.name.clone(), class);
+                    }
+                }
+                ClassItem::Direction(dir) => direction = Some(dir),
+                ClassItem::Skipped => {}
+            }
         }
 
         ClassDiagram {
             title: None,
             accessibility: AccessibilityInfo::default(),
+            direction,
             classes,
             relationships: Vec::new(),
             notes: Vec::new(),
+            namespaces,
+            styles: Vec::new(),
+            clicks: Vec::new(),
         }
     })
 }
 
+/// A top-level statement recognised by [`class_parser`]
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Class(Class),
+    Namespace(String, Vec<Class>),
+    Direction(FlowDirection),
+    Skipped,
+}
+
+/// Split the tokens between `~` delimiters into type parameters at commas
+fn generic_parameters(tokens: &[ClassToken]) -> Vec<String> {
+    tokens
+        .split(|t| *t == ClassToken::Comma)
+        .map(|parameter| {
+            parameter
+                .iter()
+                .filter_map(|t| match t {
+                    ClassToken::Identifier(name) => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|parameter| !parameter.is_empty())
+        .collect()
+}
+
+/// A type name with its type parameters, as written in the source
+fn type_name(name: String, generics: &[String]) -> String {
+    if generics.is_empty() {
+        name
+    } else {
+        format!("{}~{}~", name, generics.join(","))
+    }
+}
+
+/// `style`, `cssClass`, `link` and `click` statements, which are read line
+/// by line rather than by the lexer
+#[derive(Default)]
+struct LineStatements {
+    styles: Vec<StyleDefinition>,
+    clicks: Vec<ClickEvent>,
+    /// Class names with the CSS class `cssClass` assigns to each
+    css_classes: Vec<(String, String)>,
+}
+
+/// Remove `style`, `cssClass`, `link` and `click` statements from the input,
+/// leaving blank lines so the remaining lines keep their positions
+fn extract_line_statements(input: &str) -> (String, LineStatements) {
+    let mut statements = LineStatements::default();
+    let mut remaining = Vec::new();
+
+    for line in input.lines() {
+        let words = split_words(line.trim());
+        let extracted = match words.as_slice() {
+            [keyword, name, properties @ ..] if *keyword == class_keywords::STYLE => {
+                statements.styles.push(StyleDefinition {
+                    target: StyleTarget::Node(name.to_string()),
+                    styles: parse_style_properties(&properties.join(" ")),
+                });
+                true
+            }
+            [keyword, names, css_class] if *keyword == class_keywords::CSS_CLASS => {
+                for name in quoted_strings::unquote(names)
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                {
+                    statements
+                        .css_classes
+                        .push((name.to_string(), css_class.to_string()));
+                }
+                true
+            }
+            [keyword, name, url, rest @ ..] if *keyword == class_keywords::LINK => {
+                statements.clicks.push(href(name, url, rest));
+                true
+            }
+            [keyword, name, action, url, rest @ ..]
+                if *keyword == class_keywords::CLICK && *action == class_keywords::HREF =>
+            {
+                statements.clicks.push(href(name, url, rest));
+                true
+            }
+            [keyword, name, action, callback, ..]
+                if *keyword == class_keywords::CLICK && *action == class_keywords::CALL =>
+            {
+                // The arguments may contain spaces: `call callback("a", 1) "tooltip"`
+                let text = words[3..].join(" ");
+                let end = match quoted_strings::find_unquoted(&text, ")") {
+                    Some(end) if callback.contains('(') => end + 1,
+                    _ => callback.len(),
+                };
+                statements.clicks.push(ClickEvent {
+                    node_id: name.to_string(),
+                    action: ClickAction::Callback(text[..end].to_string()),
+                });
+                true
+            }
+            _ => false,
+        };
+        remaining.push(if extracted { "" } else { line });
+    }
+
+    (remaining.join("\n"), statements)
+}
+
+/// A link to `url` from the class `name`; `rest` holds an optional quoted
+/// tooltip and an optional target such as `_blank`
+fn href(name: &str, url: &str, rest: &[&str]) -> ClickEvent {
+    let target = rest
+        .iter()
+        .find(|word| !quoted_strings::is_quoted(word))
+        .map(|target| target.to_string());
+    ClickEvent {
+        node_id: name.to_string(),
+        action: ClickAction::Href(quoted_strings::unquote(url), target),
+    }
+}
+
+/// Split a statement at whitespace outside double quotes
+fn split_words(statement: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut in_quotes = false;
+    for (pos, ch) in statement.char_indices() {
+        if ch.is_whitespace() && !in_quotes {
+            if let Some(start) = start.take() {
+                words.push(&statement[start..pos]);
+            }
+        } else {
+            in_quotes ^= ch == '"';
+            start.get_or_insert(pos);
+        }
+    }
+    if let Some(start) = start {
+        words.push(&statement[start..]);
+    }
+    words
+}
+
 crate::create_parser_fn! {
-    pub fn parse(input: &str) -> Result<ClassDiagram> {
+    fn parse_statements(input: &str) -> Result<ClassDiagram> {
         lexer: class_lexer,
         parser: class_parser,
         diagram_type: "class"
     }
 }
 
+pub fn parse(input: &str) -> Result<ClassDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
+    let (input, statements) = extract_line_statements(input);
+    let mut diagram = parse_statements(&input)?;
+
+    for (name, css_class) in statements.css_classes {
+        if let Some(class) = diagram.classes.get_mut(&name) {
+            class.css_class = Some(css_class);
+        }
+    }
+    diagram.styles = statements.styles;
+    diagram.clicks = statements.clicks;
+
+    Ok(diagram)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,6 +637,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lexer_quoted_label_with_relationship_symbols() {
+        let input = "Animal <|-- Dog : \"is a <|-- thing\"";
+        let tokens = class_lexer().parse(input).into_result().unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                ClassToken::Identifier("Animal".to_string()),
+                ClassToken::Inheritance,
+                ClassToken::Identifier("Dog".to_string()),
+                ClassToken::Colon,
+                ClassToken::QuotedString("is a <|-- thing".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_parser_basic_class() {
         let input = "classDiagram\nclass Animal";
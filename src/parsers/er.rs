@@ -1,9 +1,31 @@
 //! Entity Relationship diagram parser implementation
+//!
+//! [`parse`] follows Mermaid's documented syntax. Diagrams found in the wild
+//! often deviate from it in small ways that Mermaid's own renderer tolerates
+//! or that authors expect it to; [`parse_with_dialect`] with
+//! [`ErDialect::Relaxed`] accepts those and reports each relaxation it used,
+//! so the files can later be normalized by printing the parsed diagram.
+//!
+//! ```rust
+//! use mermaid_parser::parsers::er::{parse_with_dialect, ErDialect};
+//!
+//! let input = "erDiagram\n    CUSTOMER ||--o{ ORDER places\n    CUSTOMER {\n        string id pk,\n    }";
+//! let (diagram, relaxations) = parse_with_dialect(input, ErDialect::Relaxed)?;
+//! assert_eq!(diagram.relationships[0].label.as_deref(), Some("places"));
+//! assert_eq!(relaxations.len(), 3);
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
 
 use crate::common::ast::{
-    AccessibilityInfo, Attribute, CardinalityValue, Entity, ErCardinality, ErDiagram,
-    ErRelationship, KeyType,
+    AccessibilityInfo, Attribute, CardinalityValue, ClassDef, Entity, ErCardinality, ErDiagram,
+    ErRelationship, KeyType, StyleDefinition, StyleTarget,
 };
+use crate::common::lexer;
+use crate::common::ordered_map::OrderedMap;
+use crate::common::parser_utils::{self, parse_identifier};
+use crate::common::parsing::key_value::parse_style_properties;
+use crate::common::parsing::lines;
+use crate::common::validation::{Location, Severity, ValidationError};
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
 use std::collections::HashMap;
@@ -13,9 +35,10 @@ pub enum ERToken {
     ERDiagram,          // "erDiagram"
     EntityName(String), // Entity identifier
     EntityAlias {
-        // alias[display name] or alias["display name"]
+        // alias[display name] or alias["display name"], optionally with :::class
         alias: String,
         name: String,
+        class: Option<String>,
     },
     RelSymbol(String),     // ||--||, ||--o{, etc.
     Label(String),         // : "relationship label"
@@ -116,6 +139,11 @@ fn er_lexer<'src>() -> impl Parser<'src, &'src str, Vec<ERToken>, extra::Err<Sim
         just("}o..||").to("many-to-one-optional"),
         just("||..o{").to("one-to-many-optional"),
         just("||..||").to("one-to-one-optional"),
+        // Any other pairing of crow's foot ends, kept as the raw symbol
+        choice((just("||"), just("|o"), just("}|"), just("}o")))
+            .then(choice((just("--"), just(".."))))
+            .then(choice((just("||"), just("o|"), just("|{"), just("o{"))))
+            .to_slice(),
     ))
     .map(|s: &str| ERToken::RelSymbol(s.to_string()));
 
@@ -169,36 +197,35 @@ fn er_lexer<'src>() -> impl Parser<'src, &'src str, Vec<ERToken>, extra::Err<Sim
         });
 
     // Quoted string
-    let quoted_string = just('"')
-        .ignore_then(none_of('"').repeated().collect::<String>())
-        .then_ignore(just('"'))
-        .map(ERToken::QuotedString);
+    let quoted_string = lexer::quoted_string().map(ERToken::QuotedString);
 
     // Entity alias: alias[name] or alias["quoted name"]
-    let entity_alias = text::ident()
+    let entity_alias = parse_identifier()
         .then_ignore(just('['))
         .then(choice((
             // Quoted name: "Customer Account"
-            just('"')
-                .ignore_then(none_of('"').repeated().collect::<String>())
-                .then_ignore(just('"')),
+            lexer::quoted_string(),
             // Unquoted name: Person
             none_of(']').repeated().collect::<String>(),
         )))
         .then_ignore(just(']'))
-        .map(|(alias, name): (&str, String)| ERToken::EntityAlias {
-            alias: alias.to_string(),
-            name: name.trim().to_string(),
-        });
+        .then(just(":::").ignore_then(parse_identifier()).or_not())
+        .map(
+            |((alias, name), class): ((&str, String), Option<&str>)| ERToken::EntityAlias {
+                alias: alias.to_string(),
+                name: name.trim().to_string(),
+                class: class.map(str::to_string),
+            },
+        );
 
     // Class assignment: entity:::class
     let class_assignment = choice((
         // Hyphenated entity with class: LINE-ITEM:::foo
-        text::ident()
+        parse_identifier()
             .then_ignore(just('-'))
-            .then(text::ident())
+            .then(parse_identifier())
             .then_ignore(just(":::"))
-            .then(text::ident())
+            .then(parse_identifier())
             .map(
                 |((first, second), class): ((&str, &str), &str)| ERToken::ClassAssignment {
                     entity: format!("{}-{}", first, second),
@@ -206,9 +233,9 @@ fn er_lexer<'src>() -> impl Parser<'src, &'src str, Vec<ERToken>, extra::Err<Sim
                 },
             ),
         // Regular entity with class: PERSON:::foo
-        text::ident()
+        parse_identifier()
             .then_ignore(just(":::"))
-            .then(text::ident())
+            .then(parse_identifier())
             .map(|(entity, class): (&str, &str)| ERToken::ClassAssignment {
                 entity: entity.to_string(),
                 class: class.to_string(),
@@ -218,12 +245,12 @@ fn er_lexer<'src>() -> impl Parser<'src, &'src str, Vec<ERToken>, extra::Err<Sim
     // Entity/Attribute names (identifiers)
     let identifier = choice((
         // Hyphenated identifier like LINE-ITEM
-        text::ident()
+        parse_identifier()
             .then(just('-'))
-            .then(text::ident())
+            .then(parse_identifier())
             .map(|((first, _), second)| format!("{}-{}", first, second)),
         // Regular identifier
-        text::ident().map(|s: &str| s.to_string()),
+        parse_identifier().map(|s: &str| s.to_string()),
     ))
     .map(ERToken::EntityName);
 
@@ -234,7 +261,7 @@ fn er_lexer<'src>() -> impl Parser<'src, &'src str, Vec<ERToken>, extra::Err<Sim
     let newline = just('\n').to(ERToken::NewLine);
 
     // Helper to ensure keywords are not followed by word characters
-    let keyword = |s: &'static str| just(s).then_ignore(text::ident().not().rewind());
+    let keyword = |s: &'static str| just(s).then_ignore(parse_identifier().not().rewind());
 
     // Natural language relationship keywords
     let to_keyword = keyword("to").to(ERToken::To);
@@ -327,8 +354,8 @@ fn er_parser<'src>(
     let entity_name = any().try_map(|t, span| {
         match t {
             ERToken::EntityName(name) => Ok(name),
-            ERToken::EntityAlias { alias, name: _ } => Ok(alias), // Use alias for relationships
-            ERToken::ClassAssignment { entity, class: _ } => Ok(entity), // Use entity name, ignore class for now
+            ERToken::EntityAlias { alias, .. } => Ok(alias), // Use alias for relationships
+            ERToken::ClassAssignment { entity, class: _ } => Ok(entity), // Classes are applied by `parse`
             ERToken::QuotedString(name) => Ok(name), // Support quoted entity names
             _ => Err(Simple::new(Some(t.into()), span)),
         }
@@ -336,7 +363,7 @@ fn er_parser<'src>(
 
     // Parse entity alias specifically for entity definitions
     let entity_alias = any().try_map(|t, span| match t {
-        ERToken::EntityAlias { alias, name } => Ok((alias, name)),
+        ERToken::EntityAlias { alias, name, .. } => Ok((alias, name)),
         _ => Err(Simple::new(Some(t.into()), span)),
     });
 
@@ -413,9 +440,11 @@ fn er_parser<'src>(
                     .collect::<Vec<_>>(),
             )
             .then_ignore(just(ERToken::RightBrace))
-            .map(|((alias, _display_name), attributes)| Entity {
+            .map(|((alias, display_name), attributes)| Entity {
                 name: alias, // Use alias as the entity identifier
                 attributes,
+                css_class: None,
+                display_name: Some(display_name),
             }),
         // Regular entity: ENTITY { attributes } or "Entity Name" { attributes }
         entity_name
@@ -430,7 +459,12 @@ fn er_parser<'src>(
                     .collect::<Vec<_>>(),
             )
             .then_ignore(just(ERToken::RightBrace))
-            .map(|(name, attributes)| Entity { name, attributes }),
+            .map(|(name, attributes)| Entity {
+                name,
+                attributes,
+                css_class: None,
+                display_name: None,
+            }),
     ));
 
     // Parse relationship symbol and convert to cardinality
@@ -574,6 +608,8 @@ fn er_parser<'src>(
     let standalone_entity = entity_name.map(|name| Entity {
         name,
         attributes: Vec::new(),
+        css_class: None,
+        display_name: None,
     });
 
     // Parse diagram content - include accessibility directives, style, and classDef
@@ -592,14 +628,21 @@ fn er_parser<'src>(
     .collect::<Vec<_>>();
 
     header.ignore_then(content).then_ignore(end()).map(|items| {
-        let mut entities = HashMap::new();
+        let mut entities = OrderedMap::new();
         let mut relationships = Vec::new();
         let mut acc_title = None;
         let mut acc_descr = None;
+        let mut styles = Vec::new();
+        let mut class_defs: HashMap<String, ClassDef> = HashMap::new();
 
-        for (entity_opt, rel_opt, title_opt, descr_opt, _style_opt, _class_def_opt) in items {
+        for (entity_opt, rel_opt, title_opt, descr_opt, style_opt, class_def_opt) in items {
+            // An entity named again, such as `CUSTOMER:::vip` after its
+            // attribute block, keeps the attributes it already has
             if let Some(entity) = entity_opt {
-                entities.insert(entity.name.clone(), entity);
+                entities
+                    .entry(entity.name.clone())
+                    .and_modify(|existing| existing.attributes.extend(entity.attributes.clone()))
+                    .or_insert(entity);
             }
             if let Some(rel) = rel_opt {
                 relationships.push(rel);
@@ -610,7 +653,38 @@ fn er_parser<'src>(
             if let Some(descr) = descr_opt {
                 acc_descr = Some(descr);
             }
-            // Style and classDef directives are parsed but not stored in the AST currently
+            // style CUSTOMER,ORDER fill:#f9f,stroke:#333
+            if let Some((ids, properties)) = style_opt
+                .as_deref()
+                .and_then(|style| style.split_once(char::is_whitespace))
+            {
+                let properties = parse_style_properties(properties.trim());
+                for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                    styles.push(StyleDefinition {
+                        target: StyleTarget::Node(id.to_string()),
+                        styles: properties.clone(),
+                    });
+                }
+            }
+            // classDef important,urgent stroke:#f00
+            if let Some((names, properties)) = class_def_opt
+                .as_deref()
+                .and_then(|class_def| class_def.split_once(char::is_whitespace))
+            {
+                let properties = parse_style_properties(properties.trim());
+                for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    let class_def =
+                        class_defs
+                            .entry(name.to_string())
+                            .or_insert_with(|| ClassDef {
+                                name: name.to_string(),
+                                styles: HashMap::new(),
+                                definitions: 0,
+                            });
+                    class_def.styles.extend(properties.clone());
+                    class_def.definitions += 1;
+                }
+            }
         }
 
         ErDiagram {
@@ -621,13 +695,15 @@ fn er_parser<'src>(
             },
             entities,
             relationships,
+            styles,
+            class_defs,
         }
     })
 }
 
 fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
     match symbol {
-        "one-to-one" | "||--||" => (
+        "one-to-one" | "||--||" | "one-to-one-optional" | "||..||" => (
             ErCardinality {
                 min: CardinalityValue::One,
                 max: CardinalityValue::One,
@@ -637,7 +713,7 @@ fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
                 max: CardinalityValue::One,
             },
         ),
-        "one-to-many" | "||--o{" => (
+        "one-to-many" | "||--o{" | "one-to-many-optional" | "||..o{" => (
             ErCardinality {
                 min: CardinalityValue::One,
                 max: CardinalityValue::One,
@@ -647,7 +723,7 @@ fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
                 max: CardinalityValue::Many,
             },
         ),
-        "many-to-one" | "}o--||" => (
+        "many-to-one" | "}o--||" | "many-to-one-optional" | "}o..||" => (
             ErCardinality {
                 min: CardinalityValue::Zero,
                 max: CardinalityValue::Many,
@@ -657,7 +733,7 @@ fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
                 max: CardinalityValue::One,
             },
         ),
-        "many-to-many" | "}o--o{" => (
+        "many-to-many" | "}o--o{" | "many-to-many-optional" | "}o..o{" => (
             ErCardinality {
                 min: CardinalityValue::Zero,
                 max: CardinalityValue::Many,
@@ -687,15 +763,28 @@ fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
                 max: CardinalityValue::One,
             },
         ),
-        "one-or-more-to-one-or-more-optional" | "}|..|{" => (
+        "one-or-more-to-one-or-more"
+        | "}|--|{"
+        | "one-or-more-to-one-or-more-optional"
+        | "}|..|{" => (
+            ErCardinality {
+                min: CardinalityValue::One,
+                max: CardinalityValue::Many,
+            },
             ErCardinality {
                 min: CardinalityValue::One,
                 max: CardinalityValue::Many,
             },
+        ),
+        "one-or-more-to-one" | "}|--||" => (
             ErCardinality {
                 min: CardinalityValue::One,
                 max: CardinalityValue::Many,
             },
+            ErCardinality {
+                min: CardinalityValue::One,
+                max: CardinalityValue::One,
+            },
         ),
         "one-to-zero-or-one" | "||--o|" => (
             ErCardinality {
@@ -797,7 +886,7 @@ fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
                 max: CardinalityValue::Many,
             },
         ),
-        _ => (
+        _ => parse_crows_foot(symbol).unwrap_or((
             ErCardinality {
                 min: CardinalityValue::Zero,
                 max: CardinalityValue::Many,
@@ -806,38 +895,223 @@ fn parse_cardinality(symbol: &str) -> (ErCardinality, ErCardinality) {
                 min: CardinalityValue::Zero,
                 max: CardinalityValue::Many,
             },
-        ),
+        )),
+    }
+}
+
+/// Decode a raw crow's foot symbol such as `}|--o|` into its two cardinalities
+fn parse_crows_foot(symbol: &str) -> Option<(ErCardinality, ErCardinality)> {
+    let (left, right) = symbol.split_once("--").or(symbol.split_once(".."))?;
+    let end = |ends: &str| {
+        let (min, max) = match ends {
+            "|o" | "o|" => (CardinalityValue::Zero, CardinalityValue::One),
+            "||" => (CardinalityValue::One, CardinalityValue::One),
+            "}o" | "o{" => (CardinalityValue::Zero, CardinalityValue::Many),
+            "}|" | "|{" => (CardinalityValue::One, CardinalityValue::Many),
+            _ => return None,
+        };
+        Some(ErCardinality { min, max })
+    };
+    Some((end(left)?, end(right)?))
+}
+
+/// How closely [`parse_with_dialect`] follows Mermaid's documented syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErDialect {
+    /// Only the documented syntax, as accepted by [`parse`]
+    #[default]
+    MermaidStrict,
+    /// Also accept trailing commas after attributes, lowercase key types
+    /// (`pk`, `fk`, `uk`) and relationship labels without the `:` before them
+    Relaxed,
+}
+
+/// Rules reported by [`parse_with_dialect`] for each relaxation it used
+pub const RELAXATION_RULES: &[&str] = &[
+    "er_trailing_comma",
+    "er_lowercase_key_type",
+    "er_missing_label_colon",
+];
+
+/// Parse an ER diagram in the given dialect, returning the diagram and a
+/// warning for every relaxation used. The warnings are always empty for
+/// [`ErDialect::MermaidStrict`].
+pub fn parse_with_dialect(
+    input: &str,
+    dialect: ErDialect,
+) -> Result<(ErDiagram, Vec<ValidationError>)> {
+    match dialect {
+        ErDialect::MermaidStrict => parse(input).map(|diagram| (diagram, Vec::new())),
+        ErDialect::Relaxed => {
+            let (normalized, relaxations) = relax(input);
+            parse(&normalized).map(|diagram| (diagram, relaxations))
+        }
     }
 }
 
+/// Rewrite relaxed syntax into the documented form, line by line so line
+/// numbers in the reported warnings match `input`
+fn relax(input: &str) -> (String, Vec<ValidationError>) {
+    let mut relaxations = Vec::new();
+    let mut in_entity = false;
+    let mut output = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let warn = |rule: &'static str, message: String| {
+            ValidationError::with_location(
+                rule,
+                message,
+                Severity::Warning,
+                Location::new(index + 1, line.len() - line.trim_start().len() + 1),
+            )
+        };
+        let trimmed = line.trim();
+        if lines::should_skip_line(line) {
+            output.push(line.to_string());
+            continue;
+        }
+
+        if in_entity {
+            if trimmed.starts_with('}') {
+                in_entity = false;
+                output.push(line.to_string());
+                continue;
+            }
+
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let mut attribute = trimmed.to_string();
+            if let Some(stripped) = attribute.strip_suffix(',') {
+                relaxations.push(warn(
+                    "er_trailing_comma",
+                    "Trailing comma after attribute".to_string(),
+                ));
+                attribute = stripped.trim_end().to_string();
+            }
+
+            // Key types come after the type and name, before any quoted comment
+            let (fields, comment) =
+                attribute.split_at(attribute.find('"').unwrap_or(attribute.len()));
+            let mut words: Vec<String> = fields.split_whitespace().map(str::to_string).collect();
+            for word in words.iter_mut().skip(2) {
+                let key = word.trim_end_matches(',');
+                if matches!(key, "pk" | "fk" | "uk") {
+                    relaxations.push(warn(
+                        "er_lowercase_key_type",
+                        format!("Lowercase key type `{}`; use `{}`", key, key.to_uppercase()),
+                    ));
+                    *word = word.to_uppercase();
+                }
+            }
+            if !comment.is_empty() {
+                words.push(comment.to_string());
+            }
+            output.push(format!("{}{}", indent, words.join(" ")));
+            continue;
+        }
+
+        if trimmed.ends_with('{') {
+            in_entity = true;
+            output.push(line.to_string());
+            continue;
+        }
+
+        // ENTITY1 <symbol> ENTITY2 label  ->  ENTITY1 <symbol> ENTITY2 : label
+        let words: Vec<&str> = trimmed.split_whitespace().collect();
+        let is_symbol =
+            |word: &str| word.len() >= 5 && (word.contains("--") || word.contains(".."));
+        if words.len() >= 4
+            && is_symbol(words[1])
+            && !words[2].ends_with(':')
+            && !words[3].starts_with(':')
+        {
+            relaxations.push(warn(
+                "er_missing_label_colon",
+                format!(
+                    "Missing `:` before relationship label `{}`",
+                    words[3..].join(" ")
+                ),
+            ));
+            let indent = &line[..line.len() - line.trim_start().len()];
+            output.push(format!(
+                "{}{} {} {} : {}",
+                indent,
+                words[0],
+                words[1],
+                words[2],
+                words[3..].join(" ")
+            ));
+            continue;
+        }
+
+        output.push(line.to_string());
+    }
+
+    (output.join("\n"), relaxations)
+}
+
 pub fn parse(input: &str) -> Result<ErDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     // Strip metadata comments before parsing
     let clean_input = crate::common::lexer::strip_metadata_comments(input);
 
-    let tokens =
-        er_lexer()
-            .parse(&clean_input)
-            .into_result()
-            .map_err(|e| ParseError::SyntaxError {
+    let lexer = er_lexer();
+    let tokens = lexer.parse(&clean_input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            &clean_input,
+            &e,
+            ParseError::SyntaxError {
                 message: "Failed to tokenize ER diagram".to_string(),
                 expected: vec![],
                 found: format!("{:?}", e),
                 line: 0,
                 column: 0,
-            })?;
+                partial: None,
+            },
+        )
+    })?;
 
-    let result =
-        er_parser()
-            .parse(&tokens[..])
-            .into_result()
-            .map_err(|e| ParseError::SyntaxError {
+    let mut diagram = er_parser().parse(&tokens[..]).into_result().map_err(|e| {
+        parser_utils::token_failure(
+            &tokens,
+            &e,
+            ParseError::SyntaxError {
                 message: "Failed to parse ER diagram".to_string(),
                 expected: vec![],
                 found: format!("{:?}", e),
                 line: 0,
                 column: 0,
+                partial: None,
+            },
+        )
+    })?;
+
+    // `ENTITY:::class` and `alias[Name]` may appear in an entity definition, a
+    // relationship or on their own; the last class or name given to an entity
+    // wins
+    for token in &tokens {
+        let (entity, class, display_name) = match token {
+            ERToken::ClassAssignment { entity, class } => (entity, Some(class), None),
+            ERToken::EntityAlias { alias, name, class } => (alias, class.as_ref(), Some(name)),
+            _ => continue,
+        };
+        let entity = diagram
+            .entities
+            .entry(entity.clone())
+            .or_insert_with(|| Entity {
+                name: entity.clone(),
+                attributes: Vec::new(),
+                css_class: None,
+                display_name: None,
             });
-    result
+        if let Some(class) = class {
+            entity.css_class = Some(class.clone());
+        }
+        if let Some(display_name) = display_name {
+            entity.display_name = Some(display_name.clone());
+        }
+    }
+    Ok(diagram)
 }
 
 #[cfg(test)]
@@ -886,7 +1160,7 @@ mod tests {
         let tokens = tokens.unwrap();
 
         // Should include: CUSTOMER, {, newline, string, name, PK, newline, }
-        let expected_tokens = vec![
+        let expected_tokens = [
             ERToken::EntityName("CUSTOMER".to_string()),
             ERToken::LeftBrace,
             ERToken::NewLine,
@@ -9,9 +9,22 @@
 //!
 //! - **Directions**: `TD`, `TB`, `BT`, `LR`, `RL`
 //! - **Node shapes**: rectangles `[text]`, diamonds `{text}`, circles `((text))`, etc.
-//! - **Edge types**: solid `-->`, dotted `-.->`, thick `==>`, with labels
+//! - **Node metadata**: `A@{ shape: diam, label: "Ok?" }` and image nodes
+//!   `A@{ img: "https://example.com/a.png", w: 60, h: 60 }`
+//! - **Edge types**: solid `-->`, dotted `-.->`, thick `==>` and invisible `~~~`
+//!   links, with `>`, `o` or `x` heads at one or both ends (`<-->`, `o--o`,
+//!   `x--x`) and labels
 //! - **Subgraphs**: nested diagram sections
-//! - **Styling**: CSS classes, inline styles, click events
+//! - **Styling**: CSS classes (`A:::name`, `class A,B name`), inline styles, click events
+//!
+//! ## Style scoping
+//!
+//! `style id ...` targets a subgraph when `id` names a subgraph anywhere in the
+//! diagram, and a node otherwise. `classDef` is global, as in Mermaid: a class
+//! defined inside a subgraph applies to the whole diagram. When a class name is
+//! defined more than once, at the top level or inside subgraphs, the
+//! definitions are merged in source order and a later property value replaces
+//! an earlier one.
 //!
 //! ## Features
 //!
@@ -45,14 +58,25 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+use crate::common::arrow_table::{self, ArrowKind};
 use crate::common::ast::{
-    AccessibilityInfo, EdgeType, FlowDirection, FlowEdge, FlowNode, FlowchartDiagram, NodeShape,
+    AccessibilityInfo, ClassDef, EdgeType, FlowDirection, FlowEdge, FlowNode, FlowchartDiagram,
+    ImagePosition, NodeShape, StyleDefinition, StyleTarget,
 };
-use crate::common::constants::{directions, flowchart_keywords};
-use crate::common::parser_utils::{parse_comment, parse_whitespace};
-use crate::error::Result;
+use crate::common::constants::{directions, directives, flowchart_keywords};
+use crate::common::frontmatter::{self, ConfigValue};
+use crate::common::ordered_map::OrderedMap;
+use crate::common::parser_utils::{
+    self, is_identifier_continue, parse_accessibility_directives, parse_comment, parse_identifier,
+    parse_whitespace,
+};
+use crate::common::parsing::key_value::parse_style_properties;
+use crate::common::parsing::{lines, quoted_strings};
+use crate::common::syntax::{ArrowSyntax, ShapeSyntax, SyntaxSummary};
+use crate::error::{ParseError, Result};
+use crate::stable::DiagramKind;
 use chumsky::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FlowToken {
@@ -88,14 +112,15 @@ pub enum FlowToken {
     DoubleRightBrace,  // }}
 
     // Edge components
-    Dash,     // -
-    DashDash, // --
-    Arrow,    // > or -->
+    Dash,           // -
+    DashDash,       // --
+    Link(EdgeType), // -->, o--o, ~~~, ...
 
     // Values
     NodeId(String),
     Text(String),
-    At, // @
+    ClassName(String), // :::name
+    At,                // @
 
     Comment(String),
     Semicolon,
@@ -146,14 +171,43 @@ fn flowchart_lexer<'src>(
         just('>').to(FlowToken::RightAngle),
     ));
 
+    // Links are an optional head, a line and an optional head, spelled as in the
+    // arrow table; anything else (`--` before a label, `o` as a node id) is left
+    // to the other tokens
+    let link_line = one_of("<ox")
+        .or_not()
+        .then(one_of("-.=~").repeated().at_least(2));
+    let link_token =
+        |spelling: &str, span| match arrow_table::lookup(DiagramKind::Flowchart, spelling) {
+            Some(arrow_table::ArrowInfo {
+                kind: ArrowKind::Flowchart(edge_type),
+                ..
+            }) => Ok(FlowToken::Link(edge_type.clone())),
+            _ => Err(Simple::new(None, span)),
+        };
+    let link = choice((
+        link_line
+            .clone()
+            .then(one_of(">ox"))
+            .to_slice()
+            .try_map(link_token),
+        link_line.to_slice().try_map(link_token),
+    ));
+
     // Edge patterns (order matters for overlapping patterns)
     let edge_patterns = choice((
-        just("-->").to(FlowToken::Arrow),
+        link,
         just(flowchart_keywords::DOUBLE_DASH).to(FlowToken::DashDash),
         just('-').to(FlowToken::Dash),
         just('>').to(FlowToken::RightAngle),
     ));
 
+    // Quoted text is opaque: arrows and brackets inside quotes are part of the label
+    let quoted_text = just('"')
+        .ignore_then(none_of("\"\n").repeated().collect::<String>())
+        .then_ignore(just('"'))
+        .map(FlowToken::Text);
+
     // Edge labels: |text| (with optional closing |)
     let edge_label = just('|')
         .then(none_of('|').repeated().collect::<String>())
@@ -174,7 +228,20 @@ fn flowchart_lexer<'src>(
         .map(|s: String| FlowToken::Text(s.trim().to_string()));
 
     // Simple identifier
-    let identifier = text::ident().map(|s: &str| FlowToken::NodeId(s.to_string()));
+    let identifier = parse_identifier().map(|s: &str| FlowToken::NodeId(s.to_string()));
+
+    // Class assignment: A:::name (names may contain single dashes, but not an arrow)
+    let class_name = just(flowchart_keywords::CLASS_SEPARATOR)
+        .ignore_then(
+            any()
+                .filter(|c: &char| is_identifier_continue(*c))
+                .repeated()
+                .at_least(1)
+                .separated_by(just('-'))
+                .at_least(1)
+                .to_slice(),
+        )
+        .map(|name: &str| FlowToken::ClassName(name.to_string()));
 
     // Semicolon and At symbol
     let semicolon = just(';').to(FlowToken::Semicolon);
@@ -183,6 +250,7 @@ fn flowchart_lexer<'src>(
     // Combine all tokens (order matters for parsing)
     let token = choice((
         comment,
+        quoted_text,
         flowchart_keyword,
         graph_keyword,
         directions_parser,
@@ -191,6 +259,7 @@ fn flowchart_lexer<'src>(
         edge_label,
         semicolon,
         at_symbol,
+        class_name,
         identifier,
         text_chars, // Keep this last to avoid conflicts
     ));
@@ -207,103 +276,647 @@ fn flowchart_lexer<'src>(
     .collect::<Vec<_>>()
 }
 
-fn parse_node_shape(left_bracket: &FlowToken, right_bracket: &FlowToken) -> NodeShape {
-    match (left_bracket, right_bracket) {
-        (FlowToken::LeftSquare, FlowToken::RightSquare) => NodeShape::Rectangle,
-        (FlowToken::LeftParen, FlowToken::RightParen) => NodeShape::RoundedRectangle,
-        (FlowToken::LeftBrace, FlowToken::RightBrace) => NodeShape::Rhombus,
-        (FlowToken::DoubleLeftParen, FlowToken::DoubleRightParen) => NodeShape::Circle,
-        (FlowToken::TripleLeftParen, FlowToken::TripleRightParen) => NodeShape::DoubleCircle,
-        (FlowToken::DoubleLeftSquare, FlowToken::DoubleRightSquare) => NodeShape::Subroutine,
-        (FlowToken::DoubleLeftBrace, FlowToken::DoubleRightBrace) => NodeShape::Hexagon,
-        _ => NodeShape::Rectangle, // Default
+/// Node shape delimiters recognized by the parser
+const NODE_SHAPES: &[(&str, &str, NodeShape)] = &[
+    ("[", "]", NodeShape::Rectangle),
+    ("(", ")", NodeShape::RoundedRectangle),
+    ("{", "}", NodeShape::Rhombus),
+    ("((", "))", NodeShape::Circle),
+    ("(((", ")))", NodeShape::DoubleCircle),
+    ("[[", "]]", NodeShape::Subroutine),
+    ("{{", "}}", NodeShape::Hexagon),
+    ("([", "])", NodeShape::Stadium),
+    ("[(", ")]", NodeShape::Cylinder),
+    (">", "]", NodeShape::Asymmetric),
+    ("[/", "/]", NodeShape::Parallelogram),
+    ("[\\", "\\]", NodeShape::ParallelogramAlt),
+    ("[/", "\\]", NodeShape::Trapezoid),
+    ("[\\", "/]", NodeShape::TrapezoidAlt),
+];
+
+/// Mermaid v11 `@{ shape: name }` names of the shapes that also have
+/// bracket syntax. Other names become [`NodeShape::Special`].
+const SHAPE_NAMES: &[(&str, NodeShape)] = &[
+    ("rect", NodeShape::Rectangle),
+    ("process", NodeShape::Rectangle),
+    ("rectangle", NodeShape::Rectangle),
+    ("rounded", NodeShape::RoundedRectangle),
+    ("event", NodeShape::RoundedRectangle),
+    ("stadium", NodeShape::Stadium),
+    ("pill", NodeShape::Stadium),
+    ("terminal", NodeShape::Stadium),
+    ("subproc", NodeShape::Subroutine),
+    ("subprocess", NodeShape::Subroutine),
+    ("subroutine", NodeShape::Subroutine),
+    ("fr-rect", NodeShape::Subroutine),
+    ("framed-rectangle", NodeShape::Subroutine),
+    ("cyl", NodeShape::Cylinder),
+    ("cylinder", NodeShape::Cylinder),
+    ("database", NodeShape::Cylinder),
+    ("db", NodeShape::Cylinder),
+    ("circle", NodeShape::Circle),
+    ("circ", NodeShape::Circle),
+    ("dbl-circ", NodeShape::DoubleCircle),
+    ("double-circle", NodeShape::DoubleCircle),
+    ("odd", NodeShape::Asymmetric),
+    ("diam", NodeShape::Rhombus),
+    ("diamond", NodeShape::Rhombus),
+    ("decision", NodeShape::Rhombus),
+    ("question", NodeShape::Rhombus),
+    ("hex", NodeShape::Hexagon),
+    ("hexagon", NodeShape::Hexagon),
+    ("prepare", NodeShape::Hexagon),
+    ("lean-r", NodeShape::Parallelogram),
+    ("lean-right", NodeShape::Parallelogram),
+    ("in-out", NodeShape::Parallelogram),
+    ("lean-l", NodeShape::ParallelogramAlt),
+    ("lean-left", NodeShape::ParallelogramAlt),
+    ("out-in", NodeShape::ParallelogramAlt),
+    ("trap-b", NodeShape::Trapezoid),
+    ("trapezoid", NodeShape::Trapezoid),
+    ("trapezoid-bottom", NodeShape::Trapezoid),
+    ("priority", NodeShape::Trapezoid),
+    ("trap-t", NodeShape::TrapezoidAlt),
+    ("inv-trapezoid", NodeShape::TrapezoidAlt),
+    ("trapezoid-top", NodeShape::TrapezoidAlt),
+    ("manual", NodeShape::TrapezoidAlt),
+];
+
+/// Mermaid v11 shapes without bracket syntax, by their short name and the
+/// aliases Mermaid accepts for them. A [`NodeShape::Special`] always holds
+/// the short name, so `shape: document` and `shape: doc` compare equal.
+const SPECIAL_SHAPE_NAMES: &[(&str, &[&str])] = &[
+    ("bang", &[]),
+    ("bolt", &["com-link", "lightning-bolt"]),
+    ("bow-rect", &["bow-tie-rectangle", "stored-data"]),
+    ("brace-r", &[]),
+    ("braces", &[]),
+    ("cloud", &[]),
+    ("comment", &["brace", "brace-l"]),
+    ("cross-circ", &["crossed-circle", "summary"]),
+    ("curv-trap", &["curved-trapezoid", "display"]),
+    ("das", &["h-cyl", "horizontal-cylinder"]),
+    ("delay", &["half-rounded-rectangle"]),
+    (
+        "div-rect",
+        &["div-proc", "divided-process", "divided-rectangle"],
+    ),
+    ("doc", &["document"]),
+    ("docs", &["documents", "st-doc", "stacked-document"]),
+    ("f-circ", &["filled-circle", "junction"]),
+    ("flag", &["paper-tape"]),
+    ("flip-tri", &["flipped-triangle", "manual-file"]),
+    ("fork", &["join"]),
+    ("fr-circ", &["framed-circle", "stop"]),
+    ("hourglass", &["collate"]),
+    ("lin-cyl", &["disk", "lined-cylinder"]),
+    ("lin-doc", &["lined-document"]),
+    (
+        "lin-rect",
+        &[
+            "lin-proc",
+            "lined-process",
+            "lined-rectangle",
+            "shaded-process",
+        ],
+    ),
+    ("notch-pent", &["loop-limit", "notched-pentagon"]),
+    ("notch-rect", &["card", "notched-rectangle"]),
+    ("sl-rect", &["manual-input", "sloped-rectangle"]),
+    ("sm-circ", &["small-circle", "start"]),
+    ("st-rect", &["processes", "procs", "stacked-rectangle"]),
+    ("tag-doc", &["tag-document", "tagged-document"]),
+    (
+        "tag-rect",
+        &["tag-proc", "tagged-process", "tagged-rectangle"],
+    ),
+    ("text", &[]),
+    ("tri", &["extract", "triangle"]),
+    ("win-pane", &["internal-storage", "window-pane"]),
+];
+
+/// The shape an `@{ shape: name }` name stands for. Names missing from both
+/// tables are kept as they are, so shapes added by newer Mermaid versions
+/// still parse.
+fn shape_from_name(name: &str) -> NodeShape {
+    if let Some((_, shape)) = SHAPE_NAMES
+        .iter()
+        .find(|(shape_name, _)| *shape_name == name)
+    {
+        return shape.clone();
+    }
+    let canonical = SPECIAL_SHAPE_NAMES
+        .iter()
+        .find(|(short, aliases)| *short == name || aliases.contains(&name))
+        .map_or(name, |(short, _)| *short);
+    NodeShape::Special(canonical.to_string())
+}
+
+/// `@{ ... }` keys that describe edges (`e1@{ animate: true }`) rather than nodes
+const EDGE_METADATA_KEYS: &[&str] = &["animate", "animation", "curve"];
+
+/// The kind of edge a link token draws, or `None` if the token is not a link
+fn link_type(token: &FlowToken) -> Option<EdgeType> {
+    match token {
+        FlowToken::Link(edge_type) => Some(edge_type.clone()),
+        _ => None,
+    }
+}
+
+/// The headers, directions, arrows and node shapes this parser recognizes
+pub fn syntax_summary() -> SyntaxSummary {
+    SyntaxSummary {
+        headers: vec![flowchart_keywords::FLOWCHART, flowchart_keywords::GRAPH],
+        keywords: vec![flowchart_keywords::CLASS],
+        directions: directions::ALL_DIRECTIONS.to_vec(),
+        arrows: arrow_table::arrows_for(DiagramKind::Flowchart)
+            .into_iter()
+            .filter_map(|arrow| match &arrow.kind {
+                ArrowKind::Flowchart(edge_type) => Some(ArrowSyntax {
+                    token: arrow.spelling,
+                    kind: format!("{:?}", edge_type),
+                }),
+                _ => None,
+            })
+            .collect(),
+        shapes: NODE_SHAPES
+            .iter()
+            .map(|(open, close, kind)| ShapeSyntax {
+                open,
+                close,
+                kind: format!("{:?}", kind),
+            })
+            .collect(),
+    }
+}
+
+fn bracket_text(token: &FlowToken) -> Option<&'static str> {
+    match token {
+        FlowToken::LeftSquare => Some("["),
+        FlowToken::RightSquare => Some("]"),
+        FlowToken::LeftParen => Some("("),
+        FlowToken::RightParen => Some(")"),
+        FlowToken::LeftBrace => Some("{"),
+        FlowToken::RightBrace => Some("}"),
+        FlowToken::DoubleLeftSquare => Some("[["),
+        FlowToken::DoubleRightSquare => Some("]]"),
+        FlowToken::DoubleLeftParen => Some("(("),
+        FlowToken::DoubleRightParen => Some("))"),
+        FlowToken::TripleLeftParen => Some("((("),
+        FlowToken::TripleRightParen => Some(")))"),
+        FlowToken::DoubleLeftBrace => Some("{{"),
+        FlowToken::DoubleRightBrace => Some("}}"),
+        _ => None,
+    }
+}
+
+/// The opening bracket of a node definition; `>` opens the asymmetric shape
+fn opening_bracket(token: &FlowToken) -> Option<&'static str> {
+    match token {
+        FlowToken::LeftSquare
+        | FlowToken::LeftParen
+        | FlowToken::LeftBrace
+        | FlowToken::DoubleLeftSquare
+        | FlowToken::DoubleLeftParen
+        | FlowToken::TripleLeftParen
+        | FlowToken::DoubleLeftBrace => bracket_text(token),
+        FlowToken::RightAngle => Some(">"),
+        _ => None,
+    }
+}
+
+fn closing_bracket(token: &FlowToken) -> Option<&'static str> {
+    match token {
+        FlowToken::RightSquare
+        | FlowToken::RightParen
+        | FlowToken::RightBrace
+        | FlowToken::DoubleRightSquare
+        | FlowToken::DoubleRightParen
+        | FlowToken::TripleRightParen
+        | FlowToken::DoubleRightBrace => bracket_text(token),
+        _ => None,
+    }
+}
+
+/// Parse the node definition whose opening bracket is at `start`, returning
+/// its shape, its label and the position after the closing bracket, or
+/// `None` if the brackets are not closed.
+///
+/// The stadium `([text])` and cylinder `[(text)]` open with two brackets. The
+/// slashes of `[/text/]` and the other slanted shapes are lexed as part of the
+/// label, so they are moved from the label to the delimiters here.
+fn parse_node_definition(
+    tokens: &[FlowToken],
+    start: usize,
+) -> Option<(NodeShape, Option<String>, usize)> {
+    let mut open = opening_bracket(tokens.get(start)?)?.to_string();
+    let mut pos = start + 1;
+    let mut depth = 1;
+    if matches!(open.as_str(), "[" | "(") {
+        if let Some(inner @ ("[" | "(")) = tokens.get(pos).and_then(opening_bracket) {
+            open.push_str(inner);
+            pos += 1;
+            depth += 1;
+        }
+    }
+
+    // Collect text tokens and node ids until the closing brackets
+    let mut text_parts = Vec::new();
+    while let Some(FlowToken::NodeId(text) | FlowToken::Text(text)) = tokens.get(pos) {
+        text_parts.push(text.as_str());
+        pos += 1;
+    }
+    let mut close = String::new();
+    for _ in 0..depth {
+        close.push_str(closing_bracket(tokens.get(pos)?)?);
+        pos += 1;
+    }
+
+    let mut text = text_parts.join(" ");
+    let slants = ['/', '\\'];
+    if open == "[" && text.len() >= 2 && text.starts_with(slants) && text.ends_with(slants) {
+        let inner = quoted_strings::unquote(&text[1..text.len() - 1]);
+        open.push_str(&text[..1]);
+        close.insert_str(0, &text[text.len() - 1..]);
+        text = inner;
+    }
+
+    let shape = NODE_SHAPES
+        .iter()
+        .find(|(shape_open, shape_close, _)| *shape_open == open && *shape_close == close)
+        .map_or(NodeShape::Rectangle, |(_, _, shape)| shape.clone());
+    Some((shape, (!text.is_empty()).then_some(text), pos))
+}
+
+/// Collect the `:::name` class assignments starting at `pos`, returning the
+/// position after them
+fn take_class_names(tokens: &[FlowToken], mut pos: usize, classes: &mut Vec<String>) -> usize {
+    while let Some(FlowToken::ClassName(name)) = tokens.get(pos) {
+        classes.push(name.clone());
+        pos += 1;
     }
+    pos
 }
 
-fn parse_simple_node_and_edges(tokens: &[FlowToken]) -> (HashMap<String, FlowNode>, Vec<FlowEdge>) {
-    let mut nodes = HashMap::new();
+/// Insert a node definition, keeping classes assigned to it earlier and the
+/// position where it was first mentioned
+fn define_node(nodes: &mut OrderedMap<String, FlowNode>, mut node: FlowNode) {
+    if let Some(existing) = nodes.get_mut(&node.id) {
+        node.classes = std::mem::take(&mut existing.classes);
+    }
+    nodes.insert(node.id.clone(), node);
+}
+
+/// Add classes to a node. A node that has not been defined yet is created
+/// with its id as label, which is how Mermaid renders a bare id.
+fn assign_classes(nodes: &mut OrderedMap<String, FlowNode>, id: &str, classes: Vec<String>) {
+    if classes.is_empty() {
+        return;
+    }
+    let node = nodes.entry(id.to_string()).or_insert_with(|| FlowNode {
+        id: id.to_string(),
+        text: Some(id.to_string()),
+        shape: NodeShape::Rectangle,
+        classes: Vec::new(),
+        icon: None,
+    });
+    for class in classes {
+        if !node.classes.contains(&class) {
+            node.classes.push(class);
+        }
+    }
+}
+
+/// The `@{ ... }` metadata attached to a node id
+struct NodeMetadata {
+    id: String,
+    fields: BTreeMap<String, ConfigValue>,
+    line: usize,
+}
+
+/// Remove `id@{ ... }` metadata blocks from the input, keeping their
+/// newlines so the remaining lines keep their positions. The node id stays
+/// in place, so `A@{ ... } --> B` is lexed as `A --> B`.
+fn extract_node_metadata(input: &str) -> Result<(String, Vec<NodeMetadata>)> {
+    let mut output = String::with_capacity(input.len());
+    let mut metadata = Vec::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find(flowchart_keywords::METADATA_START) {
+        let id_start = rest[..start]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_identifier_continue(*c))
+            .last()
+            .map_or(start, |(index, _)| index);
+        let id = &rest[id_start..start];
+        let line = input[..input.len() - rest.len() + start]
+            .matches('\n')
+            .count()
+            + 1;
+
+        let body_start = start + 1;
+        // Unterminated metadata is left to the lexer, like an unclosed bracket
+        let Some(body_len) = closing_brace(&rest[body_start..]) else {
+            break;
+        };
+        let body = &rest[body_start..body_start + body_len];
+
+        output.push_str(&rest[..start]);
+        output.extend(body.chars().filter(|c| *c == '\n'));
+        if !id.is_empty() {
+            metadata.push(NodeMetadata {
+                id: id.to_string(),
+                fields: frontmatter::parse_mapping(body, line)?,
+                line,
+            });
+        }
+        rest = &rest[body_start + body_len..];
+    }
+
+    output.push_str(rest);
+    Ok((output, metadata))
+}
+
+/// Length of the `{ ... }` block at the start of `text`, up to and including
+/// its closing brace; braces inside quotes do not count
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Apply `@{ ... }` metadata to a node, creating it if needed
+fn apply_node_metadata(
+    nodes: &mut OrderedMap<String, FlowNode>,
+    metadata: NodeMetadata,
+) -> Result<()> {
+    let NodeMetadata {
+        id,
+        mut fields,
+        line,
+    } = metadata;
+    if fields
+        .keys()
+        .all(|key| EDGE_METADATA_KEYS.contains(&key.as_str()))
+    {
+        return Ok(());
+    }
+
+    let text = |value: ConfigValue| match value {
+        ConfigValue::String(text) => Some(text),
+        ConfigValue::Number(number) => Some(number.to_string()),
+        ConfigValue::Bool(value) => Some(value.to_string()),
+        _ => None,
+    };
+    let number = |value: Option<ConfigValue>| match value {
+        Some(ConfigValue::Number(number)) => Some(number),
+        _ => None,
+    };
+    let invalid = |key: &str, value: &str| ParseError::SyntaxError {
+        message: format!("Invalid `{}` value '{}' for node '{}'", key, value, id),
+        expected: Vec::new(),
+        found: value.to_string(),
+        line,
+        column: 1,
+        partial: None,
+    };
+
+    // A bare id is its own label, except on an image node
+    let is_image = fields.contains_key("img");
+    let node = nodes.entry(id.clone()).or_insert_with(|| FlowNode {
+        id: id.clone(),
+        text: (!is_image).then(|| id.clone()),
+        shape: NodeShape::Rectangle,
+        classes: Vec::new(),
+        icon: None,
+    });
+    if let Some(label) = fields.remove("label").and_then(text) {
+        node.text = Some(label);
+    }
+    if let Some(icon) = fields.remove("icon").and_then(text) {
+        node.icon = Some(icon);
+    }
+
+    if let Some(url) = fields.remove("img").and_then(text) {
+        let position = match fields.remove("pos").and_then(text).as_deref() {
+            None => None,
+            Some("t") => Some(ImagePosition::Top),
+            Some("b") => Some(ImagePosition::Bottom),
+            Some(other) => return Err(invalid("pos", other)),
+        };
+        let constrained = match fields.remove("constraint").and_then(text).as_deref() {
+            None => None,
+            Some("on") => Some(true),
+            Some("off") => Some(false),
+            Some(other) => return Err(invalid("constraint", other)),
+        };
+        node.shape = NodeShape::Image {
+            url,
+            position,
+            width: number(fields.remove("w")),
+            height: number(fields.remove("h")),
+            constrained,
+        };
+    } else if let Some(name) = fields.remove("shape").and_then(text) {
+        node.shape = shape_from_name(&name);
+    }
+
+    Ok(())
+}
+
+/// A `class ids names` statement: ids and names are comma separated
+struct ClassStatement {
+    ids: Vec<String>,
+    classes: Vec<String>,
+}
+
+/// Statements that occupy a whole line and are handled before lexing
+#[derive(Default)]
+struct LineStatements {
+    accessibility: AccessibilityInfo,
+    classes: Vec<ClassStatement>,
+    styles: Vec<StyleDefinition>,
+    class_defs: HashMap<String, ClassDef>,
+}
+
+/// The id declared by a `subgraph` line: `subgraph id[Title]`,
+/// `subgraph id` or `subgraph "Title"`
+fn subgraph_id(declaration: &str) -> Option<&str> {
+    let declaration = declaration.trim();
+    let id = match declaration.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => declaration
+            .split(|c: char| c == '[' || c.is_whitespace())
+            .next()?,
+    };
+    (!id.is_empty()).then_some(id)
+}
+
+/// Remove accessibility, `class`, `classDef` and `style` statements from the
+/// input, leaving blank lines so the remaining lines keep their positions.
+/// Flowcharts take their title from the frontmatter only, so `title` is left
+/// to the lexer as an ordinary node id.
+fn extract_line_statements(input: &str) -> (String, LineStatements) {
+    let mut statements = LineStatements::default();
+    let mut remaining = Vec::new();
+    let mut description_block: Option<Vec<&str>> = None;
+    let mut subgraph_ids = Vec::new();
+    let mut style_statements = Vec::new();
+
+    for line in input.lines() {
+        let trimmed = line.trim();
+
+        // `accDescr { ... }` may span several lines
+        let block_text = match (description_block.is_some(), trimmed) {
+            (true, text) => Some(text),
+            (false, text) => text.strip_prefix(directives::ACC_DESC_START),
+        };
+        if let Some(text) = block_text {
+            let block = description_block.get_or_insert_with(Vec::new);
+            match text.strip_suffix(directives::ACC_DESC_END) {
+                Some(last) => {
+                    block.push(last.trim());
+                    block.retain(|part| !part.is_empty());
+                    statements.accessibility.description = Some(block.join(" "));
+                    description_block = None;
+                }
+                None => block.push(text.trim()),
+            }
+            remaining.push(String::new());
+            continue;
+        }
+
+        // `;` separates statements like a newline; whatever is left for the
+        // lexer stays on the same line so error positions keep their line
+        let mut kept = Vec::new();
+        for statement in lines::split_statements(line, ';') {
+            let trimmed = statement.trim();
+            if parse_accessibility_directives(trimmed, &mut statements.accessibility) {
+                continue;
+            }
+
+            let words: Vec<&str> = trimmed.split_whitespace().collect();
+            match words.as_slice() {
+                [keyword, ids, classes] if *keyword == flowchart_keywords::CLASS => {
+                    let split = |list: &str| {
+                        list.split(',')
+                            .map(str::trim)
+                            .filter(|item| !item.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    };
+                    statements.classes.push(ClassStatement {
+                        ids: split(ids),
+                        classes: split(classes),
+                    });
+                }
+                [keyword, names, ..] if *keyword == flowchart_keywords::CLASS_DEF => {
+                    let properties = parse_style_properties(&words[2..].join(" "));
+                    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                        let class_def = statements
+                            .class_defs
+                            .entry(name.to_string())
+                            .or_insert_with(|| ClassDef {
+                                name: name.to_string(),
+                                styles: HashMap::new(),
+                                definitions: 0,
+                            });
+                        class_def.styles.extend(properties.clone());
+                        class_def.definitions += 1;
+                    }
+                }
+                [keyword, id, ..] if *keyword == flowchart_keywords::STYLE => {
+                    style_statements.push((id.to_string(), words[2..].join(" ")));
+                }
+                [keyword, ..] if *keyword == flowchart_keywords::SUBGRAPH => {
+                    if let Some(id) = trimmed
+                        .strip_prefix(flowchart_keywords::SUBGRAPH)
+                        .and_then(subgraph_id)
+                    {
+                        subgraph_ids.push(id.to_string());
+                    }
+                    kept.push(statement);
+                }
+                _ => kept.push(statement),
+            }
+        }
+        remaining.push(kept.join(";"));
+    }
+
+    // Subgraphs may be declared after the style that targets them
+    statements.styles = style_statements
+        .into_iter()
+        .map(|(id, properties)| StyleDefinition {
+            target: if subgraph_ids.contains(&id) {
+                StyleTarget::Subgraph(id)
+            } else {
+                StyleTarget::Node(id)
+            },
+            styles: parse_style_properties(&properties),
+        })
+        .collect();
+
+    (remaining.join("\n"), statements)
+}
+
+fn parse_simple_node_and_edges(
+    tokens: &[FlowToken],
+) -> (OrderedMap<String, FlowNode>, Vec<FlowEdge>) {
+    let mut nodes = OrderedMap::new();
     let mut edges = Vec::new();
     let mut i = 0;
 
     while i < tokens.len() {
         match &tokens[i] {
             FlowToken::NodeId(node_id) => {
-                // Check if this is a node definition: A[text...] or A{text...}, etc.
-                if i + 1 < tokens.len() {
-                    if let Some(left_bracket) = tokens.get(i + 1) {
-                        if matches!(
-                            left_bracket,
-                            FlowToken::LeftSquare
-                                | FlowToken::LeftParen
-                                | FlowToken::LeftBrace
-                                | FlowToken::DoubleLeftSquare
-                                | FlowToken::DoubleLeftParen
-                                | FlowToken::TripleLeftParen
-                                | FlowToken::DoubleLeftBrace
-                        ) {
-                            // Collect text tokens and node ids until we find the closing bracket
-                            let mut text_parts = Vec::new();
-                            let mut j = i + 2;
-                            let mut found_close = false;
-
-                            while j < tokens.len() {
-                                match &tokens[j] {
-                                    FlowToken::NodeId(text) => {
-                                        text_parts.push(text.clone());
-                                        j += 1;
-                                    }
-                                    FlowToken::Text(text) => {
-                                        text_parts.push(text.clone());
-                                        j += 1;
-                                    }
-                                    bracket
-                                        if matches!(
-                                            bracket,
-                                            FlowToken::RightSquare
-                                                | FlowToken::RightParen
-                                                | FlowToken::RightBrace
-                                                | FlowToken::DoubleRightSquare
-                                                | FlowToken::DoubleRightParen
-                                                | FlowToken::TripleRightParen
-                                                | FlowToken::DoubleRightBrace
-                                        ) =>
-                                    {
-                                        let shape = parse_node_shape(left_bracket, bracket);
-                                        let node_text = if text_parts.is_empty() {
-                                            None
-                                        } else {
-                                            Some(text_parts.join(" "))
-                                        };
-
-                                        let node = FlowNode {
-                                            id: node_id.clone(),
-                                            text: node_text,
-                                            shape,
-                                            classes: Vec::new(),
-                                            icon: None,
-                                        };
-                                        nodes.insert(node_id.clone(), node);
-                                        found_close = true;
-                                        i = j + 1;
-                                        break;
-                                    }
-                                    _ => break,
-                                }
-                            }
-
-                            if found_close {
-                                // After parsing a node, check if there's an edge following it
-                                if i < tokens.len() && matches!(tokens[i], FlowToken::Arrow) {
-                                    // Continue to edge parsing below
-                                } else {
-                                    continue;
-                                }
-                            } else {
-                                // No closing bracket found - skip this malformed node to avoid infinite loop
-                                i += 1;
-                                continue;
-                            }
-                        }
+                // Node definition: A[text], A([text]), A>text], etc.
+                if tokens.get(i + 1).and_then(opening_bracket).is_some() {
+                    let Some((shape, text, end)) = parse_node_definition(tokens, i + 1) else {
+                        // No closing bracket found - skip this malformed node to avoid infinite loop
+                        i += 1;
+                        continue;
+                    };
+                    let node = FlowNode {
+                        id: node_id.clone(),
+                        text,
+                        shape,
+                        classes: Vec::new(),
+                        icon: None,
+                    };
+                    define_node(&mut nodes, node);
+                    let mut classes = Vec::new();
+                    i = take_class_names(tokens, end, &mut classes);
+                    assign_classes(&mut nodes, node_id, classes);
+
+                    // After parsing a node, continue to edge parsing only if an edge follows it
+                    if tokens.get(i).and_then(link_type).is_none() {
+                        continue;
+                    }
+                }
+
+                // Bare node with classes: A:::name
+                if matches!(tokens.get(i + 1), Some(FlowToken::ClassName(_))) {
+                    let mut classes = Vec::new();
+                    i = take_class_names(tokens, i + 1, &mut classes);
+                    assign_classes(&mut nodes, node_id, classes);
+                    if tokens.get(i).and_then(link_type).is_none() {
+                        continue;
                     }
                 }
 
@@ -311,9 +924,9 @@ fn parse_simple_node_and_edges(tokens: &[FlowToken]) -> (HashMap<String, FlowNod
 
                 // If we just parsed a node definition, check from current position
                 // Otherwise, look for an arrow after the current node id
-                let arrow_pos = if i < tokens.len() && matches!(tokens[i], FlowToken::Arrow) {
+                let arrow_pos = if i < tokens.len() && link_type(&tokens[i]).is_some() {
                     i
-                } else if i + 1 < tokens.len() && matches!(tokens[i + 1], FlowToken::Arrow) {
+                } else if i + 1 < tokens.len() && link_type(&tokens[i + 1]).is_some() {
                     i + 1
                 } else {
                     // No arrow found, skip this node
@@ -323,6 +936,7 @@ fn parse_simple_node_and_edges(tokens: &[FlowToken]) -> (HashMap<String, FlowNod
 
                 // Extract source node ID
                 let source_id = node_id.clone();
+                let edge_type = link_type(&tokens[arrow_pos]).unwrap_or(EdgeType::ARROW);
 
                 // Look for target after arrow
                 let mut target_pos = arrow_pos + 1;
@@ -334,11 +948,10 @@ fn parse_simple_node_and_edges(tokens: &[FlowToken]) -> (HashMap<String, FlowNod
                         FlowToken::Text(label_text)
                             if label_text.starts_with('|') && label_text.ends_with('|') =>
                         {
-                            // Extract label text between |pipes|
-                            let label = label_text
-                                .trim_start_matches('|')
-                                .trim_end_matches('|')
-                                .to_string();
+                            // Extract label text between |pipes|, dropping any quotes
+                            let label = quoted_strings::unquote(
+                                label_text.trim_start_matches('|').trim_end_matches('|'),
+                            );
                             edge_label = Some(label);
                             target_pos += 1;
                         }
@@ -353,87 +966,43 @@ fn parse_simple_node_and_edges(tokens: &[FlowToken]) -> (HashMap<String, FlowNod
                         let edge = FlowEdge {
                             from: source_id,
                             to: target_id.clone(),
-                            edge_type: EdgeType::Arrow,
+                            edge_type,
                             label: edge_label,
                             min_length: None,
                         };
                         edges.push(edge);
 
                         // Check if target has node definition after it
-                        if target_pos + 1 < tokens.len() {
-                            if let Some(left_bracket) = tokens.get(target_pos + 1) {
-                                if matches!(
-                                    left_bracket,
-                                    FlowToken::LeftSquare
-                                        | FlowToken::LeftParen
-                                        | FlowToken::LeftBrace
-                                        | FlowToken::DoubleLeftSquare
-                                        | FlowToken::DoubleLeftParen
-                                        | FlowToken::TripleLeftParen
-                                        | FlowToken::DoubleLeftBrace
-                                ) {
-                                    // Parse target node definition
-                                    let mut text_parts = Vec::new();
-                                    let mut j = target_pos + 2;
-
-                                    let mut found_closing = false;
-                                    while j < tokens.len() {
-                                        match &tokens[j] {
-                                            FlowToken::NodeId(text) => {
-                                                text_parts.push(text.clone());
-                                                j += 1;
-                                            }
-                                            FlowToken::Text(text) => {
-                                                text_parts.push(text.clone());
-                                                j += 1;
-                                            }
-                                            bracket
-                                                if matches!(
-                                                    bracket,
-                                                    FlowToken::RightSquare
-                                                        | FlowToken::RightParen
-                                                        | FlowToken::RightBrace
-                                                        | FlowToken::DoubleRightSquare
-                                                        | FlowToken::DoubleRightParen
-                                                        | FlowToken::TripleRightParen
-                                                        | FlowToken::DoubleRightBrace
-                                                ) =>
-                                            {
-                                                let shape = parse_node_shape(left_bracket, bracket);
-                                                let node_text = if text_parts.is_empty() {
-                                                    None
-                                                } else {
-                                                    Some(text_parts.join(" "))
-                                                };
-
-                                                let node = FlowNode {
-                                                    id: target_id.clone(),
-                                                    text: node_text,
-                                                    shape,
-                                                    classes: Vec::new(),
-                                                    icon: None,
-                                                };
-                                                nodes.insert(target_id.clone(), node);
-                                                i = j + 1;
-                                                found_closing = true;
-                                                break;
-                                            }
-                                            _ => break,
-                                        }
-                                    }
-
-                                    if found_closing {
-                                        continue;
-                                    }
-                                    // No closing bracket found for target node - treat as malformed, skip to end
-                                    i = tokens.len(); // End parsing to avoid infinite loop
-                                    continue;
-                                }
-                            }
+                        if tokens
+                            .get(target_pos + 1)
+                            .and_then(opening_bracket)
+                            .is_some()
+                        {
+                            let Some((shape, text, end)) =
+                                parse_node_definition(tokens, target_pos + 1)
+                            else {
+                                // No closing bracket found for target node - treat as malformed, skip to end
+                                i = tokens.len(); // End parsing to avoid infinite loop
+                                continue;
+                            };
+                            let node = FlowNode {
+                                id: target_id.clone(),
+                                text,
+                                shape,
+                                classes: Vec::new(),
+                                icon: None,
+                            };
+                            define_node(&mut nodes, node);
+                            let mut classes = Vec::new();
+                            i = take_class_names(tokens, end, &mut classes);
+                            assign_classes(&mut nodes, target_id, classes);
+                            continue;
                         }
 
-                        // Skip to after the target
-                        i = target_pos + 1;
+                        // Skip to after the target and any classes assigned to it
+                        let mut classes = Vec::new();
+                        i = take_class_names(tokens, target_pos + 1, &mut classes);
+                        assign_classes(&mut nodes, target_id, classes);
                         continue;
                     }
                 }
@@ -448,13 +1017,20 @@ fn parse_simple_node_and_edges(tokens: &[FlowToken]) -> (HashMap<String, FlowNod
 }
 
 pub fn parse(input: &str) -> Result<FlowchartDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
+    let (input, metadata) = extract_node_metadata(input)?;
+    let (input, statements) = extract_line_statements(&input);
+
     // First tokenize the input
-    let tokens = flowchart_lexer().parse(input).into_result().map_err(|e| {
-        crate::error::ParseError::LexError {
+    let lexer = flowchart_lexer();
+    let tokens = lexer.parse(&input).into_result().map_err(|e| {
+        let error = crate::error::ParseError::LexError {
             message: format!("Lexer error: {:?}", e),
             line: 1,
             column: 1,
-        }
+            partial: None,
+        };
+        parser_utils::lex_failure(&lexer, &input, &e, error)
     })?;
 
     // Parse the header to get direction
@@ -480,17 +1056,25 @@ pub fn parse(input: &str) -> Result<FlowchartDiagram> {
     } else {
         &[]
     };
-    let (nodes, edges) = parse_simple_node_and_edges(remaining_tokens);
+    let (mut nodes, edges) = parse_simple_node_and_edges(remaining_tokens);
+    for metadata in metadata {
+        apply_node_metadata(&mut nodes, metadata)?;
+    }
+    for statement in statements.classes {
+        for id in &statement.ids {
+            assign_classes(&mut nodes, id, statement.classes.clone());
+        }
+    }
 
     Ok(FlowchartDiagram {
         title: None,
-        accessibility: AccessibilityInfo::default(),
+        accessibility: statements.accessibility,
         direction,
         nodes,
         edges,
         subgraphs: Vec::new(),
-        styles: Vec::new(),
-        class_defs: HashMap::new(),
+        styles: statements.styles,
+        class_defs: statements.class_defs,
         clicks: Vec::new(),
     })
 }
@@ -512,6 +1096,24 @@ mod tests {
         assert_eq!(tokens[1], FlowToken::TD);
     }
 
+    #[test]
+    fn test_class_name_lexer_stops_at_arrow() {
+        let tokens = flowchart_lexer()
+            .parse("A:::my-class-->B")
+            .into_result()
+            .unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                FlowToken::NodeId("A".to_string()),
+                FlowToken::ClassName("my-class".to_string()),
+                FlowToken::Link(EdgeType::ARROW),
+                FlowToken::NodeId("B".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_simple_flowchart() {
         let input = r#"flowchart TD
@@ -577,7 +1179,7 @@ mod tests {
         let edge = &diagram.edges[0];
         assert_eq!(edge.from, "A");
         assert_eq!(edge.to, "B");
-        assert_eq!(edge.edge_type, EdgeType::Arrow);
+        assert_eq!(edge.edge_type, EdgeType::ARROW);
     }
 
     #[test]
@@ -1,6 +1,10 @@
 use crate::common::ast::{
     AccessibilityInfo, GanttDiagram, GanttSection, GanttTask, TaskStatus, Weekday, WeekdaySettings,
 };
+use crate::common::frontmatter::{self, Frontmatter};
+use crate::common::gantt;
+use crate::common::lexer;
+use crate::common::parser_utils;
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
 
@@ -16,6 +20,7 @@ pub enum GanttToken {
     TodayMarker(String),   // "todayMarker stroke-width:5px"
     InclusiveEndDates,     // "inclusiveEndDates"
     TopAxis,               // "topAxis"
+    DisplayMode(String),   // "displayMode compact"
     WeekdayMonday,         // "weekday monday"
     WeekdayTuesday,        // "weekday tuesday"
     WeekdayWednesday,      // "weekday wednesday"
@@ -43,18 +48,33 @@ pub enum GanttToken {
 }
 
 pub fn parse(input: &str) -> Result<GanttDiagram> {
-    let tokens = gantt_lexer()
-        .parse(input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize gantt diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
-
-    parse_gantt_diagram(&tokens)
+    let (yaml, input) = frontmatter::split(input);
+    let lexer = gantt_lexer();
+    let tokens = lexer.parse(input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            input,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize gantt diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    })?;
+
+    let mut diagram = parse_gantt_diagram(&tokens)?;
+    if diagram.display_mode.is_none() {
+        // Malformed frontmatter is reported by `parse_document`, not here
+        diagram.display_mode = yaml
+            .and_then(|yaml| Frontmatter::from_yaml(yaml).ok())
+            .and_then(|frontmatter| frontmatter.config.gantt)
+            .and_then(|gantt| gantt.display_mode);
+    }
+    Ok(diagram)
 }
 
 fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
@@ -72,6 +92,7 @@ fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
             found: "end of input".to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
@@ -93,6 +114,7 @@ fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
         today_marker: None,
         inclusive_end_dates: false,
         top_axis: false,
+        display_mode: None,
         weekdays: WeekdaySettings::default(),
         sections: Vec::new(),
     };
@@ -129,6 +151,9 @@ fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
             GanttToken::TopAxis => {
                 diagram.top_axis = true;
             }
+            GanttToken::DisplayMode(mode) => {
+                diagram.display_mode = Some(mode.clone());
+            }
             GanttToken::WeekdayMonday => {
                 diagram.weekdays.start_day = Some(Weekday::Monday);
             }
@@ -150,15 +175,13 @@ fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
             GanttToken::WeekdaySunday => {
                 diagram.weekdays.start_day = Some(Weekday::Sunday);
             }
-            GanttToken::WeekendFriday => {
-                if !diagram.weekdays.weekend.contains(&Weekday::Friday) {
-                    diagram.weekdays.weekend.push(Weekday::Friday);
-                }
+            GanttToken::WeekendFriday if !diagram.weekdays.weekend.contains(&Weekday::Friday) => {
+                diagram.weekdays.weekend.push(Weekday::Friday);
             }
-            GanttToken::WeekendSaturday => {
-                if !diagram.weekdays.weekend.contains(&Weekday::Saturday) {
-                    diagram.weekdays.weekend.push(Weekday::Saturday);
-                }
+            GanttToken::WeekendSaturday
+                if !diagram.weekdays.weekend.contains(&Weekday::Saturday) =>
+            {
+                diagram.weekdays.weekend.push(Weekday::Saturday);
             }
             GanttToken::Section(name) => {
                 // Save any current section
@@ -182,7 +205,8 @@ fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
                     "Unnamed Task".to_string()
                 };
 
-                let (id, start_date, duration, status, progress) = parse_task_data(data);
+                let (id, start_date, duration, status, progress) =
+                    parse_task_data(data, diagram.date_format.as_deref());
                 let mut dependencies = Vec::new();
 
                 // Extract dependencies from data
@@ -214,22 +238,18 @@ fn parse_gantt_diagram(tokens: &[GanttToken]) -> Result<GanttDiagram> {
                     });
                 }
             }
-            GanttToken::AccTitle => {
-                // Next token should be the title value
-                if i + 1 < tokens.len() {
-                    if let GanttToken::AccTitleValue(title) = &tokens[i + 1] {
-                        diagram.accessibility.title = Some(title.clone());
-                        i += 1; // Skip the value token
-                    }
+            // Next token should be the title value
+            GanttToken::AccTitle if i + 1 < tokens.len() => {
+                if let GanttToken::AccTitleValue(title) = &tokens[i + 1] {
+                    diagram.accessibility.title = Some(title.clone());
+                    i += 1; // Skip the value token
                 }
             }
-            GanttToken::AccDescr => {
-                // Next token should be the description value
-                if i + 1 < tokens.len() {
-                    if let GanttToken::AccDescrValue(descr) = &tokens[i + 1] {
-                        diagram.accessibility.description = Some(descr.clone());
-                        i += 1; // Skip the value token
-                    }
+            // Next token should be the description value
+            GanttToken::AccDescr if i + 1 < tokens.len() => {
+                if let GanttToken::AccDescrValue(descr) = &tokens[i + 1] {
+                    diagram.accessibility.description = Some(descr.clone());
+                    i += 1; // Skip the value token
                 }
             }
             _ => {
@@ -285,6 +305,11 @@ fn gantt_lexer<'src>(
         .ignore_then(none_of("\n;").repeated().collect::<String>())
         .map(|marker| GanttToken::TodayMarker(marker.trim().to_string()));
 
+    let display_mode = text::keyword("displayMode")
+        .then(whitespace.at_least(1))
+        .ignore_then(none_of("\n#;").repeated().collect::<String>())
+        .map(|mode| GanttToken::DisplayMode(mode.trim().to_string()));
+
     let flags = choice((
         text::keyword("inclusiveEndDates").map(|_| GanttToken::InclusiveEndDates),
         text::keyword("topAxis").map(|_| GanttToken::TopAxis),
@@ -346,9 +371,7 @@ fn gantt_lexer<'src>(
 
     let href = text::keyword("href")
         .then(whitespace.at_least(1))
-        .then_ignore(just('"'))
-        .then(none_of('"').repeated().collect::<String>())
-        .then_ignore(just('"'))
+        .then(lexer::quoted_string())
         .map(|(_, url)| GanttToken::Href(url));
 
     let call = text::keyword("call")
@@ -366,6 +389,7 @@ fn gantt_lexer<'src>(
         includes,
         excludes,
         today_marker,
+        display_mode,
         flags,
         weekdays,
         date,
@@ -403,6 +427,7 @@ fn is_keyword(text: &str) -> bool {
             | "todaymarker"
             | "inclusiveenddates"
             | "topaxis"
+            | "displaymode"
             | "title"
             | "section"
             | "click"
@@ -415,6 +440,7 @@ fn is_keyword(text: &str) -> bool {
 
 fn parse_task_data(
     data: &str,
+    date_format: Option<&str>,
 ) -> (
     Option<String>,
     Option<String>,
@@ -429,16 +455,35 @@ fn parse_task_data(
     let mut duration = None;
     let mut status = TaskStatus::None;
     let mut progress = None;
+    let mut has_start = false;
+
+    let is_date = |part: &str| {
+        (part.contains("-") && part.len() == 10)
+            || date_format.is_some_and(|format| gantt::parse_date(part, format).is_some())
+    };
 
     for part in parts {
         if part.starts_with("after ") {
             // Dependency
+            has_start = true;
             continue;
         }
-        if part.contains("-") && part.len() == 10 {
-            // Date format YYYY-MM-DD
-            start_date = Some(part.to_string());
-        } else if part.ends_with("d") || part.ends_with("h") || part.ends_with("w") {
+        if part.starts_with("until ") {
+            // Ends when another task starts
+            duration = Some(part.to_string());
+        } else if is_date(part) {
+            // A second date is the end date
+            if has_start {
+                duration = Some(part.to_string());
+            } else {
+                start_date = Some(part.to_string());
+                has_start = true;
+            }
+        } else if part.ends_with("d")
+            || part.ends_with("h")
+            || part.ends_with("w")
+            || gantt::Duration::parse(part).is_some()
+        {
             // Duration
             duration = Some(part.to_string());
         } else if part == "active" {
@@ -501,12 +546,29 @@ mod tests {
 
     #[test]
     fn test_task_data_parsing() {
-        let (id, start, duration, _status, _progress) = parse_task_data("a1, 2014-01-01, 30d");
+        let (id, start, duration, _status, _progress) =
+            parse_task_data("a1, 2014-01-01, 30d", None);
         assert_eq!(id, Some("a1".to_string()));
         assert_eq!(start, Some("2014-01-01".to_string()));
         assert_eq!(duration, Some("30d".to_string()));
 
-        let (_, _, _, status, _) = parse_task_data("active, done, crit");
+        let (_, _, _, status, _) = parse_task_data("active, done, crit", None);
         assert_eq!(status, TaskStatus::Critical); // Takes last status found
+
+        // Dates follow the dateFormat, and a second date is the end date
+        let format = Some("DD/MM/YYYY HH:mm");
+        let (id, start, end, _, _) =
+            parse_task_data("t1, 01/03/2024 09:00, 01/03/2024 17:30", format);
+        assert_eq!(id, Some("t1".to_string()));
+        assert_eq!(start, Some("01/03/2024 09:00".to_string()));
+        assert_eq!(end, Some("01/03/2024 17:30".to_string()));
+
+        let (_, start, end, _, _) = parse_task_data("after t1, 2024-03-08", None);
+        assert_eq!(start, None);
+        assert_eq!(end, Some("2024-03-08".to_string()));
+
+        let (id, _, end, _, _) = parse_task_data("t2, until t3", None);
+        assert_eq!(id, Some("t2".to_string()));
+        assert_eq!(end, Some("until t3".to_string()));
     }
 }
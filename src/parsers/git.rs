@@ -1,4 +1,8 @@
-use crate::common::ast::{AccessibilityInfo, CommitType, GitBranch, GitDiagram, GitOperation};
+use crate::common::ast::{
+    AccessibilityInfo, CheckoutKeyword, CommitType, GitBranch, GitDiagram, GitOperation,
+};
+use crate::common::lexer;
+use crate::common::parser_utils::{self, parse_identifier};
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
 
@@ -8,6 +12,7 @@ pub enum GitToken {
     Commit,                // "commit"
     Branch,                // "branch"
     Checkout,              // "checkout"
+    Switch,                // "switch", an alias of "checkout"
     Merge,                 // "merge"
     CherryPick,            // "cherry-pick"
     Id(String),            // "id: value"
@@ -29,16 +34,23 @@ pub enum GitToken {
 }
 
 pub fn parse(input: &str) -> Result<GitDiagram> {
-    let tokens = git_lexer()
-        .parse(input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize git diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
+    let input = crate::common::parsing::frontmatter::strip(input);
+    let lexer = git_lexer();
+    let tokens = lexer.parse(input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            input,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize git diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    })?;
 
     parse_git_diagram(&tokens)
 }
@@ -58,6 +70,7 @@ fn parse_git_diagram(tokens: &[GitToken]) -> Result<GitDiagram> {
             found: "end of input".to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
@@ -114,9 +127,9 @@ fn parse_git_diagram(tokens: &[GitToken]) -> Result<GitDiagram> {
                 i = next_i;
                 continue;
             }
-            GitToken::Checkout => {
+            GitToken::Checkout | GitToken::Switch => {
                 let (operation, next_i) = parse_checkout_operation(tokens, i)?;
-                if let GitOperation::Checkout { ref branch } = operation {
+                if let GitOperation::Checkout { ref branch, .. } = operation {
                     _current_branch = branch.clone();
                 }
                 diagram.operations.push(operation);
@@ -135,26 +148,22 @@ fn parse_git_diagram(tokens: &[GitToken]) -> Result<GitDiagram> {
                 i = next_i;
                 continue;
             }
-            GitToken::AccTitle => {
-                // Next token should be the title value
-                if i + 1 < tokens.len() {
-                    if let GitToken::AccTitleValue(title) = &tokens[i + 1] {
-                        diagram.accessibility.title = Some(title.clone());
-                        i += 1; // Skip the value token
-                    }
+            // Next token should be the title value
+            GitToken::AccTitle if i + 1 < tokens.len() => {
+                if let GitToken::AccTitleValue(title) = &tokens[i + 1] {
+                    diagram.accessibility.title = Some(title.clone());
+                    i += 1; // Skip the value token
                 }
             }
             GitToken::AccTitleValue(title) => {
                 // Direct accessibility title value
                 diagram.accessibility.title = Some(title.clone());
             }
-            GitToken::AccDescr => {
-                // Next token should be the description value
-                if i + 1 < tokens.len() {
-                    if let GitToken::AccDescrValue(descr) = &tokens[i + 1] {
-                        diagram.accessibility.description = Some(descr.clone());
-                        i += 1; // Skip the value token
-                    }
+            // Next token should be the description value
+            GitToken::AccDescr if i + 1 < tokens.len() => {
+                if let GitToken::AccDescrValue(descr) = &tokens[i + 1] {
+                    diagram.accessibility.description = Some(descr.clone());
+                    i += 1; // Skip the value token
                 }
             }
             GitToken::AccDescrValue(descr) => {
@@ -176,6 +185,7 @@ fn parse_commit_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpera
     let mut id = None;
     let mut tag = None;
     let mut commit_type = CommitType::Normal;
+    let mut explicit_type = false;
 
     // Parse optional properties
     while i < tokens.len() {
@@ -188,11 +198,13 @@ fn parse_commit_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpera
             }
             GitToken::Type(ctype) => {
                 commit_type = ctype.clone();
+                explicit_type = true;
             }
             GitToken::NewLine
             | GitToken::Commit
             | GitToken::Branch
             | GitToken::Checkout
+            | GitToken::Switch
             | GitToken::Merge
             | GitToken::CherryPick => {
                 break;
@@ -207,6 +219,7 @@ fn parse_commit_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpera
             id,
             commit_type,
             tag,
+            explicit_type,
         },
         i,
     ))
@@ -235,6 +248,7 @@ fn parse_branch_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpera
             | GitToken::Commit
             | GitToken::Branch
             | GitToken::Checkout
+            | GitToken::Switch
             | GitToken::Merge
             | GitToken::CherryPick => {
                 break;
@@ -250,6 +264,7 @@ fn parse_branch_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpera
         found: "end of input".to_string(),
         line: 0,
         column: 0,
+        partial: None,
     })?;
 
     Ok((
@@ -262,7 +277,11 @@ fn parse_branch_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpera
 }
 
 fn parse_checkout_operation(tokens: &[GitToken], start: usize) -> Result<(GitOperation, usize)> {
-    let mut i = start + 1; // Skip "checkout"
+    let keyword = match tokens[start] {
+        GitToken::Switch => CheckoutKeyword::Switch,
+        _ => CheckoutKeyword::Checkout,
+    };
+    let mut i = start + 1; // Skip "checkout" or "switch"
 
     // Get branch name
     if i < tokens.len() {
@@ -271,6 +290,7 @@ fn parse_checkout_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpe
             return Ok((
                 GitOperation::Checkout {
                     branch: branch_name.clone(),
+                    keyword,
                 },
                 i,
             ));
@@ -283,6 +303,7 @@ fn parse_checkout_operation(tokens: &[GitToken], start: usize) -> Result<(GitOpe
         found: "end of input".to_string(),
         line: 0,
         column: 0,
+        partial: None,
     })
 }
 
@@ -292,6 +313,7 @@ fn parse_merge_operation(tokens: &[GitToken], start: usize) -> Result<(GitOperat
     let mut id = None;
     let mut tag = None;
     let mut commit_type = CommitType::Normal;
+    let mut explicit_type = false;
 
     // Get branch name
     if i < tokens.len() {
@@ -312,11 +334,13 @@ fn parse_merge_operation(tokens: &[GitToken], start: usize) -> Result<(GitOperat
             }
             GitToken::Type(ctype) => {
                 commit_type = ctype.clone();
+                explicit_type = true;
             }
             GitToken::NewLine
             | GitToken::Commit
             | GitToken::Branch
             | GitToken::Checkout
+            | GitToken::Switch
             | GitToken::Merge
             | GitToken::CherryPick => {
                 break;
@@ -332,6 +356,7 @@ fn parse_merge_operation(tokens: &[GitToken], start: usize) -> Result<(GitOperat
         found: "end of input".to_string(),
         line: 0,
         column: 0,
+        partial: None,
     })?;
 
     Ok((
@@ -340,6 +365,7 @@ fn parse_merge_operation(tokens: &[GitToken], start: usize) -> Result<(GitOperat
             id,
             tag,
             commit_type,
+            explicit_type,
         },
         i,
     ))
@@ -367,6 +393,7 @@ fn parse_cherry_pick_operation(tokens: &[GitToken], start: usize) -> Result<(Git
             | GitToken::Commit
             | GitToken::Branch
             | GitToken::Checkout
+            | GitToken::Switch
             | GitToken::Merge
             | GitToken::CherryPick => {
                 break;
@@ -382,6 +409,7 @@ fn parse_cherry_pick_operation(tokens: &[GitToken], start: usize) -> Result<(Git
         found: "end of input".to_string(),
         line: 0,
         column: 0,
+        partial: None,
     })?;
 
     Ok((
@@ -409,6 +437,8 @@ fn git_lexer<'src>() -> impl Parser<'src, &'src str, Vec<GitToken>, extra::Err<S
 
     let checkout = text::keyword("checkout").map(|_| GitToken::Checkout);
 
+    let switch = text::keyword("switch").map(|_| GitToken::Switch);
+
     let merge = text::keyword("merge").map(|_| GitToken::Merge);
 
     let cherry_pick = just("cherry")
@@ -422,9 +452,7 @@ fn git_lexer<'src>() -> impl Parser<'src, &'src str, Vec<GitToken>, extra::Err<S
         .then_ignore(just(':'))
         .padded_by(whitespace)
         .ignore_then(choice((
-            just('"')
-                .ignore_then(none_of('"').repeated().collect::<String>())
-                .then_ignore(just('"')),
+            lexer::quoted_string(),
             none_of(" \t\n").repeated().at_least(1).collect::<String>(),
         )))
         .map(GitToken::Id);
@@ -434,9 +462,7 @@ fn git_lexer<'src>() -> impl Parser<'src, &'src str, Vec<GitToken>, extra::Err<S
         .then_ignore(just(':'))
         .padded_by(whitespace)
         .ignore_then(choice((
-            just('"')
-                .ignore_then(none_of('"').repeated().collect::<String>())
-                .then_ignore(just('"')),
+            lexer::quoted_string(),
             none_of(" \t\n").repeated().at_least(1).collect::<String>(),
         )))
         .map(GitToken::Tag);
@@ -464,19 +490,17 @@ fn git_lexer<'src>() -> impl Parser<'src, &'src str, Vec<GitToken>, extra::Err<S
         .then_ignore(just(':'))
         .padded_by(whitespace)
         .ignore_then(choice((
-            just('"')
-                .ignore_then(none_of('"').repeated().collect::<String>())
-                .then_ignore(just('"')),
+            lexer::quoted_string(),
             none_of(" \t\n").repeated().at_least(1).collect::<String>(),
         )))
         .map(GitToken::Parent);
 
     // Branch and commit identifiers
-    let identifier = text::ident().map(|s: &str| GitToken::BranchName(s.to_string()));
+    let identifier = parse_identifier().map(|s: &str| GitToken::BranchName(s.to_string()));
 
     let theme = text::keyword("theme")
         .then(whitespace.at_least(1))
-        .ignore_then(text::ident())
+        .ignore_then(parse_identifier())
         .map(|s: &str| GitToken::Theme(s.to_string()));
 
     let title = text::keyword("title")
@@ -504,6 +528,7 @@ fn git_lexer<'src>() -> impl Parser<'src, &'src str, Vec<GitToken>, extra::Err<S
         commit,
         branch,
         checkout,
+        switch,
         merge,
         id_prop,
         tag_prop,
@@ -1,4 +1,6 @@
 use crate::common::ast::{AccessibilityInfo, JourneyDiagram, JourneySection, JourneyTask};
+use crate::common::parser_utils;
+use crate::common::parsing::fields;
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
 
@@ -19,21 +21,133 @@ pub enum JourneyToken {
 }
 
 pub fn parse(input: &str) -> Result<JourneyDiagram> {
-    let tokens =
-        journey_lexer()
-            .parse(input)
-            .into_result()
-            .map_err(|e| ParseError::SyntaxError {
+    let input = crate::common::parsing::frontmatter::strip(input);
+    let lexer = journey_lexer();
+    let tokens = lexer.parse(input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            input,
+            &e,
+            ParseError::SyntaxError {
                 message: "Failed to tokenize journey diagram".to_string(),
                 expected: vec![],
                 found: format!("{:?}", e),
                 line: 0,
                 column: 0,
-            })?;
+                partial: None,
+            },
+        )
+    })?;
 
     parse_journey_diagram(&tokens)
 }
 
+/// Column header written by [`to_csv`] and accepted (optionally) by [`from_csv`]
+pub const CSV_HEADER: &str = "section,task,score,actors";
+
+/// Build a journey diagram from CSV rows of `section,task,score,actors`.
+///
+/// A leading header row is optional. Consecutive rows with the same section
+/// are grouped into one section; actors may be separated by `,` or `;`
+/// within their (quoted) column.
+pub fn from_csv(input: &str) -> Result<JourneyDiagram> {
+    let mut sections: Vec<JourneySection> = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = fields::parse_csv_record(line);
+        if sections.is_empty() && is_csv_header(&record) {
+            continue;
+        }
+
+        if record.len() < 3 || record.len() > 4 {
+            return Err(ParseError::SyntaxError {
+                message: format!("Expected 3 or 4 CSV columns, found {}", record.len()),
+                expected: vec![CSV_HEADER.to_string()],
+                found: line.to_string(),
+                line: line_number,
+                column: 0,
+                partial: None,
+            });
+        }
+
+        let score = record[2]
+            .parse::<i32>()
+            .map_err(|_| ParseError::SyntaxError {
+                message: "Invalid journey task score".to_string(),
+                expected: vec!["integer score".to_string()],
+                found: record[2].clone(),
+                line: line_number,
+                column: 0,
+                partial: None,
+            })?;
+
+        let actors = record
+            .get(3)
+            .map(|actors| {
+                actors
+                    .split([',', ';'])
+                    .map(|actor| actor.trim().to_string())
+                    .filter(|actor| !actor.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let task = JourneyTask {
+            name: record[1].clone(),
+            score,
+            actors,
+        };
+
+        match sections.last_mut() {
+            Some(section) if section.name == record[0] => section.tasks.push(task),
+            _ => sections.push(JourneySection {
+                name: record[0].clone(),
+                tasks: vec![task],
+            }),
+        }
+    }
+
+    Ok(JourneyDiagram {
+        title: None,
+        accessibility: AccessibilityInfo::default(),
+        sections,
+    })
+}
+
+/// Export a journey diagram as CSV rows of `section,task,score,actors`,
+/// starting with a header row
+pub fn to_csv(diagram: &JourneyDiagram) -> String {
+    let mut output = String::from(CSV_HEADER);
+    output.push('\n');
+
+    for section in &diagram.sections {
+        for task in &section.tasks {
+            let row = [
+                fields::escape_csv_field(&section.name),
+                fields::escape_csv_field(&task.name),
+                task.score.to_string(),
+                fields::escape_csv_field(&task.actors.join(", ")),
+            ];
+            output.push_str(&row.join(","));
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+fn is_csv_header(record: &[String]) -> bool {
+    record.len() >= 3
+        && record[0].eq_ignore_ascii_case("section")
+        && record[1].eq_ignore_ascii_case("task")
+        && record[2].eq_ignore_ascii_case("score")
+}
+
 fn parse_journey_diagram(tokens: &[JourneyToken]) -> Result<JourneyDiagram> {
     let mut i = 0;
 
@@ -49,6 +163,7 @@ fn parse_journey_diagram(tokens: &[JourneyToken]) -> Result<JourneyDiagram> {
             found: "end of input".to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
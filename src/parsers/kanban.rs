@@ -11,6 +11,7 @@ struct Line {
 }
 
 pub fn parse(input: &str) -> Result<KanbanDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines = preprocess_lines(input);
     parse_kanban_diagram(lines)
 }
@@ -58,6 +59,7 @@ fn parse_kanban_diagram(lines: Vec<Line>) -> Result<KanbanDiagram> {
             found: "empty input".to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
@@ -78,6 +80,7 @@ fn parse_kanban_diagram(lines: Vec<Line>) -> Result<KanbanDiagram> {
             found: first_line.content.to_string(),
             line: first_line.line_number + 1,
             column: 1,
+            partial: None,
         });
     }
 
@@ -279,6 +282,7 @@ fn parse_assignments(content: &str) -> Result<Vec<String>> {
             found: content.to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
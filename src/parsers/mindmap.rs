@@ -1,10 +1,71 @@
 //! Mindmap diagram parser implementation
-
-use crate::common::ast::{AccessibilityInfo, MindmapDiagram, MindmapNode, MindmapNodeShape};
+//!
+//! Every node gets a hierarchical id from its position in the tree: `root`
+//! for the root and `<parent>.<n>` for the n-th child, so `root.1.3` is the
+//! third child of the first branch. The ids only change when the tree itself
+//! changes, which gives tools a stable handle on nodes that Mermaid leaves
+//! anonymous.
+//!
+//! A node written as ``id["`**bold** text`"]`` holds a Markdown string, which
+//! may continue over several lines until the closing ``"` ``. Its text is a
+//! [`NodeText::Markdown`] with the lines joined by `\n`, while other nodes get
+//! [`NodeText::Plain`].
+//!
+//! Mermaid has no syntax for links between branches. [`parse_with_dialect`]
+//! with [`MindmapDialect::CrossLinks`] also accepts `from --> to` and
+//! `from -->|label| to` lines, where each end is a hierarchical id or the
+//! text of a node, and records them in [`MindmapDiagram::cross_edges`].
+//!
+//! ```rust
+//! use mermaid_parser::parsers::mindmap::{parse_with_dialect, MindmapDialect};
+//!
+//! let input = "mindmap\n  root((Plan))\n    Design\n    Build\n    Design -->|informs| Build";
+//! let diagram = parse_with_dialect(input, MindmapDialect::CrossLinks)?;
+//! assert_eq!(diagram.node("root.2").unwrap().text, "Build");
+//! assert_eq!(diagram.cross_edges[0].from, "root.1");
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::{
+    AccessibilityInfo, MindmapCrossEdge, MindmapDiagram, MindmapNode, MindmapNodeShape, NodeText,
+};
 use crate::common::parser_utils::validate_diagram_header;
 use crate::error::{ParseError, Result};
 
+/// Id of the root node; other ids extend it
+pub const ROOT_ID: &str = "root";
+
+const MARKDOWN_OPEN: &str = "\"`";
+const MARKDOWN_CLOSE: &str = "`\"";
+
+/// Delimiters of the shapes a Markdown string can be wrapped in
+const MARKDOWN_SHAPES: &[(&str, &str, MindmapNodeShape)] = &[
+    ("", "", MindmapNodeShape::Default),
+    ("[", "]", MindmapNodeShape::Square),
+    ("(", ")", MindmapNodeShape::Rounded),
+    ("((", "))", MindmapNodeShape::Circle),
+    ("(-", "-)", MindmapNodeShape::Cloud),
+    ("))", "((", MindmapNodeShape::Bang),
+    ("{{", "}}", MindmapNodeShape::Hexagon),
+];
+
+/// Which mindmap syntax [`parse_with_dialect`] accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MindmapDialect {
+    /// Only the documented syntax, as accepted by [`parse`]
+    #[default]
+    MermaidStrict,
+    /// Also accept `-->` cross-links between nodes
+    CrossLinks,
+}
+
 pub fn parse(input: &str) -> Result<MindmapDiagram> {
+    parse_with_dialect(input, MindmapDialect::MermaidStrict)
+}
+
+/// Parse a mindmap in the given dialect
+pub fn parse_with_dialect(input: &str, dialect: MindmapDialect) -> Result<MindmapDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     // Simple string-based parsing for now
     let lines: Vec<&str> = input.lines().collect();
 
@@ -24,19 +85,37 @@ pub fn parse(input: &str) -> Result<MindmapDiagram> {
             found: lines[0].to_string(),
             line: 1,
             column: 1,
+            partial: None,
         });
     }
 
     let mut nodes = Vec::new();
+    let mut cross_links = Vec::new();
+    // A node whose Markdown string has not been closed yet
+    let mut open_markdown: Option<String> = None;
 
     // Parse each line after the mindmap header
-    for line in lines.iter().skip(1) {
+    for (index, line) in lines.iter().enumerate().skip(1) {
         let trimmed = line.trim();
-        if !trimmed.is_empty() && !trimmed.starts_with("//") && !trimmed.starts_with("%%") {
+        if let Some(node) = open_markdown.as_mut() {
+            node.push('\n');
+            node.push_str(trimmed);
+            if trimmed.contains(MARKDOWN_CLOSE) {
+                nodes.extend(open_markdown.take());
+            }
+        } else if dialect == MindmapDialect::CrossLinks && trimmed.contains(CROSS_LINK) {
+            cross_links.push((index + 1, trimmed));
+        } else if !trimmed.is_empty() && !trimmed.starts_with("//") && !trimmed.starts_with("%%") {
             // Store the original line (with indentation) for hierarchy parsing
-            nodes.push(line.to_string());
+            if opens_markdown(trimmed) {
+                open_markdown = Some(line.to_string());
+            } else {
+                nodes.push(line.to_string());
+            }
         }
     }
+    // An unterminated Markdown string runs to the end of the diagram
+    nodes.extend(open_markdown);
 
     // Parse all lines into structured data
     let mut parsed_lines = Vec::new();
@@ -52,7 +131,7 @@ pub fn parse(input: &str) -> Result<MindmapDiagram> {
     // Build the hierarchy
     let root = if let Some((_, first_parsed)) = parsed_lines.first() {
         let mut root_node = MindmapNode {
-            id: generate_id(),
+            id: ROOT_ID.to_string(),
             text: first_parsed.text.clone(),
             shape: first_parsed.shape.clone(),
             icon: first_parsed.icon.clone(),
@@ -62,12 +141,12 @@ pub fn parse(input: &str) -> Result<MindmapDiagram> {
 
         // Build children hierarchy
         let root_indent = parsed_lines[0].0;
-        root_node.children = build_children(&parsed_lines, 1, root_indent);
+        root_node.children = build_children(&parsed_lines, 1, root_indent, ROOT_ID);
         root_node
     } else {
         MindmapNode {
-            id: generate_id(),
-            text: "Root".to_string(),
+            id: ROOT_ID.to_string(),
+            text: "Root".into(),
             shape: crate::common::ast::MindmapNodeShape::Default,
             icon: None,
             class: None,
@@ -75,18 +154,75 @@ pub fn parse(input: &str) -> Result<MindmapDiagram> {
         }
     };
 
+    let cross_edges = cross_links
+        .into_iter()
+        .map(|(line, text)| parse_cross_link(&root, text, line))
+        .collect::<Result<Vec<_>>>()?;
+
     Ok(MindmapDiagram {
         title: None,
         accessibility: AccessibilityInfo::default(),
         root,
+        cross_edges,
     })
 }
 
+const CROSS_LINK: &str = "-->";
+
+/// Parse `from --> to` or `from -->|label| to`, resolving both ends to ids
+fn parse_cross_link(root: &MindmapNode, text: &str, line: usize) -> Result<MindmapCrossEdge> {
+    let error = |message: &str, found: &str| ParseError::SyntaxError {
+        message: message.to_string(),
+        expected: vec!["node id or text".to_string()],
+        found: found.to_string(),
+        line,
+        column: 1,
+        partial: None,
+    };
+
+    let (from, rest) = text
+        .split_once(CROSS_LINK)
+        .ok_or_else(|| error("Expected a cross-link", text))?;
+    let rest = rest.trim_start();
+    let (label, to) = match rest.strip_prefix('|').and_then(|r| r.split_once('|')) {
+        Some((label, to)) => (Some(label.trim().to_string()), to),
+        None => (None, rest),
+    };
+
+    let resolve = |reference: &str| {
+        let reference = reference.trim();
+        resolve_node(root, reference)
+            .ok_or_else(|| error("Unknown mindmap node in cross-link", reference))
+    };
+    Ok(MindmapCrossEdge {
+        from: resolve(from)?,
+        to: resolve(to)?,
+        label: label.filter(|label| !label.is_empty()),
+    })
+}
+
+/// The id of the node whose id is `reference`, or else of the first node,
+/// in document order, whose text is `reference`
+fn resolve_node(root: &MindmapNode, reference: &str) -> Option<String> {
+    fn walk<'a>(node: &'a MindmapNode, nodes: &mut Vec<&'a MindmapNode>) {
+        nodes.push(node);
+        node.children.iter().for_each(|child| walk(child, nodes));
+    }
+    let mut nodes = Vec::new();
+    walk(root, &mut nodes);
+
+    nodes
+        .iter()
+        .find(|node| node.id == reference)
+        .or_else(|| nodes.iter().find(|node| node.text == reference))
+        .map(|node| node.id.clone())
+}
+
 // Unused complex parser functions removed - using simple string-based parsing
 
 #[derive(Debug, Clone)]
 struct ParsedLine {
-    text: String,
+    text: NodeText,
     shape: MindmapNodeShape,
     icon: Option<String>,
     class: Option<String>,
@@ -136,6 +272,7 @@ fn build_children(
     parsed_lines: &[(usize, ParsedLine)],
     start_index: usize,
     parent_indent: usize,
+    parent_id: &str,
 ) -> Vec<MindmapNode> {
     let mut children = Vec::new();
     let mut i = start_index;
@@ -175,7 +312,7 @@ fn build_children(
 
         // This is a direct child, create the node
         let mut child_node = MindmapNode {
-            id: generate_id(),
+            id: format!("{}.{}", parent_id, children.len() + 1),
             text: parsed.text.clone(),
             shape: parsed.shape.clone(),
             icon: parsed.icon.clone(),
@@ -190,7 +327,7 @@ fn build_children(
         }
 
         // Recursively build children for this child
-        child_node.children = build_children(parsed_lines, i + 1, child_indent);
+        child_node.children = build_children(parsed_lines, i + 1, child_indent, &child_node.id);
         children.push(child_node);
 
         // Move to the next sibling
@@ -200,9 +337,57 @@ fn build_children(
     children
 }
 
-fn parse_node_text(text: &str) -> (String, MindmapNodeShape) {
+/// Whether `line` starts a Markdown string that does not end on it
+fn opens_markdown(line: &str) -> bool {
+    line.rfind(MARKDOWN_OPEN)
+        .is_some_and(|start| !line[start + MARKDOWN_OPEN.len()..].contains(MARKDOWN_CLOSE))
+}
+
+/// The Markdown string of a node such as ``id["`text`"]`` and its shape
+fn parse_markdown_node(text: &str) -> Option<(NodeText, MindmapNodeShape)> {
+    let start = text.find(MARKDOWN_OPEN)?;
+    let end = text
+        .rfind(MARKDOWN_CLOSE)
+        .filter(|end| *end >= start + MARKDOWN_OPEN.len())?;
+    let before = &text[..start];
+    let after = &text[end + MARKDOWN_CLOSE.len()..];
+
+    let opening = before.trim_start_matches(|c: char| !matches!(c, '[' | '(' | ')' | '{'));
+    let id = &before[..before.len() - opening.len()];
+    if id.contains(char::is_whitespace) {
+        return None;
+    }
+    let (_, _, shape) = MARKDOWN_SHAPES
+        .iter()
+        .find(|(open, close, _)| *open == opening && *close == after)?;
+    let markdown = text[start + MARKDOWN_OPEN.len()..end].to_string();
+    Some((NodeText::Markdown(markdown), shape.clone()))
+}
+
+/// `text` without the id in front of a `[` or `(` shape, as in `id1[Text]`
+fn strip_node_id(text: &str) -> &str {
+    match text.find(['[', '(']) {
+        Some(start)
+            if start > 0
+                && !text[..start].contains(char::is_whitespace)
+                && (text.ends_with(']') || text.ends_with(')')) =>
+        {
+            &text[start..]
+        }
+        _ => text,
+    }
+}
+
+fn parse_node_text(text: &str) -> (NodeText, MindmapNodeShape) {
     let trimmed = text.trim();
+    if let Some(node) = parse_markdown_node(trimmed) {
+        return node;
+    }
+    let (text, shape) = parse_plain_node_text(trimmed);
+    (NodeText::Plain(text), shape)
+}
 
+fn parse_plain_node_text(trimmed: &str) -> (String, MindmapNodeShape) {
     // Look for embedded shapes within the text
     if let Some(start) = trimmed.find("((") {
         if let Some(end) = trimmed.rfind("))") {
@@ -241,6 +426,7 @@ fn parse_node_text(text: &str) -> (String, MindmapNodeShape) {
     }
 
     // Check for simple bracket shapes that span the entire text
+    let trimmed = strip_node_id(trimmed);
     if trimmed.starts_with("[") && trimmed.ends_with("]") {
         let content = &trimmed[1..trimmed.len() - 1];
         (content.to_string(), MindmapNodeShape::Square)
@@ -251,9 +437,3 @@ fn parse_node_text(text: &str) -> (String, MindmapNodeShape) {
         (trimmed.to_string(), MindmapNodeShape::Default)
     }
 }
-
-fn generate_id() -> String {
-    use std::sync::atomic::{AtomicUsize, Ordering};
-    static COUNTER: AtomicUsize = AtomicUsize::new(0);
-    format!("node_{}", COUNTER.fetch_add(1, Ordering::SeqCst))
-}
@@ -1,10 +1,23 @@
 //! Miscellaneous diagram parser implementation
+//!
+//! Handles `info`, the legacy `gitGraph:` syntax and unrecognized diagram
+//! types. `%%{...}%%` directives are parsed into [`InfoDiagram::options`],
+//! and a document holding nothing but directives becomes an
+//! [`MiscContent::Info`] with an empty command.
 
 use crate::common::ast::{
     GitGraphAlt, InfoDiagram, MiscContent, MiscDiagram, MiscGitCommit, RawDiagram,
 };
+use crate::common::frontmatter::ConfigValue;
+use crate::common::lexer;
+use crate::common::parser_utils::{self, parse_identifier};
+use crate::common::parsing::directives;
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
+use std::collections::BTreeMap;
+
+/// `diagram_type` of a document holding only directives
+pub const DIRECTIVES_TYPE: &str = "directives";
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum MiscToken {
@@ -48,13 +61,10 @@ fn misc_lexer<'src>() -> impl Parser<'src, &'src str, Vec<MiscToken>, extra::Err
         .map(|_| MiscToken::Comment("".to_string()));
 
     // Quoted string
-    let quoted_string = just('"')
-        .ignore_then(none_of('"').repeated().collect::<String>())
-        .then_ignore(just('"'))
-        .map(MiscToken::QuotedString);
+    let quoted_string = lexer::quoted_string().map(MiscToken::QuotedString);
 
     // Identifier or keyword
-    let identifier = text::ident().map(|s: &str| {
+    let identifier = parse_identifier().map(|s: &str| {
         // Check if it's a known keyword
         match s {
             "info" => MiscToken::Info,
@@ -97,8 +107,23 @@ fn misc_lexer<'src>() -> impl Parser<'src, &'src str, Vec<MiscToken>, extra::Err
 }
 
 fn misc_parser<'src>(
+    directives: Option<BTreeMap<String, ConfigValue>>,
+    source_lines: Vec<String>,
 ) -> impl Parser<'src, &'src [MiscToken], MiscDiagram, extra::Err<Simple<'src, MiscToken>>> {
-    any().repeated().collect::<Vec<_>>().map(|tokens| {
+    any().repeated().collect::<Vec<_>>().map(move |tokens| {
+        let only_comments = tokens
+            .iter()
+            .all(|token| matches!(token, MiscToken::Comment(_) | MiscToken::NewLine));
+        if let (true, Some(options)) = (only_comments, &directives) {
+            return MiscDiagram {
+                diagram_type: DIRECTIVES_TYPE.to_string(),
+                content: MiscContent::Info(InfoDiagram {
+                    command: String::new(),
+                    options: options.clone(),
+                }),
+            };
+        }
+
         if tokens.is_empty() {
             return MiscDiagram {
                 diagram_type: "empty".to_string(),
@@ -119,14 +144,16 @@ fn misc_parser<'src>(
         }
 
         match first_token {
-            Some(MiscToken::Info) => parse_info_diagram(&tokens),
+            Some(MiscToken::Info) => {
+                parse_info_diagram(&tokens, directives.clone().unwrap_or_default())
+            }
             Some(MiscToken::GitGraph) => parse_gitgraph_alt(&tokens),
-            _ => parse_raw_diagram(&tokens),
+            _ => parse_raw_diagram(&tokens, source_lines.clone()),
         }
     })
 }
 
-fn parse_info_diagram(tokens: &[MiscToken]) -> MiscDiagram {
+fn parse_info_diagram(tokens: &[MiscToken], options: BTreeMap<String, ConfigValue>) -> MiscDiagram {
     let mut command = String::new();
     let mut found_info = false;
 
@@ -151,7 +178,7 @@ fn parse_info_diagram(tokens: &[MiscToken]) -> MiscDiagram {
 
     MiscDiagram {
         diagram_type: "info".to_string(),
-        content: MiscContent::Info(InfoDiagram { command }),
+        content: MiscContent::Info(InfoDiagram { command, options }),
     }
 }
 
@@ -267,78 +294,141 @@ fn parse_gitgraph_alt(tokens: &[MiscToken]) -> MiscDiagram {
     }
 }
 
-fn parse_raw_diagram(tokens: &[MiscToken]) -> MiscDiagram {
-    let mut lines = Vec::new();
-    let mut current_line = Vec::new();
+/// Unrecognized diagrams keep their source lines, so printing reproduces
+/// them; the first identifier names the diagram type
+fn parse_raw_diagram(tokens: &[MiscToken], lines: Vec<String>) -> MiscDiagram {
+    let diagram_type = tokens
+        .iter()
+        .find_map(|token| match token {
+            MiscToken::Comment(_) | MiscToken::NewLine => None,
+            MiscToken::Identifier(name) | MiscToken::Keyword(name) => Some(name.clone()),
+            _ => Some("unknown".to_string()),
+        })
+        .unwrap_or_else(|| "unknown".to_string());
 
-    for token in tokens {
-        match token {
-            MiscToken::NewLine => {
-                if !current_line.is_empty() {
-                    lines.push(current_line.join(" "));
-                    current_line.clear();
-                }
-            }
-            MiscToken::Comment(_) => {
-                // Skip comments
-            }
-            _ => {
-                current_line.push(format!("{:?}", token));
-            }
-        }
+    MiscDiagram {
+        diagram_type,
+        content: MiscContent::Raw(RawDiagram { lines }),
     }
+}
 
-    if !current_line.is_empty() {
-        lines.push(current_line.join(" "));
+/// Legacy `gitGraph:` diagrams may carry an `options ... end` block of JSON
+/// renderer settings; it has no meaning for the AST, so drop it
+fn strip_git_graph_options(input: &str) -> String {
+    let is_git_graph = input
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line.starts_with("gitGraph"));
+    if !is_git_graph {
+        return input.to_string();
     }
 
-    let diagram_type = if !lines.is_empty() {
-        // Extract the first keyword/identifier as diagram type
-        let first_line_tokens: Vec<&str> = lines[0].split_whitespace().collect();
-        if !first_line_tokens.is_empty() {
-            // Remove quotes from debug format
-            first_line_tokens[0]
-                .trim_start_matches("Keyword(\"")
-                .trim_start_matches("Identifier(\"")
-                .trim_end_matches("\")")
-                .to_string()
-        } else {
-            "unknown".to_string()
-        }
-    } else {
-        "unknown".to_string()
-    };
+    let mut in_options = false;
+    input
+        .lines()
+        .filter(|line| match line.trim() {
+            "options" => {
+                in_options = true;
+                false
+            }
+            "end" if in_options => {
+                in_options = false;
+                false
+            }
+            _ => !in_options,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    MiscDiagram {
-        diagram_type,
-        content: MiscContent::Raw(RawDiagram { lines }),
+/// Remove single-line `%%{...}%%` directives from the input, returning the
+/// remaining input and the directives' options (`None` if there were none)
+fn extract_directives(input: &str) -> (String, Option<BTreeMap<String, ConfigValue>>) {
+    let mut options: Option<BTreeMap<String, ConfigValue>> = None;
+    let mut remaining = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let Some((name, argument)) = directives::split(line) else {
+            remaining.push(line);
+            continue;
+        };
+        remaining.push("");
+
+        let options = options.get_or_insert_with(BTreeMap::new);
+        let Some(argument) = argument else {
+            options.insert(name.to_string(), ConfigValue::Null);
+            continue;
+        };
+
+        // Malformed directives are kept verbatim rather than rejected
+        let value = directives::parse_argument(name, argument, index + 1).unwrap_or_else(|_| {
+            let body = line
+                .trim()
+                .trim_start_matches("%%{")
+                .trim_end_matches("}%%");
+            ConfigValue::String(body.trim().to_string())
+        });
+        match value {
+            ConfigValue::Map(config) if directives::INIT_DIRECTIVES.contains(&name) => {
+                options.extend(config)
+            }
+            value => {
+                options.insert(name.to_string(), value);
+            }
+        }
     }
+
+    (remaining.join("\n"), options)
 }
 
 pub fn parse(input: &str) -> Result<MiscDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     // Strip metadata comments before parsing
     let clean_input = crate::common::lexer::strip_metadata_comments(input);
-
-    let tokens = misc_lexer()
-        .parse(&clean_input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize misc diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
-
-    let result = misc_parser()
+    let clean_input = strip_git_graph_options(&clean_input);
+    let (clean_input, directives) = extract_directives(&clean_input);
+
+    let lexer = misc_lexer();
+    let tokens = lexer.parse(&clean_input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            &clean_input,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize misc diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    })?;
+
+    let source_lines = clean_input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("%%"))
+        .map(str::to_string)
+        .collect();
+
+    let result = misc_parser(directives, source_lines)
         .parse(&tokens[..])
         .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to parse misc diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
+        .map_err(|e| {
+            parser_utils::token_failure(
+                &tokens,
+                &e,
+                ParseError::SyntaxError {
+                    message: "Failed to parse misc diagram".to_string(),
+                    expected: vec![],
+                    found: format!("{:?}", e),
+                    line: 0,
+                    column: 0,
+                    partial: None,
+                },
+            )
         })?;
 
     Ok(result)
@@ -376,6 +466,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parser_directives() {
+        let input = "%%{init: {'theme': 'dark', 'themeVariables': {'fontSize': 14}}}%%\n%%{wrap}%%\n%% comment";
+        let diagram = parse(input).unwrap();
+        assert_eq!(diagram.diagram_type, DIRECTIVES_TYPE);
+        let MiscContent::Info(info) = diagram.content else {
+            panic!("Expected info content");
+        };
+        assert_eq!(info.command, "");
+        assert_eq!(
+            info.options.get("theme"),
+            Some(&ConfigValue::String("dark".to_string()))
+        );
+        assert!(matches!(
+            info.options.get("themeVariables"),
+            Some(ConfigValue::Map(_))
+        ));
+        assert_eq!(info.options.get("wrap"), Some(&ConfigValue::Null));
+
+        let diagram = parse("%%{init: {theme: forest}}%%\ninfo showInfo").unwrap();
+        assert_eq!(diagram.diagram_type, "info");
+        let MiscContent::Info(info) = diagram.content else {
+            panic!("Expected info content");
+        };
+        assert_eq!(info.command, "showInfo");
+        assert_eq!(info.options.len(), 1);
+    }
+
     #[test]
     fn test_parser_gitgraph() {
         let input = "gitGraph:\n    commit\n    branch develop\n    checkout develop";
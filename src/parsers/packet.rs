@@ -5,6 +5,7 @@ use crate::error::{ParseError, Result};
 
 /// Simple string-based parser for packet diagrams
 pub fn parse(input: &str) -> Result<PacketDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -50,6 +51,7 @@ pub fn parse(input: &str) -> Result<PacketDiagram> {
                         found: width_str.to_string(),
                         line: line_num + 1,
                         column: 1,
+                        partial: None,
                     })?;
 
                 // Calculate start bit based on previous fields
@@ -79,6 +81,7 @@ pub fn parse(input: &str) -> Result<PacketDiagram> {
                         found: start_str.to_string(),
                         line: line_num + 1,
                         column: 1,
+                        partial: None,
                     })?;
 
                 let end = end_str
@@ -89,6 +92,7 @@ pub fn parse(input: &str) -> Result<PacketDiagram> {
                         found: end_str.to_string(),
                         line: line_num + 1,
                         column: dash_pos + 2,
+                        partial: None,
                     })?;
 
                 (start, end)
@@ -102,6 +106,7 @@ pub fn parse(input: &str) -> Result<PacketDiagram> {
                         found: range_part.to_string(),
                         line: line_num + 1,
                         column: 1,
+                        partial: None,
                     })?;
 
                 (bit, bit)
@@ -4,6 +4,7 @@ use crate::error::{ParseError, Result};
 
 /// Simple string-based parser for pie chart diagrams
 pub fn parse(input: &str) -> Result<PieDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -36,6 +37,7 @@ pub fn parse(input: &str) -> Result<PieDiagram> {
                     found: trimmed.to_string(),
                     line: line_num + 1,
                     column: 1,
+                    partial: None,
                 });
             }
 
@@ -65,16 +67,27 @@ pub fn parse(input: &str) -> Result<PieDiagram> {
             trimmed
         };
 
+        // `pie showData` may be followed by the rest of the header, as in
+        // `pie showData title Pets`
+        let header;
+        let trimmed = match trimmed
+            .strip_prefix("pie showData")
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            Some(rest) => {
+                diagram.show_data = true;
+                header = format!("pie {}", rest.trim());
+                header.trim_end()
+            }
+            None => trimmed,
+        };
+
         // Handle pie-specific header variations
         if trimmed.starts_with("pie title ") {
             // Inline title: "pie title Chart Name"
             let title = trimmed.strip_prefix("pie title ").unwrap().trim();
             diagram.title = Some(title.to_string());
             continue;
-        } else if trimmed == "pie showData" {
-            // pie with showData
-            diagram.show_data = true;
-            continue;
         } else if trimmed.starts_with("pie accTitle:") {
             // pie accTitle: content - convert to standard format
             let acc_line = trimmed.strip_prefix("pie ").unwrap();
@@ -124,17 +137,18 @@ pub fn parse(input: &str) -> Result<PieDiagram> {
             };
 
             // Parse value
-            match value_part.parse::<f64>() {
-                Ok(value) => {
+            match parse_value(value_part) {
+                Some(value) => {
                     diagram.data.push(PieSlice { label, value });
                 }
-                Err(_) => {
+                None => {
                     return Err(ParseError::SyntaxError {
                         message: "Invalid numeric value".to_string(),
                         expected: vec!["number".to_string()],
                         found: value_part.to_string(),
                         line: line_num + 1,
                         column: colon_pos + 2,
+                        partial: None,
                     });
                 }
             }
@@ -149,6 +163,7 @@ pub fn parse(input: &str) -> Result<PieDiagram> {
                 found: effective_trimmed.to_string(),
                 line: line_num + 1,
                 column: 1,
+                partial: None,
             });
         }
     }
@@ -157,3 +172,25 @@ pub fn parse(input: &str) -> Result<PieDiagram> {
 
     Ok(diagram)
 }
+
+/// A slice value such as `42`, `42.5`, `+42`, `1.5e3` or `1,234.5`
+///
+/// Commas are read as thousands separators, and only where they separate
+/// groups of three digits, so `1,5` is rejected rather than read as `15`.
+/// Infinite and NaN values are rejected.
+fn parse_value(text: &str) -> Option<f64> {
+    if text.contains(',') {
+        let integer = text
+            .split(['.', 'e', 'E'])
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(['+', '-']);
+        let mut groups = integer.split(',');
+        let first = groups.next().unwrap_or_default();
+        if !(1..=3).contains(&first.len()) || !groups.all(|group| group.len() == 3) {
+            return None;
+        }
+        return parse_value(&text.replace(',', ""));
+    }
+    text.parse::<f64>().ok().filter(|value| value.is_finite())
+}
@@ -6,6 +6,7 @@ use crate::error::{ParseError, Result};
 
 /// Simple string-based parser for quadrant diagrams
 pub fn parse(input: &str) -> Result<QuadrantDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -123,26 +124,22 @@ pub fn parse(input: &str) -> Result<QuadrantDiagram> {
     Ok(diagram)
 }
 
-/// Parse axis definition: "Low Reach --> High Reach"
+/// Parse axis definition: "Low Reach --> High Reach", or just "Reach" for a single label
 fn parse_axis_definition(text: &str) -> Option<AxisDefinition> {
-    if let Some(arrow_pos) = text.find("-->") {
-        let start_label = text[..arrow_pos].trim();
-        let end_label = text[arrow_pos + 3..].trim();
+    let non_empty = |label: &str| {
+        let label = label.trim();
+        (!label.is_empty()).then(|| label.to_string())
+    };
 
-        Some(AxisDefinition {
-            label_start: if start_label.is_empty() {
-                None
-            } else {
-                Some(start_label.to_string())
-            },
-            label_end: if end_label.is_empty() {
-                None
-            } else {
-                Some(end_label.to_string())
-            },
-        })
-    } else {
-        None
+    match text.find("-->") {
+        Some(arrow_pos) => Some(AxisDefinition {
+            label_start: non_empty(&text[..arrow_pos]),
+            label_end: non_empty(&text[arrow_pos + 3..]),
+        }),
+        None => Some(AxisDefinition {
+            label_start: non_empty(text),
+            label_end: None,
+        }),
     }
 }
 
@@ -161,10 +158,8 @@ fn parse_data_point(name: &str, coords: &str, _line_num: usize) -> Option<DataPo
     let x = parts[0].trim().parse::<f64>().ok()?;
     let y = parts[1].trim().parse::<f64>().ok()?;
 
-    // Validate coordinate range (0.0 to 1.0)
-    if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
-        return None;
-    }
+    // Coordinates outside 0.0 to 1.0 are kept so that validation can report
+    // them and `QuadrantDiagram::normalize` can rescale them
 
     // Handle potential class name after triple colon
     let (point_name, class_name) = if let Some(class_pos) = name.find(":::") {
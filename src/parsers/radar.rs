@@ -10,6 +10,7 @@ use std::collections::HashMap;
 
 /// Simple string-based parser for radar diagrams
 pub fn parse(input: &str) -> Result<RadarDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -202,6 +203,7 @@ fn parse_axis_value(line: &str) -> Result<Option<(String, f64)>> {
                 found: value_part.to_string(),
                 line: 0, // We don't track line numbers in this simple parser
                 column: 0,
+                partial: None,
             }),
         }
     } else {
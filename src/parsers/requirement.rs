@@ -2,11 +2,15 @@
 
 use crate::common::ast::{
     AccessibilityInfo, Element, RelationshipType, Requirement, RequirementDiagram,
-    RequirementRelationship, RequirementType, RiskLevel, VerificationMethod,
+    RequirementRelationship, RequirementType, RiskLevel, StyleDefinition, StyleTarget,
+    VerificationMethod,
 };
+use crate::common::lexer;
+use crate::common::ordered_map::OrderedMap;
+use crate::common::parser_utils::{self, parse_identifier};
+use crate::common::parsing::key_value::parse_style_properties;
 use crate::error::{ParseError, Result};
 use chumsky::prelude::*;
-use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RequirementToken {
@@ -36,7 +40,7 @@ pub enum RequirementToken {
     AccTitle,                 // accTitle:
     AccDescr,                 // accDescr: or accDescr {
     Direction,                // direction
-    Style,                    // style
+    Style(String),            // style directive content
     ClassDef,                 // classDef
     Class,                    // class
     NewLine,
@@ -83,7 +87,10 @@ pub fn requirement_lexer<'src>(
         text::keyword("accTitle").map(|_| RequirementToken::AccTitle),
         text::keyword("accDescr").map(|_| RequirementToken::AccDescr),
         text::keyword("direction").map(|_| RequirementToken::Direction),
-        text::keyword("style").map(|_| RequirementToken::Style),
+        // style req1,elem1 fill:#f9f,stroke:#333
+        text::keyword("style")
+            .ignore_then(none_of('\n').repeated().collect::<String>())
+            .map(|content| RequirementToken::Style(content.trim().to_string())),
         text::keyword("classDef").map(|_| RequirementToken::ClassDef),
         text::keyword("class").map(|_| RequirementToken::Class),
     ));
@@ -112,10 +119,7 @@ pub fn requirement_lexer<'src>(
     let right_brace = just('}').map(|_| RequirementToken::RightBrace);
 
     // Quoted string (can span multiple lines in requirements)
-    let quoted_string = just('"')
-        .ignore_then(none_of("\"").repeated().collect::<String>())
-        .then_ignore(just('"'))
-        .map(RequirementToken::QuotedString);
+    let quoted_string = lexer::quoted_string().map(RequirementToken::QuotedString);
 
     // Numbers (for IDs and values)
     let number = text::int(10).map(|s: &str| RequirementToken::Identifier(s.to_string()));
@@ -130,13 +134,13 @@ pub fn requirement_lexer<'src>(
     // Simple identifier (alphanumeric with underscores, dots, slashes)
     // Also allow identifiers starting with underscores
     let identifier = choice((
-        text::ident().map(|s: &str| s.to_string()),
+        parse_identifier().map(|s: &str| s.to_string()),
         // Match identifiers that start with underscores
         just('_')
             .repeated()
             .at_least(1)
             .collect::<String>()
-            .then(text::ident().or_not())
+            .then(parse_identifier().or_not())
             .map(|(underscores, rest): (String, Option<&str>)| {
                 let mut result = underscores;
                 if let Some(r) = rest {
@@ -176,6 +180,19 @@ pub fn requirement_lexer<'src>(
         .collect::<Vec<_>>()
 }
 
+/// A top-level statement recognised by [`requirement_parser`]
+#[derive(Debug, Clone)]
+enum RequirementItem {
+    Requirement(Requirement),
+    Element(Element),
+    Relationship(RequirementRelationship),
+    AccTitle(String),
+    AccDescr(String),
+    /// The content of a `style` statement: the styled ids and their properties
+    Style(String),
+    Skipped,
+}
+
 fn requirement_parser<'src>() -> impl Parser<
     'src,
     &'src [RequirementToken],
@@ -562,15 +579,14 @@ fn requirement_parser<'src>() -> impl Parser<
                 .collect::<Vec<_>>(),
         )
         .map(|tokens| {
-            let title = tokens
+            tokens
                 .into_iter()
                 .filter_map(|t| match t {
                     RequirementToken::Identifier(s) | RequirementToken::QuotedString(s) => Some(s),
                     _ => None,
                 })
                 .collect::<Vec<_>>()
-                .join(" ");
-            ("acc_title".to_string(), title)
+                .join(" ")
         });
 
     let acc_descr_def =
@@ -618,7 +634,7 @@ fn requirement_parser<'src>() -> impl Parser<
                             .to_string()
                     }),
             )))
-            .map(|(_, descr)| ("acc_descr".to_string(), descr));
+            .map(|(_, descr)| descr);
 
     // Parse direction directive (ignored, just for compatibility)
     let direction_def = just(RequirementToken::Direction).then(
@@ -627,12 +643,11 @@ fn requirement_parser<'src>() -> impl Parser<
             .repeated(),
     );
 
-    // Parse style directive (ignored, just for compatibility)
-    let style_def = just(RequirementToken::Style).then(
-        any()
-            .filter(|t| !matches!(t, RequirementToken::NewLine))
-            .repeated(),
-    );
+    // Parse style directive: the styled ids and their properties
+    let style_def = any().try_map(|t, span| match t {
+        RequirementToken::Style(content) => Ok(content),
+        _ => Err(Simple::new(Some(t.into()), span)),
+    });
 
     // Parse classDef directive (ignored, just for compatibility)
     let class_def_directive = just(RequirementToken::ClassDef).then(
@@ -652,45 +667,55 @@ fn requirement_parser<'src>() -> impl Parser<
     header
         .then(
             choice((
-                requirement_def.map(|req| (req.name.clone(), Some(req), None, None, None)),
-                element_def.map(|elem| (elem.name.clone(), None, Some(elem), None, None)),
-                relationship_def.map(|rel| ("".to_string(), None, None, Some(rel), None)),
-                acc_title_def
-                    .map(|(key, val)| ("".to_string(), None, None, None, Some((key, val)))),
-                acc_descr_def
-                    .map(|(key, val)| ("".to_string(), None, None, None, Some((key, val)))),
-                direction_def.map(|_| ("".to_string(), None, None, None, None)),
-                style_def.map(|_| ("".to_string(), None, None, None, None)),
-                class_def_directive.map(|_| ("".to_string(), None, None, None, None)),
-                class_directive.map(|_| ("".to_string(), None, None, None, None)),
+                requirement_def.map(RequirementItem::Requirement),
+                element_def.map(RequirementItem::Element),
+                relationship_def.map(RequirementItem::Relationship),
+                acc_title_def.map(RequirementItem::AccTitle),
+                acc_descr_def.map(RequirementItem::AccDescr),
+                direction_def.to(RequirementItem::Skipped),
+                style_def.map(RequirementItem::Style),
+                class_def_directive.to(RequirementItem::Skipped),
+                class_directive.to(RequirementItem::Skipped),
                 any()
                     .filter(|t| {
                         matches!(t, RequirementToken::NewLine | RequirementToken::Comment(_))
                     })
-                    .map(|_| ("".to_string(), None, None, None, None)),
+                    .to(RequirementItem::Skipped),
             ))
             .repeated()
             .collect::<Vec<_>>(),
         )
         .map(|(_, items)| {
-            let mut requirements = HashMap::new();
-            let mut elements = HashMap::new();
+            let mut requirements = OrderedMap::new();
+            let mut elements = OrderedMap::new();
             let mut relationships = Vec::new();
             let mut accessibility = AccessibilityInfo::default();
+            let mut styles = Vec::new();
 
-            for (name, req, elem, rel, acc) in items {
-                if let Some(r) = req {
-                    requirements.insert(name, r);
-                } else if let Some(e) = elem {
-                    elements.insert(name, e);
-                } else if let Some(r) = rel {
-                    relationships.push(r);
-                } else if let Some((key, val)) = acc {
-                    match key.as_str() {
-                        "acc_title" => accessibility.title = Some(val),
-                        "acc_descr" => accessibility.description = Some(val),
-                        _ => {}
+            for item in items {
+                match item {
+                    RequirementItem::Requirement(r) => {
+                        requirements.insert(r.name.clone(), r);
                     }
+                    RequirementItem::Element(e) => {
+                        elements.insert(e.name.clone(), e);
+                    }
+                    RequirementItem::Relationship(r) => relationships.push(r),
+                    RequirementItem::AccTitle(title) => accessibility.title = Some(title),
+                    RequirementItem::AccDescr(descr) => accessibility.description = Some(descr),
+                    // style req1,elem1 fill:#f9f,stroke:#333
+                    RequirementItem::Style(style) => {
+                        if let Some((ids, properties)) = style.split_once(char::is_whitespace) {
+                            let properties = parse_style_properties(properties.trim());
+                            for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                                styles.push(StyleDefinition {
+                                    target: StyleTarget::Node(id.to_string()),
+                                    styles: properties.clone(),
+                                });
+                            }
+                        }
+                    }
+                    RequirementItem::Skipped => {}
                 }
             }
 
@@ -700,37 +725,49 @@ fn requirement_parser<'src>() -> impl Parser<
                 requirements,
                 elements,
                 relationships,
+                styles,
             }
         })
 }
 
 /// Parse requirement diagram from input string
 pub fn parse(input: &str) -> Result<RequirementDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     // Strip metadata comments before parsing
     let clean_input = crate::common::lexer::strip_metadata_comments(input);
 
-    let tokens = requirement_lexer()
-        .parse(&clean_input)
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to tokenize requirement diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        })?;
+    let lexer = requirement_lexer();
+    let tokens = lexer.parse(&clean_input).into_result().map_err(|e| {
+        parser_utils::lex_failure(
+            &lexer,
+            &clean_input,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to tokenize requirement diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    })?;
 
     let parser = requirement_parser();
-    let result = parser
-        .parse(&tokens[..])
-        .into_result()
-        .map_err(|e| ParseError::SyntaxError {
-            message: "Failed to parse requirement diagram".to_string(),
-            expected: vec![],
-            found: format!("{:?}", e),
-            line: 0,
-            column: 0,
-        });
+    let result = parser.parse(&tokens[..]).into_result().map_err(|e| {
+        parser_utils::token_failure(
+            &tokens,
+            &e,
+            ParseError::SyntaxError {
+                message: "Failed to parse requirement diagram".to_string(),
+                expected: vec![],
+                found: format!("{:?}", e),
+                line: 0,
+                column: 0,
+                partial: None,
+            },
+        )
+    });
 
     result
 }
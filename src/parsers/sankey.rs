@@ -19,6 +19,8 @@
 //! - **Node discovery** - Automatically identifies nodes from link definitions
 //! - **Value parsing** - Supports integer and floating-point flow values
 //! - **Text handling** - Processes both quoted and unquoted node names
+//! - **Node metadata** - Rows such as `Solar,color=#f5c542` fill [`SankeyNode::metadata`]
+//! - **Forward compatibility** - Extra columns after a link's value are ignored
 //! - **Error recovery** - Provides detailed error messages with suggestions
 //!
 //! ## Example
@@ -46,7 +48,7 @@
 use crate::common::ast::{SankeyDiagram, SankeyLink, SankeyNode};
 use crate::error::{format_error_snippet, Location, ParseError, Result};
 use chumsky::prelude::*;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SankeyToken {
@@ -57,6 +59,11 @@ pub enum SankeyToken {
     UnquotedText(String), // text
 }
 
+enum SankeyRow {
+    Link(SankeyLink),
+    Metadata(String, BTreeMap<String, String>),
+}
+
 /// Parse a Mermaid Sankey diagram from text input
 ///
 /// This function processes a Sankey diagram, extracting flow information and
@@ -95,6 +102,7 @@ pub enum SankeyToken {
 /// - Values cannot be parsed as numbers
 /// - Required syntax elements are missing
 pub fn parse(input: &str) -> Result<SankeyDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let tokens = sankey_lexer()
         .parse(input)
         .into_result()
@@ -125,6 +133,7 @@ pub fn parse(input: &str) -> Result<SankeyDiagram> {
                     found: "unknown error".to_string(),
                     line: 0,
                     column: 0,
+                    partial: None,
                 }
             }
         })?;
@@ -155,6 +164,7 @@ pub fn parse(input: &str) -> Result<SankeyDiagram> {
                     found: "unknown error".to_string(),
                     line: 0,
                     column: 0,
+                    partial: None,
                 }
             }
         });
@@ -239,21 +249,32 @@ fn sankey_parser<'tokens, 'src: 'tokens>() -> impl Parser<
         SankeyToken::UnquotedText(text) => text.clone(),
     };
 
-    let record = field // source
-        .then_ignore(just(&SankeyToken::Comma))
-        .then(field) // target
-        .then_ignore(just(&SankeyToken::Comma))
-        .then(field) // value
-        .map(|((source, target), value_str)| {
-            let value = value_str.trim().parse::<f64>().unwrap_or(0.0);
-            SankeyLink {
-                source: source.trim().to_string(),
-                target: target.trim().to_string(),
-                value,
+    // A row is a link `source,target,value`, where trailing columns added by
+    // newer Mermaid versions are ignored, or a node metadata row `node,key=value,...`
+    let row = field
+        .separated_by(just(&SankeyToken::Comma))
+        .at_least(2)
+        .collect::<Vec<String>>()
+        .try_map(|fields, span| {
+            if fields[1..].iter().all(|field| field.contains('=')) {
+                let metadata = fields[1..]
+                    .iter()
+                    .filter_map(|pair| pair.split_once('='))
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .collect();
+                Ok(SankeyRow::Metadata(fields[0].trim().to_string(), metadata))
+            } else if fields.len() >= 3 {
+                Ok(SankeyRow::Link(SankeyLink {
+                    source: fields[0].trim().to_string(),
+                    target: fields[1].trim().to_string(),
+                    value: fields[2].trim().parse::<f64>().unwrap_or(0.0),
+                }))
+            } else {
+                Err(Simple::new(None, span))
             }
         });
 
-    let csv_line = record.then_ignore(just(&SankeyToken::NewLine).or_not());
+    let csv_line = row.then_ignore(just(&SankeyToken::NewLine).or_not());
 
     let blank_line = just(&SankeyToken::NewLine);
 
@@ -264,9 +285,18 @@ fn sankey_parser<'tokens, 'src: 'tokens>() -> impl Parser<
         .then_ignore(just(&SankeyToken::NewLine).repeated()) // Allow blank lines after header
         .then(content_line.repeated().collect::<Vec<_>>())
         .map(|(_, lines)| {
-            let links: Vec<SankeyLink> = lines.into_iter().flatten().collect();
+            let mut links = Vec::new();
+            let mut metadata: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+            for row in lines.into_iter().flatten() {
+                match row {
+                    SankeyRow::Link(link) => links.push(link),
+                    SankeyRow::Metadata(node, fields) => {
+                        metadata.entry(node).or_default().extend(fields)
+                    }
+                }
+            }
 
-            let mut nodes = HashSet::new();
+            let mut nodes: HashSet<String> = metadata.keys().cloned().collect();
             for link in &links {
                 nodes.insert(link.source.clone());
                 nodes.insert(link.target.clone());
@@ -277,6 +307,7 @@ fn sankey_parser<'tokens, 'src: 'tokens>() -> impl Parser<
                     .into_iter()
                     .map(|name| SankeyNode {
                         id: name.clone(),
+                        metadata: metadata.remove(&name).unwrap_or_default(),
                         name,
                     })
                     .collect(),
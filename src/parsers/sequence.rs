@@ -9,10 +9,12 @@
 //! The parser supports comprehensive Mermaid sequence diagram syntax including:
 //!
 //! - **Participants**: `participant A`, `actor B`, `boundary C`
+//! - **Boxes**: `box Purple Group ... end` around participant declarations
+//! - **Quoted names**: `participant "Web Server 🚀" as WS`, for names with spaces or emoji
 //! - **Messages**: `A->>B: message`, `A-->>B: async`, `A-xB: destroy`
-//! - **Activations**: automatic and manual activation boxes
+//! - **Activations**: `activate A`, or `+`/`-` after the arrow as in `A->>+B`
 //! - **Notes**: `note over A: note text`, `note left of A`
-//! - **Control flow**: `alt/else/end`, `opt/end`, `loop/end`, `par/and/end`
+//! - **Control flow**: `alt/else/end`, `opt/end`, `loop/end`, `break/end`, `par/and/end`
 //! - **Autonumbering**: automatic message numbering
 //!
 //! ## Features
@@ -55,18 +57,27 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+use crate::common::arrow_table::{self, ArrowKind};
 use crate::common::ast::{
-    AccessibilityInfo, Alternative, ArrowType, AutoNumber, ElseBranch, Loop, Message, Note,
-    NotePosition, Optional, Participant, ParticipantType, SequenceDiagram, SequenceStatement,
+    AccessibilityInfo, Alternative, AutoNumber, Break, Color, ElseBranch, Loop, Message,
+    MessageActivation, Note, NotePosition, Optional, Participant, ParticipantBox,
+    ParticipantNaming, ParticipantType, SequenceDiagram, SequenceStatement,
 };
-use crate::common::constants::{diagram_headers, directives, sequence_keywords};
-use crate::common::parser_utils::validate_diagram_header;
+use crate::common::constants::{diagram_headers, sequence_keywords};
+use crate::common::parser_utils::{parse_common_directives, validate_diagram_header};
+use crate::common::parsing::{lines, quoted_strings};
 use crate::error::{ParseError, Result};
+use crate::stable::DiagramKind;
 use std::collections::HashMap;
 
 /// Parse a Mermaid sequence diagram
 pub fn parse(input: &str) -> Result<SequenceDiagram> {
-    let lines: Vec<&str> = input.lines().collect();
+    let input = crate::common::parsing::frontmatter::strip(input);
+    // `;` separates statements just like a newline
+    let lines: Vec<&str> = input
+        .lines()
+        .flat_map(|line| lines::split_statements(line, ';'))
+        .collect();
 
     if lines.is_empty() {
         return Err(ParseError::EmptyInput);
@@ -77,6 +88,7 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
         accessibility: AccessibilityInfo::default(),
         participants: Vec::new(),
         statements: Vec::new(),
+        boxes: Vec::new(),
         autonumber: None,
     };
 
@@ -84,6 +96,7 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
     let mut first_line_processed = false;
     let mut participant_map: HashMap<String, usize> = HashMap::new();
     let mut alias_map: HashMap<String, String> = HashMap::new();
+    let mut open_box: Option<ParticipantBox> = None;
 
     while let Some((line_num, line)) = line_iter.next() {
         // Use shared header validation utility
@@ -97,9 +110,8 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
             continue;
         }
 
-        // Handle title directive
-        if let Some(title_text) = trimmed.strip_prefix(directives::TITLE) {
-            diagram.title = Some(title_text.trim().to_string());
+        // Handle title and accessibility directives
+        if parse_common_directives(trimmed, &mut diagram.title, &mut diagram.accessibility) {
             continue;
         }
 
@@ -124,6 +136,24 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
             continue;
         }
 
+        // Handle box blocks; `end` closes the open box
+        if let Some(declaration) = trimmed
+            .strip_prefix(sequence_keywords::BOX)
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            // Boxes do not nest, so a new box closes an unterminated one
+            if let Some(participant_box) = open_box.replace(parse_box_declaration(declaration)) {
+                diagram.boxes.push(participant_box);
+            }
+            continue;
+        }
+        if trimmed == "end" {
+            if let Some(participant_box) = open_box.take() {
+                diagram.boxes.push(participant_box);
+            }
+            continue;
+        }
+
         // Handle participant/actor declarations
         if trimmed.starts_with(sequence_keywords::PARTICIPANT)
             || trimmed.starts_with(sequence_keywords::ACTOR)
@@ -137,31 +167,50 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
                     .unwrap()
             };
 
-            let (actor, alias) = if let Some(as_pos) = declaration.find(" as ") {
-                let actor_name = declaration[..as_pos].trim();
-                let alias_name = declaration[as_pos + 4..].trim();
-                (actor_name.to_string(), Some(alias_name.to_string()))
-            } else {
-                (declaration.trim().to_string(), None)
+            // Names may be quoted to hold spaces, emoji or ` as `
+            let (actor, alias) = match quoted_strings::find_unquoted(declaration, " as ") {
+                Some(as_pos) => (
+                    quoted_strings::unquote(&declaration[..as_pos]),
+                    Some(quoted_strings::unquote(&declaration[as_pos + 4..])),
+                ),
+                None => (quoted_strings::unquote(declaration), None),
             };
 
-            if !participant_map.contains_key(&actor) {
-                participant_map.insert(actor.clone(), diagram.participants.len());
+            // Track alias mapping; earlier aliases keep resolving
+            if let Some(alias_name) = &alias {
+                alias_map.insert(alias_name.clone(), actor.clone());
+            }
 
-                // Track alias mapping
-                if let Some(alias_name) = &alias {
-                    alias_map.insert(alias_name.clone(), actor.clone());
+            let participant_type = if is_actor {
+                ParticipantType::Actor
+            } else {
+                ParticipantType::Participant
+            };
+
+            if let Some(participant_box) = &mut open_box {
+                if !participant_box.participants.contains(&actor) {
+                    participant_box.participants.push(actor.clone());
                 }
+            }
 
-                diagram.participants.push(Participant {
-                    actor,
-                    alias,
-                    participant_type: if is_actor {
-                        ParticipantType::Actor
-                    } else {
-                        ParticipantType::Participant
-                    },
-                });
+            match participant_map.get(&actor) {
+                // A later declaration (or one following implicit use in a
+                // message) refines the existing participant
+                Some(&index) => {
+                    let existing = &mut diagram.participants[index];
+                    if alias.is_some() {
+                        existing.alias = alias;
+                    }
+                    existing.participant_type = participant_type;
+                }
+                None => {
+                    participant_map.insert(actor.clone(), diagram.participants.len());
+                    diagram.participants.push(Participant {
+                        actor,
+                        alias,
+                        participant_type,
+                    });
+                }
             }
             continue;
         }
@@ -223,6 +272,30 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
             continue;
         }
 
+        // Handle break blocks
+        if let Some(condition) = trimmed.strip_prefix(sequence_keywords::BREAK) {
+            diagram.statements.push(parse_break_block(
+                &mut line_iter,
+                condition.trim().to_string(),
+                &mut participant_map,
+                &mut diagram.participants,
+                &alias_map,
+            ));
+            continue;
+        }
+
+        // Handle rect blocks
+        if let Some(color) = trimmed.strip_prefix(sequence_keywords::RECT) {
+            diagram.statements.push(parse_rect_block(
+                &mut line_iter,
+                color,
+                &mut participant_map,
+                &mut diagram.participants,
+                &alias_map,
+            ));
+            continue;
+        }
+
         // Handle notes
         if trimmed.starts_with(sequence_keywords::NOTE) {
             if let Some(note) = parse_note(trimmed) {
@@ -277,15 +350,37 @@ pub fn parse(input: &str) -> Result<SequenceDiagram> {
         }
     }
 
+    if let Some(participant_box) = open_box {
+        diagram.boxes.push(participant_box);
+    }
+
+    // Notes are parsed without alias resolution; give every reference the
+    // canonical participant id
+    diagram.normalize_participant_references(ParticipantNaming::Id);
+
     Ok(diagram)
 }
 
-/// Resolve an alias to the actual participant name
+/// Split a `box` declaration into its color and title. The color comes
+/// first; without one the whole declaration is the title.
+fn parse_box_declaration(declaration: &str) -> ParticipantBox {
+    let (color, title) = match Color::parse_prefix(declaration) {
+        Some((color, title)) => (Some(color), title),
+        None => (None, declaration),
+    };
+    let title = title.trim();
+    ParticipantBox {
+        color,
+        title: (!title.is_empty()).then(|| title.to_string()),
+        participants: Vec::new(),
+    }
+}
+
+/// Resolve an alias to the actual participant name, removing the quotes
+/// around a quoted name
 fn resolve_alias(name: &str, alias_map: &HashMap<String, String>) -> String {
-    alias_map
-        .get(name)
-        .cloned()
-        .unwrap_or_else(|| name.to_string())
+    let name = quoted_strings::unquote(name);
+    alias_map.get(&name).cloned().unwrap_or(name)
 }
 
 /// Ensure a participant exists, adding it if necessary
@@ -311,30 +406,30 @@ fn parse_message(
     participants: &mut Vec<Participant>,
     alias_map: &HashMap<String, String>,
 ) -> Option<Message> {
-    // Try different arrow types (order matters - check longer patterns first)
-    let arrow_types = vec![
-        ("<<-->>", ArrowType::BiDirectionalDotted),
-        ("<<->>", ArrowType::BiDirectionalSolid),
-        ("-->>", ArrowType::DottedClosed),
-        ("->>", ArrowType::SolidClosed),
-        ("-->", ArrowType::DottedOpen),
-        ("->", ArrowType::SolidOpen),
-        ("--x", ArrowType::Cross),
-        ("-x", ArrowType::Cross),
-        ("--)", ArrowType::Point),
-        ("-)", ArrowType::Point),
-    ];
-
-    for (arrow_str, arrow_type) in arrow_types {
-        if let Some(arrow_pos) = line.find(arrow_str) {
-            let from_name = line[..arrow_pos].trim();
-            let rest = &line[arrow_pos + arrow_str.len()..];
-
-            // Find the recipient and message text
-            let (to_name, text) = if let Some(colon_pos) = rest.find(':') {
-                (rest[..colon_pos].trim(), rest[colon_pos + 1..].trim())
+    // Arrows may only appear before the message separator and outside quotes, so
+    // message text like `: go -->> there` never changes the arrow that is matched
+    let (head, text) = match quoted_strings::find_unquoted(line, ":") {
+        Some(colon_pos) => (&line[..colon_pos], line[colon_pos + 1..].trim()),
+        None => (line, ""),
+    };
+
+    // Longer arrows first, so `-->>` is not read as `-->` followed by `>`
+    for arrow in arrow_table::arrows_for(DiagramKind::Sequence) {
+        let ArrowKind::Sequence(arrow_type) = &arrow.kind else {
+            continue;
+        };
+        let arrow_str = arrow.spelling;
+        if let Some(arrow_pos) = quoted_strings::find_unquoted(head, arrow_str) {
+            let from_name = head[..arrow_pos].trim();
+            let to_name = head[arrow_pos + arrow_str.len()..].trim();
+
+            // `+` activates the receiver and `-` deactivates the sender
+            let (activation, to_name) = if let Some(name) = to_name.strip_prefix('+') {
+                (Some(MessageActivation::Activate), name.trim())
+            } else if let Some(name) = to_name.strip_prefix('-') {
+                (Some(MessageActivation::Deactivate), name.trim())
             } else {
-                (rest.trim(), "")
+                (None, to_name)
             };
 
             // Resolve aliases
@@ -349,7 +444,8 @@ fn parse_message(
                 from,
                 to,
                 text: text.to_string(),
-                arrow_type,
+                arrow_type: arrow_type.clone(),
+                activation,
             });
         }
     }
@@ -377,8 +473,8 @@ fn parse_note(line: &str) -> Option<Note> {
         return None;
     };
 
-    // Find the actor and text
-    let (actor, text) = if let Some(colon_pos) = rest.find(':') {
+    // Find the actor and text; a quoted actor name may contain `:`
+    let (actor, text) = if let Some(colon_pos) = quoted_strings::find_unquoted(rest, ":") {
         (rest[..colon_pos].trim(), rest[colon_pos + 1..].trim())
     } else {
         // Handle "over Alice,Bob" case
@@ -392,55 +488,133 @@ fn parse_note(line: &str) -> Option<Note> {
 
     Some(Note {
         position,
-        actor: actor.to_string(),
+        actor: actor
+            .split(',')
+            .map(quoted_strings::unquote)
+            .collect::<Vec<_>>()
+            .join(","),
         text: text.to_string(),
     })
 }
 
-/// Parse a loop block
-fn parse_loop_block(
+/// Parse a statement inside a block; a nested block is parsed up to and
+/// including its `end`
+fn parse_block_statement(
+    trimmed: &str,
     line_iter: &mut std::iter::Peekable<std::iter::Enumerate<std::slice::Iter<&str>>>,
-    condition: String,
     participant_map: &mut HashMap<String, usize>,
     participants: &mut Vec<Participant>,
     alias_map: &HashMap<String, String>,
 ) -> Option<SequenceStatement> {
+    if let Some(condition) = trimmed.strip_prefix(sequence_keywords::LOOP) {
+        let condition = condition.trim().to_string();
+        return parse_loop_block(
+            line_iter,
+            condition,
+            participant_map,
+            participants,
+            alias_map,
+        );
+    }
+    if let Some(condition) = trimmed.strip_prefix(sequence_keywords::ALT) {
+        let condition = condition.trim().to_string();
+        return parse_alt_block(
+            line_iter,
+            condition,
+            participant_map,
+            participants,
+            alias_map,
+        );
+    }
+    if let Some(condition) = trimmed.strip_prefix(sequence_keywords::OPT) {
+        let condition = condition.trim().to_string();
+        return parse_opt_block(
+            line_iter,
+            condition,
+            participant_map,
+            participants,
+            alias_map,
+        );
+    }
+    if let Some(condition) = trimmed.strip_prefix(sequence_keywords::BREAK) {
+        let condition = condition.trim().to_string();
+        return Some(parse_break_block(
+            line_iter,
+            condition,
+            participant_map,
+            participants,
+            alias_map,
+        ));
+    }
+    if let Some(color) = trimmed.strip_prefix(sequence_keywords::RECT) {
+        return Some(parse_rect_block(
+            line_iter,
+            color,
+            participant_map,
+            participants,
+            alias_map,
+        ));
+    }
+
+    if let Some(msg) = parse_message(trimmed, participant_map, participants, alias_map) {
+        Some(SequenceStatement::Message(msg))
+    } else if trimmed.starts_with("note ") {
+        parse_note(trimmed).map(SequenceStatement::Note)
+    } else if trimmed.starts_with("activate ") {
+        let actor_name = trimmed.strip_prefix("activate ").unwrap().trim();
+        let actor = resolve_alias(actor_name, alias_map);
+        ensure_participant(&actor, participant_map, participants);
+        Some(SequenceStatement::Activate(actor))
+    } else if trimmed.starts_with("deactivate ") {
+        let actor_name = trimmed.strip_prefix("deactivate ").unwrap().trim();
+        let actor = resolve_alias(actor_name, alias_map);
+        ensure_participant(&actor, participant_map, participants);
+        Some(SequenceStatement::Deactivate(actor))
+    } else {
+        None
+    }
+}
+
+/// Parse the statements of a block up to and including its `end`
+fn parse_block_body(
+    line_iter: &mut std::iter::Peekable<std::iter::Enumerate<std::slice::Iter<&str>>>,
+    participant_map: &mut HashMap<String, usize>,
+    participants: &mut Vec<Participant>,
+    alias_map: &HashMap<String, String>,
+) -> Vec<SequenceStatement> {
     let mut statements = Vec::new();
 
-    while let Some((_, line)) = line_iter.peek() {
+    while let Some((_, line)) = line_iter.next() {
         let trimmed = line.trim();
 
         if trimmed == "end" {
-            line_iter.next(); // consume the end
             break;
         }
 
-        line_iter.next(); // consume the line
-
         if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("%%") {
             continue;
         }
 
-        // Parse nested statements
-        if let Some(msg) = parse_message(trimmed, participant_map, participants, alias_map) {
-            statements.push(SequenceStatement::Message(msg));
-        } else if trimmed.starts_with("note ") {
-            if let Some(note) = parse_note(trimmed) {
-                statements.push(SequenceStatement::Note(note));
-            }
-        } else if trimmed.starts_with("activate ") {
-            let actor_name = trimmed.strip_prefix("activate ").unwrap().trim();
-            let actor = resolve_alias(actor_name, alias_map);
-            ensure_participant(&actor, participant_map, participants);
-            statements.push(SequenceStatement::Activate(actor));
-        } else if trimmed.starts_with("deactivate ") {
-            let actor_name = trimmed.strip_prefix("deactivate ").unwrap().trim();
-            let actor = resolve_alias(actor_name, alias_map);
-            ensure_participant(&actor, participant_map, participants);
-            statements.push(SequenceStatement::Deactivate(actor));
+        if let Some(statement) =
+            parse_block_statement(trimmed, line_iter, participant_map, participants, alias_map)
+        {
+            statements.push(statement);
         }
     }
 
+    statements
+}
+
+/// Parse a loop block
+fn parse_loop_block(
+    line_iter: &mut std::iter::Peekable<std::iter::Enumerate<std::slice::Iter<&str>>>,
+    condition: String,
+    participant_map: &mut HashMap<String, usize>,
+    participants: &mut Vec<Participant>,
+    alias_map: &HashMap<String, String>,
+) -> Option<SequenceStatement> {
+    let statements = parse_block_body(line_iter, participant_map, participants, alias_map);
+
     Some(SequenceStatement::Loop(Loop {
         condition,
         statements,
@@ -486,23 +660,7 @@ fn parse_alt_block(
 
         // Parse nested statements
         let stmt =
-            if let Some(msg) = parse_message(trimmed, participant_map, participants, alias_map) {
-                Some(SequenceStatement::Message(msg))
-            } else if trimmed.starts_with("note ") {
-                parse_note(trimmed).map(SequenceStatement::Note)
-            } else if trimmed.starts_with("activate ") {
-                let actor_name = trimmed.strip_prefix("activate ").unwrap().trim();
-                let actor = resolve_alias(actor_name, alias_map);
-                ensure_participant(&actor, participant_map, participants);
-                Some(SequenceStatement::Activate(actor))
-            } else if trimmed.starts_with("deactivate ") {
-                let actor_name = trimmed.strip_prefix("deactivate ").unwrap().trim();
-                let actor = resolve_alias(actor_name, alias_map);
-                ensure_participant(&actor, participant_map, participants);
-                Some(SequenceStatement::Deactivate(actor))
-            } else {
-                None
-            };
+            parse_block_statement(trimmed, line_iter, participant_map, participants, alias_map);
 
         if let Some(s) = stmt {
             if in_else {
@@ -535,44 +693,39 @@ fn parse_opt_block(
     participants: &mut Vec<Participant>,
     alias_map: &HashMap<String, String>,
 ) -> Option<SequenceStatement> {
-    let mut statements = Vec::new();
-
-    while let Some((_, line)) = line_iter.peek() {
-        let trimmed = line.trim();
-
-        if trimmed == "end" {
-            line_iter.next(); // consume the end
-            break;
-        }
-
-        line_iter.next(); // consume the line
-
-        if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("%%") {
-            continue;
-        }
-
-        // Parse nested statements
-        if let Some(msg) = parse_message(trimmed, participant_map, participants, alias_map) {
-            statements.push(SequenceStatement::Message(msg));
-        } else if trimmed.starts_with("note ") {
-            if let Some(note) = parse_note(trimmed) {
-                statements.push(SequenceStatement::Note(note));
-            }
-        } else if trimmed.starts_with("activate ") {
-            let actor_name = trimmed.strip_prefix("activate ").unwrap().trim();
-            let actor = resolve_alias(actor_name, alias_map);
-            ensure_participant(&actor, participant_map, participants);
-            statements.push(SequenceStatement::Activate(actor));
-        } else if trimmed.starts_with("deactivate ") {
-            let actor_name = trimmed.strip_prefix("deactivate ").unwrap().trim();
-            let actor = resolve_alias(actor_name, alias_map);
-            ensure_participant(&actor, participant_map, participants);
-            statements.push(SequenceStatement::Deactivate(actor));
-        }
-    }
+    let statements = parse_block_body(line_iter, participant_map, participants, alias_map);
 
     Some(SequenceStatement::Opt(Optional {
         condition,
         statements,
     }))
 }
+
+/// Parse a break block
+fn parse_break_block(
+    line_iter: &mut std::iter::Peekable<std::iter::Enumerate<std::slice::Iter<&str>>>,
+    condition: String,
+    participant_map: &mut HashMap<String, usize>,
+    participants: &mut Vec<Participant>,
+    alias_map: &HashMap<String, String>,
+) -> SequenceStatement {
+    let statements = parse_block_body(line_iter, participant_map, participants, alias_map);
+    SequenceStatement::Break(Break {
+        condition,
+        statements,
+    })
+}
+
+/// Parse a rect block, whose background color is the rest of its `rect` line
+fn parse_rect_block(
+    line_iter: &mut std::iter::Peekable<std::iter::Enumerate<std::slice::Iter<&str>>>,
+    color: &str,
+    participant_map: &mut HashMap<String, usize>,
+    participants: &mut Vec<Participant>,
+    alias_map: &HashMap<String, String>,
+) -> SequenceStatement {
+    let color = color.trim();
+    let color = Color::parse(color).unwrap_or_else(|| Color::Other(color.to_string()));
+    let statements = parse_block_body(line_iter, participant_map, participants, alias_map);
+    SequenceStatement::Rect { color, statements }
+}
@@ -4,16 +4,53 @@
 //! High complexity grammar (336 lines) with nested states, concurrent regions, and various state types.
 
 use crate::common::ast::{
-    AccessibilityInfo, State, StateDiagram, StateNote, StateNotePosition, StateTransition,
-    StateType, StateVersion,
+    AccessibilityInfo, ClassDef, FlowDirection, State, StateDiagram, StateNote, StateNotePosition,
+    StateRegion, StateTransition, StateType, StateVersion,
 };
 use crate::common::constants::{diagram_headers, directives, state_keywords};
+use crate::common::ordered_map::OrderedMap;
 use crate::common::parser_utils::validate_diagram_header;
+use crate::common::parsing::key_value::parse_style_properties;
 use crate::error::{ParseError, Result};
 use std::collections::HashMap;
 
+/// Statements of a composite state body, or of one of its concurrent
+/// regions, while the diagram is parsed
+#[derive(Default)]
+struct Body {
+    /// IDs of the states that first appear in this body
+    states: Vec<String>,
+    transitions: Vec<StateTransition>,
+    notes: Vec<StateNote>,
+}
+
+/// A composite state whose body is being parsed
+struct CompositeFrame {
+    id: String,
+    /// Statements since the opening brace or the last `--`
+    body: Body,
+    /// Regions already closed by `--`
+    regions: Vec<Body>,
+}
+
+/// States found so far, by id, before they are nested in their composite
+/// states
+#[derive(Default)]
+struct Scopes {
+    states: OrderedMap<String, State>,
+    /// Id of the composite state each state first appeared in; `None` for
+    /// states first seen outside any composite state
+    parents: HashMap<String, Option<String>>,
+    /// Closed bodies of composite states: one, or one per concurrent region
+    bodies: HashMap<String, Vec<Body>>,
+    /// `(state id, class)` pairs from `class` statements and `:::` suffixes,
+    /// which may name states declared later
+    classes: Vec<(String, String)>,
+}
+
 /// Parse a Mermaid state diagram
 pub fn parse(input: &str) -> Result<StateDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -24,14 +61,17 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
         title: None,
         accessibility: AccessibilityInfo::default(),
         version: StateVersion::V1,
-        states: HashMap::new(),
+        direction: None,
+        states: OrderedMap::new(),
         transitions: Vec::new(),
         notes: Vec::new(),
+        class_defs: HashMap::new(),
     };
 
     let mut line_iter = lines.iter().enumerate().peekable();
     let mut first_line_processed = false;
-    let mut state_stack: Vec<String> = Vec::new(); // For tracking nested states
+    let mut state_stack: Vec<CompositeFrame> = Vec::new(); // For tracking nested states
+    let mut scopes = Scopes::default();
     let mut _brace_count = 0;
 
     while let Some((line_num, line)) = line_iter.next() {
@@ -58,26 +98,56 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
             continue;
         }
 
-        // Handle direction directive (ignore for now)
-        if trimmed.starts_with(state_keywords::DIRECTION) {
+        // Handle direction directive; those inside composite states are not kept
+        if let Some(keyword) = trimmed.strip_prefix(state_keywords::DIRECTION) {
+            if state_stack.is_empty() {
+                if let Some(direction) = FlowDirection::from_keyword(keyword.trim()) {
+                    diagram.direction = Some(direction);
+                }
+            }
+            continue;
+        }
+
+        // Handle class definitions: classDef name fill:#f00,color:white
+        if let Some(definition) = trimmed.strip_prefix(state_keywords::CLASS_DEF) {
+            if let Some((name, properties)) = definition.trim().split_once(char::is_whitespace) {
+                let class_def = diagram
+                    .class_defs
+                    .entry(name.to_string())
+                    .or_insert_with(|| ClassDef {
+                        name: name.to_string(),
+                        styles: HashMap::new(),
+                        definitions: 0,
+                    });
+                class_def
+                    .styles
+                    .extend(parse_style_properties(properties.trim()));
+                class_def.definitions += 1;
+            }
+            continue;
+        }
+
+        // Handle class assignments: class StateA, StateB name
+        if let Some(assignment) = trimmed.strip_prefix(state_keywords::CLASS) {
+            if let Some((ids, class)) = assignment.trim().rsplit_once(char::is_whitespace) {
+                for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                    scopes.classes.push((id.to_string(), class.to_string()));
+                }
+            }
             continue;
         }
 
         // Handle state declarations
         if trimmed.starts_with(state_keywords::STATE) {
-            if let Some(state) = parse_state_declaration(trimmed, &mut diagram.states) {
+            if let Some(state) = parse_state_declaration(trimmed, &mut scopes.states) {
                 // Check if this is a composite state (ends with {)
+                add_child(&mut scopes, &mut state_stack, &state.id);
                 if trimmed.ends_with(" {") {
-                    if let Some(state_mut) = diagram.states.get_mut(&state.id) {
+                    if let Some(state_mut) = scopes.states.get_mut(&state.id) {
                         state_mut.state_type = StateType::Composite;
                     }
-                    state_stack.push(state.id.clone());
+                    state_stack.push(CompositeFrame::new(&state.id));
                     _brace_count += 1;
-                } else if let Some(parent) = state_stack.last() {
-                    // Add this state as a substate of the parent
-                    if let Some(parent_state) = diagram.states.get_mut(parent) {
-                        parent_state.substates.push(state.id.clone());
-                    }
                 }
             }
             continue;
@@ -92,8 +162,8 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
         // Handle closing brace
         if trimmed == "}" {
             _brace_count -= 1;
-            if !state_stack.is_empty() {
-                state_stack.pop();
+            if let Some(frame) = state_stack.pop() {
+                close_composite(&mut scopes, frame);
             }
             continue;
         }
@@ -101,11 +171,12 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
         // Handle state with opening brace on same line
         if !trimmed.starts_with("note") && trimmed.ends_with(" {") {
             let state_name = trimmed.trim_end_matches(" {").trim();
-            ensure_state_exists(&mut diagram.states, state_name);
-            if let Some(state) = diagram.states.get_mut(state_name) {
+            ensure_state_exists(&mut scopes.states, state_name);
+            add_child(&mut scopes, &mut state_stack, state_name);
+            if let Some(state) = scopes.states.get_mut(state_name) {
                 state.state_type = StateType::Composite;
             }
-            state_stack.push(state_name.to_string());
+            state_stack.push(CompositeFrame::new(state_name));
             _brace_count += 1;
             continue;
         }
@@ -121,72 +192,76 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
             if let Some(next_line) = line_iter.peek() {
                 if next_line.1.trim() == "{" {
                     // This is a composite state declaration
-                    ensure_state_exists(&mut diagram.states, trimmed);
-                    if let Some(state) = diagram.states.get_mut(trimmed) {
+                    ensure_state_exists(&mut scopes.states, trimmed);
+                    add_child(&mut scopes, &mut state_stack, trimmed);
+                    if let Some(state) = scopes.states.get_mut(trimmed) {
                         state.state_type = StateType::Composite;
                     }
-                    state_stack.push(trimmed.to_string());
+                    state_stack.push(CompositeFrame::new(trimmed));
                     continue;
                 }
             }
         }
 
-        // Handle concurrent separator
+        // Handle concurrent separator, which closes the current region
         if trimmed == "--" {
-            // TODO: Handle concurrent regions
+            if let Some(frame) = state_stack.last_mut() {
+                let region = std::mem::take(&mut frame.body);
+                frame.regions.push(region);
+            }
             continue;
         }
 
         // Handle notes
         if trimmed.starts_with("note ") {
             if let Some(note) = parse_note(trimmed) {
-                diagram.notes.push(note);
+                match state_stack.last_mut() {
+                    Some(frame) => frame.body.notes.push(note),
+                    None => diagram.notes.push(note),
+                }
             }
             continue;
         }
 
         // Try to parse as transition
-        if let Some(transition) = parse_transition(trimmed, &mut diagram.states) {
-            // If we're inside a composite state, add the states as substates
-            if let Some(parent) = state_stack.last().cloned() {
-                let from_state = transition.from.clone();
-                let to_state = transition.to.clone();
-
-                if !from_state.starts_with("[") {
-                    if let Some(parent_state) = diagram.states.get_mut(&parent) {
-                        if !parent_state.substates.contains(&from_state) {
-                            parent_state.substates.push(from_state);
-                        }
-                    }
-                }
-                if !to_state.starts_with("[") {
-                    if let Some(parent_state) = diagram.states.get_mut(&parent) {
-                        if !parent_state.substates.contains(&to_state) {
-                            parent_state.substates.push(to_state);
-                        }
-                    }
+        if let Some(transition) = parse_transition(trimmed, &mut scopes) {
+            // States first seen here belong to the innermost composite state
+            for state_id in [&transition.from, &transition.to] {
+                if !state_id.starts_with('[') {
+                    add_child(&mut scopes, &mut state_stack, state_id);
                 }
             }
-            diagram.transitions.push(transition);
+            match state_stack.last_mut() {
+                Some(frame) => frame.body.transitions.push(transition),
+                None => diagram.transitions.push(transition),
+            }
             continue;
         }
 
-        // If we're inside a composite state, the line might be a simple state name
-        if !state_stack.is_empty() && !trimmed.contains("-->") {
-            ensure_state_exists(&mut diagram.states, trimmed);
-            if let Some(parent) = state_stack.last() {
-                if let Some(parent_state) = diagram.states.get_mut(parent) {
-                    parent_state.substates.push(trimmed.to_string());
-                }
-            }
+        // If we're inside a composite state, the line might be a simple state
+        // name; one with a class, such as `Moving:::movement`, may be anywhere
+        if (!state_stack.is_empty() || trimmed.contains(state_keywords::CLASS_SEPARATOR))
+            && !trimmed.contains("-->")
+        {
+            let state_id = state_reference(trimmed, &mut scopes);
+            ensure_state_exists(&mut scopes.states, &state_id);
+            add_child(&mut scopes, &mut state_stack, &state_id);
         }
     }
 
+    // Close composite states left open at the end of the input
+    while let Some(frame) = state_stack.pop() {
+        close_composite(&mut scopes, frame);
+    }
+    scopes.apply_classes();
+    diagram.states = scopes.nest();
+
     // Add start and end states if they were used but not explicitly declared
     if !diagram.states.contains_key("[*]") {
         // Check if [*] is used in any transitions
-        let used_as_start = diagram.transitions.iter().any(|t| t.from == "[*]");
-        let used_as_end = diagram.transitions.iter().any(|t| t.to == "[*]");
+        let transitions = diagram.all_transitions();
+        let used_as_start = transitions.iter().any(|t| t.from == "[*]");
+        let used_as_end = transitions.iter().any(|t| t.to == "[*]");
 
         if used_as_start || used_as_end {
             diagram.states.insert(
@@ -201,8 +276,11 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
                     } else {
                         StateType::Simple // Can be both start and end
                     },
-                    substates: Vec::new(),
+                    states: OrderedMap::new(),
+                    transitions: Vec::new(),
+                    notes: Vec::new(),
                     concurrent_regions: Vec::new(),
+                    classes: Vec::new(),
                 },
             );
         }
@@ -211,8 +289,107 @@ pub fn parse(input: &str) -> Result<StateDiagram> {
     Ok(diagram)
 }
 
+impl CompositeFrame {
+    fn new(id: &str) -> Self {
+        CompositeFrame {
+            id: id.to_string(),
+            body: Body::default(),
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl Scopes {
+    /// Apply the classes assigned so far, creating states that are only
+    /// named by a `class` statement
+    fn apply_classes(&mut self) {
+        for (id, class) in std::mem::take(&mut self.classes) {
+            ensure_state_exists(&mut self.states, &id);
+            self.parents.entry(id.clone()).or_insert(None);
+            let state = self.states.get_mut(&id).expect("state was just ensured");
+            if !state.classes.contains(&class) {
+                state.classes.push(class);
+            }
+        }
+    }
+
+    /// The top-level states, each composite state holding the states that
+    /// first appeared in its body
+    fn nest(mut self) -> OrderedMap<String, State> {
+        let top_level: Vec<String> = self
+            .states
+            .keys()
+            .filter(|id| !matches!(self.parents.get(*id), Some(Some(_))))
+            .cloned()
+            .collect();
+        self.take_all(&top_level)
+    }
+
+    fn take_all(&mut self, ids: &[String]) -> OrderedMap<String, State> {
+        ids.iter()
+            .filter_map(|id| self.take(id))
+            .map(|state| (state.id.clone(), state))
+            .collect()
+    }
+
+    /// Remove the state with `id`, with the states nested inside it
+    fn take(&mut self, id: &str) -> Option<State> {
+        let mut state = self.states.remove(id)?;
+        let mut bodies = self.bodies.remove(id).unwrap_or_default();
+        if bodies.len() == 1 {
+            let body = bodies.remove(0);
+            state.states = self.take_all(&body.states);
+            state.transitions = body.transitions;
+            state.notes = body.notes;
+        } else {
+            state.concurrent_regions = bodies
+                .into_iter()
+                .map(|body| StateRegion {
+                    states: self.take_all(&body.states),
+                    transitions: body.transitions,
+                    notes: body.notes,
+                })
+                .collect();
+        }
+        Some(state)
+    }
+}
+
+/// Record the scope `state_id` first appears in, which is the innermost open
+/// composite state, or the diagram itself outside of any
+fn add_child(scopes: &mut Scopes, frames: &mut [CompositeFrame], state_id: &str) {
+    if scopes.parents.contains_key(state_id) {
+        return;
+    }
+    let frame = frames.last_mut();
+    scopes.parents.insert(
+        state_id.to_string(),
+        frame.as_ref().map(|frame| frame.id.clone()),
+    );
+    if let Some(frame) = frame {
+        frame.body.states.push(state_id.to_string());
+    }
+}
+
+/// Finish a composite state at its closing brace
+///
+/// A body split by `--` becomes the state's concurrent regions. A state whose
+/// body is written in several blocks keeps the statements of all of them.
+fn close_composite(scopes: &mut Scopes, mut frame: CompositeFrame) {
+    frame.regions.push(frame.body);
+    let bodies = scopes.bodies.entry(frame.id).or_default();
+    if bodies.len() == 1 && frame.regions.len() == 1 {
+        let region = frame.regions.remove(0);
+        bodies[0].states.extend(region.states);
+        bodies[0].transitions.extend(region.transitions);
+        bodies[0].notes.extend(region.notes);
+    } else {
+        bodies.extend(frame.regions);
+    }
+}
+
 /// Parse a state declaration line
-fn parse_state_declaration(line: &str, states: &mut HashMap<String, State>) -> Option<State> {
+fn parse_state_declaration(line: &str, states: &mut OrderedMap<String, State>) -> Option<State> {
     let state_text = line
         .strip_prefix("state ")
         .unwrap()
@@ -229,8 +406,11 @@ fn parse_state_declaration(line: &str, states: &mut HashMap<String, State>) -> O
                     id: id.clone(),
                     display_name: Some(display_name),
                     state_type: StateType::Simple,
-                    substates: Vec::new(),
+                    states: OrderedMap::new(),
+                    transitions: Vec::new(),
+                    notes: Vec::new(),
                     concurrent_regions: Vec::new(),
+                    classes: Vec::new(),
                 };
                 states.insert(id, state.clone());
                 return Some(state);
@@ -260,15 +440,18 @@ fn parse_state_declaration(line: &str, states: &mut HashMap<String, State>) -> O
         id: state_id.clone(),
         display_name: None,
         state_type,
-        substates: Vec::new(),
+        states: OrderedMap::new(),
+        transitions: Vec::new(),
+        notes: Vec::new(),
         concurrent_regions: Vec::new(),
+        classes: Vec::new(),
     };
     states.insert(state_id, state.clone());
     Some(state)
 }
 
 /// Parse a transition line
-fn parse_transition(line: &str, states: &mut HashMap<String, State>) -> Option<StateTransition> {
+fn parse_transition(line: &str, scopes: &mut Scopes) -> Option<StateTransition> {
     // Find the arrow
     if !line.contains("-->") {
         return None;
@@ -279,22 +462,22 @@ fn parse_transition(line: &str, states: &mut HashMap<String, State>) -> Option<S
         return None;
     }
 
-    let from = parts[0].trim().to_string();
+    let from = state_reference(parts[0], scopes);
     let to_and_label = parts[1].trim();
 
     // Parse the target and optional label
-    let (to, label) = if let Some(colon_pos) = to_and_label.find(':') {
+    let (to, label) = if let Some(colon_pos) = label_colon(to_and_label) {
         (
-            to_and_label[..colon_pos].trim().to_string(),
+            state_reference(&to_and_label[..colon_pos], scopes),
             Some(to_and_label[colon_pos + 1..].trim().to_string()),
         )
     } else {
-        (to_and_label.to_string(), None)
+        (state_reference(to_and_label, scopes), None)
     };
 
     // Ensure states exist
-    ensure_state_exists(states, &from);
-    ensure_state_exists(states, &to);
+    ensure_state_exists(&mut scopes.states, &from);
+    ensure_state_exists(&mut scopes.states, &to);
 
     // Parse label into event, guard, and action
     let (event, guard, action) = if let Some(label_text) = label {
@@ -312,6 +495,31 @@ fn parse_transition(line: &str, states: &mut HashMap<String, State>) -> Option<S
     })
 }
 
+/// The id of a state reference, recording the class of a `:::name` suffix
+fn state_reference(text: &str, scopes: &mut Scopes) -> String {
+    match text.split_once(state_keywords::CLASS_SEPARATOR) {
+        Some((id, class)) => {
+            let id = id.trim().to_string();
+            scopes.classes.push((id.clone(), class.trim().to_string()));
+            id
+        }
+        None => text.trim().to_string(),
+    }
+}
+
+/// Position of the colon that starts a transition label, skipping the `:::`
+/// of a class suffix
+fn label_colon(text: &str) -> Option<usize> {
+    let mut start = 0;
+    loop {
+        let colon = start + text[start..].find(':')?;
+        if !text[colon..].starts_with(state_keywords::CLASS_SEPARATOR) {
+            return Some(colon);
+        }
+        start = colon + state_keywords::CLASS_SEPARATOR.len();
+    }
+}
+
 /// Parse a transition label into event, guard, and action
 fn parse_transition_label(label: &str) -> (Option<String>, Option<String>, Option<String>) {
     let mut event = None;
@@ -391,7 +599,7 @@ fn parse_note(line: &str) -> Option<StateNote> {
 }
 
 /// Ensure a state exists in the diagram, creating it if necessary
-fn ensure_state_exists(states: &mut HashMap<String, State>, state_id: &str) {
+fn ensure_state_exists(states: &mut OrderedMap<String, State>, state_id: &str) {
     if !states.contains_key(state_id) {
         let state_type = StateType::Simple;
 
@@ -401,8 +609,11 @@ fn ensure_state_exists(states: &mut HashMap<String, State>, state_id: &str) {
                 id: state_id.to_string(),
                 display_name: None,
                 state_type,
-                substates: Vec::new(),
+                states: OrderedMap::new(),
+                transitions: Vec::new(),
+                notes: Vec::new(),
                 concurrent_regions: Vec::new(),
+                classes: Vec::new(),
             },
         );
     }
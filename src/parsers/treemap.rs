@@ -7,6 +7,7 @@ use crate::common::parser_utils::validate_diagram_header;
 use crate::error::{ParseError, Result};
 
 pub fn parse(input: &str) -> Result<TreemapDiagram> {
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -16,6 +17,7 @@ pub fn parse(input: &str) -> Result<TreemapDiagram> {
             found: "end of input".to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
@@ -49,6 +51,7 @@ pub fn parse(input: &str) -> Result<TreemapDiagram> {
             found: lines.first().unwrap_or(&"").to_string(),
             line: 1,
             column: 0,
+            partial: None,
         });
     }
 
@@ -2,15 +2,45 @@
 //!
 //! Parses XY charts with line and bar data visualization,
 //! configurable axes and data series.
+//!
+//! Axis ranges and data points may be negative or have decimals, as in
+//! `y-axis Net change -2.5 --> 7.5`; an unquoted axis title may have several
+//! words. Series take their colors from `themeVariables.xyChart.plotColorPalette`
+//! in the frontmatter config or an `%%{init}%%` directive, the latter taking
+//! precedence.
+//!
+//! ```rust
+//! use mermaid_parser::common::ast::Color;
+//! use mermaid_parser::parsers::xy;
+//!
+//! let input = r##"%%{init: {"themeVariables": {"xyChart": {"plotColorPalette": "#ff0000, #00ff00"}}}}%%
+//! xychart-beta
+//!     y-axis Net change -2.5 --> 7.5
+//!     bar [-1.5, 3]
+//!     line [0.5, 6.25]"##;
+//! let diagram = xy::parse(input)?;
+//! assert_eq!(diagram.y_axis.title.as_deref(), Some("Net change"));
+//! assert_eq!(diagram.y_axis.range, Some((-2.5, 7.5)));
+//! assert_eq!(diagram.data_series[1].color, Some(Color::Hex("00ff00".to_string())));
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
 
 use crate::common::ast::{
-    AccessibilityInfo, ChartOrientation, DataSeries, SeriesType, XAxis, XyChartDiagram, YAxis,
+    AccessibilityInfo, ChartOrientation, Color, DataSeries, SeriesType, XAxis, XyChartDiagram,
+    YAxis,
 };
+use crate::common::frontmatter::{ConfigValue, MermaidConfig};
 use crate::common::parser_utils::validate_diagram_header;
+use crate::common::parsing::directives;
 use crate::error::{ParseError, Result};
 
+/// Separates the two ends of an axis range
+const RANGE_ARROW: &str = "-->";
+
 /// Simple string-based parser for XY chart diagrams
 pub fn parse(input: &str) -> Result<XyChartDiagram> {
+    let palette = plot_color_palette(input);
+    let input = crate::common::parsing::frontmatter::strip(input);
     let lines: Vec<&str> = input.lines().collect();
 
     if lines.is_empty() {
@@ -81,14 +111,16 @@ pub fn parse(input: &str) -> Result<XyChartDiagram> {
 
         // Handle bar series
         if trimmed.starts_with("bar ") {
-            let series = parse_data_series(trimmed, SeriesType::Bar)?;
+            let mut series = parse_data_series(trimmed, SeriesType::Bar)?;
+            series.color = series_color(&palette, diagram.data_series.len());
             diagram.data_series.push(series);
             continue;
         }
 
         // Handle line series
         if trimmed.starts_with("line ") {
-            let series = parse_data_series(trimmed, SeriesType::Line)?;
+            let mut series = parse_data_series(trimmed, SeriesType::Line)?;
+            series.color = series_color(&palette, diagram.data_series.len());
             diagram.data_series.push(series);
         }
     }
@@ -107,29 +139,19 @@ fn parse_x_axis(line: &str, x_axis: &mut XAxis) -> Result<()> {
 
             if remaining.starts_with('[') {
                 x_axis.labels = parse_label_array(remaining)?;
-            } else if remaining.contains("-->") {
+            } else if remaining.contains(RANGE_ARROW) {
                 x_axis.range = parse_range(remaining)?;
             }
         }
     } else if content.starts_with('[') {
         // Just labels, no title
         x_axis.labels = parse_label_array(content)?;
-    } else if content.contains("-->") {
-        // Could be "name range" or just "range"
-        let arrow_pos = content.find("-->").unwrap();
-        let before_arrow = content[..arrow_pos].trim();
-
-        // Check if there are multiple words before the arrow
-        let parts: Vec<&str> = before_arrow.split_whitespace().collect();
-        if parts.len() > 1 {
-            // First part is the title, rest is the start of range
-            x_axis.title = Some(parts[0].to_string());
-            let range_part = &content[parts[0].len()..];
-            x_axis.range = parse_range(range_part.trim())?;
-        } else {
-            // Just a range
-            x_axis.range = parse_range(content)?;
+    } else if content.contains(RANGE_ARROW) {
+        let (title, range) = parse_titled_range(content)?;
+        if title.is_some() {
+            x_axis.title = title;
         }
+        x_axis.range = range;
     } else {
         // It's an unquoted title
         x_axis.title = Some(content.to_string());
@@ -147,26 +169,16 @@ fn parse_y_axis(line: &str, y_axis: &mut YAxis) -> Result<()> {
             y_axis.title = Some(stripped[..quote_end].to_string());
             let remaining = content[quote_end + 2..].trim();
 
-            if remaining.contains("-->") {
+            if remaining.contains(RANGE_ARROW) {
                 y_axis.range = parse_range(remaining)?;
             }
         }
-    } else if content.contains("-->") {
-        // Could be "name range" or just "range"
-        let arrow_pos = content.find("-->").unwrap();
-        let before_arrow = content[..arrow_pos].trim();
-
-        // Check if there are multiple words before the arrow
-        let parts: Vec<&str> = before_arrow.split_whitespace().collect();
-        if parts.len() > 1 {
-            // First part is the title, rest is the start of range
-            y_axis.title = Some(parts[0].to_string());
-            let range_part = &content[parts[0].len()..];
-            y_axis.range = parse_range(range_part.trim())?;
-        } else {
-            // Just a range
-            y_axis.range = parse_range(content)?;
+    } else if content.contains(RANGE_ARROW) {
+        let (title, range) = parse_titled_range(content)?;
+        if title.is_some() {
+            y_axis.title = title;
         }
+        y_axis.range = range;
     } else {
         // Unquoted title
         y_axis.title = Some(content.to_string());
@@ -212,6 +224,7 @@ fn parse_data_series(line: &str, series_type: SeriesType) -> Result<DataSeries>
         series_type,
         name,
         data,
+        color: None,
     })
 }
 
@@ -223,6 +236,7 @@ fn parse_label_array(content: &str) -> Result<Vec<String>> {
             found: content.to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
@@ -236,7 +250,7 @@ fn parse_label_array(content: &str) -> Result<Vec<String>> {
     Ok(labels)
 }
 
-fn parse_data_array(content: &str) -> Result<Vec<f64>> {
+fn parse_data_array(content: &str) -> Result<Vec<Option<f64>>> {
     if !content.starts_with('[') || !content.ends_with(']') {
         return Err(ParseError::SyntaxError {
             message: "Expected array format [num1, num2, ...]".to_string(),
@@ -244,6 +258,7 @@ fn parse_data_array(content: &str) -> Result<Vec<f64>> {
             found: content.to_string(),
             line: 0,
             column: 0,
+            partial: None,
         });
     }
 
@@ -255,27 +270,48 @@ fn parse_data_array(content: &str) -> Result<Vec<f64>> {
     let mut data = Vec::new();
     for item in inner.split(',') {
         let trimmed = item.trim();
-        if !trimmed.is_empty() {
-            let value = trimmed
-                .parse::<f64>()
-                .map_err(|_| ParseError::SyntaxError {
-                    message: format!("Invalid number: {}", trimmed),
-                    expected: vec!["number".to_string()],
-                    found: trimmed.to_string(),
-                    line: 0,
-                    column: 0,
-                })?;
-            data.push(value);
+
+        // Exported charts often contain gaps, written as `null`, `-` or
+        // nothing at all; each keeps the values after it on their x label
+        if trimmed.is_empty() || trimmed == "null" || trimmed == "-" {
+            data.push(None);
+            continue;
         }
+
+        let value = trimmed
+            .parse::<f64>()
+            .map_err(|_| ParseError::SyntaxError {
+                message: format!("Invalid number: {}", trimmed),
+                expected: vec!["number".to_string(), "null".to_string()],
+                found: trimmed.to_string(),
+                line: 0,
+                column: 0,
+                partial: None,
+            })?;
+        data.push(Some(value));
     }
 
     Ok(data)
 }
 
+/// An unquoted title followed by a range, as in `Net change -5 --> 5`: the
+/// word just before the arrow starts the range and any words before it are
+/// the title
+fn parse_titled_range(content: &str) -> Result<(Option<String>, Option<(f64, f64)>)> {
+    let arrow_pos = content.find(RANGE_ARROW).unwrap_or(content.len());
+    let before_arrow = content[..arrow_pos].trim_end();
+    let range_start = before_arrow
+        .rfind(char::is_whitespace)
+        .map_or(0, |position| position + 1);
+    let title = content[..range_start].trim();
+    let range = parse_range(&content[range_start..])?;
+    Ok(((!title.is_empty()).then(|| title.to_string()), range))
+}
+
 fn parse_range(content: &str) -> Result<Option<(f64, f64)>> {
-    if let Some(arrow_pos) = content.find("-->") {
+    if let Some(arrow_pos) = content.find(RANGE_ARROW) {
         let start_str = content[..arrow_pos].trim();
-        let end_str = content[arrow_pos + 3..].trim();
+        let end_str = content[arrow_pos + RANGE_ARROW.len()..].trim();
 
         let start = start_str
             .parse::<f64>()
@@ -285,6 +321,7 @@ fn parse_range(content: &str) -> Result<Option<(f64, f64)>> {
                 found: start_str.to_string(),
                 line: 0,
                 column: 0,
+                partial: None,
             })?;
 
         let end = end_str
@@ -295,6 +332,7 @@ fn parse_range(content: &str) -> Result<Option<(f64, f64)>> {
                 found: end_str.to_string(),
                 line: 0,
                 column: 0,
+                partial: None,
             })?;
 
         Ok(Some((start, end)))
@@ -303,6 +341,57 @@ fn parse_range(content: &str) -> Result<Option<(f64, f64)>> {
     }
 }
 
+/// The colors of `themeVariables.xyChart.plotColorPalette`, a comma
+/// separated string or a list, from the `%%{init}%%` directives or else the
+/// frontmatter config
+fn plot_color_palette(input: &str) -> Vec<Color> {
+    let init = directives::extract(input)
+        .ok()
+        .flatten()
+        .map(|init| init.config);
+    let frontmatter = crate::common::frontmatter::parse(input)
+        .ok()
+        .flatten()
+        .map(|frontmatter| frontmatter.config);
+
+    let palette = |config: &MermaidConfig| -> Option<Vec<String>> {
+        let ConfigValue::Map(theme_variables) = config.extra.get("themeVariables")? else {
+            return None;
+        };
+        let ConfigValue::Map(xy_chart) = theme_variables.get("xyChart")? else {
+            return None;
+        };
+        match xy_chart.get("plotColorPalette")? {
+            ConfigValue::String(colors) => Some(colors.split(',').map(str::to_string).collect()),
+            ConfigValue::List(colors) => Some(
+                colors
+                    .iter()
+                    .filter_map(|color| match color {
+                        ConfigValue::String(color) => Some(color.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        }
+    };
+
+    init.iter()
+        .chain(frontmatter.iter())
+        .find_map(palette)
+        .unwrap_or_default()
+        .iter()
+        .map(|color| color.trim())
+        .filter(|color| !color.is_empty())
+        .map(|color| Color::parse(color).unwrap_or_else(|| Color::Other(color.to_string())))
+        .collect()
+}
+
+/// The palette color of the series at `index`, cycling through the palette
+fn series_color(palette: &[Color], index: usize) -> Option<Color> {
+    (!palette.is_empty()).then(|| palette[index % palette.len()].clone())
+}
+
 fn unquote_string(s: &str) -> String {
     let trimmed = s.trim();
     if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() >= 2 {
@@ -336,7 +425,7 @@ mod tests {
         assert_eq!(diagram.data_series[0].series_type, SeriesType::Bar);
         assert_eq!(
             diagram.data_series[0].data,
-            vec![2500.0, 5000.0, 7500.0, 10000.0]
+            vec![Some(2500.0), Some(5000.0), Some(7500.0), Some(10000.0)]
         );
     }
 
@@ -353,7 +442,10 @@ mod tests {
         assert!(diagram.title.is_none());
         assert_eq!(diagram.x_axis.labels.len(), 3);
         assert_eq!(diagram.data_series[0].series_type, SeriesType::Line);
-        assert_eq!(diagram.data_series[0].data, vec![10.0, 50.0, 30.0]);
+        assert_eq!(
+            diagram.data_series[0].data,
+            vec![Some(10.0), Some(50.0), Some(30.0)]
+        );
     }
 
     #[test]
@@ -386,6 +478,6 @@ mod tests {
         let diagram = parse(input).unwrap();
 
         assert_eq!(diagram.orientation, ChartOrientation::Horizontal);
-        assert_eq!(diagram.data_series[0].data, vec![25.0, 45.0]);
+        assert_eq!(diagram.data_series[0].data, vec![Some(25.0), Some(45.0)]);
     }
 }
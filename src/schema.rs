@@ -0,0 +1,64 @@
+//! JSON Schema of the serialized AST
+//!
+//! With the `serde` feature a parsed diagram can be sent to other languages as
+//! JSON. This module, behind the `schema` feature, describes that JSON as a
+//! JSON Schema (draft 7), generated from the AST types with `schemars`, so
+//! consumers can validate what they receive or generate their own types.
+//!
+//! [`diagram_schema`] describes a whole [`DiagramType`], externally tagged as
+//! serde serializes it; [`schema_for_kind`] describes the AST of one diagram
+//! type alone, e.g. the `Pie` value of `{"Pie": {...}}`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::schema;
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! let json = schema::to_json(&schema::schema_for_kind(DiagramKind::Pie));
+//! assert!(json.contains("\"title\": \"PieDiagram\""));
+//! ```
+
+use crate::common::ast::*;
+use crate::stable::DiagramKind;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+/// Schema of a serialized [`DiagramType`]
+pub fn diagram_schema() -> RootSchema {
+    schema_for!(DiagramType)
+}
+
+/// Schema of the serialized AST of one diagram type
+pub fn schema_for_kind(kind: DiagramKind) -> RootSchema {
+    match kind {
+        DiagramKind::Sankey => schema_for!(SankeyDiagram),
+        DiagramKind::Timeline => schema_for!(TimelineDiagram),
+        DiagramKind::Journey => schema_for!(JourneyDiagram),
+        DiagramKind::Sequence => schema_for!(SequenceDiagram),
+        DiagramKind::Class => schema_for!(ClassDiagram),
+        DiagramKind::State => schema_for!(StateDiagram),
+        DiagramKind::Flowchart => schema_for!(FlowchartDiagram),
+        DiagramKind::Gantt => schema_for!(GanttDiagram),
+        DiagramKind::Pie => schema_for!(PieDiagram),
+        DiagramKind::Git => schema_for!(GitDiagram),
+        DiagramKind::Er => schema_for!(ErDiagram),
+        DiagramKind::C4 => schema_for!(C4Diagram),
+        DiagramKind::Mindmap => schema_for!(MindmapDiagram),
+        DiagramKind::Quadrant => schema_for!(QuadrantDiagram),
+        DiagramKind::XyChart => schema_for!(XyChartDiagram),
+        DiagramKind::Kanban => schema_for!(KanbanDiagram),
+        DiagramKind::Block => schema_for!(BlockDiagram),
+        DiagramKind::Architecture => schema_for!(ArchitectureDiagram),
+        DiagramKind::Packet => schema_for!(PacketDiagram),
+        DiagramKind::Requirement => schema_for!(RequirementDiagram),
+        DiagramKind::Treemap => schema_for!(TreemapDiagram),
+        DiagramKind::Radar => schema_for!(RadarDiagram),
+        DiagramKind::Misc => schema_for!(MiscDiagram),
+    }
+}
+
+/// Render a schema as pretty-printed JSON
+pub fn to_json(schema: &RootSchema) -> String {
+    serde_json::to_string_pretty(schema).expect("schemas always serialize")
+}
@@ -0,0 +1,729 @@
+//! Stable, semver-guarded view of the AST for downstream tools
+//!
+//! The internal AST in [`crate::common::ast`] tracks the parsers closely and gains
+//! fields whenever a parser learns new syntax, which breaks exhaustive matches and
+//! struct patterns in downstream formatters and linters. This module exposes a
+//! separate representation with an additive evolution policy:
+//!
+//! - Every enum and struct here is `#[non_exhaustive]`, so new variants and fields
+//!   can be added in minor releases without breaking downstream code.
+//! - Existing variants and fields are never removed or changed in meaning outside a
+//!   major release.
+//! - Diagram types without a stable representation yet are reported as
+//!   [`Diagram::Other`] with their [`DiagramKind`]; gaining a dedicated variant later
+//!   is an additive change.
+//! - Collections are ordered deterministically (source order, or sorted by id where
+//!   the internal AST uses a map), so output does not depend on hash ordering.
+//!
+//! Values are built from the internal representation with [`From`], or directly
+//! from source text with [`parse`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::stable::{self, Diagram};
+//!
+//! let diagram = stable::parse("flowchart LR\n    A[Start] --> B[End]")?;
+//! if let Diagram::Flowchart(flowchart) = &diagram {
+//!     assert_eq!(flowchart.nodes.len(), 2);
+//!     assert_eq!(flowchart.edges[0].from, "A");
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast;
+use crate::error::Result;
+
+/// Parse Mermaid source straight into the stable representation
+pub fn parse(input: &str) -> Result<Diagram> {
+    crate::parse_diagram(input).map(|diagram| Diagram::from(&diagram))
+}
+
+/// A parsed diagram
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Diagram {
+    Flowchart(FlowchartDiagram),
+    Sequence(SequenceDiagram),
+    Class(ClassDiagram),
+    State(StateDiagram),
+    Pie(PieDiagram),
+    Sankey(SankeyDiagram),
+    /// A diagram type that has no stable representation yet
+    Other(DiagramKind),
+}
+
+impl Diagram {
+    /// The kind of diagram, available for every variant including [`Diagram::Other`]
+    pub fn kind(&self) -> DiagramKind {
+        match self {
+            Diagram::Flowchart(_) => DiagramKind::Flowchart,
+            Diagram::Sequence(_) => DiagramKind::Sequence,
+            Diagram::Class(_) => DiagramKind::Class,
+            Diagram::State(_) => DiagramKind::State,
+            Diagram::Pie(_) => DiagramKind::Pie,
+            Diagram::Sankey(_) => DiagramKind::Sankey,
+            Diagram::Other(kind) => *kind,
+        }
+    }
+}
+
+/// Every diagram type the parser understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagramKind {
+    Sankey,
+    Timeline,
+    Journey,
+    Sequence,
+    Class,
+    State,
+    Flowchart,
+    Gantt,
+    Pie,
+    Git,
+    Er,
+    C4,
+    Mindmap,
+    Quadrant,
+    XyChart,
+    Kanban,
+    Block,
+    Architecture,
+    Packet,
+    Requirement,
+    Treemap,
+    Radar,
+    Misc,
+}
+
+impl From<&ast::DiagramType> for DiagramKind {
+    fn from(diagram: &ast::DiagramType) -> Self {
+        use ast::DiagramType as D;
+        match diagram {
+            D::Sankey(_) => DiagramKind::Sankey,
+            D::Timeline(_) => DiagramKind::Timeline,
+            D::Journey(_) => DiagramKind::Journey,
+            D::Sequence(_) => DiagramKind::Sequence,
+            D::Class(_) => DiagramKind::Class,
+            D::State(_) => DiagramKind::State,
+            D::Flowchart(_) => DiagramKind::Flowchart,
+            D::Gantt(_) => DiagramKind::Gantt,
+            D::Pie(_) => DiagramKind::Pie,
+            D::Git(_) => DiagramKind::Git,
+            D::Er(_) => DiagramKind::Er,
+            D::C4(_) => DiagramKind::C4,
+            D::Mindmap(_) => DiagramKind::Mindmap,
+            D::Quadrant(_) => DiagramKind::Quadrant,
+            D::XyChart(_) => DiagramKind::XyChart,
+            D::Kanban(_) => DiagramKind::Kanban,
+            D::Block(_) => DiagramKind::Block,
+            D::Architecture(_) => DiagramKind::Architecture,
+            D::Packet(_) => DiagramKind::Packet,
+            D::Requirement(_) => DiagramKind::Requirement,
+            D::Treemap(_) => DiagramKind::Treemap,
+            D::Radar(_) => DiagramKind::Radar,
+            D::Misc(_) => DiagramKind::Misc,
+        }
+    }
+}
+
+impl From<&ast::DiagramType> for Diagram {
+    fn from(diagram: &ast::DiagramType) -> Self {
+        match diagram {
+            ast::DiagramType::Flowchart(d) => Diagram::Flowchart(d.into()),
+            ast::DiagramType::Sequence(d) => Diagram::Sequence(d.into()),
+            ast::DiagramType::Class(d) => Diagram::Class(d.into()),
+            ast::DiagramType::State(d) => Diagram::State(d.into()),
+            ast::DiagramType::Pie(d) => Diagram::Pie(d.into()),
+            ast::DiagramType::Sankey(d) => Diagram::Sankey(d.into()),
+            other => Diagram::Other(other.into()),
+        }
+    }
+}
+
+/// Title and accessibility text shared by all diagrams
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Metadata {
+    pub title: Option<String>,
+    pub acc_title: Option<String>,
+    pub acc_description: Option<String>,
+}
+
+impl Metadata {
+    fn new(title: &Option<String>, accessibility: &ast::AccessibilityInfo) -> Self {
+        Metadata {
+            title: title.clone(),
+            acc_title: accessibility.title.clone(),
+            acc_description: accessibility.description.clone(),
+        }
+    }
+}
+
+/// A flowchart; nodes are sorted by id
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FlowchartDiagram {
+    pub metadata: Metadata,
+    pub direction: FlowDirection,
+    pub nodes: Vec<FlowNode>,
+    pub edges: Vec<FlowEdge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FlowDirection {
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FlowNode {
+    pub id: String,
+    pub label: Option<String>,
+    pub shape: NodeShape,
+    pub classes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NodeShape {
+    Rectangle,
+    RoundedRectangle,
+    Stadium,
+    Subroutine,
+    Cylinder,
+    Circle,
+    DoubleCircle,
+    Asymmetric,
+    Rhombus,
+    Hexagon,
+    Parallelogram,
+    ParallelogramAlt,
+    Trapezoid,
+    TrapezoidAlt,
+    Image,
+    /// A Mermaid v11 shape written as `@{ shape: name }`
+    Special,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct FlowEdge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EdgeKind {
+    Arrow,
+    DottedArrow,
+    ThickArrow,
+    OpenLink,
+    DottedLink,
+    ThickLink,
+    Invisible,
+    CircleEnd,
+    CrossEnd,
+    Bidirectional,
+    /// A circle at both ends: `o--o`
+    BidirectionalCircle,
+    /// A cross at both ends: `x--x`
+    BidirectionalCross,
+}
+
+impl From<&ast::FlowchartDiagram> for FlowchartDiagram {
+    fn from(diagram: &ast::FlowchartDiagram) -> Self {
+        let mut nodes: Vec<FlowNode> = diagram
+            .nodes
+            .values()
+            .map(|node| FlowNode {
+                id: node.id.clone(),
+                label: node.text.clone(),
+                shape: (&node.shape).into(),
+                classes: node.classes.clone(),
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        FlowchartDiagram {
+            metadata: Metadata::new(&diagram.title, &diagram.accessibility),
+            direction: match diagram.direction {
+                ast::FlowDirection::TB | ast::FlowDirection::TD => FlowDirection::TopDown,
+                ast::FlowDirection::BT => FlowDirection::BottomUp,
+                ast::FlowDirection::LR => FlowDirection::LeftRight,
+                ast::FlowDirection::RL => FlowDirection::RightLeft,
+            },
+            nodes,
+            edges: diagram
+                .edges
+                .iter()
+                .map(|edge| FlowEdge {
+                    from: edge.from.clone(),
+                    to: edge.to.clone(),
+                    label: edge.label.clone(),
+                    kind: (&edge.edge_type).into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&ast::NodeShape> for NodeShape {
+    fn from(shape: &ast::NodeShape) -> Self {
+        match shape {
+            ast::NodeShape::Rectangle => NodeShape::Rectangle,
+            ast::NodeShape::RoundedRectangle => NodeShape::RoundedRectangle,
+            ast::NodeShape::Stadium => NodeShape::Stadium,
+            ast::NodeShape::Subroutine => NodeShape::Subroutine,
+            ast::NodeShape::Cylinder => NodeShape::Cylinder,
+            ast::NodeShape::Circle => NodeShape::Circle,
+            ast::NodeShape::DoubleCircle => NodeShape::DoubleCircle,
+            ast::NodeShape::Asymmetric => NodeShape::Asymmetric,
+            ast::NodeShape::Rhombus => NodeShape::Rhombus,
+            ast::NodeShape::Hexagon => NodeShape::Hexagon,
+            ast::NodeShape::Parallelogram => NodeShape::Parallelogram,
+            ast::NodeShape::ParallelogramAlt => NodeShape::ParallelogramAlt,
+            ast::NodeShape::Trapezoid => NodeShape::Trapezoid,
+            ast::NodeShape::TrapezoidAlt => NodeShape::TrapezoidAlt,
+            ast::NodeShape::Image { .. } => NodeShape::Image,
+            ast::NodeShape::Special(_) => NodeShape::Special,
+        }
+    }
+}
+
+impl From<&ast::EdgeType> for EdgeKind {
+    fn from(edge_type: &ast::EdgeType) -> Self {
+        use ast::{EdgeHead as Head, EdgeLineStyle as Line};
+        match (
+            edge_type.left_head,
+            edge_type.right_head,
+            edge_type.line_style,
+        ) {
+            (_, _, Line::Invisible) => EdgeKind::Invisible,
+            (Head::Arrow, Head::Arrow, _) => EdgeKind::Bidirectional,
+            (Head::Circle, Head::Circle, _) => EdgeKind::BidirectionalCircle,
+            (Head::Cross, Head::Cross, _) => EdgeKind::BidirectionalCross,
+            (_, Head::Circle, _) => EdgeKind::CircleEnd,
+            (_, Head::Cross, _) => EdgeKind::CrossEnd,
+            (_, Head::Arrow, Line::Solid) => EdgeKind::Arrow,
+            (_, Head::Arrow, Line::Dotted) => EdgeKind::DottedArrow,
+            (_, Head::Arrow, Line::Thick) => EdgeKind::ThickArrow,
+            (_, Head::None, Line::Solid) => EdgeKind::OpenLink,
+            (_, Head::None, Line::Dotted) => EdgeKind::DottedLink,
+            (_, Head::None, Line::Thick) => EdgeKind::ThickLink,
+        }
+    }
+}
+
+/// A sequence diagram; messages from nested blocks are flattened in source order
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SequenceDiagram {
+    pub metadata: Metadata,
+    pub participants: Vec<Participant>,
+    pub messages: Vec<Message>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Participant {
+    pub id: String,
+    pub alias: Option<String>,
+    pub kind: ParticipantKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParticipantKind {
+    Participant,
+    Actor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Message {
+    pub from: String,
+    pub to: String,
+    pub text: String,
+    pub arrow: MessageArrow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MessageArrow {
+    SolidOpen,
+    SolidClosed,
+    DottedOpen,
+    DottedClosed,
+    Cross,
+    Point,
+    BidirectionalSolid,
+    BidirectionalDotted,
+}
+
+impl From<&ast::SequenceDiagram> for SequenceDiagram {
+    fn from(diagram: &ast::SequenceDiagram) -> Self {
+        let mut messages = Vec::new();
+        collect_messages(&diagram.statements, &mut messages);
+
+        SequenceDiagram {
+            metadata: Metadata::new(&diagram.title, &diagram.accessibility),
+            participants: diagram
+                .participants
+                .iter()
+                .map(|participant| Participant {
+                    id: participant.actor.clone(),
+                    alias: participant.alias.clone(),
+                    kind: match participant.participant_type {
+                        ast::ParticipantType::Participant => ParticipantKind::Participant,
+                        ast::ParticipantType::Actor => ParticipantKind::Actor,
+                    },
+                })
+                .collect(),
+            messages,
+        }
+    }
+}
+
+fn collect_messages(statements: &[ast::SequenceStatement], messages: &mut Vec<Message>) {
+    use ast::SequenceStatement as S;
+
+    for statement in statements {
+        match statement {
+            S::Message(message) => messages.push(Message {
+                from: message.from.clone(),
+                to: message.to.clone(),
+                text: message.text.clone(),
+                arrow: (&message.arrow_type).into(),
+            }),
+            S::Loop(block) => collect_messages(&block.statements, messages),
+            S::Opt(block) => collect_messages(&block.statements, messages),
+            S::Break(block) => collect_messages(&block.statements, messages),
+            S::Critical(block) => collect_messages(&block.statements, messages),
+            S::Rect { statements, .. } => collect_messages(statements, messages),
+            S::Alt(block) => {
+                collect_messages(&block.statements, messages);
+                if let Some(else_branch) = &block.else_branch {
+                    collect_messages(&else_branch.statements, messages);
+                }
+            }
+            S::Par(block) => {
+                for branch in &block.branches {
+                    collect_messages(&branch.statements, messages);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl From<&ast::ArrowType> for MessageArrow {
+    fn from(arrow: &ast::ArrowType) -> Self {
+        match arrow {
+            ast::ArrowType::SolidOpen => MessageArrow::SolidOpen,
+            ast::ArrowType::SolidClosed => MessageArrow::SolidClosed,
+            ast::ArrowType::DottedOpen => MessageArrow::DottedOpen,
+            ast::ArrowType::DottedClosed => MessageArrow::DottedClosed,
+            ast::ArrowType::Cross => MessageArrow::Cross,
+            ast::ArrowType::Point => MessageArrow::Point,
+            ast::ArrowType::BiDirectionalSolid => MessageArrow::BidirectionalSolid,
+            ast::ArrowType::BiDirectionalDotted => MessageArrow::BidirectionalDotted,
+        }
+    }
+}
+
+/// A class diagram; classes are sorted by name
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ClassDiagram {
+    pub metadata: Metadata,
+    pub classes: Vec<Class>,
+    pub relationships: Vec<ClassRelationship>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Class {
+    pub name: String,
+    /// Stereotype name without the `<<` `>>` delimiters, e.g. `interface`
+    pub stereotype: Option<String>,
+    /// Member names in declaration order
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ClassRelationship {
+    pub from: String,
+    pub to: String,
+    pub kind: ClassRelationshipKind,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClassRelationshipKind {
+    Inheritance,
+    Composition,
+    Aggregation,
+    Association,
+    Link,
+    DashedLink,
+    Dependency,
+    Realization,
+}
+
+impl From<&ast::ClassDiagram> for ClassDiagram {
+    fn from(diagram: &ast::ClassDiagram) -> Self {
+        let mut classes: Vec<Class> = diagram
+            .classes
+            .values()
+            .map(|class| Class {
+                name: class.name.clone(),
+                stereotype: class
+                    .stereotype
+                    .as_ref()
+                    .map(|stereotype| match stereotype {
+                        ast::Stereotype::Interface => "interface".to_string(),
+                        ast::Stereotype::Abstract => "abstract".to_string(),
+                        ast::Stereotype::Service => "service".to_string(),
+                        ast::Stereotype::Enumeration => "enumeration".to_string(),
+                        ast::Stereotype::Exception => "exception".to_string(),
+                        ast::Stereotype::Custom(name) => name.clone(),
+                    }),
+                members: class
+                    .members
+                    .iter()
+                    .map(|member| match member {
+                        ast::ClassMember::Property(property) => property.name.clone(),
+                        ast::ClassMember::Method(method) => method.name.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        classes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ClassDiagram {
+            metadata: Metadata::new(&diagram.title, &diagram.accessibility),
+            classes,
+            relationships: diagram
+                .relationships
+                .iter()
+                .map(|relationship| ClassRelationship {
+                    from: relationship.from.clone(),
+                    to: relationship.to.clone(),
+                    kind: match relationship.relationship_type {
+                        ast::ClassRelationshipType::Inheritance => {
+                            ClassRelationshipKind::Inheritance
+                        }
+                        ast::ClassRelationshipType::Composition => {
+                            ClassRelationshipKind::Composition
+                        }
+                        ast::ClassRelationshipType::Aggregation => {
+                            ClassRelationshipKind::Aggregation
+                        }
+                        ast::ClassRelationshipType::Association => {
+                            ClassRelationshipKind::Association
+                        }
+                        ast::ClassRelationshipType::Link => ClassRelationshipKind::Link,
+                        ast::ClassRelationshipType::DashedLink => ClassRelationshipKind::DashedLink,
+                        ast::ClassRelationshipType::Dependency => ClassRelationshipKind::Dependency,
+                        ast::ClassRelationshipType::Realization => {
+                            ClassRelationshipKind::Realization
+                        }
+                    },
+                    label: relationship.label.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A state diagram; states are sorted by id
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct StateDiagram {
+    pub metadata: Metadata,
+    pub states: Vec<State>,
+    pub transitions: Vec<StateTransition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct State {
+    pub id: String,
+    pub label: Option<String>,
+    /// Ids of states nested directly inside this one
+    pub children: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct StateTransition {
+    /// Source state id; `[*]` for the initial pseudo-state
+    pub from: String,
+    /// Target state id; `[*]` for the final pseudo-state
+    pub to: String,
+    pub label: Option<String>,
+}
+
+impl From<&ast::StateDiagram> for StateDiagram {
+    fn from(diagram: &ast::StateDiagram) -> Self {
+        let mut states: Vec<State> = diagram
+            .all_states()
+            .into_iter()
+            .map(|state| State {
+                id: state.id.clone(),
+                label: state.display_name.clone(),
+                children: state
+                    .children()
+                    .into_iter()
+                    .map(|child| child.id.clone())
+                    .collect(),
+            })
+            .collect();
+        states.sort_by(|a, b| a.id.cmp(&b.id));
+
+        StateDiagram {
+            metadata: Metadata::new(&diagram.title, &diagram.accessibility),
+            states,
+            transitions: diagram
+                .all_transitions()
+                .into_iter()
+                .map(|transition| StateTransition {
+                    from: transition.from.clone(),
+                    to: transition.to.clone(),
+                    label: transition.event.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A pie chart
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct PieDiagram {
+    pub metadata: Metadata,
+    pub show_data: bool,
+    pub slices: Vec<PieSlice>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct PieSlice {
+    pub label: String,
+    pub value: f64,
+}
+
+impl From<&ast::PieDiagram> for PieDiagram {
+    fn from(diagram: &ast::PieDiagram) -> Self {
+        PieDiagram {
+            metadata: Metadata::new(&diagram.title, &diagram.accessibility),
+            show_data: diagram.show_data,
+            slices: diagram
+                .data
+                .iter()
+                .map(|slice| PieSlice {
+                    label: slice.label.clone(),
+                    value: slice.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A Sankey diagram
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SankeyDiagram {
+    pub nodes: Vec<SankeyNode>,
+    pub links: Vec<SankeyLink>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SankeyNode {
+    pub id: String,
+    pub name: String,
+    pub metadata: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SankeyLink {
+    pub source: String,
+    pub target: String,
+    pub value: f64,
+}
+
+impl From<&ast::SankeyDiagram> for SankeyDiagram {
+    fn from(diagram: &ast::SankeyDiagram) -> Self {
+        SankeyDiagram {
+            nodes: diagram
+                .nodes
+                .iter()
+                .map(|node| SankeyNode {
+                    id: node.id.clone(),
+                    name: node.name.clone(),
+                    metadata: node.metadata.clone(),
+                })
+                .collect(),
+            links: diagram
+                .links
+                .iter()
+                .map(|link| SankeyLink {
+                    source: link.source.clone(),
+                    target: link.target.clone(),
+                    value: link.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flowchart_nodes_sorted_by_id() {
+        let diagram = parse("flowchart TD\n    C[c] --> A[a]\n    A --> B[b]").unwrap();
+        let Diagram::Flowchart(flowchart) = diagram else {
+            panic!("Expected flowchart");
+        };
+
+        let ids: Vec<&str> = flowchart.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["A", "B", "C"]);
+        assert_eq!(flowchart.direction, FlowDirection::TopDown);
+        assert_eq!(flowchart.edges[0].kind, EdgeKind::Arrow);
+    }
+
+    #[test]
+    fn test_sequence_messages_flattened() {
+        let input =
+            "sequenceDiagram\n    A->>B: one\n    loop Every time\n        B-->>A: two\n    end";
+        let Diagram::Sequence(sequence) = parse(input).unwrap() else {
+            panic!("Expected sequence diagram");
+        };
+
+        let texts: Vec<&str> = sequence.messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, vec!["one", "two"]);
+        assert_eq!(sequence.messages[1].arrow, MessageArrow::DottedClosed);
+    }
+
+    #[test]
+    fn test_unconverted_diagram_reports_kind() {
+        let diagram = parse("timeline\n    title History").unwrap();
+        assert_eq!(diagram, Diagram::Other(DiagramKind::Timeline));
+        assert_eq!(diagram.kind(), DiagramKind::Timeline);
+    }
+}
@@ -0,0 +1,245 @@
+//! Parser coverage of the Mermaid fixture corpus
+//!
+//! The `test/<type>/` directories hold samples extracted from the mermaid-js
+//! documentation and test suite. [`report`] parses every sample in one of
+//! them and records, for each grammar feature (a literal of the diagram's
+//! [`crate::grammar`]), how many samples use it and how many of those parse
+//! into the expected diagram type. Samples that fail, or that fall back to a
+//! different type such as [`DiagramKind::Misc`], are listed individually.
+//!
+//! [`CoverageReport::to_json`] renders a machine-readable summary so coverage
+//! can be tracked from one release to the next.
+
+use crate::common::escape::json_string;
+use crate::error::Result;
+use crate::grammar;
+use crate::parse_diagram;
+use crate::stable::DiagramKind;
+use std::path::{Path, PathBuf};
+
+/// Extension of fixture files
+pub const FIXTURE_EXTENSION: &str = "mermaid";
+
+/// Name of the `test/` subdirectory holding the fixtures for a diagram kind
+pub fn fixture_dir_name(kind: DiagramKind) -> &'static str {
+    match kind {
+        DiagramKind::Sankey => "sankey",
+        DiagramKind::Timeline => "timeline",
+        DiagramKind::Journey => "journey",
+        DiagramKind::Sequence => "sequence",
+        DiagramKind::Class => "class",
+        DiagramKind::State => "state",
+        DiagramKind::Flowchart => "flowchart",
+        DiagramKind::Gantt => "gantt",
+        DiagramKind::Pie => "pie",
+        DiagramKind::Git => "git",
+        DiagramKind::Er => "er",
+        DiagramKind::C4 => "c4",
+        DiagramKind::Mindmap => "mindmap",
+        DiagramKind::Quadrant => "quadrant",
+        DiagramKind::XyChart => "xy",
+        DiagramKind::Kanban => "kanban",
+        DiagramKind::Block => "block",
+        DiagramKind::Architecture => "architecture",
+        DiagramKind::Packet => "packet",
+        DiagramKind::Requirement => "requirement",
+        DiagramKind::Treemap => "treemap",
+        DiagramKind::Radar => "radar",
+        DiagramKind::Misc => "misc",
+    }
+}
+
+/// How a single fixture fared
+#[derive(Debug, Clone, PartialEq)]
+pub enum FixtureOutcome {
+    /// Parsed into the expected diagram type
+    Parsed,
+    /// Parsed, but into a different diagram type
+    OtherKind(DiagramKind),
+    /// Failed to parse, with the error message
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureResult {
+    pub path: PathBuf,
+    pub outcome: FixtureOutcome,
+}
+
+/// Usage of one grammar feature across the fixtures
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureCoverage {
+    pub feature: &'static str,
+    /// Fixtures that use the feature
+    pub fixtures: usize,
+    /// Of those, fixtures that parsed into the expected type
+    pub parsed: usize,
+}
+
+/// Outcome of a [`report`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageReport {
+    pub kind: DiagramKind,
+    /// Every non-empty fixture, sorted by path
+    pub fixtures: Vec<FixtureResult>,
+    /// Grammar features in grammar order; empty for diagram types without a grammar
+    pub features: Vec<FeatureCoverage>,
+}
+
+impl CoverageReport {
+    /// Number of fixtures that parsed into the expected type
+    pub fn parsed(&self) -> usize {
+        self.fixtures
+            .iter()
+            .filter(|fixture| fixture.outcome == FixtureOutcome::Parsed)
+            .count()
+    }
+
+    /// Fixtures that failed or parsed into another type
+    pub fn unparsed(&self) -> impl Iterator<Item = &FixtureResult> {
+        self.fixtures
+            .iter()
+            .filter(|fixture| fixture.outcome != FixtureOutcome::Parsed)
+    }
+
+    /// Share of fixtures that parsed into the expected type, from 0.0 to 1.0
+    pub fn ratio(&self) -> f64 {
+        if self.fixtures.is_empty() {
+            return 0.0;
+        }
+        self.parsed() as f64 / self.fixtures.len() as f64
+    }
+
+    /// Render the report as a JSON object
+    pub fn to_json(&self) -> String {
+        let features = self
+            .features
+            .iter()
+            .map(|feature| {
+                format!(
+                    "{{\"feature\":{},\"fixtures\":{},\"parsed\":{}}}",
+                    json_string(feature.feature),
+                    feature.fixtures,
+                    feature.parsed
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let unparsed = self
+            .unparsed()
+            .map(|fixture| {
+                let (outcome, detail) = match &fixture.outcome {
+                    FixtureOutcome::Parsed => ("parsed", String::new()),
+                    FixtureOutcome::OtherKind(kind) => ("other_kind", format!("{:?}", kind)),
+                    FixtureOutcome::Failed(message) => ("failed", message.clone()),
+                };
+                format!(
+                    "{{\"path\":{},\"outcome\":\"{}\",\"detail\":{}}}",
+                    json_string(&fixture.path.display().to_string()),
+                    outcome,
+                    json_string(&detail)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{{\"kind\":\"{:?}\",\"fixtures\":{},\"parsed\":{},\"features\":[{}],\"unparsed\":[{}]}}",
+            self.kind,
+            self.fixtures.len(),
+            self.parsed(),
+            features,
+            unparsed
+        )
+    }
+}
+
+/// Parse every fixture in `dir` as a `kind` diagram and summarize the result
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mermaid_parser::stable::DiagramKind;
+/// use mermaid_parser::testing::coverage;
+///
+/// let kind = DiagramKind::Flowchart;
+/// let dir = std::path::Path::new("test").join(coverage::fixture_dir_name(kind));
+/// let report = coverage::report(kind, dir)?;
+/// println!("{}/{} parsed", report.parsed(), report.fixtures.len());
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+pub fn report(kind: DiagramKind, dir: impl AsRef<Path>) -> Result<CoverageReport> {
+    let dir = dir.as_ref();
+    let literals = grammar::for_kind(kind)
+        .map(|grammar| grammar.literals())
+        .unwrap_or_default();
+    let mut features: Vec<FeatureCoverage> = literals
+        .into_iter()
+        .map(|feature| FeatureCoverage {
+            feature,
+            fixtures: 0,
+            parsed: 0,
+        })
+        .collect();
+    let mut fixtures = Vec::new();
+
+    for path in crate::fs::files_with_extension(dir, FIXTURE_EXTENSION)? {
+        let content = crate::fs::read_diagram(&path)?;
+        if content.is_empty() {
+            continue;
+        }
+
+        let outcome = match parse_diagram(&content) {
+            Ok(diagram) if DiagramKind::from(&diagram) == kind => FixtureOutcome::Parsed,
+            Ok(diagram) => FixtureOutcome::OtherKind(DiagramKind::from(&diagram)),
+            Err(error) => FixtureOutcome::Failed(error.to_string()),
+        };
+
+        for feature in &mut features {
+            if uses_feature(&content, feature.feature) {
+                feature.fixtures += 1;
+                if outcome == FixtureOutcome::Parsed {
+                    feature.parsed += 1;
+                }
+            }
+        }
+        fixtures.push(FixtureResult { path, outcome });
+    }
+
+    Ok(CoverageReport {
+        kind,
+        fixtures,
+        features,
+    })
+}
+
+/// Keywords must appear as whole words; punctuation anywhere
+fn uses_feature(content: &str, feature: &str) -> bool {
+    if !feature
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return content.contains(feature);
+    }
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    content.match_indices(feature).any(|(start, _)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + feature.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_features_match_whole_words() {
+        assert!(uses_feature("pie title Pets", "title"));
+        assert!(!uses_feature("pie subtitle", "title"));
+        assert!(!uses_feature("A[x] --> B", "graph"));
+        assert!(uses_feature("A[x]", "["));
+    }
+}
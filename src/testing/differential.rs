@@ -0,0 +1,382 @@
+//! Differential testing of two parser versions over a diagram corpus
+//!
+//! Before upgrading, it is worth knowing which of one's own diagrams parse
+//! differently under the new release. [`compare`] runs two
+//! [`VersionedParser`]s over the same corpus and lists every diagram whose
+//! result changed: a diagram that stopped parsing, one that started parsing,
+//! one whose AST changed, or one that fails with a different error.
+//! [`compare_dir`] does the same for every `.mermaid` and `.mmd` file under a
+//! directory.
+//!
+//! The two versions cannot share AST types, so results are compared as
+//! [`Snapshot`]s: a canonical rendering of the AST's `Debug` output in which
+//! map entries are sorted. [`CurrentParser`] is this build of the crate; an
+//! older release, added as a dependency under a renamed package, is wrapped in
+//! a [`ParserFn`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::testing::differential::{self, CurrentParser, ParserFn, Snapshot};
+//!
+//! // Stands in for `ParserFn::new("0.4.0", |input| Snapshot::from_result(mermaid_parser_04::parse_diagram(input)))`
+//! let previous = ParserFn::new("previous", |input: &str| {
+//!     Snapshot::from_result(mermaid_parser::parse_diagram(input))
+//! });
+//!
+//! let corpus = [("orders.mmd", "flowchart TD\n    A --> B")];
+//! let report = differential::compare(&previous, &CurrentParser, corpus);
+//! assert_eq!(report.compared, 1);
+//! assert!(report.is_compatible());
+//! ```
+
+use crate::common::escape::json_string;
+use crate::error::Result;
+use crate::fs::io_error;
+use std::fmt::{Debug, Display};
+use std::fs;
+use std::path::Path;
+
+/// One version of the parser
+pub trait VersionedParser {
+    /// Version shown in reports, such as the crate version
+    fn version(&self) -> String;
+
+    /// Parse `input` into a comparable result
+    fn parse(&self, input: &str) -> Snapshot;
+}
+
+/// Result of parsing one diagram, comparable across versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Snapshot {
+    /// Canonical rendering of the AST, see [`canonical_debug`]
+    Parsed(String),
+    /// Error message
+    Failed(String),
+}
+
+impl Snapshot {
+    /// Snapshot of a parse result of any version of the crate
+    pub fn from_result<T: Debug, E: Display>(result: std::result::Result<T, E>) -> Self {
+        match result {
+            Ok(diagram) => Snapshot::Parsed(canonical_debug(&diagram)),
+            Err(error) => Snapshot::Failed(error.to_string()),
+        }
+    }
+
+    pub fn is_parsed(&self) -> bool {
+        matches!(self, Snapshot::Parsed(_))
+    }
+}
+
+/// This build of the crate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CurrentParser;
+
+impl VersionedParser for CurrentParser {
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn parse(&self, input: &str) -> Snapshot {
+        Snapshot::from_result(crate::parse_diagram(input))
+    }
+}
+
+/// A parser version backed by a closure
+pub struct ParserFn<F> {
+    version: String,
+    parse: F,
+}
+
+impl<F: Fn(&str) -> Snapshot> ParserFn<F> {
+    pub fn new(version: impl Into<String>, parse: F) -> Self {
+        Self {
+            version: version.into(),
+            parse,
+        }
+    }
+}
+
+impl<F: Fn(&str) -> Snapshot> VersionedParser for ParserFn<F> {
+    fn version(&self) -> String {
+        self.version.clone()
+    }
+
+    fn parse(&self, input: &str) -> Snapshot {
+        (self.parse)(input)
+    }
+}
+
+/// How a diagram's result differs between the two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// Parsed with the baseline, fails with the candidate
+    Regressed,
+    /// Failed with the baseline, parses with the candidate
+    Fixed,
+    /// Parses with both, into different ASTs
+    Changed,
+    /// Fails with both, with different errors
+    ErrorChanged,
+}
+
+impl Change {
+    fn as_str(self) -> &'static str {
+        match self {
+            Change::Regressed => "regressed",
+            Change::Fixed => "fixed",
+            Change::Changed => "changed",
+            Change::ErrorChanged => "error_changed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramChange {
+    /// Name of the diagram in the corpus, its path for [`compare_dir`]
+    pub name: String,
+    pub change: Change,
+    pub baseline: Snapshot,
+    pub candidate: Snapshot,
+}
+
+/// Outcome of a [`compare`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifferentialReport {
+    pub baseline: String,
+    pub candidate: String,
+    /// Number of diagrams compared
+    pub compared: usize,
+    /// Diagrams whose result changed, in corpus order
+    pub changes: Vec<DiagramChange>,
+}
+
+impl DifferentialReport {
+    /// Diagrams that parsed with the baseline and fail with the candidate
+    pub fn regressions(&self) -> impl Iterator<Item = &DiagramChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.change == Change::Regressed)
+    }
+
+    /// Whether every diagram that parsed with the baseline parses into the
+    /// same AST with the candidate
+    pub fn is_compatible(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| matches!(change.change, Change::Fixed | Change::ErrorChanged))
+    }
+
+    /// Render the report as a JSON object
+    pub fn to_json(&self) -> String {
+        let changes = self
+            .changes
+            .iter()
+            .map(|change| {
+                format!(
+                    "{{\"name\":{},\"change\":\"{}\",\"baseline\":{},\"candidate\":{}}}",
+                    json_string(&change.name),
+                    change.change.as_str(),
+                    snapshot_json(&change.baseline),
+                    snapshot_json(&change.candidate)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{{\"baseline\":{},\"candidate\":{},\"compared\":{},\"changes\":[{}]}}",
+            json_string(&self.baseline),
+            json_string(&self.candidate),
+            self.compared,
+            changes
+        )
+    }
+}
+
+/// Parse every `(name, source)` diagram of `corpus` with both versions and
+/// report those whose result differs
+pub fn compare<N, S>(
+    baseline: &dyn VersionedParser,
+    candidate: &dyn VersionedParser,
+    corpus: impl IntoIterator<Item = (N, S)>,
+) -> DifferentialReport
+where
+    N: Into<String>,
+    S: AsRef<str>,
+{
+    let mut compared = 0;
+    let mut changes = Vec::new();
+    for (name, source) in corpus {
+        compared += 1;
+        let before = baseline.parse(source.as_ref());
+        let after = candidate.parse(source.as_ref());
+        if before == after {
+            continue;
+        }
+        let change = match (before.is_parsed(), after.is_parsed()) {
+            (true, false) => Change::Regressed,
+            (false, true) => Change::Fixed,
+            (true, true) => Change::Changed,
+            (false, false) => Change::ErrorChanged,
+        };
+        changes.push(DiagramChange {
+            name: name.into(),
+            change,
+            baseline: before,
+            candidate: after,
+        });
+    }
+
+    DifferentialReport {
+        baseline: baseline.version(),
+        candidate: candidate.version(),
+        compared,
+        changes,
+    }
+}
+
+/// [`compare`] over every file with one of the
+/// [`crate::fs::DIAGRAM_EXTENSIONS`] under `dir`, recursively and sorted by
+/// path
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mermaid_parser::testing::differential::{self, CurrentParser, ParserFn, Snapshot};
+///
+/// let previous = ParserFn::new("0.4.0", |input: &str| {
+///     Snapshot::from_result(mermaid_parser::parse_diagram(input))
+/// });
+/// let report = differential::compare_dir(&previous, &CurrentParser, "docs/diagrams")?;
+/// for change in report.regressions() {
+///     println!("{} no longer parses", change.name);
+/// }
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+pub fn compare_dir(
+    baseline: &dyn VersionedParser,
+    candidate: &dyn VersionedParser,
+    dir: impl AsRef<Path>,
+) -> Result<DifferentialReport> {
+    let files = crate::fs::find_diagrams(dir)?;
+    let mut corpus = Vec::with_capacity(files.len());
+    for path in files {
+        let source = fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+        corpus.push((path.display().to_string(), source));
+    }
+    Ok(compare(baseline, candidate, corpus))
+}
+
+/// Pretty `Debug` output of `value` with the entries of every map and set
+/// sorted, so that equal values render equally whatever their hash order
+pub fn canonical_debug(value: &impl Debug) -> String {
+    let rendered = format!("{:#?}", value);
+    let lines: Vec<&str> = rendered.lines().collect();
+    canonical_lines(&lines).join("\n")
+}
+
+fn canonical_lines(lines: &[&str]) -> Vec<String> {
+    let mut output = Vec::with_capacity(lines.len());
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        index += 1;
+        if !opens_map(line) {
+            output.push(line.to_string());
+            continue;
+        }
+
+        // Entries are indented one level deeper than the braces
+        let indent = indentation(line);
+        let end = lines[index..]
+            .iter()
+            .position(|line| indentation(line) <= indent)
+            .map_or(lines.len(), |offset| index + offset);
+        let mut entries: Vec<String> = Vec::new();
+        let mut start = index;
+        for position in index + 1..=end {
+            if position == end || starts_entry(lines[position], indent + 4) {
+                entries.push(canonical_lines(&lines[start..position]).join("\n"));
+                start = position;
+            }
+        }
+        entries.sort();
+
+        output.push(line.to_string());
+        output.extend(entries);
+        index = end;
+    }
+    output
+}
+
+/// Maps and sets open with a bare `{`; structs with `Name {`
+fn opens_map(line: &str) -> bool {
+    match line.trim().strip_suffix('{') {
+        Some(before) => before.is_empty() || before.ends_with(": "),
+        None => false,
+    }
+}
+
+/// An entry line, not the closing bracket of the previous entry's value
+fn starts_entry(line: &str, indent: usize) -> bool {
+    indentation(line) == indent && !line.trim_start().starts_with(['}', ']', ')'])
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn snapshot_json(snapshot: &Snapshot) -> String {
+    match snapshot {
+        Snapshot::Parsed(ast) => format!("{{\"parsed\":{}}}", json_string(ast)),
+        Snapshot::Failed(error) => format!("{{\"failed\":{}}}", json_string(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Diagram {
+        nodes: HashMap<String, Vec<u32>>,
+        edges: Vec<(String, String)>,
+    }
+
+    #[test]
+    fn test_canonical_debug_sorts_map_entries() {
+        let nodes: HashMap<String, Vec<u32>> = (0..20)
+            .map(|i| (format!("N{:02}", i), vec![i, i + 1]))
+            .collect();
+        let mut reversed = HashMap::new();
+        for (key, value) in nodes.iter().collect::<Vec<_>>().into_iter().rev() {
+            reversed.insert(key.clone(), value.clone());
+        }
+        let edges = vec![("N01".to_string(), "N00".to_string())];
+
+        let first = canonical_debug(&Diagram {
+            nodes,
+            edges: edges.clone(),
+        });
+        let second = canonical_debug(&Diagram {
+            nodes: reversed,
+            edges,
+        });
+        assert_eq!(first, second);
+        assert!(first.find("\"N00\"").unwrap() < first.find("\"N19\"").unwrap());
+        // Sequences keep their order
+        assert!(first.contains("\"N01\",\n            \"N00\""));
+    }
+
+    #[test]
+    fn test_opens_map() {
+        assert!(opens_map("    nodes: {"));
+        assert!(opens_map("        {"));
+        assert!(!opens_map("    node: FlowNode {"));
+        assert!(!opens_map("    nodes: {},"));
+    }
+}
@@ -0,0 +1,161 @@
+//! Golden-file snapshot testing for pretty printer output
+//!
+//! A golden directory holds pairs of files: `name.mermaid` is the input diagram and
+//! `name.golden` is the expected output of [`MermaidPrinter::to_mermaid`] for it.
+//! Printer changes then show up as reviewable diffs of the `.golden` files rather than
+//! edits to assert strings scattered across tests.
+//!
+//! Set the [`UPDATE_ENV_VAR`] environment variable to `1` to write the current printer
+//! output to the golden files instead of comparing against them.
+
+use crate::common::pretty_print::MermaidPrinter;
+use crate::error::{ParseError, Result};
+use crate::fs::io_error;
+use crate::parse_diagram;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that switches [`check_or_update`] into update mode
+pub const UPDATE_ENV_VAR: &str = "MERMAID_UPDATE_GOLDEN";
+
+/// Extension of golden-file inputs
+pub const INPUT_EXTENSION: &str = "mermaid";
+
+/// Extension of golden-file expected outputs
+pub const GOLDEN_EXTENSION: &str = "golden";
+
+/// A golden file whose expected output differs from the printer output
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenMismatch {
+    /// Path of the `.golden` file
+    pub path: PathBuf,
+    /// Contents of the `.golden` file, or `None` if it does not exist yet
+    pub expected: Option<String>,
+    /// Current printer output
+    pub actual: String,
+}
+
+impl fmt::Display for GoldenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(expected) = &self.expected else {
+            return write!(f, "{}: golden file is missing", self.path.display());
+        };
+
+        let mut expected_lines = expected.lines();
+        let mut actual_lines = self.actual.lines();
+        let mut line = 1;
+        loop {
+            match (expected_lines.next(), actual_lines.next()) {
+                (Some(e), Some(a)) if e == a => line += 1,
+                (None, None) => {
+                    return write!(f, "{}: differs in trailing whitespace", self.path.display())
+                }
+                (e, a) => {
+                    return write!(
+                        f,
+                        "{}:{}\n  expected: {}\n  actual:   {}",
+                        self.path.display(),
+                        line,
+                        e.unwrap_or("<end of file>"),
+                        a.unwrap_or("<end of file>")
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Outcome of a [`check_or_update`] run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoldenReport {
+    /// Number of inputs whose output matched the golden file
+    pub passed: usize,
+    /// Golden files written in update mode
+    pub updated: Vec<PathBuf>,
+    /// Golden files that did not match (always empty in update mode)
+    pub mismatches: Vec<GoldenMismatch>,
+}
+
+impl GoldenReport {
+    /// Returns true if no golden file mismatched
+    pub fn is_clean(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Panics with a description of every mismatch, for use in tests
+    pub fn assert_clean(&self) {
+        if !self.is_clean() {
+            let details: Vec<String> = self.mismatches.iter().map(|m| m.to_string()).collect();
+            panic!(
+                "{} golden file(s) out of date (rerun with {}=1 to update):\n{}",
+                self.mismatches.len(),
+                UPDATE_ENV_VAR,
+                details.join("\n")
+            );
+        }
+    }
+}
+
+/// Check every `*.mermaid` file in `dir` against its `.golden` file
+///
+/// When [`UPDATE_ENV_VAR`] is set to `1`, the golden files are rewritten with the
+/// current printer output instead. Unreadable files and inputs that fail to parse
+/// are reported as errors rather than mismatches.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mermaid_parser::testing::golden;
+///
+/// let report = golden::check_or_update("tests/golden")?;
+/// report.assert_clean();
+/// # Ok::<(), mermaid_parser::ParseError>(())
+/// ```
+pub fn check_or_update(dir: impl AsRef<Path>) -> Result<GoldenReport> {
+    let update = std::env::var(UPDATE_ENV_VAR).is_ok_and(|value| value == "1");
+    run(dir.as_ref(), update)
+}
+
+/// Same as [`check_or_update`], with the mode chosen by the caller instead of the environment
+pub fn run(dir: &Path, update: bool) -> Result<GoldenReport> {
+    let mut report = GoldenReport::default();
+
+    for input_path in crate::fs::files_with_extension(dir, INPUT_EXTENSION)? {
+        let input = fs::read_to_string(&input_path).map_err(|e| io_error(&input_path, e))?;
+        let diagram = parse_diagram(&input).map_err(|e| ParseError::SemanticError {
+            message: e.to_string(),
+            context: input_path.display().to_string(),
+        })?;
+        let actual = diagram.to_mermaid();
+
+        let golden_path = input_path.with_extension(GOLDEN_EXTENSION);
+        let expected = fs::read_to_string(&golden_path).ok();
+
+        if expected.as_deref() == Some(actual.as_str()) {
+            report.passed += 1;
+        } else if update {
+            fs::write(&golden_path, &actual).map_err(|e| io_error(&golden_path, e))?;
+            report.updated.push(golden_path);
+        } else {
+            report.mismatches.push(GoldenMismatch {
+                path: golden_path,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_directory_is_io_error() {
+        let result = run(Path::new("/nonexistent/golden/dir"), false);
+        assert!(matches!(result, Err(ParseError::IoError(_))));
+    }
+}
@@ -0,0 +1,441 @@
+//! Structural comparison against mermaid-js
+//!
+//! Enabled by the `mermaid-js` feature, which is meant for development only.
+//! [`MermaidJs`] runs the reference implementation through `node`, reads back
+//! the node ids and edge count its diagram database holds, and
+//! [`compare_corpus`] lines those up with what this crate parses from the same
+//! fixtures. The resulting [`CompatibilityReport`] lists every divergence, so
+//! claims about parser correctness can be backed by data.
+//!
+//! Only diagram types whose mermaid-js database exposes stable getters are
+//! compared: flowchart, sequence (participants only), class, ER, pie, Gantt
+//! and journey. Fixtures of other types are reported as skipped.
+//!
+//! mermaid-js expects a browser environment for some of its sanitizing, so
+//! [`MermaidJs::module`] may name a wrapper module that installs a DOM (for
+//! example with jsdom) and re-exports mermaid as its default export.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use mermaid_parser::stable::DiagramKind;
+//! use mermaid_parser::testing::mermaid_js::{self, MermaidJs};
+//!
+//! let runner = MermaidJs::default();
+//! let report = mermaid_js::compare_corpus(&runner, DiagramKind::Flowchart, "test/flowchart")?;
+//! println!("{} of {} fixtures diverge", report.divergent().count(), report.fixtures.len());
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::DiagramType;
+use crate::common::escape::json_string;
+use crate::error::Result;
+use crate::fs::io_error;
+use crate::parse_diagram;
+use crate::stable::DiagramKind;
+use crate::testing::coverage;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Script run by `node`; reads the diagram from stdin and prints one
+/// tab-separated record per line
+const SCRIPT: &str = r#"
+const { default: mermaid } = await import(process.env.MERMAID_MODULE);
+const chunks = [];
+for await (const chunk of process.stdin) chunks.push(chunk);
+const keys = (v) => (v instanceof Map ? [...v.keys()] : Object.keys(v ?? {}));
+const line = (...fields) => console.log(fields.map((f) => String(f).replace(/[\t\n]/g, " ")).join("\t"));
+try {
+  const diagram = await mermaid.mermaidAPI.getDiagramFromText(Buffer.concat(chunks).toString());
+  const db = diagram.db;
+  let nodes = [];
+  let edges = "";
+  switch (diagram.type) {
+    case "flowchart": case "flowchart-v2": case "flowchart-elk":
+      nodes = keys(db.getVertices()); edges = db.getEdges().length; break;
+    case "sequence":
+      nodes = keys(db.getActors()); break;
+    case "class": case "classDiagram":
+      nodes = keys(db.getClasses()); edges = db.getRelations().length; break;
+    case "er":
+      nodes = keys(db.getEntities()); edges = db.getRelationships().length; break;
+    case "pie":
+      nodes = keys(db.getSections()); break;
+    case "gantt": case "journey":
+      nodes = db.getTasks().map((t) => t.task); break;
+    default:
+      line("skipped", diagram.type); process.exit(0);
+  }
+  line("type", diagram.type);
+  for (const node of nodes) line("node", node);
+  line("edges", edges);
+} catch (error) {
+  line("error", error?.message ?? error);
+}
+"#;
+
+/// Nodes and edges of a parsed diagram, reduced to what both parsers expose
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Structure {
+    pub nodes: BTreeSet<String>,
+    /// `None` for diagram types whose edges are not compared
+    pub edges: Option<usize>,
+}
+
+/// What mermaid-js made of a fixture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsOutcome {
+    Parsed {
+        /// The mermaid-js diagram type, such as `flowchart-v2`
+        diagram_type: String,
+        structure: Structure,
+    },
+    /// The diagram type is not one this harness compares
+    Skipped(String),
+    Failed(String),
+}
+
+/// How to run mermaid-js
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MermaidJs {
+    /// The `node` executable
+    pub node: PathBuf,
+    /// Module specifier passed to `import()`, resolved from the working
+    /// directory; its default export must be the mermaid object
+    pub module: String,
+}
+
+impl Default for MermaidJs {
+    fn default() -> Self {
+        MermaidJs {
+            node: PathBuf::from("node"),
+            module: "mermaid".to_string(),
+        }
+    }
+}
+
+impl MermaidJs {
+    /// Parse `source` with mermaid-js
+    ///
+    /// Failing to start `node` is an [`crate::ParseError::IoError`]; mermaid-js
+    /// rejecting the diagram is a [`JsOutcome::Failed`].
+    pub fn parse(&self, source: &str) -> Result<JsOutcome> {
+        let mut child = Command::new(&self.node)
+            .args(["--input-type=module", "-e", SCRIPT])
+            .env("MERMAID_MODULE", &self.module)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| io_error(&self.node, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(source.as_bytes())
+                .map_err(|e| io_error(&self.node, e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| io_error(&self.node, e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() || stdout.trim().is_empty() {
+            return Ok(JsOutcome::Failed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(parse_script_output(&stdout))
+    }
+}
+
+/// Divergence between mermaid-js and this crate on one fixture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// mermaid-js rejected a diagram this crate parsed
+    OnlyOursParsed(String),
+    /// This crate rejected a diagram mermaid-js parsed
+    OnlyJsParsed(String),
+    /// The diagrams are of different types
+    Kind {
+        ours: DiagramKind,
+        js: String,
+    },
+    /// Node ids mermaid-js found that this crate did not
+    MissingNodes(Vec<String>),
+    /// Node ids this crate found that mermaid-js did not
+    ExtraNodes(Vec<String>),
+    EdgeCount {
+        ours: usize,
+        js: usize,
+    },
+}
+
+/// Comparison of a single fixture
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureComparison {
+    pub path: PathBuf,
+    /// Empty when both parsers agree; `None` when the fixture was skipped
+    pub divergences: Option<Vec<Divergence>>,
+}
+
+/// Outcome of a [`compare_corpus`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompatibilityReport {
+    pub kind: DiagramKind,
+    /// Every non-empty fixture, sorted by path
+    pub fixtures: Vec<FixtureComparison>,
+}
+
+impl CompatibilityReport {
+    /// Fixtures where both parsers agree
+    pub fn matching(&self) -> impl Iterator<Item = &FixtureComparison> {
+        self.fixtures
+            .iter()
+            .filter(|f| f.divergences.as_ref().is_some_and(|d| d.is_empty()))
+    }
+
+    /// Fixtures with at least one divergence
+    pub fn divergent(&self) -> impl Iterator<Item = &FixtureComparison> {
+        self.fixtures
+            .iter()
+            .filter(|f| f.divergences.as_ref().is_some_and(|d| !d.is_empty()))
+    }
+
+    /// Render the report as a JSON object listing the divergent fixtures
+    pub fn to_json(&self) -> String {
+        let skipped = self
+            .fixtures
+            .iter()
+            .filter(|f| f.divergences.is_none())
+            .count();
+        let divergent = self
+            .divergent()
+            .map(|fixture| {
+                let divergences = fixture
+                    .divergences
+                    .iter()
+                    .flatten()
+                    .map(|divergence| json_string(&format!("{:?}", divergence)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!(
+                    "{{\"path\":{},\"divergences\":[{}]}}",
+                    json_string(&fixture.path.display().to_string()),
+                    divergences
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            "{{\"kind\":\"{:?}\",\"fixtures\":{},\"matching\":{},\"skipped\":{},\"divergent\":[{}]}}",
+            self.kind,
+            self.fixtures.len(),
+            self.matching().count(),
+            skipped,
+            divergent
+        )
+    }
+}
+
+/// The structure this crate extracts for the compared diagram types
+pub fn structure(diagram: &DiagramType) -> Option<Structure> {
+    let (nodes, edges): (BTreeSet<String>, Option<usize>) = match diagram {
+        DiagramType::Flowchart(d) => (d.nodes.keys().cloned().collect(), Some(d.edges.len())),
+        DiagramType::Sequence(d) => (
+            d.participants.iter().map(|p| p.actor.clone()).collect(),
+            None,
+        ),
+        DiagramType::Class(d) => (
+            d.classes.keys().cloned().collect(),
+            Some(d.relationships.len()),
+        ),
+        DiagramType::Er(d) => (
+            d.entities.keys().cloned().collect(),
+            Some(d.relationships.len()),
+        ),
+        DiagramType::Pie(d) => (d.data.iter().map(|s| s.label.clone()).collect(), None),
+        DiagramType::Gantt(d) => (
+            d.sections
+                .iter()
+                .flat_map(|s| s.tasks.iter().map(|t| t.name.clone()))
+                .collect(),
+            None,
+        ),
+        DiagramType::Journey(d) => (
+            d.sections
+                .iter()
+                .flat_map(|s| s.tasks.iter().map(|t| t.name.clone()))
+                .collect(),
+            None,
+        ),
+        _ => return None,
+    };
+    Some(Structure { nodes, edges })
+}
+
+/// Compare this crate's parse of `source` with a mermaid-js outcome; `None`
+/// when the diagram type is not compared
+pub fn compare(source: &str, js: &JsOutcome) -> Option<Vec<Divergence>> {
+    let ours = parse_diagram(source);
+    let (diagram_type, theirs) = match js {
+        JsOutcome::Skipped(_) => return None,
+        JsOutcome::Failed(message) => {
+            return match ours {
+                Ok(diagram) => {
+                    structure(&diagram).map(|_| vec![Divergence::OnlyOursParsed(message.clone())])
+                }
+                Err(_) => Some(Vec::new()),
+            }
+        }
+        JsOutcome::Parsed {
+            diagram_type,
+            structure,
+        } => (diagram_type, structure),
+    };
+
+    let diagram = match ours {
+        Ok(diagram) => diagram,
+        Err(error) => return Some(vec![Divergence::OnlyJsParsed(error.to_string())]),
+    };
+    let kind = DiagramKind::from(&diagram);
+    let Some(ours) = structure(&diagram).filter(|_| js_kind(diagram_type) == Some(kind)) else {
+        return Some(vec![Divergence::Kind {
+            ours: kind,
+            js: diagram_type.clone(),
+        }]);
+    };
+
+    let mut divergences = Vec::new();
+    let missing: Vec<_> = theirs.nodes.difference(&ours.nodes).cloned().collect();
+    if !missing.is_empty() {
+        divergences.push(Divergence::MissingNodes(missing));
+    }
+    let extra: Vec<_> = ours.nodes.difference(&theirs.nodes).cloned().collect();
+    if !extra.is_empty() {
+        divergences.push(Divergence::ExtraNodes(extra));
+    }
+    if let (Some(ours), Some(js)) = (ours.edges, theirs.edges) {
+        if ours != js {
+            divergences.push(Divergence::EdgeCount { ours, js });
+        }
+    }
+    Some(divergences)
+}
+
+/// Run mermaid-js and this crate on every fixture in `dir`
+pub fn compare_corpus(
+    runner: &MermaidJs,
+    kind: DiagramKind,
+    dir: impl AsRef<Path>,
+) -> Result<CompatibilityReport> {
+    let dir = dir.as_ref();
+    let mut fixtures = Vec::new();
+    for path in crate::fs::files_with_extension(dir, coverage::FIXTURE_EXTENSION)? {
+        let content = crate::fs::read_diagram(&path)?;
+        if content.is_empty() {
+            continue;
+        }
+
+        let js = runner.parse(&content)?;
+        fixtures.push(FixtureComparison {
+            divergences: compare(&content, &js),
+            path,
+        });
+    }
+
+    Ok(CompatibilityReport { kind, fixtures })
+}
+
+/// The diagram kind of a mermaid-js diagram type
+fn js_kind(diagram_type: &str) -> Option<DiagramKind> {
+    match diagram_type {
+        "flowchart" | "flowchart-v2" | "flowchart-elk" => Some(DiagramKind::Flowchart),
+        "sequence" => Some(DiagramKind::Sequence),
+        "class" | "classDiagram" => Some(DiagramKind::Class),
+        "er" => Some(DiagramKind::Er),
+        "pie" => Some(DiagramKind::Pie),
+        "gantt" => Some(DiagramKind::Gantt),
+        "journey" => Some(DiagramKind::Journey),
+        _ => None,
+    }
+}
+
+fn parse_script_output(output: &str) -> JsOutcome {
+    let mut diagram_type = String::new();
+    let mut nodes = BTreeSet::new();
+    let mut edges = None;
+
+    for line in output.lines() {
+        let (record, value) = line.split_once('\t').unwrap_or((line, ""));
+        match record {
+            "error" => return JsOutcome::Failed(value.to_string()),
+            "skipped" => return JsOutcome::Skipped(value.to_string()),
+            "type" => diagram_type = value.to_string(),
+            "node" => {
+                nodes.insert(value.to_string());
+            }
+            "edges" => edges = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    JsOutcome::Parsed {
+        diagram_type,
+        structure: Structure { nodes, edges },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_output_is_parsed() {
+        let outcome = parse_script_output("type\tflowchart-v2\nnode\tA\nnode\tB\nedges\t1\n");
+        assert_eq!(
+            outcome,
+            JsOutcome::Parsed {
+                diagram_type: "flowchart-v2".to_string(),
+                structure: Structure {
+                    nodes: ["A", "B"].iter().map(|s| s.to_string()).collect(),
+                    edges: Some(1),
+                },
+            }
+        );
+        assert_eq!(
+            parse_script_output("error\tParse error on line 2\n"),
+            JsOutcome::Failed("Parse error on line 2".to_string())
+        );
+        assert_eq!(
+            parse_script_output("skipped\tmindmap\n"),
+            JsOutcome::Skipped("mindmap".to_string())
+        );
+    }
+
+    #[test]
+    fn test_compare_reports_divergences() {
+        let source = "flowchart TD\n    A --> B\n    B --> C";
+        let js = parse_script_output("type\tflowchart-v2\nnode\tA\nnode\tB\nnode\tD\nedges\t3\n");
+        assert_eq!(
+            compare(source, &js),
+            Some(vec![
+                Divergence::MissingNodes(vec!["D".to_string()]),
+                Divergence::ExtraNodes(vec!["C".to_string()]),
+                Divergence::EdgeCount { ours: 2, js: 3 },
+            ])
+        );
+
+        let js = parse_script_output("type\tflowchart-v2\nnode\tA\nnode\tB\nnode\tC\nedges\t2\n");
+        assert_eq!(compare(source, &js), Some(vec![]));
+
+        let js = JsOutcome::Failed("Lexical error".to_string());
+        assert_eq!(
+            compare(source, &js),
+            Some(vec![Divergence::OnlyOursParsed(
+                "Lexical error".to_string()
+            )])
+        );
+    }
+}
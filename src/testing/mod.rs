@@ -0,0 +1,28 @@
+//! Testing helpers for the mermaid-parser crate and downstream projects
+//!
+//! ## Module Overview
+//!
+//! - [`coverage`] - Parser coverage of the Mermaid fixture corpus
+//! - [`differential`] - Differential testing of two parser versions over a corpus
+//! - [`golden`] - Golden-file snapshot testing for pretty printer output
+//! - `mermaid_js` - Structural comparison against mermaid-js (feature `mermaid-js`)
+//!
+//! The helpers find and read fixtures with [`crate::fs`], so this module is
+//! only available with the `fs` feature, which is enabled by default.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mermaid_parser::testing::golden;
+//!
+//! // Compares every `*.mermaid` file in the directory against its `.golden` output.
+//! // Run with `MERMAID_UPDATE_GOLDEN=1` to (re)write the golden files instead.
+//! golden::check_or_update("tests/golden")?.assert_clean();
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+pub mod coverage;
+pub mod differential;
+pub mod golden;
+#[cfg(feature = "mermaid-js")]
+pub mod mermaid_js;
@@ -0,0 +1,133 @@
+//! Version stamps for persisted parse results
+//!
+//! Tools that cache ASTs need to know when a cached result is stale. The crate
+//! version changes on every release, but most releases leave most parsers
+//! untouched, so [`ParserInfo`] also records a grammar revision per
+//! [`DiagramKind`]. A revision is bumped whenever the AST produced for some
+//! input of that kind changes, which lets a cache invalidate exactly the
+//! entries whose parser changed.
+//!
+//! Stamping is opt-in: [`parse_diagram_versioned`] parses like
+//! [`crate::parse_diagram`] and attaches the current [`ParserInfo`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use mermaid_parser::version::{self, ParserInfo};
+//! use mermaid_parser::stable::DiagramKind;
+//!
+//! let parsed = version::parse_diagram_versioned("flowchart TD\n    A --> B")?;
+//! assert_eq!(parsed.info.crate_version, env!("CARGO_PKG_VERSION"));
+//!
+//! // Later, before reusing a cached AST stamped with `parsed.info`
+//! if parsed.info.is_current_for(DiagramKind::Flowchart) {
+//!     println!("cached flowchart is still valid");
+//! }
+//! # Ok::<(), mermaid_parser::ParseError>(())
+//! ```
+
+use crate::common::ast::DiagramType;
+use crate::error::Result;
+use crate::stable::DiagramKind;
+use std::collections::HashMap;
+
+/// Grammar revision of each diagram kind; bump an entry whenever its parser
+/// produces a different AST for some input
+const GRAMMAR_REVISIONS: &[(DiagramKind, u32)] = &[
+    (DiagramKind::Sankey, 1),
+    (DiagramKind::Timeline, 1),
+    (DiagramKind::Journey, 1),
+    (DiagramKind::Sequence, 1),
+    (DiagramKind::Class, 1),
+    (DiagramKind::State, 3),
+    (DiagramKind::Flowchart, 2),
+    (DiagramKind::Gantt, 1),
+    (DiagramKind::Pie, 1),
+    (DiagramKind::Git, 1),
+    (DiagramKind::Er, 2),
+    (DiagramKind::C4, 2),
+    (DiagramKind::Mindmap, 2),
+    (DiagramKind::Quadrant, 2),
+    (DiagramKind::XyChart, 3),
+    (DiagramKind::Kanban, 1),
+    (DiagramKind::Block, 1),
+    (DiagramKind::Architecture, 2),
+    (DiagramKind::Packet, 1),
+    (DiagramKind::Requirement, 2),
+    (DiagramKind::Treemap, 1),
+    (DiagramKind::Radar, 1),
+    (DiagramKind::Misc, 1),
+];
+
+/// The parser that produced a result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserInfo {
+    pub crate_version: String,
+    pub grammar_revisions: HashMap<DiagramKind, u32>,
+}
+
+impl ParserInfo {
+    /// Information about this build of the parser
+    pub fn current() -> Self {
+        ParserInfo {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            grammar_revisions: GRAMMAR_REVISIONS.iter().copied().collect(),
+        }
+    }
+
+    /// The recorded grammar revision of `kind`
+    pub fn revision(&self, kind: DiagramKind) -> Option<u32> {
+        self.grammar_revisions.get(&kind).copied()
+    }
+
+    /// Whether a `kind` AST produced by the recorded parser matches what this
+    /// build would produce, regardless of the crate version
+    pub fn is_current_for(&self, kind: DiagramKind) -> bool {
+        self.revision(kind).is_some() && self.revision(kind) == grammar_revision(kind)
+    }
+
+    /// Kinds whose grammar revision differs from this build
+    pub fn changed_kinds(&self) -> Vec<DiagramKind> {
+        GRAMMAR_REVISIONS
+            .iter()
+            .map(|(kind, _)| *kind)
+            .filter(|kind| !self.is_current_for(*kind))
+            .collect()
+    }
+}
+
+impl Default for ParserInfo {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+/// The current grammar revision of `kind`
+pub fn grammar_revision(kind: DiagramKind) -> Option<u32> {
+    GRAMMAR_REVISIONS
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, revision)| *revision)
+}
+
+/// A diagram stamped with the parser that produced it
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionedDiagram {
+    pub info: ParserInfo,
+    pub diagram: DiagramType,
+}
+
+impl VersionedDiagram {
+    /// Whether the diagram would parse to the same AST with this build
+    pub fn is_current(&self) -> bool {
+        self.info.is_current_for(DiagramKind::from(&self.diagram))
+    }
+}
+
+/// Parse `input` and stamp the result with [`ParserInfo::current`]
+pub fn parse_diagram_versioned(input: &str) -> Result<VersionedDiagram> {
+    Ok(VersionedDiagram {
+        info: ParserInfo::current(),
+        diagram: crate::parse_diagram(input)?,
+    })
+}
@@ -0,0 +1,64 @@
+use mermaid_parser::common::annotations::Annotations;
+use mermaid_parser::common::frontmatter::ConfigValue;
+use mermaid_parser::common::path::{element_paths, AstPath, ElementKind};
+use mermaid_parser::{parse_diagram, parse_document, MermaidPrinter};
+
+#[test]
+fn test_annotations_round_trip_through_frontmatter() {
+    let mut document =
+        parse_document("---\ntitle: Checkout\n---\nflowchart LR\n    A --> B").unwrap();
+    let node = AstPath::new(ElementKind::Node, "A");
+    let edge = element_paths(&document.diagram)
+        .into_iter()
+        .find(|path| path.kind() == ElementKind::Edge)
+        .unwrap();
+
+    let mut annotations = Annotations::new();
+    annotations.set(node.clone(), "owner", "team: payments");
+    annotations.set(node.clone(), "reviewed", true);
+    annotations.set(edge.clone(), "latency_ms", 120.0);
+    document.set_annotations(&annotations);
+
+    let output = document.to_mermaid();
+    assert!(output.contains("annotations:\n  "));
+    assert!(output.contains("title: Checkout"));
+
+    let reparsed = parse_document(&output).unwrap();
+    assert_eq!(reparsed.annotations().unwrap(), annotations);
+    assert_eq!(
+        reparsed.annotations().unwrap().get(&edge, "latency_ms"),
+        Some(&ConfigValue::Number(120.0))
+    );
+}
+
+#[test]
+fn test_document_without_annotations() {
+    let mut document = parse_document("pie\n    \"A\" : 1").unwrap();
+    assert!(document.annotations().unwrap().is_empty());
+
+    document.set_annotations(&Annotations::new());
+    assert!(document.frontmatter.is_none());
+
+    let invalid =
+        parse_document("---\nannotations:\n  widget/A:\n    owner: x\n---\npie\n    \"A\" : 1")
+            .unwrap();
+    assert!(invalid.annotations().is_err());
+}
+
+#[test]
+fn test_retain_existing_drops_removed_elements() {
+    let before =
+        parse_diagram("sequenceDiagram\n    Alice->>Bob: Hi\n    Bob->>Alice: Bye").unwrap();
+    let mut annotations = Annotations::new();
+    for path in element_paths(&before) {
+        annotations.set(path, "status", "approved");
+    }
+    assert_eq!(annotations.len(), 4);
+
+    let after = parse_diagram("sequenceDiagram\n    Alice->>Bob: Hi").unwrap();
+    let dropped = annotations.retain_existing(&after);
+    assert_eq!(dropped.len(), 1);
+    assert_eq!(dropped[0].kind(), ElementKind::Message);
+    assert_eq!(annotations.len(), 3);
+    assert_eq!(annotations.paths().len(), 3);
+}
@@ -1,7 +1,7 @@
 //! Additional tests to improve coverage for architecture.rs parser
 
 use mermaid_parser::common::ast::{ArchDirection, ArchEdgeType, EdgeEndpoint};
-use mermaid_parser::error::ParseError;
+use mermaid_parser::error::{FailurePoint, ParseError};
 use mermaid_parser::parsers::architecture;
 
 #[test]
@@ -28,6 +28,43 @@ fn test_invalid_header_error() {
     }
 }
 
+#[test]
+fn test_lex_error_keeps_partial_tokens() {
+    let input = "architecture-beta\n    service db(database)[Database]\n    db ? api";
+    let error = architecture::parse(input).unwrap_err();
+
+    let partial = error.partial().expect("lexer failure should record tokens");
+    assert!(matches!(
+        partial.failure,
+        FailurePoint::Lexer {
+            line: 3,
+            column: 8,
+            ..
+        }
+    ));
+    assert_eq!(partial.tokens.first().unwrap(), "ArchitectureBeta");
+    assert_eq!(partial.tokens.last().unwrap(), "Identifier(\"db\")");
+    assert!(error
+        .to_string()
+        .contains("Failed to tokenize architecture diagram"));
+}
+
+#[test]
+fn test_parse_error_keeps_token_stream() {
+    let input = "architecture-beta\n    service db(database)[Database]\n    db:L --";
+    let error = architecture::parse(input).unwrap_err();
+
+    let partial = error
+        .partial()
+        .expect("parser failure should record tokens");
+    assert_eq!(partial.tokens.len(), 9);
+    match partial.failure {
+        FailurePoint::Parser { token } => assert!(token <= partial.tokens.len()),
+        other => panic!("Expected a parser failure, got {:?}", other),
+    }
+    assert!(matches!(error, ParseError::SyntaxError { .. }));
+}
+
 #[test]
 fn test_architecture_without_beta() {
     let input = r#"architecture
@@ -319,8 +356,7 @@ fn test_all_directions() {
         let result = architecture::parse(&input);
 
         // Some direction strings might cause parsing issues
-        if result.is_ok() {
-            let diagram = result.unwrap();
+        if let Ok(diagram) = result {
             // Just verify it parses without error
             assert!(diagram.services.len() <= 1);
         }
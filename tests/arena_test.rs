@@ -0,0 +1,36 @@
+#![cfg(feature = "arena")]
+
+use mermaid_parser::arena::ArenaFlowchart;
+use mermaid_parser::parsers::flowchart;
+
+#[test]
+fn test_arena_round_trip_and_lookup() {
+    let input = r#"flowchart LR
+    A[Cart]:::primary --> B{Paid?}
+    B -->|yes| C[[Ship]]
+    B -->|no| D
+    class C primary,slow"#;
+
+    let mut diagram = flowchart::parse(input).unwrap();
+    diagram.title = Some("Orders".to_string());
+    let arena = ArenaFlowchart::from(&diagram);
+
+    assert_eq!(arena.nodes().len(), 4);
+    assert_eq!(arena.edges().len(), 3);
+    assert_eq!(arena.title.map(|title| arena.str(title)), Some("Orders"));
+
+    let c = arena.find("C").unwrap();
+    assert_eq!(
+        arena.classes(c).collect::<Vec<_>>(),
+        vec!["primary", "slow"]
+    );
+    assert_eq!(arena.str(arena.node(c).text.unwrap()), "Ship");
+
+    let labels: Vec<_> = arena
+        .outgoing(arena.find("B").unwrap())
+        .filter_map(|edge| arena.edge(edge).label.map(|label| arena.str(label)))
+        .collect();
+    assert_eq!(labels, vec!["yes", "no"]);
+
+    assert_eq!(arena.to_flowchart(), diagram);
+}
@@ -0,0 +1,64 @@
+use mermaid_parser::common::arrow_table::{
+    self, ArrowDirection, ArrowKind, HeadStyle, LineStyle, ARROWS,
+};
+use mermaid_parser::common::ast::{ArrowType, SequenceStatement};
+use mermaid_parser::stable::DiagramKind;
+use mermaid_parser::{parse_diagram, DiagramType, MermaidPrinter};
+
+#[test]
+fn test_canonical_spellings_are_listed_for_their_kind() {
+    for arrow in ARROWS {
+        let canonical = arrow_table::lookup(arrow.kind.diagram(), arrow.canonical)
+            .unwrap_or_else(|| panic!("{} has no canonical entry", arrow.spelling));
+        assert_eq!(canonical.kind, arrow.kind, "{}", arrow.spelling);
+        assert_eq!(canonical.canonical, canonical.spelling);
+        assert_eq!(arrow.kind.canonical(), arrow.canonical);
+    }
+}
+
+#[test]
+fn test_arrows_are_matched_longest_first() {
+    let spellings: Vec<_> = arrow_table::arrows_for(DiagramKind::Sequence)
+        .iter()
+        .map(|arrow| arrow.spelling)
+        .collect();
+    assert_eq!(spellings[0], "<<-->>");
+    assert!(spellings
+        .windows(2)
+        .all(|pair| pair[0].len() >= pair[1].len()));
+
+    let head = arrow_table::lookup(DiagramKind::Class, "<|--").unwrap();
+    assert_eq!(head.direction, ArrowDirection::Backward);
+    assert_eq!(head.head, HeadStyle::Triangle);
+    let link = arrow_table::lookup(DiagramKind::Flowchart, "~~~").unwrap();
+    assert_eq!(link.line, LineStyle::Invisible);
+    assert_eq!(
+        arrow_table::lookup(DiagramKind::Architecture, "<-->")
+            .unwrap()
+            .canonical,
+        "<->"
+    );
+    assert!(arrow_table::lookup(DiagramKind::State, "==>").is_none());
+}
+
+#[test]
+fn test_sequence_arrows_round_trip() {
+    for arrow in arrow_table::arrows_for(DiagramKind::Sequence) {
+        let input = format!("sequenceDiagram\n    Alice{}Bob: Hi", arrow.spelling);
+        let diagram = parse_diagram(&input).unwrap();
+        let reparsed = parse_diagram(&diagram.to_mermaid()).unwrap();
+        assert_eq!(reparsed, diagram, "{}", arrow.spelling);
+
+        let DiagramType::Sequence(sequence) = diagram else {
+            panic!("expected a sequence diagram");
+        };
+        let SequenceStatement::Message(message) = &sequence.statements[0] else {
+            panic!("expected a message for {}", arrow.spelling);
+        };
+        assert_eq!(ArrowKind::Sequence(message.arrow_type.clone()), arrow.kind);
+    }
+    assert_eq!(
+        ArrowKind::Sequence(ArrowType::BiDirectionalSolid).canonical(),
+        "<<->>"
+    );
+}
@@ -0,0 +1,44 @@
+use mermaid_parser::common::ast_graph::ast_to_flowchart;
+use mermaid_parser::{parse_diagram, MermaidPrinter};
+
+#[test]
+fn test_ast_to_flowchart_shows_containment() {
+    let diagram = parse_diagram("flowchart LR\n    A[Start] --> B\n    B --> C").unwrap();
+    let graph = ast_to_flowchart(&diagram);
+
+    let text = |id: &str| graph.nodes[id].text.clone().unwrap();
+    assert_eq!(text("n0"), "Flowchart");
+    assert_eq!(text("n1"), "FlowchartDiagram");
+
+    // Every node but the root has exactly one parent
+    assert_eq!(graph.edges.len(), graph.nodes.len() - 1);
+
+    let find = |label: &str| {
+        graph
+            .nodes
+            .values()
+            .find(|node| node.text.as_deref() == Some(label))
+            .map(|node| node.id.clone())
+            .unwrap_or_else(|| panic!("No node {:?}", label))
+    };
+    let start = find("text: Some(\"Start\")");
+    let parent = &graph
+        .edges
+        .iter()
+        .find(|edge| edge.to == start)
+        .unwrap()
+        .from;
+    assert_eq!(text(parent), "\"A\": FlowNode");
+}
+
+#[test]
+fn test_ast_flowchart_is_deterministic_and_printable() {
+    let input = "classDiagram\n    class Animal\n    class Dog\n    class Cat\n    Animal <|-- Dog";
+    let diagram = parse_diagram(input).unwrap();
+    let graph = ast_to_flowchart(&diagram);
+    assert_eq!(graph, ast_to_flowchart(&diagram));
+
+    let printed = graph.to_mermaid();
+    assert!(printed.contains("n0[Class] --> n1[ClassDiagram]"));
+    assert!(printed.contains("n1 --> "));
+}
@@ -138,3 +138,90 @@ fn test_block_composite() {
         _ => panic!("Expected Block diagram"),
     }
 }
+
+#[test]
+fn test_block_dotted_path_endpoints() {
+    use mermaid_parser::common::validation::{BlockValidator, DiagramValidator};
+
+    let input = r#"block-beta
+  block:outer
+    block:inner
+      leaf
+    end
+    sibling
+  end
+  other
+  outer.inner.leaf --> other
+  inner.leaf --> outer.sibling
+  outer.missing --> other
+"#;
+
+    match parse_diagram(input).unwrap() {
+        mermaid_parser::DiagramType::Block(diagram) => {
+            assert_eq!(diagram.connections.len(), 3);
+            assert_eq!(diagram.connections[0].from, "outer.inner.leaf");
+
+            let leaf = diagram.resolve_block("outer.inner.leaf").unwrap();
+            assert_eq!(leaf.id(), Some("leaf"));
+            assert_eq!(diagram.resolve_block("inner.leaf"), Some(leaf));
+            assert_eq!(diagram.resolve_block("leaf"), Some(leaf));
+            assert!(diagram.resolve_block("outer.leaf").is_none());
+            assert!(diagram.resolve_block("outer.missing").is_none());
+
+            let errors = BlockValidator::new().validate(&diagram).unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].rule, "undefined_block_reference");
+            assert!(errors[0].message.contains("outer.missing"));
+        }
+        _ => panic!("Expected Block diagram"),
+    }
+}
+
+#[test]
+fn test_grid_hints_across_diagram_types() {
+    use mermaid_parser::common::ast::{GridHints, LayoutDirection, PlacementHint};
+
+    let hints = |input: &str| parse_diagram(input).unwrap().grid_hints();
+    let placement = |from: &str, to: &str, direction: Option<LayoutDirection>| PlacementHint {
+        from: from.to_string(),
+        to: to.to_string(),
+        direction,
+    };
+
+    assert_eq!(
+        hints("block-beta\n    columns 3\n    a b c\n    a --> b\n    a ~~~ c"),
+        Some(GridHints {
+            columns: Some(3),
+            placements: vec![placement("a", "c", None)],
+        })
+    );
+
+    assert_eq!(
+        hints(
+            "architecture-beta
+    service db(database)[DB]
+    service api(server)[API]
+    junction j
+    db:R -- L:j
+    j:B -- T:api
+    db -- api"
+        ),
+        Some(GridHints {
+            columns: None,
+            placements: vec![
+                placement("db", "j", Some(LayoutDirection::LeftToRight)),
+                placement("j", "api", Some(LayoutDirection::TopToBottom)),
+            ],
+        })
+    );
+
+    assert_eq!(
+        hints("flowchart LR\n    A --> B\n    A ~~~ C"),
+        Some(GridHints {
+            columns: None,
+            placements: vec![placement("A", "C", Some(LayoutDirection::LeftToRight))],
+        })
+    );
+
+    assert_eq!(hints("pie\n    \"A\" : 1"), None);
+}
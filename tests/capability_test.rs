@@ -0,0 +1,34 @@
+use mermaid_parser::capability::{self, SupportLevel};
+use mermaid_parser::stable::DiagramKind;
+use mermaid_parser::{capabilities, parse_diagram};
+use std::collections::HashSet;
+
+#[test]
+fn test_every_kind_listed_once() {
+    let matrix = capabilities();
+    let kinds: HashSet<_> = matrix.iter().map(|c| c.kind).collect();
+    assert_eq!(kinds.len(), matrix.len());
+    assert_eq!(matrix.len(), 23);
+}
+
+#[test]
+fn test_full_support_has_no_unsupported_constructs() {
+    for entry in capabilities() {
+        assert_eq!(
+            entry.level == SupportLevel::Full,
+            entry.unsupported.is_empty(),
+            "{:?}",
+            entry.kind
+        );
+    }
+}
+
+#[test]
+fn test_fallback_matches_parser_routing() {
+    let git = capability::for_kind(DiagramKind::Git).unwrap();
+    assert_eq!(git.level, SupportLevel::Fallback);
+    assert_eq!(git.level.as_str(), "fallback");
+
+    let diagram = parse_diagram("gitGraph\n    commit").unwrap();
+    assert_eq!(DiagramKind::from(&diagram), DiagramKind::Misc);
+}
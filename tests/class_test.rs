@@ -134,3 +134,388 @@ fn test_basic_class_features() {
         _ => panic!("Expected Class diagram"),
     }
 }
+
+#[test]
+fn test_v2_header_and_direction() {
+    use mermaid_parser::common::ast::{FlowDirection, LayoutDirection};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = "classDiagram-v2\n    direction LR\n    class Animal\n    class Dog";
+
+    let diagram = parse_diagram(input).unwrap();
+    assert_eq!(
+        diagram.layout_direction(),
+        Some(LayoutDirection::LeftToRight)
+    );
+    let mermaid_parser::DiagramType::Class(class) = diagram else {
+        panic!("Expected Class diagram");
+    };
+    assert_eq!(class.direction, Some(FlowDirection::LR));
+    assert!(class.classes.contains_key("Animal"));
+    assert!(class.classes.contains_key("Dog"));
+
+    let printed = class.to_mermaid();
+    assert!(printed.contains("direction LR"), "{}", printed);
+    let reparsed = match parse_diagram(&printed).unwrap() {
+        mermaid_parser::DiagramType::Class(reparsed) => reparsed,
+        _ => panic!("Expected Class diagram"),
+    };
+    assert_eq!(reparsed.direction, Some(FlowDirection::LR));
+
+    let default = parse_diagram("classDiagram\n    class Animal").unwrap();
+    assert_eq!(
+        default.layout_direction(),
+        Some(LayoutDirection::TopToBottom)
+    );
+}
+
+#[test]
+fn test_generics_and_namespaces() {
+    use mermaid_parser::common::ast::ClassMember;
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"classDiagram
+    class List~T~ {
+        +Map~K,V~ items
+        +getItems() List~T~
+    }
+    namespace Shapes {
+        class Square
+        class Circle~Point~
+    }"#;
+
+    let class = match parse_diagram(input).unwrap() {
+        mermaid_parser::DiagramType::Class(class) => class,
+        _ => panic!("Expected Class diagram"),
+    };
+
+    let list = &class.classes["List"];
+    assert_eq!(list.generics, vec!["T"]);
+    let ClassMember::Property(items) = &list.members[0] else {
+        panic!("Expected property, got {:?}", list.members[0]);
+    };
+    assert_eq!(items.prop_type.as_deref(), Some("Map"));
+    assert_eq!(items.generics, vec!["K", "V"]);
+    let ClassMember::Method(get_items) = &list.members[1] else {
+        panic!("Expected method, got {:?}", list.members[1]);
+    };
+    assert_eq!(get_items.return_type.as_deref(), Some("List"));
+    assert_eq!(get_items.generics, vec!["T"]);
+
+    assert_eq!(class.namespaces["Shapes"], vec!["Square", "Circle"]);
+    assert_eq!(class.classes["Circle"].generics, vec!["Point"]);
+
+    let printed = class.to_mermaid();
+    assert!(printed.contains("class List~T~ {"), "{}", printed);
+    assert!(printed.contains("namespace Shapes {"), "{}", printed);
+    let reparsed = match parse_diagram(&printed).unwrap() {
+        mermaid_parser::DiagramType::Class(reparsed) => reparsed,
+        _ => panic!("Expected Class diagram"),
+    };
+    assert_eq!(reparsed.namespaces, class.namespaces);
+    assert_eq!(reparsed.classes["List"], class.classes["List"]);
+}
+
+#[test]
+fn test_styles_links_and_css_classes() {
+    use mermaid_parser::common::ast::{ClickAction, FlowDirection, StyleTarget};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r##"classDiagram
+    direction RL
+    class Shape
+    class Square
+    link Shape "https://example.com/shape" "Shape docs" _blank
+    click Square href "https://example.com/square"
+    click Square call showDetails("Square", 1) "Details"
+    style Shape fill:#f9f,stroke:#333,stroke-width:4px
+    cssClass "Shape,Square" highlighted"##;
+
+    let class = match parse_diagram(input).unwrap() {
+        mermaid_parser::DiagramType::Class(class) => class,
+        _ => panic!("Expected Class diagram"),
+    };
+
+    assert_eq!(class.direction, Some(FlowDirection::RL));
+    assert_eq!(class.styles.len(), 1);
+    assert_eq!(
+        class.styles[0].target,
+        StyleTarget::Node("Shape".to_string())
+    );
+    assert_eq!(class.styles[0].styles["fill"], "#f9f");
+    assert_eq!(class.styles[0].styles["stroke-width"], "4px");
+    assert_eq!(
+        class.classes["Square"].css_class.as_deref(),
+        Some("highlighted")
+    );
+
+    let actions: Vec<(&str, &ClickAction)> = class
+        .clicks
+        .iter()
+        .map(|click| (click.node_id.as_str(), &click.action))
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            (
+                "Shape",
+                &ClickAction::Href(
+                    "https://example.com/shape".to_string(),
+                    Some("_blank".to_string())
+                )
+            ),
+            (
+                "Square",
+                &ClickAction::Href("https://example.com/square".to_string(), None)
+            ),
+            (
+                "Square",
+                &ClickAction::Callback("showDetails(\"Square\", 1)".to_string())
+            ),
+        ]
+    );
+
+    let printed = class.to_mermaid();
+    let reparsed = match parse_diagram(&printed).unwrap() {
+        mermaid_parser::DiagramType::Class(reparsed) => reparsed,
+        _ => panic!("Expected Class diagram"),
+    };
+    assert_eq!(reparsed, class, "{}", printed);
+}
+
+#[test]
+fn test_api_surface() {
+    use mermaid_parser::common::ast::{
+        AccessibilityInfo, ApiMemberKind, ApiSurfaceOptions, Class, ClassDiagram, ClassMember,
+        Method, Parameter, Property, Stereotype, Visibility,
+    };
+
+    let property = |name: &str, prop_type: &str, visibility: Visibility| {
+        ClassMember::Property(Property {
+            name: name.to_string(),
+            prop_type: Some(prop_type.to_string()),
+            visibility,
+            is_static: false,
+            default_value: None,
+            generics: vec![],
+        })
+    };
+    let class = |name: &str, stereotype: Option<Stereotype>, members: Vec<ClassMember>| {
+        (
+            name.to_string(),
+            Class {
+                name: name.to_string(),
+                stereotype,
+                members,
+                annotations: vec![],
+                css_class: None,
+                generics: vec![],
+            },
+        )
+    };
+
+    let diagram = ClassDiagram {
+        title: None,
+        accessibility: AccessibilityInfo::default(),
+        direction: None,
+        classes: [
+            class(
+                "Account",
+                Some(Stereotype::Service),
+                vec![
+                    property("owner", "String", Visibility::Public),
+                    property("balance", "int", Visibility::Private),
+                    ClassMember::Method(Method {
+                        name: "deposit".to_string(),
+                        parameters: vec![
+                            Parameter {
+                                name: "amount".to_string(),
+                                param_type: Some("int".to_string()),
+                            },
+                            Parameter {
+                                name: "memo".to_string(),
+                                param_type: None,
+                            },
+                        ],
+                        return_type: Some("bool".to_string()),
+                        visibility: Visibility::Public,
+                        is_static: true,
+                        is_abstract: false,
+                        generics: vec![],
+                    }),
+                ],
+            ),
+            class(
+                "Ledger",
+                Some(Stereotype::Custom("Internal".to_string())),
+                vec![property("entries", "List", Visibility::Public)],
+            ),
+            class(
+                "Audit",
+                None,
+                vec![property("log", "String", Visibility::Protected)],
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        relationships: vec![],
+        notes: vec![],
+        namespaces: Default::default(),
+        styles: vec![],
+        clicks: vec![],
+    };
+
+    let surface = diagram.api_surface();
+    assert_eq!(surface.len(), 1);
+    assert_eq!(surface[0].class, "Account");
+    let members: Vec<(ApiMemberKind, &str)> = surface[0]
+        .members
+        .iter()
+        .map(|member| (member.kind, member.signature.as_str()))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            (ApiMemberKind::Property, "owner: String"),
+            (ApiMemberKind::Method, "deposit(amount: int, memo): bool"),
+        ]
+    );
+    assert!(surface[0].members[1].is_static);
+
+    // Callers choose which markers hide a class
+    let options = ApiSurfaceOptions {
+        excluded_stereotypes: vec!["service".to_string()],
+    };
+    let classes: Vec<String> = diagram
+        .api_surface_with(&options)
+        .into_iter()
+        .map(|api| api.class)
+        .collect();
+    assert_eq!(classes, vec!["Ledger"]);
+}
+
+#[test]
+fn test_er_stub_from_entity_classes() {
+    use mermaid_parser::common::ast::{
+        AccessibilityInfo, CardinalityValue, Class, ClassDiagram, ClassMember, ClassRelationship,
+        ClassRelationshipType, KeyType, Property, Stereotype, Visibility,
+    };
+    use mermaid_parser::common::er_stub::ErStubOptions;
+
+    let property = |name: &str, prop_type: Option<&str>| {
+        ClassMember::Property(Property {
+            name: name.to_string(),
+            prop_type: prop_type.map(str::to_string),
+            visibility: Visibility::Private,
+            is_static: false,
+            default_value: None,
+            generics: vec![],
+        })
+    };
+    let class = |name: &str, marker: Option<&str>, members: Vec<ClassMember>| {
+        (
+            name.to_string(),
+            Class {
+                name: name.to_string(),
+                stereotype: marker.map(|marker| Stereotype::Custom(marker.to_string())),
+                members,
+                annotations: vec![],
+                css_class: None,
+                generics: vec![],
+            },
+        )
+    };
+    let relationship =
+        |from: &str, to: &str, kind, to_cardinality: Option<&str>| ClassRelationship {
+            from: from.to_string(),
+            to: to.to_string(),
+            relationship_type: kind,
+            from_cardinality: None,
+            to_cardinality: to_cardinality.map(str::to_string),
+            label: None,
+        };
+
+    let diagram = ClassDiagram {
+        title: Some("Shop".to_string()),
+        accessibility: AccessibilityInfo::default(),
+        direction: None,
+        classes: [
+            class(
+                "Customer",
+                Some("Entity"),
+                vec![property("id", Some("int")), property("name", None)],
+            ),
+            class(
+                "Order",
+                Some("entity"),
+                vec![
+                    property("id", Some("int")),
+                    property("customerId", Some("int")),
+                    property("lines", Some("List~Line~")),
+                ],
+            ),
+            class("OrderService", None, vec![]),
+        ]
+        .into_iter()
+        .collect(),
+        relationships: vec![
+            relationship(
+                "Customer",
+                "Order",
+                ClassRelationshipType::Association,
+                Some("0..*"),
+            ),
+            relationship(
+                "OrderService",
+                "Order",
+                ClassRelationshipType::Dependency,
+                None,
+            ),
+            relationship(
+                "Order",
+                "Customer",
+                ClassRelationshipType::Inheritance,
+                None,
+            ),
+        ],
+        notes: vec![],
+        namespaces: Default::default(),
+        styles: vec![],
+        clicks: vec![],
+    };
+
+    let er = diagram.to_er_stub();
+    assert_eq!(er.title.as_deref(), Some("Shop"));
+    assert_eq!(er.entities.len(), 2);
+
+    let order = &er.entities["Order"].attributes;
+    let keys: Vec<(&str, &str, Option<KeyType>)> = order
+        .iter()
+        .map(|a| (a.name.as_str(), a.attr_type.as_str(), a.key_type.clone()))
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            ("id", "int", Some(KeyType::PK)),
+            ("customerId", "int", Some(KeyType::FK)),
+            ("lines", "List_Line_", None),
+        ]
+    );
+    assert_eq!(er.entities["Customer"].attributes[1].attr_type, "string");
+
+    assert_eq!(er.relationships.len(), 1);
+    let placed = &er.relationships[0];
+    assert_eq!(
+        (placed.left_entity.as_str(), placed.right_entity.as_str()),
+        ("Customer", "Order")
+    );
+    assert_eq!(placed.right_cardinality.min, CardinalityValue::Zero);
+    assert_eq!(placed.right_cardinality.max, CardinalityValue::Many);
+    assert_eq!(placed.label.as_deref(), Some("has"));
+
+    let options = ErStubOptions {
+        stereotype: "table".to_string(),
+        ..ErStubOptions::default()
+    };
+    assert!(diagram.to_er_stub_with(&options).entities.is_empty());
+}
@@ -3,18 +3,25 @@
 use std::path::PathBuf;
 
 /// Reads a test file and cleans it by removing metadata comments
+#[allow(dead_code)]
 pub fn read_and_clean_test_file(path: &PathBuf) -> String {
-    let content =
-        std::fs::read_to_string(path).unwrap_or_else(|_| panic!("Failed to read file: {:?}", path));
+    mermaid_parser::fs::read_diagram(path)
+        .unwrap_or_else(|e| panic!("Failed to read file: {:?}: {}", path, e))
+}
 
-    // Remove metadata comments (lines starting with //)
-    content
-        .lines()
-        .filter(|line| !line.starts_with("//"))
-        .collect::<Vec<_>>()
-        .join("\n")
-        .trim()
-        .to_string()
+/// Creates a fresh directory under the system temp directory holding `files`,
+/// given as (relative path, content) pairs
+#[allow(dead_code)]
+pub fn temp_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("mermaid_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    for (file, content) in files {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    root
 }
 
 /// Checks if a file should be skipped based on content
@@ -0,0 +1,103 @@
+#![cfg(feature = "fs")]
+
+mod common;
+
+use mermaid_parser::corpus::{self, DryRun, FormatFailure};
+use mermaid_parser::PrintOptions;
+
+#[test]
+fn test_format_directory_check_then_write() {
+    let markdown = "# Docs\n\n```mermaid\npie\n  \"A\" : 1\n```\n\n- item\n\n  ~~~mermaid\n  not a diagram\n  ~~~\n";
+    let root = common::temp_tree(
+        "format",
+        &[
+            ("pie.mmd", "// Source: test\npie\n  \"A\" : 1"),
+            ("clean.mmd", "pie\n    \"B\" : 2\n"),
+            ("docs/guide.md", markdown),
+            ("docs/plain.md", "# No diagrams\n"),
+            ("notes.txt", "pie\n  \"C\" : 3"),
+        ],
+    );
+    let options = PrintOptions::default();
+
+    let check = corpus::format_directory(&root, &options, DryRun::Yes).unwrap();
+    assert_eq!(check.files.len(), 3);
+    assert!(!check.is_clean());
+    let changed: Vec<_> = check
+        .changed()
+        .map(|file| file.path.file_name().unwrap().to_owned())
+        .collect();
+    assert_eq!(changed, ["guide.md", "pie.mmd"]);
+    assert_eq!(
+        std::fs::read_to_string(root.join("pie.mmd")).unwrap(),
+        "// Source: test\npie\n  \"A\" : 1"
+    );
+
+    let failed: Vec<_> = check.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].diagrams, 2);
+    assert!(matches!(
+        failed[0].failures[..],
+        [FormatFailure::Parse { line: 11, .. }]
+    ));
+
+    let written = corpus::format_directory(&root, &options, DryRun::No).unwrap();
+    assert_eq!(written.changed().count(), 2);
+    assert_eq!(
+        std::fs::read_to_string(root.join("pie.mmd")).unwrap(),
+        "// Source: test\npie\n    \"A\" : 1\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("docs/guide.md")).unwrap(),
+        markdown.replace("pie\n  \"A\"", "pie\n    \"A\"")
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("notes.txt")).unwrap(),
+        "pie\n  \"C\" : 3"
+    );
+
+    let again = corpus::format_directory(&root, &options, DryRun::Yes).unwrap();
+    assert_eq!(again.changed().count(), 0);
+    assert_eq!(again.failed().count(), 1);
+
+    let single = corpus::format_directory(root.join("clean.mmd"), &options, DryRun::Yes).unwrap();
+    assert_eq!(single.files.len(), 1);
+    assert!(single.is_clean());
+}
+
+#[test]
+fn test_unrecognized_headers_are_clustered() {
+    let markdown = "# Docs\n\n```mermaid\nzenUML\n  A.call()\n```\n\n```mermaid\ninfo\n```\n";
+    let root = common::temp_tree(
+        "unrecognized",
+        &[
+            ("a.mmd", "zenuml\n  title Demo"),
+            ("b.mmd", "%%{init: {}}%%\nvenn-beta\n  set A"),
+            ("c.mmd", "flowchart TD\n  A --> B"),
+            ("docs/guide.md", markdown),
+            ("notes.txt", "zenuml"),
+        ],
+    );
+
+    let report = corpus::unrecognized_headers(&root).unwrap();
+    assert_eq!(report.diagrams(), 5);
+    assert_eq!(report.unrecognized(), 3);
+    let clusters: Vec<_> = report
+        .clusters()
+        .into_iter()
+        .map(|cluster| (cluster.keyword, cluster.count, cluster.spellings))
+        .collect();
+    assert_eq!(
+        clusters,
+        [
+            (
+                "zenuml".to_string(),
+                2,
+                vec![("zenUML".to_string(), 1), ("zenuml".to_string(), 1)]
+            ),
+            ("venn".to_string(), 1, vec![("venn-beta".to_string(), 1)]),
+        ]
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
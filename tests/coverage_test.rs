@@ -0,0 +1,102 @@
+mod common;
+
+use mermaid_parser::stable::DiagramKind;
+use mermaid_parser::testing::coverage::{self, FixtureOutcome};
+use std::path::Path;
+
+#[test]
+fn test_flowchart_fixture_coverage_report() {
+    let kind = DiagramKind::Flowchart;
+    let dir = Path::new("test").join(coverage::fixture_dir_name(kind));
+    let report = coverage::report(kind, &dir).unwrap();
+
+    assert!(!report.fixtures.is_empty());
+    assert!(report.parsed() > 0);
+    assert_eq!(
+        report.parsed() + report.unparsed().count(),
+        report.fixtures.len()
+    );
+
+    let flowchart = report
+        .features
+        .iter()
+        .find(|feature| feature.feature == "flowchart")
+        .unwrap();
+    assert!(flowchart.fixtures > 0);
+    assert!(flowchart.parsed <= flowchart.fixtures);
+
+    let json = report.to_json();
+    assert!(json.starts_with("{\"kind\":\"Flowchart\""));
+    assert!(json.contains(&format!("\"fixtures\":{}", report.fixtures.len())));
+}
+
+#[test]
+fn test_every_fixture_directory_has_a_report() {
+    for entry in std::fs::read_dir("test").unwrap() {
+        let dir = entry.unwrap().path();
+        let name = dir.file_name().unwrap().to_str().unwrap().to_string();
+        let kind = [
+            DiagramKind::Sankey,
+            DiagramKind::Timeline,
+            DiagramKind::Journey,
+            DiagramKind::Sequence,
+            DiagramKind::Class,
+            DiagramKind::State,
+            DiagramKind::Flowchart,
+            DiagramKind::Gantt,
+            DiagramKind::Pie,
+            DiagramKind::Git,
+            DiagramKind::Er,
+            DiagramKind::C4,
+            DiagramKind::Mindmap,
+            DiagramKind::Quadrant,
+            DiagramKind::XyChart,
+            DiagramKind::Kanban,
+            DiagramKind::Block,
+            DiagramKind::Architecture,
+            DiagramKind::Packet,
+            DiagramKind::Requirement,
+            DiagramKind::Treemap,
+            DiagramKind::Radar,
+            DiagramKind::Misc,
+        ]
+        .into_iter()
+        .find(|kind| coverage::fixture_dir_name(*kind) == name)
+        .unwrap_or_else(|| panic!("No diagram kind for fixture directory {}", name));
+
+        let report = coverage::report(kind, &dir).unwrap();
+        assert_eq!(report.kind, kind);
+        assert!(report.ratio() <= 1.0);
+    }
+}
+
+#[test]
+fn test_report_counts_outcomes_and_features() {
+    let dir = common::temp_tree(
+        "coverage_outcomes",
+        &[
+            ("a.mermaid", "// Type: pie\npie showData\n    \"A\" : 1"),
+            ("b.mermaid", "pie\n    title Pets\n    \"Dogs\" : 2"),
+            ("c.mermaid", "flowchart TD\n    A --> B"),
+            ("d.mermaid", "// Type: pie\n"),
+            ("notes.txt", "pie"),
+        ],
+    );
+
+    let report = coverage::report(DiagramKind::Pie, &dir).unwrap();
+    assert_eq!(report.fixtures.len(), 3);
+    assert_eq!(report.parsed(), 2);
+    assert_eq!(
+        report.unparsed().next().unwrap().outcome,
+        FixtureOutcome::OtherKind(DiagramKind::Flowchart)
+    );
+
+    let feature = |name: &str| report.features.iter().find(|f| f.feature == name).unwrap();
+    assert_eq!(
+        (feature("showData").fixtures, feature("showData").parsed),
+        (1, 1)
+    );
+    assert_eq!(feature("title").fixtures, 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
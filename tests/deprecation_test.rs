@@ -0,0 +1,77 @@
+use mermaid_parser::common::ast::{GitOperation, StateVersion};
+use mermaid_parser::common::deprecation::{detect_legacy_syntax, modernize, modernize_source};
+use mermaid_parser::common::fix::apply_fixes;
+use mermaid_parser::{parse_diagram, DiagramType};
+
+#[test]
+fn test_modernize_graph_header() {
+    let input = "graph LR\n    A[Start] --> B[End]";
+    let diagnostics = detect_legacy_syntax(input);
+    assert_eq!(diagnostics[0].rule, "deprecated_graph_header");
+
+    // The header is not part of the AST, so there is nothing to fix
+    let mut diagram = parse_diagram(input).unwrap();
+    let report = apply_fixes(&mut diagram, &diagnostics);
+    assert!(!report.changed());
+    assert_eq!(report.skipped.len(), 1);
+
+    let output = modernize_source(input).unwrap();
+    assert!(output.starts_with("flowchart LR"));
+    assert!(detect_legacy_syntax(&output).is_empty());
+}
+
+#[test]
+fn test_modernize_state_diagram_v1() {
+    let input = "stateDiagram\n    [*] --> Idle\n    Idle --> [*]";
+    let mut diagram = parse_diagram(input).unwrap();
+    assert!(modernize(&mut diagram));
+
+    match &diagram {
+        DiagramType::State(state) => assert_eq!(state.version, StateVersion::V2),
+        other => panic!("Expected state diagram, got {:?}", other),
+    }
+    assert!(!modernize(&mut diagram));
+
+    let output = modernize_source(input).unwrap();
+    assert!(output.starts_with("stateDiagram-v2"));
+    assert!(detect_legacy_syntax(&output).is_empty());
+}
+
+#[test]
+fn test_legacy_git_graph_fixed_through_apply_fixes() {
+    let input = r#"gitGraph:
+options
+{
+    "nodeSpacing": 150
+}
+end
+commit
+branch newbranch
+checkout newbranch
+commit id: "feature"
+checkout main
+merge newbranch"#;
+
+    let diagnostics = detect_legacy_syntax(input);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].rule, "deprecated_git_graph_syntax");
+
+    let mut diagram = parse_diagram(input).unwrap();
+    let report = apply_fixes(&mut diagram, &diagnostics);
+    assert!(report.changed(), "{:?}", report);
+
+    let DiagramType::Git(git) = &diagram else {
+        panic!("Expected git diagram, got {:?}", diagram);
+    };
+    assert_eq!(git.operations.len(), 6);
+    assert!(git.branches.iter().any(|branch| branch.name == "newbranch"));
+    assert!(matches!(
+        &git.operations[3],
+        GitOperation::Commit { id: Some(id), .. } if id == "feature"
+    ));
+
+    let output = modernize_source(input).unwrap();
+    assert!(output.starts_with("gitGraph\n"));
+    assert!(!output.contains("options"));
+    assert!(detect_legacy_syntax(&output).is_empty());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use mermaid_parser::testing::differential::{
+    self, Change, CurrentParser, ParserFn, Snapshot, VersionedParser,
+};
+use std::fs;
+use std::path::PathBuf;
+
+/// Stands in for an older release that could not parse titled pie charts
+fn previous() -> impl VersionedParser {
+    ParserFn::new("0.0.1", |input: &str| {
+        if input.contains("title") {
+            Snapshot::Failed("unexpected `title`".to_string())
+        } else {
+            Snapshot::from_result(mermaid_parser::parse_diagram(input))
+        }
+    })
+}
+
+#[test]
+fn test_compare_classifies_changes() {
+    let corpus = [
+        ("same", "flowchart TD\n    A --> B\n    B --> C"),
+        ("titled", "pie\n    title Pets\n    \"Dogs\" : 2"),
+        ("broken", "pie\n    \"Dogs\" : lots"),
+    ];
+    let report = differential::compare(&previous(), &CurrentParser, corpus);
+    assert_eq!(report.compared, 3);
+    assert_eq!(report.baseline, "0.0.1");
+    assert_eq!(report.candidate, env!("CARGO_PKG_VERSION"));
+    assert_eq!(report.changes.len(), 1);
+    assert_eq!(report.changes[0].name, "titled");
+    assert_eq!(report.changes[0].change, Change::Fixed);
+    assert!(report.is_compatible());
+
+    // The same corpus the other way round
+    let report = differential::compare(&CurrentParser, &previous(), corpus);
+    assert_eq!(report.regressions().count(), 1);
+    assert!(!report.is_compatible());
+    assert!(report
+        .to_json()
+        .contains("{\"name\":\"titled\",\"change\":\"regressed\""));
+}
+
+#[test]
+fn test_compare_dir_reads_nested_diagrams() {
+    let dir = common::temp_tree(
+        "differential",
+        &[
+            ("a.mmd", "pie\n    \"A\" : 1"),
+            ("nested/b.mermaid", "pie\n    title Pets\n    \"A\" : 1"),
+            ("notes.md", "pie title Ignored"),
+        ],
+    );
+
+    let report = differential::compare_dir(&CurrentParser, &previous(), &dir).unwrap();
+    assert_eq!(report.compared, 2);
+    assert_eq!(
+        PathBuf::from(&report.changes[0].name),
+        dir.join("nested").join("b.mermaid")
+    );
+    assert!(
+        differential::compare_dir(&CurrentParser, &CurrentParser, dir.join("missing")).is_err()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "encoding")]
+
+use mermaid_parser::encoding::{self, Encoding};
+use mermaid_parser::{parse_diagram, parse_diagram_bytes, ParseError};
+
+const INPUT: &str = "flowchart LR\n    A[Café] --> B[日本]";
+
+fn utf16_le(text: &str) -> Vec<u8> {
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    bytes
+}
+
+#[test]
+fn test_utf16_and_utf8_bom_input_parses_like_utf8() {
+    let expected = parse_diagram(INPUT).unwrap();
+
+    let mut big_endian = vec![0xFE, 0xFF];
+    big_endian.extend(INPUT.encode_utf16().flat_map(u16::to_be_bytes));
+    let mut with_bom = vec![0xEF, 0xBB, 0xBF];
+    with_bom.extend(INPUT.as_bytes());
+
+    for bytes in [
+        utf16_le(INPUT),
+        big_endian,
+        with_bom,
+        INPUT.as_bytes().to_vec(),
+    ] {
+        assert_eq!(parse_diagram_bytes(&bytes).unwrap(), expected);
+    }
+    assert_eq!(encoding::detect(&utf16_le(INPUT)), Encoding::Utf16Le);
+    assert_eq!(encoding::detect(INPUT.as_bytes()), Encoding::Utf8);
+}
+
+#[test]
+fn test_undecodable_input_reports_its_offset() {
+    // An unpaired high surrogate after "pie"
+    let mut bytes = utf16_le("pie");
+    bytes.extend([0x00, 0xD8, 0x41, 0x00]);
+    assert_eq!(
+        parse_diagram_bytes(&bytes).unwrap_err(),
+        ParseError::InvalidEncoding {
+            encoding: "UTF-16LE".to_string(),
+            offset: 8,
+        }
+    );
+
+    let mut odd = utf16_le("pie");
+    odd.push(0x41);
+    assert_eq!(
+        encoding::decode(&odd).unwrap_err().to_string(),
+        "Input is not valid UTF-16LE at byte 8"
+    );
+
+    let error = parse_diagram_bytes(b"pie\n\"\xFF\" : 1").unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::InvalidEncoding {
+            encoding: "UTF-8".to_string(),
+            offset: 5,
+        }
+    );
+}
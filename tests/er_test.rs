@@ -186,3 +186,168 @@ fn test_cardinality_types() {
         _ => panic!("Expected ER diagram"),
     }
 }
+
+#[test]
+fn test_unicode_entity_names() {
+    let input = r#"erDiagram
+    고객 ||--o{ 주문 : places
+    고객 {
+        string 이름
+    }
+"#;
+
+    match parse_diagram(input).expect("Failed to parse unicode entity names") {
+        mermaid_parser::DiagramType::Er(diagram) => {
+            assert!(diagram.entities.contains_key("고객"));
+            assert_eq!(diagram.relationships.len(), 1);
+            assert_eq!(diagram.relationships[0].left_entity, "고객");
+            assert_eq!(diagram.relationships[0].right_entity, "주문");
+            assert_eq!(diagram.entities["고객"].attributes[0].name, "이름");
+        }
+        _ => panic!("Expected ER diagram"),
+    }
+}
+
+#[test]
+fn test_relaxed_dialect_reports_relaxations() {
+    use mermaid_parser::common::ast::KeyType;
+    use mermaid_parser::parsers::er::{parse_with_dialect, ErDialect};
+
+    let input = r#"erDiagram
+    CUSTOMER ||--o{ ORDER places
+    CUSTOMER {
+        string id pk, fk "primary  key"
+        string name,
+    }
+    ORDER ||--|{ LINE-ITEM : contains"#;
+
+    let (diagram, relaxations) = parse_with_dialect(input, ErDialect::Relaxed).unwrap();
+    let customer = &diagram.entities["CUSTOMER"];
+    assert_eq!(customer.attributes.len(), 2);
+    assert_eq!(customer.attributes[0].key_type, Some(KeyType::PK));
+    assert_eq!(
+        customer.attributes[0].comment.as_deref(),
+        Some("primary  key")
+    );
+    assert_eq!(diagram.relationships[0].label.as_deref(), Some("places"));
+    assert_eq!(diagram.relationships[1].label.as_deref(), Some("contains"));
+
+    let reported: Vec<(&str, usize)> = relaxations
+        .iter()
+        .map(|warning| (warning.rule, warning.location.as_ref().unwrap().line))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![
+            ("er_missing_label_colon", 2),
+            ("er_lowercase_key_type", 4),
+            ("er_lowercase_key_type", 4),
+            ("er_trailing_comma", 5),
+        ]
+    );
+
+    // The strict dialect reports nothing and does not pick up the label
+    let (strict, warnings) = parse_with_dialect(
+        "erDiagram\n    CUSTOMER ||--o{ ORDER places",
+        ErDialect::MermaidStrict,
+    )
+    .unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(strict.relationships[0].label, None);
+}
+
+#[test]
+fn test_escaped_quotes_in_attribute_comments() {
+    use mermaid_parser::{DiagramType, MermaidPrinter};
+
+    let input = r#"erDiagram
+    PART {
+        string code "len \"short\""
+        string path "C:\temp\\"
+    }"#;
+    let diagram = parse_diagram(input).unwrap();
+    let DiagramType::Er(er) = &diagram else {
+        panic!("expected an ER diagram");
+    };
+    let comments: Vec<_> = er.entities["PART"]
+        .attributes
+        .iter()
+        .map(|attribute| attribute.comment.as_deref().unwrap())
+        .collect();
+    assert_eq!(comments, ["len \"short\"", "C:\\temp\\"]);
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains(r#""len \"short\"""#));
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_styles_and_classes_round_trip() {
+    use mermaid_parser::common::ast::StyleTarget;
+    use mermaid_parser::{DiagramType, MermaidPrinter};
+
+    let input = r#"erDiagram
+    CAR {
+        string make
+    }
+    PERSON:::foo ||--|| CAR : owns
+    PERSON o{--|| HOUSE:::bar : has
+    CAR:::bar
+    style CAR,HOUSE fill:#f9f,stroke-dasharray: 5 5
+    classDef foo stroke:#f00
+    classDef bar stroke:#0f0,stroke-width:2px"#;
+
+    let diagram = parse_diagram(input).unwrap();
+    let DiagramType::Er(er) = &diagram else {
+        panic!("expected an ER diagram");
+    };
+    assert_eq!(er.entities["PERSON"].css_class.as_deref(), Some("foo"));
+    assert_eq!(er.entities["HOUSE"].css_class.as_deref(), Some("bar"));
+    assert_eq!(er.entities["CAR"].css_class.as_deref(), Some("bar"));
+    assert_eq!(er.entities["CAR"].attributes.len(), 1);
+    assert_eq!(er.styles.len(), 2);
+    assert_eq!(er.styles[1].target, StyleTarget::Node("HOUSE".to_string()));
+    assert_eq!(er.styles[1].styles["stroke-dasharray"], "5 5");
+    assert_eq!(er.class_defs["bar"].styles["stroke-width"], "2px");
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("    CAR:::bar {\n"));
+    assert!(printed.contains("    classDef foo stroke:#f00\n"));
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_entity_aliases_keep_display_names() {
+    use mermaid_parser::{DiagramType, MermaidPrinter};
+
+    let input = r#"erDiagram
+    p[Person] {
+        string name
+    }
+    a["Street Address"]:::home
+    p ||--o{ a : "lives at"
+    c[Car] ||--|| p : owns"#;
+
+    let diagram = parse_diagram(input).unwrap();
+    let DiagramType::Er(er) = &diagram else {
+        panic!("expected an ER diagram");
+    };
+    assert_eq!(er.entities["p"].display_name.as_deref(), Some("Person"));
+    assert_eq!(er.entities["p"].attributes.len(), 1);
+    assert_eq!(
+        er.entities["a"].display_name.as_deref(),
+        Some("Street Address")
+    );
+    assert_eq!(er.entities["a"].css_class.as_deref(), Some("home"));
+    assert_eq!(er.entities["c"].display_name.as_deref(), Some("Car"));
+    assert_eq!(er.relationships[1].left_entity, "c");
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("    p[Person] {\n"), "{}", printed);
+    assert!(
+        printed.contains("    a[Street Address]:::home {\n"),
+        "{}",
+        printed
+    );
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+}
@@ -0,0 +1,107 @@
+use mermaid_parser::common::events::{emit_events, ParseEvent};
+use mermaid_parser::common::stream::CHUNK_LINES;
+use mermaid_parser::stable::DiagramKind;
+use mermaid_parser::{parse_diagram, parse_diagram_events};
+
+fn events(input: &str) -> Vec<String> {
+    let mut events = Vec::new();
+    parse_diagram_events(input.as_bytes(), &mut |event: ParseEvent<'_>| {
+        events.push(format!("{:?}", event))
+    })
+    .unwrap();
+    events
+}
+
+#[test]
+fn test_flowchart_nodes_then_edges() {
+    let mut kinds = Vec::new();
+    let mut edges = Vec::new();
+    let input = "flowchart TD\n    B --> C\n    A[Start] -->|go| B";
+    parse_diagram_events(input.as_bytes(), &mut |event: ParseEvent<'_>| match event {
+        ParseEvent::DiagramStart(kind) => kinds.push(format!("{:?}", kind)),
+        ParseEvent::Node { id, .. } => kinds.push(id.to_string()),
+        ParseEvent::Edge {
+            from, to, label, ..
+        } => edges.push((from.to_string(), to.to_string(), label.map(str::to_string))),
+        ParseEvent::DiagramEnd => kinds.push("end".to_string()),
+        _ => {}
+    })
+    .unwrap();
+
+    assert_eq!(kinds, vec!["Flowchart", "B", "C", "A", "end"]);
+    assert_eq!(
+        edges,
+        vec![
+            ("B".to_string(), "C".to_string(), None),
+            ("A".to_string(), "B".to_string(), Some("go".to_string())),
+        ]
+    );
+
+    let diagram = parse_diagram(input).unwrap();
+    let mut walked = Vec::new();
+    let mut walk = |event: ParseEvent<'_>| walked.push(format!("{:?}", event));
+    emit_events(&diagram, &mut walk);
+    assert_eq!(walked, events(input));
+}
+
+#[test]
+fn test_streamed_sections_are_reported_once() {
+    let mut gantt = String::from("gantt\n    title Plan\n    section Build\n");
+    for task in 0..CHUNK_LINES + 10 {
+        gantt.push_str(&format!("    Task {} : t{}, 2024-01-01, 1d\n", task, task));
+    }
+    gantt.push_str("    section Ship\n    Release : r1, after t0, 1d\n");
+
+    let events = events(&gantt);
+    let sections: Vec<_> = events
+        .iter()
+        .filter(|event| event.starts_with("SectionStart"))
+        .collect();
+    assert_eq!(
+        sections,
+        vec!["SectionStart(\"Build\")", "SectionStart(\"Ship\")"]
+    );
+    let nodes = events
+        .iter()
+        .filter(|event| event.starts_with("Node"))
+        .count();
+    assert_eq!(nodes, CHUNK_LINES + 11);
+    assert_eq!(events[0], format!("DiagramStart({:?})", DiagramKind::Gantt));
+    assert_eq!(events[1], "Title(\"Plan\")");
+    assert_eq!(events.last().unwrap(), "DiagramEnd");
+}
+
+#[test]
+fn test_streamed_sankey_nodes_are_reported_once() {
+    let rows: String = (0..CHUNK_LINES * 2)
+        .map(|i| format!("Source,N{},1\n", i % 3))
+        .collect();
+    let mut nodes = Vec::new();
+    let mut total = 0.0;
+    parse_diagram_events(
+        format!("sankey-beta\n{}", rows).as_bytes(),
+        &mut |event: ParseEvent<'_>| match event {
+            ParseEvent::Node { id, .. } => nodes.push(id.to_string()),
+            ParseEvent::Edge {
+                value: Some(value), ..
+            } => total += value,
+            _ => {}
+        },
+    )
+    .unwrap();
+
+    nodes.sort();
+    assert_eq!(nodes, vec!["N0", "N1", "N2", "Source"]);
+    assert_eq!(total, (CHUNK_LINES * 2) as f64);
+}
+
+#[test]
+fn test_failed_parse_sends_no_diagram_end() {
+    let mut events = Vec::new();
+    let result = parse_diagram_events(
+        "pie\n    \"Dogs\" : 3\n    not a slice".as_bytes(),
+        &mut |event: ParseEvent<'_>| events.push(event == ParseEvent::DiagramEnd),
+    );
+    assert!(result.is_err());
+    assert!(!events.contains(&true));
+}
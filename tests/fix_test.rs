@@ -0,0 +1,364 @@
+use mermaid_parser::common::fix::apply_fixes;
+use mermaid_parser::common::validation::{Severity, UniversalValidator, ValidationConfig};
+use mermaid_parser::{parse_diagram, DiagramType, MermaidPrinter};
+
+fn diagnostics(diagram: &DiagramType) -> Vec<mermaid_parser::common::validation::ValidationError> {
+    let validator = UniversalValidator::with_config(ValidationConfig {
+        min_severity: Severity::Info,
+        ..Default::default()
+    });
+    validator.validate_any(diagram).err().unwrap_or_default()
+}
+
+#[test]
+fn test_fix_reserved_participant_and_missing_acc_title() {
+    let mut diagram = parse_diagram(
+        r#"sequenceDiagram
+    title Checkout
+    participant end as Finish
+    A->>end: done
+    activate end"#,
+    )
+    .unwrap();
+
+    let found = diagnostics(&diagram);
+    let rules: Vec<_> = found.iter().map(|d| d.rule).collect();
+    assert!(rules.contains(&"reserved_word_id"));
+    assert!(rules.contains(&"missing_acc_title"));
+
+    let report = apply_fixes(&mut diagram, &found);
+    assert_eq!(report.applied.len(), 2, "{:?}", report);
+    assert!(report.changed());
+
+    let DiagramType::Sequence(sequence) = &diagram else {
+        panic!("Expected sequence diagram");
+    };
+    assert_eq!(sequence.accessibility.title, Some("Checkout".to_string()));
+    let participant = sequence.resolve_actor("End").unwrap();
+    assert_eq!(participant.alias, Some("Finish".to_string()));
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("A ->> End: done"));
+    assert!(printed.contains("activate End"));
+    assert!(printed.contains("accTitle: Checkout"));
+
+    // Fixed diagrams no longer produce the fixed diagnostics
+    let remaining = diagnostics(&parse_diagram(&printed).unwrap());
+    assert!(remaining
+        .iter()
+        .all(|d| d.rule != "reserved_word_id" && d.rule != "missing_acc_title"));
+}
+
+#[test]
+fn test_fix_reserved_flowchart_node() {
+    let mut diagram = parse_diagram("flowchart TD\n    A[Start] --> B[Stop]").unwrap();
+    if let DiagramType::Flowchart(flowchart) = &mut diagram {
+        flowchart.rename_node("B", "end");
+        flowchart.nodes.get_mut("end").unwrap().text = None;
+    }
+
+    let found = diagnostics(&diagram);
+    let report = apply_fixes(&mut diagram, &found);
+    assert!(report
+        .applied
+        .iter()
+        .any(|fix| fix.description == "Renamed node 'end' to 'End'"));
+    // Without a title there is nothing to use as accTitle
+    assert!(report.skipped.iter().any(|d| d.rule == "missing_acc_title"));
+
+    let DiagramType::Flowchart(flowchart) = &diagram else {
+        panic!("Expected flowchart");
+    };
+    assert_eq!(flowchart.nodes["End"].text, Some("end".to_string()));
+    assert_eq!(flowchart.edges[0].to, "End");
+}
+
+#[test]
+fn test_fix_duplicate_quadrant_class_def() {
+    let mut diagram = parse_diagram(
+        r#"quadrantChart
+    Point A:::hot: [0.3, 0.6]
+    Point B:::cold: [0.7, 0.2]
+    classDef hot color:#ff0000
+    classDef cold color:#0000ff
+    classDef hot radius:10
+    classDef hot color:#00ff00"#,
+    )
+    .unwrap();
+
+    let found = diagnostics(&diagram);
+    let report = apply_fixes(&mut diagram, &found);
+    assert!(report
+        .applied
+        .iter()
+        .any(|f| f.rule == "duplicate_class_def"));
+
+    let DiagramType::Quadrant(quadrant) = &diagram else {
+        panic!("Expected quadrant chart");
+    };
+    assert_eq!(quadrant.styles.len(), 2);
+    assert_eq!(quadrant.styles[0].name, "hot");
+    assert_eq!(
+        quadrant.styles[0].styles,
+        vec!["color:#00ff00", "radius:10"]
+    );
+}
+
+fn duplicate_class_def_fixes(diagram: &mut DiagramType) -> Vec<String> {
+    let found = diagnostics(diagram);
+    assert!(found.iter().any(|d| d.rule == "duplicate_class_def"));
+
+    let report = apply_fixes(diagram, &found);
+    let fixes = report
+        .applied
+        .into_iter()
+        .filter(|fix| fix.rule == "duplicate_class_def")
+        .map(|fix| fix.description)
+        .collect();
+
+    // A second pass finds nothing left to merge
+    let printed = parse_diagram(&diagram.to_mermaid()).unwrap();
+    assert!(diagnostics(diagram)
+        .iter()
+        .chain(&diagnostics(&printed))
+        .all(|d| d.rule != "duplicate_class_def"));
+    fixes
+}
+
+#[test]
+fn test_fix_duplicate_flowchart_class_def() {
+    let mut diagram = parse_diagram(
+        r#"flowchart TD
+    A:::hot --> B
+    classDef hot fill:#f00
+    classDef hot stroke:#333
+    classDef hot fill:#0f0"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        duplicate_class_def_fixes(&mut diagram),
+        ["Merged 2 duplicate classDef 'hot' into the first definition"]
+    );
+    let DiagramType::Flowchart(flowchart) = &diagram else {
+        panic!("Expected flowchart");
+    };
+    let hot = &flowchart.class_defs["hot"];
+    assert_eq!(hot.definitions, 1);
+    assert_eq!(hot.styles["fill"], "#0f0");
+    assert_eq!(hot.styles["stroke"], "#333");
+}
+
+#[test]
+fn test_fix_duplicate_state_class_def() {
+    let mut diagram = parse_diagram(
+        r#"stateDiagram-v2
+    [*] --> Idle:::waiting
+    classDef waiting fill:#ccc
+    classDef waiting color:#000"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        duplicate_class_def_fixes(&mut diagram),
+        ["Merged 1 duplicate classDef 'waiting' into the first definition"]
+    );
+    let DiagramType::State(state) = &diagram else {
+        panic!("Expected state diagram");
+    };
+    let waiting = &state.class_defs["waiting"];
+    assert_eq!(waiting.definitions, 1);
+    assert_eq!(waiting.styles.len(), 2);
+}
+
+#[test]
+fn test_fix_duplicate_er_class_def() {
+    let mut diagram = parse_diagram(
+        r#"erDiagram
+    CUSTOMER:::vip ||--o{ ORDER : places
+    classDef vip fill:#ff0
+    classDef vip,audit stroke:#f00"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        duplicate_class_def_fixes(&mut diagram),
+        ["Merged 1 duplicate classDef 'vip' into the first definition"]
+    );
+    let DiagramType::Er(er) = &diagram else {
+        panic!("Expected ER diagram");
+    };
+    assert_eq!(er.class_defs["vip"].definitions, 1);
+    assert_eq!(er.class_defs["vip"].styles["stroke"], "#f00");
+    assert_eq!(er.class_defs["audit"].definitions, 1);
+}
+
+#[test]
+fn test_fix_legacy_architecture_arrows() {
+    let input = r#"architecture-beta
+    service web(server)[Web]
+    service db(database)[DB]
+    web:R --> L:db
+    web:B <-> T:db"#;
+    let mut diagram = parse_diagram(input).unwrap();
+    // The legacy spelling survives printing until it is fixed
+    assert!(diagram.to_mermaid().contains("-->"));
+
+    let found = diagnostics(&diagram);
+    let legacy: Vec<_> = found
+        .iter()
+        .filter(|d| d.rule == "deprecated_arrow_spelling")
+        .collect();
+    assert_eq!(legacy.len(), 1);
+    assert_eq!(
+        legacy[0].location.as_ref().unwrap().element_id.as_deref(),
+        Some("web --> db")
+    );
+
+    let report = apply_fixes(&mut diagram, &found);
+    assert_eq!(
+        report.applied[0].description,
+        "Replaced arrow '-->' with '->'"
+    );
+    let printed = diagram.to_mermaid();
+    assert!(!printed.contains("-->"));
+    assert!(printed.contains("->"));
+    assert!(diagnostics(&parse_diagram(&printed).unwrap())
+        .iter()
+        .all(|d| d.rule != "deprecated_arrow_spelling"));
+
+    // Fixing again changes nothing
+    let report = apply_fixes(&mut diagram, &found);
+    assert!(!report.changed());
+}
+
+#[test]
+fn test_fix_orphaned_styles_and_classes() {
+    let mut diagram = parse_diagram(
+        r#"flowchart TD
+    A:::hot --> B:::missing
+    classDef hot fill:#f00
+    classDef unused fill:#0f0
+    classDef default stroke:#333
+    style A stroke:#000
+    style Ghost fill:#ccc"#,
+    )
+    .unwrap();
+
+    let found = diagnostics(&diagram);
+    let mut orphans: Vec<_> = found
+        .iter()
+        .filter(|d| {
+            matches!(
+                d.rule,
+                "unused_class_def" | "undefined_style_class" | "orphaned_style"
+            )
+        })
+        .map(|d| {
+            (
+                d.rule,
+                d.location.as_ref().unwrap().element_id.clone().unwrap(),
+            )
+        })
+        .collect();
+    orphans.sort();
+    assert_eq!(
+        orphans,
+        vec![
+            ("orphaned_style", "Ghost".to_string()),
+            ("undefined_style_class", "missing".to_string()),
+            ("unused_class_def", "unused".to_string()),
+        ]
+    );
+
+    let report = apply_fixes(&mut diagram, &found);
+    assert!(report
+        .applied
+        .iter()
+        .any(|fix| fix.rule == "orphaned_style"));
+
+    let DiagramType::Flowchart(flowchart) = &diagram else {
+        panic!("Expected flowchart");
+    };
+    assert_eq!(flowchart.styles.len(), 1);
+    assert!(flowchart.nodes["B"].classes.is_empty());
+    let mut class_defs: Vec<_> = flowchart.class_defs.keys().collect();
+    class_defs.sort();
+    assert_eq!(class_defs, ["default", "hot"]);
+
+    let remaining = diagnostics(&parse_diagram(&diagram.to_mermaid()).unwrap());
+    assert!(remaining.iter().all(|d| !matches!(
+        d.rule,
+        "unused_class_def" | "undefined_style_class" | "orphaned_style"
+    )));
+}
+
+#[test]
+fn test_fix_orphaned_quadrant_classes() {
+    let mut diagram = parse_diagram(
+        r#"quadrantChart
+    Point A:::hot: [0.3, 0.6]
+    Point B:::cold: [0.7, 0.2]
+    classDef hot color:#ff0000
+    classDef spare color:#00ff00"#,
+    )
+    .unwrap();
+
+    let report = apply_fixes(&mut diagram, &diagnostics(&diagram));
+    let mut fixed: Vec<_> = report.applied.iter().map(|fix| fix.rule).collect();
+    fixed.sort();
+    assert_eq!(fixed, ["undefined_style_class", "unused_class_def"]);
+
+    let DiagramType::Quadrant(quadrant) = &diagram else {
+        panic!("Expected quadrant chart");
+    };
+    assert_eq!(quadrant.styles.len(), 1);
+    assert_eq!(quadrant.points[1].class, None);
+}
+
+#[test]
+fn test_fix_orphaned_er_classes_and_styles() {
+    let mut diagram = parse_diagram(
+        r#"erDiagram
+    CUSTOMER:::vip ||--o{ ORDER:::missing : places
+    classDef vip fill:#ff0
+    classDef spare fill:#0f0
+    style CUSTOMER stroke:#333
+    style GHOST fill:#ccc"#,
+    )
+    .unwrap();
+
+    let found = diagnostics(&diagram);
+    let mut orphans: Vec<_> = found
+        .iter()
+        .map(|d| {
+            (
+                d.rule,
+                d.location.as_ref().unwrap().element_id.clone().unwrap(),
+            )
+        })
+        .collect();
+    orphans.sort();
+    assert_eq!(
+        orphans,
+        vec![
+            ("orphaned_style", "GHOST".to_string()),
+            ("undefined_style_class", "missing".to_string()),
+            ("unused_class_def", "spare".to_string()),
+        ]
+    );
+
+    let report = apply_fixes(&mut diagram, &found);
+    assert_eq!(report.applied.len(), 3, "{:?}", report);
+
+    let DiagramType::Er(er) = &diagram else {
+        panic!("Expected ER diagram");
+    };
+    assert_eq!(er.class_defs.len(), 1);
+    assert!(er.class_defs.contains_key("vip"));
+    assert_eq!(er.entities["ORDER"].css_class, None);
+    assert_eq!(er.styles.len(), 1);
+
+    let remaining = diagnostics(&parse_diagram(&diagram.to_mermaid()).unwrap());
+    assert!(remaining.is_empty(), "{:?}", remaining);
+}
@@ -134,7 +134,7 @@ fn test_node_shapes() {
 
     match result.unwrap() {
         DiagramType::Flowchart(diagram) => {
-            assert_eq!(diagram.nodes.len(), 8);
+            assert_eq!(diagram.nodes.len(), 9);
 
             // Verify each node shape that was parsed
             assert!(diagram.nodes.contains_key("A"));
@@ -142,9 +142,10 @@ fn test_node_shapes() {
             assert!(diagram.nodes.contains_key("C"));
             assert!(diagram.nodes.contains_key("D"));
             assert!(diagram.nodes.contains_key("E"));
-            // F [(Cylinder)] might not be parsed
+            assert!(diagram.nodes.contains_key("F"));
             assert!(diagram.nodes.contains_key("G"));
             assert!(diagram.nodes.contains_key("H"));
+            assert!(diagram.nodes.contains_key("I"));
 
             // Check basic shapes
             assert_eq!(
@@ -167,6 +168,26 @@ fn test_node_shapes() {
                 diagram.nodes["E"].shape,
                 mermaid_parser::common::ast::NodeShape::Subroutine
             );
+
+            // Slanted and compound shapes
+            assert_eq!(
+                diagram.nodes["F"].shape,
+                mermaid_parser::common::ast::NodeShape::Cylinder
+            );
+            assert_eq!(diagram.nodes["F"].text.as_deref(), Some("Cylinder"));
+            assert_eq!(
+                diagram.nodes["G"].shape,
+                mermaid_parser::common::ast::NodeShape::Parallelogram
+            );
+            assert_eq!(diagram.nodes["G"].text.as_deref(), Some("Parallelogram"));
+            assert_eq!(
+                diagram.nodes["H"].shape,
+                mermaid_parser::common::ast::NodeShape::ParallelogramAlt
+            );
+            assert_eq!(
+                diagram.nodes["I"].shape,
+                mermaid_parser::common::ast::NodeShape::Trapezoid
+            );
         }
         _ => panic!("Expected Flowchart diagram"),
     }
@@ -231,3 +252,444 @@ fn test_accessibility_features() {
         _ => panic!("Expected Flowchart diagram"),
     }
 }
+
+#[test]
+fn test_unicode_node_ids() {
+    let input = "flowchart LR\n    견적서[Quote] --> Кнопка";
+
+    match parse_diagram(input).expect("Failed to parse unicode node IDs") {
+        DiagramType::Flowchart(diagram) => {
+            assert!(diagram.nodes.contains_key("견적서"));
+            assert_eq!(diagram.edges.len(), 1);
+            assert_eq!(diagram.edges[0].from, "견적서");
+            assert_eq!(diagram.edges[0].to, "Кнопка");
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_quoted_labels_with_arrow_sequences() {
+    let input = r#"flowchart TD
+    A["x --> y"] -->|"a --> b"| B("(paren)")"#;
+
+    match parse_diagram(input).expect("Failed to parse quoted labels") {
+        DiagramType::Flowchart(diagram) => {
+            assert_eq!(diagram.nodes["A"].text.as_deref(), Some("x --> y"));
+            assert_eq!(diagram.nodes["B"].text.as_deref(), Some("(paren)"));
+            assert_eq!(diagram.edges.len(), 1);
+            assert_eq!(diagram.edges[0].label.as_deref(), Some("a --> b"));
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_node_class_assignment() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"flowchart TD
+    A:::start --> B[Review]:::pending-review
+    B --> C{OK?}:::decision
+    class A,C highlight"#;
+
+    let diagram = parse_diagram(input).expect("Failed to parse class assignments");
+    match &diagram {
+        DiagramType::Flowchart(flowchart) => {
+            assert_eq!(flowchart.nodes["A"].classes, vec!["start", "highlight"]);
+            assert_eq!(flowchart.nodes["A"].text.as_deref(), Some("A"));
+            assert_eq!(flowchart.nodes["B"].classes, vec!["pending-review"]);
+            assert_eq!(flowchart.nodes["B"].text.as_deref(), Some("Review"));
+            assert_eq!(flowchart.nodes["C"].classes, vec!["decision", "highlight"]);
+            assert_eq!(flowchart.edges.len(), 2);
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("B[Review]:::pending-review"));
+    assert!(printed.contains("class A highlight"));
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_accessibility_statements() {
+    let input = r#"flowchart TD
+    accTitle: Checkout flow
+    accDescr {
+        From cart
+        to payment
+    }
+    A[Cart] --> B[Pay]"#;
+
+    match parse_diagram(input).expect("Failed to parse accessibility statements") {
+        DiagramType::Flowchart(diagram) => {
+            assert_eq!(diagram.title, None);
+            assert_eq!(
+                diagram.accessibility.title.as_deref(),
+                Some("Checkout flow")
+            );
+            assert_eq!(
+                diagram.accessibility.description.as_deref(),
+                Some("From cart to payment")
+            );
+            assert_eq!(diagram.edges.len(), 1);
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_title_is_an_ordinary_node_id() {
+    // Flowcharts take their title from the frontmatter, not from a statement
+    match parse_diagram("flowchart TD\n    title --> B").unwrap() {
+        DiagramType::Flowchart(diagram) => {
+            assert_eq!(diagram.title, None);
+            assert!(diagram.nodes.contains_key("title"));
+            assert_eq!(diagram.edges[0].from, "title");
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_layout_direction_normalizes_td_and_tb() {
+    use mermaid_parser::common::ast::LayoutDirection;
+
+    let direction = |input: &str| parse_diagram(input).unwrap().layout_direction();
+    assert_eq!(
+        direction("flowchart TD\n    A --> B"),
+        Some(LayoutDirection::TopToBottom)
+    );
+    assert_eq!(
+        direction("graph TB\n    A --> B"),
+        direction("flowchart TD\n    A --> B")
+    );
+    assert_eq!(
+        direction("flowchart RL\n    A --> B"),
+        Some(LayoutDirection::RightToLeft)
+    );
+    assert_eq!(direction("pie\n    \"A\" : 1"), None);
+}
+
+#[test]
+fn test_image_and_shape_metadata_nodes() {
+    use mermaid_parser::common::ast::{ImagePosition, NodeShape};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"flowchart TD
+    A@{ img: "https://example.com/logo.png", label: "Logo", pos: "b", w: 60, h: 40, constraint: "on" } --> B
+    B@{ shape: diam, label: "Ok?" }
+    C@{ shape: doc }
+    e1@{ animate: true }
+    click B href "https://example.com/help"
+"#;
+
+    let DiagramType::Flowchart(diagram) = parse_diagram(input).unwrap() else {
+        panic!("Expected Flowchart diagram");
+    };
+
+    let logo = &diagram.nodes["A"];
+    assert_eq!(logo.text.as_deref(), Some("Logo"));
+    assert_eq!(
+        logo.shape,
+        NodeShape::Image {
+            url: "https://example.com/logo.png".to_string(),
+            position: Some(ImagePosition::Bottom),
+            width: Some(60.0),
+            height: Some(40.0),
+            constrained: Some(true),
+        }
+    );
+    assert_eq!(diagram.edges.len(), 1);
+    assert_eq!(diagram.nodes["B"].shape, NodeShape::Rhombus);
+    assert_eq!(diagram.nodes["B"].text.as_deref(), Some("Ok?"));
+    assert_eq!(
+        diagram.nodes["C"].shape,
+        NodeShape::Special("doc".to_string())
+    );
+    assert!(!diagram.nodes.contains_key("e1"));
+    assert_eq!(diagram.urls(), vec!["https://example.com/logo.png"]);
+
+    let DiagramType::Flowchart(reparsed) = parse_diagram(&diagram.to_mermaid()).unwrap() else {
+        panic!("Expected Flowchart diagram");
+    };
+    assert_eq!(reparsed.nodes["A"], diagram.nodes["A"]);
+    assert_eq!(reparsed.nodes["C"], diagram.nodes["C"]);
+}
+
+#[test]
+fn test_shape_metadata_round_trip() {
+    use mermaid_parser::common::ast::NodeShape;
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"flowchart LR
+    A@{ shape: stadium, label: "Start" } --> B@{ shape: cyl, label: "Database" }
+    B --> C@{ shape: odd }
+    C --> D@{ shape: lean-r, label: "Input" }
+    D --> E@{ shape: trap-t, label: "Manual" }
+    E --> F@{ shape: document, label: "Report" }
+    F --> G@{ shape: lightning-bolt }
+    G --> H@{ shape: this-shape-does-not-exist }
+"#;
+
+    let DiagramType::Flowchart(diagram) = parse_diagram(input).unwrap() else {
+        panic!("Expected Flowchart diagram");
+    };
+
+    let shapes = [
+        ("A", NodeShape::Stadium),
+        ("B", NodeShape::Cylinder),
+        ("C", NodeShape::Asymmetric),
+        ("D", NodeShape::Parallelogram),
+        ("E", NodeShape::TrapezoidAlt),
+        ("F", NodeShape::Special("doc".to_string())),
+        ("G", NodeShape::Special("bolt".to_string())),
+        (
+            "H",
+            NodeShape::Special("this-shape-does-not-exist".to_string()),
+        ),
+    ];
+    for (id, shape) in &shapes {
+        assert_eq!(diagram.nodes[*id].shape, *shape, "{}", id);
+    }
+    assert_eq!(diagram.nodes["B"].text.as_deref(), Some("Database"));
+    assert_eq!(diagram.nodes["C"].text.as_deref(), Some("C"));
+    assert_eq!(diagram.edges.len(), 7);
+
+    let output = diagram.to_mermaid();
+    assert!(output.contains("A([Start])"), "{}", output);
+    assert!(output.contains("B[(Database)]"), "{}", output);
+    assert!(output.contains("C>C]"), "{}", output);
+    assert!(output.contains("D[/Input/]"), "{}", output);
+    assert!(output.contains("E[\\Manual/]"), "{}", output);
+    assert!(
+        output.contains("F@{ shape: doc, label: \"Report\" }"),
+        "{}",
+        output
+    );
+
+    let DiagramType::Flowchart(reparsed) = parse_diagram(&output).unwrap() else {
+        panic!("Expected Flowchart diagram");
+    };
+    assert_eq!(reparsed.nodes, diagram.nodes);
+    assert_eq!(reparsed.edges.len(), diagram.edges.len());
+}
+
+#[test]
+fn test_multiline_node_metadata() {
+    let input = "flowchart LR\n    A@{\n        img: https://example.com/a.svg\n        label: Start\n    }\n    A --> B";
+
+    let DiagramType::Flowchart(diagram) = parse_diagram(input).unwrap() else {
+        panic!("Expected Flowchart diagram");
+    };
+    assert_eq!(diagram.nodes["A"].text.as_deref(), Some("Start"));
+    assert_eq!(diagram.urls(), vec!["https://example.com/a.svg"]);
+    assert_eq!(diagram.edges.len(), 1);
+
+    let error = parse_diagram("flowchart LR\n    A --> B\n    B@{ img: x.png, pos: middle }");
+    assert!(matches!(
+        error,
+        Err(mermaid_parser::ParseError::SyntaxError { line: 3, .. })
+    ));
+}
+
+#[test]
+fn test_style_targets_subgraphs_and_nodes() {
+    use mermaid_parser::common::ast::StyleTarget;
+
+    let input = r#"flowchart TD
+    style one fill:#f9f,stroke:#333
+    subgraph one[Inputs]
+        A --> B
+    end
+    style A fill:rgb(1,2,3),stroke-width:4px
+"#;
+
+    match parse_diagram(input).expect("Failed to parse styles") {
+        DiagramType::Flowchart(diagram) => {
+            assert_eq!(diagram.styles.len(), 2);
+            assert_eq!(
+                diagram.styles[0].target,
+                StyleTarget::Subgraph("one".to_string())
+            );
+            assert_eq!(diagram.styles[0].styles["stroke"], "#333");
+            assert_eq!(diagram.styles[1].target, StyleTarget::Node("A".to_string()));
+            assert_eq!(diagram.styles[1].styles["fill"], "rgb(1,2,3)");
+            assert!(!diagram.nodes.contains_key("style"));
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_class_defs_merge_across_subgraphs() {
+    let input = r#"flowchart LR
+    classDef hot fill:#f00,color:#fff
+    subgraph inner
+        classDef hot,cold stroke:#000,fill:#0f0
+        A:::hot --> B:::cold
+    end
+"#;
+
+    match parse_diagram(input).expect("Failed to parse classDef") {
+        DiagramType::Flowchart(diagram) => {
+            let hot = &diagram.class_defs["hot"].styles;
+            assert_eq!(hot["fill"], "#0f0");
+            assert_eq!(hot["color"], "#fff");
+            assert_eq!(hot["stroke"], "#000");
+            assert_eq!(diagram.class_defs["cold"].styles.len(), 2);
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_semicolon_separated_statements_on_one_line() {
+    let input = "graph LR; classDef hot fill:#f00; A[Start] --> B; class A hot; style B color:#fff";
+
+    match parse_diagram(input).expect("Failed to parse minified flowchart") {
+        DiagramType::Flowchart(diagram) => {
+            assert_eq!(diagram.edges.len(), 1);
+            assert_eq!(diagram.nodes["A"].text.as_deref(), Some("Start"));
+            assert_eq!(diagram.class_defs["hot"].styles["fill"], "#f00");
+            assert_eq!(diagram.styles.len(), 1);
+            assert!(!diagram.nodes.contains_key("class"));
+            assert!(!diagram.nodes.contains_key("style"));
+        }
+        _ => panic!("Expected Flowchart diagram"),
+    }
+}
+
+#[test]
+fn test_collapse_subgraphs() {
+    use mermaid_parser::common::ast::{NodeShape, Subgraph};
+    use mermaid_parser::common::collapse::collapse_subgraphs;
+
+    let subgraph = |id: &str, nodes: &[&str], subgraphs: Vec<Subgraph>| Subgraph {
+        id: id.to_string(),
+        title: None,
+        nodes: nodes.iter().map(|node| node.to_string()).collect(),
+        edges: Vec::new(),
+        subgraphs,
+        direction: None,
+    };
+    let input = "flowchart TD\n    Client -->|read| Api\n    Client -->|write| Worker\n    Api --> Db\n    Worker --> Db";
+    let DiagramType::Flowchart(mut detailed) = parse_diagram(input).unwrap() else {
+        panic!("Expected Flowchart diagram");
+    };
+    detailed.subgraphs.push(subgraph(
+        "platform",
+        &[],
+        vec![
+            subgraph("services", &["Api", "Worker"], Vec::new()),
+            subgraph("storage", &["Db"], Vec::new()),
+        ],
+    ));
+    let edges = |diagram: &mermaid_parser::common::ast::FlowchartDiagram| {
+        diagram
+            .edges
+            .iter()
+            .map(|edge| (edge.from.clone(), edge.to.clone(), edge.label.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    let overview = collapse_subgraphs(&detailed, 0);
+    assert!(overview.subgraphs.is_empty());
+    assert!(!overview.nodes.contains_key("Db"));
+    assert_eq!(overview.nodes["platform"].shape, NodeShape::Subroutine);
+    assert_eq!(
+        edges(&overview),
+        [(
+            "Client".to_string(),
+            "platform".to_string(),
+            Some("read, write".to_string())
+        )]
+    );
+
+    let overview = collapse_subgraphs(&detailed, 1);
+    assert_eq!(overview.subgraphs.len(), 1);
+    assert_eq!(overview.subgraphs[0].nodes, ["services", "storage"]);
+    assert!(overview.subgraphs[0].subgraphs.is_empty());
+    assert_eq!(
+        edges(&overview),
+        [
+            (
+                "Client".to_string(),
+                "services".to_string(),
+                Some("read, write".to_string())
+            ),
+            ("services".to_string(), "storage".to_string(), None),
+        ]
+    );
+
+    assert_eq!(collapse_subgraphs(&detailed, 2), detailed);
+}
+
+#[test]
+fn test_edge_heads_on_both_ends() {
+    use mermaid_parser::common::ast::{EdgeHead, EdgeLineStyle, EdgeType};
+    use mermaid_parser::{MermaidPrinter, PrintOptions};
+
+    let input =
+        "flowchart LR\n    A o--o B\n    B x--x C\n    C <--> D\n    D <-.-> E\n    E --x F";
+    let diagram = parse_diagram(input).unwrap();
+    let DiagramType::Flowchart(flowchart) = &diagram else {
+        panic!("Expected Flowchart diagram");
+    };
+
+    let edge_types: Vec<_> = flowchart.edges.iter().map(|e| &e.edge_type).collect();
+    assert_eq!(
+        edge_types,
+        [
+            &EdgeType::MULTI_DIRECTIONAL_CIRCLE,
+            &EdgeType::MULTI_DIRECTIONAL_CROSS,
+            &EdgeType::MULTI_DIRECTIONAL,
+            &EdgeType::DOTTED_MULTI_DIRECTIONAL,
+            &EdgeType::CROSS_EDGE,
+        ]
+    );
+    assert_eq!(flowchart.edges[0].edge_type.left_head, EdgeHead::Circle);
+    assert_eq!(
+        flowchart.edges[3].edge_type.line_style,
+        EdgeLineStyle::Dotted
+    );
+    assert!(!flowchart.edges[4].edge_type.is_bidirectional());
+    assert!(flowchart.nodes.contains_key("A") && flowchart.nodes.contains_key("F"));
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("A o--o B"), "{}", printed);
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+
+    let minified = diagram.to_mermaid_pretty(&PrintOptions {
+        minify: true,
+        ..PrintOptions::default()
+    });
+    assert_eq!(parse_diagram(&minified).unwrap(), diagram, "{}", minified);
+}
+
+#[test]
+fn test_nodes_keep_declaration_order() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input =
+        "flowchart TD\n    Zeta[Last letter]\n    Alpha[First letter]\n    Mid\n    Zeta --> Alpha";
+    let diagram = parse_diagram(input).unwrap();
+    let DiagramType::Flowchart(flowchart) = &diagram else {
+        panic!("Expected Flowchart diagram");
+    };
+
+    let ids: Vec<_> = flowchart.nodes.keys().map(String::as_str).collect();
+    assert_eq!(ids, ["Zeta", "Alpha", "Mid"]);
+    assert_eq!(flowchart.nodes.get_index_of("Mid"), Some(2));
+    assert_eq!(
+        flowchart.nodes["Alpha"].text.as_deref(),
+        Some("First letter")
+    );
+
+    let printed = diagram.to_mermaid();
+    let zeta = printed.find("Zeta").unwrap();
+    let alpha = printed.find("Alpha").unwrap();
+    assert!(zeta < alpha, "{}", printed);
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+}
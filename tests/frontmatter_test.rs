@@ -0,0 +1,152 @@
+use mermaid_parser::common::frontmatter::ConfigValue;
+use mermaid_parser::common::parsing::frontmatter;
+use mermaid_parser::parsers;
+use mermaid_parser::{parse_diagram, parse_document, DiagramType, MermaidPrinter};
+
+#[test]
+fn test_flowchart_config_block() {
+    let input = r#"---
+title: Checkout
+config:
+  theme: forest
+  flowchart:
+    nodeSpacing: 50
+    rankSpacing: 80
+    curve: basis
+    htmlLabels: false
+    arrowMarkerAbsolute: true
+---
+flowchart LR
+    A[Cart] --> B[Pay]"#;
+
+    let document = parse_document(input).unwrap();
+    assert!(matches!(document.diagram, DiagramType::Flowchart(_)));
+
+    let frontmatter = document.frontmatter.unwrap();
+    assert_eq!(frontmatter.title.as_deref(), Some("Checkout"));
+    assert_eq!(frontmatter.config.theme.as_deref(), Some("forest"));
+
+    let flowchart = frontmatter.config.flowchart.unwrap();
+    assert_eq!(flowchart.node_spacing, Some(50.0));
+    assert_eq!(flowchart.rank_spacing, Some(80.0));
+    assert_eq!(flowchart.curve.as_deref(), Some("basis"));
+    assert_eq!(flowchart.html_labels, Some(false));
+    assert_eq!(
+        flowchart.extra.get("arrowMarkerAbsolute"),
+        Some(&ConfigValue::Bool(true))
+    );
+}
+
+#[test]
+fn test_gantt_flow_mapping_and_unknown_sections() {
+    let input = "---\nconfig:\n  gantt: { barHeight: 20, axisFormat: '%m-%d', topAxis: true }\n  pie: { textPosition: 0.5 }\n---\ngantt\n    dateFormat YYYY-MM-DD\n    section A\n    Task :a1, 2024-01-01, 3d";
+
+    let document = parse_document(input).unwrap();
+    assert!(matches!(document.diagram, DiagramType::Gantt(_)));
+
+    let config = document.frontmatter.unwrap().config;
+    let gantt = config.gantt.unwrap();
+    assert_eq!(gantt.bar_height, Some(20.0));
+    assert_eq!(gantt.axis_format.as_deref(), Some("%m-%d"));
+    assert_eq!(gantt.top_axis, Some(true));
+    assert!(config.flowchart.is_none());
+    assert!(matches!(config.extra.get("pie"), Some(ConfigValue::Map(_))));
+}
+
+#[test]
+fn test_parse_diagram_skips_frontmatter() {
+    let input = "---\ntitle: Pets\n---\npie\n    \"Dogs\" : 3";
+    assert!(matches!(parse_diagram(input), Ok(DiagramType::Pie(_))));
+
+    let document = parse_document("pie\n    \"Dogs\" : 3").unwrap();
+    assert!(document.frontmatter.is_none());
+}
+
+#[test]
+fn test_document_round_trip() {
+    let input = "---\ntitle: Flow\nconfig:\n  flowchart: { nodeSpacing: 30 }\n---\nflowchart TD\n    A[One] --> B[Two]";
+    let document = parse_document(input).unwrap();
+
+    let printed = document.to_mermaid();
+    assert!(
+        printed.starts_with("---\nconfig:\n  flowchart:\n    nodeSpacing: 30\ntitle: Flow\n---\n")
+    );
+    assert_eq!(parse_document(&printed).unwrap(), document);
+}
+
+#[test]
+fn test_invalid_frontmatter_is_a_syntax_error() {
+    let input = "---\nconfig:\n  flowchart: { nodeSpacing: 50\n---\nflowchart TD\n    A --> B";
+    assert!(parse_document(input).is_err());
+}
+
+#[test]
+fn test_parsers_accept_frontmatter() {
+    let header = "---\ntitle: Pets\nconfig:\n  theme: dark\n---\n";
+    let pie = format!("{}pie\n    \"Dogs\" : 386", header);
+    assert_eq!(
+        parsers::pie::parse(&pie).unwrap(),
+        parsers::pie::parse("pie\n    \"Dogs\" : 386").unwrap()
+    );
+    let sankey = format!("{}sankey-beta\nA,B,10", header);
+    assert_eq!(parsers::sankey::parse(&sankey).unwrap().links.len(), 1);
+    let timeline = format!("{}timeline\n    2024 : Launch", header);
+    assert!(parsers::timeline::parse(&timeline).is_ok());
+
+    let (metadata, body) = frontmatter::extract(&pie).unwrap();
+    assert_eq!(metadata.title.as_deref(), Some("Pets"));
+    assert_eq!(metadata.config.theme.as_deref(), Some("dark"));
+    assert!(body.starts_with("pie"));
+    assert_eq!(frontmatter::extract("pie").unwrap().0, Default::default());
+}
+
+#[test]
+fn test_document_metadata() {
+    // The frontmatter title stands in for a diagram without one
+    let document = parse_document("---\ntitle: Flows\n---\nsankey-beta\nA,B,10").unwrap();
+    let metadata = document.metadata();
+    assert_eq!(metadata.title.as_deref(), Some("Flows"));
+    assert_eq!(document.diagram.title(), None);
+
+    let document = parse_document(
+        "---\ntitle: Outer\nconfig:\n  look: handDrawn\n---\npie title Inner\n    accTitle: Pets\n    \"A\" : 1",
+    )
+    .unwrap();
+    let metadata = document.metadata();
+    assert_eq!(metadata.title.as_deref(), Some("Inner"));
+    assert_eq!(metadata.accessibility.title.as_deref(), Some("Pets"));
+    assert_eq!(metadata.config.look.as_deref(), Some("handDrawn"));
+}
+
+#[test]
+fn test_init_directives() {
+    use mermaid_parser::common::parsing::directives;
+
+    let input = "%%{init: {\"theme\": \"dark\", \"flowchart\": {\"curve\": \"basis\"}}}%%\n%%{initialize: {'look': 'handDrawn'}}%%\n%%{wrap}%%\nflowchart TD\n    A --> B";
+    let diagram = parse_diagram(input).unwrap();
+    assert!(matches!(diagram, DiagramType::Flowchart(_)));
+
+    let document = parse_document(input).unwrap();
+    let init = document.init.clone().unwrap();
+    assert_eq!(init.config.theme.as_deref(), Some("dark"));
+    assert_eq!(init.config.look.as_deref(), Some("handDrawn"));
+    assert_eq!(
+        init.config.flowchart.unwrap().curve.as_deref(),
+        Some("basis")
+    );
+    assert_eq!(document.metadata().init, document.init);
+
+    // The directive is printed again, and read back
+    let printed = document.to_mermaid();
+    assert!(printed.starts_with("%%{init: "), "{}", printed);
+    assert_eq!(parse_document(&printed).unwrap(), document);
+
+    assert_eq!(
+        directives::split("%%{init: {\"theme\": \"dark\"}}%%"),
+        Some(("init", Some("{\"theme\": \"dark\"}")))
+    );
+    assert_eq!(directives::split("  %%{wrap}%%"), Some(("wrap", None)));
+    assert_eq!(directives::split("%% comment"), None);
+    assert_eq!(directives::extract("pie\n    \"A\" : 1").unwrap(), None);
+    assert!(directives::extract("%%{init: dark}%%\npie").is_err());
+}
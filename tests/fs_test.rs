@@ -0,0 +1,94 @@
+#![cfg(feature = "fs")]
+
+mod common;
+
+use mermaid_parser::fs::{self, DiagramFile};
+use mermaid_parser::stable::DiagramKind;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_glob_and_load_detect_each_file() {
+    let root = common::temp_tree(
+        "load",
+        &[
+            ("flow.mmd", "// Type: flowchart\nflowchart TD\n    A --> B"),
+            ("nested/deeper/pie.mmd", "pie\n    \"A\" : 1"),
+            ("nested/seq.mermaid", "sequenceDiagram\n    A->>B: Hi"),
+            (
+                "nested/front.mmd",
+                "---\ntitle: T\n---\nstateDiagram-v2\n    [*] --> A",
+            ),
+            ("nested/empty.mmd", "// Source: nowhere\n"),
+            ("nested/notes.txt", "pie"),
+            (".hidden/skip.mmd", "pie"),
+        ],
+    );
+
+    let names = |paths: &[PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| {
+                path.strip_prefix(&root)
+                    .unwrap()
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .collect()
+    };
+
+    let matched = fs::glob(root.join("**").join("*.mmd")).unwrap();
+    assert_eq!(
+        names(&matched),
+        vec![
+            "flow.mmd",
+            "nested/deeper/pie.mmd",
+            "nested/empty.mmd",
+            "nested/front.mmd"
+        ]
+    );
+    assert_eq!(
+        names(&fs::glob(root.join("nested").join("s?q.*")).unwrap()),
+        vec!["nested/seq.mermaid"]
+    );
+    assert_eq!(fs::find_diagrams(&root).unwrap().len(), 5);
+
+    let files = fs::load_all(root.join("**").join("*.m*")).unwrap();
+    let kinds: Vec<Option<DiagramKind>> = files.iter().map(|file| file.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Some(DiagramKind::Flowchart),
+            Some(DiagramKind::Pie),
+            None,
+            Some(DiagramKind::State),
+            Some(DiagramKind::Sequence),
+        ]
+    );
+    assert_eq!(files[0].source, "flowchart TD\n    A --> B");
+    assert!(files[3].parse().is_ok());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_corpus_files_detect_their_directory_kind() {
+    let dir = Path::new("test").join("pie");
+    let files = fs::find_diagrams(&dir).unwrap();
+    assert!(!files.is_empty());
+
+    for path in files {
+        let file = DiagramFile::load(&path).unwrap();
+        let header = file.source.split_whitespace().next();
+        if header == Some("pie") {
+            assert_eq!(file.kind, Some(DiagramKind::Pie), "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn test_missing_directory_is_an_io_error() {
+    let error = fs::find_diagrams("does/not/exist").unwrap_err();
+    assert!(matches!(error, mermaid_parser::ParseError::IoError(_)));
+}
@@ -197,3 +197,134 @@ fn test_gantt_weekday_settings() {
         _ => panic!("Expected Gantt diagram"),
     }
 }
+
+#[test]
+fn test_gantt_calendar_export() {
+    let input = r#"gantt
+    title Release, v2
+    dateFormat YYYY-MM-DD
+    section Build
+        Design           :a1, 2024-02-26, 4d
+        Code             :b1, after a1, 1w
+    section Ship
+        Review, then fix :2d
+        Launch           :milestone, after b1, 0d
+"#;
+
+    let diagram = match parse_diagram(input).unwrap() {
+        mermaid_parser::DiagramType::Gantt(diagram) => diagram,
+        _ => panic!("Expected Gantt diagram"),
+    };
+
+    assert_eq!(
+        diagram.to_csv(),
+        "task,section,start,end,dependencies\n\
+         Design,Build,2024-02-26,2024-03-01,\n\
+         Code,Build,2024-03-01,2024-03-08,a1\n\
+         \"Review, then fix\",Ship,2024-03-08,2024-03-10,\n\
+         Launch,Ship,2024-03-08,2024-03-08,b1\n"
+    );
+
+    let ical = diagram.to_icalendar();
+    assert!(ical.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ical.contains("X-WR-CALNAME:Release\\, v2\r\n"));
+    assert_eq!(ical.matches("BEGIN:VEVENT").count(), 4);
+    assert!(ical.contains("UID:b1@mermaid-parser\r\nDTSTAMP:20240301T000000Z\r\nDTSTART;VALUE=DATE:20240301\r\nDTEND;VALUE=DATE:20240308\r\n"));
+    assert!(ical.contains("SUMMARY:Review\\, then fix\r\n"));
+    assert!(ical.ends_with("END:VCALENDAR\r\n"));
+}
+
+#[test]
+fn test_gantt_resolve_schedule() {
+    use mermaid_parser::common::ast::GanttDiagram;
+    use mermaid_parser::common::gantt::{resolve_schedule, DateTime};
+
+    let gantt = |input: &str| match parse_diagram(input).unwrap() {
+        mermaid_parser::DiagramType::Gantt(diagram) => diagram,
+        _ => panic!("Expected Gantt diagram"),
+    };
+    let times = |diagram: GanttDiagram| {
+        let time = |time: Option<DateTime>| time.map(|time| time.to_string());
+        resolve_schedule(&diagram)
+            .iter()
+            .map(|task| (task.name.clone(), time(task.start), time(task.end)))
+            .collect::<Vec<_>>()
+    };
+    let task = |name: &str, start: &str, end: &str| {
+        (
+            name.to_string(),
+            Some(start.to_string()),
+            Some(end.to_string()),
+        )
+    };
+
+    // Times of day, a forward `after` reference, `until`, and excluded days
+    let diagram = gantt(
+        r#"gantt
+    dateFormat YYYY-MM-DD HH:mm
+    excludes weekends, 2024-03-12
+    includes 2024-03-09
+    section Plan
+        Kickoff :k1, 2024-03-08 09:00, 2h
+        Review  :r1, after d1, 1d
+        Design  :d1, after k1, 3d
+        Docs    :o1, 2024-03-11 08:00, until r1
+"#,
+    );
+    assert_eq!(
+        times(diagram),
+        vec![
+            task("Kickoff", "2024-03-08T09:00:00", "2024-03-08T11:00:00"),
+            task("Review", "2024-03-13T11:00:00", "2024-03-14T11:00:00"),
+            task("Design", "2024-03-08T11:00:00", "2024-03-13T11:00:00"),
+            task("Docs", "2024-03-11T08:00:00", "2024-03-13T11:00:00"),
+        ]
+    );
+
+    // Inclusive end dates, and a Friday to Saturday weekend
+    let diagram = gantt(
+        "gantt\n    inclusiveEndDates\n    excludes weekends\n    weekend friday\n    section A\n        Fixed :a, 2024-01-01, 2024-01-03\n        Flexible :2024-03-07, 2d",
+    );
+    assert_eq!(
+        times(diagram),
+        vec![
+            task("Fixed", "2024-01-01T00:00:00", "2024-01-04T00:00:00"),
+            task("Flexible", "2024-03-07T00:00:00", "2024-03-11T00:00:00"),
+        ]
+    );
+}
+
+#[test]
+fn test_gantt_display_mode() {
+    use mermaid_parser::common::validation::{DiagramValidator, GanttValidator};
+    use mermaid_parser::MermaidPrinter;
+
+    let gantt = |input: &str| match parse_diagram(input).unwrap() {
+        mermaid_parser::DiagramType::Gantt(diagram) => diagram,
+        _ => panic!("Expected Gantt diagram"),
+    };
+
+    let diagram =
+        gantt("gantt\n    displayMode compact\n    section A\n        Task :a1, 2024-01-01, 3d");
+    assert_eq!(diagram.display_mode.as_deref(), Some("compact"));
+    assert_eq!(diagram.sections[0].tasks.len(), 1);
+    assert!(GanttValidator::new().validate(&diagram).is_ok());
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("displayMode compact"), "{}", printed);
+    assert_eq!(gantt(&printed).display_mode, diagram.display_mode);
+
+    // The frontmatter config applies when the chart has no statement of its own
+    let frontmatter = "---\nconfig:\n  gantt:\n    displayMode: compact\n---\ngantt\n    section A\n        Task :a1, 2024-01-01, 3d";
+    assert_eq!(gantt(frontmatter).display_mode.as_deref(), Some("compact"));
+    let overridden = frontmatter.replace(
+        "gantt\n    section",
+        "gantt\n    displayMode wide\n    section",
+    );
+    let diagram = gantt(&overridden);
+    assert_eq!(diagram.display_mode.as_deref(), Some("wide"));
+
+    let errors = GanttValidator::new().validate(&diagram).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].rule, "unknown_gantt_display_mode");
+}
@@ -235,3 +235,61 @@ fn test_empty_git_graph() {
     let diagram = result.unwrap();
     assert_eq!(diagram.branches.len(), 1); // Should have default main branch
 }
+
+#[test]
+fn test_switch_and_explicit_normal_type() {
+    use mermaid_parser::common::ast::{CheckoutKeyword, CommitType, GitOperation};
+    use mermaid_parser::{MermaidPrinter, PrintOptions};
+
+    let switched = r#"gitGraph
+    commit type: NORMAL
+    branch develop
+    switch develop
+    commit
+    checkout main
+    merge develop type: NORMAL"#;
+    let plain = r#"gitGraph
+    commit
+    branch develop
+    checkout develop
+    commit
+    checkout main
+    merge develop"#;
+
+    let diagram = git::parse(switched).unwrap();
+    assert!(matches!(
+        &diagram.operations[2],
+        GitOperation::Checkout { branch, keyword: CheckoutKeyword::Switch } if branch == "develop"
+    ));
+    assert!(matches!(
+        diagram.operations[0],
+        GitOperation::Commit {
+            commit_type: CommitType::Normal,
+            explicit_type: true,
+            ..
+        }
+    ));
+    // Both spellings give the same normalized stream
+    let plain = git::parse(plain).unwrap();
+    assert_ne!(diagram.operations, plain.operations);
+    assert_eq!(
+        diagram.normalized_operations(),
+        plain.normalized_operations()
+    );
+
+    // The author's spelling is printed unless asked otherwise
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("commit type: NORMAL"));
+    assert!(printed.contains("switch develop"));
+    assert!(printed.contains("merge develop type: NORMAL"));
+    assert_eq!(git::parse(&printed).unwrap(), diagram);
+
+    let options = PrintOptions {
+        normalize_keywords: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        diagram.to_mermaid_pretty(&options),
+        plain.to_mermaid_pretty(&options)
+    );
+}
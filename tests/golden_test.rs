@@ -0,0 +1,45 @@
+mod common;
+
+use mermaid_parser::testing::golden;
+
+#[test]
+fn test_pretty_print_golden_files() {
+    golden::check_or_update("tests/golden")
+        .unwrap()
+        .assert_clean();
+}
+
+fn temp_golden_dir(name: &str) -> std::path::PathBuf {
+    common::temp_tree(name, &[("pie.mermaid", "pie\n    \"A\" : 1\n")])
+}
+
+#[test]
+fn test_missing_golden_is_mismatch_then_update_fixes_it() {
+    let dir = temp_golden_dir("golden_update");
+
+    let report = golden::run(&dir, false).unwrap();
+    assert_eq!(report.mismatches.len(), 1);
+    assert!(report.mismatches[0].expected.is_none());
+
+    let report = golden::run(&dir, true).unwrap();
+    assert_eq!(report.updated, vec![dir.join("pie.golden")]);
+
+    let report = golden::run(&dir, false).unwrap();
+    assert!(report.is_clean());
+    assert_eq!(report.passed, 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_mismatch_reports_first_differing_line() {
+    let dir = temp_golden_dir("golden_mismatch");
+    std::fs::write(dir.join("pie.golden"), "pie\n    \"B\" : 1\n").unwrap();
+
+    let report = golden::run(&dir, false).unwrap();
+    let message = report.mismatches[0].to_string();
+    assert!(message.contains("pie.golden:2"), "{}", message);
+    assert!(message.contains("expected:     \"B\" : 1"), "{}", message);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
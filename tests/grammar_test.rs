@@ -0,0 +1,47 @@
+use mermaid_parser::grammar::{self, Expr};
+use mermaid_parser::parse_diagram;
+use mermaid_parser::stable::DiagramKind;
+
+#[test]
+fn test_grammar_rules_are_defined() {
+    for grammar in grammar::all() {
+        assert!(
+            grammar.undefined_rules().is_empty(),
+            "{:?} references undefined rules {:?}",
+            grammar.kind,
+            grammar.undefined_rules()
+        );
+    }
+}
+
+#[test]
+fn test_grammar_examples_parse() {
+    for grammar in grammar::all() {
+        let example = grammar.example();
+        let diagram = parse_diagram(&example).unwrap_or_else(|e| {
+            panic!(
+                "{:?} example failed to parse: {:?}\n{}",
+                grammar.kind, e, example
+            )
+        });
+        assert_eq!(DiagramKind::from(&diagram), grammar.kind, "{}", example);
+    }
+}
+
+#[test]
+fn test_flowchart_grammar_uses_parser_tables() {
+    let flowchart = grammar::for_kind(DiagramKind::Flowchart).unwrap();
+    let summary = mermaid_parser::parsers::flowchart::syntax_summary();
+
+    let Expr::Choice(shapes) = &flowchart.production("shape").unwrap().expr else {
+        panic!("shape should be a choice");
+    };
+    assert_eq!(shapes.len(), summary.shapes.len());
+
+    let ebnf = flowchart.to_ebnf();
+    assert!(ebnf.contains("header     ::= ( 'flowchart' | 'graph' )"));
+    for direction in summary.directions {
+        assert!(ebnf.contains(&format!("'{}'", direction)));
+    }
+    assert!(grammar::for_kind(DiagramKind::Gantt).is_none());
+}
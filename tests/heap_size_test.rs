@@ -0,0 +1,43 @@
+use mermaid_parser::common::ast::{AccessibilityInfo, PieDiagram, PieSlice};
+use mermaid_parser::common::heap_size::HeapSize;
+use mermaid_parser::{parse_diagram, DiagramType};
+
+#[test]
+fn test_heap_size_counts_strings_and_collections() {
+    let mut data = Vec::with_capacity(4);
+    data.push(PieSlice {
+        label: String::with_capacity(10),
+        value: 3.0,
+    });
+    let pie = PieDiagram {
+        title: Some(String::with_capacity(7)),
+        accessibility: AccessibilityInfo::default(),
+        show_data: false,
+        data,
+    };
+
+    let expected = 7 + 4 * std::mem::size_of::<PieSlice>() + 10;
+    assert_eq!(pie.heap_size(), expected);
+    assert_eq!(DiagramType::Pie(pie).estimated_heap_size(), expected);
+}
+
+#[test]
+fn test_heap_size_grows_with_the_diagram() {
+    let inputs = [
+        (
+            "flowchart TD\n    A --> B",
+            "flowchart TD\n    A[Start] --> B[Middle]\n    B --> C[End]",
+        ),
+        (
+            "sequenceDiagram\n    Alice->>Bob: Hi",
+            "sequenceDiagram\n    Alice->>Bob: Hi\n    loop Every minute\n        Bob->>Alice: Ping\n    end",
+        ),
+        ("mindmap\n  root\n    A", "mindmap\n  root\n    A\n      A1\n    B"),
+    ];
+    for (small, large) in inputs {
+        let small_size = parse_diagram(small).unwrap().estimated_heap_size();
+        let large_size = parse_diagram(large).unwrap().estimated_heap_size();
+        assert!(small_size > 0, "{}", small);
+        assert!(large_size > small_size, "{}", large);
+    }
+}
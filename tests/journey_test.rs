@@ -1,4 +1,5 @@
 use mermaid_parser::parsers::journey;
+use mermaid_parser::MermaidPrinter;
 use rstest::*;
 use std::path::PathBuf;
 
@@ -196,3 +197,106 @@ fn test_task_score_edge_cases() {
     assert_eq!(tasks[1].score, -1);
     assert_eq!(tasks[2].score, 10);
 }
+
+#[test]
+fn test_journey_from_csv() {
+    let csv = r#"section,task,score,actors
+Go to work,Make tea,5,Me
+Go to work,"Do work, then more",1,"Me, Cat"
+Go home,Sit down,3,Me;Cat
+"#;
+
+    let diagram = journey::from_csv(csv).unwrap();
+    assert_eq!(diagram.title, None);
+    assert_eq!(diagram.sections.len(), 2);
+    assert_eq!(diagram.sections[0].name, "Go to work");
+    assert_eq!(diagram.sections[0].tasks.len(), 2);
+
+    let task = &diagram.sections[0].tasks[1];
+    assert_eq!(task.name, "Do work, then more");
+    assert_eq!(task.score, 1);
+    assert_eq!(task.actors, vec!["Me", "Cat"]);
+    assert_eq!(diagram.sections[1].tasks[0].actors, vec!["Me", "Cat"]);
+
+    let error = journey::from_csv("Go home,Sit down,high,Me").unwrap_err();
+    assert!(matches!(
+        error,
+        mermaid_parser::ParseError::SyntaxError { line: 1, .. }
+    ));
+}
+
+#[test]
+fn test_journey_csv_round_trip() {
+    let input = r#"journey
+    title My working day
+    section Go to work
+        Make tea: 5: Me
+        Do work: 1: Me, Cat
+    section Go home
+        Sit down: 3: Me
+"#;
+
+    let diagram = journey::parse(input).unwrap();
+    let csv = journey::to_csv(&diagram);
+    assert_eq!(
+        csv,
+        "section,task,score,actors\n\
+         Go to work,Make tea,5,Me\n\
+         Go to work,Do work,1,\"Me, Cat\"\n\
+         Go home,Sit down,3,Me\n"
+    );
+
+    let imported = journey::from_csv(&csv).unwrap();
+    assert_eq!(imported.sections, diagram.sections);
+
+    let mermaid = mermaid_parser::DiagramType::Journey(imported).to_mermaid();
+    let reparsed = journey::parse(&mermaid).unwrap();
+    assert_eq!(reparsed.sections, diagram.sections);
+}
+
+#[test]
+fn test_journey_summary() {
+    let input = r#"journey
+    title My working day
+    section Go to work
+        Make tea: 5: Me
+        Go upstairs: 3: Me
+        Do work: 1: Me, Cat
+    section Go home
+        Go downstairs: 5: Me
+        Sit down: 1: Cat
+    section Empty
+"#;
+
+    let diagram = journey::parse(input).unwrap();
+    let summary = diagram.summary();
+
+    assert_eq!(summary.overall, Some(3.0));
+    assert_eq!(summary.sections.len(), 2);
+    assert_eq!(summary.sections[0].name, "Go to work");
+    assert_eq!(summary.sections[0].average, 3.0);
+    assert_eq!(summary.sections[0].task_count, 3);
+    assert_eq!(summary.sections[1].average, 3.0);
+
+    let actors: Vec<_> = summary
+        .actors
+        .iter()
+        .map(|a| (a.name.as_str(), a.average, a.task_count))
+        .collect();
+    assert_eq!(actors, vec![("Me", 3.5, 4), ("Cat", 1.0, 2)]);
+
+    let lowest: Vec<_> = summary
+        .lowest_tasks
+        .iter()
+        .map(|t| (t.section.as_str(), t.name.as_str(), t.score))
+        .collect();
+    assert_eq!(
+        lowest,
+        vec![("Go to work", "Do work", 1), ("Go home", "Sit down", 1)]
+    );
+
+    let empty = journey::parse("journey\n    title Nothing yet\n").unwrap();
+    let summary = empty.summary();
+    assert_eq!(summary.overall, None);
+    assert!(summary.sections.is_empty() && summary.lowest_tasks.is_empty());
+}
@@ -2,6 +2,7 @@
 
 use mermaid_parser::common::ast::*;
 use mermaid_parser::common::metrics::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use std::collections::HashMap;
 
 #[test]
@@ -32,6 +33,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
             to: "Bob".to_string(),
             text: "Start process".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         }),
         SequenceStatement::Loop(Loop {
             condition: "while active".to_string(),
@@ -42,6 +44,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                     to: "Charlie".to_string(),
                     text: "Success case".to_string(),
                     arrow_type: ArrowType::SolidOpen,
+                    activation: None,
                 })],
                 else_branch: Some(ElseBranch {
                     condition: Some("else".to_string()),
@@ -50,6 +53,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                         to: "Alice".to_string(),
                         text: "Error case".to_string(),
                         arrow_type: ArrowType::SolidOpen,
+                        activation: None,
                     })],
                 }),
             })],
@@ -61,6 +65,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                 to: "Alice".to_string(),
                 text: "Cleanup".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             })],
         }),
         SequenceStatement::Par(Parallel {
@@ -72,6 +77,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                         to: "Bob".to_string(),
                         text: "Parallel 1".to_string(),
                         arrow_type: ArrowType::SolidOpen,
+                        activation: None,
                     })],
                 },
                 ParallelBranch {
@@ -81,6 +87,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                         to: "Charlie".to_string(),
                         text: "Parallel 2".to_string(),
                         arrow_type: ArrowType::SolidOpen,
+                        activation: None,
                     })],
                 },
             ],
@@ -92,6 +99,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                 to: "Charlie".to_string(),
                 text: "Critical operation".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             })],
             options: vec![
                 CriticalOption {
@@ -101,6 +109,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
                         to: "Alice".to_string(),
                         text: "Option 1 response".to_string(),
                         arrow_type: ArrowType::SolidOpen,
+                        activation: None,
                     })],
                 },
                 CriticalOption {
@@ -111,12 +120,14 @@ fn test_comprehensive_sequence_diagram_metrics() {
                             to: "Bob".to_string(),
                             text: "Option 2a".to_string(),
                             arrow_type: ArrowType::SolidOpen,
+                            activation: None,
                         }),
                         SequenceStatement::Message(Message {
                             from: "Bob".to_string(),
                             to: "Charlie".to_string(),
                             text: "Option 2b".to_string(),
                             arrow_type: ArrowType::SolidOpen,
+                            activation: None,
                         }),
                     ],
                 },
@@ -143,6 +154,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
         accessibility: AccessibilityInfo::default(),
         participants,
         statements,
+        boxes: vec![],
         autonumber: Some(AutoNumber {
             start: Some(1),
             step: Some(1),
@@ -175,7 +187,7 @@ fn test_comprehensive_sequence_diagram_metrics() {
 
 #[test]
 fn test_complex_flowchart_metrics() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     for i in 1..=15 {
         nodes.insert(
             format!("node{}", i),
@@ -194,7 +206,7 @@ fn test_complex_flowchart_metrics() {
         edges.push(FlowEdge {
             from: format!("node{}", i),
             to: format!("node{}", i + 1),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         });
@@ -203,14 +215,14 @@ fn test_complex_flowchart_metrics() {
     edges.push(FlowEdge {
         from: "node5".to_string(),
         to: "node10".to_string(),
-        edge_type: EdgeType::Arrow,
+        edge_type: EdgeType::ARROW,
         label: Some("branch".to_string()),
         min_length: None,
     });
     edges.push(FlowEdge {
         from: "node8".to_string(),
         to: "node3".to_string(),
-        edge_type: EdgeType::DottedArrow,
+        edge_type: EdgeType::DOTTED_ARROW,
         label: Some("loop back".to_string()),
         min_length: None,
     });
@@ -272,7 +284,7 @@ fn test_complex_flowchart_metrics() {
 
 #[test]
 fn test_complex_class_diagram_metrics() {
-    let mut classes = HashMap::new();
+    let mut classes = OrderedMap::new();
 
     // Create a complex class hierarchy
     for i in 1..=10 {
@@ -283,6 +295,7 @@ fn test_complex_class_diagram_metrics() {
                 visibility: Visibility::Private,
                 is_static: false,
                 default_value: None,
+                generics: vec![],
             }),
             ClassMember::Method(Method {
                 name: format!("method{}", i),
@@ -291,6 +304,7 @@ fn test_complex_class_diagram_metrics() {
                 return_type: Some("void".to_string()),
                 is_static: false,
                 is_abstract: false,
+                generics: vec![],
             }),
         ];
 
@@ -306,6 +320,7 @@ fn test_complex_class_diagram_metrics() {
                 members,
                 annotations: vec![format!("@Component{}", i)],
                 css_class: Some(format!("class-style-{}", i)),
+                generics: vec![],
             },
         );
     }
@@ -330,6 +345,7 @@ fn test_complex_class_diagram_metrics() {
     let diagram = ClassDiagram {
         title: Some("Complex Class System".to_string()),
         accessibility: AccessibilityInfo::default(),
+        direction: None,
         classes,
         relationships,
         notes: vec![
@@ -344,6 +360,9 @@ fn test_complex_class_diagram_metrics() {
                 text: "Important class".to_string(),
             },
         ],
+        namespaces: HashMap::new(),
+        styles: vec![],
+        clicks: vec![],
     };
 
     let metrics = diagram.calculate_metrics();
@@ -372,6 +391,7 @@ fn test_large_sankey_diagram_metrics() {
         nodes.push(SankeyNode {
             id: format!("node{}", i),
             name: format!("Node {}", i),
+            metadata: Default::default(),
         });
     }
 
@@ -464,16 +484,22 @@ fn test_metrics_report_display_with_suggestions() {
                 category: SuggestionCategory::Complexity,
                 message: "High complexity detected".to_string(),
                 severity: SeverityLevel::Error,
+                target: None,
+                span: None,
             },
             Suggestion {
                 category: SuggestionCategory::Organization,
                 message: "Consider restructuring".to_string(),
                 severity: SeverityLevel::Warning,
+                target: None,
+                span: None,
             },
             Suggestion {
                 category: SuggestionCategory::Structure,
                 message: "Simplify relationships".to_string(),
                 severity: SeverityLevel::Info,
+                target: None,
+                span: None,
             },
         ],
     };
@@ -486,6 +512,33 @@ fn test_metrics_report_display_with_suggestions() {
     assert!(output.contains("Complexity: 35 (High)"));
 }
 
+#[test]
+fn test_custom_suggestions_render_with_report() {
+    use mermaid_parser::common::path::{AstPath, ElementKind};
+    use mermaid_parser::common::tokens::Span;
+
+    let diagram = mermaid_parser::parse_diagram("flowchart TD\n    A --> B").unwrap();
+    let mut report = diagram.calculate_metrics();
+    report.suggestions.push(
+        Suggestion::new(
+            SuggestionCategory::Custom("naming".to_string()),
+            "Use descriptive node ids",
+            SeverityLevel::Info,
+        )
+        .with_target(AstPath::new(ElementKind::Node, "A"))
+        .with_span(Span::new(17, 18, 2, 5)),
+    );
+    report.suggestions.push(Suggestion::new(
+        SuggestionCategory::Naming,
+        "Add a title",
+        SeverityLevel::Warning,
+    ));
+
+    let output = report.to_string();
+    assert!(output.contains("- ℹ️ [naming] node/A at 2:5: Use descriptive node ids"));
+    assert!(output.contains("- ⚠️ [Naming]: Add a title"));
+}
+
 #[test]
 fn test_diagram_type_metrics_coverage() {
     // Test various diagram types that should use generic metrics
@@ -564,6 +617,8 @@ fn test_all_severity_levels_and_categories() {
                 category: category.clone(),
                 message: format!("Test {} {:?}", category, severity),
                 severity: severity.clone(),
+                target: None,
+                span: None,
             };
 
             // Verify that suggestion was created with correct severity and category
@@ -595,22 +650,27 @@ fn test_edge_case_empty_diagrams() {
         accessibility: AccessibilityInfo::default(),
         participants: vec![],
         statements: vec![],
+        boxes: vec![],
         autonumber: None,
     };
 
     let empty_class = ClassDiagram {
         title: None,
         accessibility: AccessibilityInfo::default(),
-        classes: HashMap::new(),
+        direction: None,
+        classes: OrderedMap::new(),
         relationships: vec![],
         notes: vec![],
+        namespaces: HashMap::new(),
+        styles: vec![],
+        clicks: vec![],
     };
 
     let empty_flowchart = FlowchartDiagram {
         title: None,
         accessibility: AccessibilityInfo::default(),
         direction: FlowDirection::TD,
-        nodes: HashMap::new(),
+        nodes: OrderedMap::new(),
         edges: vec![],
         subgraphs: vec![],
         styles: vec![],
@@ -643,3 +703,52 @@ fn test_edge_case_empty_diagrams() {
         );
     }
 }
+
+#[test]
+fn test_hierarchy_metrics_and_suggestions() {
+    let leaf = |name: &str| TreemapNode {
+        name: name.to_string(),
+        value: Some(1.0),
+        children: vec![],
+    };
+    let wide = TreemapNode {
+        name: "Wide".to_string(),
+        value: None,
+        children: (0..47).map(|i| leaf(&format!("Item {}", i))).collect(),
+    };
+    let deep = TreemapNode {
+        name: "Deep".to_string(),
+        value: None,
+        children: vec![TreemapNode {
+            name: "Deeper".to_string(),
+            value: None,
+            children: vec![leaf("Deepest")],
+        }],
+    };
+    let diagram = TreemapDiagram {
+        title: None,
+        accessibility: AccessibilityInfo::default(),
+        root: TreemapNode {
+            name: "Root".to_string(),
+            value: None,
+            children: vec![wide, deep],
+        },
+    };
+
+    let hierarchy = HierarchyMetrics::from(&diagram);
+    assert_eq!(hierarchy.depth_distribution, vec![1, 2, 48, 1]);
+    assert_eq!(hierarchy.max_children, vec![2, 47, 1, 0]);
+    assert_eq!(hierarchy.branching_factor, vec![2.0, 24.0, 1.0, 0.0]);
+    assert_eq!(hierarchy.balance, 3.0 / 4.0);
+    assert_eq!(hierarchy.leaf_internal_ratio, 48.0 / 4.0);
+
+    let report = DiagramType::Treemap(diagram).calculate_metrics();
+    assert_eq!(report.basic.node_count, 52);
+    assert_eq!(report.basic.depth, 3);
+    assert_eq!(report.basic.breadth, 48);
+    assert!(report
+        .suggestions
+        .iter()
+        .any(|suggestion| suggestion.message
+            == "Consider splitting level 2 nodes: 47 children under one parent"));
+}
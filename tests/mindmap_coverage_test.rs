@@ -300,10 +300,9 @@ fn test_node_id_generation() {
     let unique_ids: std::collections::HashSet<_> = ids.iter().collect();
     assert_eq!(ids.len(), unique_ids.len());
 
-    // IDs should follow pattern node_N
-    for id in ids {
-        assert!(id.starts_with("node_"));
-    }
+    // IDs are hierarchical positions in the tree
+    let ids: Vec<&str> = ids.iter().map(|id| id.as_str()).collect();
+    assert_eq!(ids, vec!["root", "root.1", "root.2", "root.2.1"]);
 }
 
 #[test]
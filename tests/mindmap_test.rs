@@ -1,4 +1,4 @@
-use mermaid_parser::common::ast::MindmapNodeShape;
+use mermaid_parser::common::ast::{MindmapNodeShape, NodeText};
 use mermaid_parser::parsers::mindmap;
 use rstest::*;
 use std::path::PathBuf;
@@ -223,3 +223,85 @@ fn test_invalid_mindmap() {
     let result = mindmap::parse(input);
     assert!(result.is_err(), "Should fail to parse invalid mindmap");
 }
+
+#[test]
+fn test_hierarchical_ids_and_cross_links() {
+    use mermaid_parser::parsers::mindmap::{parse_with_dialect, MindmapDialect};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"mindmap
+  root((Release))
+    Design
+      Mockups
+    Build
+      root.1.1 -->|drives| Tests
+      Tests
+    Build --> Design"#;
+
+    let strict = mindmap::parse(input).unwrap();
+    assert!(strict.cross_edges.is_empty());
+    assert_eq!(
+        strict.node("root.2.1").unwrap().text,
+        "root.1.1 -->|drives| Tests"
+    );
+
+    let diagram = parse_with_dialect(input, MindmapDialect::CrossLinks).unwrap();
+    assert_eq!(diagram.root.id(), "root");
+    assert_eq!(diagram.node("root.1.1").unwrap().text, "Mockups");
+    assert_eq!(diagram.node("root.2.1").unwrap().text, "Tests");
+    assert_eq!(diagram.cross_edges.len(), 2);
+    assert_eq!(diagram.cross_edges[0].from, "root.1.1");
+    assert_eq!(diagram.cross_edges[0].to, "root.2.1");
+    assert_eq!(diagram.cross_edges[0].label.as_deref(), Some("drives"));
+    assert_eq!(diagram.cross_edges[1].from, "root.2");
+    assert_eq!(diagram.cross_edges[1].to, "root.1");
+    assert_eq!(diagram.cross_edges[1].label, None);
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("root.1.1 -->|drives| root.2.1\n"));
+    let reparsed = parse_with_dialect(&printed, MindmapDialect::CrossLinks).unwrap();
+    assert_eq!(reparsed.cross_edges, diagram.cross_edges);
+
+    let unknown = parse_with_dialect(
+        "mindmap\n  root\n    A\n    A --> Missing",
+        MindmapDialect::CrossLinks,
+    );
+    assert!(matches!(
+        unknown,
+        Err(mermaid_parser::ParseError::SyntaxError { line: 4, .. })
+    ));
+}
+
+#[test]
+fn test_markdown_strings_in_nodes() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input = "mindmap
+  root[Plan]
+    id1[\"`**bold** text with
+    a new line`\"]
+    id2((\"`_Round_`\"))
+    Plain (with brackets)";
+
+    let diagram = mindmap::parse(input).unwrap();
+    assert_eq!(diagram.root.text, NodeText::Plain("Plan".to_string()));
+    assert_eq!(diagram.root.shape, MindmapNodeShape::Square);
+
+    let first = &diagram.root.children[0];
+    assert_eq!(
+        first.text,
+        NodeText::Markdown("**bold** text with\na new line".to_string())
+    );
+    assert_eq!(first.shape, MindmapNodeShape::Square);
+    let second = &diagram.root.children[1];
+    assert!(second.text.is_markdown());
+    assert_eq!(second.text, "_Round_");
+    assert_eq!(second.shape, MindmapNodeShape::Circle);
+    let third = &diagram.root.children[2];
+    assert_eq!(third.text, NodeText::Plain("Plain (with brackets)".into()));
+    assert_eq!(third.shape, MindmapNodeShape::Default);
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("[\"`**bold** text with\na new line`\"]"));
+    assert_eq!(mindmap::parse(&printed).unwrap(), diagram);
+}
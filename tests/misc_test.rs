@@ -18,6 +18,32 @@ fn test_info_diagram() {
     }
 }
 
+#[test]
+fn test_info_and_directive_documents_round_trip() {
+    use mermaid_parser::common::ast::MiscContent;
+    use mermaid_parser::MermaidPrinter;
+
+    for input in [
+        "info showInfo",
+        "%%{init: {\"theme\": \"dark\", \"logLevel\": 1}}%%\ninfo showInfo",
+        "%%{init: {'theme': 'base', 'themeVariables': {'primaryColor': '#ff0000'}}}%%",
+        "%%{wrap}%%",
+    ] {
+        let diagram = parse_diagram(input).unwrap();
+        let DiagramType::Misc(misc) = &diagram else {
+            panic!("Expected misc diagram for {:?}", input);
+        };
+        assert!(
+            matches!(misc.content, MiscContent::Info(_)),
+            "Expected info content for {:?}",
+            input
+        );
+
+        let printed = diagram.to_mermaid();
+        assert_eq!(parse_diagram(&printed).unwrap(), diagram, "{}", printed);
+    }
+}
+
 #[test]
 fn test_gitgraph_alt() {
     let input = r#"gitGraph:
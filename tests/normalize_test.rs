@@ -0,0 +1,75 @@
+use mermaid_parser::common::normalize::{normalize, parse_normalized};
+use mermaid_parser::common::validation::{Location, Severity, ValidationError};
+use mermaid_parser::{DiagramType, ParseError};
+
+#[test]
+fn test_normalize_splits_statements_and_keeps_indentation() {
+    let source = normalize("classDiagram\n    class A; class B;A <|-- B\n    %% a; comment");
+
+    assert_eq!(
+        source.text(),
+        "classDiagram\n    class A\n    class B\n    A <|-- B\n    %% a; comment"
+    );
+    assert_eq!(source.split_lines(), &[2]);
+    assert!(source.was_split());
+    assert_eq!(source.original_location(1, 3), Some((1, 3)));
+    // `B` in `class B` is at column 20 of the original second line
+    assert_eq!(source.original_location(3, 11), Some((2, 20)));
+    assert_eq!(source.original_location(4, 5), Some((2, 22)));
+    assert_eq!(source.original_location(9, 1), None);
+}
+
+#[test]
+fn test_normalize_leaves_quotes_and_frontmatter_alone() {
+    let input = "---\nconfig:\n  themeCSS: .a { fill: red; }\n---\npie; \"a;b\" : 1; \"c\" : 2";
+    let source = normalize(input);
+
+    assert_eq!(
+        source.text(),
+        "---\nconfig:\n  themeCSS: .a { fill: red; }\n---\npie\n\"a;b\" : 1\n\"c\" : 2"
+    );
+    assert_eq!(source.split_lines(), &[5]);
+    assert_eq!(source.original_location(7, 1), Some((5, 17)));
+}
+
+#[test]
+fn test_diagnostics_are_mapped_to_the_original_line() {
+    let source = normalize("erDiagram; A ||--o{ B : has; B ||--|{ C : holds");
+
+    let error = source.map_parse_error(ParseError::LexError {
+        message: "bad token".to_string(),
+        line: 3,
+        column: 3,
+        partial: None,
+    });
+    assert!(matches!(
+        error,
+        ParseError::LexError {
+            line: 1,
+            column: 32,
+            ..
+        }
+    ));
+
+    let mut diagnostic = ValidationError::new("example", "message".to_string(), Severity::Warning);
+    diagnostic.location = Some(Location::new(2, 1));
+    let mapped = source.map_validation_error(diagnostic);
+    assert_eq!(mapped.location.map(|l| (l.line, l.column)), Some((1, 12)));
+
+    let mut by_element = ValidationError::new("example", "message".to_string(), Severity::Info);
+    by_element.location = Some(Location::with_element(0, 0, "A".to_string()));
+    let unchanged = source.map_validation_error(by_element);
+    assert_eq!(unchanged.location.map(|l| l.line), Some(0));
+}
+
+#[test]
+fn test_parse_normalized_accepts_minified_input() {
+    let diagram =
+        parse_normalized("erDiagram; CUSTOMER ||--o{ ORDER : places; ORDER ||--|{ ITEM : contains")
+            .expect("Failed to parse minified ER diagram");
+
+    match diagram {
+        DiagramType::Er(er) => assert_eq!(er.relationships.len(), 2),
+        _ => panic!("Expected ER diagram"),
+    }
+}
@@ -27,6 +27,7 @@ mod packet_coverage_tests {
             found,
             line,
             column,
+            ..
         }) = result
         {
             assert_eq!(message, "Expected packet-beta or packet header");
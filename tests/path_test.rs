@@ -0,0 +1,52 @@
+use mermaid_parser::common::path::{element_paths, AstPath, ElementKind};
+use mermaid_parser::parse_diagram;
+
+fn paths(input: &str) -> Vec<String> {
+    let diagram = parse_diagram(input).unwrap();
+    element_paths(&diagram)
+        .iter()
+        .map(|path| path.to_string())
+        .collect()
+}
+
+#[test]
+fn test_flowchart_paths_are_content_based() {
+    let before = paths("flowchart TD\n    A[Start] --> B\n    B --> C");
+    let after = paths("flowchart TD\n    X --> A\n    A[Start] --> B\n    B --> C");
+
+    // Implicit nodes are addressed by the id they are used with
+    assert_eq!(&before[..3], ["node/A", "node/B", "node/C"]);
+    assert!(before[3].starts_with("edge/e"));
+
+    // Adding an edge does not renumber the others
+    for path in &before {
+        assert!(after.contains(path), "{} missing from {:?}", path, after);
+    }
+    assert_eq!(after.len(), before.len() + 2);
+
+    // The same source always yields the same ids
+    assert_eq!(
+        before,
+        paths("flowchart TD\n    A[Start] --> B\n    B --> C")
+    );
+}
+
+#[test]
+fn test_repeated_elements_get_occurrence_suffixes() {
+    let input = "sequenceDiagram\n    Alice->>Bob: Ping\n    loop Retry\n        Alice->>Bob: Ping\n    end\n    note right of Bob: Busy";
+    let found = paths(input);
+
+    assert_eq!(&found[..2], ["participant/Alice", "participant/Bob"]);
+    let messages: Vec<&String> = found.iter().filter(|p| p.starts_with("message/")).collect();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(*messages[1], format!("{}~2", messages[0]));
+
+    let note = AstPath::parse(found.last().unwrap()).unwrap();
+    assert_eq!(note.kind(), ElementKind::Note);
+    assert!(note.id().starts_with('n'));
+}
+
+#[test]
+fn test_diagrams_without_addressable_elements() {
+    assert!(paths("pie\n    \"A\" : 1").is_empty());
+}
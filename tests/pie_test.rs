@@ -52,6 +52,37 @@ fn test_pie_with_show_data() {
     assert_eq!(diagram.data[1].value, 85.5); // Test decimal values
 }
 
+#[test]
+fn test_show_data_header_with_title_round_trips() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input = "pie showData title Key elements\n    \"Calcium\" : 42.96\n    \"Iron\" : 5";
+    let diagram = pie::parse(input).unwrap();
+    assert!(diagram.show_data);
+    assert_eq!(diagram.title.as_deref(), Some("Key elements"));
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.starts_with("pie showData title Key elements\n"));
+    assert_eq!(pie::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_signs_and_thousands_separators() {
+    let input = "pie\n    \"A\" : +42\n    \"B\" : 1,234.5\n    \"C\" : 12,345,678";
+    let values: Vec<f64> = pie::parse(input)
+        .unwrap()
+        .data
+        .iter()
+        .map(|slice| slice.value)
+        .collect();
+    assert_eq!(values, [42.0, 1234.5, 12345678.0]);
+
+    for value in ["1,5", "1234,567", ",123", "inf", "NaN", "1e999"] {
+        let input = format!("pie\n    \"A\" : {}", value);
+        assert!(pie::parse(&input).is_err(), "{}", value);
+    }
+}
+
 #[test]
 fn test_pie_separate_title() {
     let input = r#"pie
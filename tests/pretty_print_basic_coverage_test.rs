@@ -1,6 +1,7 @@
 //! Basic tests to improve pretty_print coverage for core functionality
 
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::*;
 use std::collections::HashMap;
 
@@ -28,6 +29,8 @@ fn test_print_options_edge_cases() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
     let output = diagram.to_mermaid_pretty(&zero_indent);
     assert!(output.contains("flowchart TD"));
@@ -39,6 +42,8 @@ fn test_print_options_edge_cases() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
     let large_output = diagram.to_mermaid_pretty(&large_indent);
     assert!(large_output.contains("flowchart TD"));
@@ -50,6 +55,8 @@ fn test_print_options_edge_cases() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
     let small_output = diagram.to_mermaid_pretty(&small_line);
     assert!(small_output.contains("flowchart TD"));
@@ -67,6 +74,8 @@ fn test_compact_mode_comprehensive() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: true,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let compact_output = diagram.to_mermaid_pretty(&compact_options);
@@ -104,7 +113,7 @@ fn test_compact_mode_comprehensive() {
 // Test flowchart with all flow directions
 #[test]
 fn test_flowchart_all_directions() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -147,7 +156,7 @@ fn test_flowchart_all_directions() {
 // Test flowchart with all edge types
 #[test]
 fn test_flowchart_all_edge_types() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -170,16 +179,16 @@ fn test_flowchart_all_edge_types() {
     );
 
     let edge_types = [
-        EdgeType::Arrow,
-        EdgeType::DottedArrow,
-        EdgeType::ThickArrow,
-        EdgeType::OpenLink,
-        EdgeType::DottedLink,
-        EdgeType::ThickLink,
-        EdgeType::Invisible,
-        EdgeType::CircleEdge,
-        EdgeType::CrossEdge,
-        EdgeType::MultiDirectional,
+        EdgeType::ARROW,
+        EdgeType::DOTTED_ARROW,
+        EdgeType::THICK_ARROW,
+        EdgeType::OPEN_LINK,
+        EdgeType::DOTTED_LINK,
+        EdgeType::THICK_LINK,
+        EdgeType::INVISIBLE,
+        EdgeType::CIRCLE_EDGE,
+        EdgeType::CROSS_EDGE,
+        EdgeType::MULTI_DIRECTIONAL,
     ];
 
     let expected_arrows = [
@@ -218,7 +227,7 @@ fn test_flowchart_all_edge_types() {
 // Test flowchart with labeled edges
 #[test]
 fn test_flowchart_labeled_edges() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -248,7 +257,7 @@ fn test_flowchart_labeled_edges() {
         edges: vec![FlowEdge {
             from: "A".to_string(),
             to: "B".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: Some("proceed".to_string()),
             min_length: None,
         }],
@@ -293,7 +302,9 @@ fn test_sequence_diagram_basic_elements() {
             to: "Bob".to_string(),
             text: "Hello".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         })],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -383,10 +394,12 @@ fn test_sankey_diagram_basic() {
             SankeyNode {
                 id: "A".to_string(),
                 name: "Source".to_string(),
+                metadata: Default::default(),
             },
             SankeyNode {
                 id: "B".to_string(),
                 name: "Target".to_string(),
+                metadata: Default::default(),
             },
         ],
         links: vec![SankeyLink {
@@ -427,7 +440,7 @@ fn test_empty_diagrams() {
         title: None,
         accessibility: AccessibilityInfo::default(),
         direction: FlowDirection::TD,
-        nodes: HashMap::new(),
+        nodes: OrderedMap::new(),
         edges: vec![],
         subgraphs: vec![],
         styles: vec![],
@@ -444,6 +457,7 @@ fn test_empty_diagrams() {
         accessibility: AccessibilityInfo::default(),
         participants: vec![],
         statements: vec![],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -454,7 +468,7 @@ fn test_empty_diagrams() {
 // Test alignment functionality
 #[test]
 fn test_alignment_functionality() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -484,7 +498,7 @@ fn test_alignment_functionality() {
         edges: vec![FlowEdge {
             from: "A".to_string(),
             to: "B".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         }],
@@ -1,6 +1,7 @@
 //! Comprehensive tests for pretty_print module to improve coverage
 
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::*;
 use std::collections::HashMap;
 
@@ -17,6 +18,8 @@ fn test_print_options_all_combinations() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: true,
+        minify: false,
+        normalize_keywords: false,
     };
     let compact_output = diagram.to_mermaid_pretty(&compact_options);
     for line in compact_output.lines() {
@@ -30,6 +33,8 @@ fn test_print_options_all_combinations() {
         align_arrows: false,
         sort_nodes: true,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
     let sorted_output = diagram.to_mermaid_pretty(&sort_options);
     assert!(sorted_output.contains("A[Node A]"));
@@ -43,6 +48,8 @@ fn test_print_options_all_combinations() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
     let indent_2_output = diagram.to_mermaid_pretty(&indent_2_options);
     assert!(indent_2_output.lines().nth(1).unwrap().starts_with("  "));
@@ -53,6 +60,8 @@ fn test_print_options_all_combinations() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
     let indent_8_output = diagram.to_mermaid_pretty(&indent_8_options);
     assert!(indent_8_output
@@ -65,7 +74,7 @@ fn test_print_options_all_combinations() {
 // Test all flowchart node shapes
 #[test]
 fn test_flowchart_all_node_shapes() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
 
     // Create nodes with all different shapes
     nodes.insert(
@@ -242,21 +251,21 @@ fn test_flowchart_all_node_shapes() {
     assert!(output.contains("C([Stadium])"));
     assert!(output.contains("D[[Subroutine]]"));
     assert!(output.contains("E[(Cylinder)]"));
-    assert!(output.contains("F((Circle)))"));
+    assert!(output.contains("F((Circle))\n") || output.ends_with("F((Circle))"));
     assert!(output.contains("G>Asymmetric]"));
     assert!(output.contains("H{Rhombus}"));
     assert!(output.contains("I{{Hexagon}}"));
-    assert!(output.contains("J[/Parallelogram\\]"));
-    assert!(output.contains("K[\\ParallelogramAlt/]"));
-    assert!(output.contains("L[/Trapezoid/]"));
-    assert!(output.contains("M[\\TrapezoidAlt\\]"));
+    assert!(output.contains("J[/Parallelogram/]"));
+    assert!(output.contains("K[\\ParallelogramAlt\\]"));
+    assert!(output.contains("L[/Trapezoid\\]"));
+    assert!(output.contains("M[\\TrapezoidAlt/]"));
     assert!(output.contains("N(((DoubleCircle)))"));
 }
 
 // Test all flowchart edge types
 #[test]
 fn test_flowchart_all_edge_types() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     for i in 0..=10 {
         nodes.insert(
             format!("N{}", i),
@@ -274,70 +283,70 @@ fn test_flowchart_all_edge_types() {
         FlowEdge {
             from: "N0".to_string(),
             to: "N1".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: Some("Arrow".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N1".to_string(),
             to: "N2".to_string(),
-            edge_type: EdgeType::DottedArrow,
+            edge_type: EdgeType::DOTTED_ARROW,
             label: Some("DottedArrow".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N2".to_string(),
             to: "N3".to_string(),
-            edge_type: EdgeType::ThickArrow,
+            edge_type: EdgeType::THICK_ARROW,
             label: Some("ThickArrow".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N3".to_string(),
             to: "N4".to_string(),
-            edge_type: EdgeType::OpenLink,
+            edge_type: EdgeType::OPEN_LINK,
             label: Some("OpenLink".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N4".to_string(),
             to: "N5".to_string(),
-            edge_type: EdgeType::DottedLink,
+            edge_type: EdgeType::DOTTED_LINK,
             label: Some("DottedLink".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N5".to_string(),
             to: "N6".to_string(),
-            edge_type: EdgeType::ThickLink,
+            edge_type: EdgeType::THICK_LINK,
             label: Some("ThickLink".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N6".to_string(),
             to: "N7".to_string(),
-            edge_type: EdgeType::Invisible,
+            edge_type: EdgeType::INVISIBLE,
             label: Some("Invisible".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N7".to_string(),
             to: "N8".to_string(),
-            edge_type: EdgeType::CircleEdge,
+            edge_type: EdgeType::CIRCLE_EDGE,
             label: Some("CircleEdge".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N8".to_string(),
             to: "N9".to_string(),
-            edge_type: EdgeType::CrossEdge,
+            edge_type: EdgeType::CROSS_EDGE,
             label: Some("CrossEdge".to_string()),
             min_length: None,
         },
         FlowEdge {
             from: "N9".to_string(),
             to: "N10".to_string(),
-            edge_type: EdgeType::MultiDirectional,
+            edge_type: EdgeType::MULTI_DIRECTIONAL,
             label: Some("MultiDirectional".to_string()),
             min_length: None,
         },
@@ -379,6 +388,7 @@ fn test_sequence_diagram_all_statement_types() {
             to: "B".to_string(),
             text: "Hello".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         }),
         SequenceStatement::Loop(Loop {
             condition: "while active".to_string(),
@@ -387,6 +397,7 @@ fn test_sequence_diagram_all_statement_types() {
                 to: "C".to_string(),
                 text: "Process".to_string(),
                 arrow_type: ArrowType::SolidClosed,
+                activation: None,
             })],
         }),
         SequenceStatement::Note(Note {
@@ -435,6 +446,7 @@ fn test_sequence_diagram_all_statement_types() {
             },
         ],
         statements,
+        boxes: vec![],
         autonumber: Some(AutoNumber {
             start: Some(1),
             step: Some(1),
@@ -480,9 +492,10 @@ fn test_requirement_diagram_basic() {
     let diagram = DiagramType::Requirement(RequirementDiagram {
         title: Some("System Requirements".to_string()),
         accessibility: AccessibilityInfo::default(),
-        requirements: HashMap::new(),
+        requirements: OrderedMap::new(),
         relationships: vec![],
-        elements: HashMap::new(),
+        elements: OrderedMap::new(),
+        styles: vec![],
     });
 
     let output = diagram.to_mermaid();
@@ -588,7 +601,7 @@ fn test_empty_diagram_variants() {
         title: None,
         accessibility: AccessibilityInfo::default(),
         direction: FlowDirection::TD,
-        nodes: HashMap::new(),
+        nodes: OrderedMap::new(),
         edges: vec![],
         subgraphs: vec![],
         styles: vec![],
@@ -604,6 +617,7 @@ fn test_empty_diagram_variants() {
         accessibility: AccessibilityInfo::default(),
         participants: vec![],
         statements: vec![],
+        boxes: vec![],
         autonumber: None,
     });
     let output = empty_sequence.to_mermaid();
@@ -632,7 +646,7 @@ fn test_accessibility_and_title_formatting() {
         title: Some("Main Title".to_string()),
         accessibility,
         direction: FlowDirection::LR,
-        nodes: HashMap::new(),
+        nodes: OrderedMap::new(),
         edges: vec![],
         subgraphs: vec![],
         styles: vec![],
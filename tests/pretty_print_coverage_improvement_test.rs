@@ -1,6 +1,7 @@
 //! Comprehensive tests targeting missing coverage areas in pretty_print.rs
 
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::common::pretty_print::{MermaidPrinter, PrintOptions};
 use std::collections::HashMap;
 
@@ -16,7 +17,7 @@ mod pretty_print_coverage_tests {
             accessibility: AccessibilityInfo::default(),
             direction: FlowDirection::TD,
             nodes: {
-                let mut nodes = HashMap::new();
+                let mut nodes = OrderedMap::new();
                 nodes.insert(
                     "A".to_string(),
                     FlowNode {
@@ -42,7 +43,7 @@ mod pretty_print_coverage_tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: Some("test".to_string()),
                 min_length: None,
             }],
@@ -55,6 +56,8 @@ mod pretty_print_coverage_tests {
         // Test compact mode
         let compact_options = PrintOptions {
             compact_mode: true,
+            minify: false,
+            normalize_keywords: false,
             indent_width: 2,
             max_line_length: 80,
             align_arrows: false,
@@ -72,6 +75,8 @@ mod pretty_print_coverage_tests {
         // Test align arrows mode
         let align_options = PrintOptions {
             compact_mode: false,
+            minify: false,
+            normalize_keywords: false,
             indent_width: 4,
             max_line_length: 120,
             align_arrows: true,
@@ -83,6 +88,8 @@ mod pretty_print_coverage_tests {
         // Test sort nodes mode
         let sort_options = PrintOptions {
             compact_mode: false,
+            minify: false,
+            normalize_keywords: false,
             indent_width: 2,
             max_line_length: 100,
             align_arrows: false,
@@ -94,6 +101,8 @@ mod pretty_print_coverage_tests {
         // Test combination: compact + align + sort
         let combo_options = PrintOptions {
             compact_mode: true,
+            minify: false,
+            normalize_keywords: false,
             indent_width: 8,
             max_line_length: 60,
             align_arrows: true,
@@ -115,20 +124,20 @@ mod pretty_print_coverage_tests {
             accessibility: AccessibilityInfo::default(),
             root: MindmapNode {
                 id: "root".to_string(),
-                text: "Root Node".to_string(),
+                text: "Root Node".into(),
                 shape: MindmapNodeShape::Cloud,
                 icon: Some("🌟".to_string()),
                 class: Some("root-class".to_string()),
                 children: vec![
                     MindmapNode {
                         id: "child1".to_string(),
-                        text: "".to_string(), // Empty text node with icon
+                        text: "".into(), // Empty text node with icon
                         shape: MindmapNodeShape::Square,
                         icon: Some("📁".to_string()),
                         class: None,
                         children: vec![MindmapNode {
                             id: "grandchild".to_string(),
-                            text: "Deep Node".to_string(),
+                            text: "Deep Node".into(),
                             shape: MindmapNodeShape::Default,
                             icon: None,
                             class: None,
@@ -137,7 +146,7 @@ mod pretty_print_coverage_tests {
                     },
                     MindmapNode {
                         id: "child2".to_string(),
-                        text: "Regular Child".to_string(),
+                        text: "Regular Child".into(),
                         shape: MindmapNodeShape::Hexagon,
                         icon: None,
                         class: Some("special".to_string()),
@@ -145,6 +154,7 @@ mod pretty_print_coverage_tests {
                     },
                 ],
             },
+            cross_edges: vec![],
         });
 
         let output = mindmap.to_mermaid();
@@ -159,7 +169,7 @@ mod pretty_print_coverage_tests {
     #[test]
     fn test_flowchart_edge_cases() {
         // Test flowchart with all different node shapes
-        let mut nodes = HashMap::new();
+        let mut nodes = OrderedMap::new();
         let shapes = vec![
             ("rect", NodeShape::Rectangle),
             ("round", NodeShape::RoundedRectangle),
@@ -193,16 +203,16 @@ mod pretty_print_coverage_tests {
 
         // Test all edge types
         let edge_types = [
-            EdgeType::Arrow,
-            EdgeType::DottedArrow,
-            EdgeType::ThickArrow,
-            EdgeType::OpenLink,
-            EdgeType::DottedLink,
-            EdgeType::ThickLink,
-            EdgeType::Invisible,
-            EdgeType::CircleEdge,
-            EdgeType::CrossEdge,
-            EdgeType::MultiDirectional,
+            EdgeType::ARROW,
+            EdgeType::DOTTED_ARROW,
+            EdgeType::THICK_ARROW,
+            EdgeType::OPEN_LINK,
+            EdgeType::DOTTED_LINK,
+            EdgeType::THICK_LINK,
+            EdgeType::INVISIBLE,
+            EdgeType::CIRCLE_EDGE,
+            EdgeType::CROSS_EDGE,
+            EdgeType::MULTI_DIRECTIONAL,
         ];
 
         let mut edges = vec![];
@@ -244,21 +254,23 @@ mod pretty_print_coverage_tests {
         assert!(output.contains("stadium([Text stadium])"));
         assert!(output.contains("subroutine[[Text subroutine]]"));
         assert!(output.contains("cylinder[(Text cylinder)]"));
-        assert!(output.contains("circle((Text circle)))"));
+        assert!(
+            output.contains("circle((Text circle))\n") || output.ends_with("circle((Text circle))")
+        );
         assert!(output.contains("asymmetric>Text asymmetric]"));
         assert!(output.contains("rhombus{Text rhombus}"));
         assert!(output.contains("hexagon{{Text hexagon}}"));
-        assert!(output.contains("parallelogram[/Text parallelogram\\]"));
-        assert!(output.contains("parallelogram_alt[\\Text parallelogram_alt/]"));
-        assert!(output.contains("trapezoid[/Text trapezoid/]"));
-        assert!(output.contains("trapezoid_alt[\\Text trapezoid_alt\\]"));
+        assert!(output.contains("parallelogram[/Text parallelogram/]"));
+        assert!(output.contains("parallelogram_alt[\\Text parallelogram_alt\\]"));
+        assert!(output.contains("trapezoid[/Text trapezoid\\]"));
+        assert!(output.contains("trapezoid_alt[\\Text trapezoid_alt/]"));
         assert!(output.contains("double_circle(((Text double_circle)))"));
     }
 
     // Test complex flowchart with subgraphs and styles
     #[test]
     fn test_flowchart_complex_features() {
-        let mut nodes = HashMap::new();
+        let mut nodes = OrderedMap::new();
         nodes.insert(
             "A".to_string(),
             FlowNode {
@@ -298,7 +310,7 @@ mod pretty_print_coverage_tests {
             edges: vec![FlowEdge {
                 from: "B".to_string(),
                 to: "C".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             }],
@@ -320,6 +332,7 @@ mod pretty_print_coverage_tests {
             ClassDef {
                 name: "highlight".to_string(),
                 styles: highlight_styles,
+                definitions: 1,
             },
         );
 
@@ -360,7 +373,7 @@ mod pretty_print_coverage_tests {
             edges: vec![FlowEdge {
                 from: "A".to_string(),
                 to: "sub1".to_string(),
-                edge_type: EdgeType::ThickArrow,
+                edge_type: EdgeType::THICK_ARROW,
                 label: Some("to subgraph".to_string()),
                 min_length: Some(3),
             }],
@@ -405,6 +418,7 @@ mod pretty_print_coverage_tests {
                 to: "Bob".to_string(),
                 text: "Hello".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             }),
             SequenceStatement::Note(Note {
                 position: NotePosition::RightOf,
@@ -418,6 +432,7 @@ mod pretty_print_coverage_tests {
                     to: "Alice".to_string(),
                     text: "Counter".to_string(),
                     arrow_type: ArrowType::DottedClosed,
+                    activation: None,
                 })],
             }),
             SequenceStatement::Alt(Alternative {
@@ -427,6 +442,7 @@ mod pretty_print_coverage_tests {
                     to: "Bob".to_string(),
                     text: "OK".to_string(),
                     arrow_type: ArrowType::SolidClosed,
+                    activation: None,
                 })],
                 else_branch: Some(ElseBranch {
                     condition: Some("failure".to_string()),
@@ -435,6 +451,7 @@ mod pretty_print_coverage_tests {
                         to: "Bob".to_string(),
                         text: "Error".to_string(),
                         arrow_type: ArrowType::Cross,
+                        activation: None,
                     })],
                 }),
             }),
@@ -447,6 +464,7 @@ mod pretty_print_coverage_tests {
                         to: "Alice".to_string(),
                         text: "Processing".to_string(),
                         arrow_type: ArrowType::Point,
+                        activation: None,
                     }),
                     SequenceStatement::Deactivate("Bob".to_string()),
                 ],
@@ -460,6 +478,7 @@ mod pretty_print_coverage_tests {
                             to: "Bob".to_string(),
                             text: "Parallel 1".to_string(),
                             arrow_type: ArrowType::BiDirectionalSolid,
+                            activation: None,
                         })],
                     },
                     ParallelBranch {
@@ -469,6 +488,7 @@ mod pretty_print_coverage_tests {
                             to: "Alice".to_string(),
                             text: "Parallel 2".to_string(),
                             arrow_type: ArrowType::BiDirectionalDotted,
+                            activation: None,
                         })],
                     },
                 ],
@@ -495,6 +515,7 @@ mod pretty_print_coverage_tests {
             },
             participants,
             statements,
+            boxes: vec![],
             autonumber: Some(AutoNumber {
                 visible: true,
                 start: Some(5),
@@ -523,9 +544,9 @@ mod pretty_print_coverage_tests {
         assert!(output.contains("Bob -) Alice: Processing"));
         assert!(output.contains("deactivate Bob"));
         assert!(output.contains("par branch1"));
-        assert!(output.contains("Alice <-> Bob: Parallel 1"));
+        assert!(output.contains("Alice <<->> Bob: Parallel 1"));
         assert!(output.contains("and"));
-        assert!(output.contains("Bob <--> Alice: Parallel 2"));
+        assert!(output.contains("Bob <<-->> Alice: Parallel 2"));
         assert!(output.contains("critical critical section"));
         assert!(output.contains("create participant System"));
         assert!(output.contains("option option1"));
@@ -536,7 +557,7 @@ mod pretty_print_coverage_tests {
     // Test class diagram with all features
     #[test]
     fn test_class_diagram_comprehensive() {
-        let mut classes = HashMap::new();
+        let mut classes = OrderedMap::new();
         classes.insert(
             "Animal".to_string(),
             Class {
@@ -549,6 +570,7 @@ mod pretty_print_coverage_tests {
                         visibility: Visibility::Protected,
                         is_static: false,
                         default_value: Some("'Unknown'".to_string()),
+                        generics: vec![],
                     }),
                     ClassMember::Property(Property {
                         name: "count".to_string(),
@@ -556,6 +578,7 @@ mod pretty_print_coverage_tests {
                         visibility: Visibility::Private,
                         is_static: true,
                         default_value: None,
+                        generics: vec![],
                     }),
                     ClassMember::Method(Method {
                         visibility: Visibility::Public,
@@ -573,6 +596,7 @@ mod pretty_print_coverage_tests {
                         return_type: Some("void".to_string()),
                         is_static: false,
                         is_abstract: true,
+                        generics: vec![],
                     }),
                     ClassMember::Method(Method {
                         visibility: Visibility::Package,
@@ -581,10 +605,12 @@ mod pretty_print_coverage_tests {
                         return_type: None, // Test method without return type
                         is_static: true,
                         is_abstract: false,
+                        generics: vec![],
                     }),
                 ],
                 annotations: vec!["@Entity".to_string(), "@Serializable".to_string()],
                 css_class: Some("highlight".to_string()),
+                generics: vec![],
             },
         );
 
@@ -596,6 +622,7 @@ mod pretty_print_coverage_tests {
                 members: vec![],
                 annotations: vec![],
                 css_class: None,
+                generics: vec![],
             },
         );
 
@@ -627,9 +654,13 @@ mod pretty_print_coverage_tests {
         let class_diagram = DiagramType::Class(ClassDiagram {
             title: Some("Animal Hierarchy".to_string()),
             accessibility: AccessibilityInfo::default(),
+            direction: None,
             classes,
             relationships,
             notes,
+            namespaces: HashMap::new(),
+            styles: vec![],
+            clicks: vec![],
         });
 
         let output = class_diagram.to_mermaid();
@@ -646,21 +677,39 @@ mod pretty_print_coverage_tests {
         assert!(output.contains("Dog <|-- Animal"));
         assert!(output.contains("Owner \"1\" *-- \"*\" Dog : owns"));
         assert!(output.contains("note \"Base class for all animals\""));
-        assert!(output.contains("class Animal highlight"));
+        assert!(output.contains("cssClass \"Animal\" highlight"));
     }
 
     // Test state diagram with all state types
     #[test]
     fn test_state_diagram_comprehensive() {
-        let mut states = HashMap::new();
+        let simple = |id: &str| {
+            (
+                id.to_string(),
+                State {
+                    id: id.to_string(),
+                    display_name: None,
+                    state_type: StateType::Simple,
+                    states: OrderedMap::new(),
+                    transitions: vec![],
+                    notes: vec![],
+                    concurrent_regions: vec![],
+                    classes: Vec::new(),
+                },
+            )
+        };
+        let mut states = OrderedMap::new();
         states.insert(
             "start".to_string(),
             State {
                 id: "start".to_string(),
                 display_name: None,
                 state_type: StateType::Start,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
         states.insert(
@@ -669,8 +718,11 @@ mod pretty_print_coverage_tests {
                 id: "end".to_string(),
                 display_name: None,
                 state_type: StateType::End,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
         states.insert(
@@ -679,8 +731,11 @@ mod pretty_print_coverage_tests {
                 id: "choice".to_string(),
                 display_name: Some("Decision Point".to_string()),
                 state_type: StateType::Choice,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
         states.insert(
@@ -689,8 +744,11 @@ mod pretty_print_coverage_tests {
                 id: "fork".to_string(),
                 display_name: None,
                 state_type: StateType::Fork,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
         states.insert(
@@ -699,8 +757,11 @@ mod pretty_print_coverage_tests {
                 id: "join".to_string(),
                 display_name: None,
                 state_type: StateType::Join,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
         states.insert(
@@ -709,11 +770,32 @@ mod pretty_print_coverage_tests {
                 id: "composite".to_string(),
                 display_name: Some("Composite State".to_string()),
                 state_type: StateType::Composite,
-                substates: vec!["sub1".to_string(), "sub2".to_string()],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![
-                    vec!["region1_state1".to_string(), "region1_state2".to_string()],
-                    vec!["region2_state1".to_string()],
+                    StateRegion {
+                        states: OrderedMap::from([simple("sub1"), simple("region1_state1")]),
+                        transitions: vec![StateTransition {
+                            from: "sub1".to_string(),
+                            to: "region1_state1".to_string(),
+                            event: None,
+                            guard: None,
+                            action: None,
+                        }],
+                        notes: vec![],
+                    },
+                    StateRegion {
+                        states: OrderedMap::from([simple("sub2"), simple("region2_state1")]),
+                        transitions: vec![],
+                        notes: vec![StateNote {
+                            position: StateNotePosition::RightOf,
+                            target: "sub2".to_string(),
+                            text: "Parallel".to_string(),
+                        }],
+                    },
                 ],
+                classes: Vec::new(),
             },
         );
 
@@ -757,25 +839,31 @@ mod pretty_print_coverage_tests {
             title: Some("State Machine V1".to_string()),
             accessibility: AccessibilityInfo::default(),
             version: StateVersion::V1,
+            direction: None,
             states: states.clone(),
             transitions: transitions.clone(),
             notes: notes.clone(),
+            class_defs: HashMap::new(),
         });
 
         let state_v2 = DiagramType::State(StateDiagram {
             title: Some("State Machine V2".to_string()),
             accessibility: AccessibilityInfo::default(),
             version: StateVersion::V2,
+            direction: None,
             states,
             transitions,
             notes,
+            class_defs: HashMap::new(),
         });
 
         let output_v1 = state_v1.to_mermaid();
         assert!(output_v1.contains("stateDiagram"));
         assert!(!output_v1.contains("stateDiagram-v2"));
-        assert!(output_v1.contains("[*] --> start"));
-        assert!(output_v1.contains("end --> [*]"));
+        // Start/end markers come only from transitions, which don't reference [*] here
+        assert!(!output_v1.contains("[*]"));
+        assert!(output_v1.contains("start --> choice"));
+        assert!(output_v1.contains("state end <<end>>"));
         assert!(output_v1.contains("state choice <<choice>>"));
         assert!(output_v1.contains("state fork <<fork>>"));
         assert!(output_v1.contains("state join <<join>>"));
@@ -785,6 +873,8 @@ mod pretty_print_coverage_tests {
         assert!(output_v1.contains("--"));
         assert!(output_v1.contains("region1_state1"));
         assert!(output_v1.contains("region2_state1"));
+        assert!(output_v1.contains("sub1 --> region1_state1"));
+        assert!(output_v1.contains("note right of sub2 : Parallel"));
         assert!(output_v1.contains("choice --> composite : event1 [condition] / doAction()"));
         assert!(output_v1.contains("note left of choice : Decision point"));
         assert!(output_v1.contains("note above composite : Complex state"));
@@ -797,7 +887,7 @@ mod pretty_print_coverage_tests {
     // Test ER diagram comprehensive features
     #[test]
     fn test_er_diagram_comprehensive() {
-        let mut entities = HashMap::new();
+        let mut entities = OrderedMap::new();
         entities.insert(
             "Customer".to_string(),
             Entity {
@@ -822,6 +912,8 @@ mod pretty_print_coverage_tests {
                         comment: Some("".to_string()), // Test empty comment
                     },
                 ],
+                css_class: None,
+                display_name: None,
             },
         );
 
@@ -859,13 +951,15 @@ mod pretty_print_coverage_tests {
             accessibility: AccessibilityInfo::default(),
             entities,
             relationships,
+            styles: Vec::new(),
+            class_defs: HashMap::new(),
         });
 
         let output = er_diagram.to_mermaid();
         assert!(output.contains("erDiagram"));
         assert!(output.contains("title E-commerce"));
-        assert!(output.contains("Customer o|--|{ Order : places"));
-        assert!(output.contains("Order ||--o{ Product :"));
+        assert!(output.contains("Customer |o--|{ Order : places"));
+        assert!(output.contains("Order ||--o{ Product\n"));
         assert!(output.contains("Customer {"));
         assert!(output.contains("int id PK \"Primary key\""));
         assert!(output.contains("varchar(255) email UK"));
@@ -896,11 +990,13 @@ mod pretty_print_coverage_tests {
                     id: Some("c1".to_string()),
                     commit_type: CommitType::Reverse,
                     tag: Some("v1.0".to_string()),
+                    explicit_type: true,
                 },
                 GitOperation::Commit {
                     id: Some("c2".to_string()),
                     commit_type: CommitType::Highlight,
                     tag: None,
+                    explicit_type: true,
                 },
                 GitOperation::Branch {
                     name: "feature".to_string(),
@@ -908,12 +1004,14 @@ mod pretty_print_coverage_tests {
                 },
                 GitOperation::Checkout {
                     branch: "feature".to_string(),
+                    keyword: CheckoutKeyword::Checkout,
                 },
                 GitOperation::Merge {
                     branch: "feature".to_string(),
                     id: Some("merge1".to_string()),
                     tag: Some("merge-tag".to_string()),
                     commit_type: CommitType::Normal,
+                    explicit_type: false,
                 },
                 GitOperation::CherryPick {
                     id: "cherry1".to_string(),
@@ -987,7 +1085,7 @@ mod pretty_print_coverage_tests {
     #[test]
     fn test_edge_cases_null_empty() {
         // Test flowchart with empty node text
-        let mut nodes = HashMap::new();
+        let mut nodes = OrderedMap::new();
         nodes.insert(
             "empty".to_string(),
             FlowNode {
@@ -1024,6 +1122,7 @@ mod pretty_print_coverage_tests {
             accessibility: AccessibilityInfo::default(),
             participants: vec![],
             statements: vec![],
+            boxes: vec![],
             autonumber: None,
         });
 
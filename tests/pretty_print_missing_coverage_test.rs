@@ -1,6 +1,7 @@
 //! Tests for pretty_print diagram types with missing or incomplete coverage
 
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::*;
 use std::collections::HashMap;
 
@@ -18,6 +19,7 @@ fn test_gantt_diagram_pretty_print() {
         today_marker: None,
         inclusive_end_dates: false,
         top_axis: false,
+        display_mode: None,
         weekdays: WeekdaySettings::default(),
         sections: vec![
             GanttSection {
@@ -89,15 +91,33 @@ fn test_gantt_diagram_pretty_print() {
 // Test State diagram pretty printing with complex features
 #[test]
 fn test_state_diagram_comprehensive_pretty_print() {
-    let mut states = HashMap::new();
+    let sub = |id: &str| {
+        (
+            id.to_string(),
+            State {
+                id: id.to_string(),
+                display_name: None,
+                state_type: StateType::Simple,
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
+                concurrent_regions: vec![],
+                classes: Vec::new(),
+            },
+        )
+    };
+    let mut states = OrderedMap::new();
     states.insert(
         "idle".to_string(),
         State {
             id: "idle".to_string(),
             display_name: Some("Idle State".to_string()),
             state_type: StateType::Simple,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         },
     );
     states.insert(
@@ -106,8 +126,11 @@ fn test_state_diagram_comprehensive_pretty_print() {
             id: "active".to_string(),
             display_name: Some("Active State".to_string()),
             state_type: StateType::Simple,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         },
     );
     states.insert(
@@ -116,8 +139,17 @@ fn test_state_diagram_comprehensive_pretty_print() {
             id: "composite".to_string(),
             display_name: Some("Composite State".to_string()),
             state_type: StateType::Composite,
-            substates: vec!["sub1".to_string(), "sub2".to_string()],
+            states: OrderedMap::from([sub("sub1"), sub("sub2")]),
+            transitions: vec![StateTransition {
+                from: "sub1".to_string(),
+                to: "sub2".to_string(),
+                event: None,
+                guard: None,
+                action: None,
+            }],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         },
     );
 
@@ -125,6 +157,7 @@ fn test_state_diagram_comprehensive_pretty_print() {
         title: Some("State Machine".to_string()),
         accessibility: AccessibilityInfo::default(),
         version: StateVersion::V2,
+        direction: None,
         states,
         transitions: vec![
             StateTransition {
@@ -147,6 +180,7 @@ fn test_state_diagram_comprehensive_pretty_print() {
             target: "idle".to_string(),
             text: "Initial state".to_string(),
         }],
+        class_defs: HashMap::new(),
     });
 
     let output = diagram.to_mermaid();
@@ -156,15 +190,19 @@ fn test_state_diagram_comprehensive_pretty_print() {
     assert!(output.contains("idle --> active : start [condition_met] / initialize()"));
     assert!(output.contains("active --> idle : stop"));
     assert!(output.contains("note right of idle : Initial state"));
+    assert!(output
+        .contains("state composite {\n        sub1\n        sub2\n        sub1 --> sub2\n    }"));
 
     // Test state diagram with different version
     let v1_diagram = DiagramType::State(StateDiagram {
         title: None,
         accessibility: AccessibilityInfo::default(),
         version: StateVersion::V1,
-        states: HashMap::new(),
+        direction: None,
+        states: OrderedMap::new(),
         transitions: vec![],
         notes: vec![],
+        class_defs: HashMap::new(),
     });
     let v1_output = v1_diagram.to_mermaid();
     assert!(v1_output.contains("stateDiagram"));
@@ -173,7 +211,7 @@ fn test_state_diagram_comprehensive_pretty_print() {
 // Test Class diagram with comprehensive features
 #[test]
 fn test_class_diagram_comprehensive_pretty_print() {
-    let mut classes = HashMap::new();
+    let mut classes = OrderedMap::new();
     classes.insert(
         "Animal".to_string(),
         Class {
@@ -186,6 +224,7 @@ fn test_class_diagram_comprehensive_pretty_print() {
                     visibility: Visibility::Protected,
                     is_static: false,
                     default_value: None,
+                    generics: vec![],
                 }),
                 ClassMember::Method(Method {
                     visibility: Visibility::Public,
@@ -194,10 +233,12 @@ fn test_class_diagram_comprehensive_pretty_print() {
                     return_type: Some("void".to_string()),
                     is_static: false,
                     is_abstract: true,
+                    generics: vec![],
                 }),
             ],
             annotations: vec!["@Entity".to_string()],
             css_class: Some("highlight".to_string()),
+            generics: vec![],
         },
     );
 
@@ -213,15 +254,18 @@ fn test_class_diagram_comprehensive_pretty_print() {
                 return_type: Some("void".to_string()),
                 is_static: false,
                 is_abstract: false,
+                generics: vec![],
             })],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         },
     );
 
     let diagram = DiagramType::Class(ClassDiagram {
         title: Some("Animal Hierarchy".to_string()),
         accessibility: AccessibilityInfo::default(),
+        direction: None,
         classes,
         relationships: vec![ClassRelationship {
             from: "Dog".to_string(),
@@ -236,6 +280,9 @@ fn test_class_diagram_comprehensive_pretty_print() {
             actor: "Animal".to_string(),
             text: "Base class for all animals".to_string(),
         }],
+        namespaces: HashMap::new(),
+        styles: vec![],
+        clicks: vec![],
     });
 
     let output = diagram.to_mermaid();
@@ -294,21 +341,21 @@ fn test_mindmap_diagram_comprehensive_pretty_print() {
         accessibility: AccessibilityInfo::default(),
         root: MindmapNode {
             id: "root".to_string(),
-            text: "Project".to_string(),
+            text: "Project".into(),
             shape: MindmapNodeShape::Cloud,
             icon: Some("📁".to_string()),
             class: Some("root-style".to_string()),
             children: vec![
                 MindmapNode {
                     id: "frontend".to_string(),
-                    text: "Frontend".to_string(),
+                    text: "Frontend".into(),
                     shape: MindmapNodeShape::Square,
                     icon: Some("🖥️".to_string()),
                     class: None,
                     children: vec![
                         MindmapNode {
                             id: "react".to_string(),
-                            text: "React Components".to_string(),
+                            text: "React Components".into(),
                             shape: MindmapNodeShape::Default,
                             icon: None,
                             class: None,
@@ -316,7 +363,7 @@ fn test_mindmap_diagram_comprehensive_pretty_print() {
                         },
                         MindmapNode {
                             id: "styles".to_string(),
-                            text: "CSS Styles".to_string(),
+                            text: "CSS Styles".into(),
                             shape: MindmapNodeShape::Rounded,
                             icon: None,
                             class: None,
@@ -326,13 +373,13 @@ fn test_mindmap_diagram_comprehensive_pretty_print() {
                 },
                 MindmapNode {
                     id: "backend".to_string(),
-                    text: "Backend".to_string(),
+                    text: "Backend".into(),
                     shape: MindmapNodeShape::Hexagon,
                     icon: Some("⚙️".to_string()),
                     class: Some("backend-style".to_string()),
                     children: vec![MindmapNode {
                         id: "api".to_string(),
-                        text: "REST API".to_string(),
+                        text: "REST API".into(),
                         shape: MindmapNodeShape::Default,
                         icon: None,
                         class: None,
@@ -341,6 +388,7 @@ fn test_mindmap_diagram_comprehensive_pretty_print() {
                 },
             ],
         },
+        cross_edges: vec![],
     });
 
     let output = diagram.to_mermaid();
@@ -384,12 +432,14 @@ fn test_xychart_diagram_comprehensive_pretty_print() {
             DataSeries {
                 series_type: SeriesType::Line,
                 name: Some("2023".to_string()),
-                data: vec![20.0, 35.0, 45.0, 60.0],
+                data: vec![Some(20.0), Some(35.0), Some(45.0), Some(60.0)],
+                color: None,
             },
             DataSeries {
                 series_type: SeriesType::Line,
                 name: Some("2024".to_string()),
-                data: vec![25.0, 40.0, 55.0, 70.0],
+                data: vec![Some(25.0), Some(40.0), Some(55.0), Some(70.0)],
+                color: None,
             },
         ],
     });
@@ -516,7 +566,7 @@ fn test_requirement_diagram_comprehensive_pretty_print() {
         title: Some("System Requirements".to_string()),
         accessibility: AccessibilityInfo::default(),
         requirements: {
-            let mut map = HashMap::new();
+            let mut map = OrderedMap::new();
             map.insert(
                 "REQ-1".to_string(),
                 Requirement {
@@ -542,7 +592,7 @@ fn test_requirement_diagram_comprehensive_pretty_print() {
             map
         },
         elements: {
-            let mut map = HashMap::new();
+            let mut map = OrderedMap::new();
             map.insert(
                 "SYS-1".to_string(),
                 Element {
@@ -558,6 +608,7 @@ fn test_requirement_diagram_comprehensive_pretty_print() {
             target: "SYS-1".to_string(),
             relationship_type: RelationshipType::Satisfies,
         }],
+        styles: vec![],
     });
 
     let output = diagram.to_mermaid();
@@ -610,7 +661,7 @@ fn test_packet_diagram_comprehensive_pretty_print() {
 // Test ER diagram with comprehensive features
 #[test]
 fn test_er_diagram_comprehensive_pretty_print() {
-    let mut entities = HashMap::new();
+    let mut entities = OrderedMap::new();
     entities.insert(
         "Customer".to_string(),
         Entity {
@@ -635,6 +686,8 @@ fn test_er_diagram_comprehensive_pretty_print() {
                     comment: None,
                 },
             ],
+            css_class: None,
+            display_name: None,
         },
     );
 
@@ -662,6 +715,8 @@ fn test_er_diagram_comprehensive_pretty_print() {
                     comment: None,
                 },
             ],
+            css_class: None,
+            display_name: None,
         },
     );
 
@@ -682,6 +737,8 @@ fn test_er_diagram_comprehensive_pretty_print() {
             },
             label: Some("places".to_string()),
         }],
+        styles: Vec::new(),
+        class_defs: HashMap::new(),
     });
 
     let output = diagram.to_mermaid();
@@ -717,6 +774,8 @@ fn test_pretty_printer_write_method() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let output = diagram.to_mermaid_pretty(&options);
@@ -1,4 +1,8 @@
+mod common;
+
 use mermaid_parser::{parse_diagram, DiagramType, MermaidPrinter, PrintOptions};
+use rstest::*;
+use std::path::PathBuf;
 
 #[test]
 fn test_flowchart_basic_pretty_print() {
@@ -27,6 +31,8 @@ fn test_flowchart_pretty_print_with_options() {
         align_arrows: true,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let output = diagram.to_mermaid_pretty(&options);
@@ -45,6 +51,40 @@ fn test_flowchart_pretty_print_with_options() {
     );
 }
 
+#[test]
+fn test_flowchart_quoted_label_round_trip() {
+    let input = "flowchart TD\nA[\"x --> y\"] --> B";
+
+    let diagram = parse_diagram(input).expect("Failed to parse diagram");
+    let output = diagram.to_mermaid();
+    assert!(output.contains("A[\"x --> y\"] --> B"));
+
+    let reparsed = parse_diagram(&output).expect("Failed to reparse pretty-printed output");
+    assert_eq!(diagram, reparsed);
+}
+
+#[test]
+fn test_printers_escape_special_characters() {
+    let flowchart = parse_diagram("flowchart TD\nA[\"wait; retry\"] --> B").unwrap();
+    let output = flowchart.to_mermaid();
+    assert!(output.contains("A[\"wait; retry\"] --> B"));
+    assert_eq!(parse_diagram(&output).unwrap(), flowchart);
+
+    let sankey = parse_diagram("sankey-beta\n\"Acme, Inc\",\"Say \"\"hi\"\"\",5").unwrap();
+    let output = sankey.to_mermaid();
+    assert!(output.contains("\"Acme, Inc\",\"Say \"\"hi\"\"\",5"));
+    assert_eq!(parse_diagram(&output).unwrap(), sankey);
+
+    let DiagramType::Flowchart(mut quoted) = parse_diagram("flowchart TD\nA --> B").unwrap() else {
+        panic!("Expected flowchart");
+    };
+    quoted.nodes.get_mut("A").unwrap().text = Some("say \"hi\"".to_string());
+    quoted.edges[0].label = Some("yes|no".to_string());
+    let output = quoted.to_mermaid();
+    assert!(output.contains("A[\"say #quot;hi#quot;\"]"));
+    assert!(output.contains("|yes#124;no|"));
+}
+
 #[test]
 fn test_sequence_diagram_pretty_print() {
     let input = "sequenceDiagram\nAlice->>Bob: Hello Bob!\nBob-->>Alice: Hi Alice!\nloop Every minute\nAlice->>Bob: How are you?\nend";
@@ -150,6 +190,8 @@ fn test_compact_mode() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: true,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let output = diagram.to_mermaid_pretty(&options);
@@ -300,6 +342,8 @@ fn test_sorted_nodes_option() {
         align_arrows: true,
         sort_nodes: true,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let output = diagram.to_mermaid_pretty(&options);
@@ -330,6 +374,8 @@ fn test_arrow_alignment() {
         align_arrows: true,
         sort_nodes: false,
         compact_mode: false,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let output = diagram.to_mermaid_pretty(&options);
@@ -350,3 +396,159 @@ fn test_arrow_alignment() {
         }
     }
 }
+
+#[test]
+fn test_flowchart_minify() {
+    let input = "flowchart LR\nA[Start]-->B{Decision}\nB-->|Yes|C[Process]\nB-->|Yes|D[End]\nC-->D\nE-->D\nX[X]-->C";
+    let diagram = match parse_diagram(input).expect("Failed to parse diagram") {
+        DiagramType::Flowchart(flowchart) => flowchart,
+        other => panic!("Expected flowchart, got {:?}", other),
+    };
+
+    let (output, renames) = diagram.to_mermaid_minified();
+    assert_eq!(
+        output,
+        "graph LR\na[Start]-->b{Decision}\nb-->|Yes|c[Process]&d[End]\nc&E-->d\nX-->c"
+    );
+    // Nodes shown by their own id keep it
+    let renamed: Vec<(&str, &str)> = renames
+        .iter()
+        .map(|(old, new)| (old.as_str(), new.as_str()))
+        .collect();
+    assert_eq!(
+        renamed,
+        vec![("A", "a"), ("B", "b"), ("C", "c"), ("D", "d")]
+    );
+
+    let options = PrintOptions {
+        minify: true,
+        ..Default::default()
+    };
+    let flowchart = DiagramType::Flowchart(diagram);
+    assert_eq!(flowchart.to_mermaid_pretty(&options), output);
+    assert!(output.len() < flowchart.to_mermaid().len());
+}
+
+#[test]
+fn test_flowchart_minify_skips_taken_ids_and_defaults() {
+    use mermaid_parser::common::ast::{
+        AccessibilityInfo, ClickAction, ClickEvent, FlowDirection, FlowNode, FlowchartDiagram,
+        NodeShape,
+    };
+
+    let mut diagram = FlowchartDiagram {
+        title: None,
+        accessibility: AccessibilityInfo::default(),
+        direction: FlowDirection::TB,
+        nodes: Default::default(),
+        edges: vec![],
+        subgraphs: vec![],
+        styles: vec![],
+        class_defs: Default::default(),
+        clicks: vec![],
+    };
+    for (id, text) in [("first", "One"), ("second", "Two"), ("a", "a")] {
+        diagram.nodes.insert(
+            id.to_string(),
+            FlowNode {
+                id: id.to_string(),
+                text: Some(text.to_string()),
+                shape: NodeShape::Rectangle,
+                classes: vec![],
+                icon: None,
+            },
+        );
+    }
+    diagram.clicks.push(ClickEvent {
+        node_id: "first".to_string(),
+        action: ClickAction::Href("https://example.com".to_string(), Some("_self".to_string())),
+    });
+
+    let (output, renames) = diagram.to_mermaid_minified();
+    assert_eq!(renames["first"], "b");
+    assert_eq!(renames["second"], "c");
+    assert_eq!(
+        output,
+        "graph\na&b[One]&c[Two]\nclick b \"https://example.com\""
+    );
+}
+
+#[rstest]
+fn test_printing_is_idempotent_across_diagram_types(#[files("test/*/*.mermaid")] path: PathBuf) {
+    use mermaid_parser::common::pretty_print::check_idempotent;
+
+    let content = common::read_and_clean_test_file(&path);
+
+    // Files testing error conditions have nothing to print
+    let Ok(diagram) = parse_diagram(&content) else {
+        return;
+    };
+    for options in [
+        PrintOptions::default(),
+        PrintOptions {
+            indent_width: 2,
+            ..Default::default()
+        },
+    ] {
+        if let Err(error) = check_idempotent(&diagram, &options) {
+            panic!("{:?}\n{}\n{:?}", path, error, error);
+        }
+        assert!(diagram.is_idempotent(&options));
+    }
+}
+
+#[test]
+fn test_check_idempotent_reports_unparseable_output() {
+    use mermaid_parser::common::ast::{MiscContent, MiscDiagram, RawDiagram};
+    use mermaid_parser::common::pretty_print::{check_idempotent, IdempotenceError};
+
+    let diagram = DiagramType::Misc(MiscDiagram {
+        diagram_type: "unknown".to_string(),
+        content: MiscContent::Raw(RawDiagram {
+            lines: vec!["not a diagram".to_string()],
+        }),
+    });
+    match check_idempotent(&diagram, &PrintOptions::default()) {
+        Err(IdempotenceError::Reparse { printed, .. }) => assert_eq!(printed, "not a diagram"),
+        other => panic!("expected a reparse failure, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_print_presets_per_diagram_kind() {
+    use mermaid_parser::common::pretty_print::ALIGN_ARROWS_MAX_EDGES;
+    use mermaid_parser::stable::DiagramKind;
+    use mermaid_parser::PrintPresets;
+
+    let sequence =
+        parse_diagram("sequenceDiagram\n    loop Poll\n        A->>B: Hi\n    end").unwrap();
+    let options = PrintOptions::for_diagram(&sequence);
+    assert_eq!(options.indent_width, 2);
+    assert!(sequence
+        .to_mermaid_pretty(&options)
+        .contains("\n  loop Poll\n    A ->> B: Hi\n"));
+
+    let small = parse_diagram("flowchart TD\n    A --> B").unwrap();
+    assert!(PrintOptions::for_diagram(&small).align_arrows);
+    let edges: String = (0..=ALIGN_ARROWS_MAX_EDGES)
+        .map(|i| format!("\n    N{} --> N{}", i, i + 1))
+        .collect();
+    let large = parse_diagram(&format!("flowchart TD{}", edges)).unwrap();
+    assert!(!PrintOptions::for_diagram(&large).align_arrows);
+    assert!(!PrintOptions::preset(DiagramKind::Pie).align_arrows);
+
+    let mut presets = PrintPresets::new();
+    assert_eq!(presets.names().collect::<Vec<_>>(), ["compact", "default"]);
+    presets.register(
+        "wide",
+        PrintOptions {
+            indent_width: 8,
+            ..Default::default()
+        },
+    );
+    let wide = presets.get("wide").unwrap().clone();
+    presets.set_for_kind(DiagramKind::Sequence, wide);
+    assert_eq!(presets.for_diagram(&sequence).indent_width, 8);
+    assert_eq!(presets.for_kind(DiagramKind::Flowchart).indent_width, 4);
+    assert!(presets.get("missing").is_none());
+}
@@ -13,6 +13,8 @@ mod tests {
             align_arrows: false,
             sort_nodes: false,
             compact_mode: false,
+            minify: false,
+            normalize_keywords: false,
         };
 
         // Test by pretty printing a simple flowchart
@@ -34,6 +36,8 @@ mod tests {
             align_arrows: false,
             sort_nodes: false,
             compact_mode: true,
+            minify: false,
+            normalize_keywords: false,
         };
 
         let input = "flowchart TD\nA[Start] --> B[End]";
@@ -103,7 +107,7 @@ mod tests {
         assert!(output.contains("A[Rectangle]"));
         assert!(output.contains("B(Round)"));
         assert!(output.contains("C{Diamond}"));
-        assert!(output.contains("D((Circle)))"));
+        assert!(output.lines().any(|line| line.trim() == "D((Circle))"));
         assert!(output.contains("E(((Triple)))"));
         assert!(output.contains("F[[Subroutine]]"));
         assert!(output.contains("G{{Hexagon}}"));
@@ -282,6 +286,8 @@ mod tests {
             align_arrows: false,
             sort_nodes: true,
             compact_mode: false,
+            minify: false,
+            normalize_keywords: false,
         };
 
         let input = "flowchart TD\nC[Node C]\nA[Node A]\nB[Node B]";
@@ -1,5 +1,6 @@
 //! Additional tests to improve coverage for quadrant.rs parser
 
+use mermaid_parser::common::validation::{DiagramValidator, QuadrantValidator};
 use mermaid_parser::error::ParseError;
 use mermaid_parser::parsers::quadrant;
 
@@ -53,15 +54,17 @@ fn test_accessibility_with_space_syntax() {
 
 #[test]
 fn test_axis_definition_edge_cases() {
-    // Test axis without arrow
+    // Test axis without arrow: a single start label
     let input1 = r#"quadrantChart
-    x-axis Bad axis definition
+    x-axis Single axis label
     y-axis Low --> High"#;
 
     let result1 = quadrant::parse(input1);
     assert!(result1.is_ok());
     let diagram1 = result1.unwrap();
-    assert!(diagram1.x_axis.is_none()); // Should be None due to no arrow
+    let x_axis = diagram1.x_axis.unwrap();
+    assert_eq!(x_axis.label_start, Some("Single axis label".to_string()));
+    assert_eq!(x_axis.label_end, None);
     assert!(diagram1.y_axis.is_some());
 
     // Test axis with empty labels
@@ -159,10 +162,17 @@ fn test_coordinate_range_validation() {
     assert!(result.is_ok());
     let diagram = result.unwrap();
 
-    // Only Points A and B should be valid (within 0.0-1.0 range)
-    assert_eq!(diagram.points.len(), 2);
-    assert_eq!(diagram.points[0].name, "Point A");
-    assert_eq!(diagram.points[1].name, "Point B");
+    // Out-of-range points are kept and reported by validation
+    assert_eq!(diagram.points.len(), 6);
+    assert_eq!(diagram.points[2].x, -0.1);
+
+    let errors = QuadrantValidator::new().validate(&diagram).unwrap_err();
+    let flagged: Vec<_> = errors
+        .iter()
+        .filter(|error| error.rule == "quadrant_point_out_of_range")
+        .map(|error| error.location.as_ref().unwrap().element_id.clone().unwrap())
+        .collect();
+    assert_eq!(flagged, ["Point C", "Point D", "Point E", "Point F"]);
 }
 
 #[test]
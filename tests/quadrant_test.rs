@@ -101,7 +101,7 @@ fn test_coordinate_validation() {
     let diagram = quadrant::parse(input).unwrap();
     assert_eq!(diagram.points.len(), 3);
 
-    // Invalid coordinates should be ignored
+    // Out-of-range coordinates
     let input_invalid = r#"quadrantChart
     Point A: [0.3, 0.6]
     Point B: [-0.1, 0.5]
@@ -110,9 +110,9 @@ fn test_coordinate_validation() {
 "#;
 
     let diagram_invalid = quadrant::parse(input_invalid).unwrap();
-    // Only Point A should be valid
-    assert_eq!(diagram_invalid.points.len(), 1);
-    assert_eq!(diagram_invalid.points[0].name, "Point A");
+    // Out-of-range coordinates are kept for validation to report
+    assert_eq!(diagram_invalid.points.len(), 4);
+    assert_eq!(diagram_invalid.points[3].x, 1.5);
 }
 
 #[test]
@@ -201,3 +201,52 @@ fn test_data_point_with_class() {
     assert_eq!(diagram.styles.len(), 1);
     assert_eq!(diagram.styles[0].name, "important");
 }
+
+#[test]
+fn test_single_ended_axis_round_trip() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input = "quadrantChart\n    x-axis Reach\n    y-axis \"Low\" --> \"High\"";
+    let diagram = quadrant::parse(input).unwrap();
+
+    let x_axis = diagram.x_axis.as_ref().unwrap();
+    assert_eq!(x_axis.label_start, Some("Reach".to_string()));
+    assert_eq!(x_axis.label_end, None);
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("x-axis Reach\n"), "{}", printed);
+    assert!(
+        printed.contains("y-axis \"Low\" --> \"High\""),
+        "{}",
+        printed
+    );
+    assert_eq!(quadrant::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_out_of_range_points() {
+    use mermaid_parser::common::validation::{DiagramValidator, QuadrantValidator};
+
+    let input = r#"quadrantChart
+    Alpha: [1.0, 2.0]
+    Beta: [-1.0, 0.5]
+    Gamma: [0.5, 0.5]
+"#;
+
+    let mut diagram = quadrant::parse(input).unwrap();
+    let errors = QuadrantValidator::new().validate(&diagram).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|error| error.rule == "quadrant_point_out_of_range"));
+
+    let report = diagram.normalize();
+    assert_eq!(report.rescales.len(), 2);
+    assert_eq!(report.rescales[0].from, (-1.0, 1.0));
+    assert_eq!(report.rescales[1].from, (0.0, 2.0));
+    let coordinates: Vec<_> = diagram.points.iter().map(|p| (p.x, p.y)).collect();
+    assert_eq!(coordinates, vec![(1.0, 1.0), (0.0, 0.25), (0.75, 0.25)]);
+    assert!(QuadrantValidator::new().validate(&diagram).is_ok());
+
+    assert!(!diagram.normalize().changed());
+}
@@ -252,3 +252,28 @@ fn test_empty_radar_should_fail() {
     assert_eq!(diagram.datasets.len(), 0);
     assert_eq!(diagram.axes.len(), 0);
 }
+
+#[test]
+fn test_out_of_range_values() {
+    use mermaid_parser::common::validation::{DiagramValidator, RadarValidator};
+
+    let input = r#"radar
+    ds Team
+    "Speed" : 150
+    "Power" : 60
+"#;
+
+    let mut diagram = radar::parse(input).unwrap();
+    let errors = RadarValidator::new().validate(&diagram).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].rule, "radar_value_out_of_range");
+    assert!(errors[0].message.contains("'Speed'"));
+
+    let report = diagram.normalize();
+    assert_eq!(report.rescales[0].from, (0.0, 150.0));
+    assert_eq!(report.rescales[0].to, (0.0, 100.0));
+    assert_eq!(report.values.len(), 2);
+    assert_eq!(report.values[1].field, "Power");
+    assert_eq!(diagram.datasets[0].values, vec![100.0, 40.0]);
+    assert!(RadarValidator::new().validate(&diagram).is_ok());
+}
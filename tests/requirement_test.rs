@@ -1,6 +1,6 @@
 use chumsky::Parser;
 use mermaid_parser::common::ast::{
-    RelationshipType, RequirementType, RiskLevel, VerificationMethod,
+    RelationshipType, RequirementType, RiskLevel, StyleTarget, VerificationMethod,
 };
 use mermaid_parser::parsers::requirement;
 use rstest::*;
@@ -254,3 +254,48 @@ req2 <- copies - req1
     assert_eq!(rel.target, "req2");
     assert_eq!(rel.relationship_type, RelationshipType::Copies);
 }
+
+#[test]
+fn test_styles_and_reversed_relationships() {
+    let input = r#"requirementDiagram
+
+requirement req1 {
+    id: 1
+    text: first
+}
+
+element sys {
+    type: system
+}
+
+sys <- satisfies - req1
+req1 - traces -> sys
+style req1,sys fill:#f9f,stroke:#333
+"#;
+
+    let diagram = requirement::parse(input).unwrap();
+
+    assert_eq!(diagram.relationships.len(), 2);
+    for rel in &diagram.relationships {
+        assert_eq!(rel.source, "req1");
+        assert_eq!(rel.target, "sys");
+    }
+    assert_eq!(
+        diagram.relationships[0].relationship_type,
+        RelationshipType::Satisfies
+    );
+
+    assert_eq!(diagram.styles.len(), 2);
+    assert_eq!(
+        diagram.styles[0].target,
+        StyleTarget::Node("req1".to_string())
+    );
+    assert_eq!(
+        diagram.styles[1].target,
+        StyleTarget::Node("sys".to_string())
+    );
+    assert_eq!(
+        diagram.styles[1].styles.get("fill").map(String::as_str),
+        Some("#f9f")
+    );
+}
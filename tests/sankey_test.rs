@@ -150,3 +150,41 @@ fn test_empty_sankey() {
         _ => panic!("Expected Sankey diagram"),
     }
 }
+
+#[test]
+fn test_sankey_node_metadata_and_extra_columns() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sankey-beta
+Solar,color=#f5c542,group=renewable
+Solar,Grid,120,#ff0000,future column
+Grid,Homes,80
+Homes,"label=Homes, offices"
+"#;
+
+    let diagram = match parse_diagram(input).expect("Failed to parse sankey metadata") {
+        mermaid_parser::DiagramType::Sankey(diagram) => diagram,
+        _ => panic!("Expected Sankey diagram"),
+    };
+    assert_eq!(diagram.nodes.len(), 3);
+    assert_eq!(diagram.links.len(), 2);
+    assert_eq!(diagram.links[0].value, 120.0);
+
+    let node = |name: &str| diagram.nodes.iter().find(|n| n.name == name).unwrap();
+    assert_eq!(node("Solar").metadata["color"], "#f5c542");
+    assert_eq!(node("Solar").metadata["group"], "renewable");
+    assert_eq!(node("Homes").metadata["label"], "Homes, offices");
+    assert!(node("Grid").metadata.is_empty());
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("Solar,color=#f5c542,group=renewable\n"));
+    assert!(printed.contains("Homes,\"label=Homes, offices\"\n"));
+    match parse_diagram(&printed).unwrap() {
+        mermaid_parser::DiagramType::Sankey(reparsed) => {
+            let solar = reparsed.nodes.iter().find(|n| n.name == "Solar").unwrap();
+            assert_eq!(solar.metadata, node("Solar").metadata);
+            assert_eq!(reparsed.links, diagram.links);
+        }
+        _ => panic!("Expected Sankey diagram"),
+    }
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "schema")]
+
+use mermaid_parser::parse_diagram;
+use mermaid_parser::schema;
+use mermaid_parser::stable::DiagramKind;
+
+#[test]
+fn test_diagram_schema_describes_serialized_ast() {
+    let schema = serde_json::to_value(schema::diagram_schema()).unwrap();
+    let variants = schema["oneOf"].as_array().unwrap();
+    assert_eq!(variants.len(), 23);
+    assert!(variants
+        .iter()
+        .any(|variant| variant["required"][0] == "Pie"));
+
+    let pie = &schema["definitions"]["PieDiagram"]["properties"];
+    let serialized =
+        serde_json::to_value(parse_diagram("pie title Pets\n    \"Dogs\" : 386").unwrap()).unwrap();
+    for field in serialized["Pie"].as_object().unwrap().keys() {
+        assert!(pie.get(field).is_some(), "missing {}", field);
+    }
+}
+
+#[test]
+fn test_schema_for_kind() {
+    let json = schema::to_json(&schema::schema_for_kind(DiagramKind::Flowchart));
+    assert!(json.contains("\"title\": \"FlowchartDiagram\""));
+    assert!(json.contains("\"FlowNode\""));
+}
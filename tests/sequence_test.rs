@@ -65,18 +65,16 @@ fn test_sequence_files(#[files("test/sequence/*.mermaid")] path: PathBuf) {
                 // Every message should reference valid participants
                 for statement in &diagram.statements {
                     use mermaid_parser::common::ast::SequenceStatement;
-                    match statement {
-                        SequenceStatement::Message(msg) => {
-                            // The participants in messages are automatically added if not declared
-                            // So we just check that from and to are not empty
-                            assert!(
-                                !msg.from.is_empty(),
-                                "Message has empty 'from' in {:?}",
-                                path
-                            );
-                            assert!(!msg.to.is_empty(), "Message has empty 'to' in {:?}", path);
-                        }
-                        _ => {} // Other statement types are valid
+                    // Other statement types are valid
+                    if let SequenceStatement::Message(msg) = statement {
+                        // The participants in messages are automatically added if not declared
+                        // So we just check that from and to are not empty
+                        assert!(
+                            !msg.from.is_empty(),
+                            "Message has empty 'from' in {:?}",
+                            path
+                        );
+                        assert!(!msg.to.is_empty(), "Message has empty 'to' in {:?}", path);
                     }
                 }
             }
@@ -142,6 +140,195 @@ fn test_actor_declaration() {
     assert_eq!(bob.unwrap().alias, Some("B".to_string()));
 }
 
+#[test]
+fn test_resolve_actor_and_normalize_aliases() {
+    use mermaid_parser::common::ast::{ParticipantNaming, SequenceStatement};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sequenceDiagram
+    A->>Bob: implicit first
+    actor A as Alice
+    participant Bob as B
+    Alice->>B: hello
+    note over Alice,B: both
+    activate Alice"#;
+
+    let mut diagram = sequence::parse(input).unwrap();
+    assert_eq!(diagram.participants.len(), 2);
+
+    let alice = diagram.resolve_actor("Alice").unwrap();
+    assert_eq!(alice.actor, "A");
+    assert_eq!(alice.participant_type, ParticipantType::Actor);
+    assert_eq!(diagram.resolve_actor("A"), Some(alice));
+    assert_eq!(diagram.resolve_actor("B").unwrap().actor, "Bob");
+    assert!(diagram.resolve_actor("Carol").is_none());
+
+    let references = |diagram: &mermaid_parser::common::ast::SequenceDiagram| {
+        diagram
+            .statements
+            .iter()
+            .map(|statement| match statement {
+                SequenceStatement::Message(msg) => format!("{}->{}", msg.from, msg.to),
+                SequenceStatement::Note(note) => note.actor.clone(),
+                SequenceStatement::Activate(actor) => actor.clone(),
+                other => panic!("Unexpected statement {:?}", other),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(references(&diagram), vec!["A->Bob", "A->Bob", "A,Bob", "A"]);
+
+    let printed = DiagramType::Sequence(diagram.clone()).to_mermaid();
+    assert!(printed.contains("actor A as Alice"));
+    match parse_diagram(&printed).unwrap() {
+        DiagramType::Sequence(reparsed) => assert_eq!(reparsed, diagram),
+        other => panic!("Expected sequence diagram, got {:?}", other),
+    }
+
+    diagram.normalize_participant_references(ParticipantNaming::DisplayName);
+    assert_eq!(
+        references(&diagram),
+        vec!["Alice->B", "Alice->B", "Alice,B", "Alice"]
+    );
+}
+
+#[test]
+fn test_remove_and_reorder_participants() {
+    use mermaid_parser::common::ast::{
+        ArrowType, ParticipantRemoval, PassThroughMessages, SequenceDiagram, SequenceStatement,
+    };
+
+    let input = r#"sequenceDiagram
+    participant A
+    participant Proxy as P
+    participant C
+    A->>P: request
+    P->>C: forward
+    C-->>P: reply
+    P-->>A: respond
+    note over P,C: relayed
+    activate P
+    loop Retry
+        P->>P: backoff
+    end
+    opt Audit
+        P->>A: log
+    end"#;
+
+    let summary = |diagram: &SequenceDiagram| {
+        fn walk(statements: &[SequenceStatement], out: &mut Vec<String>) {
+            for statement in statements {
+                match statement {
+                    SequenceStatement::Message(msg) => {
+                        out.push(format!("{}->{}", msg.from, msg.to))
+                    }
+                    SequenceStatement::Note(note) => out.push(format!("note {}", note.actor)),
+                    SequenceStatement::Activate(actor) => out.push(format!("activate {}", actor)),
+                    SequenceStatement::Loop(block) => {
+                        out.push("loop".to_string());
+                        walk(&block.statements, out);
+                    }
+                    SequenceStatement::Opt(block) => {
+                        out.push("opt".to_string());
+                        walk(&block.statements, out);
+                    }
+                    other => panic!("Unexpected statement {:?}", other),
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(&diagram.statements, &mut out);
+        out
+    };
+
+    let original = sequence::parse(input).unwrap();
+
+    let mut dropped = original.clone();
+    assert!(dropped.remove_participant("P", ParticipantRemoval::DropMessages));
+    let actors: Vec<&str> = dropped
+        .participants
+        .iter()
+        .map(|p| p.actor.as_str())
+        .collect();
+    assert_eq!(actors, vec!["A", "C"]);
+    assert_eq!(summary(&dropped), vec!["note C"]);
+
+    let mut rewired = original.clone();
+    assert!(rewired.remove_participant("Proxy", ParticipantRemoval::Rewire("C".to_string())));
+    assert_eq!(
+        summary(&rewired),
+        vec!["A->C", "C->A", "note C", "loop", "C->C", "opt", "C->A"]
+    );
+
+    let mut unchanged = original.clone();
+    assert!(!unchanged.remove_participant("Nobody", ParticipantRemoval::DropMessages));
+    assert!(!unchanged.remove_participant("P", ParticipantRemoval::Rewire("Proxy".to_string())));
+    assert_eq!(unchanged, original);
+
+    let mut reordered = original.clone();
+    assert!(reordered.reorder_participants(&["C", "A"]));
+    let actors: Vec<&str> = reordered
+        .participants
+        .iter()
+        .map(|p| p.actor.as_str())
+        .collect();
+    assert_eq!(actors, vec!["C", "A", "Proxy"]);
+    assert_eq!(reordered.statements, original.statements);
+    assert!(!reordered.reorder_participants(&["Z"]));
+
+    let view = original.filtered(&["A", "C", "Nobody"]);
+    let actors: Vec<&str> = view.participants.iter().map(|p| p.actor.as_str()).collect();
+    assert_eq!(actors, vec!["A", "C"]);
+    assert_eq!(summary(&view), vec!["note C"]);
+
+    let view = original.filtered_with(&["A", "C"], PassThroughMessages::Collapse);
+    assert_eq!(summary(&view), vec!["A->C", "C->A", "note C"]);
+    match &view.statements[0] {
+        SequenceStatement::Message(message) => {
+            assert_eq!(message.text, "request");
+            assert_eq!(message.arrow_type, ArrowType::SolidClosed);
+        }
+        other => panic!("Expected a message, got {:?}", other),
+    }
+    assert_eq!(original.filtered(&["Proxy"]).participants.len(), 1);
+}
+
+#[test]
+fn test_message_text_with_arrow_sequences() {
+    use mermaid_parser::common::ast::{ArrowType, SequenceStatement};
+
+    let input = "sequenceDiagram\n    Alice->>Bob: go -->> there";
+    let diagram = sequence::parse(input).unwrap();
+
+    match &diagram.statements[0] {
+        SequenceStatement::Message(msg) => {
+            assert_eq!(msg.from, "Alice");
+            assert_eq!(msg.to, "Bob");
+            assert_eq!(msg.text, "go -->> there");
+            assert_eq!(msg.arrow_type, ArrowType::SolidClosed);
+        }
+        other => panic!("Expected message, got {:?}", other),
+    }
+    assert_eq!(diagram.participants.len(), 2);
+}
+
+#[test]
+fn test_message_text_with_colons_and_semicolons() {
+    use mermaid_parser::common::ast::SequenceStatement;
+
+    let input = "sequenceDiagram\n    A->>B: Result: 42; B-->>A: ok\n    A->>B: \"x; y\"";
+    let diagram = sequence::parse(input).unwrap();
+
+    let texts: Vec<&str> = diagram
+        .statements
+        .iter()
+        .filter_map(|statement| match statement {
+            SequenceStatement::Message(msg) => Some(msg.text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, vec!["Result: 42", "ok", "\"x; y\""]);
+}
+
 #[test]
 fn test_arrow_types() {
     let tests = vec![
@@ -274,3 +461,321 @@ fn test_complex_example() {
     let result = sequence::parse(input);
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_quoted_participant_names() {
+    use mermaid_parser::common::ast::SequenceStatement;
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sequenceDiagram
+    participant "Web Server 🚀" as WS
+    participant "Cache as a service"
+    WS->>"Cache as a service": get: key
+    "Web Server 🚀"-->>DB: query
+    note over "Web Server 🚀": ready
+    activate "Cache as a service""#;
+
+    let diagram = sequence::parse(input).unwrap();
+    let actors: Vec<&str> = diagram
+        .participants
+        .iter()
+        .map(|p| p.actor.as_str())
+        .collect();
+    assert_eq!(actors, vec!["Web Server 🚀", "Cache as a service", "DB"]);
+    assert_eq!(diagram.participants[0].alias.as_deref(), Some("WS"));
+
+    match &diagram.statements[..] {
+        [SequenceStatement::Message(first), SequenceStatement::Message(second), SequenceStatement::Note(note), SequenceStatement::Activate(actor)] =>
+        {
+            assert_eq!(
+                (first.from.as_str(), first.to.as_str()),
+                ("Web Server 🚀", "Cache as a service")
+            );
+            assert_eq!(first.text, "get: key");
+            assert_eq!(second.from, "Web Server 🚀");
+            assert_eq!(note.actor, "Web Server 🚀");
+            assert_eq!(actor, "Cache as a service");
+        }
+        other => panic!("Unexpected statements {:?}", other),
+    }
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("participant \"Web Server 🚀\" as WS"));
+    assert!(printed.contains("\"Web Server 🚀\" ->> \"Cache as a service\": get: key"));
+    assert!(printed.contains("participant DB"));
+    assert_eq!(sequence::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_participant_boxes() {
+    use mermaid_parser::common::ast::{Color, ParticipantBox, ParticipantRemoval};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sequenceDiagram
+    box Purple Alice & John
+    participant A
+    participant J
+    end
+    box rgb(33, 66, 99) Backend
+    actor B
+    end
+    box Another Group
+    participant C
+    end
+    participant D
+    A->>J: Hello John
+    B->>C: Hello Charley
+    C->>D: Hi"#;
+
+    let diagram = sequence::parse(input).unwrap();
+    let actors: Vec<&str> = diagram
+        .participants
+        .iter()
+        .map(|p| p.actor.as_str())
+        .collect();
+    assert_eq!(actors, vec!["A", "J", "B", "C", "D"]);
+    assert_eq!(
+        diagram.boxes,
+        vec![
+            ParticipantBox {
+                color: Some(Color::Named("Purple".to_string())),
+                title: Some("Alice & John".to_string()),
+                participants: vec!["A".to_string(), "J".to_string()],
+            },
+            ParticipantBox {
+                color: Some(Color::Function("rgb(33, 66, 99)".to_string())),
+                title: Some("Backend".to_string()),
+                participants: vec!["B".to_string()],
+            },
+            ParticipantBox {
+                color: None,
+                title: Some("Another Group".to_string()),
+                participants: vec!["C".to_string()],
+            },
+        ]
+    );
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains(
+        "box Purple Alice & John\n        participant A\n        participant J\n    end"
+    ));
+    assert!(printed.contains("box rgb(33, 66, 99) Backend"));
+    assert_eq!(sequence::parse(&printed).unwrap(), diagram);
+
+    let mut without_b = diagram.clone();
+    assert!(without_b.remove_participant("B", ParticipantRemoval::DropMessages));
+    assert_eq!(without_b.boxes.len(), 2);
+    assert!(!without_b.to_mermaid().contains("Backend"));
+}
+
+#[test]
+fn test_rect_blocks() {
+    use mermaid_parser::common::ast::{Color, SequenceStatement};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sequenceDiagram
+    participant Alice
+    participant John
+    rect rgb(191, 223, 255)
+    note right of Alice: Alice calls John.
+    Alice->>John: Hello John
+    loop Every minute
+        rect rgba(0, 0, 255, .1)
+        John-->>Alice: Still here
+        end
+    end
+    end
+    alt is sick
+        rect LightYellow
+        Alice->>John: Get well
+        end
+    else is well
+        Alice->>John: Great
+    end"#;
+
+    let diagram = sequence::parse(input).unwrap();
+    assert_eq!(diagram.statements.len(), 2);
+
+    let SequenceStatement::Rect { color, statements } = &diagram.statements[0] else {
+        panic!("Expected rect, got {:?}", diagram.statements[0]);
+    };
+    assert_eq!(color, &Color::Function("rgb(191, 223, 255)".to_string()));
+    assert!(matches!(statements[0], SequenceStatement::Note(_)));
+    assert!(matches!(statements[1], SequenceStatement::Message(_)));
+    let SequenceStatement::Loop(block) = &statements[2] else {
+        panic!("Expected loop, got {:?}", statements[2]);
+    };
+    assert!(matches!(
+        &block.statements[..],
+        [SequenceStatement::Rect { color, statements }]
+            if *color == Color::Function("rgba(0, 0, 255, .1)".to_string()) && statements.len() == 1
+    ));
+
+    let SequenceStatement::Alt(alt) = &diagram.statements[1] else {
+        panic!("Expected alt, got {:?}", diagram.statements[1]);
+    };
+    assert!(matches!(
+        &alt.statements[..],
+        [SequenceStatement::Rect { color, .. }] if *color == Color::Named("LightYellow".to_string())
+    ));
+    assert_eq!(alt.else_branch.as_ref().unwrap().statements.len(), 1);
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("    rect rgb(191, 223, 255)\n        note right of Alice"));
+    assert_eq!(sequence::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_box_and_rect_colors() {
+    use mermaid_parser::common::ast::{Color, SequenceStatement};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sequenceDiagram
+    box transparent Aqua
+    participant A
+    end
+    box #ccc
+    participant B
+    end
+    box Aqua
+    participant C
+    end
+    rect not-a-color
+    A->>B: Hi
+    end"#;
+
+    let diagram = sequence::parse(input).unwrap();
+    let boxes: Vec<(Option<&Color>, Option<&str>)> = diagram
+        .boxes
+        .iter()
+        .map(|b| (b.color.as_ref(), b.title.as_deref()))
+        .collect();
+    assert_eq!(
+        boxes,
+        vec![
+            (Some(&Color::Transparent), Some("Aqua")),
+            (Some(&Color::Hex("ccc".to_string())), None),
+            (Some(&Color::Named("Aqua".to_string())), None),
+        ]
+    );
+    assert!(matches!(
+        &diagram.statements[0],
+        SequenceStatement::Rect { color: Color::Other(text), .. } if text == "not-a-color"
+    ));
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("box transparent Aqua"), "{}", printed);
+    assert!(printed.contains("box #ccc"), "{}", printed);
+    assert_eq!(sequence::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_break_and_activation_shorthand() {
+    use mermaid_parser::common::ast::{MessageActivation, SequenceStatement};
+    use mermaid_parser::common::validation::{DiagramValidator, SequenceValidator};
+    use mermaid_parser::MermaidPrinter;
+
+    let input = r#"sequenceDiagram
+    participant Consumer
+    participant API
+    Consumer->>+API: Book something
+    API->>API: Start booking process
+    break when the booking process fails
+        API-->>Consumer: show failure
+    end
+    API-->>-Consumer: Booked"#;
+
+    let diagram = sequence::parse(input).unwrap();
+    assert_eq!(diagram.participants.len(), 2);
+    assert_eq!(diagram.statements.len(), 4);
+
+    let SequenceStatement::Message(call) = &diagram.statements[0] else {
+        panic!("Expected message, got {:?}", diagram.statements[0]);
+    };
+    assert_eq!(call.to, "API");
+    assert_eq!(call.activation, Some(MessageActivation::Activate));
+    assert_eq!(
+        call.implied_activation(),
+        Some(SequenceStatement::Activate("API".to_string()))
+    );
+
+    let SequenceStatement::Break(block) = &diagram.statements[2] else {
+        panic!("Expected break, got {:?}", diagram.statements[2]);
+    };
+    assert_eq!(block.condition, "when the booking process fails");
+    assert_eq!(block.statements.len(), 1);
+
+    let SequenceStatement::Message(reply) = &diagram.statements[3] else {
+        panic!("Expected message, got {:?}", diagram.statements[3]);
+    };
+    assert_eq!(reply.activation, Some(MessageActivation::Deactivate));
+    assert_eq!(
+        reply.implied_activation(),
+        Some(SequenceStatement::Deactivate("API".to_string()))
+    );
+
+    // The shorthand activations balance like the statements
+    assert!(SequenceValidator::new().validate(&diagram).is_ok());
+
+    let printed = diagram.to_mermaid();
+    assert!(
+        printed.contains("Consumer ->> +API: Book something"),
+        "{}",
+        printed
+    );
+    assert!(printed.contains("    break when the booking process fails\n"));
+    assert_eq!(sequence::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_flat_message_and_note_accessors() {
+    let input = r#"sequenceDiagram
+    autonumber 10 5
+    participant A as Alice
+    participant B as Bob
+    Alice->>B: Hello
+    note over A,B: Greeting
+    loop Every minute
+        B-->>A: Hi
+        alt Busy
+            A->>A: Think
+        else Free
+            A->>B: Chat
+            note right of B: Listening
+        end
+    end
+    opt Later
+        B->>A: Ping
+        A->>B: Pong
+    end"#;
+
+    let diagram = sequence::parse(input).unwrap();
+    let texts: Vec<&str> = diagram.messages().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, ["Hello", "Hi", "Think", "Chat", "Ping", "Pong"]);
+
+    let numbers: Vec<Option<i32>> = diagram.numbered_messages().map(|(n, _)| n).collect();
+    assert_eq!(
+        numbers,
+        [Some(10), Some(15), Some(20), Some(25), Some(30), Some(35)]
+    );
+
+    let notes: Vec<&str> = diagram.notes().map(|note| note.text.as_str()).collect();
+    assert_eq!(notes, ["Greeting", "Listening"]);
+
+    let pairs: Vec<(&str, Option<&str>)> = diagram
+        .interaction_pairs()
+        .map(|(request, reply)| (request.text.as_str(), reply.map(|m| m.text.as_str())))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ("Hello", Some("Hi")),
+            ("Think", None),
+            ("Chat", Some("Ping")),
+            ("Pong", None),
+        ]
+    );
+
+    let unnumbered = sequence::parse("sequenceDiagram\n    A->>B: Hi").unwrap();
+    assert_eq!(unnumbered.numbered_messages().next().unwrap().0, None);
+}
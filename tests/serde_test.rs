@@ -0,0 +1,24 @@
+#![cfg(feature = "serde")]
+
+use mermaid_parser::{parse_diagram, DiagramType};
+
+#[test]
+fn test_ast_json_round_trip() {
+    let inputs = [
+        "pie title Pets\n    \"Dogs\" : 386\n    \"Cats\" : 85",
+        "flowchart LR\n    A[Start] -->|go| B{Choice}\n    style A fill:#f9f",
+        "sequenceDiagram\n    Alice->>Bob: Hello\n    Note over Alice,Bob: Greeting",
+        "sankey-beta\nSolar,color=#f5c542\nSolar,Grid,10",
+        "%%{init: {\"theme\": \"dark\"}}%%\ninfo",
+    ];
+    for input in inputs {
+        let diagram = parse_diagram(input).unwrap();
+        let json = serde_json::to_string(&diagram).unwrap();
+        let back: DiagramType = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, diagram, "{}", json);
+    }
+
+    let pie = serde_json::to_value(parse_diagram(inputs[0]).unwrap()).unwrap();
+    assert_eq!(pie["Pie"]["title"], "Pets");
+    assert_eq!(pie["Pie"]["data"][1]["value"], 85.0);
+}
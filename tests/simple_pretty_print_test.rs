@@ -20,6 +20,8 @@ fn test_pretty_printer_with_options() {
         align_arrows: false,
         sort_nodes: false,
         compact_mode: true,
+        minify: false,
+        normalize_keywords: false,
     };
 
     let output = diagram.to_mermaid_pretty(&options);
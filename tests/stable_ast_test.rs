@@ -0,0 +1,35 @@
+use mermaid_parser::parse_diagram;
+use mermaid_parser::stable::{self, Diagram, DiagramKind, MessageArrow};
+
+#[test]
+fn test_stable_conversion_matches_parse() {
+    let input = "sequenceDiagram\n    participant A as Alice\n    A->>B: Hello";
+    let internal = parse_diagram(input).unwrap();
+
+    let from_internal = Diagram::from(&internal);
+    assert_eq!(stable::parse(input).unwrap(), from_internal);
+
+    // Downstream matches need a wildcard arm, since variants can be added
+    match from_internal {
+        Diagram::Sequence(sequence) => {
+            assert_eq!(sequence.participants[0].alias, Some("Alice".to_string()));
+            assert_eq!(sequence.messages[0].arrow, MessageArrow::SolidClosed);
+        }
+        other => panic!("Expected sequence diagram, got {:?}", other.kind()),
+    }
+}
+
+#[test]
+fn test_every_diagram_reports_kind() {
+    let cases = [
+        ("pie\n    \"A\" : 1", DiagramKind::Pie),
+        ("stateDiagram-v2\n    [*] --> A", DiagramKind::State),
+        ("classDiagram\n    class Animal", DiagramKind::Class),
+        ("sankey-beta\nA,B,1", DiagramKind::Sankey),
+        ("journey\n    title Day", DiagramKind::Journey),
+    ];
+
+    for (input, kind) in cases {
+        assert_eq!(stable::parse(input).unwrap().kind(), kind, "{}", input);
+    }
+}
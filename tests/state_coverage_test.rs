@@ -3,6 +3,7 @@
 use mermaid_parser::common::ast::{StateNotePosition, StateType, StateVersion};
 use mermaid_parser::error::ParseError;
 use mermaid_parser::parsers::state;
+use mermaid_parser::{DiagramType, MermaidPrinter};
 
 #[test]
 fn test_direction_directive() {
@@ -35,8 +36,9 @@ fn test_composite_state_braces_same_line() {
 
     let machine = &diagram.states["Machine"];
     assert_eq!(machine.state_type, StateType::Composite);
-    assert!(machine.substates.contains(&"Idle".to_string()));
-    assert!(machine.substates.contains(&"Running".to_string()));
+    assert!(machine.states.contains_key("Idle"));
+    assert!(machine.states.contains_key("Running"));
+    assert_eq!(machine.transitions.len(), 3);
 }
 
 #[test]
@@ -78,13 +80,17 @@ fn test_nested_composite_states() {
 
     let outer = &diagram.states["OuterState"];
     assert_eq!(outer.state_type, StateType::Composite);
-    assert!(outer.substates.contains(&"InnerState".to_string()));
-    assert!(outer.substates.contains(&"Done".to_string()));
+    assert!(outer.states.contains_key("InnerState"));
+    assert!(outer.states.contains_key("Done"));
+    assert_eq!(outer.transitions.len(), 1);
 
-    let inner = &diagram.states["InnerState"];
+    let inner = &outer.states["InnerState"];
     assert_eq!(inner.state_type, StateType::Composite);
-    assert!(inner.substates.contains(&"InnerFirst".to_string()));
-    assert!(inner.substates.contains(&"InnerSecond".to_string()));
+    assert!(inner.states.contains_key("InnerFirst"));
+    assert!(inner.states.contains_key("InnerSecond"));
+    assert_eq!(inner.transitions.len(), 2);
+    assert!(!diagram.states.contains_key("InnerState"));
+    assert!(diagram.transitions.is_empty());
 }
 
 #[test]
@@ -106,9 +112,84 @@ fn test_concurrent_regions() {
 
     let active = &diagram.states["Active"];
     assert_eq!(active.state_type, StateType::Composite);
-    // The concurrent separator is parsed but regions aren't tracked separately yet
-    assert!(active.substates.contains(&"NumLockOff".to_string()));
-    assert!(active.substates.contains(&"CapsLockOff".to_string()));
+    assert!(active.states.is_empty());
+
+    assert_eq!(active.concurrent_regions.len(), 2);
+    let num_lock = &active.concurrent_regions[0];
+    assert_eq!(num_lock.states.len(), 2);
+    assert!(num_lock.states.contains_key("NumLockOff"));
+    assert_eq!(num_lock.transitions.len(), 3);
+    assert_eq!(num_lock.transitions[0].from, "[*]");
+    let caps_lock = &active.concurrent_regions[1];
+    assert_eq!(caps_lock.states.len(), 2);
+    assert!(caps_lock.states.contains_key("CapsLockOn"));
+    assert_eq!(
+        caps_lock.transitions[1].event,
+        Some("EvCapsLockPressed".to_string())
+    );
+
+    // Region transitions are owned by the region, not the diagram
+    assert!(diagram.transitions.is_empty());
+    assert_eq!(diagram.all_transitions().len(), 6);
+}
+
+#[test]
+fn test_nested_composite_round_trip() {
+    let input = r#"stateDiagram-v2
+    [*] --> Vehicle
+    state Vehicle {
+        [*] --> Parked
+        Parked --> Moving : start
+        state Moving {
+            [*] --> Slow
+            Slow --> Fast
+            note right of Fast : Vroom
+        }
+    }"#;
+
+    let diagram = state::parse(input).unwrap();
+    assert_eq!(diagram.transitions.len(), 1);
+    let vehicle = &diagram.states["Vehicle"];
+    assert_eq!(vehicle.transitions.len(), 2);
+    let moving = &vehicle.states["Moving"];
+    assert_eq!(moving.transitions[1].to, "Fast");
+    assert_eq!(moving.notes[0].text, "Vroom");
+    assert_eq!(diagram.all_states().len(), 6);
+
+    let printed = DiagramType::State(diagram.clone()).to_mermaid();
+    assert!(printed.contains("    state Vehicle {\n        state Moving {\n            Fast"));
+    assert_eq!(state::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_concurrent_regions_round_trip() {
+    let input = r#"stateDiagram-v2
+    [*] --> Active
+    state Active {
+        [*] --> NumLockOff
+        NumLockOff --> NumLockOn : EvNumLockPressed
+        note right of NumLockOn : Lit
+        --
+        [*] --> CapsLockOff
+        CapsLockOff --> CapsLockOn : EvCapsLockPressed
+    }
+    Active --> [*]"#;
+
+    let diagram = state::parse(input).unwrap();
+    assert_eq!(diagram.transitions.len(), 2);
+    assert!(diagram.notes.is_empty());
+    assert_eq!(diagram.all_notes().len(), 1);
+
+    let active = &diagram.states["Active"];
+    assert_eq!(active.concurrent_regions[0].notes[0].target, "NumLockOn");
+
+    let printed = DiagramType::State(diagram.clone()).to_mermaid();
+    let reparsed = state::parse(&printed).unwrap();
+    assert_eq!(
+        reparsed.states["Active"].concurrent_regions,
+        active.concurrent_regions
+    );
+    assert_eq!(reparsed.transitions, diagram.transitions);
 }
 
 #[test]
@@ -273,21 +354,28 @@ fn test_complex_nested_composite_states() {
     // Check top-level composite
     let vehicle = &diagram.states["Vehicle"];
     assert_eq!(vehicle.state_type, StateType::Composite);
-    assert!(vehicle.substates.contains(&"Parked".to_string()));
-    assert!(vehicle.substates.contains(&"Moving".to_string()));
+    assert!(vehicle.states.contains_key("Parked"));
+    assert!(vehicle.states.contains_key("Moving"));
+    assert_eq!(vehicle.transitions.len(), 3);
 
     // Check nested composite
-    let moving = &diagram.states["Moving"];
+    let moving = &vehicle.states["Moving"];
     assert_eq!(moving.state_type, StateType::Composite);
-    assert!(moving.substates.contains(&"Accelerating".to_string()));
-    assert!(moving.substates.contains(&"Cruising".to_string()));
-    assert!(moving.substates.contains(&"Decelerating".to_string()));
+    assert!(moving.states.contains_key("Accelerating"));
+    assert!(moving.states.contains_key("Cruising"));
+    assert!(moving.states.contains_key("Decelerating"));
+    assert_eq!(moving.transitions.len(), 4);
 
     // Check deeply nested composite
-    let cruising = &diagram.states["Cruising"];
+    let cruising = &moving.states["Cruising"];
     assert_eq!(cruising.state_type, StateType::Composite);
-    assert!(cruising.substates.contains(&"NormalSpeed".to_string()));
-    assert!(cruising.substates.contains(&"HighSpeed".to_string()));
+    assert!(cruising.states.contains_key("NormalSpeed"));
+    assert!(cruising.states.contains_key("HighSpeed"));
+    assert_eq!(cruising.transitions[1].event, Some("SpeedUp".to_string()));
+
+    // Nested states are found from the diagram at any depth
+    assert_eq!(diagram.state("HighSpeed").unwrap().id, "HighSpeed");
+    assert_eq!(diagram.all_transitions().len(), 10);
 }
 
 #[test]
@@ -438,10 +526,10 @@ fn test_composite_state_with_substates_on_same_line() {
 
     let container = &diagram.states["Container"];
     assert_eq!(container.state_type, StateType::Composite);
-    assert_eq!(container.substates.len(), 3);
-    assert!(container.substates.contains(&"SubState1".to_string()));
-    assert!(container.substates.contains(&"SubState2".to_string()));
-    assert!(container.substates.contains(&"SubState3".to_string()));
+    assert_eq!(container.states.len(), 3);
+    assert!(container.states.contains_key("SubState1"));
+    assert!(container.states.contains_key("SubState2"));
+    assert!(container.states.contains_key("SubState3"));
 }
 
 #[test]
@@ -490,14 +578,14 @@ fn test_state_declaration_with_opening_brace() {
 
     let my_composite = &diagram.states["MyComposite"];
     assert_eq!(my_composite.state_type, StateType::Composite);
-    assert!(my_composite.substates.contains(&"Inner1".to_string()));
-    assert!(my_composite.substates.contains(&"Inner2".to_string()));
+    assert!(my_composite.states.contains_key("Inner1"));
+    assert!(my_composite.states.contains_key("Inner2"));
 
     let nc = &diagram.states["nc"];
     assert_eq!(nc.display_name, Some("Named Composite".to_string()));
     assert_eq!(nc.state_type, StateType::Composite);
-    assert!(nc.substates.contains(&"InnerA".to_string()));
-    assert!(nc.substates.contains(&"InnerB".to_string()));
+    assert!(nc.states.contains_key("InnerA"));
+    assert!(nc.states.contains_key("InnerB"));
 }
 
 #[test]
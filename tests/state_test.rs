@@ -35,6 +35,48 @@ fn test_state_files(#[files("test/state/*.mermaid")] path: PathBuf) {
     }
 }
 
+#[rstest]
+fn test_state_files_round_trip(#[files("test/state/*.mermaid")] path: PathBuf) {
+    use mermaid_parser::MermaidPrinter;
+
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Failed to read file: {:?}", path));
+    let content = content
+        .lines()
+        .filter(|line| !line.starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let diagram = state::parse(content.trim()).unwrap();
+    let printed = diagram.to_mermaid();
+    let reparsed = state::parse(&printed)
+        .unwrap_or_else(|e| panic!("Failed to reparse {:?}: {}\n{}", path, e, printed));
+
+    assert_eq!(
+        reparsed, diagram,
+        "Round trip changed {:?}:\n{}",
+        path, printed
+    );
+}
+
+#[test]
+fn test_start_end_markers_printed_once_from_transitions() {
+    use mermaid_parser::MermaidPrinter;
+
+    let input = "stateDiagram-v2\n    [*] --> Idle\n    Idle --> [*]";
+    let diagram = state::parse(input).unwrap();
+    let printed = diagram.to_mermaid();
+
+    assert_eq!(printed.matches("[*] --> Idle").count(), 1, "{}", printed);
+    assert_eq!(printed.matches("Idle --> [*]").count(), 1, "{}", printed);
+    assert!(
+        !printed.lines().any(|line| line.trim() == "[*]"),
+        "{}",
+        printed
+    );
+    assert_eq!(state::parse(&printed).unwrap(), diagram);
+}
+
 #[test]
 fn test_simple_state_diagram() {
     let input = r#"stateDiagram-v2
@@ -173,12 +215,13 @@ fn test_composite_states() {
 
     let moving = &diagram.states["Moving"];
     assert_eq!(moving.state_type, StateType::Composite);
-    assert!(moving.substates.contains(&"Idle".to_string()));
-    assert!(moving.substates.contains(&"Running".to_string()));
+    assert!(moving.states.contains_key("Idle"));
+    assert!(moving.states.contains_key("Running"));
 
-    // Should have states for Idle and Running
-    assert!(diagram.states.contains_key("Idle"));
-    assert!(diagram.states.contains_key("Running"));
+    // Idle and Running belong to Moving, with the transitions between them
+    assert!(!diagram.states.contains_key("Idle"));
+    assert_eq!(moving.transitions.len(), 3);
+    assert!(diagram.transitions.is_empty());
 }
 
 #[test]
@@ -285,7 +328,7 @@ fn test_complex_example() {
     // Check composite state
     let auth_state = &diagram.states["Authenticating"];
     assert_eq!(auth_state.state_type, StateType::Composite);
-    assert!(!auth_state.substates.is_empty());
+    assert!(!auth_state.states.is_empty());
 
     // Check choice state
     let choice_state = &diagram.states["choice1"];
@@ -313,3 +356,84 @@ fn test_error_cases() {
     let result = state::parse("not a state diagram");
     assert!(result.is_err());
 }
+
+#[test]
+fn test_direction_statement() {
+    use mermaid_parser::common::ast::{FlowDirection, LayoutDirection};
+    use mermaid_parser::{DiagramType, MermaidPrinter};
+
+    let input = r#"stateDiagram-v2
+    direction LR
+    state Busy {
+        direction TB
+        [*] --> Working
+    }
+    [*] --> Busy"#;
+
+    let diagram = parse_diagram(input).unwrap();
+    match &diagram {
+        DiagramType::State(state) => assert_eq!(state.direction, Some(FlowDirection::LR)),
+        other => panic!("Expected state diagram, got {:?}", other),
+    }
+    assert_eq!(
+        diagram.layout_direction(),
+        Some(LayoutDirection::LeftToRight)
+    );
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("    direction LR\n"));
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_class_definitions_and_assignments() {
+    use mermaid_parser::common::validation::UniversalValidator;
+    use mermaid_parser::{DiagramType, MermaidPrinter};
+
+    let input = r#"stateDiagram-v2
+    classDef badBadEvent fill:#f00,color:white,font-weight:bold
+    classDef movement font-style:italic
+    [*] --> Still
+    Still --> Moving:::movement : push
+    Moving --> Crash:::badBadEvent
+    state Broken {
+        Wreck:::badBadEvent
+    }
+    class Still, Crash movement"#;
+
+    let diagram = parse_diagram(input).unwrap();
+    let DiagramType::State(state) = &diagram else {
+        panic!("Expected state diagram, got {:?}", diagram);
+    };
+    assert_eq!(
+        state.class_defs["badBadEvent"].styles["font-weight"],
+        "bold"
+    );
+    assert_eq!(state.states["Moving"].classes, vec!["movement"]);
+    assert_eq!(state.states["Still"].classes, vec!["movement"]);
+    assert_eq!(
+        state.states["Crash"].classes,
+        vec!["badBadEvent", "movement"]
+    );
+    assert_eq!(
+        state.states["Broken"].states["Wreck"].classes,
+        vec!["badBadEvent"]
+    );
+    let push = &state.transitions[1];
+    assert_eq!(
+        (push.to.as_str(), push.event.as_deref()),
+        ("Moving", Some("push"))
+    );
+
+    let printed = diagram.to_mermaid();
+    assert!(printed.contains("    classDef badBadEvent color:white,fill:#f00,font-weight:bold\n"));
+    assert!(printed.contains("    class Crash badBadEvent\n"));
+    assert_eq!(parse_diagram(&printed).unwrap(), diagram);
+
+    let errors = UniversalValidator::new()
+        .validate_any(&parse_diagram("stateDiagram-v2\n    [*] --> A:::missing").unwrap())
+        .unwrap_err();
+    assert!(errors
+        .iter()
+        .any(|error| error.rule == "undefined_style_class"));
+}
@@ -0,0 +1,95 @@
+use mermaid_parser::common::stream::CHUNK_LINES;
+use mermaid_parser::{parse_diagram, parse_diagram_from_reader, DiagramType, ParseError};
+use std::io::Cursor;
+
+fn both(input: &str) -> (DiagramType, DiagramType) {
+    (
+        parse_diagram(input).unwrap(),
+        parse_diagram_from_reader(Cursor::new(input)).unwrap(),
+    )
+}
+
+#[test]
+fn test_streamed_sections_match_whole_parse() {
+    let mut timeline = String::from("---\ntitle: Ignored\n---\ntimeline\n    title History\n");
+    for section in 0..3 {
+        timeline.push_str(&format!("    section Era {}\n", section));
+        for year in 0..CHUNK_LINES {
+            timeline.push_str(&format!("        {} : Event {}\n", year, year));
+        }
+    }
+    let (whole, streamed) = both(&timeline);
+    assert_eq!(streamed, whole);
+    if let DiagramType::Timeline(diagram) = streamed {
+        assert_eq!(diagram.sections.len(), 3);
+        assert_eq!(diagram.sections[2].items.len(), 2 * CHUNK_LINES);
+    }
+
+    let mut gantt = String::from("gantt\n    dateFormat YYYY-MM-DD\n    title Plan\n");
+    for task in 0..CHUNK_LINES + 10 {
+        gantt.push_str(&format!("    Task {} : t{}, 2024-01-01, 1d\n", task, task));
+    }
+    gantt.push_str("    section Later\n    Wrap up : after t0, 2d\n");
+    let (whole, streamed) = both(&gantt);
+    assert_eq!(streamed, whole);
+}
+
+#[test]
+fn test_streamed_gantt_keeps_frontmatter_config() {
+    let mut gantt = String::from(
+        "---\nconfig:\n  gantt:\n    displayMode: compact\n---\n%%{init: {\"theme\": \"dark\"}}%%\ngantt\n    dateFormat YYYY-MM-DD\n",
+    );
+    for task in 0..CHUNK_LINES * 2 {
+        gantt.push_str(&format!("    Task {} : t{}, 2024-01-01, 1d\n", task, task));
+    }
+    let (whole, streamed) = both(&gantt);
+    assert_eq!(streamed, whole);
+    let DiagramType::Gantt(diagram) = streamed else {
+        panic!("expected a gantt diagram");
+    };
+    assert_eq!(diagram.display_mode.as_deref(), Some("compact"));
+    assert_eq!(diagram.sections[0].tasks.len(), CHUNK_LINES * 2);
+}
+
+#[test]
+fn test_streamed_pie_and_sankey() {
+    let input = "pie showData\n    title Pets\n    \"Dogs\" : 386\n    \"Cats\" : 85";
+    let (whole, streamed) = both(input);
+    assert_eq!(streamed, whole);
+
+    let mut sankey = String::from("sankey-beta\nSolar,color=#f5c542\n");
+    for i in 0..CHUNK_LINES * 2 {
+        sankey.push_str(&format!("Solar,Grid{},{}\n", i % 3, i));
+    }
+    let (DiagramType::Sankey(whole), DiagramType::Sankey(streamed)) = both(&sankey) else {
+        panic!("expected sankey diagrams");
+    };
+    assert_eq!(streamed.links, whole.links);
+    assert_eq!(streamed.nodes.len(), 4);
+    let solar = streamed
+        .nodes
+        .iter()
+        .find(|node| node.id == "Solar")
+        .unwrap();
+    assert_eq!(solar.metadata["color"], "#f5c542");
+}
+
+#[test]
+fn test_streamed_errors_and_fallback() {
+    let mut input = String::from("pie\n");
+    for i in 0..CHUNK_LINES + 5 {
+        input.push_str(&format!("    \"S{}\" : {}\n", i, i));
+    }
+    input.push_str("    \"Bad\" : lots\n");
+    match parse_diagram_from_reader(Cursor::new(&input)) {
+        Err(ParseError::SyntaxError { line, .. }) => assert_eq!(line, CHUNK_LINES + 7),
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+
+    let (whole, streamed) = both("// Comment\nflowchart TD\n    A --> B");
+    assert_eq!(streamed, whole);
+    assert_eq!(
+        parse_diagram_from_reader(Cursor::new("\n// nothing\n")),
+        Err(ParseError::EmptyInput)
+    );
+}
@@ -0,0 +1,48 @@
+use mermaid_parser::parse_diagram;
+use mermaid_parser::stable::DiagramKind;
+
+#[test]
+fn test_summary_counts() {
+    let cases = [
+        ("sankey-beta\nA,B,10\nB,C,5", DiagramKind::Sankey, 3, 2),
+        (
+            "sequenceDiagram\n    Alice->>Bob: Hi\n    loop Every minute\n        Bob->>Alice: Ping\n    end",
+            DiagramKind::Sequence,
+            2,
+            2,
+        ),
+        ("pie\n    \"Dogs\" : 3\n    \"Cats\" : 2", DiagramKind::Pie, 2, 0),
+        ("mindmap\n  root\n    A\n      A1\n    B", DiagramKind::Mindmap, 4, 3),
+    ];
+    for (input, kind, nodes, edges) in cases {
+        let summary = parse_diagram(input).unwrap().summary();
+        assert_eq!(summary.kind, kind, "{}", input);
+        assert_eq!((summary.nodes, summary.edges), (nodes, edges), "{}", input);
+    }
+}
+
+#[test]
+fn test_summary_title_accessibility_and_size() {
+    let small = parse_diagram("flowchart TD\n    A --> B")
+        .unwrap()
+        .summary();
+    assert!(!small.has_title);
+    assert!(!small.has_accessibility);
+
+    let large = parse_diagram(
+        "---\ntitle: Ignored\n---\nflowchart TD\n    A --> B\n    B --> C\n    C --> D",
+    )
+    .unwrap()
+    .summary();
+    assert!(
+        !large.has_title,
+        "the frontmatter is not part of the diagram"
+    );
+    assert!(large.bytes > small.bytes);
+
+    let pie = parse_diagram("pie title Pets\n    accTitle: Pet count\n    \"Dogs\" : 3")
+        .unwrap()
+        .summary();
+    assert!(pie.has_title);
+    assert!(pie.has_accessibility);
+}
@@ -0,0 +1,72 @@
+//! The syntax summaries are generated into editor grammars, so every entry
+//! must actually be recognized by its parser with the advertised meaning
+
+use mermaid_parser::parsers::flowchart;
+
+#[test]
+fn test_flowchart_summary_is_stable() {
+    let summary = flowchart::syntax_summary();
+
+    assert_eq!(summary.headers, vec!["flowchart", "graph"]);
+    assert_eq!(summary.keywords, vec!["class"]);
+    assert_eq!(summary.directions, vec!["TD", "TB", "BT", "LR", "RL"]);
+
+    let arrows: Vec<_> = summary.arrows.iter().map(|a| a.token).collect();
+    assert_eq!(
+        arrows,
+        vec![
+            "-..->", "<-.->", "--->", "-.->", "===>", "----", "-..-", "====", "<-->", "<==>",
+            "o--o", "x--x", "-->", "==>", "---", "-.-", "===", "~~~", "--o", "--x"
+        ]
+    );
+
+    let shapes: Vec<_> = summary
+        .shapes
+        .iter()
+        .map(|s| format!("{}{}", s.open, s.close))
+        .collect();
+    assert_eq!(
+        shapes,
+        vec![
+            "[]", "()", "{}", "(())", "((()))", "[[]]", "{{}}", "([])", "[()]", ">]", "[//]",
+            "[\\\\]", "[/\\]", "[\\/]"
+        ]
+    );
+}
+
+#[test]
+fn test_flowchart_summary_headers_and_directions_parse() {
+    let summary = flowchart::syntax_summary();
+
+    for header in &summary.headers {
+        for direction in &summary.directions {
+            let input = format!("{} {}\n    A[a] --> B[b]", header, direction);
+            let diagram = flowchart::parse(&input).unwrap();
+            assert_eq!(format!("{:?}", diagram.direction), *direction, "{}", input);
+            assert_eq!(diagram.edges.len(), 1, "{}", input);
+        }
+    }
+}
+
+#[test]
+fn test_flowchart_summary_arrows_parse() {
+    for arrow in flowchart::syntax_summary().arrows {
+        let input = format!("flowchart TD\n    A[a] {} B[b]", arrow.token);
+        let diagram = flowchart::parse(&input).unwrap();
+        assert_eq!(format!("{:?}", diagram.edges[0].edge_type), arrow.kind);
+    }
+}
+
+#[test]
+fn test_flowchart_summary_shapes_parse() {
+    for shape in flowchart::syntax_summary().shapes {
+        let input = format!(
+            "flowchart TD\n    A{}label{} --> B[b]",
+            shape.open, shape.close
+        );
+        let diagram = flowchart::parse(&input).unwrap();
+        let node = &diagram.nodes["A"];
+        assert_eq!(format!("{:?}", node.shape), shape.kind, "{}", input);
+        assert_eq!(node.text.as_deref(), Some("label"), "{}", input);
+    }
+}
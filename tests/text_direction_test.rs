@@ -0,0 +1,53 @@
+use mermaid_parser::common::text_direction::{self, TextDirection};
+use mermaid_parser::common::validation::UniversalValidator;
+use mermaid_parser::parse_diagram;
+
+fn rules(input: &str) -> Vec<&'static str> {
+    let diagram = parse_diagram(input).unwrap();
+    let errors = UniversalValidator::new()
+        .validate_any(&diagram)
+        .err()
+        .unwrap_or_default();
+    errors.iter().map(|error| error.rule).collect()
+}
+
+#[test]
+fn test_diagram_text_direction() {
+    let hebrew = parse_diagram("pie\n    \"כלבים\" : 40\n    \"חתולים\" : 20").unwrap();
+    assert_eq!(hebrew.text_direction(), TextDirection::RightToLeft);
+
+    let mixed = parse_diagram("pie\n    \"كلاب\" : 40\n    \"Cats\" : 20").unwrap();
+    assert_eq!(mixed.text_direction(), TextDirection::Mixed);
+    assert_eq!(mixed.text_direction().dir_attribute(), "auto");
+
+    let english = parse_diagram("pie\n    \"Dogs\" : 40\n    \"42\" : 20").unwrap();
+    assert_eq!(english.text_direction(), TextDirection::LeftToRight);
+
+    let labels = text_direction::labels(&hebrew);
+    assert_eq!(labels.len(), 2);
+    assert_eq!(labels[0].text, "כלבים");
+}
+
+#[test]
+fn test_sequence_labels_include_nested_messages() {
+    let diagram =
+        parse_diagram("sequenceDiagram\n    loop כל דקה\n        A->>B: בדיקה\n    end").unwrap();
+    let labels = text_direction::labels(&diagram);
+    let texts: Vec<_> = labels.iter().map(|label| label.text.as_str()).collect();
+    assert!(texts.contains(&"כל דקה"));
+    assert!(texts.contains(&"בדיקה"));
+    assert!(labels.iter().any(|label| label.element == "A->B"));
+}
+
+#[test]
+fn test_rtl_validation_warnings() {
+    let found = rules("pie\n    \"Cats חתולים\" : 40\n    \"כלבים!\" : 20");
+    assert!(found.contains(&"mixed_direction_label"));
+    assert!(found.contains(&"rtl_label_neutral_edge"));
+
+    let found = rules("flowchart LR\n    A[התחלה] --> B[סיום]");
+    assert_eq!(found, ["rtl_left_to_right_layout"]);
+
+    assert!(rules("flowchart RL\n    A[התחלה] --> B[סיום]").is_empty());
+    assert!(rules("pie\n    \"Dogs!\" : 40").is_empty());
+}
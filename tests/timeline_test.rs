@@ -159,3 +159,85 @@ fn test_timeline_chronological_structure() {
         _ => panic!("Expected Timeline diagram"),
     }
 }
+
+#[test]
+fn test_timeline_chronological_periods_and_events_between() {
+    use mermaid_parser::common::ast::PeriodKey;
+
+    let input = r#"timeline
+    title Social media
+    section Later
+        2006 : Twitter
+    section Earlier
+        2002 : LinkedIn
+        2004 : Facebook : Google
+             : Orkut
+        2004-02 : Flickr
+"#;
+
+    let DiagramType::Timeline(diagram) = parse_diagram(input).unwrap() else {
+        panic!("Expected Timeline diagram");
+    };
+
+    let periods = diagram.periods();
+    assert_eq!(periods[2].label, "2004");
+    assert_eq!(periods[2].events, vec!["Facebook", "Google", "Orkut"]);
+    assert_eq!(
+        periods[3].key,
+        PeriodKey::Date {
+            year: 2004,
+            month: Some(2),
+            day: None
+        }
+    );
+
+    let labels: Vec<&str> = diagram
+        .chronological_periods()
+        .iter()
+        .map(|period| period.label)
+        .collect();
+    assert_eq!(labels, vec!["2002", "2004", "2004-02", "2006"]);
+
+    assert_eq!(
+        diagram.events_between("2003", "2005"),
+        vec!["Facebook", "Google", "Orkut", "Flickr"]
+    );
+    assert_eq!(
+        diagram.events_between("2004-02", "2006"),
+        vec!["Flickr", "Twitter"]
+    );
+    assert!(diagram.events_between("2007", "2010").is_empty());
+}
+
+#[test]
+fn test_timeline_validator_reports_order_and_duplicates() {
+    use mermaid_parser::common::validation::{DiagramValidator, TimelineValidator};
+
+    let input = r#"timeline
+    section Plan
+        Q1 : Kickoff
+        2021 : Design
+        2020 : Research
+        Q1 : Review
+        2021 : Build
+"#;
+
+    let DiagramType::Timeline(diagram) = parse_diagram(input).unwrap() else {
+        panic!("Expected Timeline diagram");
+    };
+
+    let errors = TimelineValidator::new().validate(&diagram).unwrap_err();
+    let rules: Vec<&str> = errors.iter().map(|error| error.rule).collect();
+    assert_eq!(
+        rules,
+        vec![
+            "timeline_period_out_of_order",
+            "duplicate_timeline_period",
+            "duplicate_timeline_period"
+        ]
+    );
+    assert_eq!(
+        errors[0].location.as_ref().unwrap().element_id.as_deref(),
+        Some("2020")
+    );
+}
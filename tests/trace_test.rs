@@ -0,0 +1,138 @@
+use mermaid_parser::common::ast::{SequenceDiagram, SequenceStatement};
+use mermaid_parser::common::trace::{parse_spans, spans_to_json, Span, CLIENT};
+use mermaid_parser::{parse_diagram, DiagramType, MermaidPrinter};
+
+const CHECKOUT_TRACE: &str = r#"{"spans": [
+    {"span_id": "a", "parent_id": null, "service": "web", "name": "POST /checkout", "start_ms": 0, "end_ms": 120},
+    {"span_id": "c", "parent_id": "a", "service": "payments", "name": "charge", "start_ms": 30, "end_ms": 110},
+    {"span_id": "b", "parent_id": "a", "service": "cart", "name": "load cart", "start_ms": 5, "end_ms": 25.5},
+    {"span_id": "d", "parent_id": "c", "service": "bank", "name": "authorize \"visa\"", "start_ms": 40, "end_ms": 100, "kind": "client"}
+]}"#;
+
+fn sequence(input: &str) -> SequenceDiagram {
+    match parse_diagram(input).unwrap() {
+        DiagramType::Sequence(diagram) => diagram,
+        other => panic!("Expected sequence diagram, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_import_trace_as_sequence_diagram() {
+    let spans = parse_spans(CHECKOUT_TRACE).unwrap();
+    assert_eq!(spans.len(), 4);
+    assert_eq!(spans[3].name, "authorize \"visa\"");
+    assert_eq!(spans[2].duration_ms(), 20.5);
+
+    let diagram = SequenceDiagram::from_spans(&spans);
+    let participants: Vec<&str> = diagram
+        .participants
+        .iter()
+        .map(|participant| participant.actor.as_str())
+        .collect();
+    assert_eq!(
+        participants,
+        vec![CLIENT, "web", "cart", "payments", "bank"]
+    );
+
+    let calls: Vec<(&str, &str, &str)> = diagram
+        .statements
+        .iter()
+        .filter_map(|statement| match statement {
+            SequenceStatement::Message(message) => Some((
+                message.from.as_str(),
+                message.to.as_str(),
+                message.text.as_str(),
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            (CLIENT, "web", "POST /checkout"),
+            ("web", "cart", "load cart"),
+            ("web", "payments", "charge"),
+            ("payments", "bank", "authorize \"visa\""),
+        ]
+    );
+
+    let output = diagram.to_mermaid();
+    assert!(output.contains("note over cart: 20.5 ms"), "{}", output);
+}
+
+#[test]
+fn test_trace_round_trips_through_mermaid() {
+    let spans = parse_spans(CHECKOUT_TRACE).unwrap();
+    let printed = SequenceDiagram::from_spans(&spans).to_mermaid();
+    let exported = sequence(&printed).to_spans();
+
+    let shape: Vec<(&str, Option<&str>, &str, f64)> = exported
+        .iter()
+        .map(|span| {
+            (
+                span.service.as_str(),
+                span.parent_id.as_deref(),
+                span.name.as_str(),
+                span.duration_ms(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        shape,
+        vec![
+            ("web", None, "POST /checkout", 120.0),
+            ("cart", Some("1"), "load cart", 20.5),
+            ("payments", Some("1"), "charge", 80.0),
+            ("bank", Some("3"), "authorize \"visa\"", 60.0),
+        ]
+    );
+
+    // Children are laid out one after another inside their parent
+    assert_eq!(exported[2].start_ms, exported[1].end_ms);
+    assert_eq!(parse_spans(&spans_to_json(&exported)).unwrap(), exported);
+}
+
+#[test]
+fn test_export_hand_written_diagram() {
+    let diagram = sequence(
+        "sequenceDiagram
+    Alice->>API: GET /orders
+    note over API: 50 ms
+    loop every order
+        API->>DB: SELECT order
+    end
+    API-->>Alice: orders
+    Alice->>API: GET /profile",
+    );
+
+    let spans = diagram.to_spans();
+    let shape: Vec<(&str, Option<&str>, f64, f64)> = spans
+        .iter()
+        .map(|span| {
+            (
+                span.service.as_str(),
+                span.parent_id.as_deref(),
+                span.start_ms,
+                span.end_ms,
+            )
+        })
+        .collect();
+    assert_eq!(
+        shape,
+        vec![
+            ("API", None, 0.0, 50.0),
+            ("DB", Some("1"), 0.0, 0.0),
+            ("API", None, 50.0, 50.0),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_spans_errors() {
+    assert!(parse_spans("[{\"span_id\": \"1\"}]").is_err());
+    assert!(parse_spans("{\"spans\": [").is_err());
+    assert!(parse_spans("[] trailing").is_err());
+
+    let spans: Vec<Span> = parse_spans("[]").unwrap();
+    assert!(SequenceDiagram::from_spans(&spans).participants.is_empty());
+}
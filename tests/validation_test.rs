@@ -1,11 +1,12 @@
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::common::validation::*;
 use std::collections::HashMap;
 
 #[test]
 fn test_flowchart_validation_comprehensive() {
     // Create a flowchart with multiple validation issues
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -46,14 +47,14 @@ fn test_flowchart_validation_comprehensive() {
             FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
             FlowEdge {
                 from: "B".to_string(),
                 to: "UNDEFINED".to_string(), // Undefined node
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
@@ -125,6 +126,7 @@ fn test_sequence_validation_comprehensive() {
                 to: "Charlie".to_string(), // Undefined participant
                 text: "Hello".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             }),
             SequenceStatement::Activate("Bob".to_string()),
             SequenceStatement::Message(Message {
@@ -132,9 +134,11 @@ fn test_sequence_validation_comprehensive() {
                 to: "Alice".to_string(),
                 text: "Response".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             }),
             // Missing deactivate for Bob - should cause unbalanced activation error
         ],
+        boxes: vec![],
         autonumber: None,
     };
 
@@ -152,7 +156,7 @@ fn test_sequence_validation_comprehensive() {
 
 #[test]
 fn test_class_validation_comprehensive() {
-    let mut classes = HashMap::new();
+    let mut classes = OrderedMap::new();
     classes.insert(
         "A".to_string(),
         Class {
@@ -165,6 +169,7 @@ fn test_class_validation_comprehensive() {
                     visibility: Visibility::Public,
                     is_static: false,
                     default_value: None,
+                    generics: vec![],
                 }),
                 ClassMember::Property(Property {
                     name: "field1".to_string(), // Duplicate member
@@ -172,10 +177,12 @@ fn test_class_validation_comprehensive() {
                     visibility: Visibility::Private,
                     is_static: false,
                     default_value: None,
+                    generics: vec![],
                 }),
             ],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         },
     );
     classes.insert(
@@ -186,6 +193,7 @@ fn test_class_validation_comprehensive() {
             members: vec![],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         },
     );
     classes.insert(
@@ -196,12 +204,14 @@ fn test_class_validation_comprehensive() {
             members: vec![],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         },
     );
 
     let diagram = ClassDiagram {
         title: None,
         accessibility: AccessibilityInfo::default(),
+        direction: None,
         classes,
         relationships: vec![
             ClassRelationship {
@@ -238,6 +248,9 @@ fn test_class_validation_comprehensive() {
             },
         ],
         notes: vec![],
+        namespaces: HashMap::new(),
+        styles: vec![],
+        clicks: vec![],
     };
 
     let validator = ClassValidator::new();
@@ -254,15 +267,18 @@ fn test_class_validation_comprehensive() {
 
 #[test]
 fn test_state_validation_comprehensive() {
-    let mut states = HashMap::new();
+    let mut states = OrderedMap::new();
     states.insert(
         "A".to_string(),
         State {
             id: "A".to_string(),
             display_name: Some("State A".to_string()),
             state_type: StateType::Start,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         },
     );
     states.insert(
@@ -271,8 +287,11 @@ fn test_state_validation_comprehensive() {
             id: "B".to_string(),
             display_name: Some("State B".to_string()),
             state_type: StateType::End,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         },
     );
     states.insert(
@@ -281,8 +300,11 @@ fn test_state_validation_comprehensive() {
             id: "C".to_string(),
             display_name: Some("Unreachable State".to_string()),
             state_type: StateType::Simple,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         },
     );
 
@@ -290,6 +312,7 @@ fn test_state_validation_comprehensive() {
         title: None,
         accessibility: AccessibilityInfo::default(),
         version: StateVersion::V2,
+        direction: None,
         states,
         transitions: vec![
             StateTransition {
@@ -316,6 +339,7 @@ fn test_state_validation_comprehensive() {
             // State C is unreachable
         ],
         notes: vec![],
+        class_defs: HashMap::new(),
     };
 
     let validator = StateValidator::new();
@@ -333,7 +357,7 @@ fn test_state_validation_comprehensive() {
 #[test]
 fn test_universal_validator() {
     // Test with a flowchart that has validation issues
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -353,7 +377,7 @@ fn test_universal_validator() {
         edges: vec![FlowEdge {
             from: "A".to_string(),
             to: "UNDEFINED".to_string(), // Error: undefined node reference
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         }],
@@ -382,7 +406,7 @@ fn test_validation_config() {
     };
     config.ignore_rules.insert("isolated_node"); // Ignore isolated node warnings
 
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -423,14 +447,14 @@ fn test_validation_config() {
             FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
             FlowEdge {
                 from: "B".to_string(),
                 to: "UNDEFINED".to_string(), // Error: undefined reference
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
@@ -458,7 +482,7 @@ fn test_valid_diagrams_pass_validation() {
     // Test that valid diagrams pass validation
 
     // Valid flowchart
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -488,7 +512,7 @@ fn test_valid_diagrams_pass_validation() {
         edges: vec![FlowEdge {
             from: "A".to_string(),
             to: "B".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         }],
@@ -524,14 +548,17 @@ fn test_valid_diagrams_pass_validation() {
                 to: "Bob".to_string(),
                 text: "Hello".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             }),
             SequenceStatement::Message(Message {
                 from: "Bob".to_string(),
                 to: "Alice".to_string(),
                 text: "Hi".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             }),
         ],
+        boxes: vec![],
         autonumber: None,
     };
 
@@ -557,3 +584,179 @@ fn test_validation_error_display() {
     assert!(display_str.contains("10:5"));
     assert!(display_str.contains("node_id"));
 }
+
+#[test]
+fn test_registered_rule() {
+    use mermaid_parser::stable::DiagramKind;
+
+    #[derive(Debug)]
+    struct UppercaseIds;
+
+    impl Rule for UppercaseIds {
+        fn name(&self) -> &'static str {
+            "uppercase_ids"
+        }
+
+        fn applies_to(&self, kind: DiagramKind) -> bool {
+            kind == DiagramKind::Flowchart
+        }
+
+        fn check(&self, diagram: &DiagramType) -> Vec<ValidationError> {
+            let DiagramType::Flowchart(flowchart) = diagram else {
+                return Vec::new();
+            };
+            let mut ids: Vec<&String> = flowchart
+                .nodes
+                .keys()
+                .filter(|id| id.chars().any(|c| c.is_lowercase()))
+                .collect();
+            ids.sort();
+            ids.into_iter()
+                .map(|id| ValidationError::error(self.name(), format!("'{}' is not uppercase", id)))
+                .collect()
+        }
+    }
+
+    let mut config = ValidationConfig::default();
+    config.register_rule(UppercaseIds);
+    let validator = UniversalValidator::with_config(config);
+
+    let flowchart =
+        mermaid_parser::parse_diagram("flowchart TD\n    A[Start] --> b[Stop]").unwrap();
+    let errors: Vec<ValidationError> = validator
+        .validate_any(&flowchart)
+        .unwrap_err()
+        .into_iter()
+        .filter(|error| error.rule == "uppercase_ids")
+        .collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "'b' is not uppercase");
+
+    // Rules only run for the kinds they apply to
+    let sequence =
+        mermaid_parser::parse_diagram("sequenceDiagram\n    accTitle: Login\n    a->>b: hi")
+            .unwrap();
+    assert!(validator
+        .validate_any(&sequence)
+        .err()
+        .unwrap_or_default()
+        .iter()
+        .all(|error| error.rule != "uppercase_ids"));
+
+    // Ignoring the rule by name disables it
+    let mut config = ValidationConfig::default();
+    config.register_rule(UppercaseIds);
+    config.ignore_rules.insert("uppercase_ids");
+    let validator = UniversalValidator::with_config(config);
+    assert!(validator
+        .validate_any(&flowchart)
+        .err()
+        .unwrap_or_default()
+        .iter()
+        .all(|error| error.rule != "uppercase_ids"));
+}
+
+#[test]
+fn test_requirement_validation() {
+    let input = r#"requirementDiagram
+    requirement login {
+        id: 1
+        text: users can log in
+    }
+    requirement logout {
+        id: 1
+        risk: low
+    }
+    element app {
+        type: service
+    }
+    app - satisfies -> login
+    tests - verifies -> logout
+    element app {
+        type: service
+    }"#;
+
+    let errors = RequirementValidator::new().validate_source(input).unwrap();
+    let rules: Vec<_> = errors.iter().map(|error| error.rule).collect();
+    assert_eq!(
+        rules,
+        vec![
+            "undefined_requirement_reference",
+            "duplicate_requirement_id",
+            "duplicate_requirement_name",
+        ]
+    );
+    let lines: Vec<_> = errors
+        .iter()
+        .map(|error| error.location.as_ref().unwrap().line)
+        .collect();
+    assert_eq!(lines, vec![14, 6, 15]);
+
+    // A strict profile also demands risk and verification method
+    let validator = RequirementValidator::with_config(ValidationConfig {
+        requirement_profile: RequirementProfile::strict(),
+        ..Default::default()
+    });
+    let missing: Vec<_> = validator
+        .validate_source(input)
+        .unwrap()
+        .into_iter()
+        .filter(|error| error.rule == "missing_requirement_field")
+        .map(|error| error.message)
+        .collect();
+    assert_eq!(
+        missing,
+        vec![
+            "Requirement 'login' has no risk",
+            "Requirement 'login' has no verifymethod",
+            "Requirement 'logout' has no verifymethod",
+        ]
+    );
+}
+
+#[test]
+fn test_text_normalizer_folds_duplicate_names() {
+    use mermaid_parser::common::text_normalizer::TextNormalizer;
+
+    let config = |normalizer: TextNormalizer| ValidationConfig {
+        text_normalizer: normalizer,
+        ..Default::default()
+    };
+
+    let input = "classDiagram
+    class User {
+        +String userName
+        +String username
+    }";
+    let DiagramType::Class(diagram) = mermaid_parser::parse_diagram(input).unwrap() else {
+        panic!("expected a class diagram");
+    };
+    let duplicates = |normalizer: TextNormalizer| {
+        ClassValidator::with_config(config(normalizer))
+            .validate(&diagram)
+            .err()
+            .unwrap_or_default()
+            .iter()
+            .any(|error| error.rule == "duplicate_member")
+    };
+    assert!(!duplicates(TextNormalizer::EXACT));
+    assert!(duplicates(TextNormalizer::IGNORE_CASE));
+
+    let requirements = r#"requirementDiagram
+    requirement LoginFlow {
+        id: 1
+    }
+    element Portal {
+        type: system
+    }
+    Portal - satisfies -> loginflow"#;
+    let undefined = |normalizer: TextNormalizer| {
+        RequirementValidator::with_config(config(normalizer))
+            .validate_source(requirements)
+            .unwrap()
+            .iter()
+            .any(|error| error.rule == "undefined_requirement_reference")
+    };
+    assert!(undefined(TextNormalizer::EXACT));
+    assert!(!undefined(TextNormalizer::LENIENT));
+}
@@ -0,0 +1,40 @@
+use mermaid_parser::stable::DiagramKind;
+use mermaid_parser::version::{self, ParserInfo};
+
+#[test]
+fn test_current_info_covers_every_kind() {
+    let info = ParserInfo::current();
+    assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.grammar_revisions.len(), 23);
+    assert!(info.changed_kinds().is_empty());
+    assert_eq!(
+        info.revision(DiagramKind::Flowchart),
+        version::grammar_revision(DiagramKind::Flowchart)
+    );
+}
+
+#[test]
+fn test_stale_revision_invalidates_only_its_kind() {
+    let mut info = ParserInfo::current();
+    info.crate_version = "0.0.1".to_string();
+    info.grammar_revisions.insert(DiagramKind::Sankey, 0);
+    info.grammar_revisions.remove(&DiagramKind::Pie);
+
+    assert!(info.is_current_for(DiagramKind::Flowchart));
+    assert!(!info.is_current_for(DiagramKind::Sankey));
+    assert!(!info.is_current_for(DiagramKind::Pie));
+    assert_eq!(
+        info.changed_kinds(),
+        vec![DiagramKind::Sankey, DiagramKind::Pie]
+    );
+}
+
+#[test]
+fn test_parse_diagram_versioned() {
+    let mut parsed = version::parse_diagram_versioned("sankey-beta\nA,B,10").unwrap();
+    assert_eq!(DiagramKind::from(&parsed.diagram), DiagramKind::Sankey);
+    assert!(parsed.is_current());
+
+    parsed.info.grammar_revisions.insert(DiagramKind::Sankey, 0);
+    assert!(!parsed.is_current());
+}
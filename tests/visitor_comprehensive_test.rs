@@ -1,6 +1,7 @@
 //! Comprehensive tests targeting missing coverage areas in visitor.rs
 
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::common::visitor::{
     AstVisitor, AstVisitorMut, ComplexityAnalyzer, NodeCounter, ReferenceValidator, TitleSetter,
 };
@@ -20,6 +21,7 @@ mod visitor_comprehensive_tests {
         let sankey_node = SankeyNode {
             id: "node1".to_string(),
             name: "Node 1".to_string(),
+            metadata: Default::default(),
         };
         let sankey_link = SankeyLink {
             source: "node1".to_string(),
@@ -40,7 +42,7 @@ mod visitor_comprehensive_tests {
         let flow_edge = FlowEdge {
             from: "flow1".to_string(),
             to: "flow2".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         };
@@ -53,6 +55,7 @@ mod visitor_comprehensive_tests {
             to: "Bob".to_string(),
             text: "Hello".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         };
         counter.visit_sequence_message(&sequence_message);
 
@@ -63,6 +66,7 @@ mod visitor_comprehensive_tests {
             members: vec![],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         };
         counter.visit_class_definition(&class_def);
 
@@ -71,8 +75,11 @@ mod visitor_comprehensive_tests {
             id: "state1".to_string(),
             display_name: Some("State 1".to_string()),
             state_type: StateType::Simple,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         };
         let state_transition = StateTransition {
             from: "state1".to_string(),
@@ -100,14 +107,17 @@ mod visitor_comprehensive_tests {
                 SankeyNode {
                     id: "A".to_string(),
                     name: "Node A".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "B".to_string(),
                     name: "Node B".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "C".to_string(),
                     name: "Node C".to_string(),
+                    metadata: Default::default(),
                 },
             ],
             links: vec![
@@ -137,7 +147,8 @@ mod visitor_comprehensive_tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             version: StateVersion::V1,
-            states: HashMap::new(),
+            direction: None,
+            states: OrderedMap::new(),
             transitions: vec![
                 StateTransition {
                     from: "A".to_string(),
@@ -155,6 +166,7 @@ mod visitor_comprehensive_tests {
                 },
             ],
             notes: vec![],
+            class_defs: HashMap::new(),
         };
 
         let mut analyzer = ComplexityAnalyzer::new();
@@ -169,7 +181,8 @@ mod visitor_comprehensive_tests {
         let diagram = ClassDiagram {
             title: None,
             accessibility: AccessibilityInfo::default(),
-            classes: HashMap::new(),
+            direction: None,
+            classes: OrderedMap::new(),
             relationships: vec![ClassRelationship {
                 from: "ClassA".to_string(),
                 to: "ClassB".to_string(),
@@ -179,6 +192,9 @@ mod visitor_comprehensive_tests {
                 label: None,
             }],
             notes: vec![],
+            namespaces: HashMap::new(),
+            styles: vec![],
+            clicks: vec![],
         };
 
         let mut analyzer = ComplexityAnalyzer::new();
@@ -243,6 +259,7 @@ mod visitor_comprehensive_tests {
             accessibility: AccessibilityInfo::default(),
             participants: vec![],
             statements: vec![],
+            boxes: vec![],
             autonumber: None,
         };
         setter.visit_sequence_mut(&mut sequence);
@@ -252,9 +269,13 @@ mod visitor_comprehensive_tests {
         let mut class = ClassDiagram {
             title: None,
             accessibility: AccessibilityInfo::default(),
-            classes: HashMap::new(),
+            direction: None,
+            classes: OrderedMap::new(),
             relationships: vec![],
             notes: vec![],
+            namespaces: HashMap::new(),
+            styles: vec![],
+            clicks: vec![],
         };
         setter.visit_class_mut(&mut class);
         assert_eq!(class.title, Some(title.clone()));
@@ -264,9 +285,11 @@ mod visitor_comprehensive_tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             version: StateVersion::V1,
-            states: HashMap::new(),
+            direction: None,
+            states: OrderedMap::new(),
             transitions: vec![],
             notes: vec![],
+            class_defs: HashMap::new(),
         };
         setter.visit_state_mut(&mut state);
         assert_eq!(state.title, Some(title.clone()));
@@ -276,7 +299,7 @@ mod visitor_comprehensive_tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             direction: FlowDirection::TD,
-            nodes: HashMap::new(),
+            nodes: OrderedMap::new(),
             edges: vec![],
             subgraphs: vec![],
             styles: vec![],
@@ -313,6 +336,7 @@ mod visitor_comprehensive_tests {
             accessibility: AccessibilityInfo::default(),
             participants: vec![],
             statements: vec![],
+            boxes: vec![],
             autonumber: None,
         });
         validator.visit_misc(&MiscDiagram {
@@ -324,6 +348,7 @@ mod visitor_comprehensive_tests {
         validator.visit_sankey_node(&SankeyNode {
             id: "test".to_string(),
             name: "Test".to_string(),
+            metadata: Default::default(),
         });
         validator.visit_sankey_link(&SankeyLink {
             source: "a".to_string(),
@@ -340,7 +365,7 @@ mod visitor_comprehensive_tests {
         validator.visit_flow_edge(&FlowEdge {
             from: "a".to_string(),
             to: "b".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         });
@@ -349,6 +374,7 @@ mod visitor_comprehensive_tests {
             to: "b".to_string(),
             text: "test".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         });
         validator.visit_class_definition(&Class {
             name: "Test".to_string(),
@@ -356,13 +382,17 @@ mod visitor_comprehensive_tests {
             members: vec![],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         });
         validator.visit_state_node(&State {
             id: "test".to_string(),
             display_name: None,
             state_type: StateType::Simple,
-            substates: vec![],
+            states: OrderedMap::new(),
+            transitions: vec![],
+            notes: vec![],
             concurrent_regions: vec![],
+            classes: Vec::new(),
         });
         validator.visit_state_transition(&StateTransition {
             from: "a".to_string(),
@@ -425,6 +455,7 @@ mod visitor_comprehensive_tests {
         counter.visit_sankey_node(&SankeyNode {
             id: "test".to_string(),
             name: "Test".to_string(),
+            metadata: Default::default(),
         });
         counter.visit_sankey_link(&SankeyLink {
             source: "A".to_string(),
@@ -436,6 +467,7 @@ mod visitor_comprehensive_tests {
             to: "B".to_string(),
             text: "Test".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         });
 
         assert_eq!(counter.nodes(), 1);
@@ -461,10 +493,12 @@ mod visitor_comprehensive_tests {
                 SankeyNode {
                     id: "A".to_string(),
                     name: "Node A".to_string(),
+                    metadata: Default::default(),
                 },
                 SankeyNode {
                     id: "B".to_string(),
                     name: "Node B".to_string(),
+                    metadata: Default::default(),
                 },
             ],
             links: vec![SankeyLink {
@@ -483,15 +517,18 @@ mod visitor_comprehensive_tests {
 
     #[test]
     fn test_reference_validator_with_state() {
-        let mut states = HashMap::new();
+        let mut states = OrderedMap::new();
         states.insert(
             "start".to_string(),
             State {
                 id: "start".to_string(),
                 display_name: Some("Start state".to_string()),
                 state_type: StateType::Start,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
         states.insert(
@@ -500,8 +537,11 @@ mod visitor_comprehensive_tests {
                 id: "end".to_string(),
                 display_name: Some("End state".to_string()),
                 state_type: StateType::End,
-                substates: vec![],
+                states: OrderedMap::new(),
+                transitions: vec![],
+                notes: vec![],
                 concurrent_regions: vec![],
+                classes: Vec::new(),
             },
         );
 
@@ -509,6 +549,7 @@ mod visitor_comprehensive_tests {
             title: None,
             accessibility: AccessibilityInfo::default(),
             version: StateVersion::V1,
+            direction: None,
             states,
             transitions: vec![
                 StateTransition {
@@ -527,6 +568,7 @@ mod visitor_comprehensive_tests {
                 },
             ],
             notes: vec![],
+            class_defs: HashMap::new(),
         };
 
         let mut validator = ReferenceValidator::new();
@@ -540,7 +582,7 @@ mod visitor_comprehensive_tests {
 
     #[test]
     fn test_reference_validator_with_class() {
-        let mut classes = HashMap::new();
+        let mut classes = OrderedMap::new();
         classes.insert(
             "ClassA".to_string(),
             Class {
@@ -549,12 +591,14 @@ mod visitor_comprehensive_tests {
                 members: vec![],
                 annotations: vec![],
                 css_class: None,
+                generics: vec![],
             },
         );
 
         let diagram = ClassDiagram {
             title: None,
             accessibility: AccessibilityInfo::default(),
+            direction: None,
             classes,
             relationships: vec![ClassRelationship {
                 from: "ClassA".to_string(),
@@ -565,6 +609,9 @@ mod visitor_comprehensive_tests {
                 label: None,
             }],
             notes: vec![],
+            namespaces: HashMap::new(),
+            styles: vec![],
+            clicks: vec![],
         };
 
         let mut validator = ReferenceValidator::new();
@@ -687,14 +734,17 @@ mod visitor_comprehensive_tests {
                     to: "Bob".to_string(),
                     text: "Hello".to_string(),
                     arrow_type: ArrowType::SolidOpen,
+                    activation: None,
                 }),
                 SequenceStatement::Message(Message {
                     from: "Bob".to_string(),
                     to: "Alice".to_string(),
                     text: "Hi there".to_string(),
                     arrow_type: ArrowType::SolidClosed,
+                    activation: None,
                 }),
             ],
+            boxes: vec![],
             autonumber: None,
         };
 
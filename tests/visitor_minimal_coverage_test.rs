@@ -105,6 +105,7 @@ mod visitor_minimal_coverage_tests {
             accessibility: AccessibilityInfo::default(),
             participants: vec![],
             statements: vec![],
+            boxes: vec![],
             autonumber: None,
         };
         validator.visit_sequence(&sequence);
@@ -122,6 +123,7 @@ mod visitor_minimal_coverage_tests {
         let sankey_node = SankeyNode {
             id: "test".to_string(),
             name: "Test".to_string(),
+            metadata: Default::default(),
         };
         validator.visit_sankey_node(&sankey_node);
 
@@ -144,7 +146,7 @@ mod visitor_minimal_coverage_tests {
         let flow_edge = FlowEdge {
             from: "A".to_string(),
             to: "B".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         };
@@ -155,6 +157,7 @@ mod visitor_minimal_coverage_tests {
             to: "B".to_string(),
             text: "Test".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         };
         validator.visit_sequence_message(&message);
 
@@ -164,6 +167,7 @@ mod visitor_minimal_coverage_tests {
             members: vec![],
             annotations: vec![],
             css_class: None,
+            generics: vec![],
         };
         validator.visit_class_definition(&class);
 
@@ -1,13 +1,14 @@
 //! Comprehensive tests for the visitor pattern module
 
 use mermaid_parser::common::ast::*;
+use mermaid_parser::common::ordered_map::OrderedMap;
 use mermaid_parser::common::visitor::*;
 use std::collections::HashMap;
 
 // Test the ReferenceValidator visitor
 #[test]
 fn test_reference_validator_valid_flowchart() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -37,7 +38,7 @@ fn test_reference_validator_valid_flowchart() {
         edges: vec![FlowEdge {
             from: "A".to_string(),
             to: "B".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         }],
@@ -55,7 +56,7 @@ fn test_reference_validator_valid_flowchart() {
 
 #[test]
 fn test_reference_validator_undefined_node() {
-    let nodes = HashMap::new(); // No nodes defined
+    let nodes = OrderedMap::new(); // No nodes defined
 
     let diagram = DiagramType::Flowchart(FlowchartDiagram {
         title: None,
@@ -65,7 +66,7 @@ fn test_reference_validator_undefined_node() {
         edges: vec![FlowEdge {
             from: "A".to_string(),
             to: "B".to_string(),
-            edge_type: EdgeType::Arrow,
+            edge_type: EdgeType::ARROW,
             label: None,
             min_length: None,
         }],
@@ -103,7 +104,9 @@ fn test_reference_validator_sequence_diagram() {
             to: "Bob".to_string(),
             text: "Hello".to_string(),
             arrow_type: ArrowType::SolidOpen,
+            activation: None,
         })],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -116,7 +119,7 @@ fn test_reference_validator_sequence_diagram() {
 // Test the ComplexityAnalyzer visitor
 #[test]
 fn test_complexity_analyzer_flowchart() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -157,14 +160,14 @@ fn test_complexity_analyzer_flowchart() {
             FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
             FlowEdge {
                 from: "B".to_string(),
                 to: "C".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: Some("Yes".to_string()),
                 min_length: None,
             },
@@ -206,8 +209,10 @@ fn test_complexity_analyzer_sequence_diagram() {
                 to: "B".to_string(),
                 text: "Request".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             })],
         })],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -221,7 +226,7 @@ fn test_complexity_analyzer_sequence_diagram() {
 // Test the TitleSetter visitor
 #[test]
 fn test_title_setter_flowchart() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -260,6 +265,7 @@ fn test_title_setter_sequence_diagram() {
         accessibility: AccessibilityInfo::default(),
         participants: vec![],
         statements: vec![],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -290,7 +296,7 @@ fn test_title_setter_timeline() {
 // Test the NodeCounter visitor
 #[test]
 fn test_node_counter_flowchart() {
-    let mut nodes = HashMap::new();
+    let mut nodes = OrderedMap::new();
     nodes.insert(
         "A".to_string(),
         FlowNode {
@@ -331,14 +337,14 @@ fn test_node_counter_flowchart() {
             FlowEdge {
                 from: "A".to_string(),
                 to: "B".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
             FlowEdge {
                 from: "B".to_string(),
                 to: "C".to_string(),
-                edge_type: EdgeType::Arrow,
+                edge_type: EdgeType::ARROW,
                 label: None,
                 min_length: None,
             },
@@ -384,14 +390,17 @@ fn test_node_counter_sequence_diagram() {
                 to: "Bob".to_string(),
                 text: "Hello Bob".to_string(),
                 arrow_type: ArrowType::SolidOpen,
+                activation: None,
             }),
             SequenceStatement::Message(Message {
                 from: "Bob".to_string(),
                 to: "Charlie".to_string(),
                 text: "Hello Charlie".to_string(),
                 arrow_type: ArrowType::SolidClosed,
+                activation: None,
             }),
         ],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -409,7 +418,7 @@ fn test_node_counter_empty_diagrams() {
         title: None,
         accessibility: AccessibilityInfo::default(),
         direction: FlowDirection::TD,
-        nodes: HashMap::new(),
+        nodes: OrderedMap::new(),
         edges: vec![],
         subgraphs: vec![],
         styles: vec![],
@@ -429,6 +438,7 @@ fn test_node_counter_empty_diagrams() {
         accessibility: AccessibilityInfo::default(),
         participants: vec![],
         statements: vec![],
+        boxes: vec![],
         autonumber: None,
     });
 
@@ -447,20 +457,20 @@ fn test_complex_nested_structure() {
         accessibility: AccessibilityInfo::default(),
         root: MindmapNode {
             id: "root".to_string(),
-            text: "Central Topic".to_string(),
+            text: "Central Topic".into(),
             shape: MindmapNodeShape::Cloud,
             icon: None,
             class: None,
             children: vec![
                 MindmapNode {
                     id: "branch1".to_string(),
-                    text: "Branch 1".to_string(),
+                    text: "Branch 1".into(),
                     shape: MindmapNodeShape::Square,
                     icon: None,
                     class: None,
                     children: vec![MindmapNode {
                         id: "leaf1".to_string(),
-                        text: "Leaf 1".to_string(),
+                        text: "Leaf 1".into(),
                         shape: MindmapNodeShape::Default,
                         icon: None,
                         class: None,
@@ -469,7 +479,7 @@ fn test_complex_nested_structure() {
                 },
                 MindmapNode {
                     id: "branch2".to_string(),
-                    text: "Branch 2".to_string(),
+                    text: "Branch 2".into(),
                     shape: MindmapNodeShape::Rounded,
                     icon: None,
                     class: None,
@@ -477,6 +487,7 @@ fn test_complex_nested_structure() {
                 },
             ],
         },
+        cross_edges: vec![],
     });
 
     let mut counter = NodeCounter::new();
@@ -0,0 +1,52 @@
+use mermaid_parser::common::validation::Severity;
+use mermaid_parser::common::warnings::IGNORED_STATEMENT_RULE;
+use mermaid_parser::{parse_diagram, parse_diagram_with_warnings};
+
+#[test]
+fn test_warnings_for_dropped_statements() {
+    let input = "---\ntitle: Specs\n---\nrequirementDiagram\n    element e1 {\n        type: simulation\n    }\n    classDef important stroke:#f00\n    class e1 important";
+    let outcome = parse_diagram_with_warnings(input).unwrap();
+    assert_eq!(outcome.diagram, parse_diagram(input).unwrap());
+
+    let lines: Vec<usize> = outcome
+        .warnings
+        .iter()
+        .map(|warning| warning.location.as_ref().unwrap().line)
+        .collect();
+    assert_eq!(lines, [8, 9]);
+    assert!(outcome.warnings.iter().all(|warning| {
+        warning.rule == IGNORED_STATEMENT_RULE && warning.severity == Severity::Warning
+    }));
+    assert!(outcome.warnings[0].message.contains("`classDef`"));
+
+    let requirement = "requirementDiagram\n    direction LR\n    requirement r1 {\n        id: 1\n        text: Must work\n        risk: high\n        verifymethod: test\n    }";
+    let outcome = parse_diagram_with_warnings(requirement).unwrap();
+    assert_eq!(outcome.warnings.len(), 1);
+    assert_eq!(outcome.warnings[0].location.as_ref().unwrap().column, 5);
+}
+
+#[test]
+fn test_no_warnings_for_kept_statements() {
+    let flowchart = "flowchart TD\n    A --> B\n    style A fill:#f9f\n    classDef hot fill:#f00";
+    assert!(parse_diagram_with_warnings(flowchart)
+        .unwrap()
+        .warnings
+        .is_empty());
+    let er = "erDiagram\n    CUSTOMER ||--o{ ORDER : places\n    style CUSTOMER fill:#f9f";
+    assert!(parse_diagram_with_warnings(er).unwrap().warnings.is_empty());
+    let requirement =
+        "requirementDiagram\n    element e1 {\n        type: simulation\n    }\n    style e1 fill:#f9f";
+    assert!(parse_diagram_with_warnings(requirement)
+        .unwrap()
+        .warnings
+        .is_empty());
+
+    // `style` is only dropped by some parsers, and comments are not statements
+    let pie = "pie\n    %% style is not a statement here\n    \"A\" : 1";
+    assert!(parse_diagram_with_warnings(pie)
+        .unwrap()
+        .warnings
+        .is_empty());
+
+    assert!(parse_diagram_with_warnings("").is_err());
+}
@@ -0,0 +1,96 @@
+use mermaid_parser::common::workspace::Workspace;
+
+#[test]
+fn test_subroutine_call_graph_across_flowcharts() {
+    let mut workspace = Workspace::new();
+    workspace
+        .add_source(
+            "order.mmd",
+            r#"---
+title: Place order
+---
+flowchart TD
+    A[Cart] --> B[[Payment]]
+    B --> C[[Fulfilment]]
+    C --> D[[Notify customer]]"#,
+        )
+        .unwrap();
+    workspace
+        .add_source(
+            "payment.mmd",
+            r#"---
+title: Payment
+---
+flowchart LR
+    A[Card] --> B{Approved?}
+    B --> C[[Notify customer]]"#,
+        )
+        .unwrap();
+    workspace
+        .add_source(
+            "fulfilment.mmd",
+            "---\ntitle: Fulfilment\n---\nflowchart TD\n    A[Pick] --> B[Pack]",
+        )
+        .unwrap();
+    workspace
+        .add_source(
+            "sequence.mmd",
+            "sequenceDiagram\n    title Notify\n    A->>B: hi",
+        )
+        .unwrap();
+
+    let graph = workspace.call_graph();
+    assert_eq!(graph.calls.len(), 4);
+    assert_eq!(
+        graph.callees("order.mmd"),
+        vec!["payment.mmd", "fulfilment.mmd"]
+    );
+    assert_eq!(graph.callers("payment.mmd"), vec!["order.mmd"]);
+    assert!(graph.callees("fulfilment.mmd").is_empty());
+
+    let dangling: Vec<_> = graph
+        .dangling()
+        .map(|call| (call.caller.as_str(), call.node_id.as_str()))
+        .collect();
+    assert_eq!(dangling, vec![("order.mmd", "D"), ("payment.mmd", "C")]);
+
+    let diagnostics = graph.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].rule, "dangling_subroutine");
+    assert_eq!(
+        diagnostics[0]
+            .location
+            .as_ref()
+            .unwrap()
+            .element_id
+            .as_deref(),
+        Some("D")
+    );
+    assert_eq!(diagnostics[0].source.as_deref(), Some("order.mmd"));
+    assert!(diagnostics[0]
+        .to_string()
+        .ends_with("(at order.mmd:0:0, element: D)"));
+}
+
+#[test]
+fn test_parse_errors_name_their_source() {
+    use mermaid_parser::{parse_diagram_named, ParseError};
+
+    let mut workspace = Workspace::new();
+    let error = workspace
+        .add_source("empty.mmd", "// nothing here")
+        .unwrap_err();
+    assert_eq!(error.source_name(), Some("empty.mmd"));
+    assert!(error.to_string().starts_with("empty.mmd: "));
+    assert!(workspace.diagrams.is_empty());
+
+    let error = parse_diagram_named("   ", "README.md").unwrap_err();
+    assert_eq!(error.root(), &ParseError::EmptyInput);
+    assert_eq!(
+        error.to_string(),
+        format!("README.md: {}", ParseError::EmptyInput)
+    );
+
+    // The first name given is kept
+    assert_eq!(error.in_source("other.md").source_name(), Some("README.md"));
+}
@@ -56,7 +56,7 @@ fn test_simple_bar_chart() {
     assert_eq!(diagram.data_series[0].series_type, SeriesType::Bar);
     assert_eq!(
         diagram.data_series[0].data,
-        vec![2500.0, 5000.0, 7500.0, 10000.0]
+        vec![Some(2500.0), Some(5000.0), Some(7500.0), Some(10000.0)]
     );
 }
 
@@ -73,7 +73,10 @@ fn test_line_chart() {
     assert!(diagram.title.is_none());
     assert_eq!(diagram.x_axis.labels.len(), 3);
     assert_eq!(diagram.data_series[0].series_type, SeriesType::Line);
-    assert_eq!(diagram.data_series[0].data, vec![10.0, 50.0, 30.0]);
+    assert_eq!(
+        diagram.data_series[0].data,
+        vec![Some(10.0), Some(50.0), Some(30.0)]
+    );
 }
 
 #[test]
@@ -106,7 +109,7 @@ fn test_horizontal_chart() {
     let diagram = xy::parse(input).unwrap();
 
     assert_eq!(diagram.orientation, ChartOrientation::Horizontal);
-    assert_eq!(diagram.data_series[0].data, vec![25.0, 45.0]);
+    assert_eq!(diagram.data_series[0].data, vec![Some(25.0), Some(45.0)]);
 }
 
 #[test]
@@ -135,9 +138,9 @@ fn test_decimal_values() {
     let diagram = xy::parse(input).unwrap();
 
     let values = &diagram.data_series[0].data;
-    assert_eq!(values[0], 25.5);
-    assert_eq!(values[1], 50.75);
-    assert_eq!(values[2], 85.25);
+    assert_eq!(values[0], Some(25.5));
+    assert_eq!(values[1], Some(50.75));
+    assert_eq!(values[2], Some(85.25));
 }
 
 #[test]
@@ -212,3 +215,139 @@ fn test_quoted_labels() {
     assert_eq!(diagram.y_axis.title, Some("Revenue (USD)".to_string()));
     assert_eq!(diagram.data_series[0].name, Some("Sales".to_string()));
 }
+
+#[test]
+fn test_sparse_series_with_gaps() {
+    use mermaid_parser::common::validation::{DiagramValidator, Severity, XyChartValidator};
+    use mermaid_parser::{DiagramType, MermaidPrinter};
+
+    let input = r#"xychart-beta
+    x-axis [jan, feb, mar, apr]
+    y-axis 0 --> 100
+    line "Exported" [10, null, 30, -]
+    bar "Partial" [5, 15]
+"#;
+
+    let diagram = xy::parse(input).unwrap();
+    assert_eq!(
+        diagram.data_series[0].data,
+        vec![Some(10.0), None, Some(30.0), None]
+    );
+    assert_eq!(diagram.data_series[1].data, vec![Some(5.0), Some(15.0)]);
+
+    let errors = XyChartValidator::new().validate(&diagram).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].rule, "sparse_series");
+    assert_eq!(errors[0].severity, Severity::Warning);
+
+    let printed = DiagramType::XyChart(diagram.clone()).to_mermaid();
+    assert!(printed.contains("[10, null, 30, null]"));
+    assert_eq!(xy::parse(&printed).unwrap(), diagram);
+}
+
+#[test]
+fn test_empty_items_are_gaps() {
+    let input = r#"xychart-beta
+    x-axis [jan, feb, mar, apr]
+    line [1, , 3, 4]
+"#;
+
+    let diagram = xy::parse(input).unwrap();
+    assert_eq!(
+        diagram.data_series[0].data,
+        vec![Some(1.0), None, Some(3.0), Some(4.0)]
+    );
+    let mar = diagram
+        .x_axis
+        .labels
+        .iter()
+        .position(|l| l == "mar")
+        .unwrap();
+    assert_eq!(diagram.data_series[0].data[mar], Some(3.0));
+}
+
+#[test]
+fn test_values_outside_y_axis_range() {
+    use mermaid_parser::common::validation::{DiagramValidator, XyChartValidator};
+
+    let input = r#"xychart-beta
+    x-axis [q1, q2, q3]
+    y-axis 0 --> 100
+    bar "Revenue" [50, 200, null]
+"#;
+
+    let mut diagram = xy::parse(input).unwrap();
+    let errors = XyChartValidator::new().validate(&diagram).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].rule, "xy_value_out_of_range");
+    assert!(errors[0].message.contains("'q2'"));
+
+    let report = diagram.normalize();
+    assert_eq!(report.rescales[0].from, (0.0, 200.0));
+    assert_eq!(report.values.len(), 2);
+    assert_eq!(
+        diagram.data_series[0].data,
+        vec![Some(25.0), Some(100.0), None]
+    );
+    assert!(XyChartValidator::new().validate(&diagram).is_ok());
+}
+
+#[test]
+fn test_signed_decimal_ranges_and_palette_colors() {
+    use mermaid_parser::common::ast::Color;
+    use mermaid_parser::{parse_document, MermaidPrinter};
+
+    let input = r##"---
+config:
+  themeVariables:
+    xyChart:
+      plotColorPalette: "#0000ff, red"
+---
+xychart-beta
+    x-axis Temperature delta -10.5 --> 20.25
+    y-axis "Load" -1 --> -0.5
+    line [-0.75, -0.6]
+    bar [-0.9, -0.55]
+    line [-0.8, -0.7]
+"##;
+
+    let diagram = xy::parse(input).unwrap();
+    assert_eq!(diagram.x_axis.title.as_deref(), Some("Temperature delta"));
+    assert_eq!(diagram.x_axis.range, Some((-10.5, 20.25)));
+    assert_eq!(diagram.y_axis.range, Some((-1.0, -0.5)));
+    assert_eq!(diagram.data_series[0].data, vec![Some(-0.75), Some(-0.6)]);
+    let colors: Vec<_> = diagram
+        .data_series
+        .iter()
+        .map(|series| series.color.clone())
+        .collect();
+    assert_eq!(
+        colors,
+        vec![
+            Some(Color::Hex("0000ff".to_string())),
+            Some(Color::Named("red".to_string())),
+            Some(Color::Hex("0000ff".to_string())),
+        ]
+    );
+
+    // An init directive takes precedence over the frontmatter
+    let overridden = format!(
+        "%%{{init: {{\"themeVariables\": {{\"xyChart\": {{\"plotColorPalette\": \"#00ff00\"}}}}}}}}%%\n{}",
+        input.split("---\n").nth(2).unwrap()
+    );
+    let diagram = xy::parse(&overridden).unwrap();
+    assert_eq!(
+        diagram.data_series[1].color,
+        Some(Color::Hex("00ff00".to_string()))
+    );
+
+    // The palette lives in the configuration, which the document keeps
+    let document = parse_document(&overridden).unwrap();
+    let reparsed = parse_document(&document.to_mermaid()).unwrap();
+    assert_eq!(reparsed, document);
+    assert!(
+        xy::parse("xychart-beta\n    bar [1]").unwrap().data_series[0]
+            .color
+            .is_none()
+    );
+}